@@ -86,7 +86,11 @@ pub(crate) async fn download_chunk(request: ChunkDownloadRequest<'_>) -> ChunkRe
     // including body transfer. A 13MB chunk at 1MB/s takes 13 seconds, which
     // would exceed a 30s timeout under load. Connection timeout is set on the
     // reqwest::Client builder in the orchestrator instead.
-    let response = match client.get(url).header("Range", &range_header).send().await {
+    let mut http_request = client.get(url).header("Range", &range_header);
+    for (name, value) in &config.headers {
+        http_request = http_request.header(name, value);
+    }
+    let response = match http_request.send().await {
         Ok(resp) => resp,
         Err(e) => {
             return ChunkResult::Failed {
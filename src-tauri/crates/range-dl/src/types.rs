@@ -28,6 +28,9 @@ pub struct RangeDownloadConfig {
     pub retry_backoff_base: Duration,
     /// Connection timeout per chunk HTTP request (default: 30s).
     pub connect_timeout: Duration,
+    /// Extra headers sent with every chunk request, e.g. SSE-C key headers
+    /// that the presigned URL does not carry (default: none).
+    pub headers: Vec<(String, String)>,
 }
 
 impl Default for RangeDownloadConfig {
@@ -39,6 +42,7 @@ impl Default for RangeDownloadConfig {
             max_retries: 5,
             retry_backoff_base: Duration::from_secs(1),
             connect_timeout: Duration::from_secs(30),
+            headers: Vec::new(),
         }
     }
 }
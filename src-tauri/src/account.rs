@@ -1,6 +1,64 @@
 use crate::db;
+use crate::providers::aws::credentials::{self, AwsProfile, CredentialSource};
+use crate::providers::credential_probe::CapabilityReport;
+use crate::secrets;
 use serde::{Deserialize, Serialize};
 
+// ============ Credential Handles ============
+
+/// The credential currently saved for an AWS / MinIO / RustFS account.
+async fn saved_secret(provider: &str, account_id: &str) -> Result<String, String> {
+    db::list_account_credentials(provider, account_id)
+        .await
+        .map_err(|e| format!("Failed to read saved credentials: {}", e))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Account {} not found", account_id))
+}
+
+/// Read the credentials of an account before its rows are deleted, so they
+/// can be removed from the secret store afterwards.
+async fn account_credentials(provider: &str, account_id: &str) -> Vec<String> {
+    db::list_account_credentials(provider, account_id)
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to read credentials of {}: {}", account_id, e);
+            Vec::new()
+        })
+}
+
+/// Keep the saved capability report in step with the credentials: store a
+/// fresh report, and drop the old one when the keys changed without a new
+/// check.
+async fn update_capabilities(
+    provider: &str,
+    owner_id: &str,
+    report: Option<&CapabilityReport>,
+    keys_changed: bool,
+) -> Result<(), String> {
+    if report.is_none() && !keys_changed {
+        return Ok(());
+    }
+    let result = match provider {
+        "r2" => {
+            let token_id = owner_id
+                .parse()
+                .map_err(|_| format!("Invalid token id: {}", owner_id))?;
+            db::save_token_capabilities(token_id, report).await
+        }
+        _ => db::save_account_capabilities(provider, owner_id, report).await,
+    };
+    result.map_err(|e| format!("Failed to save credential capabilities: {}", e))
+}
+
+async fn release_credentials(values: &[String]) {
+    for value in values {
+        if let Err(e) = secrets::delete_credential(value).await {
+            log::warn!("Failed to remove stored credential: {}", e);
+        }
+    }
+}
+
 // ============ Account Commands ============
 
 #[tauri::command]
@@ -26,9 +84,12 @@ pub async fn update_account(id: String, name: Option<String>) -> Result<(), Stri
 
 #[tauri::command]
 pub async fn delete_account(id: String) -> Result<(), String> {
+    let stored = account_credentials("r2", &id).await;
     db::delete_account(&id)
         .await
-        .map_err(|e| format!("Failed to delete account: {}", e))
+        .map_err(|e| format!("Failed to delete account: {}", e))?;
+    release_credentials(&stored).await;
+    Ok(())
 }
 
 // ============ Token Commands ============
@@ -40,6 +101,9 @@ pub struct CreateTokenInput {
     pub api_token: String,
     pub access_key_id: String,
     pub secret_access_key: String,
+    /// Report from `validate_credentials`, saved with the credentials.
+    #[serde(default)]
+    pub capabilities: Option<CapabilityReport>,
 }
 
 #[tauri::command]
@@ -51,15 +115,26 @@ pub async fn list_tokens(account_id: String) -> Result<Vec<db::Token>, String> {
 
 #[tauri::command]
 pub async fn create_token(input: CreateTokenInput) -> Result<db::Token, String> {
-    db::create_token(
+    let api_token = secrets::store_credential("r2", &input.api_token, None).await?;
+    let secret_access_key = secrets::store_credential("r2", &input.secret_access_key, None).await?;
+    let mut token = db::create_token(
         &input.account_id,
         input.name.as_deref(),
-        &input.api_token,
+        &api_token,
         &input.access_key_id,
-        &input.secret_access_key,
+        &secret_access_key,
     )
     .await
-    .map_err(|e| format!("Failed to create token: {}", e))
+    .map_err(|e| format!("Failed to create token: {}", e))?;
+    update_capabilities(
+        "r2",
+        &token.id.to_string(),
+        input.capabilities.as_ref(),
+        false,
+    )
+    .await?;
+    token.capabilities = input.capabilities;
+    Ok(token)
 }
 
 #[derive(Debug, Deserialize)]
@@ -69,26 +144,59 @@ pub struct UpdateTokenInput {
     pub api_token: String,
     pub access_key_id: String,
     pub secret_access_key: String,
+    /// Report from `validate_credentials`, saved with the credentials.
+    #[serde(default)]
+    pub capabilities: Option<CapabilityReport>,
 }
 
 #[tauri::command]
 pub async fn update_token(input: UpdateTokenInput) -> Result<(), String> {
+    let current = db::get_token(input.id)
+        .await
+        .map_err(|e| format!("Failed to get token: {}", e))?
+        .ok_or_else(|| format!("Token {} not found", input.id))?;
+    let api_token =
+        secrets::store_credential("r2", &input.api_token, Some(&current.api_token)).await?;
+    let secret_access_key = secrets::store_credential(
+        "r2",
+        &input.secret_access_key,
+        Some(&current.secret_access_key),
+    )
+    .await?;
     db::update_token(
         input.id,
         input.name.as_deref(),
-        &input.api_token,
+        &api_token,
         &input.access_key_id,
-        &input.secret_access_key,
+        &secret_access_key,
     )
     .await
-    .map_err(|e| format!("Failed to update token: {}", e))
+    .map_err(|e| format!("Failed to update token: {}", e))?;
+    let keys_changed = input.access_key_id != current.access_key_id
+        || secret_access_key != current.secret_access_key;
+    update_capabilities(
+        "r2",
+        &input.id.to_string(),
+        input.capabilities.as_ref(),
+        keys_changed,
+    )
+    .await?;
+    secrets::release_replaced_credential(&current.api_token, &api_token).await;
+    secrets::release_replaced_credential(&current.secret_access_key, &secret_access_key).await;
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn delete_token(id: i64) -> Result<(), String> {
+    let stored = match db::get_token(id).await {
+        Ok(Some(token)) => vec![token.api_token, token.secret_access_key],
+        _ => Vec::new(),
+    };
     db::delete_token(id)
         .await
-        .map_err(|e| format!("Failed to delete token: {}", e))
+        .map_err(|e| format!("Failed to delete token: {}", e))?;
+    release_credentials(&stored).await;
+    Ok(())
 }
 
 #[tauri::command]
@@ -179,6 +287,21 @@ pub struct CreateAwsAccountInput {
     pub endpoint_scheme: Option<String>,
     pub endpoint_host: Option<String>,
     pub force_path_style: bool,
+    #[serde(default)]
+    pub credentials: CredentialSource,
+    /// Report from `validate_credentials`, saved with the credentials.
+    #[serde(default)]
+    pub capabilities: Option<CapabilityReport>,
+}
+
+/// Profiles must exist when an account is linked to them.
+fn check_credential_source(source: &CredentialSource) -> Result<(), String> {
+    match source {
+        CredentialSource::Profile { name } if credentials::find_profile(name).is_none() => {
+            Err(format!("AWS profile '{}' not found", name))
+        }
+        _ => Ok(()),
+    }
 }
 
 #[tauri::command]
@@ -190,17 +313,24 @@ pub async fn list_aws_accounts() -> Result<Vec<db::AwsAccount>, String> {
 
 #[tauri::command]
 pub async fn create_aws_account(input: CreateAwsAccountInput) -> Result<db::AwsAccount, String> {
-    db::create_aws_account(
+    check_credential_source(&input.credentials)?;
+    let secret_access_key =
+        secrets::store_credential("aws", &input.secret_access_key, None).await?;
+    let mut account = db::create_aws_account(
         input.name.as_deref(),
         &input.access_key_id,
-        &input.secret_access_key,
+        &secret_access_key,
         &input.region,
         input.endpoint_scheme.as_deref().unwrap_or("https"),
         input.endpoint_host.as_deref(),
         input.force_path_style,
+        &input.credentials,
     )
     .await
-    .map_err(|e| format!("Failed to create AWS account: {}", e))
+    .map_err(|e| format!("Failed to create AWS account: {}", e))?;
+    update_capabilities("aws", &account.id, input.capabilities.as_ref(), false).await?;
+    account.capabilities = input.capabilities;
+    Ok(account)
 }
 
 #[derive(Debug, Deserialize)]
@@ -213,29 +343,106 @@ pub struct UpdateAwsAccountInput {
     pub endpoint_scheme: Option<String>,
     pub endpoint_host: Option<String>,
     pub force_path_style: bool,
+    #[serde(default)]
+    pub credentials: CredentialSource,
+    /// Report from `validate_credentials`, saved with the credentials.
+    #[serde(default)]
+    pub capabilities: Option<CapabilityReport>,
 }
 
 #[tauri::command]
 pub async fn update_aws_account(input: UpdateAwsAccountInput) -> Result<(), String> {
+    check_credential_source(&input.credentials)?;
+    let current = saved_secret("aws", &input.id).await?;
+    let secret_access_key =
+        secrets::store_credential("aws", &input.secret_access_key, Some(&current)).await?;
     db::update_aws_account(
         &input.id,
         input.name.as_deref(),
         &input.access_key_id,
-        &input.secret_access_key,
+        &secret_access_key,
         &input.region,
         input.endpoint_scheme.as_deref().unwrap_or("https"),
         input.endpoint_host.as_deref(),
         input.force_path_style,
+        &input.credentials,
     )
     .await
-    .map_err(|e| format!("Failed to update AWS account: {}", e))
+    .map_err(|e| format!("Failed to update AWS account: {}", e))?;
+    update_capabilities(
+        "aws",
+        &input.id,
+        input.capabilities.as_ref(),
+        secret_access_key != current,
+    )
+    .await?;
+    secrets::release_replaced_credential(&current, &secret_access_key).await;
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn delete_aws_account(id: String) -> Result<(), String> {
+    let stored = account_credentials("aws", &id).await;
     db::delete_aws_account(&id)
         .await
-        .map_err(|e| format!("Failed to delete AWS account: {}", e))
+        .map_err(|e| format!("Failed to delete AWS account: {}", e))?;
+    release_credentials(&stored).await;
+    Ok(())
+}
+
+/// Named profiles from the shared AWS config and credentials files. Files are
+/// re-read, and cached profile credentials dropped so edits take effect.
+#[tauri::command]
+pub async fn list_aws_profiles() -> Result<Vec<AwsProfile>, String> {
+    credentials::reset_providers();
+    Ok(credentials::list_profiles())
+}
+
+/// Create an account linked to each named profile. The region comes from the
+/// profile (default us-east-1); profiles that are already linked are skipped.
+#[tauri::command]
+pub async fn import_aws_profiles(names: Vec<String>) -> Result<Vec<db::AwsAccount>, String> {
+    let existing = db::list_aws_accounts()
+        .await
+        .map_err(|e| format!("Failed to list AWS accounts: {}", e))?;
+
+    let mut created = Vec::new();
+    for name in names {
+        let source = CredentialSource::Profile { name: name.clone() };
+        if existing.iter().any(|account| account.credentials == source) {
+            continue;
+        }
+        let profile = credentials::find_profile(&name)
+            .ok_or_else(|| format!("AWS profile '{}' not found", name))?;
+        let account = db::create_aws_account(
+            Some(&name),
+            "",
+            "",
+            profile.region.as_deref().unwrap_or("us-east-1"),
+            "https",
+            None,
+            false,
+            &source,
+        )
+        .await
+        .map_err(|e| format!("Failed to create AWS account: {}", e))?;
+        created.push(account);
+    }
+    Ok(created)
+}
+
+/// Start a session for a profile with `mfa_serial` using a code from the MFA
+/// device. Returns the session expiry (unix seconds); requests fail with an
+/// MFA hint once it lapses.
+#[tauri::command]
+pub async fn start_aws_mfa_session(
+    profile: String,
+    token_code: String,
+    duration_secs: Option<i32>,
+) -> Result<i64, String> {
+    credentials::start_mfa_session(&profile, &token_code, duration_secs)
+        .await
+        .map_err(|e| format!("Failed to start MFA session: {}", e))
 }
 
 #[derive(Debug, Deserialize)]
@@ -290,6 +497,9 @@ pub struct CreateMinioAccountInput {
     pub endpoint_scheme: String,
     pub endpoint_host: String,
     pub force_path_style: bool,
+    /// Report from `validate_credentials`, saved with the credentials.
+    #[serde(default)]
+    pub capabilities: Option<CapabilityReport>,
 }
 
 #[tauri::command]
@@ -303,16 +513,21 @@ pub async fn list_minio_accounts() -> Result<Vec<db::MinioAccount>, String> {
 pub async fn create_minio_account(
     input: CreateMinioAccountInput,
 ) -> Result<db::MinioAccount, String> {
-    db::create_minio_account(
+    let secret_access_key =
+        secrets::store_credential("minio", &input.secret_access_key, None).await?;
+    let mut account = db::create_minio_account(
         input.name.as_deref(),
         &input.access_key_id,
-        &input.secret_access_key,
+        &secret_access_key,
         &input.endpoint_scheme,
         &input.endpoint_host,
         input.force_path_style,
     )
     .await
-    .map_err(|e| format!("Failed to create MinIO account: {}", e))
+    .map_err(|e| format!("Failed to create MinIO account: {}", e))?;
+    update_capabilities("minio", &account.id, input.capabilities.as_ref(), false).await?;
+    account.capabilities = input.capabilities;
+    Ok(account)
 }
 
 #[derive(Debug, Deserialize)]
@@ -324,28 +539,46 @@ pub struct UpdateMinioAccountInput {
     pub endpoint_scheme: String,
     pub endpoint_host: String,
     pub force_path_style: bool,
+    /// Report from `validate_credentials`, saved with the credentials.
+    #[serde(default)]
+    pub capabilities: Option<CapabilityReport>,
 }
 
 #[tauri::command]
 pub async fn update_minio_account(input: UpdateMinioAccountInput) -> Result<(), String> {
+    let current = saved_secret("minio", &input.id).await?;
+    let secret_access_key =
+        secrets::store_credential("minio", &input.secret_access_key, Some(&current)).await?;
     db::update_minio_account(
         &input.id,
         input.name.as_deref(),
         &input.access_key_id,
-        &input.secret_access_key,
+        &secret_access_key,
         &input.endpoint_scheme,
         &input.endpoint_host,
         input.force_path_style,
     )
     .await
-    .map_err(|e| format!("Failed to update MinIO account: {}", e))
+    .map_err(|e| format!("Failed to update MinIO account: {}", e))?;
+    update_capabilities(
+        "minio",
+        &input.id,
+        input.capabilities.as_ref(),
+        secret_access_key != current,
+    )
+    .await?;
+    secrets::release_replaced_credential(&current, &secret_access_key).await;
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn delete_minio_account(id: String) -> Result<(), String> {
+    let stored = account_credentials("minio", &id).await;
     db::delete_minio_account(&id)
         .await
-        .map_err(|e| format!("Failed to delete MinIO account: {}", e))
+        .map_err(|e| format!("Failed to delete MinIO account: {}", e))?;
+    release_credentials(&stored).await;
+    Ok(())
 }
 
 #[derive(Debug, Deserialize)]
@@ -399,6 +632,9 @@ pub struct CreateRustfsAccountInput {
     pub secret_access_key: String,
     pub endpoint_scheme: String,
     pub endpoint_host: String,
+    /// Report from `validate_credentials`, saved with the credentials.
+    #[serde(default)]
+    pub capabilities: Option<CapabilityReport>,
 }
 
 #[tauri::command]
@@ -412,16 +648,21 @@ pub async fn list_rustfs_accounts() -> Result<Vec<db::RustfsAccount>, String> {
 pub async fn create_rustfs_account(
     input: CreateRustfsAccountInput,
 ) -> Result<db::RustfsAccount, String> {
-    db::create_rustfs_account(
+    let secret_access_key =
+        secrets::store_credential("rustfs", &input.secret_access_key, None).await?;
+    let mut account = db::create_rustfs_account(
         input.name.as_deref(),
         &input.access_key_id,
-        &input.secret_access_key,
+        &secret_access_key,
         &input.endpoint_scheme,
         &input.endpoint_host,
         true,
     )
     .await
-    .map_err(|e| format!("Failed to create RustFS account: {}", e))
+    .map_err(|e| format!("Failed to create RustFS account: {}", e))?;
+    update_capabilities("rustfs", &account.id, input.capabilities.as_ref(), false).await?;
+    account.capabilities = input.capabilities;
+    Ok(account)
 }
 
 #[derive(Debug, Deserialize)]
@@ -432,28 +673,46 @@ pub struct UpdateRustfsAccountInput {
     pub secret_access_key: String,
     pub endpoint_scheme: String,
     pub endpoint_host: String,
+    /// Report from `validate_credentials`, saved with the credentials.
+    #[serde(default)]
+    pub capabilities: Option<CapabilityReport>,
 }
 
 #[tauri::command]
 pub async fn update_rustfs_account(input: UpdateRustfsAccountInput) -> Result<(), String> {
+    let current = saved_secret("rustfs", &input.id).await?;
+    let secret_access_key =
+        secrets::store_credential("rustfs", &input.secret_access_key, Some(&current)).await?;
     db::update_rustfs_account(
         &input.id,
         input.name.as_deref(),
         &input.access_key_id,
-        &input.secret_access_key,
+        &secret_access_key,
         &input.endpoint_scheme,
         &input.endpoint_host,
         true,
     )
     .await
-    .map_err(|e| format!("Failed to update RustFS account: {}", e))
+    .map_err(|e| format!("Failed to update RustFS account: {}", e))?;
+    update_capabilities(
+        "rustfs",
+        &input.id,
+        input.capabilities.as_ref(),
+        secret_access_key != current,
+    )
+    .await?;
+    secrets::release_replaced_credential(&current, &secret_access_key).await;
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn delete_rustfs_account(id: String) -> Result<(), String> {
+    let stored = account_credentials("rustfs", &id).await;
     db::delete_rustfs_account(&id)
         .await
-        .map_err(|e| format!("Failed to delete RustFS account: {}", e))
+        .map_err(|e| format!("Failed to delete RustFS account: {}", e))?;
+    release_credentials(&stored).await;
+    Ok(())
 }
 
 #[derive(Debug, Deserialize)]
@@ -0,0 +1,1166 @@
+//! Export and import of accounts, tokens and bucket settings.
+//!
+//! A bundle is a versioned JSON or TOML document with every saved account.
+//! Secrets can be written as is, left out, or sealed with a passphrase.
+//! Imports match bundle entries against saved accounts and skip, overwrite
+//! or add a renamed copy of the ones that already exist.
+
+use crate::crypto::{self, random_bytes, KEY_SIZE, NONCE_SIZE, SALT_SIZE};
+use crate::db;
+use crate::providers::aws::credentials::CredentialSource;
+use crate::secrets;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Current bundle format version. Bundles from newer versions are refused.
+pub const BUNDLE_VERSION: u32 = 1;
+
+const SECRET_AAD: &[u8] = b"r2-account-bundle-v1";
+
+/// Bucket settings as stored by the `save_*_buckets_*` functions.
+type BucketRow = (String, Option<String>, Option<String>, bool, Option<String>);
+
+// ============ Bundle Model ============
+
+/// How secrets are written into a bundle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretMode {
+    #[default]
+    Plain,
+    Omit,
+    /// Sealed with a key derived from a passphrase.
+    Encrypted,
+}
+
+/// What to do with a bundle entry whose account already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictMode {
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BundleFormat {
+    Json,
+    Toml,
+}
+
+impl BundleFormat {
+    /// `.toml` files are TOML, anything else is JSON.
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => BundleFormat::Toml,
+            _ => BundleFormat::Json,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountBundle {
+    pub version: u32,
+    pub exported_at: i64,
+    pub secrets: SecretMode,
+    /// Base64 salt of the passphrase key when secrets are encrypted.
+    pub salt: Option<String>,
+    #[serde(default)]
+    pub r2: Vec<R2AccountEntry>,
+    #[serde(default)]
+    pub aws: Vec<AwsAccountEntry>,
+    #[serde(default)]
+    pub minio: Vec<S3AccountEntry>,
+    #[serde(default)]
+    pub rustfs: Vec<S3AccountEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct R2AccountEntry {
+    /// Cloudflare account id
+    pub id: String,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub tokens: Vec<TokenEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenEntry {
+    pub name: Option<String>,
+    pub access_key_id: String,
+    pub api_token: Option<String>,
+    pub secret_access_key: Option<String>,
+    #[serde(default)]
+    pub buckets: Vec<R2BucketEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct R2BucketEntry {
+    pub name: String,
+    pub public_domain: Option<String>,
+    pub public_domain_scheme: Option<String>,
+    #[serde(default)]
+    pub is_public: bool,
+    pub public_path_prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AwsAccountEntry {
+    pub name: Option<String>,
+    pub access_key_id: String,
+    pub secret_access_key: Option<String>,
+    pub region: String,
+    pub endpoint_scheme: String,
+    pub endpoint_host: Option<String>,
+    #[serde(default)]
+    pub force_path_style: bool,
+    #[serde(default)]
+    pub credentials: CredentialSource,
+    #[serde(default)]
+    pub buckets: Vec<S3BucketEntry>,
+}
+
+/// A MinIO or RustFS account.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct S3AccountEntry {
+    pub name: Option<String>,
+    pub access_key_id: String,
+    pub secret_access_key: Option<String>,
+    pub endpoint_scheme: String,
+    pub endpoint_host: String,
+    #[serde(default)]
+    pub force_path_style: bool,
+    #[serde(default)]
+    pub buckets: Vec<S3BucketEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct S3BucketEntry {
+    pub name: String,
+    pub public_domain_scheme: Option<String>,
+    pub public_domain_host: Option<String>,
+    #[serde(default)]
+    pub is_public: bool,
+    pub public_path_prefix: Option<String>,
+}
+
+impl R2BucketEntry {
+    fn row(&self) -> BucketRow {
+        (
+            self.name.clone(),
+            self.public_domain.clone(),
+            self.public_domain_scheme.clone(),
+            self.is_public,
+            self.public_path_prefix.clone(),
+        )
+    }
+}
+
+impl S3BucketEntry {
+    fn row(&self) -> BucketRow {
+        (
+            self.name.clone(),
+            self.public_domain_scheme.clone(),
+            self.public_domain_host.clone(),
+            self.is_public,
+            self.public_path_prefix.clone(),
+        )
+    }
+}
+
+fn encode_bundle(bundle: &AccountBundle, format: BundleFormat) -> Result<String, String> {
+    match format {
+        BundleFormat::Json => serde_json::to_string_pretty(bundle).map_err(|e| e.to_string()),
+        BundleFormat::Toml => toml::to_string_pretty(bundle).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| format!("Failed to encode bundle: {}", e))
+}
+
+fn decode_bundle(text: &str, format: BundleFormat) -> Result<AccountBundle, String> {
+    let bundle: AccountBundle = match format {
+        BundleFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+        BundleFormat::Toml => toml::from_str(text).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| format!("Invalid account bundle: {}", e))?;
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "Account bundle version {} is newer than this app supports ({})",
+            bundle.version, BUNDLE_VERSION
+        ));
+    }
+    Ok(bundle)
+}
+
+// ============ Secrets ============
+
+/// Writes secrets into a bundle and reads them back according to its mode.
+struct SecretCodec {
+    mode: SecretMode,
+    key: Option<[u8; KEY_SIZE]>,
+}
+
+impl SecretCodec {
+    fn key(passphrase: Option<&str>, salt: &[u8]) -> Result<[u8; KEY_SIZE], String> {
+        let passphrase = passphrase
+            .filter(|p| !p.is_empty())
+            .ok_or("A passphrase is required for encrypted secrets")?;
+        crypto::derive_key(passphrase, salt)
+    }
+
+    /// Codec for a new bundle, with the salt to record in it.
+    fn for_export(
+        mode: SecretMode,
+        passphrase: Option<&str>,
+    ) -> Result<(Self, Option<String>), String> {
+        if mode != SecretMode::Encrypted {
+            return Ok((SecretCodec { mode, key: None }, None));
+        }
+        let salt = random_bytes::<SALT_SIZE>()?;
+        let codec = SecretCodec {
+            mode,
+            key: Some(Self::key(passphrase, &salt)?),
+        };
+        Ok((codec, Some(BASE64.encode(salt))))
+    }
+
+    fn for_import(bundle: &AccountBundle, passphrase: Option<&str>) -> Result<Self, String> {
+        let key = match bundle.secrets {
+            SecretMode::Encrypted => {
+                let salt = bundle
+                    .salt
+                    .as_deref()
+                    .and_then(|salt| BASE64.decode(salt).ok())
+                    .ok_or("Encrypted bundle has no valid salt")?;
+                Some(Self::key(passphrase, &salt)?)
+            }
+            _ => None,
+        };
+        Ok(SecretCodec {
+            mode: bundle.secrets,
+            key,
+        })
+    }
+
+    fn seal(&self, secret: &str) -> Result<Option<String>, String> {
+        match (self.mode, &self.key) {
+            (SecretMode::Omit, _) => Ok(None),
+            (SecretMode::Encrypted, Some(key)) => {
+                let sealed = crypto::seal(key, secret.as_bytes(), SECRET_AAD)
+                    .map_err(|_| "Failed to encrypt secret".to_string())?;
+                Ok(Some(BASE64.encode(sealed)))
+            }
+            _ => Ok(Some(secret.to_string())),
+        }
+    }
+
+    fn open(&self, value: Option<&str>) -> Result<Option<String>, String> {
+        let Some(value) = value.filter(|v| !v.is_empty()) else {
+            return Ok(None);
+        };
+        match (self.mode, &self.key) {
+            (SecretMode::Omit, _) => Ok(None),
+            (SecretMode::Encrypted, Some(key)) => {
+                let sealed = BASE64
+                    .decode(value)
+                    .ok()
+                    .filter(|sealed| sealed.len() > NONCE_SIZE)
+                    .ok_or("Encrypted secret is damaged")?;
+                let secret = crypto::open(key, &sealed, SECRET_AAD)
+                    .ok_or("Wrong passphrase or damaged bundle")?;
+                String::from_utf8(secret)
+                    .map(Some)
+                    .map_err(|_| "Encrypted secret is damaged".to_string())
+            }
+            _ => Ok(Some(value.to_string())),
+        }
+    }
+
+    /// The bundle value for a stored credential, resolving handles first.
+    async fn export(&self, stored: &str) -> Result<Option<String>, String> {
+        if stored.is_empty() || self.mode == SecretMode::Omit {
+            return Ok(None);
+        }
+        self.seal(&secrets::resolve(stored).await?)
+    }
+}
+
+// ============ Export ============
+
+fn db_error(action: &str) -> impl Fn(Box<dyn std::error::Error + Send + Sync>) -> String + '_ {
+    move |e| format!("Failed to {}: {}", action, e)
+}
+
+async fn collect_bundle(
+    mode: SecretMode,
+    passphrase: Option<&str>,
+) -> Result<AccountBundle, String> {
+    let (codec, salt) = SecretCodec::for_export(mode, passphrase)?;
+    let mut bundle = AccountBundle {
+        version: BUNDLE_VERSION,
+        exported_at: chrono::Utc::now().timestamp(),
+        secrets: mode,
+        salt,
+        r2: Vec::new(),
+        aws: Vec::new(),
+        minio: Vec::new(),
+        rustfs: Vec::new(),
+    };
+
+    let list_error = db_error("read saved accounts");
+    for account in db::list_accounts().await.map_err(&list_error)? {
+        let mut tokens = Vec::new();
+        for token in db::list_tokens_by_account(&account.id)
+            .await
+            .map_err(&list_error)?
+        {
+            let buckets = db::list_buckets_by_token(token.id)
+                .await
+                .map_err(&list_error)?
+                .into_iter()
+                .map(|b| R2BucketEntry {
+                    name: b.name,
+                    public_domain: b.public_domain,
+                    public_domain_scheme: b.public_domain_scheme,
+                    is_public: b.is_public,
+                    public_path_prefix: b.public_path_prefix,
+                })
+                .collect();
+            tokens.push(TokenEntry {
+                name: token.name,
+                access_key_id: token.access_key_id,
+                api_token: codec.export(&token.api_token).await?,
+                secret_access_key: codec.export(&token.secret_access_key).await?,
+                buckets,
+            });
+        }
+        bundle.r2.push(R2AccountEntry {
+            id: account.id,
+            name: account.name,
+            tokens,
+        });
+    }
+
+    for account in db::list_aws_accounts().await.map_err(&list_error)? {
+        let buckets = db::list_aws_buckets_by_account(&account.id)
+            .await
+            .map_err(&list_error)?
+            .into_iter()
+            .map(|b| S3BucketEntry {
+                name: b.name,
+                public_domain_scheme: b.public_domain_scheme,
+                public_domain_host: b.public_domain_host,
+                is_public: b.is_public,
+                public_path_prefix: b.public_path_prefix,
+            })
+            .collect();
+        bundle.aws.push(AwsAccountEntry {
+            name: account.name,
+            access_key_id: account.access_key_id,
+            secret_access_key: codec.export(&account.secret_access_key).await?,
+            region: account.region,
+            endpoint_scheme: account.endpoint_scheme,
+            endpoint_host: account.endpoint_host,
+            force_path_style: account.force_path_style,
+            credentials: account.credentials,
+            buckets,
+        });
+    }
+
+    for account in db::list_minio_accounts().await.map_err(&list_error)? {
+        let buckets = db::list_minio_buckets_by_account(&account.id)
+            .await
+            .map_err(&list_error)?
+            .into_iter()
+            .map(|b| S3BucketEntry {
+                name: b.name,
+                public_domain_scheme: b.public_domain_scheme,
+                public_domain_host: b.public_domain_host,
+                is_public: b.is_public,
+                public_path_prefix: b.public_path_prefix,
+            })
+            .collect();
+        bundle.minio.push(S3AccountEntry {
+            name: account.name,
+            access_key_id: account.access_key_id,
+            secret_access_key: codec.export(&account.secret_access_key).await?,
+            endpoint_scheme: account.endpoint_scheme,
+            endpoint_host: account.endpoint_host,
+            force_path_style: account.force_path_style,
+            buckets,
+        });
+    }
+
+    for account in db::list_rustfs_accounts().await.map_err(&list_error)? {
+        let buckets = db::list_rustfs_buckets_by_account(&account.id)
+            .await
+            .map_err(&list_error)?
+            .into_iter()
+            .map(|b| S3BucketEntry {
+                name: b.name,
+                public_domain_scheme: b.public_domain_scheme,
+                public_domain_host: b.public_domain_host,
+                is_public: b.is_public,
+                public_path_prefix: b.public_path_prefix,
+            })
+            .collect();
+        bundle.rustfs.push(S3AccountEntry {
+            name: account.name,
+            access_key_id: account.access_key_id,
+            secret_access_key: codec.export(&account.secret_access_key).await?,
+            endpoint_scheme: account.endpoint_scheme,
+            endpoint_host: account.endpoint_host,
+            force_path_style: account.force_path_style,
+            buckets,
+        });
+    }
+
+    Ok(bundle)
+}
+
+// ============ Import ============
+
+/// What an import did, by `provider/account` label.
+#[derive(Debug, Default, Serialize)]
+pub struct ImportSummary {
+    pub created: Vec<String>,
+    pub overwritten: Vec<String>,
+    /// Entries added next to an existing account under a new name.
+    pub renamed: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+/// `base`, or `base (2)`, `base (3)`, ... when the name is taken.
+fn unique_name(base: &str, taken: &[String]) -> String {
+    if !taken.iter().any(|name| name == base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{} ({})", base, n))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_default()
+}
+
+/// The columns of a saved AWS / MinIO / RustFS account that imports match on.
+#[derive(Debug, Clone)]
+struct SavedAccount {
+    id: String,
+    name: Option<String>,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+enum Placement {
+    Create { name: Option<String>, renamed: bool },
+    Overwrite(SavedAccount),
+    Skip,
+}
+
+/// Match an entry by name, or by access key when either side has no name.
+fn place(
+    saved: &[SavedAccount],
+    name: Option<&str>,
+    access_key_id: &str,
+    conflict: ConflictMode,
+) -> Placement {
+    let name = name.filter(|n| !n.is_empty());
+    let existing = saved
+        .iter()
+        .find(|account| match (name, account.name.as_deref()) {
+            (Some(name), Some(saved_name)) if !saved_name.is_empty() => name == saved_name,
+            _ => !access_key_id.is_empty() && access_key_id == account.access_key_id,
+        });
+    let Some(existing) = existing else {
+        return Placement::Create {
+            name: name.map(str::to_string),
+            renamed: false,
+        };
+    };
+    match conflict {
+        ConflictMode::Skip => Placement::Skip,
+        ConflictMode::Overwrite => Placement::Overwrite(existing.clone()),
+        ConflictMode::Rename => {
+            let taken: Vec<String> = saved.iter().filter_map(|a| a.name.clone()).collect();
+            Placement::Create {
+                name: Some(unique_name(name.unwrap_or(access_key_id), &taken)),
+                renamed: true,
+            }
+        }
+    }
+}
+
+impl ImportSummary {
+    fn record(&mut self, placement: &Placement, label: String) {
+        match placement {
+            Placement::Create { renamed: false, .. } => self.created.push(label),
+            Placement::Create { renamed: true, .. } => self.renamed.push(label),
+            Placement::Overwrite(_) => self.overwritten.push(label),
+            Placement::Skip => self.skipped.push(label),
+        }
+    }
+}
+
+fn entry_label(provider: &str, name: Option<&str>, fallback: &str) -> String {
+    format!(
+        "{}/{}",
+        provider,
+        name.filter(|n| !n.is_empty()).unwrap_or(fallback)
+    )
+}
+
+/// Secret to save for an entry: the imported one, or the current one when the
+/// bundle has none. Returns the value to save in the row.
+async fn import_secret(
+    provider: &str,
+    imported: Option<String>,
+    current: Option<&str>,
+) -> Result<String, String> {
+    let value = imported.unwrap_or_else(|| current.unwrap_or_default().to_string());
+    secrets::store_credential(provider, &value, current).await
+}
+
+async fn import_r2(
+    entry: &R2AccountEntry,
+    codec: &SecretCodec,
+    conflict: ConflictMode,
+    summary: &mut ImportSummary,
+) -> Result<(), String> {
+    let label = entry_label("r2", entry.name.as_deref(), &entry.id);
+    let exists = db::get_account(&entry.id)
+        .await
+        .map_err(db_error("read account"))?
+        .is_some();
+    // A Cloudflare account id cannot be renamed, so a renamed import adds
+    // its tokens to the existing account under names of their own.
+    let placement = match (exists, conflict) {
+        (false, _) => Placement::Create {
+            name: entry.name.clone(),
+            renamed: false,
+        },
+        (true, ConflictMode::Skip) => Placement::Skip,
+        (true, ConflictMode::Rename) => Placement::Create {
+            name: None,
+            renamed: true,
+        },
+        (true, ConflictMode::Overwrite) => Placement::Overwrite(SavedAccount {
+            id: entry.id.clone(),
+            name: entry.name.clone(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+        }),
+    };
+
+    let saved_tokens = match &placement {
+        Placement::Skip => {
+            summary.record(&placement, label);
+            return Ok(());
+        }
+        Placement::Create { renamed: false, .. } => {
+            db::create_account(&entry.id, entry.name.as_deref())
+                .await
+                .map_err(db_error("create account"))?;
+            Vec::new()
+        }
+        _ => db::list_tokens_by_account(&entry.id)
+            .await
+            .map_err(db_error("read tokens"))?,
+    };
+    if let (Placement::Overwrite(_), Some(name)) = (&placement, entry.name.as_deref()) {
+        db::update_account(&entry.id, Some(name))
+            .await
+            .map_err(db_error("update account"))?;
+    }
+
+    let mut taken: Vec<String> = saved_tokens.iter().filter_map(|t| t.name.clone()).collect();
+    for token in &entry.tokens {
+        let api_token = codec.open(token.api_token.as_deref())?;
+        let secret_access_key = codec.open(token.secret_access_key.as_deref())?;
+        let matched = match placement {
+            Placement::Overwrite(_) => saved_tokens
+                .iter()
+                .find(|t| t.access_key_id == token.access_key_id),
+            _ => None,
+        };
+
+        let token_id = match matched {
+            Some(current) => {
+                let api_token =
+                    import_secret("r2", api_token, Some(current.api_token.as_str())).await?;
+                let secret_access_key = import_secret(
+                    "r2",
+                    secret_access_key,
+                    Some(current.secret_access_key.as_str()),
+                )
+                .await?;
+                db::update_token(
+                    current.id,
+                    token.name.as_deref().or(current.name.as_deref()),
+                    &api_token,
+                    &token.access_key_id,
+                    &secret_access_key,
+                )
+                .await
+                .map_err(db_error("update token"))?;
+                secrets::release_replaced_credential(&current.api_token, &api_token).await;
+                secrets::release_replaced_credential(
+                    &current.secret_access_key,
+                    &secret_access_key,
+                )
+                .await;
+                current.id
+            }
+            None => {
+                let name = match placement {
+                    Placement::Create { renamed: true, .. } => Some(unique_name(
+                        token.name.as_deref().unwrap_or(&token.access_key_id),
+                        &taken,
+                    )),
+                    _ => token.name.clone(),
+                };
+                let api_token = import_secret("r2", api_token, None).await?;
+                let secret_access_key = import_secret("r2", secret_access_key, None).await?;
+                let created = db::create_token(
+                    &entry.id,
+                    name.as_deref(),
+                    &api_token,
+                    &token.access_key_id,
+                    &secret_access_key,
+                )
+                .await
+                .map_err(db_error("create token"))?;
+                taken.extend(name);
+                created.id
+            }
+        };
+
+        let buckets: Vec<BucketRow> = token.buckets.iter().map(R2BucketEntry::row).collect();
+        db::save_buckets_for_token(token_id, &buckets)
+            .await
+            .map_err(db_error("save buckets"))?;
+    }
+
+    summary.record(&placement, label);
+    Ok(())
+}
+
+async fn import_aws(
+    entry: &AwsAccountEntry,
+    codec: &SecretCodec,
+    conflict: ConflictMode,
+    summary: &mut ImportSummary,
+) -> Result<(), String> {
+    let saved: Vec<SavedAccount> = db::list_aws_accounts()
+        .await
+        .map_err(db_error("read saved accounts"))?
+        .into_iter()
+        .map(|a| SavedAccount {
+            id: a.id,
+            name: a.name,
+            access_key_id: a.access_key_id,
+            secret_access_key: a.secret_access_key,
+        })
+        .collect();
+    let placement = place(
+        &saved,
+        entry.name.as_deref(),
+        &entry.access_key_id,
+        conflict,
+    );
+    let secret = codec.open(entry.secret_access_key.as_deref())?;
+
+    let account_id = match &placement {
+        Placement::Skip => None,
+        Placement::Create { name, .. } => {
+            let secret = import_secret("aws", secret, None).await?;
+            let account = db::create_aws_account(
+                name.as_deref(),
+                &entry.access_key_id,
+                &secret,
+                &entry.region,
+                &entry.endpoint_scheme,
+                entry.endpoint_host.as_deref(),
+                entry.force_path_style,
+                &entry.credentials,
+            )
+            .await
+            .map_err(db_error("create AWS account"))?;
+            Some(account.id)
+        }
+        Placement::Overwrite(current) => {
+            let secret =
+                import_secret("aws", secret, Some(current.secret_access_key.as_str())).await?;
+            db::update_aws_account(
+                &current.id,
+                entry.name.as_deref().or(current.name.as_deref()),
+                &entry.access_key_id,
+                &secret,
+                &entry.region,
+                &entry.endpoint_scheme,
+                entry.endpoint_host.as_deref(),
+                entry.force_path_style,
+                &entry.credentials,
+            )
+            .await
+            .map_err(db_error("update AWS account"))?;
+            secrets::release_replaced_credential(&current.secret_access_key, &secret).await;
+            Some(current.id.clone())
+        }
+    };
+    if let Some(account_id) = account_id {
+        let buckets: Vec<BucketRow> = entry.buckets.iter().map(S3BucketEntry::row).collect();
+        db::save_aws_buckets_for_account(&account_id, &buckets)
+            .await
+            .map_err(db_error("save AWS buckets"))?;
+    }
+
+    summary.record(
+        &placement,
+        entry_label("aws", entry.name.as_deref(), &entry.access_key_id),
+    );
+    Ok(())
+}
+
+/// MinIO and RustFS accounts share a shape; this picks the tables.
+#[derive(Debug, Clone, Copy)]
+enum S3Provider {
+    Minio,
+    Rustfs,
+}
+
+impl S3Provider {
+    fn id(self) -> &'static str {
+        match self {
+            S3Provider::Minio => "minio",
+            S3Provider::Rustfs => "rustfs",
+        }
+    }
+
+    async fn saved_accounts(self) -> db::DbResult<Vec<SavedAccount>> {
+        Ok(match self {
+            S3Provider::Minio => db::list_minio_accounts()
+                .await?
+                .into_iter()
+                .map(|a| SavedAccount {
+                    id: a.id,
+                    name: a.name,
+                    access_key_id: a.access_key_id,
+                    secret_access_key: a.secret_access_key,
+                })
+                .collect(),
+            S3Provider::Rustfs => db::list_rustfs_accounts()
+                .await?
+                .into_iter()
+                .map(|a| SavedAccount {
+                    id: a.id,
+                    name: a.name,
+                    access_key_id: a.access_key_id,
+                    secret_access_key: a.secret_access_key,
+                })
+                .collect(),
+        })
+    }
+
+    async fn create(
+        self,
+        name: Option<&str>,
+        entry: &S3AccountEntry,
+        secret: &str,
+    ) -> db::DbResult<String> {
+        Ok(match self {
+            S3Provider::Minio => {
+                db::create_minio_account(
+                    name,
+                    &entry.access_key_id,
+                    secret,
+                    &entry.endpoint_scheme,
+                    &entry.endpoint_host,
+                    entry.force_path_style,
+                )
+                .await?
+                .id
+            }
+            S3Provider::Rustfs => {
+                db::create_rustfs_account(
+                    name,
+                    &entry.access_key_id,
+                    secret,
+                    &entry.endpoint_scheme,
+                    &entry.endpoint_host,
+                    entry.force_path_style,
+                )
+                .await?
+                .id
+            }
+        })
+    }
+
+    async fn update(
+        self,
+        id: &str,
+        name: Option<&str>,
+        entry: &S3AccountEntry,
+        secret: &str,
+    ) -> db::DbResult<()> {
+        match self {
+            S3Provider::Minio => {
+                db::update_minio_account(
+                    id,
+                    name,
+                    &entry.access_key_id,
+                    secret,
+                    &entry.endpoint_scheme,
+                    &entry.endpoint_host,
+                    entry.force_path_style,
+                )
+                .await
+            }
+            S3Provider::Rustfs => {
+                db::update_rustfs_account(
+                    id,
+                    name,
+                    &entry.access_key_id,
+                    secret,
+                    &entry.endpoint_scheme,
+                    &entry.endpoint_host,
+                    entry.force_path_style,
+                )
+                .await
+            }
+        }
+    }
+
+    async fn save_buckets(self, account_id: &str, buckets: &[BucketRow]) -> db::DbResult<()> {
+        match self {
+            S3Provider::Minio => {
+                db::save_minio_buckets_for_account(account_id, buckets).await?;
+            }
+            S3Provider::Rustfs => {
+                db::save_rustfs_buckets_for_account(account_id, buckets).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn import_s3(
+    provider: S3Provider,
+    entry: &S3AccountEntry,
+    codec: &SecretCodec,
+    conflict: ConflictMode,
+    summary: &mut ImportSummary,
+) -> Result<(), String> {
+    let saved = provider
+        .saved_accounts()
+        .await
+        .map_err(db_error("read saved accounts"))?;
+    let placement = place(
+        &saved,
+        entry.name.as_deref(),
+        &entry.access_key_id,
+        conflict,
+    );
+    let secret = codec.open(entry.secret_access_key.as_deref())?;
+
+    let account_id = match &placement {
+        Placement::Skip => None,
+        Placement::Create { name, .. } => {
+            let secret = import_secret(provider.id(), secret, None).await?;
+            let id = provider
+                .create(name.as_deref(), entry, &secret)
+                .await
+                .map_err(db_error("create account"))?;
+            Some(id)
+        }
+        Placement::Overwrite(current) => {
+            let secret = import_secret(
+                provider.id(),
+                secret,
+                Some(current.secret_access_key.as_str()),
+            )
+            .await?;
+            provider
+                .update(
+                    &current.id,
+                    entry.name.as_deref().or(current.name.as_deref()),
+                    entry,
+                    &secret,
+                )
+                .await
+                .map_err(db_error("update account"))?;
+            secrets::release_replaced_credential(&current.secret_access_key, &secret).await;
+            Some(current.id.clone())
+        }
+    };
+    if let Some(account_id) = account_id {
+        let buckets: Vec<BucketRow> = entry.buckets.iter().map(S3BucketEntry::row).collect();
+        provider
+            .save_buckets(&account_id, &buckets)
+            .await
+            .map_err(db_error("save buckets"))?;
+    }
+
+    summary.record(
+        &placement,
+        entry_label(provider.id(), entry.name.as_deref(), &entry.access_key_id),
+    );
+    Ok(())
+}
+
+async fn apply_bundle(
+    bundle: &AccountBundle,
+    passphrase: Option<&str>,
+    conflict: ConflictMode,
+) -> Result<ImportSummary, String> {
+    let codec = SecretCodec::for_import(bundle, passphrase)?;
+    let mut summary = ImportSummary::default();
+    for entry in &bundle.r2 {
+        import_r2(entry, &codec, conflict, &mut summary).await?;
+    }
+    for entry in &bundle.aws {
+        import_aws(entry, &codec, conflict, &mut summary).await?;
+    }
+    for entry in &bundle.minio {
+        import_s3(S3Provider::Minio, entry, &codec, conflict, &mut summary).await?;
+    }
+    for entry in &bundle.rustfs {
+        import_s3(S3Provider::Rustfs, entry, &codec, conflict, &mut summary).await?;
+    }
+    Ok(summary)
+}
+
+// ============ Commands ============
+
+/// Write every saved account to `path`, as TOML for `.toml` files and JSON
+/// otherwise. Returns the number of accounts exported.
+#[tauri::command]
+pub async fn export_accounts(
+    path: String,
+    secrets: Option<SecretMode>,
+    passphrase: Option<String>,
+) -> Result<usize, String> {
+    let path = Path::new(&path);
+    let bundle = collect_bundle(secrets.unwrap_or_default(), passphrase.as_deref()).await?;
+    let text = encode_bundle(&bundle, BundleFormat::from_path(path))?;
+    tokio::fs::write(path, text)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(bundle.r2.len() + bundle.aws.len() + bundle.minio.len() + bundle.rustfs.len())
+}
+
+/// Import the accounts of a bundle written by `export_accounts`.
+#[tauri::command]
+pub async fn import_accounts(
+    path: String,
+    conflict: ConflictMode,
+    passphrase: Option<String>,
+) -> Result<ImportSummary, String> {
+    let path = Path::new(&path);
+    let text = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let bundle = decode_bundle(&text, BundleFormat::from_path(path))?;
+    apply_bundle(&bundle, passphrase.as_deref(), conflict).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_bundle(mode: SecretMode) -> AccountBundle {
+        AccountBundle {
+            version: BUNDLE_VERSION,
+            exported_at: 1_700_000_000,
+            secrets: mode,
+            salt: None,
+            r2: vec![R2AccountEntry {
+                id: "bundle-test-r2".to_string(),
+                name: Some("Personal".to_string()),
+                tokens: vec![TokenEntry {
+                    name: Some("main".to_string()),
+                    access_key_id: "r2-key".to_string(),
+                    api_token: None,
+                    secret_access_key: None,
+                    buckets: vec![R2BucketEntry {
+                        name: "photos".to_string(),
+                        public_domain: Some("cdn.example.com".to_string()),
+                        public_domain_scheme: Some("https".to_string()),
+                        is_public: true,
+                        public_path_prefix: Some("img/".to_string()),
+                    }],
+                }],
+            }],
+            aws: vec![AwsAccountEntry {
+                name: Some("bundle-test-aws".to_string()),
+                access_key_id: String::new(),
+                secret_access_key: None,
+                region: "eu-west-1".to_string(),
+                endpoint_scheme: "https".to_string(),
+                endpoint_host: None,
+                force_path_style: false,
+                credentials: CredentialSource::Profile {
+                    name: "work".to_string(),
+                },
+                buckets: vec![S3BucketEntry {
+                    name: "logs".to_string(),
+                    public_domain_scheme: None,
+                    public_domain_host: None,
+                    is_public: false,
+                    public_path_prefix: None,
+                }],
+            }],
+            minio: vec![S3AccountEntry {
+                name: Some("bundle-test-minio".to_string()),
+                access_key_id: "minio-key".to_string(),
+                secret_access_key: None,
+                endpoint_scheme: "http".to_string(),
+                endpoint_host: "localhost:9000".to_string(),
+                force_path_style: true,
+                buckets: vec![S3BucketEntry {
+                    name: "backups".to_string(),
+                    public_domain_scheme: Some("http".to_string()),
+                    public_domain_host: Some("localhost:9000".to_string()),
+                    is_public: true,
+                    public_path_prefix: None,
+                }],
+            }],
+            rustfs: vec![S3AccountEntry {
+                name: Some("bundle-test-rustfs".to_string()),
+                access_key_id: "rustfs-key".to_string(),
+                secret_access_key: None,
+                endpoint_scheme: "http".to_string(),
+                endpoint_host: "localhost:9100".to_string(),
+                force_path_style: true,
+                buckets: Vec::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn bundles_round_trip_as_json_and_toml() {
+        let mut bundle = sample_bundle(SecretMode::Plain);
+        bundle.r2[0].tokens[0].api_token = Some("api-token".to_string());
+        bundle.minio[0].secret_access_key = Some("minio-secret".to_string());
+        for format in [BundleFormat::Json, BundleFormat::Toml] {
+            let text = encode_bundle(&bundle, format).unwrap();
+            assert_eq!(decode_bundle(&text, format).unwrap(), bundle);
+        }
+    }
+
+    #[test]
+    fn format_follows_extension() {
+        assert_eq!(
+            BundleFormat::from_path(Path::new("accounts.TOML")),
+            BundleFormat::Toml
+        );
+        assert_eq!(
+            BundleFormat::from_path(Path::new("accounts.json")),
+            BundleFormat::Json
+        );
+    }
+
+    #[test]
+    fn newer_bundles_are_refused() {
+        let mut bundle = sample_bundle(SecretMode::Omit);
+        bundle.version = BUNDLE_VERSION + 1;
+        let text = encode_bundle(&bundle, BundleFormat::Json).unwrap();
+        assert!(decode_bundle(&text, BundleFormat::Json).is_err());
+    }
+
+    #[test]
+    fn encrypted_secrets_need_the_passphrase() {
+        let (codec, salt) =
+            SecretCodec::for_export(SecretMode::Encrypted, Some("correct horse")).unwrap();
+        let sealed = codec.seal("s3cret").unwrap();
+        assert_ne!(sealed.as_deref(), Some("s3cret"));
+
+        let mut bundle = sample_bundle(SecretMode::Encrypted);
+        bundle.salt = salt;
+        let opened = SecretCodec::for_import(&bundle, Some("correct horse"))
+            .unwrap()
+            .open(sealed.as_deref())
+            .unwrap();
+        assert_eq!(opened.as_deref(), Some("s3cret"));
+
+        let wrong = SecretCodec::for_import(&bundle, Some("wrong horse")).unwrap();
+        assert!(wrong.open(sealed.as_deref()).is_err());
+        assert!(SecretCodec::for_import(&bundle, None).is_err());
+    }
+
+    #[test]
+    fn omitted_secrets_stay_out() {
+        let (codec, salt) = SecretCodec::for_export(SecretMode::Omit, None).unwrap();
+        assert_eq!(salt, None);
+        assert_eq!(codec.seal("s3cret").unwrap(), None);
+    }
+
+    #[test]
+    fn unique_names_count_up() {
+        let taken = vec!["work".to_string(), "work (2)".to_string()];
+        assert_eq!(unique_name("home", &taken), "home");
+        assert_eq!(unique_name("work", &taken), "work (3)");
+    }
+
+    /// Only this test's entries, so other rows in the shared database do not
+    /// matter.
+    fn own_entries(mut bundle: AccountBundle) -> AccountBundle {
+        let ours = |name: &Option<String>| {
+            name.as_deref()
+                .is_some_and(|n| n.starts_with("bundle-test-"))
+        };
+        bundle.exported_at = 0;
+        bundle.r2.retain(|a| a.id == "bundle-test-r2");
+        bundle.aws.retain(|a| ours(&a.name));
+        bundle.minio.retain(|a| ours(&a.name));
+        bundle.rustfs.retain(|a| ours(&a.name));
+        bundle
+    }
+
+    #[test]
+    fn database_round_trip() {
+        db::run_db_test(async {
+            let mut bundle = sample_bundle(SecretMode::Omit);
+            bundle.exported_at = 0;
+
+            let summary = apply_bundle(&bundle, None, ConflictMode::Skip)
+                .await
+                .unwrap();
+            assert_eq!(summary.created.len(), 4);
+            let exported = collect_bundle(SecretMode::Omit, None).await.unwrap();
+            assert_eq!(own_entries(exported), bundle);
+
+            let summary = apply_bundle(&bundle, None, ConflictMode::Skip)
+                .await
+                .unwrap();
+            assert_eq!(summary.skipped.len(), 4);
+
+            let mut changed = bundle.clone();
+            changed.minio[0].endpoint_host = "minio.internal:9000".to_string();
+            changed.r2[0].tokens[0].buckets.clear();
+            let summary = apply_bundle(&changed, None, ConflictMode::Overwrite)
+                .await
+                .unwrap();
+            assert_eq!(summary.overwritten.len(), 4);
+            let exported = collect_bundle(SecretMode::Omit, None).await.unwrap();
+            assert_eq!(own_entries(exported), changed);
+
+            let summary = apply_bundle(&bundle, None, ConflictMode::Rename)
+                .await
+                .unwrap();
+            assert_eq!(summary.renamed.len(), 4);
+            let exported = collect_bundle(SecretMode::Omit, None).await.unwrap();
+            let names: Vec<_> = exported
+                .minio
+                .iter()
+                .filter_map(|a| a.name.clone())
+                .collect();
+            assert!(names.contains(&"bundle-test-minio (2)".to_string()));
+            let tokens: Vec<_> = own_entries(exported).r2[0]
+                .tokens
+                .iter()
+                .filter_map(|t| t.name.clone())
+                .collect();
+            assert_eq!(tokens.len(), 2);
+            assert!(tokens.contains(&"main (2)".to_string()));
+        });
+    }
+}
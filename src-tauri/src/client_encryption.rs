@@ -0,0 +1,535 @@
+//! Client-side encryption for buckets whose provider should only ever see
+//! ciphertext.
+//!
+//! Every object gets a random data key. The file is encrypted with
+//! ChaCha20-Poly1305 in fixed-size segments (the STREAM construction: a random
+//! nonce prefix, a segment counter and a last-segment flag), so it is
+//! authenticated piecewise and truncation is detected. The data key is wrapped
+//! with a key derived from the bucket's passphrase (Argon2id) or key file
+//! (SHA-256), using a per-object salt. Everything needed to decrypt, except the
+//! passphrase or key file itself, is stored in the object's user metadata, so
+//! any machine with the same secret can read the object back.
+
+use crate::crypto::{self, random_bytes, KEY_SIZE, NONCE_SIZE, SALT_SIZE};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Plaintext bytes per encrypted segment.
+pub const SEGMENT_SIZE: usize = 64 * 1024;
+/// Upper bound accepted from metadata, so a bad object cannot force a huge buffer.
+const MAX_SEGMENT_SIZE: usize = 16 * 1024 * 1024;
+const TAG_SIZE: usize = 16;
+const NONCE_PREFIX_SIZE: usize = 7;
+const ENVELOPE_VERSION: &str = "1";
+/// Associated data binding the wrapped data key to this format.
+const WRAP_AAD: &[u8] = b"r2-client-encryption-v1";
+
+/// Prefix S3 puts in front of user-metadata keys on the wire.
+pub const USER_METADATA_PREFIX: &str = "x-amz-meta-";
+const META_VERSION: &str = "cse-version";
+const META_KDF: &str = "cse-kdf";
+const META_SALT: &str = "cse-salt";
+const META_WRAPPED_KEY: &str = "cse-key";
+const META_NONCE_PREFIX: &str = "cse-nonce";
+const META_SEGMENT_SIZE: &str = "cse-segment";
+const META_PLAINTEXT_SIZE: &str = "cse-size";
+
+/// Secret the bucket's data keys are wrapped with.
+#[derive(Clone)]
+pub enum ClientKey {
+    Passphrase(String),
+    /// Raw contents of a key file.
+    KeyFile(Vec<u8>),
+}
+
+impl fmt::Debug for ClientKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientKey::Passphrase(_) => f.write_str("ClientKey::Passphrase(..)"),
+            ClientKey::KeyFile(_) => f.write_str("ClientKey::KeyFile(..)"),
+        }
+    }
+}
+
+impl ClientKey {
+    fn kdf(&self) -> &'static str {
+        match self {
+            ClientKey::Passphrase(_) => "argon2id",
+            ClientKey::KeyFile(_) => "sha256",
+        }
+    }
+
+    fn derive_wrapping_key(&self, kdf: &str, salt: &[u8]) -> Result<[u8; KEY_SIZE], String> {
+        if kdf != self.kdf() {
+            return Err(match kdf {
+                "argon2id" => {
+                    "Object was encrypted with a passphrase, but this bucket uses a key file"
+                }
+                "sha256" => {
+                    "Object was encrypted with a key file, but this bucket uses a passphrase"
+                }
+                _ => "Object was encrypted with an unknown key derivation",
+            }
+            .to_string());
+        }
+        match self {
+            ClientKey::Passphrase(passphrase) => crypto::derive_key(passphrase, salt),
+            ClientKey::KeyFile(contents) => {
+                let mut hasher = Sha256::new();
+                hasher.update(salt);
+                hasher.update(contents);
+                let mut out = [0u8; KEY_SIZE];
+                out.copy_from_slice(&hasher.finalize());
+                Ok(out)
+            }
+        }
+    }
+}
+
+/// Per-object parameters stored in user metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    kdf: String,
+    salt: Vec<u8>,
+    /// Wrap nonce followed by the encrypted data key.
+    wrapped_key: Vec<u8>,
+    nonce_prefix: [u8; NONCE_PREFIX_SIZE],
+    segment_size: usize,
+    plaintext_size: u64,
+}
+
+impl Envelope {
+    pub fn plaintext_size(&self) -> u64 {
+        self.plaintext_size
+    }
+
+    /// User metadata to store with the object (keys without `x-amz-meta-`).
+    pub fn to_metadata(&self) -> HashMap<String, String> {
+        HashMap::from([
+            (META_VERSION.to_string(), ENVELOPE_VERSION.to_string()),
+            (META_KDF.to_string(), self.kdf.clone()),
+            (META_SALT.to_string(), BASE64.encode(&self.salt)),
+            (
+                META_WRAPPED_KEY.to_string(),
+                BASE64.encode(&self.wrapped_key),
+            ),
+            (
+                META_NONCE_PREFIX.to_string(),
+                BASE64.encode(self.nonce_prefix),
+            ),
+            (META_SEGMENT_SIZE.to_string(), self.segment_size.to_string()),
+            (
+                META_PLAINTEXT_SIZE.to_string(),
+                self.plaintext_size.to_string(),
+            ),
+        ])
+    }
+
+    /// Metadata as raw request headers, for the presigned uploaders.
+    pub fn to_headers(&self) -> Vec<(String, String)> {
+        let mut headers: Vec<(String, String)> = self
+            .to_metadata()
+            .into_iter()
+            .map(|(name, value)| (format!("{}{}", USER_METADATA_PREFIX, name), value))
+            .collect();
+        headers.sort();
+        headers
+    }
+
+    /// Read an envelope back from user metadata. `None` when the object was
+    /// not client-side encrypted.
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Result<Option<Self>, String> {
+        let Some(version) = metadata.get(META_VERSION) else {
+            return Ok(None);
+        };
+        if version != ENVELOPE_VERSION {
+            return Err(format!(
+                "Unsupported client-side encryption version: {}",
+                version
+            ));
+        }
+        let field = |name: &str| {
+            metadata
+                .get(name)
+                .ok_or_else(|| format!("Encrypted object is missing the {} metadata", name))
+        };
+        let decode = |name: &str| {
+            field(name).and_then(|value| {
+                BASE64
+                    .decode(value)
+                    .map_err(|_| format!("Invalid {} metadata", name))
+            })
+        };
+
+        let nonce_prefix: [u8; NONCE_PREFIX_SIZE] = decode(META_NONCE_PREFIX)?
+            .try_into()
+            .map_err(|_| format!("Invalid {} metadata", META_NONCE_PREFIX))?;
+        let segment_size: usize = field(META_SEGMENT_SIZE)?
+            .parse()
+            .map_err(|_| format!("Invalid {} metadata", META_SEGMENT_SIZE))?;
+        if segment_size == 0 || segment_size > MAX_SEGMENT_SIZE {
+            return Err(format!("Invalid {} metadata", META_SEGMENT_SIZE));
+        }
+
+        Ok(Some(Envelope {
+            kdf: field(META_KDF)?.clone(),
+            salt: decode(META_SALT)?,
+            wrapped_key: decode(META_WRAPPED_KEY)?,
+            nonce_prefix,
+            segment_size,
+            plaintext_size: field(META_PLAINTEXT_SIZE)?
+                .parse()
+                .map_err(|_| format!("Invalid {} metadata", META_PLAINTEXT_SIZE))?,
+        }))
+    }
+
+    /// Like `from_metadata`, reading `x-amz-meta-*` response headers.
+    pub fn from_headers(headers: &reqwest::header::HeaderMap) -> Result<Option<Self>, String> {
+        let metadata = headers
+            .iter()
+            .filter_map(|(name, value)| {
+                let name = name.as_str().strip_prefix(USER_METADATA_PREFIX)?;
+                Some((name.to_string(), value.to_str().ok()?.to_string()))
+            })
+            .collect();
+        Self::from_metadata(&metadata)
+    }
+
+    fn unwrap_data_key(&self, key: &ClientKey) -> Result<ChaCha20Poly1305, String> {
+        if self.wrapped_key.len() <= NONCE_SIZE {
+            return Err(format!("Invalid {} metadata", META_WRAPPED_KEY));
+        }
+        let wrapping_key = key.derive_wrapping_key(&self.kdf, &self.salt)?;
+        let data_key = crypto::open(&wrapping_key, &self.wrapped_key, WRAP_AAD)
+            .ok_or_else(|| "Wrong passphrase or key file for this object".to_string())?;
+        if data_key.len() != KEY_SIZE {
+            return Err(format!("Invalid {} metadata", META_WRAPPED_KEY));
+        }
+        Ok(ChaCha20Poly1305::new(Key::from_slice(&data_key)))
+    }
+}
+
+fn segment_nonce(prefix: &[u8; NONCE_PREFIX_SIZE], counter: u32, last: bool) -> Nonce {
+    let mut nonce = [0u8; 12];
+    nonce[..NONCE_PREFIX_SIZE].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_SIZE..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = last as u8;
+    *Nonce::from_slice(&nonce)
+}
+
+/// Size of the stored object for a plaintext of `plaintext_len` bytes.
+pub fn ciphertext_len(plaintext_len: u64) -> u64 {
+    let segment = SEGMENT_SIZE as u64;
+    let segments = plaintext_len.div_ceil(segment).max(1);
+    plaintext_len + segments * TAG_SIZE as u64
+}
+
+/// Fill `buf` from `reader`, stopping early only at end of input.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+fn seal_stream(
+    key: &ClientKey,
+    plaintext_size: u64,
+    segment_size: usize,
+    mut reader: impl Read,
+    mut writer: impl Write,
+) -> Result<Envelope, String> {
+    let salt = random_bytes::<SALT_SIZE>()?;
+    let data_key = random_bytes::<KEY_SIZE>()?;
+    let nonce_prefix = random_bytes::<NONCE_PREFIX_SIZE>()?;
+
+    let wrapping_key = key.derive_wrapping_key(key.kdf(), &salt)?;
+    let wrapped_key = crypto::seal(&wrapping_key, &data_key, WRAP_AAD)
+        .map_err(|_| "Failed to wrap data key".to_string())?;
+
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&data_key));
+    let io_err = |e: std::io::Error| format!("Failed to encrypt file: {}", e);
+
+    // Read one segment ahead so the final segment can be flagged.
+    let mut current = vec![0u8; segment_size];
+    let mut next = vec![0u8; segment_size];
+    let mut current_len = read_full(&mut reader, &mut current).map_err(io_err)?;
+    let mut counter: u32 = 0;
+    let mut total: u64 = 0;
+    loop {
+        let next_len = if current_len == segment_size {
+            read_full(&mut reader, &mut next).map_err(io_err)?
+        } else {
+            0
+        };
+        let last = next_len == 0;
+        let sealed = cipher
+            .encrypt(
+                &segment_nonce(&nonce_prefix, counter, last),
+                &current[..current_len],
+            )
+            .map_err(|_| "Failed to encrypt segment".to_string())?;
+        writer.write_all(&sealed).map_err(io_err)?;
+        total += current_len as u64;
+        if last {
+            break;
+        }
+        counter = counter
+            .checked_add(1)
+            .ok_or_else(|| "File is too large to encrypt".to_string())?;
+        std::mem::swap(&mut current, &mut next);
+        current_len = next_len;
+    }
+    writer.flush().map_err(io_err)?;
+
+    if total != plaintext_size {
+        return Err("File changed while it was being encrypted".to_string());
+    }
+
+    Ok(Envelope {
+        kdf: key.kdf().to_string(),
+        salt: salt.to_vec(),
+        wrapped_key,
+        nonce_prefix,
+        segment_size,
+        plaintext_size,
+    })
+}
+
+fn open_stream(
+    key: &ClientKey,
+    envelope: &Envelope,
+    ciphertext_size: u64,
+    mut reader: impl Read,
+    mut writer: impl Write,
+) -> Result<(), String> {
+    let cipher = envelope.unwrap_data_key(key)?;
+    let io_err = |e: std::io::Error| format!("Failed to decrypt file: {}", e);
+    let sealed_segment = (envelope.segment_size + TAG_SIZE) as u64;
+    let segments = ciphertext_size.div_ceil(sealed_segment).max(1);
+
+    let mut buffer = vec![0u8; sealed_segment as usize];
+    let mut total: u64 = 0;
+    for index in 0..segments {
+        let read = read_full(&mut reader, &mut buffer).map_err(io_err)?;
+        let last = index + 1 == segments;
+        if read < TAG_SIZE || (!last && read as u64 != sealed_segment) {
+            return Err("Encrypted object is truncated".to_string());
+        }
+        let counter =
+            u32::try_from(index).map_err(|_| "Encrypted object is too large".to_string())?;
+        let plaintext = cipher
+            .decrypt(
+                &segment_nonce(&envelope.nonce_prefix, counter, last),
+                &buffer[..read],
+            )
+            .map_err(|_| {
+                "Encrypted object failed authentication (corrupted or truncated)".to_string()
+            })?;
+        writer.write_all(&plaintext).map_err(io_err)?;
+        total += plaintext.len() as u64;
+    }
+    writer.flush().map_err(io_err)?;
+
+    if total != envelope.plaintext_size {
+        return Err("Decrypted size does not match the object's metadata".to_string());
+    }
+    Ok(())
+}
+
+/// Encrypted copy of a local file, removed when dropped.
+pub struct SealedFile {
+    path: PathBuf,
+    envelope: Envelope,
+}
+
+impl SealedFile {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn envelope(&self) -> &Envelope {
+        &self.envelope
+    }
+}
+
+impl Drop for SealedFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Encrypt `source` into a temporary file ready for upload.
+pub async fn seal_file(source: &Path, key: &ClientKey) -> Result<SealedFile, String> {
+    let source = source.to_path_buf();
+    let key = key.clone();
+    tokio::task::spawn_blocking(move || {
+        let input = File::open(&source).map_err(|e| format!("Failed to open file: {}", e))?;
+        let plaintext_size = input
+            .metadata()
+            .map_err(|e| format!("Failed to get file metadata: {}", e))?
+            .len();
+        let suffix = hex::encode(random_bytes::<8>()?);
+        let path = std::env::temp_dir().join(format!("r2-cse-{}.enc", suffix));
+        let output = File::create(&path)
+            .map_err(|e| format!("Failed to create encrypted temp file: {}", e))?;
+        // Constructed before encrypting so the temp file is removed on error.
+        let mut sealed = SealedFile {
+            path,
+            envelope: Envelope {
+                kdf: String::new(),
+                salt: Vec::new(),
+                wrapped_key: Vec::new(),
+                nonce_prefix: [0; NONCE_PREFIX_SIZE],
+                segment_size: SEGMENT_SIZE,
+                plaintext_size,
+            },
+        };
+        sealed.envelope = seal_stream(
+            &key,
+            plaintext_size,
+            SEGMENT_SIZE,
+            BufReader::new(input),
+            BufWriter::new(output),
+        )?;
+        Ok(sealed)
+    })
+    .await
+    .map_err(|e| format!("Encryption task failed: {}", e))?
+}
+
+/// Decrypt a downloaded `ciphertext` file into `destination`. A partial
+/// destination is removed on failure.
+pub async fn open_file(
+    ciphertext: &Path,
+    destination: &Path,
+    envelope: &Envelope,
+    key: &ClientKey,
+) -> Result<(), String> {
+    let ciphertext = ciphertext.to_path_buf();
+    let destination = destination.to_path_buf();
+    let envelope = envelope.clone();
+    let key = key.clone();
+    tokio::task::spawn_blocking(move || {
+        let input = File::open(&ciphertext)
+            .map_err(|e| format!("Failed to open downloaded file: {}", e))?;
+        let ciphertext_size = input
+            .metadata()
+            .map_err(|e| format!("Failed to get file metadata: {}", e))?
+            .len();
+        let output =
+            File::create(&destination).map_err(|e| format!("Failed to create file: {}", e))?;
+        let result = open_stream(
+            &key,
+            &envelope,
+            ciphertext_size,
+            BufReader::new(input),
+            BufWriter::new(output),
+        );
+        if result.is_err() {
+            let _ = std::fs::remove_file(&destination);
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("Decryption task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_file() -> ClientKey {
+        ClientKey::KeyFile(vec![42u8; 64])
+    }
+
+    fn seal(key: &ClientKey, plaintext: &[u8], segment: usize) -> (Envelope, Vec<u8>) {
+        let mut ciphertext = Vec::new();
+        let envelope = seal_stream(
+            key,
+            plaintext.len() as u64,
+            segment,
+            plaintext,
+            &mut ciphertext,
+        )
+        .unwrap();
+        (envelope, ciphertext)
+    }
+
+    fn open(key: &ClientKey, envelope: &Envelope, ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+        let mut plaintext = Vec::new();
+        open_stream(
+            key,
+            envelope,
+            ciphertext.len() as u64,
+            ciphertext,
+            &mut plaintext,
+        )?;
+        Ok(plaintext)
+    }
+
+    #[test]
+    fn round_trips_across_segment_boundaries() {
+        let key = key_file();
+        for len in [0usize, 1, 31, 32, 33, 100] {
+            let plaintext: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let (envelope, ciphertext) = seal(&key, &plaintext, 32);
+            assert_eq!(open(&key, &envelope, &ciphertext).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn ciphertext_len_matches_sealed_output() {
+        let key = key_file();
+        for len in [0usize, 1, SEGMENT_SIZE, SEGMENT_SIZE + 1] {
+            let (_, ciphertext) = seal(&key, &vec![7u8; len], SEGMENT_SIZE);
+            assert_eq!(ciphertext.len() as u64, ciphertext_len(len as u64));
+        }
+    }
+
+    #[test]
+    fn passphrase_must_match() {
+        let key = ClientKey::Passphrase("correct horse".to_string());
+        let (envelope, ciphertext) = seal(&key, b"archive", 32);
+        assert_eq!(open(&key, &envelope, &ciphertext).unwrap(), b"archive");
+
+        let wrong = ClientKey::Passphrase("battery staple".to_string());
+        assert!(open(&wrong, &envelope, &ciphertext).is_err());
+        assert!(open(&key_file(), &envelope, &ciphertext).is_err());
+    }
+
+    #[test]
+    fn detects_tampering_and_truncation() {
+        let key = key_file();
+        let (envelope, ciphertext) = seal(&key, &[1u8; 80], 32);
+
+        let mut tampered = ciphertext.clone();
+        tampered[5] ^= 1;
+        assert!(open(&key, &envelope, &tampered).is_err());
+
+        // Dropping the final segment leaves a valid-looking but non-final tail.
+        let truncated = &ciphertext[..2 * (32 + TAG_SIZE)];
+        assert!(open(&key, &envelope, truncated).is_err());
+    }
+
+    #[test]
+    fn envelope_survives_metadata_round_trip() {
+        let (envelope, _) = seal(&key_file(), b"data", 32);
+        let parsed = Envelope::from_metadata(&envelope.to_metadata())
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed, envelope);
+        assert_eq!(Envelope::from_metadata(&HashMap::new()).unwrap(), None);
+    }
+}
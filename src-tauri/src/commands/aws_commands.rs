@@ -1,12 +1,22 @@
+use crate::client_encryption::ciphertext_len;
+use crate::commands::batch_delete::{
+    delete_progress_emitter, finish_batch_delete, skip_locked_keys, BatchDeleteResult,
+};
 use crate::commands::batch_move::{
-    fallback_batch_id, run_batch_move, BatchMoveResult, MoveOperation,
+    copy_progress_emitter, fallback_batch_id, run_batch_move, BatchMoveResult, MoveOperation,
 };
+use crate::commands::bucket_encryption::resolve_bucket_encryption;
 use crate::commands::cache_events::{get_unique_parent_paths, CacheUpdatedEvent};
-use crate::commands::delete_cache::{update_cache_after_batch_delete, update_cache_after_delete};
+use crate::commands::client_encryption::resolve_upload_encryption;
+use crate::commands::delete_cache::update_cache_after_delete;
+use crate::commands::delta_sync::{finish_delta_sync, SyncChangeSummary, SyncMode};
 use crate::commands::move_cache::{update_cache_after_batch_move, update_cache_after_move};
+use crate::commands::shared_links::{record_share, ShareOptions, ShareTarget};
 use crate::commands::upload_cache::update_cache_after_upload;
-use crate::db::{self, CachedFile};
+use crate::db::{self, CacheScope, CachedFile, CachedObjectMeta};
 use crate::providers::aws;
+use crate::providers::config::ProviderConfig;
+use crate::providers::storage_class::parse_storage_class;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use tauri::Emitter;
@@ -21,6 +31,20 @@ pub struct AwsConfigInput {
     pub endpoint_scheme: Option<String>,
     pub endpoint_host: Option<String>,
     pub force_path_style: bool,
+    #[serde(default)]
+    pub credentials: aws::CredentialSource,
+}
+
+impl AwsConfigInput {
+    pub(crate) fn cache_scope(&self) -> CacheScope {
+        CacheScope::new(
+            "aws",
+            self.endpoint_scheme.as_deref(),
+            self.endpoint_host.as_deref(),
+            &self.account_id,
+            &self.bucket,
+        )
+    }
 }
 
 impl From<AwsConfigInput> for aws::AwsConfig {
@@ -33,6 +57,9 @@ impl From<AwsConfigInput> for aws::AwsConfig {
             endpoint_scheme: input.endpoint_scheme,
             endpoint_host: input.endpoint_host,
             force_path_style: input.force_path_style,
+            credentials: input.credentials,
+            encryption: None,
+            storage_class: None,
         }
     }
 }
@@ -50,6 +77,8 @@ pub struct ListObjectsInput {
 pub struct SyncResult {
     pub count: i32,
     pub timestamp: i64,
+    /// What changed, for syncs applied as a delta.
+    pub changes: Option<SyncChangeSummary>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -73,6 +102,7 @@ pub async fn list_aws_buckets(
     endpoint_scheme: Option<String>,
     endpoint_host: Option<String>,
     force_path_style: bool,
+    credentials: Option<aws::CredentialSource>,
 ) -> Result<Vec<aws::AwsBucket>, String> {
     let config = aws::AwsConfig {
         bucket: String::new(),
@@ -82,6 +112,9 @@ pub async fn list_aws_buckets(
         endpoint_scheme,
         endpoint_host,
         force_path_style,
+        credentials: credentials.unwrap_or_default(),
+        encryption: None,
+        storage_class: None,
     };
 
     aws::list_buckets(&config)
@@ -128,29 +161,31 @@ pub async fn list_all_aws_objects(
 #[tauri::command]
 pub async fn sync_aws_bucket(
     config: AwsConfigInput,
+    mode: Option<SyncMode>,
     app: tauri::AppHandle,
 ) -> Result<SyncResult, String> {
     let account_id = config.account_id.clone();
+    let scope = config.cache_scope();
     let aws_config: aws::AwsConfig = config.into();
     let bucket = aws_config.bucket.clone();
     let now = chrono::Utc::now().timestamp();
+    let delta = mode.unwrap_or_default().is_delta(&scope).await?;
 
     let _ = app.emit("sync-phase", "fetching");
 
-    db::begin_sync(&bucket, &account_id)
+    db::begin_sync(&scope)
         .await
         .map_err(|e| format!("Failed to clear cache: {}", e))?;
 
     // Spawn dedicated store task
     let (tx, mut rx) = tokio::sync::mpsc::channel::<Vec<CachedFile>>(8);
-    let store_bucket = bucket.clone();
-    let store_account_id = account_id.clone();
+    let store_scope = scope.clone();
     let store_app = app.clone();
     let store_handle = tokio::spawn(async move {
         let mut stored_count: usize = 0;
         while let Some(batch) = rx.recv().await {
             let batch_len = batch.len();
-            db::store_file_batch(&store_bucket, &store_account_id, &batch)
+            db::store_file_batch(&store_scope, &batch)
                 .await
                 .map_err(|e| format!("Failed to store files: {}", e))?;
             stored_count += batch_len;
@@ -204,6 +239,10 @@ pub async fn sync_aws_bucket(
                             .map(|dt| dt.to_string())
                             .unwrap_or_default(),
                         synced_at: now,
+                        storage_class: obj.storage_class().map(|class| class.as_str().to_string()),
+                        etag: obj.e_tag().map(str::to_string),
+                        content_type: None,
+                        checksum: None,
                     });
                 }
             }
@@ -231,26 +270,34 @@ pub async fn sync_aws_bucket(
         .map_err(|e| format!("Store task panicked: {}", e))?
         .map_err(|e| format!("Store failed: {}", e))?;
 
-    db::finish_sync(&bucket, &account_id, stored_count)
-        .await
-        .map_err(|e| format!("Failed to update sync metadata: {}", e))?;
+    let changes = if delta {
+        let _ = app.emit("sync-phase", "indexing");
+        Some(finish_delta_sync(&app, &scope, stored_count, &folder_keys).await?)
+    } else {
+        db::finish_sync(&scope, stored_count)
+            .await
+            .map_err(|e| format!("Failed to update sync metadata: {}", e))?;
 
-    let _ = app.emit("sync-phase", "indexing");
+        // Build directory tree from DB
+        let _ = app.emit("sync-phase", "indexing");
 
-    let app_clone = app.clone();
-    let indexing_callback = move |current: usize, total: usize| {
-        let _ = app_clone.emit("indexing-progress", IndexingProgress { current, total });
-    };
+        let app_clone = app.clone();
+        let indexing_callback = move |current: usize, total: usize| {
+            let _ = app_clone.emit("indexing-progress", IndexingProgress { current, total });
+        };
 
-    db::build_directory_tree_from_db(&bucket, &account_id, &folder_keys, Some(indexing_callback))
-        .await
-        .map_err(|e| format!("Failed to build directory tree: {}", e))?;
+        db::build_directory_tree_from_db(&scope, &folder_keys, Some(indexing_callback))
+            .await
+            .map_err(|e| format!("Failed to build directory tree: {}", e))?;
+        None
+    };
 
     let _ = app.emit("sync-phase", "complete");
 
     Ok(SyncResult {
         count: stored_count as i32,
         timestamp: now,
+        changes,
     })
 }
 
@@ -284,8 +331,7 @@ pub async fn delete_aws_object(
     key: String,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
-    let bucket = config.bucket.clone();
-    let account_id = config.account_id.clone();
+    let scope = config.cache_scope();
     let aws_config: aws::AwsConfig = config.into();
 
     aws::delete_object(&aws_config, &key)
@@ -293,90 +339,30 @@ pub async fn delete_aws_object(
         .map_err(|e| format!("Failed to delete object: {}", e))?;
 
     // Update cache and emit events (including paths-removed if any folders became empty)
-    update_cache_after_delete(&app, &bucket, &account_id, &key).await?;
+    update_cache_after_delete(&app, &scope, &key).await?;
 
     Ok(())
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub struct BatchDeleteProgress {
-    pub completed: usize,
-    pub total: usize,
-    pub failed: usize,
-}
-
-#[derive(Debug, Clone, Serialize)]
-pub struct BatchDeleteResult {
-    pub deleted: usize,
-    pub failed: usize,
-    pub errors: Vec<String>,
-}
-
 #[tauri::command]
 pub async fn batch_delete_aws_objects(
     config: AwsConfigInput,
     keys: Vec<String>,
     app: tauri::AppHandle,
 ) -> Result<BatchDeleteResult, String> {
-    let bucket = config.bucket.clone();
-    let account_id = config.account_id.clone();
+    let scope = config.cache_scope();
     let aws_config: aws::AwsConfig = config.into();
-    let total = keys.len();
-
-    if total == 0 {
-        return Ok(BatchDeleteResult {
-            deleted: 0,
-            failed: 0,
-            errors: vec![],
-        });
-    }
-
-    let mut completed = 0;
-    let mut failed = 0;
-    let mut errors: Vec<String> = vec![];
-    let mut deleted_keys: Vec<String> = vec![];
-
-    const BATCH_SIZE: usize = 1000;
-
-    for chunk in keys.chunks(BATCH_SIZE) {
-        let batch_keys: Vec<String> = chunk.to_vec();
-        let batch_count = batch_keys.len();
-
-        match aws::delete_objects(&aws_config, batch_keys.clone()).await {
-            Ok(_) => {
-                completed += batch_count;
-                deleted_keys.extend(batch_keys);
-            }
-            Err(e) => {
-                failed += batch_count;
-                errors.push(format!("Batch delete failed: {}", e));
-            }
-        }
-
-        let _ = app.emit(
-            "batch-delete-progress",
-            BatchDeleteProgress {
-                completed,
-                total,
-                failed,
-            },
-        );
+    if keys.is_empty() {
+        return Ok(BatchDeleteResult::empty());
     }
 
-    // Update cache and emit events (including paths-removed if any folders became empty)
-    if !deleted_keys.is_empty() {
-        if let Err(e) =
-            update_cache_after_batch_delete(&app, &bucket, &account_id, &deleted_keys).await
-        {
-            errors.push(e);
-        }
-    }
+    let (keys, skipped) = skip_locked_keys(ProviderConfig::Aws(aws_config.clone()), keys).await?;
+    let on_progress = delete_progress_emitter(&app, keys.len());
+    let outcome = aws::delete_objects(&aws_config, keys, Some(on_progress))
+        .await
+        .map_err(|e| format!("Batch delete failed: {}", e))?;
 
-    Ok(BatchDeleteResult {
-        deleted: completed,
-        failed,
-        errors,
-    })
+    Ok(finish_batch_delete(&app, &scope, outcome, skipped).await)
 }
 
 #[tauri::command]
@@ -388,14 +374,21 @@ pub async fn rename_aws_object(
 ) -> Result<(), String> {
     let bucket = config.bucket.clone();
     let account_id = config.account_id.clone();
-    let aws_config: aws::AwsConfig = config.into();
-
-    aws::rename_object(&aws_config, &old_key, &new_key)
-        .await
-        .map_err(|e| format!("Failed to rename object: {}", e))?;
+    let scope = config.cache_scope();
+    let mut aws_config: aws::AwsConfig = config.into();
+    aws_config.encryption = resolve_bucket_encryption("aws", &account_id, &bucket).await?;
+
+    aws::rename_object(
+        &aws_config,
+        &old_key,
+        &new_key,
+        Some(copy_progress_emitter(&app, &old_key)),
+    )
+    .await
+    .map_err(|e| format!("Failed to rename object: {}", e))?;
 
     // Update cache and emit events (including paths-created/removed)
-    update_cache_after_move(&app, &bucket, &account_id, &old_key, &new_key).await?;
+    update_cache_after_move(&app, &scope, &old_key, &new_key).await?;
 
     Ok(())
 }
@@ -405,17 +398,31 @@ pub async fn batch_move_aws_objects(
     config: AwsConfigInput,
     operations: Vec<MoveOperation>,
     batch_id: Option<String>,
+    storage_class: Option<String>,
     app: tauri::AppHandle,
 ) -> Result<BatchMoveResult, String> {
     let bucket = config.bucket.clone();
     let account_id = config.account_id.clone();
-    let aws_config: aws::AwsConfig = config.into();
+    let scope = config.cache_scope();
+    let mut aws_config: aws::AwsConfig = config.into();
+    aws_config.encryption = resolve_bucket_encryption("aws", &account_id, &bucket).await?;
+    // Without a class the moved objects keep their current one
+    aws_config.storage_class = storage_class
+        .as_deref()
+        .map(parse_storage_class)
+        .transpose()?;
+    let chosen_class = aws_config
+        .storage_class
+        .as_ref()
+        .map(|class| class.as_str().to_string());
     let batch_id = batch_id.unwrap_or_else(fallback_batch_id);
 
+    let progress_app = app.clone();
     let rename = move |op: MoveOperation| {
         let config = aws_config.clone();
+        let on_progress = copy_progress_emitter(&progress_app, &op.old_key);
         async move {
-            aws::rename_object(&config, &op.old_key, &op.new_key)
+            aws::rename_object(&config, &op.old_key, &op.new_key, Some(on_progress))
                 .await
                 .map_err(|e| e.to_string())
         }
@@ -425,11 +432,16 @@ pub async fn batch_move_aws_objects(
 
     let mut errors = outcome.errors;
     if !outcome.successful.is_empty() {
-        if let Err(e) =
-            update_cache_after_batch_move(&app, &bucket, &account_id, &outcome.successful).await
-        {
+        if let Err(e) = update_cache_after_batch_move(&app, &scope, &outcome.successful).await {
             errors.push(e);
         }
+        if let Some(class) = chosen_class.as_deref() {
+            for (_, new_key) in &outcome.successful {
+                if let Err(e) = db::set_cached_storage_class(&scope, new_key, class).await {
+                    log::warn!("Failed to record storage class: {}", e);
+                }
+            }
+        }
     }
 
     Ok(BatchMoveResult {
@@ -444,13 +456,35 @@ pub async fn generate_aws_signed_url(
     config: AwsConfigInput,
     key: String,
     expires_in: Option<u64>,
+    share: Option<ShareOptions>,
 ) -> Result<String, String> {
-    let aws_config: aws::AwsConfig = config.into();
+    let account_id = config.account_id.clone();
+    let access_key_id = config.access_key_id.clone();
+    let mut aws_config: aws::AwsConfig = config.into();
+    aws_config.encryption =
+        resolve_bucket_encryption("aws", &account_id, &aws_config.bucket).await?;
     let expires_in_secs = expires_in.unwrap_or(3600);
 
-    aws::generate_presigned_url(&aws_config, &key, expires_in_secs)
+    let url = aws::generate_presigned_url(&aws_config, &key, expires_in_secs)
         .await
-        .map_err(|e| format!("Failed to generate signed URL: {}", e))
+        .map_err(|e| format!("Failed to generate signed URL: {}", e))?;
+
+    record_share(
+        share,
+        ShareTarget {
+            provider: "aws",
+            account_id: &account_id,
+            bucket: &aws_config.bucket,
+            signing_key_id: &access_key_id,
+        },
+        &key,
+        "GET",
+        &url,
+        expires_in_secs,
+    )
+    .await;
+
+    Ok(url)
 }
 
 #[tauri::command]
@@ -463,7 +497,9 @@ pub async fn upload_aws_content(
 ) -> Result<String, String> {
     let bucket = config.bucket.clone();
     let account_id = config.account_id.clone();
-    let aws_config: aws::AwsConfig = config.into();
+    let scope = config.cache_scope();
+    let mut aws_config: aws::AwsConfig = config.into();
+    aws_config.encryption = resolve_bucket_encryption("aws", &account_id, &bucket).await?;
 
     let content_bytes = content.into_bytes();
     let new_size = content_bytes.len() as i64;
@@ -474,21 +510,21 @@ pub async fn upload_aws_content(
 
     let last_modified = chrono::Utc::now().to_rfc3339();
 
-    let (size_delta, is_new_file) =
-        db::update_cached_file(&bucket, &account_id, &key, new_size, &last_modified)
-            .await
-            .map_err(|e| format!("Failed to update file cache: {}", e))?;
+    let (size_delta, is_new_file) = db::update_cached_file(&scope, &key, new_size, &last_modified)
+        .await
+        .map_err(|e| format!("Failed to update file cache: {}", e))?;
+    let meta = CachedObjectMeta {
+        etag: (!etag.is_empty()).then(|| etag.clone()),
+        content_type,
+        ..Default::default()
+    };
+    db::set_cached_object_meta(&scope, &key, &meta)
+        .await
+        .map_err(|e| format!("Failed to update file cache: {}", e))?;
 
-    db::update_directory_tree_for_file(
-        &bucket,
-        &account_id,
-        &key,
-        size_delta,
-        &last_modified,
-        is_new_file,
-    )
-    .await
-    .map_err(|e| format!("Failed to update directory tree: {}", e))?;
+    db::update_directory_tree_for_file(&scope, &key, size_delta, &last_modified, is_new_file)
+        .await
+        .map_err(|e| format!("Failed to update directory tree: {}", e))?;
 
     let _ = app.emit(
         "cache-updated",
@@ -533,8 +569,25 @@ pub async fn upload_aws_file(
     endpoint_scheme: Option<String>,
     endpoint_host: Option<String>,
     force_path_style: bool,
+    storage_class: Option<String>,
+    credentials: Option<aws::CredentialSource>,
 ) -> Result<UploadResult, String> {
-    let config = aws::AwsConfig {
+    let storage_class = match storage_class
+        .as_deref()
+        .map(parse_storage_class)
+        .transpose()
+    {
+        Ok(storage_class) => storage_class,
+        Err(e) => {
+            return Ok(UploadResult {
+                task_id,
+                success: false,
+                error: Some(e),
+                upload_id: None,
+            })
+        }
+    };
+    let mut config = aws::AwsConfig {
         bucket,
         access_key_id,
         secret_access_key,
@@ -542,7 +595,23 @@ pub async fn upload_aws_file(
         endpoint_scheme,
         endpoint_host,
         force_path_style,
+        credentials: credentials.unwrap_or_default(),
+        encryption: None,
+        storage_class,
     };
+    let (encryption, client_key) =
+        match resolve_upload_encryption("aws", &account_id, &config.bucket).await {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                return Ok(UploadResult {
+                    task_id,
+                    success: false,
+                    error: Some(e),
+                    upload_id: None,
+                })
+            }
+        };
+    config.encryption = encryption;
 
     let path = PathBuf::from(&file_path);
     if !path.exists() {
@@ -584,6 +653,7 @@ pub async fn upload_aws_file(
         &key,
         &path,
         content_type.as_deref(),
+        client_key.as_ref(),
         Some(progress_callback),
     )
     .await;
@@ -600,14 +670,36 @@ pub async fn upload_aws_file(
                 },
             );
 
+            // Client-side encrypted objects are stored larger than the source file
+            let stored_size = match client_key {
+                Some(_) => ciphertext_len(file_size),
+                None => file_size,
+            };
             let last_modified = chrono::Utc::now().to_rfc3339();
+            let scope = CacheScope::new(
+                "aws",
+                config.endpoint_scheme.as_deref(),
+                config.endpoint_host.as_deref(),
+                &account_id,
+                &config.bucket,
+            );
+            // Without a class S3 stores the object as STANDARD
+            let stored_class = config
+                .storage_class
+                .as_ref()
+                .map_or("STANDARD", |class| class.as_str());
+            let meta = CachedObjectMeta {
+                storage_class: Some(stored_class.to_string()),
+                content_type,
+                ..Default::default()
+            };
             if let Err(err) = update_cache_after_upload(
                 &app,
-                &config.bucket,
-                &account_id,
+                &scope,
                 &key,
-                file_size as i64,
+                stored_size as i64,
                 &last_modified,
+                &meta,
             )
             .await
             {
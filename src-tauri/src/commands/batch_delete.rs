@@ -0,0 +1,106 @@
+//! Shared batch delete result/progress types used by all provider command modules.
+//!
+//! Providers delete in chunks of 1000 and report failures per key; this turns
+//! the outcome into the command result and updates the cache only for keys
+//! the server actually removed. Keys under Object Lock are held back before
+//! the request and reported as skipped rather than failed.
+
+use crate::commands::delete_cache::update_cache_after_batch_delete;
+use crate::db::CacheScope;
+use crate::providers::batch_delete::{DeleteFailure, DeleteObjectsOutcome, DeleteProgressFn};
+use crate::providers::config::ProviderConfig;
+use crate::providers::object_lock::{self, SkippedObject};
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::Emitter;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchDeleteProgress {
+    pub completed: usize,
+    pub total: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchDeleteResult {
+    pub deleted: usize,
+    pub failed: usize,
+    pub errors: Vec<String>,
+    pub deleted_keys: Vec<String>,
+    pub failed_keys: Vec<DeleteFailure>,
+    pub skipped: usize,
+    pub skipped_keys: Vec<SkippedObject>,
+}
+
+impl BatchDeleteResult {
+    pub(crate) fn empty() -> Self {
+        BatchDeleteResult {
+            deleted: 0,
+            failed: 0,
+            errors: vec![],
+            deleted_keys: vec![],
+            failed_keys: vec![],
+            skipped: 0,
+            skipped_keys: vec![],
+        }
+    }
+}
+
+/// Progress callback emitting `batch-delete-progress` for a batch of `total` keys.
+pub(crate) fn delete_progress_emitter(app: &tauri::AppHandle, total: usize) -> DeleteProgressFn {
+    let app = app.clone();
+    Arc::new(move |completed, failed| {
+        let _ = app.emit(
+            "batch-delete-progress",
+            BatchDeleteProgress {
+                completed,
+                total,
+                failed,
+            },
+        );
+    })
+}
+
+/// Split off keys Object Lock would refuse to delete.
+pub(crate) async fn skip_locked_keys(
+    config: ProviderConfig,
+    keys: Vec<String>,
+) -> Result<(Vec<String>, Vec<SkippedObject>), String> {
+    object_lock::partition_locked(&config, keys)
+        .await
+        .map_err(|e| format!("Failed to check object locks: {}", e))
+}
+
+/// Build the command result and update the cache for the deleted keys only.
+pub(crate) async fn finish_batch_delete(
+    app: &tauri::AppHandle,
+    scope: &CacheScope,
+    outcome: DeleteObjectsOutcome,
+    skipped: Vec<SkippedObject>,
+) -> BatchDeleteResult {
+    let mut errors: Vec<String> = outcome
+        .failed
+        .iter()
+        .map(|f| match &f.code {
+            Some(code) => format!("{}: {} ({})", f.key, f.message, code),
+            None => format!("{}: {}", f.key, f.message),
+        })
+        .collect();
+
+    // Update cache and emit events (including paths-removed if any folders became empty)
+    if !outcome.deleted.is_empty() {
+        if let Err(e) = update_cache_after_batch_delete(app, scope, &outcome.deleted).await {
+            errors.push(e);
+        }
+    }
+
+    BatchDeleteResult {
+        deleted: outcome.deleted.len(),
+        failed: outcome.failed.len(),
+        errors,
+        deleted_keys: outcome.deleted,
+        failed_keys: outcome.failed,
+        skipped: skipped.len(),
+        skipped_keys: skipped,
+    }
+}
@@ -2,13 +2,16 @@
 //!
 //! Runs rename (copy + delete) operations with adaptive parallelism and emits
 //! throttled, enriched `batch-move-progress` events consumed by the transfer
-//! panel and the folder rename modal.
+//! panel and the folder rename modal. Copies of large objects (multipart
+//! UploadPartCopy) additionally report byte progress via `copy-progress`.
 
+use crate::providers::multipart_copy::CopyProgressFn;
+use crate::transfer_progress::ThrottleGate;
 use serde::{Deserialize, Serialize};
 use std::future::Future;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tauri::Emitter;
 use tokio::sync::Semaphore;
 
@@ -39,9 +42,36 @@ pub struct BatchMoveProgress {
     pub done: bool,
 }
 
+/// Byte progress of a single large server-side copy.
+#[derive(Debug, Clone, Serialize)]
+pub struct CopyProgress {
+    pub key: String,
+    pub copied_bytes: u64,
+    pub total_bytes: u64,
+}
+
 /// Minimum interval between progress event emissions per batch.
 const EMIT_INTERVAL_MS: u64 = 100;
 
+/// Progress callback emitting throttled `copy-progress` events for `key`.
+pub(crate) fn copy_progress_emitter(app: &tauri::AppHandle, key: &str) -> CopyProgressFn {
+    let app = app.clone();
+    let key = key.to_string();
+    let gate = ThrottleGate::new(Duration::from_millis(EMIT_INTERVAL_MS));
+    Arc::new(move |copied_bytes, total_bytes| {
+        if copied_bytes >= total_bytes || gate.try_pass() {
+            let _ = app.emit(
+                "copy-progress",
+                CopyProgress {
+                    key: key.clone(),
+                    copied_bytes,
+                    total_bytes,
+                },
+            );
+        }
+    })
+}
+
 /// Scale rename parallelism with batch size: small batches stay gentle,
 /// large folder renames fan out wider (server-side copies are cheap).
 pub(crate) fn batch_concurrency(total: usize) -> usize {
@@ -65,23 +95,33 @@ pub(crate) struct BatchMoveOutcome {
     pub successful: Vec<(String, String)>,
 }
 
-struct BatchProgressTracker {
+/// Throttled `batch-move-progress` emitter. The total can grow while work is
+/// running, for operations that list objects as they go.
+pub(crate) struct BatchProgressTracker {
     batch_id: String,
-    total: usize,
+    total: AtomicUsize,
     started: Instant,
     last_emit_ms: AtomicU64,
 }
 
 impl BatchProgressTracker {
-    fn new(batch_id: String, total: usize) -> Self {
+    pub(crate) fn new(batch_id: String, total: usize) -> Self {
         Self {
             batch_id,
-            total,
+            total: AtomicUsize::new(total),
             started: Instant::now(),
             last_emit_ms: AtomicU64::new(0),
         }
     }
 
+    pub(crate) fn total(&self) -> usize {
+        self.total.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn add_total(&self, count: usize) {
+        self.total.fetch_add(count, Ordering::SeqCst);
+    }
+
     fn payload(
         &self,
         processed: usize,
@@ -95,7 +135,8 @@ impl BatchProgressTracker {
         } else {
             0.0
         };
-        let remaining = self.total.saturating_sub(processed);
+        let total = self.total();
+        let remaining = total.saturating_sub(processed);
         let eta_ms = if ops_per_sec > 0.0 {
             ((remaining as f64 / ops_per_sec) * 1000.0) as u64
         } else {
@@ -104,7 +145,7 @@ impl BatchProgressTracker {
         BatchMoveProgress {
             batch_id: self.batch_id.clone(),
             completed: processed,
-            total: self.total,
+            total,
             failed,
             current_key: current_key.to_string(),
             ops_per_sec,
@@ -114,7 +155,13 @@ impl BatchProgressTracker {
     }
 
     /// Emit at most once per EMIT_INTERVAL_MS. Final (`done`) events bypass the throttle.
-    fn emit(&self, app: &tauri::AppHandle, processed: usize, failed: usize, current_key: &str) {
+    pub(crate) fn emit(
+        &self,
+        app: &tauri::AppHandle,
+        processed: usize,
+        failed: usize,
+        current_key: &str,
+    ) {
         let now_ms = self.started.elapsed().as_millis() as u64;
         let last = self.last_emit_ms.load(Ordering::Relaxed);
         if now_ms.saturating_sub(last) < EMIT_INTERVAL_MS {
@@ -133,7 +180,7 @@ impl BatchProgressTracker {
         );
     }
 
-    fn emit_final(&self, app: &tauri::AppHandle, processed: usize, failed: usize) {
+    pub(crate) fn emit_final(&self, app: &tauri::AppHandle, processed: usize, failed: usize) {
         let _ = app.emit(
             "batch-move-progress",
             self.payload(processed, failed, "", true),
@@ -205,7 +252,7 @@ where
             }
 
             let processed = succeeded.load(Ordering::SeqCst) + failed.load(Ordering::SeqCst);
-            if processed < tracker.total {
+            if processed < tracker.total() {
                 tracker.emit(&app, processed, failed.load(Ordering::SeqCst), &key);
             }
         }));
@@ -0,0 +1,108 @@
+//! Bucket administration commands: create, delete (optionally emptying first) and CORS.
+
+use crate::commands::batch_delete::BatchDeleteProgress;
+use crate::commands::provider_config::{build_provider_config, ProviderConfigInput};
+use crate::db;
+use crate::providers::buckets;
+use crate::providers::cors::{self, BucketCorsRule};
+use serde::Serialize;
+use tauri::Emitter;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeleteBucketResult {
+    /// Objects removed while emptying the bucket.
+    pub objects_deleted: usize,
+    pub errors: Vec<String>,
+}
+
+/// Create the bucket in `config.bucket`. `location` is the region / location
+/// constraint (AWS, MinIO) or location hint (R2).
+#[tauri::command]
+pub async fn create_storage_bucket(
+    config: ProviderConfigInput,
+    location: Option<String>,
+) -> Result<(), String> {
+    let config = build_provider_config(&config)?;
+    buckets::create_bucket(&config, location.as_deref())
+        .await
+        .map_err(|e| format!("Failed to create bucket: {}", e))
+}
+
+/// Delete the bucket. With `empty_first`, every object is deleted through
+/// batched DeleteObjects (emitting `batch-delete-progress`) before the bucket
+/// itself is removed. The local file cache for the bucket is cleared.
+#[tauri::command]
+pub async fn delete_storage_bucket(
+    config: ProviderConfigInput,
+    empty_first: bool,
+    app: tauri::AppHandle,
+) -> Result<DeleteBucketResult, String> {
+    let scope = config.cache_scope();
+    let config = build_provider_config(&config)?;
+
+    let mut objects_deleted = 0;
+    let mut errors = Vec::new();
+
+    if empty_first {
+        let progress_app = app.clone();
+        let outcome = buckets::empty_bucket(&config, move |deleted, failed, total| {
+            let _ = progress_app.emit(
+                "batch-delete-progress",
+                BatchDeleteProgress {
+                    completed: deleted,
+                    total,
+                    failed,
+                },
+            );
+        })
+        .await
+        .map_err(|e| format!("Failed to empty bucket: {}", e))?;
+
+        objects_deleted = outcome.deleted.len();
+        if !outcome.failed.is_empty() {
+            errors.extend(
+                outcome
+                    .failed
+                    .iter()
+                    .map(|f| format!("{}: {}", f.key, f.message)),
+            );
+            return Ok(DeleteBucketResult {
+                objects_deleted,
+                errors,
+            });
+        }
+    }
+
+    if let Err(e) = buckets::delete_bucket(&config).await {
+        return Err(format!("Failed to delete bucket: {}", e));
+    }
+
+    if let Err(e) = db::clear_file_cache(&scope).await {
+        errors.push(format!("Failed to clear file cache: {}", e));
+    }
+
+    Ok(DeleteBucketResult {
+        objects_deleted,
+        errors,
+    })
+}
+
+#[tauri::command]
+pub async fn get_bucket_cors(config: ProviderConfigInput) -> Result<Vec<BucketCorsRule>, String> {
+    let config = build_provider_config(&config)?;
+    cors::get_bucket_cors(&config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Replace all CORS rules on the bucket. An empty list removes the configuration.
+#[tauri::command]
+pub async fn put_bucket_cors(
+    config: ProviderConfigInput,
+    rules: Vec<BucketCorsRule>,
+) -> Result<(), String> {
+    let config = build_provider_config(&config)?;
+    cors::put_bucket_cors(&config, &rules)
+        .await
+        .map_err(|e| e.to_string())
+}
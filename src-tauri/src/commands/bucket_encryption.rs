@@ -0,0 +1,295 @@
+//! Per-bucket default encryption (SSE-S3, SSE-KMS, SSE-C).
+//!
+//! Settings are stored per (provider, account, bucket) and the SSE-C key is
+//! kept in the OS keychain. `resolve_bucket_encryption` turns them into the
+//! `ObjectEncryption` that the upload, copy, presign and download paths apply.
+
+use crate::db::{self, BucketEncryptionRow};
+use crate::providers::encryption::{
+    supported_modes, CustomerKey, EncryptionMode, ObjectEncryption,
+};
+use crate::secrets;
+use serde::{Deserialize, Serialize};
+
+const SSE_C_SECRET_KIND: &str = "sse-c";
+const PROVIDERS: &[&str] = &["r2", "aws", "minio", "rustfs"];
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BucketEncryptionInput {
+    pub mode: EncryptionMode,
+    /// SSE-KMS key ID or alias; the bucket's default KMS key when omitted.
+    pub kms_key_id: Option<String>,
+    #[serde(default)]
+    pub bucket_key_enabled: bool,
+    /// Base64 SSE-C key to import. Without it, the stored key is kept or a
+    /// new one is generated.
+    pub customer_key: Option<String>,
+    /// Allow `customer_key` to overwrite a different stored key. Objects
+    /// written with the old key become unreadable without a backup of it.
+    #[serde(default)]
+    pub replace_customer_key: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BucketEncryptionSettings {
+    pub provider: String,
+    pub account_id: String,
+    pub bucket: String,
+    pub mode: EncryptionMode,
+    pub kms_key_id: Option<String>,
+    pub bucket_key_enabled: bool,
+    /// MD5 of the stored SSE-C key, to tell keys apart without revealing them.
+    pub customer_key_md5: Option<String>,
+    pub supported_modes: Vec<EncryptionMode>,
+    pub updated_at: Option<i64>,
+}
+
+fn validate_provider(provider: &str) -> Result<(), String> {
+    if PROVIDERS.contains(&provider) {
+        Ok(())
+    } else {
+        Err(format!("Unsupported provider: {}", provider))
+    }
+}
+
+fn validate_input(provider: &str, input: &BucketEncryptionInput) -> Result<(), String> {
+    if !supported_modes(provider).contains(&input.mode) {
+        return Err(format!(
+            "{} is not available for {} buckets",
+            input.mode.as_str(),
+            provider
+        ));
+    }
+    if input.mode != EncryptionMode::SseKms
+        && input
+            .kms_key_id
+            .as_deref()
+            .is_some_and(|id| !id.trim().is_empty())
+    {
+        return Err("A KMS key ID only applies to sse-kms".to_string());
+    }
+    if input.mode != EncryptionMode::SseC && input.customer_key.is_some() {
+        return Err("A customer key only applies to sse-c".to_string());
+    }
+    Ok(())
+}
+
+async fn load_customer_key(secret_ref: &str) -> Result<Option<CustomerKey>, String> {
+    match secrets::load_secret(secret_ref).await? {
+        Some(encoded) => CustomerKey::from_base64(&encoded).map(Some),
+        None => Ok(None),
+    }
+}
+
+async fn settings_from_row(
+    provider: &str,
+    account_id: &str,
+    bucket: &str,
+    row: Option<BucketEncryptionRow>,
+) -> Result<BucketEncryptionSettings, String> {
+    let supported = supported_modes(provider).to_vec();
+    let Some(row) = row else {
+        return Ok(BucketEncryptionSettings {
+            provider: provider.to_string(),
+            account_id: account_id.to_string(),
+            bucket: bucket.to_string(),
+            mode: EncryptionMode::None,
+            kms_key_id: None,
+            bucket_key_enabled: false,
+            customer_key_md5: None,
+            supported_modes: supported,
+            updated_at: None,
+        });
+    };
+
+    let customer_key_md5 = match row.customer_key_ref.as_deref() {
+        Some(secret_ref) => load_customer_key(secret_ref)
+            .await?
+            .map(|key| key.key_md5().to_string()),
+        None => None,
+    };
+
+    Ok(BucketEncryptionSettings {
+        mode: EncryptionMode::parse(&row.mode)?,
+        provider: row.provider,
+        account_id: row.account_id,
+        bucket: row.bucket,
+        kms_key_id: row.kms_key_id,
+        bucket_key_enabled: row.bucket_key_enabled,
+        customer_key_md5,
+        supported_modes: supported,
+        updated_at: Some(row.updated_at),
+    })
+}
+
+/// Encryption to apply to requests against a bucket, or `None` when the
+/// bucket has no settings. Fails if SSE-C is configured but the key is gone
+/// from the keychain, rather than silently writing unencrypted objects.
+pub(crate) async fn resolve_bucket_encryption(
+    provider: &str,
+    account_id: &str,
+    bucket: &str,
+) -> Result<Option<ObjectEncryption>, String> {
+    let Some(row) = db::get_bucket_encryption(provider, account_id, bucket)
+        .await
+        .map_err(|e| format!("Failed to load bucket encryption: {}", e))?
+    else {
+        return Ok(None);
+    };
+
+    match EncryptionMode::parse(&row.mode)? {
+        EncryptionMode::None => Ok(None),
+        EncryptionMode::SseS3 => Ok(Some(ObjectEncryption::S3)),
+        EncryptionMode::SseKms => Ok(Some(ObjectEncryption::Kms {
+            key_id: row.kms_key_id,
+            bucket_key_enabled: row.bucket_key_enabled,
+        })),
+        EncryptionMode::SseC => {
+            let key = match row.customer_key_ref.as_deref() {
+                Some(secret_ref) => load_customer_key(secret_ref).await?,
+                None => None,
+            };
+            key.map(|key| Some(ObjectEncryption::Customer(key)))
+                .ok_or_else(|| {
+                    format!(
+                        "SSE-C key for bucket '{}' is missing from the system keychain",
+                        bucket
+                    )
+                })
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_bucket_encryption_settings(
+    provider: String,
+    account_id: String,
+    bucket: String,
+) -> Result<BucketEncryptionSettings, String> {
+    validate_provider(&provider)?;
+    let row = db::get_bucket_encryption(&provider, &account_id, &bucket)
+        .await
+        .map_err(|e| format!("Failed to load bucket encryption: {}", e))?;
+    settings_from_row(&provider, &account_id, &bucket, row).await
+}
+
+/// Save a bucket's default encryption. A stored SSE-C key is kept when the
+/// mode changes so objects written with it stay readable after switching back.
+#[tauri::command]
+pub async fn set_bucket_encryption_settings(
+    provider: String,
+    account_id: String,
+    bucket: String,
+    settings: BucketEncryptionInput,
+) -> Result<BucketEncryptionSettings, String> {
+    validate_provider(&provider)?;
+    validate_input(&provider, &settings)?;
+
+    let existing = db::get_bucket_encryption(&provider, &account_id, &bucket)
+        .await
+        .map_err(|e| format!("Failed to load bucket encryption: {}", e))?;
+    let mut customer_key_ref = existing.and_then(|row| row.customer_key_ref);
+
+    if settings.mode == EncryptionMode::SseC {
+        let secret_ref = secrets::secret_ref(SSE_C_SECRET_KIND, &provider, &account_id, &bucket);
+        let stored = load_customer_key(&secret_ref).await?;
+        let key = match (settings.customer_key.as_deref(), stored) {
+            (Some(encoded), stored) => {
+                let key = CustomerKey::from_base64(encoded)?;
+                if stored
+                    .as_ref()
+                    .is_some_and(|old| old != &key && !settings.replace_customer_key)
+                {
+                    return Err(
+                        "A different SSE-C key is already stored for this bucket; objects written with it would become unreadable. Export it first and confirm the replacement."
+                            .to_string(),
+                    );
+                }
+                Some(key)
+            }
+            (None, Some(_)) => None,
+            (None, None) => Some(CustomerKey::generate()?),
+        };
+        if let Some(key) = key {
+            secrets::store_secret(&secret_ref, key.key_base64()).await?;
+        }
+        customer_key_ref = Some(secret_ref);
+    }
+
+    let kms_key_id = settings
+        .kms_key_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|id| !id.is_empty());
+    let row = db::save_bucket_encryption(
+        &provider,
+        &account_id,
+        &bucket,
+        settings.mode.as_str(),
+        kms_key_id,
+        settings.mode == EncryptionMode::SseKms && settings.bucket_key_enabled,
+        customer_key_ref.as_deref(),
+    )
+    .await
+    .map_err(|e| format!("Failed to save bucket encryption: {}", e))?;
+
+    settings_from_row(&provider, &account_id, &bucket, Some(row)).await
+}
+
+/// Return the bucket's SSE-C key (base64) so it can be backed up. Losing it
+/// makes every object written with it unreadable.
+#[tauri::command]
+pub async fn export_bucket_customer_key(
+    provider: String,
+    account_id: String,
+    bucket: String,
+) -> Result<String, String> {
+    validate_provider(&provider)?;
+    let secret_ref = db::get_bucket_encryption(&provider, &account_id, &bucket)
+        .await
+        .map_err(|e| format!("Failed to load bucket encryption: {}", e))?
+        .and_then(|row| row.customer_key_ref)
+        .ok_or_else(|| format!("No SSE-C key stored for bucket '{}'", bucket))?;
+    load_customer_key(&secret_ref)
+        .await?
+        .map(|key| key.key_base64().to_string())
+        .ok_or_else(|| {
+            format!(
+                "SSE-C key for bucket '{}' is missing from the system keychain",
+                bucket
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(mode: EncryptionMode) -> BucketEncryptionInput {
+        BucketEncryptionInput {
+            mode,
+            kms_key_id: None,
+            bucket_key_enabled: false,
+            customer_key: None,
+            replace_customer_key: false,
+        }
+    }
+
+    #[test]
+    fn r2_rejects_managed_encryption() {
+        assert!(validate_input("r2", &input(EncryptionMode::SseKms)).is_err());
+        assert!(validate_input("r2", &input(EncryptionMode::SseC)).is_ok());
+        assert!(validate_input("aws", &input(EncryptionMode::SseKms)).is_ok());
+    }
+
+    #[test]
+    fn key_options_must_match_mode() {
+        let mut kms_on_s3 = input(EncryptionMode::SseS3);
+        kms_on_s3.kms_key_id = Some("alias/media".to_string());
+        assert!(validate_input("aws", &kms_on_s3).is_err());
+
+        let mut customer_on_kms = input(EncryptionMode::SseKms);
+        customer_on_kms.customer_key = Some("a2V5".to_string());
+        assert!(validate_input("aws", &customer_on_kms).is_err());
+    }
+}
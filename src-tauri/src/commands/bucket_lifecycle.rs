@@ -0,0 +1,34 @@
+//! Bucket lifecycle rule commands.
+
+use crate::commands::provider_config::{build_provider_config, ProviderConfigInput};
+use crate::providers::lifecycle::{self, BucketLifecycleRule};
+
+#[tauri::command]
+pub async fn get_bucket_lifecycle(
+    config: ProviderConfigInput,
+) -> Result<Vec<BucketLifecycleRule>, String> {
+    let config = build_provider_config(&config)?;
+    lifecycle::get_bucket_lifecycle(&config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Replace all lifecycle rules on the bucket. An empty list removes the configuration.
+#[tauri::command]
+pub async fn put_bucket_lifecycle(
+    config: ProviderConfigInput,
+    rules: Vec<BucketLifecycleRule>,
+) -> Result<(), String> {
+    let config = build_provider_config(&config)?;
+    lifecycle::put_bucket_lifecycle(&config, &rules)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_bucket_lifecycle(config: ProviderConfigInput) -> Result<(), String> {
+    let config = build_provider_config(&config)?;
+    lifecycle::delete_bucket_lifecycle(&config)
+        .await
+        .map_err(|e| e.to_string())
+}
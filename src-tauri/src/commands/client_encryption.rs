@@ -0,0 +1,272 @@
+//! Per-bucket client-side encryption settings.
+//!
+//! The passphrase is kept in the OS keychain; key files stay where the user
+//! put them and are read whenever a transfer needs the key.
+//! `resolve_client_key` hands the upload and download paths the secret to
+//! wrap or unwrap per-object keys with.
+
+use crate::client_encryption::ClientKey;
+use crate::commands::bucket_encryption::resolve_bucket_encryption;
+use crate::db::{self, ClientEncryptionRow};
+use crate::providers::encryption::ObjectEncryption;
+use crate::secrets;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const PASSPHRASE_SECRET_KIND: &str = "cse-passphrase";
+const MIN_PASSPHRASE_LEN: usize = 12;
+const MIN_KEY_FILE_LEN: usize = 32;
+const GENERATED_KEY_FILE_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClientKeySource {
+    Passphrase,
+    KeyFile,
+}
+
+impl ClientKeySource {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ClientKeySource::Passphrase => "passphrase",
+            ClientKeySource::KeyFile => "key-file",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "passphrase" => Ok(ClientKeySource::Passphrase),
+            "key-file" => Ok(ClientKeySource::KeyFile),
+            other => Err(format!("Unknown client key source: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClientEncryptionInput {
+    /// `None` turns client-side encryption off for new uploads.
+    pub key_source: Option<ClientKeySource>,
+    /// Required when switching to a passphrase; omit to keep the stored one.
+    pub passphrase: Option<String>,
+    pub key_file_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientEncryptionSettings {
+    pub provider: String,
+    pub account_id: String,
+    pub bucket: String,
+    pub key_source: Option<ClientKeySource>,
+    pub key_file_path: Option<String>,
+    pub updated_at: Option<i64>,
+}
+
+fn settings_from_row(
+    provider: &str,
+    account_id: &str,
+    bucket: &str,
+    row: Option<ClientEncryptionRow>,
+) -> Result<ClientEncryptionSettings, String> {
+    Ok(match row {
+        Some(row) => ClientEncryptionSettings {
+            key_source: Some(ClientKeySource::parse(&row.key_source)?),
+            provider: row.provider,
+            account_id: row.account_id,
+            bucket: row.bucket,
+            key_file_path: row.key_file_path,
+            updated_at: Some(row.updated_at),
+        },
+        None => ClientEncryptionSettings {
+            provider: provider.to_string(),
+            account_id: account_id.to_string(),
+            bucket: bucket.to_string(),
+            key_source: None,
+            key_file_path: None,
+            updated_at: None,
+        },
+    })
+}
+
+fn validate_passphrase(passphrase: &str) -> Result<(), String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!(
+            "Passphrase must be at least {} characters",
+            MIN_PASSPHRASE_LEN
+        ));
+    }
+    Ok(())
+}
+
+async fn read_key_file(path: &str) -> Result<Vec<u8>, String> {
+    let contents = tokio::fs::read(path)
+        .await
+        .map_err(|e| format!("Failed to read key file {}: {}", path, e))?;
+    if contents.len() < MIN_KEY_FILE_LEN {
+        return Err(format!(
+            "Key file must contain at least {} bytes",
+            MIN_KEY_FILE_LEN
+        ));
+    }
+    Ok(contents)
+}
+
+/// Secret to encrypt uploads to, and decrypt downloads from, a bucket; `None`
+/// when client-side encryption is off. Fails if the passphrase or key file is
+/// gone, rather than silently uploading plaintext.
+pub(crate) async fn resolve_client_key(
+    provider: &str,
+    account_id: &str,
+    bucket: &str,
+) -> Result<Option<ClientKey>, String> {
+    let Some(row) = db::get_client_encryption(provider, account_id, bucket)
+        .await
+        .map_err(|e| format!("Failed to load client-side encryption settings: {}", e))?
+    else {
+        return Ok(None);
+    };
+
+    match ClientKeySource::parse(&row.key_source)? {
+        ClientKeySource::Passphrase => {
+            let secret_ref = row
+                .passphrase_ref
+                .ok_or_else(|| "Client-side encryption passphrase is not set".to_string())?;
+            secrets::load_secret(&secret_ref)
+                .await?
+                .map(|passphrase| Some(ClientKey::Passphrase(passphrase)))
+                .ok_or_else(|| {
+                    format!(
+                        "Client-side encryption passphrase for bucket '{}' is missing from the system keychain",
+                        bucket
+                    )
+                })
+        }
+        ClientKeySource::KeyFile => {
+            let path = row
+                .key_file_path
+                .ok_or_else(|| "Client-side encryption key file is not set".to_string())?;
+            Ok(Some(ClientKey::KeyFile(read_key_file(&path).await?)))
+        }
+    }
+}
+
+/// Server-side encryption and client key for an upload to a bucket.
+pub(crate) async fn resolve_upload_encryption(
+    provider: &str,
+    account_id: &str,
+    bucket: &str,
+) -> Result<(Option<ObjectEncryption>, Option<ClientKey>), String> {
+    let encryption = resolve_bucket_encryption(provider, account_id, bucket).await?;
+    let client_key = resolve_client_key(provider, account_id, bucket).await?;
+    Ok((encryption, client_key))
+}
+
+#[tauri::command]
+pub async fn get_client_encryption_settings(
+    provider: String,
+    account_id: String,
+    bucket: String,
+) -> Result<ClientEncryptionSettings, String> {
+    let row = db::get_client_encryption(&provider, &account_id, &bucket)
+        .await
+        .map_err(|e| format!("Failed to load client-side encryption settings: {}", e))?;
+    settings_from_row(&provider, &account_id, &bucket, row)
+}
+
+/// Save a bucket's client-side encryption settings. Objects already uploaded
+/// keep needing the secret they were encrypted with; changing or turning off
+/// the key does not re-encrypt them.
+#[tauri::command]
+pub async fn set_client_encryption_settings(
+    provider: String,
+    account_id: String,
+    bucket: String,
+    settings: ClientEncryptionInput,
+) -> Result<ClientEncryptionSettings, String> {
+    let secret_ref = secrets::secret_ref(PASSPHRASE_SECRET_KIND, &provider, &account_id, &bucket);
+
+    let row = match settings.key_source {
+        None => {
+            db::delete_client_encryption(&provider, &account_id, &bucket)
+                .await
+                .map_err(|e| format!("Failed to save client-side encryption settings: {}", e))?;
+            secrets::delete_secret(&secret_ref).await?;
+            None
+        }
+        Some(ClientKeySource::Passphrase) => {
+            match settings.passphrase.as_deref() {
+                Some(passphrase) => {
+                    validate_passphrase(passphrase)?;
+                    secrets::store_secret(&secret_ref, passphrase).await?;
+                }
+                None if secrets::load_secret(&secret_ref).await?.is_some() => {}
+                None => return Err("A passphrase is required".to_string()),
+            }
+            Some(
+                db::save_client_encryption(
+                    &provider,
+                    &account_id,
+                    &bucket,
+                    ClientKeySource::Passphrase.as_str(),
+                    Some(&secret_ref),
+                    None,
+                )
+                .await
+                .map_err(|e| format!("Failed to save client-side encryption settings: {}", e))?,
+            )
+        }
+        Some(ClientKeySource::KeyFile) => {
+            let path = settings
+                .key_file_path
+                .as_deref()
+                .ok_or_else(|| "A key file is required".to_string())?;
+            read_key_file(path).await?;
+            secrets::delete_secret(&secret_ref).await?;
+            Some(
+                db::save_client_encryption(
+                    &provider,
+                    &account_id,
+                    &bucket,
+                    ClientKeySource::KeyFile.as_str(),
+                    None,
+                    Some(path),
+                )
+                .await
+                .map_err(|e| format!("Failed to save client-side encryption settings: {}", e))?,
+            )
+        }
+    };
+
+    settings_from_row(&provider, &account_id, &bucket, row)
+}
+
+/// Write a new random key file. Refuses to overwrite an existing file.
+#[tauri::command]
+pub async fn create_client_key_file(path: String) -> Result<(), String> {
+    if Path::new(&path).exists() {
+        return Err(format!("{} already exists", path));
+    }
+    let mut contents = vec![0u8; GENERATED_KEY_FILE_LEN];
+    getrandom::getrandom(&mut contents).map_err(|e| format!("Failed to generate key: {}", e))?;
+    tokio::fs::write(&path, contents)
+        .await
+        .map_err(|e| format!("Failed to write key file: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_source_round_trips_through_db_strings() {
+        for source in [ClientKeySource::Passphrase, ClientKeySource::KeyFile] {
+            assert_eq!(ClientKeySource::parse(source.as_str()).unwrap(), source);
+        }
+        assert!(ClientKeySource::parse("none").is_err());
+    }
+
+    #[test]
+    fn short_passphrases_are_rejected() {
+        assert!(validate_passphrase("hunter2").is_err());
+        assert!(validate_passphrase("correct horse battery").is_ok());
+    }
+}
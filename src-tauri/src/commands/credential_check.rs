@@ -0,0 +1,33 @@
+//! Credential validation: probe what a key pair may do before relying on it.
+
+use crate::commands::provider_config::{build_encrypted_provider_config, ProviderConfigInput};
+use crate::db;
+use crate::providers::credential_probe::{self, CapabilityReport};
+
+/// Probe ListBuckets, ListObjectsV2 and a scratch write / read / delete with
+/// the credentials in `config`. An empty `config.bucket` probes the first
+/// visible bucket.
+///
+/// The report is saved with the R2 token `token_id`, or with the AWS / MinIO
+/// / RustFS account `config.account_id` once it exists. Forms checking keys
+/// before saving pass the report to the create command instead.
+#[tauri::command]
+pub async fn validate_credentials(
+    config: ProviderConfigInput,
+    token_id: Option<i64>,
+) -> Result<CapabilityReport, String> {
+    let provider_config = build_encrypted_provider_config(&config).await?;
+    let report = credential_probe::probe_credentials(&provider_config)
+        .await
+        .map_err(|e| format!("Failed to validate credentials: {}", e))?;
+
+    let saved = match (config.provider.as_str(), token_id) {
+        ("r2", Some(token_id)) => db::save_token_capabilities(token_id, Some(&report)).await,
+        ("r2", None) => Ok(()),
+        (provider, _) => {
+            db::save_account_capabilities(provider, &config.account_id, Some(&report)).await
+        }
+    };
+    saved.map_err(|e| format!("Failed to save credential capabilities: {}", e))?;
+    Ok(report)
+}
@@ -1,7 +1,7 @@
 use crate::commands::cache_events::{
     get_unique_parent_paths, CacheUpdatedEvent, PathsRemovedEvent,
 };
-use crate::db;
+use crate::db::{self, CacheScope};
 use log::{error, info};
 use std::collections::{HashMap, HashSet};
 use std::sync::OnceLock;
@@ -11,7 +11,7 @@ use tokio::sync::Mutex;
 use tokio::time::sleep;
 
 struct DeleteCacheQueueState {
-    pending: HashMap<(String, String), HashSet<String>>,
+    pending: HashMap<CacheScope, HashSet<String>>,
     scheduled: bool,
 }
 
@@ -30,21 +30,19 @@ fn delete_cache_queue() -> &'static Mutex<DeleteCacheQueueState> {
 /// Handles file cache, directory tree updates, and emits appropriate events.
 pub(crate) async fn update_cache_after_delete(
     app: &AppHandle,
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     key: &str,
 ) -> Result<(), String> {
     // Delete the file from cache and get its size
-    let file_size = db::delete_cached_file(bucket, account_id, key)
+    let file_size = db::delete_cached_file(scope, key)
         .await
         .map_err(|e| format!("Failed to update file cache: {}", e))?;
 
     if let Some(file_size) = file_size {
         // Update directory tree and get removed paths
-        let removed_paths =
-            db::update_directory_tree_for_delete(bucket, account_id, key, file_size)
-                .await
-                .map_err(|e| format!("Failed to update directory tree: {}", e))?;
+        let removed_paths = db::update_directory_tree_for_delete(scope, key, file_size)
+            .await
+            .map_err(|e| format!("Failed to update directory tree: {}", e))?;
 
         // Emit paths-removed event if any paths were removed
         if !removed_paths.is_empty() {
@@ -68,8 +66,7 @@ pub(crate) async fn update_cache_after_delete(
 /// Handles file cache, directory tree updates, and emits appropriate events.
 pub(crate) async fn update_cache_after_batch_delete(
     app: &AppHandle,
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     deleted_keys: &[String],
 ) -> Result<(), String> {
     if deleted_keys.is_empty() {
@@ -77,7 +74,7 @@ pub(crate) async fn update_cache_after_batch_delete(
     }
 
     // Delete files from cache and get their sizes
-    let file_sizes = db::delete_cached_files_batch(bucket, account_id, deleted_keys)
+    let file_sizes = db::delete_cached_files_batch(scope, deleted_keys)
         .await
         .map_err(|e| format!("Failed to update file cache: {}", e))?;
 
@@ -86,19 +83,14 @@ pub(crate) async fn update_cache_after_batch_delete(
         .filter_map(|key| file_sizes.get(key).map(|size| (key.clone(), *size)))
         .collect();
 
-    let all_removed_paths = match db::update_directory_tree_for_delete_batch(
-        bucket,
-        account_id,
-        &deleted_entries,
-    )
-    .await
-    {
-        Ok(paths) => paths,
-        Err(e) => {
-            error!("delete_cache_batch: dir_tree update failed: {}", e);
-            Vec::new()
-        }
-    };
+    let all_removed_paths =
+        match db::update_directory_tree_for_delete_batch(scope, &deleted_entries).await {
+            Ok(paths) => paths,
+            Err(e) => {
+                error!("delete_cache_batch: dir_tree update failed: {}", e);
+                Vec::new()
+            }
+        };
 
     // Emit paths-removed event if any paths were removed
     if !all_removed_paths.is_empty() {
@@ -123,18 +115,13 @@ pub(crate) async fn update_cache_after_batch_delete(
 }
 
 /// Queue cache updates for deletes to avoid duplicated directory calculations.
-pub(crate) async fn queue_cache_after_delete(
-    app: AppHandle,
-    bucket: String,
-    account_id: String,
-    key: String,
-) {
+pub(crate) async fn queue_cache_after_delete(app: AppHandle, scope: CacheScope, key: String) {
     let should_schedule = {
         let queue = delete_cache_queue();
         let mut state = queue.lock().await;
         state
             .pending
-            .entry((bucket.clone(), account_id.clone()))
+            .entry(scope)
             .or_insert_with(HashSet::new)
             .insert(key);
         if state.scheduled {
@@ -158,20 +145,20 @@ pub(crate) async fn queue_cache_after_delete(
             std::mem::take(&mut state.pending)
         };
 
-        for ((bucket, account_id), keys) in batch {
+        for (scope, keys) in batch {
             let key_list: Vec<String> = keys.into_iter().collect();
             info!(
                 "delete_cache_batch: flushing {} keys for {}/{}",
                 key_list.len(),
-                account_id,
-                bucket
+                scope.account_id(),
+                scope.bucket()
             );
-            if let Err(e) =
-                update_cache_after_batch_delete(&app, &bucket, &account_id, &key_list).await
-            {
+            if let Err(e) = update_cache_after_batch_delete(&app, &scope, &key_list).await {
                 error!(
                     "delete_cache_batch: failed for {}/{}: {}",
-                    account_id, bucket, e
+                    scope.account_id(),
+                    scope.bucket(),
+                    e
                 );
             }
         }
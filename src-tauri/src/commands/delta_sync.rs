@@ -0,0 +1,181 @@
+//! Delta sync: apply a fresh bucket listing to the cache as inserts, updates
+//! and deletes, and patch the directory tree, instead of replacing the cache
+//! and rebuilding the tree.
+
+use crate::commands::cache_events::{
+    get_unique_parent_paths, CacheUpdatedEvent, PathsRemovedEvent,
+};
+use crate::db::{self, CacheScope};
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+/// How a bucket sync writes its listing into the cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncMode {
+    /// Delta when the bucket has been fully synced before, full otherwise.
+    #[default]
+    Auto,
+    /// Replace the cached listing and rebuild the directory tree.
+    Full,
+    Delta,
+}
+
+impl SyncMode {
+    /// Whether this sync should be applied as a delta. A delta against an
+    /// empty cache is only slower than a full sync, so `Auto` needs a
+    /// previous full sync.
+    pub(crate) async fn is_delta(self, scope: &CacheScope) -> Result<bool, String> {
+        match self {
+            SyncMode::Full => Ok(false),
+            SyncMode::Delta => Ok(true),
+            SyncMode::Auto => db::has_full_sync(scope)
+                .await
+                .map_err(|e| format!("Failed to read sync state: {}", e)),
+        }
+    }
+}
+
+/// What a delta sync changed, emitted as `sync-delta`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncChangeSummary {
+    pub bucket: String,
+    pub account_id: String,
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    /// Net change of the bucket's total size.
+    pub size_delta: i64,
+    /// Folders whose contents changed, including ancestors.
+    pub affected_paths: Vec<String>,
+    /// Folders dropped from the tree because they became empty.
+    pub removed_paths: Vec<String>,
+}
+
+/// Step 3 for delta syncs: apply the staged listing's differences, patch the
+/// directory tree with the incremental helpers and emit the change events.
+pub(crate) async fn finish_delta_sync(
+    app: &tauri::AppHandle,
+    scope: &CacheScope,
+    file_count: usize,
+    folder_keys: &[String],
+) -> Result<SyncChangeSummary, String> {
+    let delta = db::finish_delta_sync(scope, file_count)
+        .await
+        .map_err(|e| format!("Failed to apply sync changes: {}", e))?;
+    let tree_error = |e: Box<dyn std::error::Error + Send + Sync>| {
+        format!("Failed to update directory tree: {}", e)
+    };
+
+    let removed: Vec<(String, i64)> = delta
+        .removed
+        .iter()
+        .map(|file| (file.key.clone(), file.previous_size))
+        .collect();
+    let removed_paths = db::update_directory_tree_for_delete_batch(scope, &removed)
+        .await
+        .map_err(tree_error)?;
+
+    for file in &delta.added {
+        db::update_directory_tree_for_file(scope, &file.key, file.size, &file.last_modified, true)
+            .await
+            .map_err(tree_error)?;
+    }
+    for file in &delta.updated {
+        db::update_directory_tree_for_file(
+            scope,
+            &file.key,
+            file.size - file.previous_size,
+            &file.last_modified,
+            false,
+        )
+        .await
+        .map_err(tree_error)?;
+    }
+
+    // Folder marker objects keep empty folders in the tree
+    for folder in folder_keys {
+        db::ensure_directory_node(scope, folder)
+            .await
+            .map_err(tree_error)?;
+    }
+
+    let changed_keys: Vec<String> = delta
+        .added
+        .iter()
+        .chain(&delta.updated)
+        .chain(&delta.removed)
+        .map(|file| file.key.clone())
+        .collect();
+    let affected_paths = if changed_keys.is_empty() {
+        Vec::new()
+    } else {
+        get_unique_parent_paths(&changed_keys)
+    };
+
+    if !removed_paths.is_empty() {
+        let _ = app.emit(
+            "paths-removed",
+            PathsRemovedEvent {
+                removed_paths: removed_paths.clone(),
+            },
+        );
+    }
+    if !affected_paths.is_empty() {
+        let _ = app.emit(
+            "cache-updated",
+            CacheUpdatedEvent {
+                action: "sync".to_string(),
+                affected_paths: affected_paths.clone(),
+            },
+        );
+    }
+
+    let summary = SyncChangeSummary {
+        bucket: scope.bucket().to_string(),
+        account_id: scope.account_id().to_string(),
+        added: delta.added.len(),
+        updated: delta.updated.len(),
+        removed: delta.removed.len(),
+        size_delta: changed_size(&delta),
+        affected_paths,
+        removed_paths,
+    };
+    let _ = app.emit("sync-delta", summary.clone());
+    Ok(summary)
+}
+
+fn changed_size(delta: &db::SyncDelta) -> i64 {
+    delta
+        .added
+        .iter()
+        .chain(&delta.updated)
+        .chain(&delta.removed)
+        .map(|file| file.size - file.previous_size)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use db::file_cache::DeltaFile;
+
+    fn file(key: &str, size: i64, previous_size: i64) -> DeltaFile {
+        DeltaFile {
+            key: key.to_string(),
+            size,
+            previous_size,
+            last_modified: String::new(),
+        }
+    }
+
+    #[test]
+    fn size_delta_nets_all_changes() {
+        let delta = db::SyncDelta {
+            added: vec![file("new.bin", 100, 0)],
+            updated: vec![file("grown.txt", 30, 10)],
+            removed: vec![file("old.bin", 0, 50)],
+        };
+        assert_eq!(changed_size(&delta), 70);
+    }
+}
@@ -0,0 +1,660 @@
+//! Duplicate objects across synced buckets and accounts.
+//!
+//! Candidates come from the local cache: objects of equal size with equal
+//! single-part ETags (the MD5 of the content) are duplicates. Multipart ETags
+//! depend on the part size, so equal content can carry different ones; with
+//! `verify_multipart` every object in such a size group is downloaded through
+//! range-dl and compared by SHA-256 instead. Cleanup is planned here and
+//! carried out by the existing batch delete and batch move commands.
+
+use crate::commands::provider_config::{build_encrypted_provider_config, ProviderConfigInput};
+use crate::db::{self, CacheScope, CachedFile};
+use crate::providers::config::ProviderConfig;
+use crate::providers::encryption::read_headers;
+use crate::providers::presign::{presign, PresignMethod, PresignOptions};
+use range_dl::{ChunkEvent, DownloadTarget, RangeDownloadConfig, RangeDownloader};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tauri::Emitter;
+use tokio::io::AsyncReadExt;
+use tokio::sync::Semaphore;
+
+/// Objects downloaded at once for hashing; each download is itself chunked.
+const MAX_CONCURRENT_HASHES: usize = 4;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DuplicateScanOptions {
+    /// Ignore objects smaller than this; empty objects are never reported.
+    #[serde(default)]
+    pub min_size: i64,
+    /// Download and hash objects whose multipart ETags can't be compared.
+    #[serde(default)]
+    pub verify_multipart: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateMatch {
+    /// Equal size and single-part ETag (MD5 of the content).
+    Etag,
+    /// Equal size and multipart ETag: same content uploaded with the same
+    /// part size, very likely but not verified.
+    MultipartEtag,
+    /// Equal size and SHA-256 of the downloaded content.
+    ContentHash,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateObject {
+    pub provider: String,
+    pub account_id: String,
+    pub bucket: String,
+    pub key: String,
+    pub last_modified: String,
+    pub storage_class: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub match_kind: DuplicateMatch,
+    /// ETag or SHA-256 shared by the objects.
+    pub fingerprint: String,
+    pub size: i64,
+    pub objects: Vec<DuplicateObject>,
+    /// Object to keep; defaults to the oldest.
+    pub keep: usize,
+    /// Bytes freed by removing every copy but one.
+    pub reclaimable_bytes: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateReport {
+    /// Largest reclaimable first.
+    pub groups: Vec<DuplicateGroup>,
+    pub duplicate_objects: usize,
+    pub reclaimable_bytes: i64,
+    /// Objects downloaded for hashing in this scan.
+    pub hashed: usize,
+    pub hash_errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct HashProgress {
+    hashed: usize,
+    total: usize,
+}
+
+/// A cached file and the target it was found in.
+struct Candidate {
+    target: usize,
+    file: CachedFile,
+    sha256: Option<String>,
+}
+
+fn normalize_etag(etag: &str) -> String {
+    etag.trim().trim_matches('"').to_ascii_lowercase()
+}
+
+fn is_multipart_etag(etag: &str) -> bool {
+    etag.contains('-')
+}
+
+/// Size groups whose objects can only be compared by content: some ETag is
+/// missing or multipart.
+fn needs_hashing(files: &[&CachedFile]) -> bool {
+    files.len() > 1
+        && files.iter().any(|f| match f.etag.as_deref() {
+            Some(etag) => is_multipart_etag(etag),
+            None => true,
+        })
+}
+
+/// Group candidates into duplicates. Within a size, content hashes are used
+/// when every object has one, ETags otherwise.
+fn group_duplicates(
+    candidates: Vec<Candidate>,
+    targets: &[ProviderConfigInput],
+) -> Vec<DuplicateGroup> {
+    let mut by_size: BTreeMap<i64, Vec<Candidate>> = BTreeMap::new();
+    for candidate in candidates {
+        by_size
+            .entry(candidate.file.size)
+            .or_default()
+            .push(candidate);
+    }
+
+    let mut groups = Vec::new();
+    for (size, same_size) in by_size {
+        let hashed = same_size.iter().all(|c| c.sha256.is_some());
+        let mut by_fingerprint: BTreeMap<(DuplicateMatch, String), Vec<Candidate>> =
+            BTreeMap::new();
+        for candidate in same_size {
+            let fingerprint = if hashed {
+                candidate
+                    .sha256
+                    .clone()
+                    .map(|sha| (DuplicateMatch::ContentHash, sha))
+            } else {
+                candidate.file.etag.as_deref().map(|etag| {
+                    let etag = normalize_etag(etag);
+                    if is_multipart_etag(&etag) {
+                        (DuplicateMatch::MultipartEtag, etag)
+                    } else {
+                        (DuplicateMatch::Etag, etag)
+                    }
+                })
+            };
+            if let Some(fingerprint) = fingerprint {
+                by_fingerprint
+                    .entry(fingerprint)
+                    .or_default()
+                    .push(candidate);
+            }
+        }
+
+        for ((match_kind, fingerprint), mut members) in by_fingerprint {
+            if members.len() < 2 {
+                continue;
+            }
+            members.sort_by(|a, b| {
+                (&a.file.last_modified, a.target, &a.file.key).cmp(&(
+                    &b.file.last_modified,
+                    b.target,
+                    &b.file.key,
+                ))
+            });
+            let objects: Vec<DuplicateObject> = members
+                .into_iter()
+                .map(|c| {
+                    let target = &targets[c.target];
+                    DuplicateObject {
+                        provider: target.provider.clone(),
+                        account_id: target.account_id.clone(),
+                        bucket: target.bucket.clone(),
+                        key: c.file.key,
+                        last_modified: c.file.last_modified,
+                        storage_class: c.file.storage_class,
+                    }
+                })
+                .collect();
+            groups.push(DuplicateGroup {
+                match_kind,
+                fingerprint,
+                size,
+                reclaimable_bytes: size * (objects.len() as i64 - 1),
+                objects,
+                keep: 0,
+            });
+        }
+    }
+
+    groups.sort_by(|a, b| {
+        b.reclaimable_bytes
+            .cmp(&a.reclaimable_bytes)
+            .then_with(|| a.fingerprint.cmp(&b.fingerprint))
+    });
+    groups
+}
+
+// ============ Content Hashing ============
+
+static HASH_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn hash_scratch_path() -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "r2-dedupe-{}-{}",
+        std::process::id(),
+        HASH_FILE_COUNTER.fetch_add(1, Ordering::SeqCst)
+    ))
+}
+
+async fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| format!("Failed to open downloaded copy: {}", e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .await
+            .map_err(|e| format!("Failed to read downloaded copy: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Download `key` to a scratch file with range-dl and return its SHA-256.
+async fn hash_object(config: &ProviderConfig, key: &str, size: i64) -> Result<String, String> {
+    let cfg = config.clone();
+    let key_owned = key.to_string();
+    let url_provider: range_dl::UrlProvider = Box::new(move || {
+        let cfg = cfg.clone();
+        let options = PresignOptions {
+            method: PresignMethod::Get,
+            key: key_owned.clone(),
+            expires_in_secs: 3600,
+            download_filename: None,
+            response_content_disposition: None,
+            response_content_type: None,
+            content_type: None,
+            post: None,
+        };
+        Box::pin(async move {
+            presign(&cfg, &options)
+                .await
+                .map(|request| request.url)
+                .map_err(|e| format!("Failed to generate presigned URL: {}", e))
+        })
+    });
+
+    let destination = hash_scratch_path();
+    let downloader = RangeDownloader::new(
+        url_provider,
+        DownloadTarget {
+            file_size: size as u64,
+            destination: destination.clone(),
+        },
+        RangeDownloadConfig {
+            headers: read_headers(config.encryption()),
+            ..Default::default()
+        },
+    );
+    let (mut rx, control) = downloader.start().await?;
+
+    let mut outcome = Err(format!("Download of {} ended early", key));
+    while let Some(event) = rx.recv().await {
+        match event {
+            ChunkEvent::Complete { .. } => {
+                outcome = Ok(());
+                break;
+            }
+            ChunkEvent::Failed { error } => {
+                outcome = Err(error);
+                break;
+            }
+            ChunkEvent::Cancelled => {
+                outcome = Err(format!("Download of {} was cancelled", key));
+                break;
+            }
+            _ => {}
+        }
+    }
+    drop(control);
+
+    let hash = match outcome {
+        Ok(()) => sha256_file(&destination).await,
+        Err(e) => Err(e),
+    };
+    let _ = tokio::fs::remove_file(&destination).await;
+    hash
+}
+
+/// Fill in recorded hashes, then download and hash the rest of the objects in
+/// size groups that need it. Returns how many were downloaded and the errors.
+async fn hash_candidates(
+    app: &tauri::AppHandle,
+    candidates: &mut [Candidate],
+    scopes: &[CacheScope],
+    targets: &[ProviderConfigInput],
+) -> Result<(usize, Vec<String>), String> {
+    let mut sizes: HashMap<i64, Vec<&CachedFile>> = HashMap::new();
+    for candidate in candidates.iter() {
+        sizes
+            .entry(candidate.file.size)
+            .or_default()
+            .push(&candidate.file);
+    }
+    let hash_sizes: Vec<i64> = sizes
+        .into_iter()
+        .filter(|(_, files)| needs_hashing(files))
+        .map(|(size, _)| size)
+        .collect();
+
+    let mut pending: Vec<usize> = Vec::new();
+    for (index, candidate) in candidates.iter_mut().enumerate() {
+        if !hash_sizes.contains(&candidate.file.size) {
+            continue;
+        }
+        let file = &candidate.file;
+        candidate.sha256 = db::get_object_hash(
+            &scopes[candidate.target],
+            &file.key,
+            file.size,
+            file.etag.as_deref(),
+        )
+        .await
+        .map_err(|e| format!("Failed to read content hashes: {}", e))?;
+        if candidate.sha256.is_none() {
+            pending.push(index);
+        }
+    }
+    if pending.is_empty() {
+        return Ok((0, Vec::new()));
+    }
+
+    let mut configs: HashMap<usize, Arc<ProviderConfig>> = HashMap::new();
+    for &index in &pending {
+        let target = candidates[index].target;
+        if !configs.contains_key(&target) {
+            let config = build_encrypted_provider_config(&targets[target]).await?;
+            configs.insert(target, Arc::new(config));
+        }
+    }
+
+    let total = pending.len();
+    let done = Arc::new(AtomicUsize::new(0));
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_HASHES));
+    let mut handles = Vec::with_capacity(total);
+    for index in pending {
+        let permit = match semaphore.clone().acquire_owned().await {
+            Ok(p) => p,
+            Err(_) => break,
+        };
+        let candidate = &candidates[index];
+        let config = configs[&candidate.target].clone();
+        let scope = scopes[candidate.target].clone();
+        let file = candidate.file.clone();
+        let done = done.clone();
+        let app = app.clone();
+
+        handles.push(tokio::spawn(async move {
+            let _permit = permit;
+            let result = async {
+                let sha256 = hash_object(&config, &file.key, file.size).await?;
+                db::save_object_hash(&scope, &file.key, file.size, file.etag.as_deref(), &sha256)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                Ok::<String, String>(sha256)
+            }
+            .await
+            .map_err(|e| format!("{}/{}: {}", scope.bucket(), file.key, e));
+
+            let hashed = done.fetch_add(1, Ordering::SeqCst) + 1;
+            let _ = app.emit("duplicate-hash-progress", HashProgress { hashed, total });
+            (index, result)
+        }));
+    }
+
+    let mut hashed = 0;
+    let mut errors = Vec::new();
+    for handle in handles {
+        match handle.await {
+            Ok((index, Ok(sha256))) => {
+                candidates[index].sha256 = Some(sha256);
+                hashed += 1;
+            }
+            Ok((_, Err(e))) => errors.push(e),
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+    Ok((hashed, errors))
+}
+
+// ============ Commands ============
+
+/// Find duplicate objects across the cached listings of `targets`.
+#[tauri::command]
+pub async fn find_duplicates(
+    app: tauri::AppHandle,
+    targets: Vec<ProviderConfigInput>,
+    options: Option<DuplicateScanOptions>,
+) -> Result<DuplicateReport, String> {
+    let options = options.unwrap_or_default();
+    let scopes: Vec<CacheScope> = targets.iter().map(|t| t.cache_scope()).collect();
+
+    let mut candidates: Vec<Candidate> = db::get_same_size_files(&scopes, options.min_size.max(1))
+        .await
+        .map_err(|e| format!("Failed to read cached files: {}", e))?
+        .into_iter()
+        .map(|(target, file)| Candidate {
+            target,
+            file,
+            sha256: None,
+        })
+        .collect();
+
+    let (hashed, hash_errors) = if options.verify_multipart {
+        hash_candidates(&app, &mut candidates, &scopes, &targets).await?
+    } else {
+        (0, Vec::new())
+    };
+
+    let groups = group_duplicates(candidates, &targets);
+    Ok(DuplicateReport {
+        duplicate_objects: groups.iter().map(|g| g.objects.len() - 1).sum(),
+        reclaimable_bytes: groups.iter().map(|g| g.reclaimable_bytes).sum(),
+        groups,
+        hashed,
+        hash_errors,
+    })
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DuplicateAction {
+    /// Delete every copy but the kept one.
+    Delete,
+    /// Move every copy but the kept one under `prefix` in its own bucket.
+    Move { prefix: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlannedMove {
+    pub old_key: String,
+    pub new_key: String,
+}
+
+/// Work for one bucket, shaped for `batch_delete_*_objects` (`delete_keys`)
+/// and `batch_move_*_objects` (`moves`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DuplicateCleanupBatch {
+    pub provider: String,
+    pub account_id: String,
+    pub bucket: String,
+    pub delete_keys: Vec<String>,
+    pub moves: Vec<PlannedMove>,
+}
+
+fn plan_cleanup(
+    groups: &[DuplicateGroup],
+    action: &DuplicateAction,
+) -> Result<Vec<DuplicateCleanupBatch>, String> {
+    let prefix = match action {
+        DuplicateAction::Delete => None,
+        DuplicateAction::Move { prefix } => {
+            let prefix = prefix.trim_start_matches('/');
+            if prefix.is_empty() {
+                return Err("A destination prefix is required to move duplicates".into());
+            }
+            Some(if prefix.ends_with('/') {
+                prefix.to_string()
+            } else {
+                format!("{}/", prefix)
+            })
+        }
+    };
+
+    let mut batches: Vec<DuplicateCleanupBatch> = Vec::new();
+    for group in groups {
+        if group.keep >= group.objects.len() {
+            return Err(format!(
+                "Kept copy {} is not part of the group of {} objects",
+                group.keep,
+                group.objects.len()
+            ));
+        }
+        for (index, object) in group.objects.iter().enumerate() {
+            if index == group.keep {
+                continue;
+            }
+            let position = batches.iter().position(|b| {
+                b.provider == object.provider
+                    && b.account_id == object.account_id
+                    && b.bucket == object.bucket
+            });
+            let batch = match position {
+                Some(position) => &mut batches[position],
+                None => {
+                    batches.push(DuplicateCleanupBatch {
+                        provider: object.provider.clone(),
+                        account_id: object.account_id.clone(),
+                        bucket: object.bucket.clone(),
+                        delete_keys: Vec::new(),
+                        moves: Vec::new(),
+                    });
+                    batches.last_mut().unwrap()
+                }
+            };
+            match &prefix {
+                None => batch.delete_keys.push(object.key.clone()),
+                // Already set aside by an earlier cleanup
+                Some(prefix) if object.key.starts_with(prefix.as_str()) => {}
+                Some(prefix) => batch.moves.push(PlannedMove {
+                    old_key: object.key.clone(),
+                    new_key: format!("{}{}", prefix, object.key),
+                }),
+            }
+        }
+    }
+    batches.retain(|b| !b.delete_keys.is_empty() || !b.moves.is_empty());
+    Ok(batches)
+}
+
+/// Turn duplicate groups (with their chosen `keep`) into per-bucket batches
+/// for the batch delete or batch move commands.
+#[tauri::command]
+pub async fn plan_duplicate_cleanup(
+    groups: Vec<DuplicateGroup>,
+    action: DuplicateAction,
+) -> Result<Vec<DuplicateCleanupBatch>, String> {
+    plan_cleanup(&groups, &action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(bucket: &str) -> ProviderConfigInput {
+        ProviderConfigInput {
+            provider: "r2".to_string(),
+            account_id: "acct".to_string(),
+            bucket: bucket.to_string(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            region: None,
+            endpoint_scheme: None,
+            endpoint_host: None,
+            force_path_style: None,
+            credentials: Default::default(),
+        }
+    }
+
+    fn candidate(target: usize, key: &str, size: i64, etag: &str, modified: &str) -> Candidate {
+        Candidate {
+            target,
+            file: CachedFile {
+                bucket: String::new(),
+                account_id: String::new(),
+                key: key.to_string(),
+                parent_path: String::new(),
+                name: String::new(),
+                size,
+                last_modified: modified.to_string(),
+                synced_at: 0,
+                storage_class: None,
+                etag: Some(etag.to_string()),
+                content_type: None,
+                checksum: None,
+            },
+            sha256: None,
+        }
+    }
+
+    #[test]
+    fn groups_by_size_and_etag_oldest_first() {
+        let targets = [target("a"), target("b")];
+        let groups = group_duplicates(
+            vec![
+                candidate(1, "copy.jpg", 100, "\"ABC\"", "2024-02-01"),
+                candidate(0, "orig.jpg", 100, "\"abc\"", "2024-01-01"),
+                candidate(0, "other.jpg", 100, "\"def\"", "2024-01-01"),
+                candidate(0, "big-1.iso", 500, "\"x-2\"", "2024-01-01"),
+                candidate(1, "big-2.iso", 500, "\"x-2\"", "2024-01-01"),
+            ],
+            &targets,
+        );
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].match_kind, DuplicateMatch::MultipartEtag);
+        assert_eq!(groups[0].reclaimable_bytes, 500);
+        assert_eq!(groups[1].match_kind, DuplicateMatch::Etag);
+        assert_eq!(groups[1].fingerprint, "abc");
+        let keys: Vec<(&str, &str)> = groups[1]
+            .objects
+            .iter()
+            .map(|o| (o.bucket.as_str(), o.key.as_str()))
+            .collect();
+        assert_eq!(keys, vec![("a", "orig.jpg"), ("b", "copy.jpg")]);
+    }
+
+    #[test]
+    fn content_hashes_override_etags_once_every_object_has_one() {
+        let targets = [target("a")];
+        let mut first = candidate(0, "one.bin", 10, "\"a-2\"", "2024-01-01");
+        let mut second = candidate(0, "two.bin", 10, "\"b-3\"", "2024-01-01");
+        assert!(needs_hashing(&[&first.file, &second.file]));
+        first.sha256 = Some("feed".to_string());
+        second.sha256 = Some("feed".to_string());
+
+        let groups = group_duplicates(vec![first, second], &targets);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].match_kind, DuplicateMatch::ContentHash);
+        assert_eq!(groups[0].fingerprint, "feed");
+    }
+
+    #[test]
+    fn cleanup_plans_skip_the_kept_copy() {
+        let targets = [target("a"), target("b")];
+        let mut groups = group_duplicates(
+            vec![
+                candidate(0, "x.txt", 5, "\"e\"", "2024-01-01"),
+                candidate(1, "x.txt", 5, "\"e\"", "2024-01-02"),
+                candidate(1, "y.txt", 5, "\"e\"", "2024-01-03"),
+            ],
+            &targets,
+        );
+        groups[0].keep = 1;
+
+        let deletes = plan_cleanup(&groups, &DuplicateAction::Delete).unwrap();
+        assert_eq!(deletes.len(), 2);
+        assert_eq!(deletes[0].bucket, "a");
+        assert_eq!(deletes[0].delete_keys, vec!["x.txt"]);
+        assert_eq!(deletes[1].delete_keys, vec!["y.txt"]);
+
+        let moves = plan_cleanup(
+            &groups,
+            &DuplicateAction::Move {
+                prefix: "dupes".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            moves[1].moves,
+            vec![PlannedMove {
+                old_key: "y.txt".to_string(),
+                new_key: "dupes/y.txt".to_string(),
+            }]
+        );
+
+        groups[0].keep = 3;
+        assert!(plan_cleanup(&groups, &DuplicateAction::Delete).is_err());
+    }
+}
@@ -1,6 +1,8 @@
 //! File caching commands for Tauri frontend
 
-use crate::db::{self, CachedDirectoryNode, CachedFile};
+use crate::db::{
+    self, CacheScope, CachedDirectoryNode, CachedFile, SearchQuery, StorageClassUsage,
+};
 use crate::r2;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use serde::{Deserialize, Serialize};
@@ -14,6 +16,12 @@ pub struct CachedFileResponse {
     pub size: i64,
     #[serde(rename = "lastModified")]
     pub last_modified: String,
+    #[serde(rename = "storageClass")]
+    pub storage_class: Option<String>,
+    pub etag: Option<String>,
+    #[serde(rename = "contentType")]
+    pub content_type: Option<String>,
+    pub checksum: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -39,6 +47,15 @@ pub struct SearchResultResponse {
     pub total_count: i32,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchPageResponse {
+    pub files: Vec<CachedFileResponse>,
+    #[serde(rename = "totalCount")]
+    pub total_count: i64,
+    #[serde(rename = "nextCursor")]
+    pub next_cursor: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BucketSummaryResponse {
     #[serde(rename = "totalFiles")]
@@ -51,6 +68,16 @@ pub struct BucketSummaryResponse {
     pub is_complete: bool,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StorageClassUsageResponse {
+    #[serde(rename = "storageClass")]
+    pub storage_class: Option<String>,
+    #[serde(rename = "fileCount")]
+    pub file_count: i64,
+    #[serde(rename = "totalSize")]
+    pub total_size: i64,
+}
+
 #[derive(Debug, Clone, Serialize)]
 struct IndexingProgress {
     current: usize,
@@ -65,6 +92,20 @@ impl From<CachedFile> for CachedFileResponse {
             key: file.key,
             size: file.size,
             last_modified: file.last_modified,
+            storage_class: file.storage_class,
+            etag: file.etag,
+            content_type: file.content_type,
+            checksum: file.checksum,
+        }
+    }
+}
+
+impl From<StorageClassUsage> for StorageClassUsageResponse {
+    fn from(usage: StorageClassUsage) -> Self {
+        StorageClassUsageResponse {
+            storage_class: usage.storage_class,
+            file_count: usage.file_count,
+            total_size: usage.total_size,
         }
     }
 }
@@ -85,13 +126,13 @@ impl From<CachedDirectoryNode> for DirectoryNodeResponse {
 
 // ============ Helper Functions ============
 
-async fn get_current_bucket_info() -> Result<(String, String), String> {
+async fn get_current_scope() -> Result<CacheScope, String> {
     let config = db::get_current_config()
         .await
         .map_err(|e| format!("Failed to get current config: {}", e))?
         .ok_or_else(|| "No active configuration".to_string())?;
 
-    Ok((config.bucket, config.account_id))
+    Ok(CacheScope::from(&config))
 }
 
 // ============ Commands ============
@@ -101,7 +142,7 @@ pub async fn store_all_files(
     files: Vec<r2::R2Object>,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
-    let (bucket, account_id) = get_current_bucket_info().await?;
+    let scope = get_current_scope().await?;
     let now = chrono::Utc::now().timestamp();
 
     let _ = app.emit("sync-phase", "storing");
@@ -109,27 +150,31 @@ pub async fn store_all_files(
     let cached_files: Vec<CachedFile> = files
         .into_iter()
         .map(|f| CachedFile {
-            bucket: bucket.clone(),
-            account_id: account_id.clone(),
+            bucket: scope.bucket().to_string(),
+            account_id: scope.account_id().to_string(),
             key: f.key,
             parent_path: String::new(), // Computed in store_all_files from key
             name: String::new(),        // Computed in store_all_files from key
             size: f.size,
             last_modified: f.last_modified,
             synced_at: now,
+            storage_class: None,
+            etag: (!f.etag.is_empty()).then_some(f.etag),
+            content_type: None,
+            checksum: None,
         })
         .collect();
 
-    db::store_all_files(&bucket, &account_id, &cached_files)
+    db::store_all_files(&scope, &cached_files)
         .await
         .map_err(|e| format!("Failed to store files: {}", e))
 }
 
 #[tauri::command]
 pub async fn get_all_cached_files() -> Result<Vec<CachedFileResponse>, String> {
-    let (bucket, account_id) = get_current_bucket_info().await?;
+    let scope = get_current_scope().await?;
 
-    let files = db::get_all_cached_files(&bucket, &account_id)
+    let files = db::get_all_cached_files(&scope)
         .await
         .map_err(|e| format!("Failed to get cached files: {}", e))?;
 
@@ -138,9 +183,9 @@ pub async fn get_all_cached_files() -> Result<Vec<CachedFileResponse>, String> {
 
 #[tauri::command]
 pub async fn search_cached_files(query: String) -> Result<SearchResultResponse, String> {
-    let (bucket, account_id) = get_current_bucket_info().await?;
+    let scope = get_current_scope().await?;
 
-    let result = db::search_cached_files(&bucket, &account_id, &query)
+    let result = db::search_cached_files(&scope, &query)
         .await
         .map_err(|e| format!("Failed to search files: {}", e))?;
 
@@ -150,14 +195,81 @@ pub async fn search_cached_files(query: String) -> Result<SearchResultResponse,
     })
 }
 
+/// Indexed search with structured filters, sorting and cursor paging.
+#[tauri::command]
+pub async fn search_files(query: SearchQuery) -> Result<SearchPageResponse, String> {
+    let scope = get_current_scope().await?;
+
+    let page = db::search_files(&scope, &query)
+        .await
+        .map_err(|e| format!("Failed to search files: {}", e))?;
+
+    Ok(SearchPageResponse {
+        files: page.files.into_iter().map(|f| f.into()).collect(),
+        total_count: page.total_count,
+        next_cursor: page.next_cursor,
+    })
+}
+
+/// A cached file with everything recorded about it (ETag, class, content
+/// type, checksum).
+#[tauri::command]
+pub async fn get_cached_file(key: String) -> Result<Option<CachedFileResponse>, String> {
+    let scope = get_current_scope().await?;
+
+    let file = db::get_cached_file(&scope, &key)
+        .await
+        .map_err(|e| format!("Failed to get cached file: {}", e))?;
+
+    Ok(file.map(|f| f.into()))
+}
+
+/// Cached files sharing an ETag, i.e. most likely the same content.
+#[tauri::command]
+pub async fn find_cached_files_by_etag(etag: String) -> Result<Vec<CachedFileResponse>, String> {
+    let scope = get_current_scope().await?;
+
+    let files = db::find_cached_files_by_etag(&scope, &etag)
+        .await
+        .map_err(|e| format!("Failed to find cached files: {}", e))?;
+
+    Ok(files.into_iter().map(|f| f.into()).collect())
+}
+
+/// Cached files in a storage class; no class selects files the listing
+/// reported without one.
+#[tauri::command]
+pub async fn get_cached_files_by_storage_class(
+    storage_class: Option<String>,
+) -> Result<Vec<CachedFileResponse>, String> {
+    let scope = get_current_scope().await?;
+
+    let files = db::get_cached_files_by_storage_class(&scope, storage_class.as_deref())
+        .await
+        .map_err(|e| format!("Failed to get cached files: {}", e))?;
+
+    Ok(files.into_iter().map(|f| f.into()).collect())
+}
+
+#[tauri::command]
+pub async fn get_storage_class_usage() -> Result<Vec<StorageClassUsageResponse>, String> {
+    let scope = get_current_scope().await?;
+
+    let usage = db::get_storage_class_usage(&scope)
+        .await
+        .map_err(|e| format!("Failed to get storage class usage: {}", e))?;
+
+    Ok(usage.into_iter().map(|u| u.into()).collect())
+}
+
 /// Bucket-wide summary (total files + size) for the status bar.
 /// Cheap: single-row directory-tree lookup after a full sync, falling back to
 /// one SQL aggregate over the partial lazy cache — never a LIKE scan.
 #[tauri::command]
 pub async fn get_bucket_summary() -> Result<BucketSummaryResponse, String> {
-    let (bucket, account_id) = get_current_bucket_info().await?;
+    let scope = get_current_scope().await?;
 
-    let summary = db::get_bucket_summary(&bucket, &account_id)
+    let summary = db::get_bucket_summary(&scope)
         .await
         .map_err(|e| format!("Failed to get bucket summary: {}", e))?;
 
@@ -171,16 +283,16 @@ pub async fn get_bucket_summary() -> Result<BucketSummaryResponse, String> {
 
 #[tauri::command]
 pub async fn calculate_folder_size(prefix: String) -> Result<i64, String> {
-    let (bucket, account_id) = get_current_bucket_info().await?;
+    let scope = get_current_scope().await?;
 
-    db::calculate_folder_size(&bucket, &account_id, &prefix)
+    db::calculate_folder_size(&scope, &prefix)
         .await
         .map_err(|e| format!("Failed to calculate folder size: {}", e))
 }
 
 #[tauri::command]
 pub async fn build_directory_tree(app: tauri::AppHandle) -> Result<(), String> {
-    let (bucket, account_id) = get_current_bucket_info().await?;
+    let scope = get_current_scope().await?;
 
     let _ = app.emit("sync-phase", "indexing");
 
@@ -190,7 +302,7 @@ pub async fn build_directory_tree(app: tauri::AppHandle) -> Result<(), String> {
     };
 
     // Build from DB instead of loading all files into memory
-    db::build_directory_tree_from_db(&bucket, &account_id, &[], Some(progress_callback))
+    db::build_directory_tree_from_db(&scope, &[], Some(progress_callback))
         .await
         .map_err(|e| format!("Failed to build directory tree: {}", e))?;
 
@@ -201,9 +313,9 @@ pub async fn build_directory_tree(app: tauri::AppHandle) -> Result<(), String> {
 
 #[tauri::command]
 pub async fn get_directory_node(path: String) -> Result<Option<DirectoryNodeResponse>, String> {
-    let (bucket, account_id) = get_current_bucket_info().await?;
+    let scope = get_current_scope().await?;
 
-    let node = db::get_directory_node(&bucket, &account_id, &path)
+    let node = db::get_directory_node(&scope, &path)
         .await
         .map_err(|e| format!("Failed to get directory node: {}", e))?;
 
@@ -212,9 +324,9 @@ pub async fn get_directory_node(path: String) -> Result<Option<DirectoryNodeResp
 
 #[tauri::command]
 pub async fn get_all_directory_nodes() -> Result<Vec<DirectoryNodeResponse>, String> {
-    let (bucket, account_id) = get_current_bucket_info().await?;
+    let scope = get_current_scope().await?;
 
-    let nodes = db::get_all_directory_nodes(&bucket, &account_id)
+    let nodes = db::get_all_directory_nodes(&scope)
         .await
         .map_err(|e| format!("Failed to get directory nodes: {}", e))?;
 
@@ -223,9 +335,9 @@ pub async fn get_all_directory_nodes() -> Result<Vec<DirectoryNodeResponse>, Str
 
 #[tauri::command]
 pub async fn clear_file_cache() -> Result<(), String> {
-    let (bucket, account_id) = get_current_bucket_info().await?;
+    let scope = get_current_scope().await?;
 
-    db::clear_file_cache(&bucket, &account_id)
+    db::clear_file_cache(&scope)
         .await
         .map_err(|e| format!("Failed to clear cache: {}", e))
 }
@@ -242,10 +354,10 @@ pub struct FolderContentsResponse {
 /// This is the cache equivalent of S3 ListObjectsV2 with delimiter="/"
 #[tauri::command]
 pub async fn get_folder_contents(prefix: Option<String>) -> Result<FolderContentsResponse, String> {
-    let (bucket, account_id) = get_current_bucket_info().await?;
+    let scope = get_current_scope().await?;
     let prefix_str = prefix.unwrap_or_default();
 
-    let result = db::get_folder_contents(&bucket, &account_id, &prefix_str)
+    let result = db::get_folder_contents(&scope, &prefix_str)
         .await
         .map_err(|e| format!("Failed to get folder contents: {}", e))?;
 
@@ -1,11 +1,11 @@
-use crate::db::{self, CachedFile};
+use crate::commands::delta_sync::{finish_delta_sync, SyncChangeSummary, SyncMode};
+use crate::commands::parallel_listing::{self, S3_LIST_SLOTS};
+use crate::commands::sync_manager::{self, SyncHandle};
+use crate::db::{self, CacheScope, CachedFile};
 use crate::providers::aws;
 use crate::providers::minio;
 use crate::r2;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashSet, VecDeque};
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::{Arc, LazyLock};
 use tauri::Emitter;
 
 // ============ Types ============
@@ -24,11 +24,30 @@ pub struct LazyListInput {
     pub force_path_style: Option<bool>,
     pub region: Option<String>,
     pub force_refresh: Option<bool>,
+    #[serde(default)]
+    pub credentials: aws::CredentialSource,
+    /// How a background sync applies its listing to the cache.
+    #[serde(default)]
+    pub sync_mode: SyncMode,
+}
+
+impl LazyListInput {
+    pub(crate) fn cache_scope(&self) -> CacheScope {
+        CacheScope::new(
+            self.provider.as_deref().unwrap_or("r2"),
+            self.endpoint_scheme.as_deref(),
+            self.endpoint_host.as_deref(),
+            &self.account_id,
+            &self.bucket,
+        )
+    }
 }
 
 // ============ Provider-Aware Client Factory ============
 
-async fn create_client_for_input(input: &LazyListInput) -> Result<aws_sdk_s3::Client, String> {
+pub(crate) async fn create_client_for_input(
+    input: &LazyListInput,
+) -> Result<aws_sdk_s3::Client, String> {
     let provider = input.provider.as_deref().unwrap_or("r2");
     match provider {
         "minio" | "rustfs" => {
@@ -39,9 +58,10 @@ async fn create_client_for_input(input: &LazyListInput) -> Result<aws_sdk_s3::Cl
                 endpoint_scheme: input
                     .endpoint_scheme
                     .clone()
-                    .unwrap_or_else(|| "http".into()),
+                    .unwrap_or_else(|| db::default_endpoint_scheme(provider).into()),
                 endpoint_host: input.endpoint_host.clone().unwrap_or_default(),
                 force_path_style: input.force_path_style.unwrap_or(true),
+                encryption: None,
             };
             minio::create_minio_client(&config)
                 .await
@@ -56,6 +76,9 @@ async fn create_client_for_input(input: &LazyListInput) -> Result<aws_sdk_s3::Cl
                 endpoint_scheme: input.endpoint_scheme.clone(),
                 endpoint_host: input.endpoint_host.clone(),
                 force_path_style: input.force_path_style.unwrap_or(false),
+                credentials: input.credentials.clone(),
+                encryption: None,
+                storage_class: None,
             };
             aws::create_aws_client(&config)
                 .await
@@ -67,6 +90,7 @@ async fn create_client_for_input(input: &LazyListInput) -> Result<aws_sdk_s3::Cl
                 bucket: input.bucket.clone(),
                 access_key_id: input.access_key_id.clone(),
                 secret_access_key: input.secret_access_key.clone(),
+                encryption: None,
             };
             r2::create_r2_client(&config)
                 .await
@@ -91,56 +115,18 @@ pub struct LazyFileItem {
     pub last_modified: String,
 }
 
-// ============ list_prefix Command ============
-
-/// Lazy-list a single prefix using delimiter="/".
-/// If cache is fresh (< 60s), serves from SQLite. Otherwise hits S3.
-#[tauri::command]
-pub async fn list_prefix(
-    input: LazyListInput,
-    app: tauri::AppHandle,
-) -> Result<LazyListResult, String> {
-    let bucket = &input.bucket;
-    let account_id = &input.account_id;
-    let prefix = &input.prefix;
-
-    // Check if prefix was recently synced (within 60 seconds)
-    let cached_time = db::prefix_sync::get_prefix_sync_time(bucket, account_id, prefix)
-        .await
-        .map_err(|e| format!("DB error: {}", e))?;
-
+// ============ Prefix Listing ============
+
+/// List the immediate children of `prefix` (delimiter "/"), skipping folder
+/// marker objects. Emits `folder-load-progress` for multi-page prefixes when
+/// given an app handle.
+pub(crate) async fn fetch_prefix_listing(
+    client: &aws_sdk_s3::Client,
+    scope: &CacheScope,
+    prefix: &str,
+    app: Option<&tauri::AppHandle>,
+) -> Result<(Vec<CachedFile>, Vec<String>), String> {
     let now = chrono::Utc::now().timestamp();
-    const STALE_THRESHOLD_SECS: i64 = 60;
-
-    if !input.force_refresh.unwrap_or(false) {
-        if let Some(synced_at) = cached_time {
-            if now - synced_at < STALE_THRESHOLD_SECS {
-                // Serve from cache
-                let contents = db::get_folder_contents(bucket, account_id, prefix)
-                    .await
-                    .map_err(|e| format!("DB error: {}", e))?;
-
-                return Ok(LazyListResult {
-                    files: contents
-                        .files
-                        .into_iter()
-                        .map(|f| LazyFileItem {
-                            name: f.name,
-                            key: f.key,
-                            size: f.size,
-                            last_modified: f.last_modified,
-                        })
-                        .collect(),
-                    folders: contents.folders,
-                    prefix: prefix.clone(),
-                    from_cache: true,
-                });
-            }
-        }
-    }
-
-    // Cache is stale or missing -- fetch from S3
-    let client = create_client_for_input(&input).await?;
 
     // Paginate with delimiter to get immediate children only
     let mut all_files: Vec<CachedFile> = Vec::new();
@@ -152,7 +138,7 @@ pub async fn list_prefix(
         let create_request = || {
             let mut request = client
                 .list_objects_v2()
-                .bucket(bucket)
+                .bucket(scope.bucket())
                 .delimiter("/")
                 .max_keys(1000);
 
@@ -168,7 +154,10 @@ pub async fn list_prefix(
         };
 
         let response = {
-            let _list_guard = S3_LIST_LOCK.lock().await;
+            let _list_slot = S3_LIST_SLOTS
+                .acquire()
+                .await
+                .map_err(|e| format!("S3 list failed: {}", e))?;
             match create_request().send().await {
                 Ok(response) => response,
                 Err(first_error) => create_request().send().await.map_err(|retry_error| {
@@ -191,8 +180,8 @@ pub async fn list_prefix(
                 }
                 let (parent_path, name) = db::parse_key(&key);
                 all_files.push(CachedFile {
-                    bucket: bucket.clone(),
-                    account_id: account_id.clone(),
+                    bucket: scope.bucket().to_string(),
+                    account_id: scope.account_id().to_string(),
                     key,
                     parent_path,
                     name,
@@ -202,6 +191,10 @@ pub async fn list_prefix(
                         .map(|dt| dt.to_string())
                         .unwrap_or_default(),
                     synced_at: now,
+                    storage_class: obj.storage_class().map(|class| class.as_str().to_string()),
+                    etag: obj.e_tag().map(str::to_string),
+                    content_type: None,
+                    checksum: None,
                 });
             }
         }
@@ -214,7 +207,7 @@ pub async fn list_prefix(
         }
 
         // Emit progress for multi-page prefixes
-        if page_count > 1 {
+        if let Some(app) = app.filter(|_| page_count > 1) {
             let _ = app.emit(
                 "folder-load-progress",
                 serde_json::json!({
@@ -231,22 +224,76 @@ pub async fn list_prefix(
         continuation_token = response.next_continuation_token().map(|s| s.to_string());
     }
 
+    Ok((all_files, all_folders))
+}
+
+// ============ list_prefix Command ============
+
+/// Lazy-list a single prefix using delimiter="/".
+/// If cache is fresh (< 60s), serves from SQLite. Otherwise hits S3.
+#[tauri::command]
+pub async fn list_prefix(
+    input: LazyListInput,
+    app: tauri::AppHandle,
+) -> Result<LazyListResult, String> {
+    let scope = input.cache_scope();
+    let prefix = &input.prefix;
+
+    // Check if prefix was recently synced (within 60 seconds)
+    let cached_time = db::prefix_sync::get_prefix_sync_time(&scope, prefix)
+        .await
+        .map_err(|e| format!("DB error: {}", e))?;
+
+    let now = chrono::Utc::now().timestamp();
+    const STALE_THRESHOLD_SECS: i64 = 60;
+
+    if !input.force_refresh.unwrap_or(false) {
+        if let Some(synced_at) = cached_time {
+            if now - synced_at < STALE_THRESHOLD_SECS {
+                // Serve from cache
+                let contents = db::get_folder_contents(&scope, prefix)
+                    .await
+                    .map_err(|e| format!("DB error: {}", e))?;
+
+                return Ok(LazyListResult {
+                    files: contents
+                        .files
+                        .into_iter()
+                        .map(|f| LazyFileItem {
+                            name: f.name,
+                            key: f.key,
+                            size: f.size,
+                            last_modified: f.last_modified,
+                        })
+                        .collect(),
+                    folders: contents.folders,
+                    prefix: prefix.clone(),
+                    from_cache: true,
+                });
+            }
+        }
+    }
+
+    // Cache is stale or missing -- fetch from S3
+    let client = create_client_for_input(&input).await?;
+    let (all_files, all_folders) =
+        fetch_prefix_listing(&client, &scope, prefix, Some(&app)).await?;
+
     // Cache results in SQLite
-    db::upsert_prefix_files(bucket, account_id, prefix, &all_files)
+    db::upsert_prefix_files(&scope, prefix, &all_files)
         .await
         .map_err(|e| format!("Failed to cache files: {}", e))?;
 
     // Upsert folder entries into directory_tree for this prefix
     for folder in &all_folders {
-        db::ensure_directory_node(bucket, account_id, folder)
+        db::ensure_directory_node(&scope, folder)
             .await
             .map_err(|e| format!("Failed to upsert directory node: {}", e))?;
     }
 
     // Record sync time
     db::prefix_sync::set_prefix_sync_time(
-        bucket,
-        account_id,
+        &scope,
         prefix,
         all_files.len() as i32,
         all_folders.len() as i32,
@@ -274,21 +321,11 @@ pub async fn list_prefix(
 
 // ============ Background Sync (Task 3) ============
 
-// Global cancellation token for background sync (one per app)
-static BACKGROUND_CANCEL: LazyLock<Arc<AtomicBool>> =
-    LazyLock::new(|| Arc::new(AtomicBool::new(false)));
-static S3_LIST_LOCK: LazyLock<tokio::sync::Mutex<()>> =
-    LazyLock::new(|| tokio::sync::Mutex::new(()));
-static BACKGROUND_RUN_ID: AtomicU64 = AtomicU64::new(0);
-static BACKGROUND_SYNC_LOCK: LazyLock<tokio::sync::Mutex<()>> =
-    LazyLock::new(|| tokio::sync::Mutex::new(()));
-
-fn is_background_run_active(run_id: u64) -> bool {
-    BACKGROUND_RUN_ID.load(Ordering::SeqCst) == run_id && !BACKGROUND_CANCEL.load(Ordering::SeqCst)
-}
-
 #[derive(Debug, Clone, Serialize)]
 pub struct BackgroundSyncProgress {
+    pub job_id: u64,
+    pub bucket: String,
+    pub account_id: String,
     pub objects_fetched: usize,
     pub bytes_fetched: i64,
     pub estimated_total: Option<usize>,
@@ -298,68 +335,108 @@ pub struct BackgroundSyncProgress {
 
 #[derive(Debug, Clone, Serialize)]
 pub struct BackgroundSyncResult {
+    pub job_id: u64,
+    pub bucket: String,
+    pub account_id: String,
     pub total_objects: usize,
     pub total_bytes: i64,
     pub cancelled: bool,
+    /// What changed, for syncs applied as a delta.
+    pub changes: Option<SyncChangeSummary>,
+}
+
+/// Emitted as `sync-job-error` alongside the plain `background-sync-error`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncJobError {
+    pub job_id: u64,
+    pub bucket: String,
+    pub account_id: String,
+    pub error: String,
 }
 
+/// Start a background sync of the input's bucket and return its job id.
+/// A sync already running for the same bucket is cancelled and replaced;
+/// syncs of other buckets keep running.
 #[tauri::command]
 pub async fn start_background_sync(
     input: LazyListInput,
     app: tauri::AppHandle,
-) -> Result<(), String> {
-    let run_id = BACKGROUND_RUN_ID.fetch_add(1, Ordering::SeqCst) + 1;
-
-    // Reset cancellation flag
-    BACKGROUND_CANCEL.store(false, Ordering::SeqCst);
+) -> Result<u64, String> {
+    let provider = input.provider.as_deref().unwrap_or("r2");
+    let handle = sync_manager::register_sync(input.cache_scope(), provider);
+    let job_id = handle.id;
+    let bucket = input.bucket.clone();
+    let account_id = input.account_id.clone();
 
     // Spawn background task -- returns immediately
     tokio::spawn(async move {
-        let result = run_background_sync(input, app.clone(), run_id).await;
-        let is_active = is_background_run_active(run_id);
+        let result = run_background_sync(input, app.clone(), handle.clone()).await;
+        let is_active = handle.is_active();
+        handle.finish();
 
         match result {
             Ok(sync_result) if is_active && !sync_result.cancelled => {
                 let _ = app.emit("background-sync-complete", sync_result);
             }
             Err(e) if is_active => {
+                let _ = app.emit(
+                    "sync-job-error",
+                    SyncJobError {
+                        job_id: handle.id,
+                        bucket,
+                        account_id,
+                        error: e.clone(),
+                    },
+                );
                 let _ = app.emit("background-sync-error", e);
             }
             _ => {}
         }
     });
 
-    Ok(())
+    Ok(job_id)
 }
 
 async fn run_background_sync(
     input: LazyListInput,
     app: tauri::AppHandle,
-    run_id: u64,
+    handle: SyncHandle,
 ) -> Result<BackgroundSyncResult, String> {
-    let _sync_guard = BACKGROUND_SYNC_LOCK.lock().await;
+    // Wait for earlier runs of this bucket and for a free slot
+    let _turn = handle.wait_turn().await;
 
     let bucket = input.bucket.clone();
     let account_id = input.account_id.clone();
+    let scope = input.cache_scope();
 
-    if !is_background_run_active(run_id) {
+    if !handle.is_active() {
         return Ok(BackgroundSyncResult {
+            job_id: handle.id,
+            bucket: bucket.clone(),
+            account_id: account_id.clone(),
             total_objects: 0,
             total_bytes: 0,
             cancelled: true,
+            changes: None,
         });
     }
 
+    let delta = input.sync_mode.is_delta(&scope).await?;
+
     // Begin sync (staging table)
-    db::begin_sync(&bucket, &account_id)
+    db::begin_sync(&scope)
         .await
         .map_err(|e| format!("Failed to begin sync: {}", e))?;
 
-    if !is_background_run_active(run_id) {
+    if !handle.is_active() {
         return Ok(BackgroundSyncResult {
+            job_id: handle.id,
+            bucket: bucket.clone(),
+            account_id: account_id.clone(),
             total_objects: 0,
             total_bytes: 0,
             cancelled: true,
+            changes: None,
         });
     }
 
@@ -368,17 +445,17 @@ async fn run_background_sync(
 
     // Spawn store task
     let (tx, mut rx) = tokio::sync::mpsc::channel::<Vec<CachedFile>>(8);
-    let store_bucket = bucket.clone();
-    let store_account_id = account_id.clone();
+    let store_scope = scope.clone();
+    let store_job = handle.clone();
     let store_handle = tokio::spawn(async move {
         let mut stored_count: usize = 0;
         while let Some(batch) = rx.recv().await {
-            if !is_background_run_active(run_id) {
+            if !store_job.is_active() {
                 break;
             }
 
             let batch_len = batch.len();
-            db::store_file_batch(&store_bucket, &store_account_id, &batch)
+            db::store_file_batch(&store_scope, &batch)
                 .await
                 .map_err(|e| format!("Failed to store files: {}", e))?;
             stored_count += batch_len;
@@ -386,152 +463,85 @@ async fn run_background_sync(
         Ok::<usize, String>(stored_count)
     });
 
-    // Fetch loop with progress emission
+    // List with parallel listers; progress and storing stay here
     let mut fetched_count: usize = 0;
     let mut fetched_bytes: i64 = 0;
     let mut folder_keys: Vec<String> = Vec::new();
     let start_time = std::time::Instant::now();
     let use_delimiter_crawl = input.provider.as_deref() == Some("rustfs");
 
-    let mut pending_prefixes: VecDeque<String> = VecDeque::from([String::new()]);
-    let mut seen_prefixes: HashSet<String> = HashSet::from([String::new()]);
-
-    while let Some(current_prefix) = pending_prefixes.pop_front() {
-        let mut continuation_token: Option<String> = None;
-
-        loop {
-            if !is_background_run_active(run_id) {
-                drop(tx);
-                return Ok(BackgroundSyncResult {
-                    total_objects: fetched_count,
-                    total_bytes: fetched_bytes,
-                    cancelled: true,
-                });
-            }
-
-            let create_request = || {
-                let mut request = client.list_objects_v2().bucket(&bucket).max_keys(1000);
-
-                if use_delimiter_crawl {
-                    request = request.delimiter("/");
-                    if !current_prefix.is_empty() {
-                        request = request.prefix(&current_prefix);
-                    }
-                }
-
-                if let Some(token) = &continuation_token {
-                    request = request.continuation_token(token);
-                }
-
-                request
-            };
-
-            let response = {
-                let _list_guard = S3_LIST_LOCK.lock().await;
-                match create_request().send().await {
-                    Ok(response) => response,
-                    Err(first_error) => create_request().send().await.map_err(|retry_error| {
-                        format!(
-                            "S3 list failed after retry: {}; first attempt: {}",
-                            retry_error, first_error
-                        )
-                    })?,
-                }
-            };
-
-            if !is_background_run_active(run_id) {
-                drop(tx);
-                return Ok(BackgroundSyncResult {
-                    total_objects: fetched_count,
-                    total_bytes: fetched_bytes,
-                    cancelled: true,
-                });
-            }
-
-            let is_truncated = response.is_truncated().unwrap_or(false);
-            let next_token = response.next_continuation_token().map(|s| s.to_string());
-            let now = chrono::Utc::now().timestamp();
-
-            let mut batch: Vec<CachedFile> = Vec::new();
-            for obj in response.contents() {
-                if let Some(key) = obj.key() {
-                    let key = key.to_string();
-                    if key.ends_with('/') {
-                        folder_keys.push(key);
-                    } else {
-                        let (parent_path, name) = db::parse_key(&key);
-                        batch.push(CachedFile {
-                            bucket: bucket.clone(),
-                            account_id: account_id.clone(),
-                            key,
-                            parent_path,
-                            name,
-                            size: obj.size().unwrap_or(0),
-                            last_modified: obj
-                                .last_modified()
-                                .map(|dt| dt.to_string())
-                                .unwrap_or_default(),
-                            synced_at: now,
-                        });
-                    }
-                }
-            }
-
-            if use_delimiter_crawl {
-                for cp in response.common_prefixes() {
-                    if let Some(prefix) = cp.prefix() {
-                        let prefix = prefix.to_string();
-                        folder_keys.push(prefix.clone());
-                        if seen_prefixes.insert(prefix.clone()) {
-                            pending_prefixes.push_back(prefix);
-                        }
-                    }
-                }
-            }
+    let mut listing = parallel_listing::start_bucket_listing(
+        client,
+        bucket.clone(),
+        account_id.clone(),
+        use_delimiter_crawl,
+        handle.clone(),
+    );
+
+    let emit_progress = |fetched_count: usize, fetched_bytes: i64, complete: bool| {
+        // Calculate speed
+        let elapsed = start_time.elapsed().as_secs_f64().max(0.001);
+        let speed = fetched_count as f64 / elapsed;
+
+        handle.record_progress(fetched_count, fetched_bytes);
+        let _ = app.emit(
+            "background-sync-progress",
+            BackgroundSyncProgress {
+                job_id: handle.id,
+                bucket: bucket.clone(),
+                account_id: account_id.clone(),
+                objects_fetched: fetched_count,
+                bytes_fetched: fetched_bytes,
+                estimated_total: complete.then_some(fetched_count),
+                is_running: true,
+                speed,
+            },
+        );
+    };
 
-            fetched_count += batch.len();
-            fetched_bytes += batch.iter().map(|f| f.size).sum::<i64>();
+    while let Some(page) = listing.pages.recv().await {
+        let page = page?;
+
+        if !handle.is_active() {
+            drop(tx);
+            return Ok(BackgroundSyncResult {
+                job_id: handle.id,
+                bucket: bucket.clone(),
+                account_id: account_id.clone(),
+                total_objects: fetched_count,
+                total_bytes: fetched_bytes,
+                cancelled: true,
+                changes: None,
+            });
+        }
 
-            // Calculate speed
-            let elapsed = start_time.elapsed().as_secs_f64().max(0.001);
-            let speed = fetched_count as f64 / elapsed;
+        folder_keys.extend(page.folder_keys);
+        fetched_count += page.files.len();
+        fetched_bytes += page.files.iter().map(|f| f.size).sum::<i64>();
 
-            // Emit progress every page
-            let _ = app.emit(
-                "background-sync-progress",
-                BackgroundSyncProgress {
-                    objects_fetched: fetched_count,
-                    bytes_fetched: fetched_bytes,
-                    estimated_total: {
-                        let has_pending_prefixes =
-                            use_delimiter_crawl && !pending_prefixes.is_empty();
-                        if is_truncated || has_pending_prefixes {
-                            None
-                        } else {
-                            Some(fetched_count)
-                        }
-                    },
-                    is_running: true,
-                    speed,
-                },
-            );
+        // Emit progress every page
+        emit_progress(fetched_count, fetched_bytes, false);
 
-            if !batch.is_empty() {
-                tx.send(batch)
-                    .await
-                    .map_err(|_| "Store task crashed".to_string())?;
-            }
-
-            if !is_truncated {
-                break;
-            }
-            continuation_token = next_token;
+        if !page.files.is_empty() {
+            tx.send(page.files)
+                .await
+                .map_err(|_| "Store task crashed".to_string())?;
         }
+    }
 
-        if !use_delimiter_crawl {
-            break;
-        }
+    if !handle.is_active() {
+        drop(tx);
+        return Ok(BackgroundSyncResult {
+            job_id: handle.id,
+            bucket: bucket.clone(),
+            account_id: account_id.clone(),
+            total_objects: fetched_count,
+            total_bytes: fetched_bytes,
+            cancelled: true,
+            changes: None,
+        });
     }
+    emit_progress(fetched_count, fetched_bytes, true);
 
     // Wait for store task
     drop(tx);
@@ -540,42 +550,67 @@ async fn run_background_sync(
         .map_err(|e| format!("Store task panicked: {}", e))?
         .map_err(|e| format!("Store failed: {}", e))?;
 
-    if !is_background_run_active(run_id) {
+    if !handle.is_active() {
         return Ok(BackgroundSyncResult {
+            job_id: handle.id,
+            bucket: bucket.clone(),
+            account_id: account_id.clone(),
             total_objects: fetched_count,
             total_bytes: fetched_bytes,
             cancelled: true,
+            changes: None,
+        });
+    }
+
+    if delta {
+        let changes = finish_delta_sync(&app, &scope, stored_count, &folder_keys).await?;
+        return Ok(BackgroundSyncResult {
+            job_id: handle.id,
+            bucket: bucket.clone(),
+            account_id: account_id.clone(),
+            total_objects: fetched_count,
+            total_bytes: fetched_bytes,
+            cancelled: false,
+            changes: Some(changes),
         });
     }
 
     // Finish sync (swap staging -> live)
-    db::finish_sync(&bucket, &account_id, stored_count)
+    db::finish_sync(&scope, stored_count)
         .await
         .map_err(|e| format!("Failed to finish sync: {}", e))?;
 
-    if !is_background_run_active(run_id) {
+    if !handle.is_active() {
         return Ok(BackgroundSyncResult {
+            job_id: handle.id,
+            bucket: bucket.clone(),
+            account_id: account_id.clone(),
             total_objects: fetched_count,
             total_bytes: fetched_bytes,
             cancelled: true,
+            changes: None,
         });
     }
 
     // Build directory tree
-    db::build_directory_tree_from_db(&bucket, &account_id, &folder_keys, None::<fn(usize, usize)>)
+    db::build_directory_tree_from_db(&scope, &folder_keys, None::<fn(usize, usize)>)
         .await
         .map_err(|e| format!("Failed to build tree: {}", e))?;
 
     Ok(BackgroundSyncResult {
+        job_id: handle.id,
+        bucket: bucket.clone(),
+        account_id: account_id.clone(),
         total_objects: fetched_count,
         total_bytes: fetched_bytes,
         cancelled: false,
+        changes: None,
     })
 }
 
+/// Cancel background sync `job_id`, or all of them when none is given.
 #[tauri::command]
-pub async fn cancel_background_sync() -> Result<(), String> {
-    BACKGROUND_RUN_ID.fetch_add(1, Ordering::SeqCst);
-    BACKGROUND_CANCEL.store(true, Ordering::SeqCst);
+pub async fn cancel_background_sync(job_id: Option<u64>) -> Result<(), String> {
+    sync_manager::cancel_syncs(job_id);
     Ok(())
 }
@@ -1,11 +1,20 @@
+use crate::client_encryption::ciphertext_len;
+use crate::commands::batch_delete::{
+    delete_progress_emitter, finish_batch_delete, skip_locked_keys, BatchDeleteResult,
+};
 use crate::commands::batch_move::{
-    fallback_batch_id, run_batch_move, BatchMoveResult, MoveOperation,
+    copy_progress_emitter, fallback_batch_id, run_batch_move, BatchMoveResult, MoveOperation,
 };
+use crate::commands::bucket_encryption::resolve_bucket_encryption;
 use crate::commands::cache_events::{get_unique_parent_paths, CacheUpdatedEvent};
-use crate::commands::delete_cache::{update_cache_after_batch_delete, update_cache_after_delete};
+use crate::commands::client_encryption::resolve_upload_encryption;
+use crate::commands::delete_cache::update_cache_after_delete;
+use crate::commands::delta_sync::{finish_delta_sync, SyncChangeSummary, SyncMode};
 use crate::commands::move_cache::{update_cache_after_batch_move, update_cache_after_move};
+use crate::commands::shared_links::{record_share, ShareOptions, ShareTarget};
 use crate::commands::upload_cache::update_cache_after_upload;
-use crate::db::{self, CachedFile};
+use crate::db::{self, CacheScope, CachedFile, CachedObjectMeta};
+use crate::providers::config::ProviderConfig;
 use crate::providers::minio;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -20,6 +29,26 @@ pub struct MinioConfigInput {
     pub endpoint_scheme: String,
     pub endpoint_host: String,
     pub force_path_style: bool,
+    /// Provider bucket settings are looked up under; RustFS commands reuse
+    /// the MinIO ones with `"rustfs"`.
+    #[serde(skip, default = "default_provider")]
+    pub provider: &'static str,
+}
+
+fn default_provider() -> &'static str {
+    "minio"
+}
+
+impl MinioConfigInput {
+    fn cache_scope(&self) -> CacheScope {
+        CacheScope::new(
+            self.provider,
+            Some(&self.endpoint_scheme),
+            Some(&self.endpoint_host),
+            &self.account_id,
+            &self.bucket,
+        )
+    }
 }
 
 impl From<MinioConfigInput> for minio::MinioConfig {
@@ -31,6 +60,7 @@ impl From<MinioConfigInput> for minio::MinioConfig {
             endpoint_scheme: input.endpoint_scheme,
             endpoint_host: input.endpoint_host,
             force_path_style: input.force_path_style,
+            encryption: None,
         }
     }
 }
@@ -48,6 +78,8 @@ pub struct ListObjectsInput {
 pub struct SyncResult {
     pub count: i32,
     pub timestamp: i64,
+    /// What changed, for syncs applied as a delta.
+    pub changes: Option<SyncChangeSummary>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -78,6 +110,7 @@ pub async fn list_minio_buckets(
         endpoint_scheme,
         endpoint_host,
         force_path_style,
+        encryption: None,
     };
 
     minio::list_buckets(&config)
@@ -126,29 +159,31 @@ pub async fn list_all_minio_objects(
 #[tauri::command]
 pub async fn sync_minio_bucket(
     config: MinioConfigInput,
+    mode: Option<SyncMode>,
     app: tauri::AppHandle,
 ) -> Result<SyncResult, String> {
     let account_id = config.account_id.clone();
+    let scope = config.cache_scope();
     let minio_config: minio::MinioConfig = config.into();
     let bucket = minio_config.bucket.clone();
     let now = chrono::Utc::now().timestamp();
+    let delta = mode.unwrap_or_default().is_delta(&scope).await?;
 
     let _ = app.emit("sync-phase", "fetching");
 
-    db::begin_sync(&bucket, &account_id)
+    db::begin_sync(&scope)
         .await
         .map_err(|e| format!("Failed to clear cache: {}", e))?;
 
     // Spawn dedicated store task
     let (tx, mut rx) = tokio::sync::mpsc::channel::<Vec<CachedFile>>(8);
-    let store_bucket = bucket.clone();
-    let store_account_id = account_id.clone();
+    let store_scope = scope.clone();
     let store_app = app.clone();
     let store_handle = tokio::spawn(async move {
         let mut stored_count: usize = 0;
         while let Some(batch) = rx.recv().await {
             let batch_len = batch.len();
-            db::store_file_batch(&store_bucket, &store_account_id, &batch)
+            db::store_file_batch(&store_scope, &batch)
                 .await
                 .map_err(|e| format!("Failed to store files: {}", e))?;
             stored_count += batch_len;
@@ -202,6 +237,10 @@ pub async fn sync_minio_bucket(
                             .map(|dt| dt.to_string())
                             .unwrap_or_default(),
                         synced_at: now,
+                        storage_class: obj.storage_class().map(|class| class.as_str().to_string()),
+                        etag: obj.e_tag().map(str::to_string),
+                        content_type: None,
+                        checksum: None,
                     });
                 }
             }
@@ -229,26 +268,34 @@ pub async fn sync_minio_bucket(
         .map_err(|e| format!("Store task panicked: {}", e))?
         .map_err(|e| format!("Store failed: {}", e))?;
 
-    db::finish_sync(&bucket, &account_id, stored_count)
-        .await
-        .map_err(|e| format!("Failed to update sync metadata: {}", e))?;
+    let changes = if delta {
+        let _ = app.emit("sync-phase", "indexing");
+        Some(finish_delta_sync(&app, &scope, stored_count, &folder_keys).await?)
+    } else {
+        db::finish_sync(&scope, stored_count)
+            .await
+            .map_err(|e| format!("Failed to update sync metadata: {}", e))?;
 
-    let _ = app.emit("sync-phase", "indexing");
+        // Build directory tree from DB
+        let _ = app.emit("sync-phase", "indexing");
 
-    let app_clone = app.clone();
-    let indexing_callback = move |current: usize, total: usize| {
-        let _ = app_clone.emit("indexing-progress", IndexingProgress { current, total });
-    };
+        let app_clone = app.clone();
+        let indexing_callback = move |current: usize, total: usize| {
+            let _ = app_clone.emit("indexing-progress", IndexingProgress { current, total });
+        };
 
-    db::build_directory_tree_from_db(&bucket, &account_id, &folder_keys, Some(indexing_callback))
-        .await
-        .map_err(|e| format!("Failed to build directory tree: {}", e))?;
+        db::build_directory_tree_from_db(&scope, &folder_keys, Some(indexing_callback))
+            .await
+            .map_err(|e| format!("Failed to build directory tree: {}", e))?;
+        None
+    };
 
     let _ = app.emit("sync-phase", "complete");
 
     Ok(SyncResult {
         count: stored_count as i32,
         timestamp: now,
+        changes,
     })
 }
 
@@ -283,8 +330,7 @@ pub async fn delete_minio_object(
     key: String,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
-    let bucket = config.bucket.clone();
-    let account_id = config.account_id.clone();
+    let scope = config.cache_scope();
     let minio_config: minio::MinioConfig = config.into();
 
     minio::delete_object(&minio_config, &key)
@@ -292,90 +338,31 @@ pub async fn delete_minio_object(
         .map_err(|e| format!("Failed to delete object: {}", e))?;
 
     // Update cache and emit events (including paths-removed if any folders became empty)
-    update_cache_after_delete(&app, &bucket, &account_id, &key).await?;
+    update_cache_after_delete(&app, &scope, &key).await?;
 
     Ok(())
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub struct BatchDeleteProgress {
-    pub completed: usize,
-    pub total: usize,
-    pub failed: usize,
-}
-
-#[derive(Debug, Clone, Serialize)]
-pub struct BatchDeleteResult {
-    pub deleted: usize,
-    pub failed: usize,
-    pub errors: Vec<String>,
-}
-
 #[tauri::command]
 pub async fn batch_delete_minio_objects(
     config: MinioConfigInput,
     keys: Vec<String>,
     app: tauri::AppHandle,
 ) -> Result<BatchDeleteResult, String> {
-    let bucket = config.bucket.clone();
-    let account_id = config.account_id.clone();
+    let scope = config.cache_scope();
     let minio_config: minio::MinioConfig = config.into();
-    let total = keys.len();
-
-    if total == 0 {
-        return Ok(BatchDeleteResult {
-            deleted: 0,
-            failed: 0,
-            errors: vec![],
-        });
-    }
-
-    let mut completed = 0;
-    let mut failed = 0;
-    let mut errors: Vec<String> = vec![];
-    let mut deleted_keys: Vec<String> = vec![];
-
-    const BATCH_SIZE: usize = 1000;
-
-    for chunk in keys.chunks(BATCH_SIZE) {
-        let batch_keys: Vec<String> = chunk.to_vec();
-        let batch_count = batch_keys.len();
-
-        match minio::delete_objects(&minio_config, batch_keys.clone()).await {
-            Ok(_) => {
-                completed += batch_count;
-                deleted_keys.extend(batch_keys);
-            }
-            Err(e) => {
-                failed += batch_count;
-                errors.push(format!("Batch delete failed: {}", e));
-            }
-        }
-
-        let _ = app.emit(
-            "batch-delete-progress",
-            BatchDeleteProgress {
-                completed,
-                total,
-                failed,
-            },
-        );
+    if keys.is_empty() {
+        return Ok(BatchDeleteResult::empty());
     }
 
-    // Update cache and emit events (including paths-removed if any folders became empty)
-    if !deleted_keys.is_empty() {
-        if let Err(e) =
-            update_cache_after_batch_delete(&app, &bucket, &account_id, &deleted_keys).await
-        {
-            errors.push(e);
-        }
-    }
+    let (keys, skipped) =
+        skip_locked_keys(ProviderConfig::Minio(minio_config.clone()), keys).await?;
+    let on_progress = delete_progress_emitter(&app, keys.len());
+    let outcome = minio::delete_objects(&minio_config, keys, Some(on_progress))
+        .await
+        .map_err(|e| format!("Batch delete failed: {}", e))?;
 
-    Ok(BatchDeleteResult {
-        deleted: completed,
-        failed,
-        errors,
-    })
+    Ok(finish_batch_delete(&app, &scope, outcome, skipped).await)
 }
 
 #[tauri::command]
@@ -387,14 +374,22 @@ pub async fn rename_minio_object(
 ) -> Result<(), String> {
     let bucket = config.bucket.clone();
     let account_id = config.account_id.clone();
-    let minio_config: minio::MinioConfig = config.into();
-
-    minio::rename_object(&minio_config, &old_key, &new_key)
-        .await
-        .map_err(|e| format!("Failed to rename object: {}", e))?;
+    let provider = config.provider;
+    let scope = config.cache_scope();
+    let mut minio_config: minio::MinioConfig = config.into();
+    minio_config.encryption = resolve_bucket_encryption(provider, &account_id, &bucket).await?;
+
+    minio::rename_object(
+        &minio_config,
+        &old_key,
+        &new_key,
+        Some(copy_progress_emitter(&app, &old_key)),
+    )
+    .await
+    .map_err(|e| format!("Failed to rename object: {}", e))?;
 
     // Update cache and emit events (including paths-created/removed)
-    update_cache_after_move(&app, &bucket, &account_id, &old_key, &new_key).await?;
+    update_cache_after_move(&app, &scope, &old_key, &new_key).await?;
 
     Ok(())
 }
@@ -408,13 +403,18 @@ pub async fn batch_move_minio_objects(
 ) -> Result<BatchMoveResult, String> {
     let bucket = config.bucket.clone();
     let account_id = config.account_id.clone();
-    let minio_config: minio::MinioConfig = config.into();
+    let provider = config.provider;
+    let scope = config.cache_scope();
+    let mut minio_config: minio::MinioConfig = config.into();
+    minio_config.encryption = resolve_bucket_encryption(provider, &account_id, &bucket).await?;
     let batch_id = batch_id.unwrap_or_else(fallback_batch_id);
 
+    let progress_app = app.clone();
     let rename = move |op: MoveOperation| {
         let config = minio_config.clone();
+        let on_progress = copy_progress_emitter(&progress_app, &op.old_key);
         async move {
-            minio::rename_object(&config, &op.old_key, &op.new_key)
+            minio::rename_object(&config, &op.old_key, &op.new_key, Some(on_progress))
                 .await
                 .map_err(|e| e.to_string())
         }
@@ -424,9 +424,7 @@ pub async fn batch_move_minio_objects(
 
     let mut errors = outcome.errors;
     if !outcome.successful.is_empty() {
-        if let Err(e) =
-            update_cache_after_batch_move(&app, &bucket, &account_id, &outcome.successful).await
-        {
+        if let Err(e) = update_cache_after_batch_move(&app, &scope, &outcome.successful).await {
             errors.push(e);
         }
     }
@@ -447,13 +445,36 @@ pub async fn generate_minio_signed_url(
     config: MinioConfigInput,
     key: String,
     expires_in: Option<u64>,
+    share: Option<ShareOptions>,
 ) -> Result<String, String> {
-    let minio_config: minio::MinioConfig = config.into();
+    let account_id = config.account_id.clone();
+    let access_key_id = config.access_key_id.clone();
+    let provider = config.provider;
+    let mut minio_config: minio::MinioConfig = config.into();
+    minio_config.encryption =
+        resolve_bucket_encryption(provider, &account_id, &minio_config.bucket).await?;
     let expires_in_secs = expires_in.unwrap_or(3600);
 
-    minio::generate_presigned_url(&minio_config, &key, expires_in_secs)
+    let url = minio::generate_presigned_url(&minio_config, &key, expires_in_secs)
         .await
-        .map_err(|e| format!("Failed to generate signed URL: {}", e))
+        .map_err(|e| format!("Failed to generate signed URL: {}", e))?;
+
+    record_share(
+        share,
+        ShareTarget {
+            provider: "minio",
+            account_id: &account_id,
+            bucket: &minio_config.bucket,
+            signing_key_id: &access_key_id,
+        },
+        &key,
+        "GET",
+        &url,
+        expires_in_secs,
+    )
+    .await;
+
+    Ok(url)
 }
 
 #[tauri::command]
@@ -466,7 +487,10 @@ pub async fn upload_minio_content(
 ) -> Result<String, String> {
     let bucket = config.bucket.clone();
     let account_id = config.account_id.clone();
-    let minio_config: minio::MinioConfig = config.into();
+    let provider = config.provider;
+    let scope = config.cache_scope();
+    let mut minio_config: minio::MinioConfig = config.into();
+    minio_config.encryption = resolve_bucket_encryption(provider, &account_id, &bucket).await?;
 
     let content_bytes = content.into_bytes();
     let new_size = content_bytes.len() as i64;
@@ -477,21 +501,21 @@ pub async fn upload_minio_content(
 
     let last_modified = chrono::Utc::now().to_rfc3339();
 
-    let (size_delta, is_new_file) =
-        db::update_cached_file(&bucket, &account_id, &key, new_size, &last_modified)
-            .await
-            .map_err(|e| format!("Failed to update file cache: {}", e))?;
+    let (size_delta, is_new_file) = db::update_cached_file(&scope, &key, new_size, &last_modified)
+        .await
+        .map_err(|e| format!("Failed to update file cache: {}", e))?;
+    let meta = CachedObjectMeta {
+        etag: (!etag.is_empty()).then(|| etag.clone()),
+        content_type,
+        ..Default::default()
+    };
+    db::set_cached_object_meta(&scope, &key, &meta)
+        .await
+        .map_err(|e| format!("Failed to update file cache: {}", e))?;
 
-    db::update_directory_tree_for_file(
-        &bucket,
-        &account_id,
-        &key,
-        size_delta,
-        &last_modified,
-        is_new_file,
-    )
-    .await
-    .map_err(|e| format!("Failed to update directory tree: {}", e))?;
+    db::update_directory_tree_for_file(&scope, &key, size_delta, &last_modified, is_new_file)
+        .await
+        .map_err(|e| format!("Failed to update directory tree: {}", e))?;
 
     let _ = app.emit(
         "cache-updated",
@@ -543,8 +567,49 @@ pub async fn upload_minio_file(
         endpoint_scheme,
         endpoint_host,
         force_path_style,
+        encryption: None,
     };
 
+    upload_file_to(
+        "minio",
+        app,
+        task_id,
+        file_path,
+        key,
+        content_type,
+        account_id,
+        config,
+    )
+    .await
+}
+
+/// Upload a local file to a MinIO-compatible bucket, applying the bucket's
+/// encryption settings saved under `provider`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn upload_file_to(
+    provider: &'static str,
+    app: tauri::AppHandle,
+    task_id: String,
+    file_path: String,
+    key: String,
+    content_type: Option<String>,
+    account_id: String,
+    mut config: minio::MinioConfig,
+) -> Result<UploadResult, String> {
+    let (encryption, client_key) =
+        match resolve_upload_encryption(provider, &account_id, &config.bucket).await {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                return Ok(UploadResult {
+                    task_id,
+                    success: false,
+                    error: Some(e),
+                    upload_id: None,
+                })
+            }
+        };
+    config.encryption = encryption;
+
     let path = PathBuf::from(&file_path);
     if !path.exists() {
         return Ok(UploadResult {
@@ -585,6 +650,7 @@ pub async fn upload_minio_file(
         &key,
         &path,
         content_type.as_deref(),
+        client_key.as_ref(),
         Some(progress_callback),
     )
     .await;
@@ -601,14 +667,30 @@ pub async fn upload_minio_file(
                 },
             );
 
+            // Client-side encrypted objects are stored larger than the source file
+            let stored_size = match client_key {
+                Some(_) => ciphertext_len(file_size),
+                None => file_size,
+            };
             let last_modified = chrono::Utc::now().to_rfc3339();
+            let scope = CacheScope::new(
+                provider,
+                Some(&config.endpoint_scheme),
+                Some(&config.endpoint_host),
+                &account_id,
+                &config.bucket,
+            );
+            let meta = CachedObjectMeta {
+                content_type,
+                ..Default::default()
+            };
             if let Err(err) = update_cache_after_upload(
                 &app,
-                &config.bucket,
-                &account_id,
+                &scope,
                 &key,
-                file_size as i64,
+                stored_size as i64,
                 &last_modified,
+                &meta,
             )
             .await
             {
@@ -1,7 +1,7 @@
 use crate::commands::cache_events::{
     get_unique_parent_paths, CacheUpdatedEvent, PathsCreatedEvent, PathsRemovedEvent,
 };
-use crate::db;
+use crate::db::{self, CacheScope};
 use std::collections::HashSet;
 use tauri::{AppHandle, Emitter};
 
@@ -9,8 +9,7 @@ use tauri::{AppHandle, Emitter};
 /// Handles file cache, directory tree updates, and emits appropriate events.
 pub(crate) async fn update_cache_after_move(
     app: &AppHandle,
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     old_key: &str,
     new_key: &str,
 ) -> Result<(), String> {
@@ -18,20 +17,14 @@ pub(crate) async fn update_cache_after_move(
         return Ok(());
     }
 
-    if let Some((size, last_modified)) = db::move_cached_file(bucket, account_id, old_key, new_key)
+    if let Some((size, last_modified)) = db::move_cached_file(scope, old_key, new_key)
         .await
         .map_err(|e| format!("Failed to update file cache: {}", e))?
     {
-        let move_result = db::update_directory_tree_for_move(
-            bucket,
-            account_id,
-            old_key,
-            new_key,
-            size,
-            &last_modified,
-        )
-        .await
-        .map_err(|e| format!("Failed to update directory tree: {}", e))?;
+        let move_result =
+            db::update_directory_tree_for_move(scope, old_key, new_key, size, &last_modified)
+                .await
+                .map_err(|e| format!("Failed to update directory tree: {}", e))?;
 
         if !move_result.removed_paths.is_empty() {
             let _ = app.emit(
@@ -68,8 +61,7 @@ pub(crate) async fn update_cache_after_move(
 /// Handles file cache, directory tree updates, and emits appropriate events.
 pub(crate) async fn update_cache_after_batch_move(
     app: &AppHandle,
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     operations: &[(String, String)],
 ) -> Result<(), String> {
     if operations.is_empty() {
@@ -85,21 +77,14 @@ pub(crate) async fn update_cache_after_batch_move(
             continue;
         }
 
-        if let Some((size, last_modified)) =
-            db::move_cached_file(bucket, account_id, old_key, new_key)
-                .await
-                .map_err(|e| format!("Failed to update file cache: {}", e))?
-        {
-            let move_result = db::update_directory_tree_for_move(
-                bucket,
-                account_id,
-                old_key,
-                new_key,
-                size,
-                &last_modified,
-            )
+        if let Some((size, last_modified)) = db::move_cached_file(scope, old_key, new_key)
             .await
-            .map_err(|e| format!("Failed to update directory tree: {}", e))?;
+            .map_err(|e| format!("Failed to update file cache: {}", e))?
+        {
+            let move_result =
+                db::update_directory_tree_for_move(scope, old_key, new_key, size, &last_modified)
+                    .await
+                    .map_err(|e| format!("Failed to update directory tree: {}", e))?;
 
             for path in move_result.removed_paths {
                 removed_paths.insert(path);
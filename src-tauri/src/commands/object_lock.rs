@@ -0,0 +1,62 @@
+//! Object Lock commands: retention and legal hold on AWS / MinIO / RustFS.
+
+use crate::commands::provider_config::{build_provider_config, ProviderConfigInput};
+use crate::providers::object_lock::{self, ObjectLockState, ObjectRetention};
+
+/// Whether the bucket was created with Object Lock enabled.
+#[tauri::command]
+pub async fn get_bucket_object_lock(config: ProviderConfigInput) -> Result<bool, String> {
+    let config = build_provider_config(&config)?;
+    object_lock::bucket_lock_enabled(&config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_object_lock(
+    config: ProviderConfigInput,
+    key: String,
+) -> Result<ObjectLockState, String> {
+    let config = build_provider_config(&config)?;
+    object_lock::get_object_lock(&config, &key)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Set retention, or remove it when `retention` is omitted. Returns the
+/// state read back after the change.
+#[tauri::command]
+pub async fn put_object_retention(
+    config: ProviderConfigInput,
+    key: String,
+    retention: Option<ObjectRetention>,
+    bypass_governance: Option<bool>,
+) -> Result<ObjectLockState, String> {
+    let config = build_provider_config(&config)?;
+    object_lock::put_object_retention(
+        &config,
+        &key,
+        retention.as_ref(),
+        bypass_governance.unwrap_or(false),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    object_lock::get_object_lock(&config, &key)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn put_object_legal_hold(
+    config: ProviderConfigInput,
+    key: String,
+    enabled: bool,
+) -> Result<ObjectLockState, String> {
+    let config = build_provider_config(&config)?;
+    object_lock::put_object_legal_hold(&config, &key, enabled)
+        .await
+        .map_err(|e| e.to_string())?;
+    object_lock::get_object_lock(&config, &key)
+        .await
+        .map_err(|e| e.to_string())
+}
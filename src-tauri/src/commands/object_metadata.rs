@@ -0,0 +1,84 @@
+//! Fetch content type and checksum of cached objects with HEAD.
+
+use crate::commands::batch_move::batch_concurrency;
+use crate::commands::provider_config::{build_encrypted_provider_config, ProviderConfigInput};
+use crate::db::{self, CachedObjectMeta};
+use crate::providers::object_metadata::head_object_metadata_with;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetadataFetchResult {
+    /// Keys whose cache row was updated.
+    pub updated: usize,
+    /// Keys read but not cached (yet), so nothing was recorded.
+    pub not_cached: usize,
+    pub failed: usize,
+    pub errors: Vec<String>,
+}
+
+/// HEAD each key and record its ETag, storage class, content type and
+/// checksum in the file cache.
+#[tauri::command]
+pub async fn fetch_object_metadata(
+    config: ProviderConfigInput,
+    keys: Vec<String>,
+) -> Result<MetadataFetchResult, String> {
+    let scope = Arc::new(config.cache_scope());
+    let config = Arc::new(build_encrypted_provider_config(&config).await?);
+    let client = config
+        .create_client()
+        .await
+        .map_err(|e| format!("Failed to create client: {}", e))?;
+
+    let semaphore = Arc::new(Semaphore::new(batch_concurrency(keys.len())));
+    let mut handles = Vec::with_capacity(keys.len());
+    for key in keys {
+        let permit = match semaphore.clone().acquire_owned().await {
+            Ok(p) => p,
+            Err(_) => break,
+        };
+        let client = client.clone();
+        let config = config.clone();
+        let scope = scope.clone();
+
+        handles.push(tokio::spawn(async move {
+            let _permit = permit;
+            let head = head_object_metadata_with(&client, &config, &key)
+                .await
+                .map_err(|e| format!("{}: {}", key, e))?;
+            let meta = CachedObjectMeta {
+                etag: head.etag,
+                storage_class: head.storage_class,
+                content_type: head.content_type,
+                checksum: head.checksum,
+            };
+            db::set_cached_object_meta(&scope, &key, &meta)
+                .await
+                .map_err(|e| format!("{}: {}", key, e))
+        }));
+    }
+
+    let mut result = MetadataFetchResult {
+        updated: 0,
+        not_cached: 0,
+        failed: 0,
+        errors: Vec::new(),
+    };
+    for handle in handles {
+        match handle.await {
+            Ok(Ok(true)) => result.updated += 1,
+            Ok(Ok(false)) => result.not_cached += 1,
+            Ok(Err(e)) => {
+                result.failed += 1;
+                result.errors.push(e);
+            }
+            Err(e) => {
+                result.failed += 1;
+                result.errors.push(e.to_string());
+            }
+        }
+    }
+    Ok(result)
+}
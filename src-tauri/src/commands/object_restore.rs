@@ -0,0 +1,122 @@
+//! Restore of archived AWS objects (Glacier / Deep Archive / Intelligent-
+//! Tiering archive tiers).
+//!
+//! Requests are tracked in the database and polled in the background by
+//! `download::restore`, which also requeues downloads waiting on them.
+
+use super::aws_commands::AwsConfigInput;
+use crate::commands::bucket_encryption::resolve_bucket_encryption;
+use crate::db::{self, CacheScope, ObjectRestore};
+use crate::download::restore::{record_archive_state, watch_restore};
+use crate::providers::aws;
+use crate::providers::storage_class::{ArchiveState, RestoreStatus, RestoreTier};
+
+async fn build_config(config: AwsConfigInput) -> Result<(aws::AwsConfig, CacheScope), String> {
+    let scope = config.cache_scope();
+    let mut aws_config: aws::AwsConfig = config.into();
+    aws_config.encryption =
+        resolve_bucket_encryption("aws", scope.account_id(), &aws_config.bucket).await?;
+    Ok((aws_config, scope))
+}
+
+/// Scope of an AWS bucket given by account and optional custom endpoint.
+fn aws_scope(
+    account_id: &str,
+    bucket: &str,
+    endpoint_scheme: Option<&str>,
+    endpoint_host: Option<&str>,
+) -> CacheScope {
+    CacheScope::new("aws", endpoint_scheme, endpoint_host, account_id, bucket)
+}
+
+/// Request a temporary restored copy of an archived object, kept for `days`
+/// (omitted for Intelligent-Tiering), and start tracking it.
+#[tauri::command]
+pub async fn restore_aws_object(
+    config: AwsConfigInput,
+    key: String,
+    tier: RestoreTier,
+    days: Option<i32>,
+    app: tauri::AppHandle,
+) -> Result<ObjectRestore, String> {
+    let (aws_config, scope) = build_config(config).await?;
+
+    let state = aws::head_archive_state(&aws_config, &key)
+        .await
+        .map_err(|e| format!("Failed to read object: {}", e))?;
+    if state.status == RestoreStatus::NotArchived {
+        return Err(format!("'{}' is not archived", key));
+    }
+    state.validate_restore(tier, days)?;
+
+    aws::restore_object(&aws_config, &key, tier, days)
+        .await
+        .map_err(|e| format!("Failed to restore object: {}", e))?;
+
+    let restore = db::save_object_restore(
+        &scope,
+        &key,
+        tier.as_str(),
+        days.map(i64::from),
+        RestoreStatus::InProgress.as_str(),
+    )
+    .await
+    .map_err(|e| format!("Failed to save restore: {}", e))?;
+
+    watch_restore(app, aws_config, scope, key);
+    Ok(restore)
+}
+
+/// Read an object's current archive and restore state from HEAD.
+#[tauri::command]
+pub async fn get_aws_restore_status(
+    config: AwsConfigInput,
+    key: String,
+    app: tauri::AppHandle,
+) -> Result<ArchiveState, String> {
+    let (aws_config, scope) = build_config(config).await?;
+
+    let state = aws::head_archive_state(&aws_config, &key)
+        .await
+        .map_err(|e| format!("Failed to read object: {}", e))?;
+    record_archive_state(&app, &scope, &key, &state).await;
+    Ok(state)
+}
+
+#[tauri::command]
+pub async fn list_aws_object_restores(
+    account_id: String,
+    bucket: String,
+    endpoint_scheme: Option<String>,
+    endpoint_host: Option<String>,
+) -> Result<Vec<ObjectRestore>, String> {
+    let scope = aws_scope(
+        &account_id,
+        &bucket,
+        endpoint_scheme.as_deref(),
+        endpoint_host.as_deref(),
+    );
+    db::list_object_restores(&scope)
+        .await
+        .map_err(|e| format!("Failed to list restores: {}", e))
+}
+
+/// Stop tracking a restore. The restore itself cannot be cancelled.
+#[tauri::command]
+pub async fn forget_aws_object_restore(
+    account_id: String,
+    bucket: String,
+    key: String,
+    endpoint_scheme: Option<String>,
+    endpoint_host: Option<String>,
+) -> Result<(), String> {
+    let scope = aws_scope(
+        &account_id,
+        &bucket,
+        endpoint_scheme.as_deref(),
+        endpoint_host.as_deref(),
+    );
+    db::delete_object_restore(&scope, &key)
+        .await
+        .map_err(|e| format!("Failed to delete restore: {}", e))
+}
@@ -0,0 +1,202 @@
+//! Object tagging commands (single object and batch over a selection or prefix).
+
+use crate::commands::batch_move::{batch_concurrency, fallback_batch_id};
+use crate::commands::provider_config::{build_provider_config, ProviderConfigInput};
+use crate::providers::capability::{ensure_supported, Feature};
+use crate::providers::config::ProviderConfig;
+use crate::providers::s3_client::list_all_keys;
+use crate::providers::tagging::{self, ObjectTag, TagMode};
+use crate::transfer_progress::ThrottleGate;
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::Emitter;
+use tokio::sync::Semaphore;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchTagProgress {
+    pub batch_id: String,
+    pub completed: usize,
+    pub total: usize,
+    pub failed: usize,
+    pub done: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchTagResult {
+    pub tagged: usize,
+    pub failed: usize,
+    pub errors: Vec<String>,
+}
+
+#[tauri::command]
+pub async fn get_object_tags(
+    config: ProviderConfigInput,
+    key: String,
+) -> Result<Vec<ObjectTag>, String> {
+    let config = build_provider_config(&config)?;
+    tagging::get_object_tags(&config, &key)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn put_object_tags(
+    config: ProviderConfigInput,
+    key: String,
+    tags: Vec<ObjectTag>,
+) -> Result<(), String> {
+    let config = build_provider_config(&config)?;
+    tagging::put_object_tags(&config, &key, &tags)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_object_tags(config: ProviderConfigInput, key: String) -> Result<(), String> {
+    let config = build_provider_config(&config)?;
+    tagging::delete_object_tags(&config, &key)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn tag_one(
+    client: &aws_sdk_s3::Client,
+    config: &ProviderConfig,
+    key: &str,
+    tags: &[ObjectTag],
+    mode: TagMode,
+) -> Result<(), String> {
+    let next = if mode == TagMode::Replace {
+        tags.to_vec()
+    } else {
+        let existing = tagging::get_object_tags_with(client, config, key)
+            .await
+            .map_err(|e| e.to_string())?;
+        tagging::apply_tag_mode(&existing, tags, mode)
+    };
+
+    tagging::put_object_tags_with(client, config, key, &next)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Apply tags to an explicit selection of keys, or to every object under
+/// `prefix` when `keys` is not given. Emits `batch-tag-progress`.
+#[tauri::command]
+pub async fn batch_tag_objects(
+    config: ProviderConfigInput,
+    keys: Option<Vec<String>>,
+    prefix: Option<String>,
+    tags: Vec<ObjectTag>,
+    mode: Option<TagMode>,
+    batch_id: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<BatchTagResult, String> {
+    let config = build_provider_config(&config)?;
+    let mode = mode.unwrap_or_default();
+    let batch_id = batch_id.unwrap_or_else(fallback_batch_id);
+
+    if mode != TagMode::Remove {
+        tagging::validate_tags(&tags)?;
+    }
+    ensure_supported(&config, Feature::ObjectTagging).map_err(|e| e.to_string())?;
+
+    let client = config
+        .create_client()
+        .await
+        .map_err(|e| format!("Failed to create client: {}", e))?;
+
+    let keys = match (keys, prefix) {
+        (Some(keys), _) => keys,
+        (None, Some(prefix)) => list_all_keys(&client, config.bucket(), &prefix)
+            .await
+            .map_err(|e| format!("Failed to list objects: {}", e))?,
+        (None, None) => return Err("Either keys or prefix is required".to_string()),
+    };
+
+    let total = keys.len();
+    if total == 0 {
+        return Ok(BatchTagResult {
+            tagged: 0,
+            failed: 0,
+            errors: vec![],
+        });
+    }
+
+    let semaphore = Arc::new(Semaphore::new(batch_concurrency(total)));
+    let completed = Arc::new(AtomicUsize::new(0));
+    let failed = Arc::new(AtomicUsize::new(0));
+    let errors = Arc::new(tokio::sync::Mutex::new(Vec::<String>::new()));
+    let gate = Arc::new(ThrottleGate::new(Duration::from_millis(100)));
+    let config = Arc::new(config);
+    let tags = Arc::new(tags);
+
+    let mut handles = Vec::with_capacity(total);
+    for key in keys {
+        let permit = match semaphore.clone().acquire_owned().await {
+            Ok(p) => p,
+            Err(_) => break,
+        };
+        let client = client.clone();
+        let config = config.clone();
+        let tags = tags.clone();
+        let completed = completed.clone();
+        let failed = failed.clone();
+        let errors = errors.clone();
+        let gate = gate.clone();
+        let app = app.clone();
+        let batch_id = batch_id.clone();
+
+        handles.push(tokio::spawn(async move {
+            let _permit = permit;
+            match tag_one(&client, &config, &key, &tags, mode).await {
+                Ok(()) => {
+                    completed.fetch_add(1, Ordering::SeqCst);
+                }
+                Err(e) => {
+                    failed.fetch_add(1, Ordering::SeqCst);
+                    errors.lock().await.push(format!("{}: {}", key, e));
+                }
+            }
+
+            if gate.try_pass() {
+                let _ = app.emit(
+                    "batch-tag-progress",
+                    BatchTagProgress {
+                        batch_id,
+                        completed: completed.load(Ordering::SeqCst),
+                        total,
+                        failed: failed.load(Ordering::SeqCst),
+                        done: false,
+                    },
+                );
+            }
+        }));
+    }
+
+    for handle in handles {
+        let _ = handle.await;
+    }
+
+    let tagged = completed.load(Ordering::SeqCst);
+    let failed = failed.load(Ordering::SeqCst);
+    let _ = app.emit(
+        "batch-tag-progress",
+        BatchTagProgress {
+            batch_id,
+            completed: tagged,
+            total,
+            failed,
+            done: true,
+        },
+    );
+
+    let errors = errors.lock().await.clone();
+    Ok(BatchTagResult {
+        tagged,
+        failed,
+        errors,
+    })
+}
@@ -0,0 +1,394 @@
+//! Parallel bucket listing for background syncs.
+//!
+//! The bucket is cut into shards that several listers work through at once.
+//! Delimiter crawls (RustFS) shard by the prefixes each listing discovers.
+//! Flat listings start as a single key range; whenever a lister is idle, a
+//! busy one hands over the upper half of its remaining range, split at a key
+//! between the last one it saw and the range's end (`start_after` marks the
+//! lower bound). Pages go back to the sync over a channel, which keeps
+//! progress and storing in one place.
+
+use crate::commands::sync_manager::SyncHandle;
+use crate::db::{self, CachedFile};
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::sync::{mpsc, Notify, Semaphore};
+use tokio::task::JoinHandle;
+
+/// Listers working on one sync.
+const LISTERS_PER_SYNC: usize = 4;
+
+/// ListObjectsV2 requests in flight across the app: lazy listing and the
+/// listers of every running sync.
+const MAX_CONCURRENT_LISTS: usize = 8;
+/// Of those, requests the sync listers may hold, so folder browsing and the
+/// prefix watcher always find a free slot.
+const MAX_BACKGROUND_LISTS: usize = 6;
+
+pub(crate) static S3_LIST_SLOTS: LazyLock<Semaphore> =
+    LazyLock::new(|| Semaphore::new(MAX_CONCURRENT_LISTS));
+static BACKGROUND_LIST_SLOTS: LazyLock<Semaphore> =
+    LazyLock::new(|| Semaphore::new(MAX_BACKGROUND_LISTS));
+
+/// Pivots are kept within ASCII; keys sorting above it stay in the upper half.
+const PIVOT_CHAR_LIMIT: u32 = 0x7f;
+/// S3 keys are at most 1024 bytes.
+const MAX_PIVOT_LEN: usize = 1024;
+
+/// Part of the bucket one lister works through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ListShard {
+    /// Immediate children of a prefix, listed with delimiter "/".
+    Prefix(String),
+    /// Keys after `start_after` up to and including `end`; `None` is unbounded.
+    Range {
+        start_after: Option<String>,
+        end: Option<String>,
+    },
+}
+
+/// One listed page: files to store and folder keys for the directory tree.
+#[derive(Debug, Default)]
+pub(crate) struct ListedPage {
+    pub files: Vec<CachedFile>,
+    pub folder_keys: Vec<String>,
+}
+
+struct ShardQueue {
+    pending: VecDeque<ListShard>,
+    seen_prefixes: HashSet<String>,
+    /// Listers working on a shard; while any is, more shards may appear.
+    busy: usize,
+    /// Listers waiting for a shard.
+    idle: usize,
+}
+
+struct ListContext {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+    account_id: String,
+    delimiter_crawl: bool,
+    handle: SyncHandle,
+    queue: Mutex<ShardQueue>,
+    wake: Notify,
+}
+
+impl ListContext {
+    /// The next shard to list, waiting while other listers may still add
+    /// some. `None` once the listing is complete or cancelled.
+    async fn next_shard(&self) -> Option<ListShard> {
+        loop {
+            if !self.handle.is_active() {
+                return None;
+            }
+            // Created before checking the queue so a push in between wakes it
+            let notified = self.wake.notified();
+            {
+                let mut queue = self.queue.lock().unwrap();
+                if let Some(shard) = queue.pending.pop_front() {
+                    queue.busy += 1;
+                    return Some(shard);
+                }
+                if queue.busy == 0 {
+                    return None;
+                }
+                queue.idle += 1;
+            }
+            notified.await;
+            self.queue.lock().unwrap().idle -= 1;
+        }
+    }
+
+    fn finish_shard(&self) {
+        self.queue.lock().unwrap().busy -= 1;
+        self.wake.notify_waiters();
+    }
+
+    fn push_prefix(&self, prefix: &str) {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.seen_prefixes.insert(prefix.to_string()) {
+            queue
+                .pending
+                .push_back(ListShard::Prefix(prefix.to_string()));
+            drop(queue);
+            self.wake.notify_waiters();
+        }
+    }
+
+    /// When a lister is idle, split the range after `last_key` and queue its
+    /// upper half. Returns the new end of the caller's range.
+    fn split_for_idle(&self, last_key: &str, end: Option<&str>) -> Option<String> {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.idle == 0 || !queue.pending.is_empty() {
+            return None;
+        }
+        let pivot = split_point(last_key, end)?;
+        queue.pending.push_back(ListShard::Range {
+            start_after: Some(pivot.clone()),
+            end: end.map(str::to_string),
+        });
+        drop(queue);
+        self.wake.notify_waiters();
+        Some(pivot)
+    }
+}
+
+/// A key strictly between `low` and `high` (unbounded when `None`), as short
+/// as possible, to split a listing range at.
+fn split_point(low: &str, high: Option<&str>) -> Option<String> {
+    let low: Vec<u32> = low.chars().map(u32::from).collect();
+    let high: Option<Vec<u32>> = high.map(|high| high.chars().map(u32::from).collect());
+    // Once the pivot sorts below `high`, anything may follow
+    let mut bounded = high.is_some();
+    let mut pivot = String::new();
+
+    for i in 0..MAX_PIVOT_LEN {
+        // 0 stands for "ended", which sorts before every character
+        let a = low.get(i).copied().unwrap_or(0);
+        let b = match &high {
+            Some(high) if bounded => high.get(i).copied().unwrap_or(0),
+            _ => PIVOT_CHAR_LIMIT.max(a + 1),
+        };
+
+        if b >= a + 2 {
+            pivot.push(char::from_u32(a + (b - a) / 2)?);
+            return Some(pivot);
+        }
+        if b == a + 1 {
+            if a == 0 {
+                return None;
+            }
+            bounded = false;
+        } else if b < a || a == 0 {
+            // Equal strings, or `high` sorting before `low`
+            return None;
+        }
+        pivot.push(char::from_u32(a)?);
+    }
+    None
+}
+
+async fn list_shard(
+    ctx: &ListContext,
+    shard: ListShard,
+    pages: &mpsc::Sender<Result<ListedPage, String>>,
+) -> Result<(), String> {
+    let (prefix, start_after, mut end) = match shard {
+        ListShard::Prefix(prefix) => (prefix, None, None),
+        ListShard::Range { start_after, end } => (String::new(), start_after, end),
+    };
+    let mut continuation_token: Option<String> = None;
+
+    loop {
+        if !ctx.handle.is_active() {
+            return Ok(());
+        }
+
+        let create_request = || {
+            let mut request = ctx
+                .client
+                .list_objects_v2()
+                .bucket(&ctx.bucket)
+                .max_keys(1000);
+
+            if ctx.delimiter_crawl {
+                request = request.delimiter("/");
+                if !prefix.is_empty() {
+                    request = request.prefix(&prefix);
+                }
+            }
+
+            if let Some(token) = &continuation_token {
+                request = request.continuation_token(token);
+            } else if let Some(start_after) = &start_after {
+                request = request.start_after(start_after);
+            }
+
+            request
+        };
+
+        let response = {
+            let _background_slot = BACKGROUND_LIST_SLOTS
+                .acquire()
+                .await
+                .map_err(|e| format!("S3 list failed: {}", e))?;
+            let _list_slot = S3_LIST_SLOTS
+                .acquire()
+                .await
+                .map_err(|e| format!("S3 list failed: {}", e))?;
+            match create_request().send().await {
+                Ok(response) => response,
+                Err(first_error) => create_request().send().await.map_err(|retry_error| {
+                    format!(
+                        "S3 list failed after retry: {}; first attempt: {}",
+                        retry_error, first_error
+                    )
+                })?,
+            }
+        };
+
+        let is_truncated = response.is_truncated().unwrap_or(false);
+        let next_token = response.next_continuation_token().map(|s| s.to_string());
+        let now = chrono::Utc::now().timestamp();
+
+        let mut page = ListedPage::default();
+        let mut last_key: Option<String> = None;
+        let mut past_end = false;
+        for obj in response.contents() {
+            let Some(key) = obj.key() else {
+                continue;
+            };
+            if end.as_deref().is_some_and(|end| key > end) {
+                // The rest belongs to a range handed to another lister
+                past_end = true;
+                break;
+            }
+            last_key = Some(key.to_string());
+
+            let key = key.to_string();
+            if key.ends_with('/') {
+                page.folder_keys.push(key);
+            } else {
+                let (parent_path, name) = db::parse_key(&key);
+                page.files.push(CachedFile {
+                    bucket: ctx.bucket.clone(),
+                    account_id: ctx.account_id.clone(),
+                    key,
+                    parent_path,
+                    name,
+                    size: obj.size().unwrap_or(0),
+                    last_modified: obj
+                        .last_modified()
+                        .map(|dt| dt.to_string())
+                        .unwrap_or_default(),
+                    synced_at: now,
+                    storage_class: obj.storage_class().map(|class| class.as_str().to_string()),
+                    etag: obj.e_tag().map(str::to_string),
+                    content_type: None,
+                    checksum: None,
+                });
+            }
+        }
+
+        if ctx.delimiter_crawl {
+            for cp in response.common_prefixes() {
+                if let Some(prefix) = cp.prefix() {
+                    page.folder_keys.push(prefix.to_string());
+                    ctx.push_prefix(prefix);
+                }
+            }
+        }
+
+        if pages.send(Ok(page)).await.is_err() {
+            // The sync stopped reading
+            return Ok(());
+        }
+
+        if !is_truncated || past_end {
+            return Ok(());
+        }
+        if !ctx.delimiter_crawl {
+            if let Some(last_key) = &last_key {
+                if let Some(pivot) = ctx.split_for_idle(last_key, end.as_deref()) {
+                    end = Some(pivot);
+                }
+            }
+        }
+        continuation_token = next_token;
+    }
+}
+
+async fn run_lister(ctx: Arc<ListContext>, pages: mpsc::Sender<Result<ListedPage, String>>) {
+    while let Some(shard) = ctx.next_shard().await {
+        let result = list_shard(&ctx, shard, &pages).await;
+        ctx.finish_shard();
+        if let Err(e) = result {
+            let _ = pages.send(Err(e)).await;
+            return;
+        }
+    }
+}
+
+/// Running listers of one sync; dropping it stops them.
+pub(crate) struct BucketListing {
+    pub pages: mpsc::Receiver<Result<ListedPage, String>>,
+    listers: Vec<JoinHandle<()>>,
+}
+
+impl Drop for BucketListing {
+    fn drop(&mut self) {
+        for lister in &self.listers {
+            lister.abort();
+        }
+    }
+}
+
+/// Start listing the whole bucket with `LISTERS_PER_SYNC` listers. The page
+/// channel closes once every shard has been listed (or the job cancelled);
+/// a lister error arrives as an `Err` page.
+pub(crate) fn start_bucket_listing(
+    client: aws_sdk_s3::Client,
+    bucket: String,
+    account_id: String,
+    delimiter_crawl: bool,
+    handle: SyncHandle,
+) -> BucketListing {
+    let first_shard = if delimiter_crawl {
+        ListShard::Prefix(String::new())
+    } else {
+        ListShard::Range {
+            start_after: None,
+            end: None,
+        }
+    };
+    let ctx = Arc::new(ListContext {
+        client,
+        bucket,
+        account_id,
+        delimiter_crawl,
+        handle,
+        queue: Mutex::new(ShardQueue {
+            pending: VecDeque::from([first_shard]),
+            seen_prefixes: HashSet::from([String::new()]),
+            busy: 0,
+            idle: 0,
+        }),
+        wake: Notify::new(),
+    });
+
+    let (page_tx, pages) = mpsc::channel(LISTERS_PER_SYNC * 2);
+    let listers = (0..LISTERS_PER_SYNC)
+        .map(|_| tokio::spawn(run_lister(ctx.clone(), page_tx.clone())))
+        .collect();
+
+    BucketListing { pages, listers }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_point;
+
+    #[test]
+    fn split_points_fall_strictly_inside_the_range() {
+        let cases: &[(&str, Option<&str>, Option<&str>)] = &[
+            ("a", None, Some("p")),
+            ("data/001", Some("data/9"), Some("data/4")),
+            ("ab", Some("ac"), Some("ab?")),
+            ("", Some("b"), Some("1")),
+            ("~~", None, Some("~~?")),
+            ("same", Some("same"), None),
+            ("b", Some("a"), None),
+            ("a", Some("a\u{1}"), None),
+        ];
+        for (low, high, expected) in cases {
+            let pivot = split_point(low, *high);
+            assert_eq!(pivot.as_deref(), *expected, "split of ({low}, {high:?})");
+            if let Some(pivot) = pivot {
+                assert!(pivot.as_str() > *low);
+                if let Some(high) = high {
+                    assert!(pivot.as_str() < *high);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,452 @@
+//! Recursive folder operations executed server-side: delete, copy and rename
+//! everything under a prefix.
+//!
+//! The prefix is listed live page by page instead of being expanded from the
+//! local cache, so operations are complete even while a sync is still running.
+//! Work is streamed through bounded workers as pages arrive and reported on
+//! `batch-move-progress`; `cancel_prefix_operation` stops it between objects.
+
+use crate::commands::batch_move::{fallback_batch_id, BatchProgressTracker};
+use crate::commands::delete_cache::update_cache_after_batch_delete;
+use crate::commands::move_cache::update_cache_after_batch_move;
+use crate::commands::provider_config::{
+    build_encrypted_provider_config, build_provider_config, ProviderConfigInput,
+};
+use crate::commands::upload_cache::update_cache_after_batch_copy;
+use crate::db::CacheScope;
+use crate::providers::batch_delete;
+use crate::providers::capability::describe_sdk_error;
+use crate::providers::config::ProviderConfig;
+use crate::providers::encryption::ObjectEncryption;
+use crate::providers::multipart_copy::{self, CopyEncryption};
+use crate::providers::s3_client::{list_objects_page, ListedObject};
+use aws_sdk_s3::Client;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock};
+use tokio::sync::{Mutex, Semaphore};
+
+/// Concurrent copy/rename workers, or concurrent DeleteObjects pages.
+const MAX_PREFIX_WORKERS: usize = 8;
+
+static PREFIX_CANCEL_REGISTRY: LazyLock<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PrefixOperation {
+    Delete,
+    Copy,
+    Rename,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrefixOperationResult {
+    pub batch_id: String,
+    pub processed: usize,
+    pub failed: usize,
+    pub errors: Vec<String>,
+    pub cancelled: bool,
+}
+
+/// Folder prefixes always end with `/`, so `photos` never matches `photos2/`.
+fn normalize_prefix(prefix: &str) -> String {
+    let trimmed = prefix.trim_start_matches('/');
+    if trimmed.is_empty() || trimmed.ends_with('/') {
+        trimmed.to_string()
+    } else {
+        format!("{}/", trimmed)
+    }
+}
+
+fn validate_prefixes(source: &str, dest: Option<&str>) -> Result<(), String> {
+    if source.is_empty() {
+        return Err("A folder prefix is required; use bucket deletion for the whole bucket".into());
+    }
+    if let Some(dest) = dest {
+        if dest.starts_with(source) {
+            return Err("Destination folder cannot be inside the source folder".into());
+        }
+    }
+    Ok(())
+}
+
+fn dest_key_for(key: &str, source: &str, dest: &str) -> String {
+    format!("{}{}", dest, &key[source.len()..])
+}
+
+#[derive(Default)]
+struct PrefixProgress {
+    succeeded: AtomicUsize,
+    failed: AtomicUsize,
+    errors: Mutex<Vec<String>>,
+    deleted: Mutex<Vec<String>>,
+    moved: Mutex<Vec<(String, String)>>,
+    copied: Mutex<Vec<(String, i64, String)>>,
+}
+
+impl PrefixProgress {
+    fn processed(&self) -> usize {
+        self.succeeded.load(Ordering::SeqCst) + self.failed.load(Ordering::SeqCst)
+    }
+
+    async fn record_failure(&self, error: String) {
+        self.failed.fetch_add(1, Ordering::SeqCst);
+        self.errors.lock().await.push(error);
+    }
+}
+
+/// Folder placeholders (`a/b/`) are copied and deleted like any other object
+/// but are not tracked in the file cache.
+fn cached_key(key: &str) -> bool {
+    !key.ends_with('/')
+}
+
+async fn register_cancel(batch_id: &str) -> Arc<AtomicBool> {
+    let flag = Arc::new(AtomicBool::new(false));
+    PREFIX_CANCEL_REGISTRY
+        .lock()
+        .await
+        .insert(batch_id.to_string(), flag.clone());
+    flag
+}
+
+async fn unregister_cancel(batch_id: &str) {
+    PREFIX_CANCEL_REGISTRY.lock().await.remove(batch_id);
+}
+
+async fn copy_one(
+    client: &Client,
+    bucket: &str,
+    object: &ListedObject,
+    dest_key: &str,
+    encryption: Option<&ObjectEncryption>,
+    delete_source: bool,
+) -> Result<(), String> {
+    multipart_copy::copy_object(
+        client,
+        bucket,
+        &object.key,
+        bucket,
+        dest_key,
+        CopyEncryption::same(encryption),
+        None,
+        None,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if delete_source {
+        client
+            .delete_object()
+            .bucket(bucket)
+            .key(&object.key)
+            .send()
+            .await
+            .map_err(|e| describe_sdk_error(e).to_string())?;
+    }
+    Ok(())
+}
+
+async fn run_prefix_operation(
+    app: &tauri::AppHandle,
+    config: ProviderConfig,
+    scope: &CacheScope,
+    operation: PrefixOperation,
+    source_prefix: String,
+    dest_prefix: Option<String>,
+    batch_id: String,
+) -> Result<PrefixOperationResult, String> {
+    validate_prefixes(&source_prefix, dest_prefix.as_deref())?;
+
+    let client = config
+        .create_client()
+        .await
+        .map_err(|e| format!("Failed to create client: {}", e))?;
+    let bucket = config.bucket().to_string();
+    let encryption = config.encryption().cloned();
+    let dest_prefix = dest_prefix.unwrap_or_default();
+
+    let cancelled = register_cancel(&batch_id).await;
+    let tracker = Arc::new(BatchProgressTracker::new(batch_id.clone(), 0));
+    let progress = Arc::new(PrefixProgress::default());
+    let semaphore = Arc::new(Semaphore::new(MAX_PREFIX_WORKERS));
+    let mut handles = Vec::new();
+    let mut continuation_token: Option<String> = None;
+    let mut list_error = None;
+
+    while !cancelled.load(Ordering::SeqCst) {
+        let page = match list_objects_page(
+            &client,
+            &bucket,
+            &source_prefix,
+            continuation_token.as_deref(),
+        )
+        .await
+        {
+            Ok(page) => page,
+            Err(e) => {
+                list_error = Some(format!("Failed to list '{}': {}", source_prefix, e));
+                break;
+            }
+        };
+        tracker.add_total(page.objects.len());
+
+        if operation == PrefixOperation::Delete {
+            if !page.objects.is_empty() {
+                let Ok(permit) = semaphore.clone().acquire_owned().await else {
+                    break;
+                };
+                let keys: Vec<String> = page.objects.into_iter().map(|o| o.key).collect();
+                let client = client.clone();
+                let bucket = bucket.clone();
+                let progress = progress.clone();
+                let tracker = tracker.clone();
+                let app = app.clone();
+
+                handles.push(tokio::spawn(async move {
+                    let _permit = permit;
+                    let last_key = keys.last().cloned().unwrap_or_default();
+                    match batch_delete::delete_objects(&client, &bucket, keys, None).await {
+                        Ok(outcome) => {
+                            progress
+                                .succeeded
+                                .fetch_add(outcome.deleted.len(), Ordering::SeqCst);
+                            progress
+                                .failed
+                                .fetch_add(outcome.failed.len(), Ordering::SeqCst);
+                            progress.errors.lock().await.extend(
+                                outcome
+                                    .failed
+                                    .iter()
+                                    .map(|f| format!("{}: {}", f.key, f.message)),
+                            );
+                            progress.deleted.lock().await.extend(outcome.deleted);
+                        }
+                        Err(e) => progress.record_failure(e.to_string()).await,
+                    }
+                    tracker.emit(
+                        &app,
+                        progress.processed(),
+                        progress.failed.load(Ordering::SeqCst),
+                        &last_key,
+                    );
+                }));
+            }
+        } else {
+            for object in page.objects {
+                let Ok(permit) = semaphore.clone().acquire_owned().await else {
+                    break;
+                };
+                if cancelled.load(Ordering::SeqCst) {
+                    break;
+                }
+                let dest_key = dest_key_for(&object.key, &source_prefix, &dest_prefix);
+                let client = client.clone();
+                let bucket = bucket.clone();
+                let encryption = encryption.clone();
+                let progress = progress.clone();
+                let tracker = tracker.clone();
+                let app = app.clone();
+
+                handles.push(tokio::spawn(async move {
+                    let _permit = permit;
+                    let rename = operation == PrefixOperation::Rename;
+                    match copy_one(
+                        &client,
+                        &bucket,
+                        &object,
+                        &dest_key,
+                        encryption.as_ref(),
+                        rename,
+                    )
+                    .await
+                    {
+                        Ok(()) => {
+                            progress.succeeded.fetch_add(1, Ordering::SeqCst);
+                            if cached_key(&object.key) {
+                                if rename {
+                                    progress
+                                        .moved
+                                        .lock()
+                                        .await
+                                        .push((object.key.clone(), dest_key));
+                                } else {
+                                    progress.copied.lock().await.push((
+                                        dest_key,
+                                        object.size,
+                                        object.last_modified.clone(),
+                                    ));
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            progress
+                                .record_failure(format!("{}: {}", object.key, e))
+                                .await
+                        }
+                    }
+                    tracker.emit(
+                        &app,
+                        progress.processed(),
+                        progress.failed.load(Ordering::SeqCst),
+                        &object.key,
+                    );
+                }));
+            }
+        }
+
+        continuation_token = page.next_token;
+        if continuation_token.is_none() {
+            break;
+        }
+    }
+
+    for handle in handles {
+        let _ = handle.await;
+    }
+    unregister_cancel(&batch_id).await;
+
+    let processed = progress.processed();
+    let failed = progress.failed.load(Ordering::SeqCst);
+    tracker.emit_final(app, processed, failed);
+
+    let mut errors = std::mem::take(&mut *progress.errors.lock().await);
+    errors.extend(list_error);
+
+    let cache_result = match operation {
+        PrefixOperation::Delete => {
+            let deleted: Vec<String> = progress
+                .deleted
+                .lock()
+                .await
+                .iter()
+                .filter(|key| cached_key(key))
+                .cloned()
+                .collect();
+            update_cache_after_batch_delete(app, scope, &deleted).await
+        }
+        PrefixOperation::Copy => {
+            let copied = progress.copied.lock().await;
+            update_cache_after_batch_copy(app, scope, &copied).await
+        }
+        PrefixOperation::Rename => {
+            let moved = progress.moved.lock().await;
+            update_cache_after_batch_move(app, scope, &moved).await
+        }
+    };
+    if let Err(e) = cache_result {
+        errors.push(e);
+    }
+
+    Ok(PrefixOperationResult {
+        batch_id,
+        processed,
+        failed,
+        errors,
+        cancelled: cancelled.load(Ordering::SeqCst),
+    })
+}
+
+/// Delete every object under `prefix`, listing it live.
+#[tauri::command]
+pub async fn delete_prefix(
+    config: ProviderConfigInput,
+    prefix: String,
+    batch_id: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<PrefixOperationResult, String> {
+    let provider_config = build_provider_config(&config)?;
+    run_prefix_operation(
+        &app,
+        provider_config,
+        &config.cache_scope(),
+        PrefixOperation::Delete,
+        normalize_prefix(&prefix),
+        None,
+        batch_id.unwrap_or_else(fallback_batch_id),
+    )
+    .await
+}
+
+/// Copy every object under `source_prefix` to `dest_prefix` in the same bucket.
+#[tauri::command]
+pub async fn copy_prefix(
+    config: ProviderConfigInput,
+    source_prefix: String,
+    dest_prefix: String,
+    batch_id: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<PrefixOperationResult, String> {
+    let provider_config = build_encrypted_provider_config(&config).await?;
+    run_prefix_operation(
+        &app,
+        provider_config,
+        &config.cache_scope(),
+        PrefixOperation::Copy,
+        normalize_prefix(&source_prefix),
+        Some(normalize_prefix(&dest_prefix)),
+        batch_id.unwrap_or_else(fallback_batch_id),
+    )
+    .await
+}
+
+/// Rename a folder: copy every object under `source_prefix` to `dest_prefix`
+/// and delete each original once its copy succeeded.
+#[tauri::command]
+pub async fn rename_prefix(
+    config: ProviderConfigInput,
+    source_prefix: String,
+    dest_prefix: String,
+    batch_id: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<PrefixOperationResult, String> {
+    let provider_config = build_encrypted_provider_config(&config).await?;
+    run_prefix_operation(
+        &app,
+        provider_config,
+        &config.cache_scope(),
+        PrefixOperation::Rename,
+        normalize_prefix(&source_prefix),
+        Some(normalize_prefix(&dest_prefix)),
+        batch_id.unwrap_or_else(fallback_batch_id),
+    )
+    .await
+}
+
+/// Stop a running prefix operation. Objects already in flight finish.
+#[tauri::command]
+pub async fn cancel_prefix_operation(batch_id: String) -> Result<(), String> {
+    if let Some(flag) = PREFIX_CANCEL_REGISTRY.lock().await.get(&batch_id) {
+        flag.store(true, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefixes_are_normalized_to_folders() {
+        assert_eq!(normalize_prefix("photos"), "photos/");
+        assert_eq!(normalize_prefix("/photos/2024/"), "photos/2024/");
+        assert_eq!(normalize_prefix(""), "");
+    }
+
+    #[test]
+    fn destination_inside_source_is_rejected() {
+        assert!(validate_prefixes("a/", Some("a/b/")).is_err());
+        assert!(validate_prefixes("a/", Some("a/")).is_err());
+        assert!(validate_prefixes("a/", Some("ab/")).is_ok());
+        assert!(validate_prefixes("", None).is_err());
+    }
+
+    #[test]
+    fn dest_key_keeps_relative_path() {
+        assert_eq!(
+            dest_key_for("old/sub/file.txt", "old/", "new/"),
+            "new/sub/file.txt"
+        );
+    }
+}
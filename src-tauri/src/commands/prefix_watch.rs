@@ -0,0 +1,462 @@
+//! Opt-in watcher that keeps the visible folders up to date.
+//!
+//! While running it re-lists the watched prefixes, applies what changed to the
+//! cache and directory tree, and emits `paths-created`, `paths-removed` and
+//! `cache-updated` like local operations do. Quiet polls back off up to a
+//! maximum interval; a change, or new prefixes to watch, resets it.
+
+use crate::commands::cache_events::{
+    get_unique_parent_paths, CacheUpdatedEvent, PathsCreatedEvent, PathsRemovedEvent,
+};
+use crate::commands::lazy_sync::{create_client_for_input, fetch_prefix_listing, LazyListInput};
+use crate::db::{self, CacheScope, CachedFile};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::LazyLock;
+use std::time::Duration;
+use tauri::Emitter;
+use tokio::sync::{Mutex, Notify};
+
+const DEFAULT_INTERVAL_SECS: u64 = 15;
+const DEFAULT_MAX_INTERVAL_SECS: u64 = 120;
+
+// One watcher per app, for the bucket on screen
+static WATCH_RUN_ID: AtomicU64 = AtomicU64::new(0);
+static WATCHED_PREFIXES: LazyLock<Mutex<Vec<String>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+static WATCH_WAKE: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+fn is_watch_active(run_id: u64) -> bool {
+    WATCH_RUN_ID.load(Ordering::SeqCst) == run_id
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct PrefixWatchOptions {
+    /// Seconds between polls while things change.
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    /// Upper bound the interval backs off to while nothing changes.
+    #[serde(default = "default_max_interval_secs")]
+    pub max_interval_secs: u64,
+}
+
+fn default_interval_secs() -> u64 {
+    DEFAULT_INTERVAL_SECS
+}
+
+fn default_max_interval_secs() -> u64 {
+    DEFAULT_MAX_INTERVAL_SECS
+}
+
+impl Default for PrefixWatchOptions {
+    fn default() -> Self {
+        Self {
+            interval_secs: DEFAULT_INTERVAL_SECS,
+            max_interval_secs: DEFAULT_MAX_INTERVAL_SECS,
+        }
+    }
+}
+
+impl PrefixWatchOptions {
+    fn base_interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+
+    fn max_interval(&self) -> Duration {
+        self.base_interval()
+            .max(Duration::from_secs(self.max_interval_secs))
+    }
+}
+
+/// Interval before the next poll: back to the base after a change, doubled
+/// (up to the maximum) after a quiet or failed poll.
+fn next_interval(current: Duration, changed: bool, options: &PrefixWatchOptions) -> Duration {
+    if changed {
+        options.base_interval()
+    } else {
+        (current * 2).min(options.max_interval())
+    }
+}
+
+/// Emitted as `prefix-watch-error` when polling a prefix fails; the watcher
+/// keeps running and retries after backing off.
+#[derive(Debug, Clone, Serialize)]
+pub struct PrefixWatchError {
+    pub prefix: String,
+    pub error: String,
+}
+
+// ============ Diffing ============
+
+/// Differences between the cached contents of one prefix and a fresh listing.
+#[derive(Debug, Default)]
+struct PrefixChanges {
+    /// Files new at this level, as listed.
+    added: Vec<CachedFile>,
+    /// Files whose object changed, with their previous size.
+    updated: Vec<(CachedFile, i64)>,
+    /// (key, size) of files no longer listed.
+    removed: Vec<(String, i64)>,
+    created_folders: Vec<String>,
+    removed_folders: Vec<String>,
+}
+
+impl PrefixChanges {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.updated.is_empty()
+            && self.removed.is_empty()
+            && self.created_folders.is_empty()
+            && self.removed_folders.is_empty()
+    }
+
+    fn changed_keys(&self) -> Vec<String> {
+        self.added
+            .iter()
+            .chain(self.updated.iter().map(|(file, _)| file))
+            .map(|file| file.key.clone())
+            .chain(self.removed.iter().map(|(key, _)| key.clone()))
+            .chain(self.created_folders.iter().cloned())
+            .chain(self.removed_folders.iter().cloned())
+            .collect()
+    }
+}
+
+/// Same object version: same size, and the same ETag, or the same
+/// last_modified where either side has no ETag.
+fn same_object(cached: &CachedFile, listed: &CachedFile) -> bool {
+    cached.size == listed.size
+        && match (&cached.etag, &listed.etag) {
+            (Some(cached_etag), Some(listed_etag)) => cached_etag == listed_etag,
+            _ => cached.last_modified == listed.last_modified,
+        }
+}
+
+fn diff_prefix(
+    cached_files: &[CachedFile],
+    cached_folders: &[String],
+    listed_files: &[CachedFile],
+    listed_folders: &[String],
+) -> PrefixChanges {
+    let cached_by_key: HashMap<&str, &CachedFile> = cached_files
+        .iter()
+        .map(|file| (file.key.as_str(), file))
+        .collect();
+    let listed_keys: HashSet<&str> = listed_files.iter().map(|file| file.key.as_str()).collect();
+
+    let mut changes = PrefixChanges::default();
+    for file in listed_files {
+        match cached_by_key.get(file.key.as_str()) {
+            None => changes.added.push(file.clone()),
+            Some(cached) if !same_object(cached, file) => {
+                changes.updated.push((file.clone(), cached.size))
+            }
+            Some(_) => {}
+        }
+    }
+    changes.removed = cached_files
+        .iter()
+        .filter(|file| !listed_keys.contains(file.key.as_str()))
+        .map(|file| (file.key.clone(), file.size))
+        .collect();
+
+    let cached_folders: HashSet<&String> = cached_folders.iter().collect();
+    let listed_folder_set: HashSet<&String> = listed_folders.iter().collect();
+    changes.created_folders = listed_folders
+        .iter()
+        .filter(|folder| !cached_folders.contains(folder))
+        .cloned()
+        .collect();
+    changes.removed_folders = cached_folders
+        .into_iter()
+        .filter(|folder| !listed_folder_set.contains(folder))
+        .cloned()
+        .collect();
+    changes.removed_folders.sort();
+    changes
+}
+
+// ============ Polling ============
+
+/// Re-list `prefix` and apply the differences to the cache. Returns whether
+/// anything changed.
+async fn poll_prefix(
+    app: &tauri::AppHandle,
+    client: &aws_sdk_s3::Client,
+    scope: &CacheScope,
+    prefix: &str,
+) -> Result<bool, String> {
+    let db_error = |e: Box<dyn std::error::Error + Send + Sync>| format!("DB error: {}", e);
+
+    let was_listed = db::prefix_sync::get_prefix_sync_time(scope, prefix)
+        .await
+        .map_err(db_error)?
+        .is_some();
+    let cached = db::get_folder_contents(scope, prefix)
+        .await
+        .map_err(db_error)?;
+    let (files, folders) = fetch_prefix_listing(client, scope, prefix, None).await?;
+
+    // A prefix never listed before has nothing to compare against
+    let changes = if was_listed {
+        diff_prefix(&cached.files, &cached.folders, &files, &folders)
+    } else {
+        PrefixChanges::default()
+    };
+
+    if was_listed && changes.is_empty() {
+        db::prefix_sync::set_prefix_sync_time(
+            scope,
+            prefix,
+            files.len() as i32,
+            folders.len() as i32,
+        )
+        .await
+        .map_err(db_error)?;
+        return Ok(false);
+    }
+
+    db::upsert_prefix_files(scope, prefix, &files)
+        .await
+        .map_err(|e| format!("Failed to cache files: {}", e))?;
+    for folder in &folders {
+        db::ensure_directory_node(scope, folder)
+            .await
+            .map_err(|e| format!("Failed to upsert directory node: {}", e))?;
+    }
+    db::prefix_sync::set_prefix_sync_time(scope, prefix, files.len() as i32, folders.len() as i32)
+        .await
+        .map_err(db_error)?;
+
+    if !was_listed {
+        return Ok(false);
+    }
+
+    let tree_error = |e: Box<dyn std::error::Error + Send + Sync>| {
+        format!("Failed to update directory tree: {}", e)
+    };
+
+    for file in &changes.added {
+        db::update_directory_tree_for_file(scope, &file.key, file.size, &file.last_modified, true)
+            .await
+            .map_err(tree_error)?;
+    }
+    for (file, previous_size) in &changes.updated {
+        db::update_directory_tree_for_file(
+            scope,
+            &file.key,
+            file.size - previous_size,
+            &file.last_modified,
+            false,
+        )
+        .await
+        .map_err(tree_error)?;
+    }
+
+    // Files of folders that disappeared are gone too
+    let mut removed = changes.removed.clone();
+    for folder in &changes.removed_folders {
+        let deleted = db::delete_cached_prefix(scope, folder)
+            .await
+            .map_err(|e| format!("Failed to update file cache: {}", e))?;
+        removed.extend(deleted);
+    }
+    let mut removed_paths = db::update_directory_tree_for_delete_batch(scope, &removed)
+        .await
+        .map_err(tree_error)?;
+    for folder in &changes.removed_folders {
+        db::remove_directory_subtree(scope, folder)
+            .await
+            .map_err(tree_error)?;
+        if !removed_paths.contains(folder) {
+            removed_paths.push(folder.clone());
+        }
+    }
+
+    if !changes.created_folders.is_empty() {
+        let _ = app.emit(
+            "paths-created",
+            PathsCreatedEvent {
+                created_paths: changes.created_folders.clone(),
+            },
+        );
+    }
+    if !removed_paths.is_empty() {
+        let _ = app.emit("paths-removed", PathsRemovedEvent { removed_paths });
+    }
+    let _ = app.emit(
+        "cache-updated",
+        CacheUpdatedEvent {
+            action: "watch".to_string(),
+            affected_paths: get_unique_parent_paths(&changes.changed_keys()),
+        },
+    );
+
+    Ok(true)
+}
+
+async fn run_prefix_watch(
+    input: LazyListInput,
+    options: PrefixWatchOptions,
+    app: tauri::AppHandle,
+    run_id: u64,
+) {
+    let scope = input.cache_scope();
+    let client = match create_client_for_input(&input).await {
+        Ok(client) => client,
+        Err(error) => {
+            let _ = app.emit(
+                "prefix-watch-error",
+                PrefixWatchError {
+                    prefix: input.prefix.clone(),
+                    error,
+                },
+            );
+            return;
+        }
+    };
+
+    let mut interval = options.base_interval();
+    loop {
+        // New prefixes or stop: poll (or exit) right away
+        if tokio::time::timeout(interval, WATCH_WAKE.notified())
+            .await
+            .is_ok()
+        {
+            interval = options.base_interval();
+        }
+        if !is_watch_active(run_id) {
+            return;
+        }
+
+        let prefixes = WATCHED_PREFIXES.lock().await.clone();
+        let mut changed = false;
+        for prefix in prefixes {
+            if !is_watch_active(run_id) {
+                return;
+            }
+            match poll_prefix(&app, &client, &scope, &prefix).await {
+                Ok(prefix_changed) => changed |= prefix_changed,
+                Err(error) => {
+                    let _ = app.emit("prefix-watch-error", PrefixWatchError { prefix, error });
+                }
+            }
+        }
+        interval = next_interval(interval, changed, &options);
+    }
+}
+
+// ============ Commands ============
+
+/// Start watching `prefixes` (default: `input.prefix`) of the input's bucket,
+/// replacing any running watcher.
+#[tauri::command]
+pub async fn start_prefix_watch(
+    input: LazyListInput,
+    prefixes: Option<Vec<String>>,
+    options: Option<PrefixWatchOptions>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let run_id = WATCH_RUN_ID.fetch_add(1, Ordering::SeqCst) + 1;
+    *WATCHED_PREFIXES.lock().await = prefixes.unwrap_or_else(|| vec![input.prefix.clone()]);
+    // Let a previous watcher notice it was replaced
+    WATCH_WAKE.notify_waiters();
+
+    tokio::spawn(run_prefix_watch(
+        input,
+        options.unwrap_or_default(),
+        app,
+        run_id,
+    ));
+    Ok(())
+}
+
+/// Replace the watched prefixes, e.g. after navigating, and poll them now.
+#[tauri::command]
+pub async fn set_watched_prefixes(prefixes: Vec<String>) -> Result<(), String> {
+    *WATCHED_PREFIXES.lock().await = prefixes;
+    WATCH_WAKE.notify_waiters();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn stop_prefix_watch() -> Result<(), String> {
+    WATCH_RUN_ID.fetch_add(1, Ordering::SeqCst);
+    WATCHED_PREFIXES.lock().await.clear();
+    WATCH_WAKE.notify_waiters();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(key: &str, size: i64, etag: Option<&str>) -> CachedFile {
+        let (parent_path, name) = db::parse_key(key);
+        CachedFile {
+            bucket: "bucket".to_string(),
+            account_id: "acct".to_string(),
+            key: key.to_string(),
+            parent_path,
+            name,
+            size,
+            last_modified: "2024-01-01T00:00:00Z".to_string(),
+            synced_at: 0,
+            storage_class: None,
+            etag: etag.map(str::to_string),
+            content_type: None,
+            checksum: None,
+        }
+    }
+
+    #[test]
+    fn diff_finds_added_updated_removed_files_and_folders() {
+        let cached = vec![
+            file("dir/same.txt", 1, Some("\"a\"")),
+            file("dir/edited.txt", 2, Some("\"b\"")),
+            file("dir/gone.txt", 3, None),
+        ];
+        let listed = vec![
+            file("dir/same.txt", 1, Some("\"a\"")),
+            file("dir/edited.txt", 2, Some("\"c\"")),
+            file("dir/new.txt", 4, None),
+        ];
+        let changes = diff_prefix(
+            &cached,
+            &["dir/old/".to_string(), "dir/kept/".to_string()],
+            &listed,
+            &["dir/kept/".to_string(), "dir/upload/".to_string()],
+        );
+
+        let keys = |files: Vec<&CachedFile>| -> Vec<String> {
+            files.into_iter().map(|file| file.key.clone()).collect()
+        };
+        assert_eq!(keys(changes.added.iter().collect()), vec!["dir/new.txt"]);
+        assert_eq!(
+            keys(changes.updated.iter().map(|(file, _)| file).collect()),
+            vec!["dir/edited.txt"]
+        );
+        assert_eq!(changes.updated[0].1, 2);
+        assert_eq!(changes.removed, vec![("dir/gone.txt".to_string(), 3)]);
+        assert_eq!(changes.created_folders, vec!["dir/upload/".to_string()]);
+        assert_eq!(changes.removed_folders, vec!["dir/old/".to_string()]);
+
+        assert!(diff_prefix(&cached, &[], &cached, &[]).is_empty());
+    }
+
+    #[test]
+    fn interval_backs_off_while_quiet_and_resets_on_change() {
+        let options = PrefixWatchOptions {
+            interval_secs: 10,
+            max_interval_secs: 30,
+        };
+        let base = options.base_interval();
+        let once = next_interval(base, false, &options);
+        assert_eq!(once, Duration::from_secs(20));
+        assert_eq!(
+            next_interval(once, false, &options),
+            Duration::from_secs(30)
+        );
+        assert_eq!(next_interval(once, true, &options), base);
+    }
+}
@@ -0,0 +1,65 @@
+//! Presigned request builder commands.
+
+use crate::commands::provider_config::{
+    build_encrypted_provider_config, build_provider_config, ProviderConfigInput,
+};
+use crate::commands::shared_links::{record_share, ShareOptions, ShareTarget};
+use crate::providers::aws::credentials::resolve_credentials;
+use crate::providers::aws::CredentialSource;
+use crate::providers::config::ProviderConfig;
+use crate::providers::presign::{self, PresignMethod, PresignOptions, PresignedRequest};
+
+/// Build a presigned GET, PUT or POST request. Expiry is validated against
+/// the provider's maximum. With `share`, the request is recorded as a shared link.
+#[tauri::command]
+pub async fn generate_presigned_request(
+    config: ProviderConfigInput,
+    options: PresignOptions,
+    share: Option<ShareOptions>,
+) -> Result<PresignedRequest, String> {
+    let provider_config = build_encrypted_provider_config(&config).await?;
+    let request = presign::presign(&provider_config, &options)
+        .await
+        .map_err(|e| format!("Failed to generate presigned request: {}", e))?;
+
+    let shared_key = match &options.post {
+        Some(post) if request.method == PresignMethod::Post => post
+            .key_prefix
+            .clone()
+            .unwrap_or_else(|| options.key.clone()),
+        _ => options.key.clone(),
+    };
+    // Profile and environment credentials sign with a key that is not stored
+    let signing_key_id = match &provider_config {
+        ProviderConfig::Aws(aws) if aws.credentials != CredentialSource::Static => {
+            resolve_credentials(&aws.credentials, &aws.access_key_id, &aws.secret_access_key)
+                .await
+                .map(|credentials| credentials.access_key_id().to_string())
+                .unwrap_or_default()
+        }
+        _ => config.access_key_id.clone(),
+    };
+    record_share(
+        share,
+        ShareTarget {
+            provider: provider_config.provider_name(),
+            account_id: &config.account_id,
+            bucket: &config.bucket,
+            signing_key_id: &signing_key_id,
+        },
+        &shared_key,
+        request.method.as_str(),
+        &request.url,
+        options.expires_in_secs,
+    )
+    .await;
+
+    Ok(request)
+}
+
+/// Longest expiry (seconds) the provider accepts for presigned requests.
+#[tauri::command]
+pub async fn get_presign_max_expiry(config: ProviderConfigInput) -> Result<u64, String> {
+    let config = build_provider_config(&config)?;
+    Ok(presign::max_expiry_secs(&config))
+}
@@ -0,0 +1,145 @@
+//! Provider-aware config input shared by bucket-feature commands.
+
+use crate::commands::bucket_encryption::resolve_bucket_encryption;
+use crate::db::{default_endpoint_scheme, CacheScope};
+use crate::providers::config::ProviderConfig;
+use crate::providers::{aws, minio};
+use crate::r2;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderConfigInput {
+    pub provider: String,
+    pub account_id: String,
+    pub bucket: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub region: Option<String>,
+    pub endpoint_scheme: Option<String>,
+    pub endpoint_host: Option<String>,
+    pub force_path_style: Option<bool>,
+    #[serde(default)]
+    pub credentials: aws::CredentialSource,
+}
+
+impl ProviderConfigInput {
+    /// Cache scope of the bucket this input points at.
+    pub(crate) fn cache_scope(&self) -> CacheScope {
+        CacheScope::new(
+            &self.provider,
+            self.endpoint_scheme.as_deref(),
+            self.endpoint_host.as_deref(),
+            &self.account_id,
+            &self.bucket,
+        )
+    }
+}
+
+pub(crate) fn build_provider_config(input: &ProviderConfigInput) -> Result<ProviderConfig, String> {
+    match input.provider.as_str() {
+        "aws" => Ok(ProviderConfig::Aws(aws::AwsConfig {
+            bucket: input.bucket.clone(),
+            access_key_id: input.access_key_id.clone(),
+            secret_access_key: input.secret_access_key.clone(),
+            region: input
+                .region
+                .clone()
+                .ok_or_else(|| "AWS region is required".to_string())?,
+            endpoint_scheme: input.endpoint_scheme.clone(),
+            endpoint_host: input.endpoint_host.clone(),
+            force_path_style: input.force_path_style.unwrap_or(false),
+            credentials: input.credentials.clone(),
+            encryption: None,
+            storage_class: None,
+        })),
+        "minio" | "rustfs" => {
+            let config = minio::MinioConfig {
+                bucket: input.bucket.clone(),
+                access_key_id: input.access_key_id.clone(),
+                secret_access_key: input.secret_access_key.clone(),
+                endpoint_scheme: input
+                    .endpoint_scheme
+                    .clone()
+                    .unwrap_or_else(|| default_endpoint_scheme(&input.provider).to_string()),
+                endpoint_host: input
+                    .endpoint_host
+                    .clone()
+                    .ok_or_else(|| format!("{} endpoint host is required", input.provider))?,
+                force_path_style: input.provider == "rustfs"
+                    || input.force_path_style.unwrap_or(true),
+                encryption: None,
+            };
+            if input.provider == "rustfs" {
+                Ok(ProviderConfig::Rustfs(config))
+            } else {
+                Ok(ProviderConfig::Minio(config))
+            }
+        }
+        "r2" => Ok(ProviderConfig::R2(r2::R2Config {
+            account_id: input.account_id.clone(),
+            bucket: input.bucket.clone(),
+            access_key_id: input.access_key_id.clone(),
+            secret_access_key: input.secret_access_key.clone(),
+            encryption: None,
+        })),
+        _ => Err(format!("Unsupported provider: {}", input.provider)),
+    }
+}
+
+/// `build_provider_config` with the bucket's saved default encryption applied,
+/// for commands that read or write object data.
+pub(crate) async fn build_encrypted_provider_config(
+    input: &ProviderConfigInput,
+) -> Result<ProviderConfig, String> {
+    let mut config = build_provider_config(input)?;
+    config.set_encryption(
+        resolve_bucket_encryption(&input.provider, &input.account_id, &input.bucket).await?,
+    );
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::lazy_sync::LazyListInput;
+
+    #[test]
+    fn lazy_and_provider_inputs_share_a_scope() {
+        for provider in ["minio", "rustfs", "aws"] {
+            for scheme in [None, Some("http"), Some("https")] {
+                let provider_input = ProviderConfigInput {
+                    provider: provider.to_string(),
+                    account_id: "acct".to_string(),
+                    bucket: "photos".to_string(),
+                    access_key_id: String::new(),
+                    secret_access_key: String::new(),
+                    region: None,
+                    endpoint_scheme: scheme.map(str::to_string),
+                    endpoint_host: Some("nas:9000".to_string()),
+                    force_path_style: None,
+                    credentials: Default::default(),
+                };
+                let lazy_input = LazyListInput {
+                    account_id: "acct".to_string(),
+                    bucket: "photos".to_string(),
+                    access_key_id: String::new(),
+                    secret_access_key: String::new(),
+                    prefix: String::new(),
+                    provider: Some(provider.to_string()),
+                    endpoint_scheme: scheme.map(str::to_string),
+                    endpoint_host: Some("nas:9000".to_string()),
+                    force_path_style: None,
+                    region: None,
+                    force_refresh: None,
+                    credentials: Default::default(),
+                    sync_mode: Default::default(),
+                };
+                assert_eq!(
+                    provider_input.cache_scope(),
+                    lazy_input.cache_scope(),
+                    "{provider} {scheme:?}"
+                );
+            }
+        }
+    }
+}
@@ -1,13 +1,20 @@
 //! R2 API commands for Tauri frontend
 
+use crate::commands::batch_delete::{
+    delete_progress_emitter, finish_batch_delete, BatchDeleteResult,
+};
 use crate::commands::batch_move::{
-    fallback_batch_id, run_batch_move, BatchMoveResult, MoveOperation,
+    copy_progress_emitter, fallback_batch_id, run_batch_move, BatchMoveResult, MoveOperation,
 };
+use crate::commands::bucket_encryption::resolve_bucket_encryption;
 use crate::commands::cache_events::{get_unique_parent_paths, CacheUpdatedEvent};
-use crate::commands::delete_cache::{update_cache_after_batch_delete, update_cache_after_delete};
+use crate::commands::delete_cache::update_cache_after_delete;
+use crate::commands::delta_sync::{finish_delta_sync, SyncChangeSummary, SyncMode};
 use crate::commands::move_cache::{update_cache_after_batch_move, update_cache_after_move};
-use crate::db::{self, CachedFile};
+use crate::commands::shared_links::{record_share, ShareOptions, ShareTarget};
+use crate::db::{self, CacheScope, CachedFile, CachedObjectMeta};
 use crate::r2;
+use crate::secrets;
 use serde::{Deserialize, Serialize};
 use tauri::Emitter;
 
@@ -28,6 +35,7 @@ impl From<R2ConfigInput> for r2::R2Config {
             bucket: input.bucket,
             access_key_id: input.access_key_id,
             secret_access_key: input.secret_access_key,
+            encryption: None,
         }
     }
 }
@@ -54,6 +62,7 @@ pub async fn list_r2_buckets(
         bucket: String::new(),
         access_key_id,
         secret_access_key,
+        encryption: None,
     };
 
     r2::list_buckets(&config)
@@ -103,6 +112,8 @@ pub async fn list_all_r2_objects(
 pub struct SyncResult {
     pub count: i32,
     pub timestamp: i64,
+    /// What changed, for syncs applied as a delta.
+    pub changes: Option<SyncChangeSummary>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -116,30 +127,32 @@ struct IndexingProgress {
 #[tauri::command]
 pub async fn sync_bucket(
     config: R2ConfigInput,
+    mode: Option<SyncMode>,
     app: tauri::AppHandle,
 ) -> Result<SyncResult, String> {
     let r2_config: r2::R2Config = config.into();
     let bucket = r2_config.bucket.clone();
     let account_id = r2_config.account_id.clone();
+    let scope = CacheScope::r2(&account_id, &bucket);
     let now = chrono::Utc::now().timestamp();
+    let delta = mode.unwrap_or_default().is_delta(&scope).await?;
 
     let _ = app.emit("sync-phase", "fetching");
 
     // Clear old cache
-    db::begin_sync(&bucket, &account_id)
+    db::begin_sync(&scope)
         .await
         .map_err(|e| format!("Failed to clear cache: {}", e))?;
 
     // Spawn dedicated store task — DB writes happen here without blocking the fetch loop
     let (tx, mut rx) = tokio::sync::mpsc::channel::<Vec<CachedFile>>(8);
-    let store_bucket = bucket.clone();
-    let store_account_id = account_id.clone();
+    let store_scope = scope.clone();
     let store_app = app.clone();
     let store_handle = tokio::spawn(async move {
         let mut stored_count: usize = 0;
         while let Some(batch) = rx.recv().await {
             let batch_len = batch.len();
-            db::store_file_batch(&store_bucket, &store_account_id, &batch)
+            db::store_file_batch(&store_scope, &batch)
                 .await
                 .map_err(|e| format!("Failed to store files: {}", e))?;
             stored_count += batch_len;
@@ -194,6 +207,10 @@ pub async fn sync_bucket(
                             .map(|dt| dt.to_string())
                             .unwrap_or_default(),
                         synced_at: now,
+                        storage_class: obj.storage_class().map(|class| class.as_str().to_string()),
+                        etag: obj.e_tag().map(str::to_string),
+                        content_type: None,
+                        checksum: None,
                     });
                 }
             }
@@ -222,27 +239,34 @@ pub async fn sync_bucket(
         .map_err(|e| format!("Store task panicked: {}", e))?
         .map_err(|e| format!("Store failed: {}", e))?;
 
-    db::finish_sync(&bucket, &account_id, stored_count)
-        .await
-        .map_err(|e| format!("Failed to update sync metadata: {}", e))?;
+    let changes = if delta {
+        let _ = app.emit("sync-phase", "indexing");
+        Some(finish_delta_sync(&app, &scope, stored_count, &folder_keys).await?)
+    } else {
+        db::finish_sync(&scope, stored_count)
+            .await
+            .map_err(|e| format!("Failed to update sync metadata: {}", e))?;
 
-    // Build directory tree from DB
-    let _ = app.emit("sync-phase", "indexing");
+        // Build directory tree from DB
+        let _ = app.emit("sync-phase", "indexing");
 
-    let app_clone = app.clone();
-    let indexing_callback = move |current: usize, total: usize| {
-        let _ = app_clone.emit("indexing-progress", IndexingProgress { current, total });
-    };
+        let app_clone = app.clone();
+        let indexing_callback = move |current: usize, total: usize| {
+            let _ = app_clone.emit("indexing-progress", IndexingProgress { current, total });
+        };
 
-    db::build_directory_tree_from_db(&bucket, &account_id, &folder_keys, Some(indexing_callback))
-        .await
-        .map_err(|e| format!("Failed to build directory tree: {}", e))?;
+        db::build_directory_tree_from_db(&scope, &folder_keys, Some(indexing_callback))
+            .await
+            .map_err(|e| format!("Failed to build directory tree: {}", e))?;
+        None
+    };
 
     let _ = app.emit("sync-phase", "complete");
 
     Ok(SyncResult {
         count: stored_count as i32,
         timestamp: now,
+        changes,
     })
 }
 
@@ -286,8 +310,7 @@ pub async fn delete_r2_object(
     key: String,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
-    let bucket = config.bucket.clone();
-    let account_id = config.account_id.clone();
+    let scope = CacheScope::r2(&config.account_id, &config.bucket);
     let r2_config: r2::R2Config = config.into();
 
     // Delete from R2
@@ -296,90 +319,31 @@ pub async fn delete_r2_object(
         .map_err(|e| format!("Failed to delete object: {}", e))?;
 
     // Update cache and emit events (including paths-removed if any folders became empty)
-    update_cache_after_delete(&app, &bucket, &account_id, &key).await?;
+    update_cache_after_delete(&app, &scope, &key).await?;
 
     Ok(())
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub struct BatchDeleteProgress {
-    pub completed: usize,
-    pub total: usize,
-    pub failed: usize,
-}
-
-#[derive(Debug, Clone, Serialize)]
-pub struct BatchDeleteResult {
-    pub deleted: usize,
-    pub failed: usize,
-    pub errors: Vec<String>,
-}
-
 #[tauri::command]
 pub async fn batch_delete_r2_objects(
     config: R2ConfigInput,
     keys: Vec<String>,
     app: tauri::AppHandle,
 ) -> Result<BatchDeleteResult, String> {
-    let bucket = config.bucket.clone();
-    let account_id = config.account_id.clone();
+    let scope = CacheScope::r2(&config.account_id, &config.bucket);
     let r2_config: r2::R2Config = config.into();
     let total = keys.len();
 
     if total == 0 {
-        return Ok(BatchDeleteResult {
-            deleted: 0,
-            failed: 0,
-            errors: vec![],
-        });
-    }
-
-    let mut completed = 0;
-    let mut failed = 0;
-    let mut errors: Vec<String> = vec![];
-    let mut deleted_keys: Vec<String> = vec![];
-
-    const BATCH_SIZE: usize = 1000;
-
-    for chunk in keys.chunks(BATCH_SIZE) {
-        let batch_keys: Vec<String> = chunk.to_vec();
-        let batch_count = batch_keys.len();
-
-        match r2::delete_objects(&r2_config, batch_keys.clone()).await {
-            Ok(_) => {
-                completed += batch_count;
-                deleted_keys.extend(batch_keys);
-            }
-            Err(e) => {
-                failed += batch_count;
-                errors.push(format!("Batch delete failed: {}", e));
-            }
-        }
-
-        let _ = app.emit(
-            "batch-delete-progress",
-            BatchDeleteProgress {
-                completed,
-                total,
-                failed,
-            },
-        );
+        return Ok(BatchDeleteResult::empty());
     }
 
-    // Update cache and emit events (including paths-removed if any folders became empty)
-    if !deleted_keys.is_empty() {
-        if let Err(e) =
-            update_cache_after_batch_delete(&app, &bucket, &account_id, &deleted_keys).await
-        {
-            errors.push(e);
-        }
-    }
+    let on_progress = delete_progress_emitter(&app, total);
+    let outcome = r2::delete_objects(&r2_config, keys, Some(on_progress))
+        .await
+        .map_err(|e| format!("Batch delete failed: {}", e))?;
 
-    Ok(BatchDeleteResult {
-        deleted: completed,
-        failed,
-        errors,
-    })
+    Ok(finish_batch_delete(&app, &scope, outcome, Vec::new()).await)
 }
 
 // ============ Rename/Move Commands ============
@@ -393,15 +357,22 @@ pub async fn rename_r2_object(
 ) -> Result<(), String> {
     let bucket = config.bucket.clone();
     let account_id = config.account_id.clone();
-    let r2_config: r2::R2Config = config.into();
+    let scope = CacheScope::r2(&account_id, &bucket);
+    let mut r2_config: r2::R2Config = config.into();
+    r2_config.encryption = resolve_bucket_encryption("r2", &account_id, &bucket).await?;
 
     // Rename in R2
-    r2::rename_object(&r2_config, &old_key, &new_key)
-        .await
-        .map_err(|e| format!("Failed to rename object: {}", e))?;
+    r2::rename_object(
+        &r2_config,
+        &old_key,
+        &new_key,
+        Some(copy_progress_emitter(&app, &old_key)),
+    )
+    .await
+    .map_err(|e| format!("Failed to rename object: {}", e))?;
 
     // Update cache and emit events (including paths-created/removed)
-    update_cache_after_move(&app, &bucket, &account_id, &old_key, &new_key).await?;
+    update_cache_after_move(&app, &scope, &old_key, &new_key).await?;
 
     Ok(())
 }
@@ -415,13 +386,17 @@ pub async fn batch_move_r2_objects(
 ) -> Result<BatchMoveResult, String> {
     let bucket = config.bucket.clone();
     let account_id = config.account_id.clone();
-    let r2_config: r2::R2Config = config.into();
+    let scope = CacheScope::r2(&account_id, &bucket);
+    let mut r2_config: r2::R2Config = config.into();
+    r2_config.encryption = resolve_bucket_encryption("r2", &account_id, &bucket).await?;
     let batch_id = batch_id.unwrap_or_else(fallback_batch_id);
 
+    let progress_app = app.clone();
     let rename = move |op: MoveOperation| {
         let config = r2_config.clone();
+        let on_progress = copy_progress_emitter(&progress_app, &op.old_key);
         async move {
-            r2::rename_object(&config, &op.old_key, &op.new_key)
+            r2::rename_object(&config, &op.old_key, &op.new_key, Some(on_progress))
                 .await
                 .map_err(|e| e.to_string())
         }
@@ -431,9 +406,7 @@ pub async fn batch_move_r2_objects(
 
     let mut errors = outcome.errors;
     if !outcome.successful.is_empty() {
-        if let Err(e) =
-            update_cache_after_batch_move(&app, &bucket, &account_id, &outcome.successful).await
-        {
+        if let Err(e) = update_cache_after_batch_move(&app, &scope, &outcome.successful).await {
             errors.push(e);
         }
     }
@@ -452,13 +425,34 @@ pub async fn generate_signed_url(
     config: R2ConfigInput,
     key: String,
     expires_in: Option<u64>,
+    share: Option<ShareOptions>,
 ) -> Result<String, String> {
-    let r2_config: r2::R2Config = config.into();
+    let account_id = config.account_id.clone();
+    let access_key_id = config.access_key_id.clone();
+    let mut r2_config: r2::R2Config = config.into();
+    r2_config.encryption = resolve_bucket_encryption("r2", &account_id, &r2_config.bucket).await?;
     let expires_in_secs = expires_in.unwrap_or(3600);
 
-    r2::generate_presigned_url(&r2_config, &key, expires_in_secs)
+    let url = r2::generate_presigned_url(&r2_config, &key, expires_in_secs)
         .await
-        .map_err(|e| format!("Failed to generate signed URL: {}", e))
+        .map_err(|e| format!("Failed to generate signed URL: {}", e))?;
+
+    record_share(
+        share,
+        ShareTarget {
+            provider: "r2",
+            account_id: &account_id,
+            bucket: &r2_config.bucket,
+            signing_key_id: &access_key_id,
+        },
+        &key,
+        "GET",
+        &url,
+        expires_in_secs,
+    )
+    .await;
+
+    Ok(url)
 }
 
 // ============ Upload Content ============
@@ -473,7 +467,9 @@ pub async fn upload_r2_content(
 ) -> Result<String, String> {
     let bucket = config.bucket.clone();
     let account_id = config.account_id.clone();
-    let r2_config: r2::R2Config = config.into();
+    let scope = CacheScope::r2(&account_id, &bucket);
+    let mut r2_config: r2::R2Config = config.into();
+    r2_config.encryption = resolve_bucket_encryption("r2", &account_id, &bucket).await?;
 
     // Calculate content size before converting to bytes
     let content_bytes = content.into_bytes();
@@ -488,22 +484,22 @@ pub async fn upload_r2_content(
     let last_modified = chrono::Utc::now().to_rfc3339();
 
     // Update the file record and get (size_delta, is_new_file)
-    let (size_delta, is_new_file) =
-        db::update_cached_file(&bucket, &account_id, &key, new_size, &last_modified)
-            .await
-            .map_err(|e| format!("Failed to update file cache: {}", e))?;
+    let (size_delta, is_new_file) = db::update_cached_file(&scope, &key, new_size, &last_modified)
+        .await
+        .map_err(|e| format!("Failed to update file cache: {}", e))?;
+    let meta = CachedObjectMeta {
+        etag: (!etag.is_empty()).then(|| etag.clone()),
+        content_type,
+        ..Default::default()
+    };
+    db::set_cached_object_meta(&scope, &key, &meta)
+        .await
+        .map_err(|e| format!("Failed to update file cache: {}", e))?;
 
     // Update directory tree (all ancestor folders)
-    db::update_directory_tree_for_file(
-        &bucket,
-        &account_id,
-        &key,
-        size_delta,
-        &last_modified,
-        is_new_file,
-    )
-    .await
-    .map_err(|e| format!("Failed to update directory tree: {}", e))?;
+    db::update_directory_tree_for_file(&scope, &key, size_delta, &last_modified, is_new_file)
+        .await
+        .map_err(|e| format!("Failed to update directory tree: {}", e))?;
 
     // Emit cache-updated event
     let _ = app.emit(
@@ -516,3 +512,135 @@ pub async fn upload_r2_content(
 
     Ok(etag)
 }
+
+// ============ Public Access (Cloudflare API) ============
+
+#[derive(Debug, Clone, Serialize)]
+pub struct R2PublicAccess {
+    pub managed_domain: r2::ManagedDomain,
+    pub custom_domains: Vec<r2::CustomDomain>,
+    /// Whether the bucket is reachable publicly through any domain.
+    pub is_public: bool,
+    /// Domain stored locally as the bucket's public domain after syncing.
+    pub public_domain: Option<String>,
+}
+
+/// Resolve (account_id, api_token) for a saved token.
+async fn load_cloudflare_credentials(token_id: i64) -> Result<(String, String), String> {
+    let token = db::get_token(token_id)
+        .await
+        .map_err(|e| format!("Failed to load token: {}", e))?
+        .ok_or_else(|| format!("Token {} not found", token_id))?;
+    let api_token = secrets::resolve(&token.api_token).await?;
+    Ok((token.account_id, api_token))
+}
+
+/// Fetch the real public-access state and write it back to the local bucket row.
+async fn sync_r2_public_access(token_id: i64, bucket: &str) -> Result<R2PublicAccess, String> {
+    let (account_id, api_token) = load_cloudflare_credentials(token_id).await?;
+
+    let managed_domain = r2::get_managed_domain(&account_id, &api_token, bucket)
+        .await
+        .map_err(|e| format!("Failed to get r2.dev access: {}", e))?;
+    let custom_domains = r2::list_custom_domains(&account_id, &api_token, bucket)
+        .await
+        .map_err(|e| format!("Failed to list custom domains: {}", e))?;
+
+    let active_custom: Vec<&str> = custom_domains
+        .iter()
+        .filter(|d| d.is_active())
+        .map(|d| d.domain.as_str())
+        .collect();
+    let is_public = managed_domain.enabled || !active_custom.is_empty();
+
+    // Keep the user's chosen domain while it still serves the bucket,
+    // otherwise prefer a custom domain over r2.dev.
+    let saved_domain = db::list_buckets_by_token(token_id)
+        .await
+        .map_err(|e| format!("Failed to load buckets: {}", e))?
+        .into_iter()
+        .find(|b| b.name == bucket)
+        .and_then(|b| b.public_domain);
+    let still_serving = |domain: &str| {
+        active_custom.contains(&domain)
+            || (managed_domain.enabled && managed_domain.domain == domain)
+    };
+    let public_domain = match saved_domain {
+        Some(domain) if still_serving(&domain) => Some(domain),
+        _ => active_custom.first().map(|d| d.to_string()).or_else(|| {
+            managed_domain
+                .enabled
+                .then(|| managed_domain.domain.clone())
+        }),
+    };
+
+    db::set_bucket_public_state(token_id, bucket, is_public, public_domain.as_deref())
+        .await
+        .map_err(|e| format!("Failed to update bucket: {}", e))?;
+
+    Ok(R2PublicAccess {
+        managed_domain,
+        custom_domains,
+        is_public,
+        public_domain,
+    })
+}
+
+#[tauri::command]
+pub async fn get_r2_public_access(token_id: i64, bucket: String) -> Result<R2PublicAccess, String> {
+    sync_r2_public_access(token_id, &bucket).await
+}
+
+#[tauri::command]
+pub async fn set_r2_managed_domain(
+    token_id: i64,
+    bucket: String,
+    enabled: bool,
+) -> Result<R2PublicAccess, String> {
+    let (account_id, api_token) = load_cloudflare_credentials(token_id).await?;
+    r2::set_managed_domain(&account_id, &api_token, &bucket, enabled)
+        .await
+        .map_err(|e| format!("Failed to update r2.dev access: {}", e))?;
+    sync_r2_public_access(token_id, &bucket).await
+}
+
+#[tauri::command]
+pub async fn add_r2_custom_domain(
+    token_id: i64,
+    bucket: String,
+    domain: String,
+    zone_id: String,
+) -> Result<R2PublicAccess, String> {
+    let (account_id, api_token) = load_cloudflare_credentials(token_id).await?;
+    r2::add_custom_domain(&account_id, &api_token, &bucket, &domain, &zone_id, true)
+        .await
+        .map_err(|e| format!("Failed to add custom domain: {}", e))?;
+    sync_r2_public_access(token_id, &bucket).await
+}
+
+#[tauri::command]
+pub async fn set_r2_custom_domain_enabled(
+    token_id: i64,
+    bucket: String,
+    domain: String,
+    enabled: bool,
+) -> Result<R2PublicAccess, String> {
+    let (account_id, api_token) = load_cloudflare_credentials(token_id).await?;
+    r2::set_custom_domain_enabled(&account_id, &api_token, &bucket, &domain, enabled)
+        .await
+        .map_err(|e| format!("Failed to update custom domain: {}", e))?;
+    sync_r2_public_access(token_id, &bucket).await
+}
+
+#[tauri::command]
+pub async fn remove_r2_custom_domain(
+    token_id: i64,
+    bucket: String,
+    domain: String,
+) -> Result<R2PublicAccess, String> {
+    let (account_id, api_token) = load_cloudflare_credentials(token_id).await?;
+    r2::remove_custom_domain(&account_id, &api_token, &bucket, &domain)
+        .await
+        .map_err(|e| format!("Failed to remove custom domain: {}", e))?;
+    sync_r2_public_access(token_id, &bucket).await
+}
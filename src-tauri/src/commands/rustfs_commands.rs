@@ -1,6 +1,9 @@
 use super::minio_commands::{
     self, ListObjectsInput as MinioListObjectsInput, MinioConfigInput, SyncResult, UploadResult,
 };
+use crate::commands::bucket_encryption::resolve_bucket_encryption;
+use crate::commands::delta_sync::SyncMode;
+use crate::commands::shared_links::{record_share, ShareOptions, ShareTarget};
 use crate::providers::rustfs;
 use serde::Deserialize;
 
@@ -26,6 +29,7 @@ impl From<RustfsConfigInput> for MinioConfigInput {
             endpoint_scheme: input.endpoint_scheme,
             endpoint_host: input.endpoint_host,
             force_path_style: true,
+            provider: "rustfs",
         }
     }
 }
@@ -87,9 +91,10 @@ pub async fn list_all_rustfs_objects(
 #[tauri::command]
 pub async fn sync_rustfs_bucket(
     config: RustfsConfigInput,
+    mode: Option<SyncMode>,
     app: tauri::AppHandle,
 ) -> Result<SyncResult, String> {
-    minio_commands::sync_minio_bucket(config.into(), app).await
+    minio_commands::sync_minio_bucket(config.into(), mode, app).await
 }
 
 #[tauri::command]
@@ -115,7 +120,7 @@ pub async fn batch_delete_rustfs_objects(
     config: RustfsConfigInput,
     keys: Vec<String>,
     app: tauri::AppHandle,
-) -> Result<minio_commands::BatchDeleteResult, String> {
+) -> Result<crate::commands::batch_delete::BatchDeleteResult, String> {
     minio_commands::batch_delete_minio_objects(config.into(), keys, app).await
 }
 
@@ -144,8 +149,36 @@ pub async fn generate_rustfs_signed_url(
     config: RustfsConfigInput,
     key: String,
     expires_in_secs: Option<u64>,
+    share: Option<ShareOptions>,
 ) -> Result<String, String> {
-    minio_commands::generate_minio_signed_url(config.into(), key, expires_in_secs).await
+    let account_id = config.account_id.clone();
+    let access_key_id = config.access_key_id.clone();
+    let minio_config: MinioConfigInput = config.into();
+    let mut rustfs_config: rustfs::RustfsConfig = minio_config.into();
+    rustfs_config.encryption =
+        resolve_bucket_encryption("rustfs", &account_id, &rustfs_config.bucket).await?;
+    let expires_in_secs = expires_in_secs.unwrap_or(3600);
+
+    let url = rustfs::generate_presigned_url(&rustfs_config, &key, expires_in_secs)
+        .await
+        .map_err(|e| format!("Failed to generate signed URL: {}", e))?;
+
+    record_share(
+        share,
+        ShareTarget {
+            provider: "rustfs",
+            account_id: &account_id,
+            bucket: &rustfs_config.bucket,
+            signing_key_id: &access_key_id,
+        },
+        &key,
+        "GET",
+        &url,
+        expires_in_secs,
+    )
+    .await;
+
+    Ok(url)
 }
 
 #[tauri::command]
@@ -175,19 +208,25 @@ pub async fn upload_rustfs_file(
     endpoint_host: String,
     _force_path_style: bool,
 ) -> Result<UploadResult, String> {
-    minio_commands::upload_minio_file(
+    let config = rustfs::RustfsConfig {
+        bucket,
+        access_key_id,
+        secret_access_key,
+        endpoint_scheme,
+        endpoint_host,
+        force_path_style: true,
+        encryption: None,
+    };
+
+    minio_commands::upload_file_to(
+        "rustfs",
         app,
         task_id,
         file_path,
         key,
         content_type,
         account_id,
-        bucket,
-        access_key_id,
-        secret_access_key,
-        endpoint_scheme,
-        endpoint_host,
-        true,
+        config,
     )
     .await
 }
@@ -0,0 +1,24 @@
+//! Secret store commands: status and the master passphrase of the encrypted
+//! file used when the OS keychain is unavailable.
+
+use crate::secrets::{self, SecretStoreStatus};
+
+#[tauri::command]
+pub async fn get_secret_store_status() -> Result<SecretStoreStatus, String> {
+    secrets::status().await
+}
+
+/// Unlock the file store, creating it with this passphrase on first use, then
+/// move credentials still saved in plain text into it.
+#[tauri::command]
+pub async fn unlock_secret_store(passphrase: String) -> Result<SecretStoreStatus, String> {
+    secrets::unlock(&passphrase).await?;
+    secrets::migrate_plaintext_credentials().await?;
+    secrets::status().await
+}
+
+#[tauri::command]
+pub async fn lock_secret_store() -> Result<(), String> {
+    secrets::lock().await;
+    Ok(())
+}
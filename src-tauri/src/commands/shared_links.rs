@@ -0,0 +1,220 @@
+//! Shared link registry: signed URLs handed out for sharing are recorded with
+//! their expiry and the credential that signed them.
+//!
+//! A presigned URL cannot be invalidated on its own; it stays valid until it
+//! expires or the signing access key is rotated. Revoking a link therefore
+//! explains the rotation and lists every other live link the rotation breaks.
+
+use crate::db::{self, NewSharedLink, SharedLink, SharedLinkStatus};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+/// Passed by signed-URL commands when the URL is being shared (as opposed to
+/// an internal preview), so it gets recorded.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ShareOptions {
+    pub note: Option<String>,
+}
+
+/// Who signed a link and for which bucket.
+pub(crate) struct ShareTarget<'a> {
+    pub provider: &'a str,
+    pub account_id: &'a str,
+    pub bucket: &'a str,
+    pub signing_key_id: &'a str,
+}
+
+/// Record a generated URL when `share` is set. Failures are logged rather than
+/// returned so the caller still gets its URL.
+pub(crate) async fn record_share(
+    share: Option<ShareOptions>,
+    target: ShareTarget<'_>,
+    key: &str,
+    method: &str,
+    url: &str,
+    expires_in_secs: u64,
+) {
+    let Some(share) = share else {
+        return;
+    };
+
+    let link = NewSharedLink {
+        provider: target.provider.to_string(),
+        account_id: target.account_id.to_string(),
+        bucket: target.bucket.to_string(),
+        key: key.to_string(),
+        method: method.to_string(),
+        url: url.to_string(),
+        signing_key_id: target.signing_key_id.to_string(),
+        note: share.note.filter(|n| !n.trim().is_empty()),
+        expires_at: chrono::Utc::now().timestamp() + expires_in_secs as i64,
+    };
+
+    if let Err(e) = db::create_shared_link(&link).await {
+        warn!(
+            "shared_link_record_failed: {}/{} error={}",
+            link.bucket, link.key, e
+        );
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedLinkEntry {
+    #[serde(flatten)]
+    pub link: SharedLink,
+    /// `active`, `expired` or `revoked`
+    pub status: &'static str,
+}
+
+impl SharedLinkEntry {
+    fn new(link: SharedLink, now: i64) -> Self {
+        let status = if link.is_expired(now) {
+            "expired"
+        } else if link.revoked_at.is_some() {
+            "revoked"
+        } else {
+            "active"
+        };
+        SharedLinkEntry { link, status }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RevokeSharedLinkResult {
+    pub link: SharedLinkEntry,
+    pub explanation: String,
+    /// Where to rotate the signing key for this provider.
+    pub rotation_hint: String,
+    /// Other unexpired links signed by the same key; rotating it breaks them too.
+    pub affected_links: Vec<SharedLinkEntry>,
+}
+
+fn rotation_hint(provider: &str) -> &'static str {
+    match provider {
+        "r2" => "Roll or delete the R2 API token in the Cloudflare dashboard (R2 > Manage API tokens), then update the token in this app.",
+        "aws" => "Deactivate the access key in IAM (or let the temporary session expire), create a new one, then update the account in this app.",
+        _ => "Remove the access key on the server (e.g. `mc admin user svcacct rm`), create a new one, then update the account in this app.",
+    }
+}
+
+fn mask_key_id(key_id: &str) -> String {
+    let visible: String = key_id
+        .chars()
+        .rev()
+        .take(4)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    format!("…{}", visible)
+}
+
+fn revoke_explanation(link: &SharedLink, affected: usize) -> String {
+    let mut text = format!(
+        "Signed URLs cannot be revoked individually. This link stays valid until {} unless access key {} is rotated.",
+        chrono::DateTime::from_timestamp(link.expires_at, 0)
+            .map(|d| d.to_rfc3339())
+            .unwrap_or_else(|| link.expires_at.to_string()),
+        mask_key_id(&link.signing_key_id),
+    );
+    if affected > 0 {
+        text.push_str(&format!(
+            " Rotating that key also invalidates {} other active link{} signed with it.",
+            affected,
+            if affected == 1 { "" } else { "s" }
+        ));
+    }
+    text
+}
+
+#[tauri::command]
+pub async fn list_shared_links(
+    status: Option<SharedLinkStatus>,
+) -> Result<Vec<SharedLinkEntry>, String> {
+    let now = chrono::Utc::now().timestamp();
+    let links = db::list_shared_links(status.unwrap_or_default())
+        .await
+        .map_err(|e| format!("Failed to list shared links: {}", e))?;
+    Ok(links
+        .into_iter()
+        .map(|link| SharedLinkEntry::new(link, now))
+        .collect())
+}
+
+/// Mark a link revoked and report what it takes to actually invalidate it.
+#[tauri::command]
+pub async fn revoke_shared_link(id: i64) -> Result<RevokeSharedLinkResult, String> {
+    db::mark_shared_link_revoked(id)
+        .await
+        .map_err(|e| format!("Failed to revoke link: {}", e))?;
+    let link = db::get_shared_link(id)
+        .await
+        .map_err(|e| format!("Failed to load link: {}", e))?
+        .ok_or_else(|| format!("Shared link {} not found", id))?;
+
+    let affected = db::list_links_signed_by(&link.provider, &link.signing_key_id, link.id)
+        .await
+        .map_err(|e| format!("Failed to list related links: {}", e))?;
+
+    let now = chrono::Utc::now().timestamp();
+    Ok(RevokeSharedLinkResult {
+        explanation: revoke_explanation(&link, affected.len()),
+        rotation_hint: rotation_hint(&link.provider).to_string(),
+        link: SharedLinkEntry::new(link, now),
+        affected_links: affected
+            .into_iter()
+            .map(|l| SharedLinkEntry::new(l, now))
+            .collect(),
+    })
+}
+
+/// Remove a link from the registry (does not affect the URL itself).
+#[tauri::command]
+pub async fn delete_shared_link(id: i64) -> Result<(), String> {
+    db::delete_shared_link(id)
+        .await
+        .map_err(|e| format!("Failed to delete shared link: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(expires_at: i64, revoked_at: Option<i64>) -> SharedLink {
+        SharedLink {
+            id: 1,
+            provider: "aws".to_string(),
+            account_id: "acct".to_string(),
+            bucket: "media".to_string(),
+            key: "a.txt".to_string(),
+            method: "GET".to_string(),
+            url: "https://example.com/a.txt".to_string(),
+            signing_key_id: "AKIAEXAMPLEKEY1234".to_string(),
+            note: None,
+            created_at: 0,
+            expires_at,
+            revoked_at,
+        }
+    }
+
+    #[test]
+    fn status_reflects_expiry_and_revocation() {
+        assert_eq!(SharedLinkEntry::new(link(100, None), 50).status, "active");
+        assert_eq!(
+            SharedLinkEntry::new(link(100, Some(60)), 70).status,
+            "revoked"
+        );
+        assert_eq!(
+            SharedLinkEntry::new(link(100, Some(60)), 100).status,
+            "expired"
+        );
+    }
+
+    #[test]
+    fn explanation_masks_key_and_counts_affected_links() {
+        let text = revoke_explanation(&link(0, None), 2);
+        assert!(text.contains("…1234"));
+        assert!(!text.contains("AKIAEXAMPLE"));
+        assert!(text.contains("2 other active links"));
+    }
+}
@@ -0,0 +1,241 @@
+//! Storage analytics of the current bucket, computed from the cached index
+
+use super::file_cache::{CachedFileResponse, StorageClassUsageResponse};
+use crate::db::{self, AgeBucket, CacheScope, CurrentConfig, PrefixUsage, TypeGrouping, TypeUsage};
+use crate::providers::pricing;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const DEFAULT_LIMIT: usize = 20;
+
+// ============ Response Types ============
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrefixUsageResponse {
+    pub path: String,
+    #[serde(rename = "fileCount")]
+    pub file_count: i64,
+    #[serde(rename = "totalSize")]
+    pub total_size: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TypeUsageResponse {
+    pub kind: Option<String>,
+    #[serde(rename = "fileCount")]
+    pub file_count: i64,
+    #[serde(rename = "totalSize")]
+    pub total_size: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgeBucketResponse {
+    pub label: String,
+    #[serde(rename = "maxAgeDays")]
+    pub max_age_days: Option<i64>,
+    #[serde(rename = "fileCount")]
+    pub file_count: i64,
+    #[serde(rename = "totalSize")]
+    pub total_size: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StorageSnapshotResponse {
+    #[serde(rename = "recordedAt")]
+    pub recorded_at: i64,
+    #[serde(rename = "fileCount")]
+    pub file_count: i64,
+    #[serde(rename = "totalSize")]
+    pub total_size: i64,
+    pub classes: Vec<StorageClassUsageResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StorageClassCostResponse {
+    #[serde(rename = "storageClass")]
+    pub storage_class: Option<String>,
+    #[serde(rename = "totalSize")]
+    pub total_size: i64,
+    /// USD per GB-month; `None` when the class has no known price.
+    #[serde(rename = "pricePerGbMonth")]
+    pub price_per_gb_month: Option<f64>,
+    #[serde(rename = "monthlyCost")]
+    pub monthly_cost: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StorageCostResponse {
+    pub provider: String,
+    pub classes: Vec<StorageClassCostResponse>,
+    /// Sum over the classes with a known price.
+    #[serde(rename = "monthlyCost")]
+    pub monthly_cost: f64,
+    /// Bytes in classes without a known price, left out of `monthlyCost`.
+    #[serde(rename = "unpricedSize")]
+    pub unpriced_size: i64,
+}
+
+impl From<PrefixUsage> for PrefixUsageResponse {
+    fn from(usage: PrefixUsage) -> Self {
+        PrefixUsageResponse {
+            path: usage.path,
+            file_count: usage.file_count,
+            total_size: usage.total_size,
+        }
+    }
+}
+
+impl From<TypeUsage> for TypeUsageResponse {
+    fn from(usage: TypeUsage) -> Self {
+        TypeUsageResponse {
+            kind: usage.kind,
+            file_count: usage.file_count,
+            total_size: usage.total_size,
+        }
+    }
+}
+
+impl From<AgeBucket> for AgeBucketResponse {
+    fn from(bucket: AgeBucket) -> Self {
+        AgeBucketResponse {
+            label: bucket.label,
+            max_age_days: bucket.max_age_days,
+            file_count: bucket.file_count,
+            total_size: bucket.total_size,
+        }
+    }
+}
+
+// ============ Helper Functions ============
+
+async fn get_current_config() -> Result<CurrentConfig, String> {
+    db::get_current_config()
+        .await
+        .map_err(|e| format!("Failed to get current config: {}", e))?
+        .ok_or_else(|| "No active configuration".to_string())
+}
+
+async fn get_current_scope() -> Result<CacheScope, String> {
+    Ok(CacheScope::from(&get_current_config().await?))
+}
+
+// ============ Commands ============
+
+/// Largest folders of the bucket, or the immediate subfolders of `parent`.
+#[tauri::command]
+pub async fn get_largest_prefixes(
+    parent: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<PrefixUsageResponse>, String> {
+    let scope = get_current_scope().await?;
+
+    let prefixes =
+        db::get_largest_prefixes(&scope, parent.as_deref(), limit.unwrap_or(DEFAULT_LIMIT))
+            .await
+            .map_err(|e| format!("Failed to get largest prefixes: {}", e))?;
+
+    Ok(prefixes.into_iter().map(|p| p.into()).collect())
+}
+
+#[tauri::command]
+pub async fn get_largest_files(limit: Option<usize>) -> Result<Vec<CachedFileResponse>, String> {
+    let scope = get_current_scope().await?;
+
+    let files = db::get_largest_files(&scope, limit.unwrap_or(DEFAULT_LIMIT))
+        .await
+        .map_err(|e| format!("Failed to get largest files: {}", e))?;
+
+    Ok(files.into_iter().map(|f| f.into()).collect())
+}
+
+#[tauri::command]
+pub async fn get_type_breakdown(
+    grouping: Option<TypeGrouping>,
+) -> Result<Vec<TypeUsageResponse>, String> {
+    let scope = get_current_scope().await?;
+
+    let usage = db::get_type_breakdown(&scope, grouping.unwrap_or_default())
+        .await
+        .map_err(|e| format!("Failed to get type breakdown: {}", e))?;
+
+    Ok(usage.into_iter().map(|u| u.into()).collect())
+}
+
+#[tauri::command]
+pub async fn get_age_histogram() -> Result<Vec<AgeBucketResponse>, String> {
+    let scope = get_current_scope().await?;
+
+    let buckets = db::get_age_histogram(&scope, chrono::Utc::now())
+        .await
+        .map_err(|e| format!("Failed to get age histogram: {}", e))?;
+
+    Ok(buckets.into_iter().map(|b| b.into()).collect())
+}
+
+/// Bucket totals recorded by past syncs since `since` (Unix seconds).
+#[tauri::command]
+pub async fn get_storage_history(
+    since: Option<i64>,
+) -> Result<Vec<StorageSnapshotResponse>, String> {
+    let scope = get_current_scope().await?;
+
+    let history = db::get_storage_history(&scope, since)
+        .await
+        .map_err(|e| format!("Failed to get storage history: {}", e))?;
+
+    Ok(history
+        .into_iter()
+        .map(|snapshot| StorageSnapshotResponse {
+            recorded_at: snapshot.recorded_at,
+            file_count: snapshot.file_count,
+            total_size: snapshot.total_size,
+            classes: snapshot.classes.into_iter().map(|u| u.into()).collect(),
+        })
+        .collect())
+}
+
+/// Monthly storage cost of the cached bucket at list prices, with
+/// `price_overrides` (USD per GB-month by storage class) taking precedence.
+#[tauri::command]
+pub async fn estimate_storage_cost(
+    price_overrides: Option<HashMap<String, f64>>,
+) -> Result<StorageCostResponse, String> {
+    let config = get_current_config().await?;
+    let scope = CacheScope::from(&config);
+    let overrides = price_overrides.unwrap_or_default();
+
+    let usage = db::get_storage_class_usage(&scope)
+        .await
+        .map_err(|e| format!("Failed to get storage class usage: {}", e))?;
+
+    let mut monthly_cost = 0.0;
+    let mut unpriced_size = 0;
+    let classes = usage
+        .into_iter()
+        .map(|u| {
+            let price = pricing::price_per_gb_month(
+                &config.provider,
+                u.storage_class.as_deref(),
+                &overrides,
+            );
+            let cost = price.map(|price| pricing::monthly_cost(u.total_size, price));
+            match cost {
+                Some(cost) => monthly_cost += cost,
+                None => unpriced_size += u.total_size,
+            }
+            StorageClassCostResponse {
+                storage_class: u.storage_class,
+                total_size: u.total_size,
+                price_per_gb_month: price,
+                monthly_cost: cost,
+            }
+        })
+        .collect();
+
+    Ok(StorageCostResponse {
+        provider: config.provider.as_str().to_string(),
+        classes,
+        monthly_cost,
+        unpriced_size,
+    })
+}
@@ -0,0 +1,214 @@
+//! Background sync jobs, one per (provider, endpoint, account, bucket).
+//!
+//! Each job has its own id and cancellation flag, so syncing one bucket no
+//! longer cancels another. Starting a sync for a bucket that already has one
+//! replaces it, and runs of one bucket (including cancelled ones still
+//! winding down) never overlap, since they share its staging rows. At most
+//! `MAX_CONCURRENT_SYNCS` jobs list at once, the rest wait queued.
+
+use crate::db::CacheScope;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::sync::{OwnedMutexGuard, Semaphore, SemaphorePermit};
+
+const MAX_CONCURRENT_SYNCS: usize = 3;
+
+type ScopeLock = Arc<tokio::sync::Mutex<()>>;
+
+static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(0);
+static SYNC_JOBS: LazyLock<Mutex<HashMap<CacheScope, SyncJob>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+// Kept per bucket after its jobs finish, so a later run waits on the same lock
+static SCOPE_LOCKS: LazyLock<Mutex<HashMap<CacheScope, ScopeLock>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static SYNC_SLOTS: LazyLock<Semaphore> = LazyLock::new(|| Semaphore::new(MAX_CONCURRENT_SYNCS));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncJobState {
+    /// Waiting for a previous run of the bucket or for a free slot.
+    Queued,
+    Running,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncJobInfo {
+    pub job_id: u64,
+    pub provider: String,
+    pub account_id: String,
+    pub bucket: String,
+    pub state: SyncJobState,
+    pub started_at: i64,
+    pub objects_fetched: usize,
+    pub bytes_fetched: i64,
+}
+
+struct SyncJob {
+    info: SyncJobInfo,
+    cancel: Arc<AtomicBool>,
+}
+
+/// A registered job, held by the task running it.
+#[derive(Clone)]
+pub(crate) struct SyncHandle {
+    pub(crate) id: u64,
+    scope: CacheScope,
+    cancel: Arc<AtomicBool>,
+    scope_lock: ScopeLock,
+}
+
+/// Permission to run: the bucket's lock and one of the global slots.
+pub(crate) struct SyncTurn {
+    _scope: OwnedMutexGuard<()>,
+    _slot: SemaphorePermit<'static>,
+}
+
+impl SyncHandle {
+    pub(crate) fn is_active(&self) -> bool {
+        !self.cancel.load(Ordering::SeqCst)
+    }
+
+    /// Wait until earlier runs of the bucket have finished and a slot is
+    /// free, then mark the job running.
+    pub(crate) async fn wait_turn(&self) -> SyncTurn {
+        let scope = self.scope_lock.clone().lock_owned().await;
+        let slot = SYNC_SLOTS
+            .acquire()
+            .await
+            .expect("sync slots are never closed");
+        self.update(|info| info.state = SyncJobState::Running);
+        SyncTurn {
+            _scope: scope,
+            _slot: slot,
+        }
+    }
+
+    pub(crate) fn record_progress(&self, objects_fetched: usize, bytes_fetched: i64) {
+        self.update(|info| {
+            info.objects_fetched = objects_fetched;
+            info.bytes_fetched = bytes_fetched;
+        });
+    }
+
+    /// Remove the job from the registry, unless it was already replaced.
+    pub(crate) fn finish(&self) {
+        let mut jobs = SYNC_JOBS.lock().unwrap();
+        if jobs
+            .get(&self.scope)
+            .is_some_and(|job| job.info.job_id == self.id)
+        {
+            jobs.remove(&self.scope);
+        }
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut SyncJobInfo)) {
+        let mut jobs = SYNC_JOBS.lock().unwrap();
+        if let Some(job) = jobs.get_mut(&self.scope) {
+            if job.info.job_id == self.id {
+                apply(&mut job.info);
+            }
+        }
+    }
+}
+
+/// Register a sync of `scope`, cancelling the bucket's current job if any.
+pub(crate) fn register_sync(scope: CacheScope, provider: &str) -> SyncHandle {
+    let id = NEXT_JOB_ID.fetch_add(1, Ordering::SeqCst) + 1;
+    let cancel = Arc::new(AtomicBool::new(false));
+
+    let scope_lock = SCOPE_LOCKS
+        .lock()
+        .unwrap()
+        .entry(scope.clone())
+        .or_default()
+        .clone();
+
+    let mut jobs = SYNC_JOBS.lock().unwrap();
+    if let Some(previous) = jobs.get(&scope) {
+        previous.cancel.store(true, Ordering::SeqCst);
+    }
+    jobs.insert(
+        scope.clone(),
+        SyncJob {
+            info: SyncJobInfo {
+                job_id: id,
+                provider: provider.to_string(),
+                account_id: scope.account_id().to_string(),
+                bucket: scope.bucket().to_string(),
+                state: SyncJobState::Queued,
+                started_at: chrono::Utc::now().timestamp(),
+                objects_fetched: 0,
+                bytes_fetched: 0,
+            },
+            cancel: cancel.clone(),
+        },
+    );
+
+    SyncHandle {
+        id,
+        scope,
+        cancel,
+        scope_lock,
+    }
+}
+
+/// Cancel job `job_id`, or every job when `None`. Cancelled jobs leave the
+/// registry right away; their tasks stop at the next check.
+pub(crate) fn cancel_syncs(job_id: Option<u64>) -> usize {
+    let mut jobs = SYNC_JOBS.lock().unwrap();
+    let mut cancelled = 0;
+    jobs.retain(|_, job| {
+        if job_id.is_some_and(|id| id != job.info.job_id) {
+            return true;
+        }
+        job.cancel.store(true, Ordering::SeqCst);
+        cancelled += 1;
+        false
+    });
+    cancelled
+}
+
+fn list_jobs() -> Vec<SyncJobInfo> {
+    let jobs = SYNC_JOBS.lock().unwrap();
+    let mut list: Vec<SyncJobInfo> = jobs.values().map(|job| job.info.clone()).collect();
+    list.sort_by_key(|info| info.job_id);
+    list
+}
+
+/// Queued and running background syncs, oldest first.
+#[tauri::command]
+pub async fn list_sync_jobs() -> Result<Vec<SyncJobInfo>, String> {
+    Ok(list_jobs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jobs_are_per_bucket_and_replacing_one_cancels_only_it() {
+        let first = register_sync(CacheScope::r2("jobs-acct", "jobs-a"), "r2");
+        let other = register_sync(CacheScope::r2("jobs-acct", "jobs-b"), "r2");
+        let replacement = register_sync(CacheScope::r2("jobs-acct", "jobs-a"), "r2");
+
+        assert!(!first.is_active());
+        assert!(other.is_active());
+        assert!(replacement.is_active());
+        // The replaced run still finishing doesn't drop its successor
+        first.finish();
+
+        let ids: Vec<u64> = list_jobs()
+            .into_iter()
+            .filter(|info| info.account_id == "jobs-acct")
+            .map(|info| info.job_id)
+            .collect();
+        assert_eq!(ids, vec![other.id, replacement.id]);
+
+        assert_eq!(cancel_syncs(Some(other.id)), 1);
+        assert!(!other.is_active());
+        assert!(replacement.is_active());
+        replacement.finish();
+    }
+}
@@ -1,32 +1,29 @@
 use crate::commands::cache_events::{get_unique_parent_paths, CacheUpdatedEvent};
-use crate::db;
+use crate::db::{self, CacheScope, CachedObjectMeta};
 use tauri::{AppHandle, Emitter};
 
+/// Update cache after an upload created or replaced `key`. `meta` holds what
+/// the upload knows about the new object (content type, storage class, ...).
 pub(crate) async fn update_cache_after_upload(
     app: &AppHandle,
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     key: &str,
     new_size: i64,
     last_modified: &str,
+    meta: &CachedObjectMeta,
 ) -> Result<(), String> {
     // Insert or update the file in cache, returns (size_delta, is_new_file)
-    let (size_delta, is_new_file) =
-        db::update_cached_file(bucket, account_id, key, new_size, last_modified)
-            .await
-            .map_err(|e| format!("Failed to update file cache: {}", e))?;
+    let (size_delta, is_new_file) = db::update_cached_file(scope, key, new_size, last_modified)
+        .await
+        .map_err(|e| format!("Failed to update file cache: {}", e))?;
+    db::set_cached_object_meta(scope, key, meta)
+        .await
+        .map_err(|e| format!("Failed to update file cache: {}", e))?;
 
     // Update directory tree - create nodes if needed (for new folders)
-    db::update_directory_tree_for_file(
-        bucket,
-        account_id,
-        key,
-        size_delta,
-        last_modified,
-        is_new_file,
-    )
-    .await
-    .map_err(|e| format!("Failed to update directory tree: {}", e))?;
+    db::update_directory_tree_for_file(scope, key, size_delta, last_modified, is_new_file)
+        .await
+        .map_err(|e| format!("Failed to update directory tree: {}", e))?;
 
     let _ = app.emit(
         "cache-updated",
@@ -38,3 +35,35 @@ pub(crate) async fn update_cache_after_upload(
 
     Ok(())
 }
+
+/// Update cache after server-side copies created `(key, size, last_modified)` objects.
+pub(crate) async fn update_cache_after_batch_copy(
+    app: &AppHandle,
+    scope: &CacheScope,
+    copied: &[(String, i64, String)],
+) -> Result<(), String> {
+    if copied.is_empty() {
+        return Ok(());
+    }
+
+    for (key, size, last_modified) in copied {
+        let (size_delta, is_new_file) = db::update_cached_file(scope, key, *size, last_modified)
+            .await
+            .map_err(|e| format!("Failed to update file cache: {}", e))?;
+
+        db::update_directory_tree_for_file(scope, key, size_delta, last_modified, is_new_file)
+            .await
+            .map_err(|e| format!("Failed to update directory tree: {}", e))?;
+    }
+
+    let keys: Vec<String> = copied.iter().map(|(key, _, _)| key.clone()).collect();
+    let _ = app.emit(
+        "cache-updated",
+        CacheUpdatedEvent {
+            action: "update".to_string(),
+            affected_paths: get_unique_parent_paths(&keys),
+        },
+    );
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+//! Key derivation and sealing shared by the secret store, client-side
+//! encryption and account bundles.
+//!
+//! Passphrases are stretched with Argon2id (default parameters) and data is
+//! sealed with ChaCha20-Poly1305 under a random nonce, written in front of
+//! the ciphertext. Each caller passes its own associated data, so a value
+//! sealed for one purpose can't be opened as another.
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+pub const KEY_SIZE: usize = 32;
+pub const SALT_SIZE: usize = 16;
+pub const NONCE_SIZE: usize = 12;
+
+pub fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut buf = [0u8; N];
+    getrandom::getrandom(&mut buf)
+        .map_err(|e| format!("Failed to generate random bytes: {}", e))?;
+    Ok(buf)
+}
+
+/// Key derived from `passphrase` with Argon2id.
+pub fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_SIZE], String> {
+    let mut key = [0u8; KEY_SIZE];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key from passphrase: {}", e))?;
+    Ok(key)
+}
+
+/// Encrypt `msg` under `key`: a random nonce followed by the ciphertext.
+pub fn seal(key: &[u8; KEY_SIZE], msg: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = random_bytes::<NONCE_SIZE>()?;
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg, aad })
+        .map_err(|_| "Failed to encrypt".to_string())?;
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypt what `seal` wrote. `None` when it is truncated, was sealed under
+/// another key or associated data, or was tampered with.
+pub fn open(key: &[u8; KEY_SIZE], sealed: &[u8], aad: &[u8]) -> Option<Vec<u8>> {
+    if sealed.len() <= NONCE_SIZE {
+        return None;
+    }
+    let (nonce, msg) = sealed.split_at(NONCE_SIZE);
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), Payload { msg, aad })
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_values_open_only_with_their_key_and_aad() {
+        let key = derive_key("correct horse", &[7; SALT_SIZE]).unwrap();
+        let sealed = seal(&key, b"s3cr3t", b"purpose-a").unwrap();
+        assert_eq!(
+            open(&key, &sealed, b"purpose-a").as_deref(),
+            Some(&b"s3cr3t"[..])
+        );
+        assert_eq!(open(&key, &sealed, b"purpose-b"), None);
+
+        let other = derive_key("wrong horse", &[7; SALT_SIZE]).unwrap();
+        assert_eq!(open(&other, &sealed, b"purpose-a"), None);
+        assert_eq!(open(&key, &sealed[..NONCE_SIZE], b"purpose-a"), None);
+    }
+}
@@ -9,6 +9,19 @@ pub struct Account {
     pub updated_at: i64,
 }
 
+/// Get SQL for creating the accounts table
+pub fn get_table_sql() -> &'static str {
+    "
+    -- Multi-account tables
+    CREATE TABLE IF NOT EXISTS accounts (
+        id TEXT PRIMARY KEY,
+        name TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+    "
+}
+
 /// Create a new account
 pub async fn create_account(id: &str, name: Option<&str>) -> DbResult<Account> {
     let conn = get_connection()?.lock().await;
@@ -27,7 +40,6 @@ pub async fn create_account(id: &str, name: Option<&str>) -> DbResult<Account> {
 }
 
 /// Get account by ID
-#[allow(dead_code)]
 pub async fn get_account(id: &str) -> DbResult<Option<Account>> {
     let conn = get_connection()?.lock().await;
     let mut rows = conn
@@ -1,4 +1,7 @@
+use super::capabilities::parse_capabilities;
 use super::{get_connection, DbResult};
+use crate::providers::aws::CredentialSource;
+use crate::providers::credential_probe::CapabilityReport;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,6 +14,11 @@ pub struct AwsAccount {
     pub endpoint_scheme: String,
     pub endpoint_host: Option<String>,
     pub force_path_style: bool,
+    /// Profile / environment accounts leave the key fields empty.
+    #[serde(default)]
+    pub credentials: CredentialSource,
+    #[serde(default)]
+    pub capabilities: Option<CapabilityReport>,
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -26,6 +34,9 @@ pub fn get_table_sql() -> &'static str {
         endpoint_scheme TEXT NOT NULL,
         endpoint_host TEXT,
         force_path_style INTEGER NOT NULL DEFAULT 0,
+        credential_source TEXT NOT NULL DEFAULT 'static',
+        profile_name TEXT,
+        capabilities TEXT,
         created_at INTEGER NOT NULL,
         updated_at INTEGER NOT NULL
     );
@@ -43,6 +54,7 @@ async fn generate_id(conn: &turso::Connection) -> DbResult<String> {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn create_aws_account(
     name: Option<&str>,
     access_key_id: &str,
@@ -51,15 +63,17 @@ pub async fn create_aws_account(
     endpoint_scheme: &str,
     endpoint_host: Option<&str>,
     force_path_style: bool,
+    credentials: &CredentialSource,
 ) -> DbResult<AwsAccount> {
     let conn = get_connection()?.lock().await;
     let now = chrono::Utc::now().timestamp();
     let id = generate_id(&conn).await?;
     let force_value = if force_path_style { 1 } else { 0 };
+    let (source_kind, profile_name) = credentials.to_columns();
 
     conn.execute(
-        "INSERT INTO aws_accounts (id, name, access_key_id, secret_access_key, region, endpoint_scheme, endpoint_host, force_path_style, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        "INSERT INTO aws_accounts (id, name, access_key_id, secret_access_key, region, endpoint_scheme, endpoint_host, force_path_style, credential_source, profile_name, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
         turso::params![
             id.as_str(),
             name,
@@ -69,6 +83,8 @@ pub async fn create_aws_account(
             endpoint_scheme,
             endpoint_host,
             force_value,
+            source_kind,
+            profile_name,
             now,
             now
         ],
@@ -83,6 +99,8 @@ pub async fn create_aws_account(
         endpoint_scheme: endpoint_scheme.to_string(),
         endpoint_host: endpoint_host.map(|s| s.to_string()),
         force_path_style,
+        credentials: credentials.clone(),
+        capabilities: None,
         created_at: now,
         updated_at: now,
     })
@@ -92,7 +110,7 @@ pub async fn list_aws_accounts() -> DbResult<Vec<AwsAccount>> {
     let conn = get_connection()?.lock().await;
     let mut rows = conn
         .query(
-            "SELECT id, name, access_key_id, secret_access_key, region, endpoint_scheme, endpoint_host, force_path_style, created_at, updated_at
+            "SELECT id, name, access_key_id, secret_access_key, region, endpoint_scheme, endpoint_host, force_path_style, created_at, updated_at, credential_source, profile_name, capabilities
              FROM aws_accounts ORDER BY created_at",
             (),
         )
@@ -101,6 +119,7 @@ pub async fn list_aws_accounts() -> DbResult<Vec<AwsAccount>> {
     let mut accounts = Vec::new();
     while let Some(row) = rows.next().await? {
         let force_value: i64 = row.get(7)?;
+        let source_kind: String = row.get(10)?;
         accounts.push(AwsAccount {
             id: row.get(0)?,
             name: row.get(1)?,
@@ -110,6 +129,8 @@ pub async fn list_aws_accounts() -> DbResult<Vec<AwsAccount>> {
             endpoint_scheme: row.get(5)?,
             endpoint_host: row.get(6)?,
             force_path_style: force_value != 0,
+            credentials: CredentialSource::from_columns(&source_kind, row.get(11)?),
+            capabilities: parse_capabilities(row.get(12)?),
             created_at: row.get(8)?,
             updated_at: row.get(9)?,
         });
@@ -127,16 +148,19 @@ pub async fn update_aws_account(
     endpoint_scheme: &str,
     endpoint_host: Option<&str>,
     force_path_style: bool,
+    credentials: &CredentialSource,
 ) -> DbResult<()> {
     let conn = get_connection()?.lock().await;
     let now = chrono::Utc::now().timestamp();
     let force_value = if force_path_style { 1 } else { 0 };
+    let (source_kind, profile_name) = credentials.to_columns();
 
     conn.execute(
         "UPDATE aws_accounts
          SET name = ?1, access_key_id = ?2, secret_access_key = ?3, region = ?4,
-             endpoint_scheme = ?5, endpoint_host = ?6, force_path_style = ?7, updated_at = ?8
-         WHERE id = ?9",
+             endpoint_scheme = ?5, endpoint_host = ?6, force_path_style = ?7, updated_at = ?8,
+             credential_source = ?9, profile_name = ?10
+         WHERE id = ?11",
         turso::params![
             name,
             access_key_id,
@@ -146,6 +170,8 @@ pub async fn update_aws_account(
             endpoint_host,
             force_value,
             now,
+            source_kind,
+            profile_name,
             id
         ],
     )
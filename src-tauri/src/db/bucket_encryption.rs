@@ -0,0 +1,132 @@
+use super::{get_connection, DbResult};
+use serde::{Deserialize, Serialize};
+
+/// Default server-side encryption for writes to a bucket.
+///
+/// Kept apart from the provider bucket tables because those are rewritten
+/// on every bucket-list save. The SSE-C key itself lives in the OS keychain;
+/// only its reference is stored here.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BucketEncryptionRow {
+    pub provider: String,
+    pub account_id: String,
+    pub bucket: String,
+    /// `none`, `sse-s3`, `sse-kms` or `sse-c`
+    pub mode: String,
+    pub kms_key_id: Option<String>,
+    pub bucket_key_enabled: bool,
+    /// Keychain reference of the SSE-C key.
+    pub customer_key_ref: Option<String>,
+    pub updated_at: i64,
+}
+
+pub fn get_table_sql() -> &'static str {
+    "
+    CREATE TABLE IF NOT EXISTS bucket_encryption (
+        provider TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        bucket TEXT NOT NULL,
+        mode TEXT NOT NULL DEFAULT 'none',
+        kms_key_id TEXT,
+        bucket_key_enabled INTEGER NOT NULL DEFAULT 0,
+        customer_key_ref TEXT,
+        updated_at INTEGER NOT NULL,
+        PRIMARY KEY (provider, account_id, bucket)
+    );
+    "
+}
+
+/// Get the encryption settings of a bucket, if any were saved
+pub async fn get_bucket_encryption(
+    provider: &str,
+    account_id: &str,
+    bucket: &str,
+) -> DbResult<Option<BucketEncryptionRow>> {
+    let conn = get_connection()?.lock().await;
+    let mut rows = conn
+        .query(
+            "SELECT provider, account_id, bucket, mode, kms_key_id, bucket_key_enabled, customer_key_ref, updated_at
+             FROM bucket_encryption WHERE provider = ?1 AND account_id = ?2 AND bucket = ?3",
+            turso::params![provider, account_id, bucket],
+        )
+        .await?;
+
+    match rows.next().await? {
+        Some(row) => {
+            let bucket_key_enabled: i64 = row.get(5)?;
+            Ok(Some(BucketEncryptionRow {
+                provider: row.get(0)?,
+                account_id: row.get(1)?,
+                bucket: row.get(2)?,
+                mode: row.get(3)?,
+                kms_key_id: row.get(4)?,
+                bucket_key_enabled: bucket_key_enabled != 0,
+                customer_key_ref: row.get(6)?,
+                updated_at: row.get(7)?,
+            }))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Insert or replace the encryption settings of a bucket
+pub async fn save_bucket_encryption(
+    provider: &str,
+    account_id: &str,
+    bucket: &str,
+    mode: &str,
+    kms_key_id: Option<&str>,
+    bucket_key_enabled: bool,
+    customer_key_ref: Option<&str>,
+) -> DbResult<BucketEncryptionRow> {
+    let conn = get_connection()?.lock().await;
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO bucket_encryption
+         (provider, account_id, bucket, mode, kms_key_id, bucket_key_enabled, customer_key_ref, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+         ON CONFLICT (provider, account_id, bucket) DO UPDATE SET
+            mode = excluded.mode,
+            kms_key_id = excluded.kms_key_id,
+            bucket_key_enabled = excluded.bucket_key_enabled,
+            customer_key_ref = excluded.customer_key_ref,
+            updated_at = excluded.updated_at",
+        turso::params![
+            provider,
+            account_id,
+            bucket,
+            mode,
+            kms_key_id.map(str::to_string),
+            bucket_key_enabled as i64,
+            customer_key_ref.map(str::to_string),
+            now
+        ],
+    )
+    .await?;
+
+    Ok(BucketEncryptionRow {
+        provider: provider.to_string(),
+        account_id: account_id.to_string(),
+        bucket: bucket.to_string(),
+        mode: mode.to_string(),
+        kms_key_id: kms_key_id.map(str::to_string),
+        bucket_key_enabled,
+        customer_key_ref: customer_key_ref.map(str::to_string),
+        updated_at: now,
+    })
+}
+
+/// Forget the encryption settings of a bucket
+pub async fn delete_bucket_encryption(
+    provider: &str,
+    account_id: &str,
+    bucket: &str,
+) -> DbResult<()> {
+    let conn = get_connection()?.lock().await;
+    conn.execute(
+        "DELETE FROM bucket_encryption WHERE provider = ?1 AND account_id = ?2 AND bucket = ?3",
+        turso::params![provider, account_id, bucket],
+    )
+    .await?;
+    Ok(())
+}
@@ -14,6 +14,27 @@ pub struct Bucket {
     pub updated_at: i64,
 }
 
+/// Get SQL for creating the R2 buckets table
+pub fn get_table_sql() -> &'static str {
+    "
+    CREATE TABLE IF NOT EXISTS buckets (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        token_id INTEGER NOT NULL REFERENCES tokens(id),
+        name TEXT NOT NULL,
+        public_domain TEXT,
+        public_domain_scheme TEXT,
+        is_public INTEGER NOT NULL DEFAULT 0,
+        public_path_prefix TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL,
+        UNIQUE(token_id, name)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_buckets_token ON buckets(token_id);
+    CREATE INDEX IF NOT EXISTS idx_buckets_unique ON buckets(token_id, name);
+    "
+}
+
 /// Create a new bucket
 #[allow(dead_code)]
 pub async fn create_bucket(
@@ -174,3 +195,22 @@ pub async fn save_buckets_for_token(
     }
     Ok(result)
 }
+
+/// Sync the public-access fields of a saved bucket with the state reported by
+/// Cloudflare. Buckets that were never saved locally are left alone.
+pub async fn set_bucket_public_state(
+    token_id: i64,
+    name: &str,
+    is_public: bool,
+    public_domain: Option<&str>,
+) -> DbResult<()> {
+    let conn = get_connection()?.lock().await;
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "UPDATE buckets SET is_public = ?1, public_domain = ?2, public_domain_scheme = 'https', updated_at = ?3
+         WHERE token_id = ?4 AND name = ?5",
+        turso::params![is_public as i64, public_domain, now, token_id, name],
+    )
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,134 @@
+//! Which storage endpoint a cached listing belongs to.
+//!
+//! Account ids alone don't identify a bucket: an R2 account id can equal a
+//! MinIO one, and a MinIO account whose endpoint is edited points at another
+//! server. The cache tables (`cached_files`, `directory_tree`, `sync_meta`,
+//! `prefix_sync_times`) therefore store a scope key of
+//! `provider|endpoint|account_id` in their `account_id` column. The transfer
+//! tables keep the raw account id for display and store the key alongside it
+//! (`scope_key` on downloads and restores, `source_scope_key` on moves).
+
+use super::tokens::CurrentConfig;
+
+/// Scheme of an endpoint given without one, for clients and scopes alike.
+/// MinIO and RustFS are mostly self-hosted behind plain HTTP.
+pub fn default_endpoint_scheme(provider: &str) -> &'static str {
+    match provider {
+        "minio" | "rustfs" => "http",
+        _ => "https",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheScope {
+    bucket: String,
+    account_id: String,
+    key: String,
+}
+
+impl CacheScope {
+    /// `endpoint_scheme` defaults to `default_endpoint_scheme`; without an
+    /// endpoint host the provider's own endpoint is assumed. R2 endpoints
+    /// derive from the account id, so any endpoint given for R2 is ignored.
+    pub fn new(
+        provider: &str,
+        endpoint_scheme: Option<&str>,
+        endpoint_host: Option<&str>,
+        account_id: &str,
+        bucket: &str,
+    ) -> Self {
+        Self {
+            bucket: bucket.to_string(),
+            account_id: account_id.to_string(),
+            key: scope_key(provider, endpoint_scheme, endpoint_host, account_id),
+        }
+    }
+
+    pub fn r2(account_id: &str, bucket: &str) -> Self {
+        Self::new("r2", None, None, account_id, bucket)
+    }
+
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    pub fn account_id(&self) -> &str {
+        &self.account_id
+    }
+
+    /// Values for the `(bucket, account_id)` columns of the cache tables.
+    pub fn columns(&self) -> (&str, &str) {
+        (&self.bucket, &self.key)
+    }
+}
+
+impl From<&CurrentConfig> for CacheScope {
+    fn from(config: &CurrentConfig) -> Self {
+        Self::new(
+            config.provider.as_str(),
+            config.endpoint_scheme.as_deref(),
+            config.endpoint_host.as_deref(),
+            &config.account_id,
+            &config.bucket,
+        )
+    }
+}
+
+/// Scope key stored in the cache tables' `account_id` column.
+pub(super) fn scope_key(
+    provider: &str,
+    endpoint_scheme: Option<&str>,
+    endpoint_host: Option<&str>,
+    account_id: &str,
+) -> String {
+    let provider = provider.trim().to_ascii_lowercase();
+    let host = endpoint_host
+        .map(|host| host.trim().trim_end_matches('/').to_ascii_lowercase())
+        .filter(|host| !host.is_empty() && provider != "r2");
+    let endpoint = match host {
+        Some(host) => {
+            let scheme = endpoint_scheme
+                .map(|scheme| scheme.trim().to_ascii_lowercase())
+                .filter(|scheme| !scheme.is_empty())
+                .unwrap_or_else(|| default_endpoint_scheme(&provider).to_string());
+            format!("{}://{}", scheme, host)
+        }
+        None => String::new(),
+    };
+    format!("{}|{}|{}", provider, endpoint, account_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn providers_with_the_same_account_id_get_separate_scopes() {
+        let r2 = CacheScope::r2("shared", "photos");
+        let minio = CacheScope::new("minio", Some("http"), Some("nas:9000"), "shared", "photos");
+        assert_ne!(r2, minio);
+        assert_eq!(r2.columns(), ("photos", "r2||shared"));
+        assert_eq!(minio.columns(), ("photos", "minio|http://nas:9000|shared"));
+    }
+
+    #[test]
+    fn endpoints_are_normalized() {
+        let a = CacheScope::new("minio", Some("HTTP"), Some("NAS:9000/"), "acct", "b");
+        let b = CacheScope::new("minio", Some("http"), Some("nas:9000"), "acct", "b");
+        let other = CacheScope::new("minio", Some("http"), Some("backup:9000"), "acct", "b");
+        assert_eq!(a, b);
+        assert_ne!(a, other);
+        assert_eq!(
+            CacheScope::new("minio", None, Some("nas:9000"), "acct", "b"),
+            b
+        );
+        assert_eq!(
+            scope_key("aws", None, Some("s3.example.com"), "acct"),
+            "aws|https://s3.example.com|acct"
+        );
+        assert_eq!(
+            scope_key("aws", Some("https"), Some(""), "acct"),
+            "aws||acct"
+        );
+    }
+}
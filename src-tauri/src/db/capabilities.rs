@@ -0,0 +1,47 @@
+use super::{get_connection, DbResult};
+use crate::providers::credential_probe::CapabilityReport;
+
+/// Decode a `capabilities` column. Reports that no longer parse are treated
+/// as never checked.
+pub(crate) fn parse_capabilities(value: Option<String>) -> Option<CapabilityReport> {
+    value.and_then(|json| serde_json::from_str(&json).ok())
+}
+
+fn encode(report: Option<&CapabilityReport>) -> DbResult<Option<String>> {
+    Ok(report.map(serde_json::to_string).transpose()?)
+}
+
+/// Save (or clear) the capability report of an R2 token.
+pub async fn save_token_capabilities(
+    token_id: i64,
+    report: Option<&CapabilityReport>,
+) -> DbResult<()> {
+    let conn = get_connection()?.lock().await;
+    conn.execute(
+        "UPDATE tokens SET capabilities = ?1 WHERE id = ?2",
+        turso::params![encode(report)?, token_id],
+    )
+    .await?;
+    Ok(())
+}
+
+/// Save (or clear) the capability report of an AWS / MinIO / RustFS account.
+pub async fn save_account_capabilities(
+    provider: &str,
+    account_id: &str,
+    report: Option<&CapabilityReport>,
+) -> DbResult<()> {
+    let table = match provider {
+        "aws" => "aws_accounts",
+        "minio" => "minio_accounts",
+        "rustfs" => "rustfs_accounts",
+        _ => return Err(format!("Unsupported provider: {}", provider).into()),
+    };
+    let conn = get_connection()?.lock().await;
+    conn.execute(
+        &format!("UPDATE {table} SET capabilities = ?1 WHERE id = ?2"),
+        turso::params![encode(report)?, account_id],
+    )
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use super::{get_connection, DbResult};
+use serde::{Deserialize, Serialize};
+
+/// Client-side encryption settings of a bucket. The passphrase itself lives in
+/// the OS keychain; a key file is referenced by path and never copied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientEncryptionRow {
+    pub provider: String,
+    pub account_id: String,
+    pub bucket: String,
+    /// `passphrase` or `key-file`
+    pub key_source: String,
+    /// Keychain reference of the passphrase.
+    pub passphrase_ref: Option<String>,
+    pub key_file_path: Option<String>,
+    pub updated_at: i64,
+}
+
+pub fn get_table_sql() -> &'static str {
+    "
+    CREATE TABLE IF NOT EXISTS client_encryption (
+        provider TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        bucket TEXT NOT NULL,
+        key_source TEXT NOT NULL,
+        passphrase_ref TEXT,
+        key_file_path TEXT,
+        updated_at INTEGER NOT NULL,
+        PRIMARY KEY (provider, account_id, bucket)
+    );
+    "
+}
+
+/// Get the client-side encryption settings of a bucket, if enabled
+pub async fn get_client_encryption(
+    provider: &str,
+    account_id: &str,
+    bucket: &str,
+) -> DbResult<Option<ClientEncryptionRow>> {
+    let conn = get_connection()?.lock().await;
+    let mut rows = conn
+        .query(
+            "SELECT provider, account_id, bucket, key_source, passphrase_ref, key_file_path, updated_at
+             FROM client_encryption WHERE provider = ?1 AND account_id = ?2 AND bucket = ?3",
+            turso::params![provider, account_id, bucket],
+        )
+        .await?;
+
+    match rows.next().await? {
+        Some(row) => Ok(Some(ClientEncryptionRow {
+            provider: row.get(0)?,
+            account_id: row.get(1)?,
+            bucket: row.get(2)?,
+            key_source: row.get(3)?,
+            passphrase_ref: row.get(4)?,
+            key_file_path: row.get(5)?,
+            updated_at: row.get(6)?,
+        })),
+        None => Ok(None),
+    }
+}
+
+/// Insert or replace the client-side encryption settings of a bucket
+pub async fn save_client_encryption(
+    provider: &str,
+    account_id: &str,
+    bucket: &str,
+    key_source: &str,
+    passphrase_ref: Option<&str>,
+    key_file_path: Option<&str>,
+) -> DbResult<ClientEncryptionRow> {
+    let conn = get_connection()?.lock().await;
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO client_encryption
+         (provider, account_id, bucket, key_source, passphrase_ref, key_file_path, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT (provider, account_id, bucket) DO UPDATE SET
+            key_source = excluded.key_source,
+            passphrase_ref = excluded.passphrase_ref,
+            key_file_path = excluded.key_file_path,
+            updated_at = excluded.updated_at",
+        turso::params![
+            provider,
+            account_id,
+            bucket,
+            key_source,
+            passphrase_ref.map(str::to_string),
+            key_file_path.map(str::to_string),
+            now
+        ],
+    )
+    .await?;
+
+    Ok(ClientEncryptionRow {
+        provider: provider.to_string(),
+        account_id: account_id.to_string(),
+        bucket: bucket.to_string(),
+        key_source: key_source.to_string(),
+        passphrase_ref: passphrase_ref.map(str::to_string),
+        key_file_path: key_file_path.map(str::to_string),
+        updated_at: now,
+    })
+}
+
+/// Turn off client-side encryption for a bucket
+pub async fn delete_client_encryption(
+    provider: &str,
+    account_id: &str,
+    bucket: &str,
+) -> DbResult<()> {
+    let conn = get_connection()?.lock().await;
+    conn.execute(
+        "DELETE FROM client_encryption WHERE provider = ?1 AND account_id = ?2 AND bucket = ?3",
+        turso::params![provider, account_id, bucket],
+    )
+    .await?;
+    Ok(())
+}
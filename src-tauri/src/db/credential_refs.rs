@@ -0,0 +1,125 @@
+use super::{get_connection, DbResult};
+use crate::secrets::HANDLE_PREFIX;
+
+/// A column holding an account credential.
+struct CredentialColumn {
+    table: &'static str,
+    column: &'static str,
+    /// Column with the account the credential belongs to.
+    account_column: &'static str,
+    provider: &'static str,
+}
+
+const CREDENTIAL_COLUMNS: &[CredentialColumn] = &[
+    CredentialColumn {
+        table: "tokens",
+        column: "api_token",
+        account_column: "account_id",
+        provider: "r2",
+    },
+    CredentialColumn {
+        table: "tokens",
+        column: "secret_access_key",
+        account_column: "account_id",
+        provider: "r2",
+    },
+    CredentialColumn {
+        table: "aws_accounts",
+        column: "secret_access_key",
+        account_column: "id",
+        provider: "aws",
+    },
+    CredentialColumn {
+        table: "minio_accounts",
+        column: "secret_access_key",
+        account_column: "id",
+        provider: "minio",
+    },
+    CredentialColumn {
+        table: "rustfs_accounts",
+        column: "secret_access_key",
+        account_column: "id",
+        provider: "rustfs",
+    },
+];
+
+/// A credential column that still holds the secret itself rather than a
+/// secret store handle.
+#[derive(Debug, Clone)]
+pub struct PlaintextCredential {
+    table: &'static str,
+    column: &'static str,
+    row_id: String,
+    pub provider: &'static str,
+    pub value: String,
+}
+
+pub async fn list_plaintext_credentials() -> DbResult<Vec<PlaintextCredential>> {
+    let conn = get_connection()?.lock().await;
+    let pattern = format!("{}%", HANDLE_PREFIX);
+    let mut credentials = Vec::new();
+    for column in CREDENTIAL_COLUMNS {
+        let sql = format!(
+            "SELECT CAST(id AS TEXT), {column} FROM {table}
+             WHERE {column} != '' AND {column} NOT LIKE ?1",
+            column = column.column,
+            table = column.table,
+        );
+        let mut rows = conn.query(&sql, turso::params![pattern.as_str()]).await?;
+        while let Some(row) = rows.next().await? {
+            credentials.push(PlaintextCredential {
+                table: column.table,
+                column: column.column,
+                row_id: row.get(0)?,
+                provider: column.provider,
+                value: row.get(1)?,
+            });
+        }
+    }
+    Ok(credentials)
+}
+
+/// Swap a plaintext credential for its handle, unless the row changed since
+/// it was read.
+pub async fn replace_plaintext_credential(
+    credential: &PlaintextCredential,
+    handle: &str,
+) -> DbResult<()> {
+    let conn = get_connection()?.lock().await;
+    let sql = format!(
+        "UPDATE {table} SET {column} = ?1 WHERE CAST(id AS TEXT) = ?2 AND {column} = ?3",
+        table = credential.table,
+        column = credential.column,
+    );
+    conn.execute(
+        &sql,
+        turso::params![
+            handle,
+            credential.row_id.as_str(),
+            credential.value.as_str()
+        ],
+    )
+    .await?;
+    Ok(())
+}
+
+/// Credential values stored for an account: the secret key of an AWS /
+/// MinIO / RustFS account, or both credentials of every token of an R2
+/// account.
+pub async fn list_account_credentials(provider: &str, account_id: &str) -> DbResult<Vec<String>> {
+    let conn = get_connection()?.lock().await;
+    let mut values = Vec::new();
+    for column in CREDENTIAL_COLUMNS.iter().filter(|c| c.provider == provider) {
+        let sql = format!(
+            "SELECT {column} FROM {table} WHERE {account} = ?1",
+            column = column.column,
+            table = column.table,
+            account = column.account_column,
+        );
+        let mut rows = conn.query(&sql, turso::params![account_id]).await?;
+        while let Some(row) = rows.next().await? {
+            values.push(row.get(0)?);
+        }
+    }
+    Ok(values)
+}
@@ -5,7 +5,7 @@
 //! - Batch database inserts for performance
 //! - Progress reporting during build
 
-use super::{get_connection, CachedFile, DbResult};
+use super::{get_connection, CacheScope, CachedFile, DbResult};
 use std::collections::{BTreeSet, HashMap, HashSet};
 
 /// Batch size for database inserts
@@ -77,7 +77,7 @@ fn compute_parent_path(path: &str) -> String {
 /// ```ignore
 /// let builder = DirectoryTreeBuilder::new();
 /// let nodes = builder.build(files, Some(progress_cb)).await;
-/// DirectoryTreeBuilder::store(bucket, account_id, &nodes).await?;
+/// DirectoryTreeBuilder::store(scope, &nodes).await?;
 /// ```
 #[allow(dead_code)]
 pub struct DirectoryTreeBuilder {
@@ -317,7 +317,8 @@ impl DirectoryTreeBuilder {
 
     /// Store computed nodes to database with batch inserts.
     /// Clears existing tree first.
-    pub async fn store(bucket: &str, account_id: &str, nodes: &[ComputedNode]) -> DbResult<()> {
+    pub async fn store(scope: &CacheScope, nodes: &[ComputedNode]) -> DbResult<()> {
+        let (bucket, account_id) = scope.columns();
         let now = chrono::Utc::now().timestamp();
         let conn = get_connection()?.lock().await;
         conn.execute("BEGIN TRANSACTION", ()).await?;
@@ -415,14 +416,14 @@ impl Default for DirectoryTreeBuilder {
 /// - Only unique directory paths are loaded (~10K vs 1M files)
 /// - No per-file string allocations for path parsing
 pub async fn build_directory_tree_from_db<F>(
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     folder_keys: &[String],
     progress_callback: Option<F>,
 ) -> DbResult<()>
 where
     F: FnMut(usize, usize) + Send + 'static,
 {
+    let (bucket, account_id) = scope.columns();
     let mut progress: Option<ProgressCallback> =
         progress_callback.map(|f| Box::new(f) as ProgressCallback);
 
@@ -563,14 +564,13 @@ where
     }
 
     // Step 5: Store to DB
-    DirectoryTreeBuilder::store(bucket, account_id, &nodes).await
+    DirectoryTreeBuilder::store(scope, &nodes).await
 }
 
 /// Convenience function matching old API signature (kept for backward compatibility)
 #[allow(dead_code)]
 pub async fn build_directory_tree<F>(
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     files: &[CachedFile],
     folder_keys: &[String],
     progress_callback: Option<F>,
@@ -583,7 +583,7 @@ where
 
     let builder = DirectoryTreeBuilder::new();
     let nodes = builder.build(files, folder_keys, progress).await;
-    DirectoryTreeBuilder::store(bucket, account_id, &nodes).await
+    DirectoryTreeBuilder::store(scope, &nodes).await
 }
 
 /// Helper to get all ancestor paths for a file key (including root "")
@@ -624,13 +624,13 @@ fn get_ancestor_paths(key: &str) -> (String, Vec<String>) {
 /// - `size_delta`: negative for delete/move-from, positive for add/move-to/size-increase
 /// - `last_modified`: Some() to update timestamp (add/update), None to skip (delete)
 async fn apply_directory_delta(
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     key: &str,
     file_count_delta: i32,
     size_delta: i64,
     last_modified: Option<&str>,
 ) -> DbResult<()> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
     let now = chrono::Utc::now().timestamp();
 
@@ -714,25 +714,24 @@ async fn apply_directory_delta(
 /// Decreases file_count and sizes for the file's parent and all ancestors.
 /// Returns a list of paths that were removed because they became empty.
 pub async fn update_directory_tree_for_delete(
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     key: &str,
     file_size: i64,
 ) -> DbResult<Vec<String>> {
     // Apply the delta first
-    apply_directory_delta(bucket, account_id, key, -1, -file_size, None).await?;
+    apply_directory_delta(scope, key, -1, -file_size, None).await?;
 
     // Check for and remove empty paths
-    remove_empty_paths(bucket, account_id, key).await
+    remove_empty_paths(scope, key).await
 }
 
 /// Update directory tree for a batch of file deletions.
 /// Applies deltas for all files, then removes empty paths once for all affected ancestors.
 pub async fn update_directory_tree_for_delete_batch(
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     deleted: &[(String, i64)],
 ) -> DbResult<Vec<String>> {
+    let (bucket, account_id) = scope.columns();
     if deleted.is_empty() {
         return Ok(Vec::new());
     }
@@ -740,7 +739,7 @@ pub async fn update_directory_tree_for_delete_batch(
     let mut affected_paths: HashSet<String> = HashSet::new();
 
     for (key, file_size) in deleted {
-        apply_directory_delta(bucket, account_id, key, -1, -file_size, None).await?;
+        apply_directory_delta(scope, key, -1, -file_size, None).await?;
 
         let (_, paths) = get_ancestor_paths(key);
         for path in paths.into_iter().filter(|p| !p.is_empty()) {
@@ -809,7 +808,8 @@ pub async fn update_directory_tree_for_delete_batch(
 
 /// Detect which ancestor paths for a key do not yet exist in directory_tree.
 /// Returns the list of paths that would be newly created.
-async fn detect_new_paths(bucket: &str, account_id: &str, key: &str) -> DbResult<Vec<String>> {
+async fn detect_new_paths(scope: &CacheScope, key: &str) -> DbResult<Vec<String>> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
     let (_, paths_to_check) = get_ancestor_paths(key);
 
@@ -834,7 +834,8 @@ async fn detect_new_paths(bucket: &str, account_id: &str, key: &str) -> DbResult
 
 /// Check all ancestor paths and remove any that have become empty (total_file_count == 0).
 /// Returns the list of removed paths.
-async fn remove_empty_paths(bucket: &str, account_id: &str, key: &str) -> DbResult<Vec<String>> {
+async fn remove_empty_paths(scope: &CacheScope, key: &str) -> DbResult<Vec<String>> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
     let (_, paths_to_check) = get_ancestor_paths(key);
 
@@ -873,8 +874,7 @@ async fn remove_empty_paths(bucket: &str, account_id: &str, key: &str) -> DbResu
 /// Update directory tree for a file move/rename operation.
 /// Decreases counts/sizes in old location, increases in new location.
 pub async fn update_directory_tree_for_move(
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     old_key: &str,
     new_key: &str,
     file_size: i64,
@@ -893,24 +893,16 @@ pub async fn update_directory_tree_for_move(
     }
 
     // Detect which paths will be newly created for the new location
-    let created_paths = detect_new_paths(bucket, account_id, new_key).await?;
+    let created_paths = detect_new_paths(scope, new_key).await?;
 
     // Decrease in old location
-    apply_directory_delta(bucket, account_id, old_key, -1, -file_size, None).await?;
+    apply_directory_delta(scope, old_key, -1, -file_size, None).await?;
 
     // Remove empty paths left behind
-    let removed_paths = remove_empty_paths(bucket, account_id, old_key).await?;
+    let removed_paths = remove_empty_paths(scope, old_key).await?;
 
     // Increase in new location
-    apply_directory_delta(
-        bucket,
-        account_id,
-        new_key,
-        1,
-        file_size,
-        Some(last_modified),
-    )
-    .await?;
+    apply_directory_delta(scope, new_key, 1, file_size, Some(last_modified)).await?;
 
     Ok(MoveTreeResult {
         removed_paths,
@@ -920,7 +912,8 @@ pub async fn update_directory_tree_for_move(
 
 /// Ensure a directory node exists in the tree (creates with zero counts if missing).
 /// Used by lazy sync when discovering folders via common prefixes.
-pub async fn ensure_directory_node(bucket: &str, account_id: &str, path: &str) -> DbResult<()> {
+pub async fn ensure_directory_node(scope: &CacheScope, path: &str) -> DbResult<()> {
+    let (bucket, account_id) = scope.columns();
     let now = chrono::Utc::now().timestamp();
     let parent = compute_parent_path(path);
     let conn = get_connection()?.lock().await;
@@ -950,6 +943,22 @@ pub async fn ensure_directory_node(bucket: &str, account_id: &str, path: &str) -
     Ok(())
 }
 
+/// Drop a directory node and every node below it, whatever their counts.
+/// Used when a listing shows the folder no longer exists.
+pub async fn remove_directory_subtree(scope: &CacheScope, path: &str) -> DbResult<()> {
+    let (bucket, account_id) = scope.columns();
+    let conn = get_connection()?.lock().await;
+
+    conn.execute(
+        "DELETE FROM directory_tree
+         WHERE bucket = ?1 AND account_id = ?2 AND substr(path, 1, length(?3)) = ?3",
+        turso::params![bucket, account_id, path],
+    )
+    .await?;
+
+    Ok(())
+}
+
 /// Update directory tree when a file is uploaded or updated.
 /// For new files: increments file counts AND updates sizes.
 /// For existing files: only updates sizes (delta).
@@ -957,8 +966,7 @@ pub async fn ensure_directory_node(bucket: &str, account_id: &str, path: &str) -
 /// - `is_new_file`: true if the file was just created, false if overwriting existing
 /// - `size_delta`: size change (new_size - old_size, or new_size if new file)
 pub async fn update_directory_tree_for_file(
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     key: &str,
     size_delta: i64,
     last_modified: &str,
@@ -966,8 +974,7 @@ pub async fn update_directory_tree_for_file(
 ) -> DbResult<()> {
     let file_count_delta = if is_new_file { 1 } else { 0 };
     apply_directory_delta(
-        bucket,
-        account_id,
+        scope,
         key,
         file_count_delta,
         size_delta,
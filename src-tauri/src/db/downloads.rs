@@ -1,4 +1,4 @@
-use super::{get_connection, DbResult};
+use super::{get_connection, CacheScope, DbResult};
 use serde::{Deserialize, Serialize};
 
 /// Download session status (for future type-safe status handling)
@@ -17,6 +17,9 @@ pub enum DownloadStatus {
     Failed,
     #[serde(rename = "cancelled")]
     Cancelled,
+    /// Archived object; queued again once its restore completes
+    #[serde(rename = "waiting-restore")]
+    WaitingRestore,
 }
 
 impl std::fmt::Display for DownloadStatus {
@@ -78,22 +81,27 @@ pub fn get_table_sql() -> &'static str {
         status TEXT NOT NULL DEFAULT 'pending',
         error TEXT,
         created_at INTEGER NOT NULL,
-        updated_at INTEGER NOT NULL
+        updated_at INTEGER NOT NULL,
+        scope_key TEXT NOT NULL DEFAULT ''
     );
 
     CREATE INDEX IF NOT EXISTS idx_download_sessions_status ON download_sessions(status);
-    CREATE INDEX IF NOT EXISTS idx_download_sessions_bucket ON download_sessions(bucket, account_id);
+    CREATE INDEX IF NOT EXISTS idx_download_sessions_bucket ON download_sessions(bucket, scope_key);
     "
 }
 
-/// Create a new download session
-pub async fn create_download_session(session: &DownloadSession) -> DbResult<()> {
+/// Create a new download session from the bucket of `scope`
+pub async fn create_download_session(
+    scope: &CacheScope,
+    session: &DownloadSession,
+) -> DbResult<()> {
+    let (bucket, scope_key) = scope.columns();
     let conn = get_connection()?.lock().await;
     conn.execute(
         "INSERT INTO download_sessions 
          (id, object_key, file_name, file_size, downloaded_bytes, local_path, 
-          bucket, account_id, status, error, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+          bucket, account_id, status, error, created_at, updated_at, scope_key)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
         turso::params![
             session.id.clone(),
             session.object_key.clone(),
@@ -101,12 +109,13 @@ pub async fn create_download_session(session: &DownloadSession) -> DbResult<()>
             session.file_size,
             session.downloaded_bytes,
             session.local_path.clone(),
-            session.bucket.clone(),
-            session.account_id.clone(),
+            bucket,
+            scope.account_id(),
             session.status.clone(),
             session.error.clone(),
             session.created_at,
             session.updated_at,
+            scope_key,
         ],
     )
     .await?;
@@ -188,18 +197,18 @@ pub async fn get_download_session(session_id: &str) -> DbResult<Option<DownloadS
 
 /// Get all download sessions for a specific bucket
 pub async fn get_download_sessions_for_bucket(
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
 ) -> DbResult<Vec<DownloadSession>> {
+    let (bucket, scope_key) = scope.columns();
     let conn = get_connection()?.lock().await;
     let mut rows = conn
         .query(
             "SELECT id, object_key, file_name, file_size, downloaded_bytes, local_path,
                 bucket, account_id, status, error, created_at, updated_at
          FROM download_sessions 
-         WHERE bucket = ?1 AND account_id = ?2
+         WHERE bucket = ?1 AND scope_key = ?2
          ORDER BY updated_at DESC",
-            turso::params![bucket, account_id],
+            turso::params![bucket, scope_key],
         )
         .await?;
 
@@ -236,20 +245,20 @@ pub async fn delete_download_session(session_id: &str) -> DbResult<()> {
 
 /// Get pending download sessions for a bucket (ordered by created_at)
 pub async fn get_pending_downloads(
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     limit: i64,
 ) -> DbResult<Vec<DownloadSession>> {
+    let (bucket, scope_key) = scope.columns();
     let conn = get_connection()?.lock().await;
     let mut rows = conn
         .query(
             "SELECT id, object_key, file_name, file_size, downloaded_bytes, local_path,
                 bucket, account_id, status, error, created_at, updated_at
          FROM download_sessions 
-         WHERE bucket = ?1 AND account_id = ?2 AND status = 'pending'
+         WHERE bucket = ?1 AND scope_key = ?2 AND status = 'pending'
          ORDER BY created_at ASC
          LIMIT ?3",
-            turso::params![bucket, account_id, limit],
+            turso::params![bucket, scope_key, limit],
         )
         .await?;
 
@@ -274,13 +283,14 @@ pub async fn get_pending_downloads(
 }
 
 /// Count active (downloading) sessions for a bucket
-pub async fn count_active_downloads(bucket: &str, account_id: &str) -> DbResult<i64> {
+pub async fn count_active_downloads(scope: &CacheScope) -> DbResult<i64> {
+    let (bucket, scope_key) = scope.columns();
     let conn = get_connection()?.lock().await;
     let mut rows = conn
         .query(
             "SELECT COUNT(*) FROM download_sessions 
-         WHERE bucket = ?1 AND account_id = ?2 AND status = 'downloading'",
-            turso::params![bucket, account_id],
+         WHERE bucket = ?1 AND scope_key = ?2 AND status = 'downloading'",
+            turso::params![bucket, scope_key],
         )
         .await?;
 
@@ -291,14 +301,49 @@ pub async fn count_active_downloads(bucket: &str, account_id: &str) -> DbResult<
     }
 }
 
+/// Move sessions waiting on an object's restore back to pending.
+/// Returns the ids of the released sessions.
+pub async fn release_restored_downloads(
+    scope: &CacheScope,
+    object_key: &str,
+) -> DbResult<Vec<String>> {
+    let (bucket, scope_key) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    let mut rows = conn
+        .query(
+            "SELECT id FROM download_sessions
+         WHERE bucket = ?1 AND scope_key = ?2 AND object_key = ?3 AND status = 'waiting-restore'",
+            turso::params![bucket, scope_key, object_key],
+        )
+        .await?;
+
+    let mut ids = Vec::new();
+    while let Some(row) = rows.next().await? {
+        ids.push(row.get::<String>(0)?);
+    }
+    drop(rows);
+
+    if !ids.is_empty() {
+        let now = chrono::Utc::now().timestamp();
+        conn.execute(
+            "UPDATE download_sessions SET status = 'pending', error = NULL, updated_at = ?1
+             WHERE bucket = ?2 AND scope_key = ?3 AND object_key = ?4 AND status = 'waiting-restore'",
+            turso::params![now, bucket, scope_key, object_key],
+        )
+        .await?;
+    }
+    Ok(ids)
+}
+
 /// Set all downloading tasks to paused (for pause all)
-pub async fn pause_all_downloads(bucket: &str, account_id: &str) -> DbResult<i64> {
+pub async fn pause_all_downloads(scope: &CacheScope) -> DbResult<i64> {
+    let (bucket, scope_key) = scope.columns();
     let conn = get_connection()?.lock().await;
     let now = chrono::Utc::now().timestamp();
     conn.execute(
         "UPDATE download_sessions SET status = 'paused', updated_at = ?1
-         WHERE bucket = ?2 AND account_id = ?3 AND status IN ('downloading', 'pending')",
-        turso::params![now, bucket, account_id],
+         WHERE bucket = ?2 AND scope_key = ?3 AND status IN ('downloading', 'pending')",
+        turso::params![now, bucket, scope_key],
     )
     .await?;
 
@@ -331,13 +376,14 @@ pub async fn pause_stale_downloads_on_startup() -> DbResult<i64> {
 }
 
 /// Set all paused tasks to pending (for start all)
-pub async fn resume_all_downloads(bucket: &str, account_id: &str) -> DbResult<i64> {
+pub async fn resume_all_downloads(scope: &CacheScope) -> DbResult<i64> {
+    let (bucket, scope_key) = scope.columns();
     let conn = get_connection()?.lock().await;
     let now = chrono::Utc::now().timestamp();
     conn.execute(
         "UPDATE download_sessions SET status = 'pending', updated_at = ?1 
-         WHERE bucket = ?2 AND account_id = ?3 AND status = 'paused'",
-        turso::params![now, bucket, account_id],
+         WHERE bucket = ?2 AND scope_key = ?3 AND status = 'paused'",
+        turso::params![now, bucket, scope_key],
     )
     .await?;
 
@@ -351,13 +397,14 @@ pub async fn resume_all_downloads(bucket: &str, account_id: &str) -> DbResult<i6
 }
 
 /// Delete all finished downloads (completed, failed, cancelled)
-pub async fn delete_finished_downloads(bucket: &str, account_id: &str) -> DbResult<i64> {
+pub async fn delete_finished_downloads(scope: &CacheScope) -> DbResult<i64> {
+    let (bucket, scope_key) = scope.columns();
     let conn = get_connection()?.lock().await;
     conn.execute(
         "DELETE FROM download_sessions 
-         WHERE bucket = ?1 AND account_id = ?2 
+         WHERE bucket = ?1 AND scope_key = ?2 
          AND status IN ('completed', 'failed', 'cancelled')",
-        turso::params![bucket, account_id],
+        turso::params![bucket, scope_key],
     )
     .await?;
 
@@ -370,11 +417,12 @@ pub async fn delete_finished_downloads(bucket: &str, account_id: &str) -> DbResu
 }
 
 /// Delete all downloads for a bucket (only call when no active downloads)
-pub async fn delete_all_downloads(bucket: &str, account_id: &str) -> DbResult<i64> {
+pub async fn delete_all_downloads(scope: &CacheScope) -> DbResult<i64> {
+    let (bucket, scope_key) = scope.columns();
     let conn = get_connection()?.lock().await;
     conn.execute(
-        "DELETE FROM download_sessions WHERE bucket = ?1 AND account_id = ?2",
-        turso::params![bucket, account_id],
+        "DELETE FROM download_sessions WHERE bucket = ?1 AND scope_key = ?2",
+        turso::params![bucket, scope_key],
     )
     .await?;
 
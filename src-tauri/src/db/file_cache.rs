@@ -1,5 +1,8 @@
-use super::{get_connection, DbResult};
+use super::file_search::insert_key_terms;
+use super::storage_analytics::record_storage_snapshot;
+use super::{get_connection, CacheScope, DbResult};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 // ============ File Cache Structs ============
 
@@ -13,6 +16,40 @@ pub struct CachedFile {
     pub size: i64,
     pub last_modified: String,
     pub synced_at: i64,
+    /// Provider storage class (e.g. `GLACIER`); `None` when the listing omits it.
+    #[serde(default)]
+    pub storage_class: Option<String>,
+    /// ETag from the listing, used to spot content changes between syncs.
+    #[serde(default)]
+    pub etag: Option<String>,
+    /// MIME type, recorded on upload or fetched with HEAD; listings don't
+    /// carry it.
+    #[serde(default)]
+    pub content_type: Option<String>,
+    /// Additional checksum as `ALGORITHM:base64`, e.g. `CRC32C:yZRlqg==`.
+    /// Like `content_type`, only known once fetched.
+    #[serde(default)]
+    pub checksum: Option<String>,
+}
+
+/// Columns read by `row_to_cached_file`, in order.
+pub(super) const CACHED_FILE_COLUMNS: &str = "bucket, account_id, key, parent_path, name, size, last_modified, synced_at, storage_class, etag, content_type, checksum";
+
+pub(super) fn row_to_cached_file(row: &turso::Row) -> DbResult<CachedFile> {
+    Ok(CachedFile {
+        bucket: row.get(0)?,
+        account_id: row.get(1)?,
+        key: row.get(2)?,
+        parent_path: row.get(3)?,
+        name: row.get(4)?,
+        size: row.get(5)?,
+        last_modified: row.get(6)?,
+        synced_at: row.get(7)?,
+        storage_class: row.get(8)?,
+        etag: row.get(9)?,
+        content_type: row.get(10)?,
+        checksum: row.get(11)?,
+    })
 }
 
 /// Helper to extract parent path and name from a key
@@ -45,9 +82,7 @@ pub struct CachedDirectoryNode {
 pub fn get_table_sql() -> &'static str {
     "
     -- File cache tables (replaces IndexedDB)
-    -- Drop old table to recreate with new schema
-    DROP TABLE IF EXISTS cached_files;
-    
+    -- account_id holds the cache scope key, see cache_scope.rs
     CREATE TABLE IF NOT EXISTS cached_files (
         bucket TEXT NOT NULL,
         account_id TEXT NOT NULL,
@@ -57,12 +92,13 @@ pub fn get_table_sql() -> &'static str {
         size INTEGER NOT NULL,
         last_modified TEXT NOT NULL,
         synced_at INTEGER NOT NULL,
+        storage_class TEXT,
+        etag TEXT,
+        content_type TEXT,
+        checksum TEXT,
         PRIMARY KEY (bucket, account_id, key)
     );
 
-    -- Drop old directory_tree to recreate with new schema
-    DROP TABLE IF EXISTS directory_tree;
-    
     CREATE TABLE IF NOT EXISTS directory_tree (
         bucket TEXT NOT NULL,
         account_id TEXT NOT NULL,
@@ -85,87 +121,40 @@ pub fn get_table_sql() -> &'static str {
         PRIMARY KEY (bucket, account_id)
     );
 
+    -- Listing of a sync in flight, swapped into cached_files by finish_sync
+    CREATE TABLE IF NOT EXISTS cached_files_staging (
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        key TEXT NOT NULL,
+        parent_path TEXT NOT NULL,
+        name TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        last_modified TEXT NOT NULL,
+        synced_at INTEGER NOT NULL,
+        storage_class TEXT,
+        etag TEXT,
+        PRIMARY KEY (bucket, account_id, key)
+    );
+
     -- Index for fast folder listing (exact match on parent_path)
     CREATE INDEX IF NOT EXISTS idx_cached_files_parent ON cached_files(bucket, account_id, parent_path);
     CREATE INDEX IF NOT EXISTS idx_directory_tree_parent ON directory_tree(bucket, account_id, parent_path);
+    CREATE INDEX IF NOT EXISTS idx_cached_files_etag ON cached_files(bucket, account_id, etag);
     "
 }
 
 // ============ File Cache Functions ============
 
-/// Store all files for a bucket (clears existing) - optimized with batch inserts
-pub async fn store_all_files(bucket: &str, account_id: &str, files: &[CachedFile]) -> DbResult<()> {
-    // Batch insert files - SQLite supports multi-row INSERT.
-    // Keep well below SQLite parameter limits: 1000 * 8 = 8000 params.
-    const BATCH_SIZE: usize = 1000;
-    const YIELD_EVERY_BATCHES: usize = 8;
-
+/// Store all files for a bucket, replacing what was cached for it.
+pub async fn store_all_files(scope: &CacheScope, files: &[CachedFile]) -> DbResult<()> {
+    let (bucket, account_id) = scope.columns();
     let now = chrono::Utc::now().timestamp();
     let conn = get_connection()?.lock().await;
     conn.execute("BEGIN TRANSACTION", ()).await?;
 
     let tx_result = async {
-        conn.execute(
-            "DELETE FROM cached_files WHERE bucket = ?1 AND account_id = ?2",
-            turso::params![bucket, account_id],
-        )
-        .await?;
-
-        for (batch_idx, chunk) in files.chunks(BATCH_SIZE).enumerate() {
-            if chunk.is_empty() {
-                continue;
-            }
-
-            // Build multi-value INSERT statement (8 columns)
-            let placeholders: Vec<String> = chunk
-                .iter()
-                .enumerate()
-                .map(|(i, _)| {
-                    let base = i * 8;
-                    format!(
-                        "(?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{})",
-                        base + 1,
-                        base + 2,
-                        base + 3,
-                        base + 4,
-                        base + 5,
-                        base + 6,
-                        base + 7,
-                        base + 8
-                    )
-                })
-                .collect();
-
-            let sql = format!(
-                "INSERT INTO cached_files (bucket, account_id, key, parent_path, name, size, last_modified, synced_at) VALUES {}",
-                placeholders.join(", ")
-            );
-
-            let mut params: Vec<turso::Value> = Vec::with_capacity(chunk.len() * 8);
-            for file in chunk {
-                // Reuse precomputed parent_path/name when provided.
-                let (parent_path, name) = if file.name.is_empty() {
-                    parse_key(&file.key)
-                } else {
-                    (file.parent_path.clone(), file.name.clone())
-                };
-
-                params.push(bucket.to_string().into());
-                params.push(account_id.to_string().into());
-                params.push(file.key.clone().into());
-                params.push(parent_path.into());
-                params.push(name.into());
-                params.push(file.size.into());
-                params.push(file.last_modified.clone().into());
-                params.push(file.synced_at.into());
-            }
-
-            conn.execute(&sql, params).await?;
-
-            if (batch_idx + 1) % YIELD_EVERY_BATCHES == 0 {
-                tokio::task::yield_now().await;
-            }
-        }
+        let cached = cached_keys(&conn, bucket, account_id, None).await?;
+        write_listing(&conn, bucket, account_id, &cached, files, None).await?;
 
         conn.execute(
             "INSERT INTO sync_meta (bucket, account_id, last_sync, file_count)
@@ -174,6 +163,7 @@ pub async fn store_all_files(bucket: &str, account_id: &str, files: &[CachedFile
             turso::params![bucket, account_id, now, files.len() as i32],
         )
         .await?;
+        record_storage_snapshot(&conn, bucket, account_id, now).await?;
 
         Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
     }
@@ -189,36 +179,30 @@ pub async fn store_all_files(bucket: &str, account_id: &str, files: &[CachedFile
 }
 
 /// Get all cached files for a bucket
-pub async fn get_all_cached_files(bucket: &str, account_id: &str) -> DbResult<Vec<CachedFile>> {
+pub async fn get_all_cached_files(scope: &CacheScope) -> DbResult<Vec<CachedFile>> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
     let mut rows = conn
         .query(
-            "SELECT bucket, account_id, key, parent_path, name, size, last_modified, synced_at
-         FROM cached_files
-         WHERE bucket = ?1 AND account_id = ?2
-         ORDER BY key",
+            &format!(
+                "SELECT {CACHED_FILE_COLUMNS} FROM cached_files
+                 WHERE bucket = ?1 AND account_id = ?2
+                 ORDER BY key"
+            ),
             turso::params![bucket, account_id],
         )
         .await?;
 
     let mut files = Vec::new();
     while let Some(row) = rows.next().await? {
-        files.push(CachedFile {
-            bucket: row.get(0)?,
-            account_id: row.get(1)?,
-            key: row.get(2)?,
-            parent_path: row.get(3)?,
-            name: row.get(4)?,
-            size: row.get(5)?,
-            last_modified: row.get(6)?,
-            synced_at: row.get(7)?,
-        });
+        files.push(row_to_cached_file(&row)?);
     }
     Ok(files)
 }
 
 /// Get a single file's size from cache (returns 0 if not found)
-pub async fn get_cached_file_size(bucket: &str, account_id: &str, key: &str) -> DbResult<i64> {
+pub async fn get_cached_file_size(scope: &CacheScope, key: &str) -> DbResult<i64> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
     let mut rows = conn
         .query(
@@ -234,14 +218,218 @@ pub async fn get_cached_file_size(bucket: &str, account_id: &str, key: &str) ->
     }
 }
 
+/// Get a single file's storage class from cache (None if unknown or not cached)
+pub async fn get_cached_storage_class(scope: &CacheScope, key: &str) -> DbResult<Option<String>> {
+    let (bucket, account_id) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    let mut rows = conn
+        .query(
+            "SELECT storage_class FROM cached_files WHERE bucket = ?1 AND account_id = ?2 AND key = ?3",
+            turso::params![bucket, account_id, key],
+        )
+        .await?;
+
+    if let Some(row) = rows.next().await? {
+        Ok(row.get(0)?)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Record a cached file's storage class after an upload, copy or transition.
+pub async fn set_cached_storage_class(
+    scope: &CacheScope,
+    key: &str,
+    storage_class: &str,
+) -> DbResult<()> {
+    let (bucket, account_id) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    conn.execute(
+        "UPDATE cached_files SET storage_class = ?1 WHERE bucket = ?2 AND account_id = ?3 AND key = ?4",
+        turso::params![storage_class, bucket, account_id, key],
+    )
+    .await?;
+    Ok(())
+}
+
+/// Object metadata learned outside a listing, from an upload response or a
+/// HEAD request. `None` fields leave the cached value as it is.
+#[derive(Debug, Clone, Default)]
+pub struct CachedObjectMeta {
+    pub etag: Option<String>,
+    pub storage_class: Option<String>,
+    pub content_type: Option<String>,
+    pub checksum: Option<String>,
+}
+
+fn optional_value(value: &Option<String>) -> turso::Value {
+    value
+        .clone()
+        .map(|s| s.into())
+        .unwrap_or(turso::Value::Null)
+}
+
+/// Record metadata for a cached file. Returns false when the key isn't cached.
+pub async fn set_cached_object_meta(
+    scope: &CacheScope,
+    key: &str,
+    meta: &CachedObjectMeta,
+) -> DbResult<bool> {
+    let (bucket, account_id) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    conn.execute(
+        "UPDATE cached_files SET
+           etag = COALESCE(?1, etag),
+           storage_class = COALESCE(?2, storage_class),
+           content_type = COALESCE(?3, content_type),
+           checksum = COALESCE(?4, checksum)
+         WHERE bucket = ?5 AND account_id = ?6 AND key = ?7",
+        vec![
+            optional_value(&meta.etag),
+            optional_value(&meta.storage_class),
+            optional_value(&meta.content_type),
+            optional_value(&meta.checksum),
+            bucket.to_string().into(),
+            account_id.to_string().into(),
+            key.to_string().into(),
+        ],
+    )
+    .await?;
+
+    let mut rows = conn.query("SELECT changes()", turso::params![]).await?;
+    match rows.next().await? {
+        Some(row) => Ok(row.get::<i64>(0)? > 0),
+        None => Ok(false),
+    }
+}
+
+/// A single cached file with all its recorded metadata.
+pub async fn get_cached_file(scope: &CacheScope, key: &str) -> DbResult<Option<CachedFile>> {
+    let (bucket, account_id) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    let mut rows = conn
+        .query(
+            &format!(
+                "SELECT {CACHED_FILE_COLUMNS} FROM cached_files
+                 WHERE bucket = ?1 AND account_id = ?2 AND key = ?3"
+            ),
+            turso::params![bucket, account_id, key],
+        )
+        .await?;
+
+    match rows.next().await? {
+        Some(row) => Ok(Some(row_to_cached_file(&row)?)),
+        None => Ok(None),
+    }
+}
+
+/// Cached files whose ETag equals `etag`. Quotes are optional, listings
+/// return ETags quoted.
+pub async fn find_cached_files_by_etag(
+    scope: &CacheScope,
+    etag: &str,
+) -> DbResult<Vec<CachedFile>> {
+    let (bucket, account_id) = scope.columns();
+    let quoted = format!("\"{}\"", etag.trim_matches('"'));
+    let conn = get_connection()?.lock().await;
+    let mut rows = conn
+        .query(
+            &format!(
+                "SELECT {CACHED_FILE_COLUMNS} FROM cached_files
+                 WHERE bucket = ?1 AND account_id = ?2 AND etag IN (?3, ?4)
+                 ORDER BY key"
+            ),
+            turso::params![bucket, account_id, quoted.as_str(), etag.trim_matches('"')],
+        )
+        .await?;
+
+    let mut files = Vec::new();
+    while let Some(row) = rows.next().await? {
+        files.push(row_to_cached_file(&row)?);
+    }
+    Ok(files)
+}
+
+/// Cached files in a storage class. `None` selects files whose class the
+/// listing didn't report, which providers use for their default class.
+pub async fn get_cached_files_by_storage_class(
+    scope: &CacheScope,
+    storage_class: Option<&str>,
+) -> DbResult<Vec<CachedFile>> {
+    let (bucket, account_id) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    let mut rows = match storage_class {
+        Some(class) => {
+            conn.query(
+                &format!(
+                    "SELECT {CACHED_FILE_COLUMNS} FROM cached_files
+                     WHERE bucket = ?1 AND account_id = ?2 AND storage_class = ?3
+                     ORDER BY key"
+                ),
+                turso::params![bucket, account_id, class],
+            )
+            .await?
+        }
+        None => {
+            conn.query(
+                &format!(
+                    "SELECT {CACHED_FILE_COLUMNS} FROM cached_files
+                     WHERE bucket = ?1 AND account_id = ?2 AND storage_class IS NULL
+                     ORDER BY key"
+                ),
+                turso::params![bucket, account_id],
+            )
+            .await?
+        }
+    };
+
+    let mut files = Vec::new();
+    while let Some(row) = rows.next().await? {
+        files.push(row_to_cached_file(&row)?);
+    }
+    Ok(files)
+}
+
+/// File count and bytes per storage class.
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageClassUsage {
+    /// `None` for files whose listing didn't report a class.
+    pub storage_class: Option<String>,
+    pub file_count: i64,
+    pub total_size: i64,
+}
+
+/// Break the cached files of a bucket down by storage class, largest first.
+pub async fn get_storage_class_usage(scope: &CacheScope) -> DbResult<Vec<StorageClassUsage>> {
+    let (bucket, account_id) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    let mut rows = conn
+        .query(
+            "SELECT storage_class, COUNT(*), COALESCE(SUM(size), 0)
+             FROM cached_files
+             WHERE bucket = ?1 AND account_id = ?2
+             GROUP BY storage_class
+             ORDER BY 3 DESC",
+            turso::params![bucket, account_id],
+        )
+        .await?;
+
+    let mut usage = Vec::new();
+    while let Some(row) = rows.next().await? {
+        usage.push(StorageClassUsage {
+            storage_class: row.get(0)?,
+            file_count: row.get(1)?,
+            total_size: row.get(2)?,
+        });
+    }
+    Ok(usage)
+}
+
 /// Delete a single cached file.
 /// Returns the file's size for directory tree updates (negative delta).
 /// None means the file was not found in cache.
-pub async fn delete_cached_file(
-    bucket: &str,
-    account_id: &str,
-    key: &str,
-) -> DbResult<Option<i64>> {
+pub async fn delete_cached_file(scope: &CacheScope, key: &str) -> DbResult<Option<i64>> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
 
     // Get file size before deleting
@@ -259,12 +447,18 @@ pub async fn delete_cached_file(
     };
     drop(rows);
 
-    // Delete the file record
+    // Delete the file record, then its search terms. Terms left behind by a
+    // failure in between match no row, so search stays correct.
     conn.execute(
         "DELETE FROM cached_files WHERE bucket = ?1 AND account_id = ?2 AND key = ?3",
         turso::params![bucket, account_id, key],
     )
     .await?;
+    conn.execute(
+        "DELETE FROM cached_file_terms WHERE bucket = ?1 AND account_id = ?2 AND key = ?3",
+        turso::params![bucket, account_id, key],
+    )
+    .await?;
 
     Ok(Some(size))
 }
@@ -272,10 +466,10 @@ pub async fn delete_cached_file(
 /// Delete multiple cached files in batch.
 /// Returns a map of key -> size for directory tree updates.
 pub async fn delete_cached_files_batch(
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     keys: &[String],
 ) -> DbResult<std::collections::HashMap<String, i64>> {
+    let (bucket, account_id) = scope.columns();
     if keys.is_empty() {
         return Ok(std::collections::HashMap::new());
     }
@@ -311,40 +505,75 @@ pub async fn delete_cached_files_batch(
         }
     }
 
-    // Delete all files
-    for chunk in keys.chunks(500) {
-        let placeholders: Vec<String> = chunk
-            .iter()
-            .enumerate()
-            .map(|(i, _)| format!("?{}", i + 3))
-            .collect();
+    // Delete all files, then their search terms
+    let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+    execute_for_keys(
+        &conn,
+        "DELETE FROM cached_files WHERE bucket = ?1 AND account_id = ?2 AND key IN ({})",
+        bucket,
+        account_id,
+        &keys,
+    )
+    .await?;
+    execute_for_keys(
+        &conn,
+        "DELETE FROM cached_file_terms WHERE bucket = ?1 AND account_id = ?2 AND key IN ({})",
+        bucket,
+        account_id,
+        &keys,
+    )
+    .await?;
 
-        let sql = format!(
-            "DELETE FROM cached_files WHERE bucket = ?1 AND account_id = ?2 AND key IN ({})",
-            placeholders.join(", ")
-        );
+    Ok(file_sizes)
+}
 
-        let mut params: Vec<turso::Value> = Vec::new();
-        params.push(bucket.to_string().into());
-        params.push(account_id.to_string().into());
-        for key in chunk {
-            params.push(key.clone().into());
-        }
+/// Delete every cached file under `prefix`, e.g. when the folder is gone.
+/// Returns (key, size) pairs for directory tree updates.
+pub async fn delete_cached_prefix(
+    scope: &CacheScope,
+    prefix: &str,
+) -> DbResult<Vec<(String, i64)>> {
+    let (bucket, account_id) = scope.columns();
+    let conn = get_connection()?.lock().await;
 
-        conn.execute(&sql, params).await?;
+    // substr instead of LIKE so '%' and '_' in keys match literally
+    let mut rows = conn
+        .query(
+            "SELECT key, size FROM cached_files
+             WHERE bucket = ?1 AND account_id = ?2 AND substr(key, 1, length(?3)) = ?3",
+            turso::params![bucket, account_id, prefix],
+        )
+        .await?;
+    let mut deleted = Vec::new();
+    while let Some(row) = rows.next().await? {
+        deleted.push((row.get(0)?, row.get(1)?));
     }
+    drop(rows);
 
-    Ok(file_sizes)
+    conn.execute(
+        "DELETE FROM cached_files
+         WHERE bucket = ?1 AND account_id = ?2 AND substr(key, 1, length(?3)) = ?3",
+        turso::params![bucket, account_id, prefix],
+    )
+    .await?;
+    conn.execute(
+        "DELETE FROM cached_file_terms
+         WHERE bucket = ?1 AND account_id = ?2 AND substr(key, 1, length(?3)) = ?3",
+        turso::params![bucket, account_id, prefix],
+    )
+    .await?;
+
+    Ok(deleted)
 }
 
 /// Move/rename a cached file to a new key.
 /// Returns (size, last_modified) for directory tree updates.
 pub async fn move_cached_file(
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     old_key: &str,
     new_key: &str,
 ) -> DbResult<Option<(i64, String)>> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
 
     // Get file info
@@ -366,36 +595,57 @@ pub async fn move_cached_file(
     let (new_parent_path, new_name) = parse_key(new_key);
     let now = chrono::Utc::now().timestamp();
 
-    // Update the file record with new key, parent_path, and name
-    conn.execute(
-        "UPDATE cached_files SET key = ?1, parent_path = ?2, name = ?3, synced_at = ?4
-         WHERE bucket = ?5 AND account_id = ?6 AND key = ?7",
-        turso::params![
-            new_key,
-            new_parent_path,
-            new_name,
-            now,
-            bucket,
-            account_id,
-            old_key
-        ],
-    )
-    .await?;
+    conn.execute("BEGIN TRANSACTION", ()).await?;
+
+    let tx_result = async {
+        // Update the file record with new key, parent_path, and name
+        conn.execute(
+            "UPDATE cached_files SET key = ?1, parent_path = ?2, name = ?3, synced_at = ?4
+             WHERE bucket = ?5 AND account_id = ?6 AND key = ?7",
+            turso::params![
+                new_key,
+                new_parent_path,
+                new_name,
+                now,
+                bucket,
+                account_id,
+                old_key
+            ],
+        )
+        .await?;
+        conn.execute(
+            "DELETE FROM cached_file_terms WHERE bucket = ?1 AND account_id = ?2 AND key = ?3",
+            turso::params![bucket, account_id, old_key],
+        )
+        .await?;
+        insert_key_terms(&conn, "cached_file_terms", bucket, account_id, &[new_key]).await?;
+
+        Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+    }
+    .await;
 
+    if let Err(err) = tx_result {
+        let _ = conn.execute("ROLLBACK", ()).await;
+        return Err(err);
+    }
+
+    conn.execute("COMMIT", ()).await?;
     Ok(file_info)
 }
 
-/// Update or insert a single cached file.
+/// Update or insert a single cached file. An overwritten file's storage
+/// class, ETag, content type and checksum are unknown until the caller
+/// records them with `set_cached_object_meta` or the next sync.
 /// Returns (size_delta, is_new_file) for directory tree updates.
 /// - size_delta: new_size - old_size (or new_size if new file)
 /// - is_new_file: true if this was an insert, false if update
 pub async fn update_cached_file(
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     key: &str,
     new_size: i64,
     last_modified: &str,
 ) -> DbResult<(i64, bool)> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
 
     // Get old size for delta calculation (if file exists)
@@ -416,15 +666,33 @@ pub async fn update_cached_file(
     let now = chrono::Utc::now().timestamp();
     let (parent_path, name) = parse_key(key);
 
-    // Use INSERT OR REPLACE to handle both new files and updates
-    conn.execute(
-        "INSERT INTO cached_files (bucket, account_id, key, parent_path, name, size, last_modified, synced_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-         ON CONFLICT (bucket, account_id, key) DO UPDATE SET
-           size = ?6, last_modified = ?7, synced_at = ?8",
-        turso::params![bucket, account_id, key, parent_path, name, new_size, last_modified, now],
-    ).await?;
+    conn.execute("BEGIN TRANSACTION", ()).await?;
+
+    let tx_result = async {
+        // Use INSERT OR REPLACE to handle both new files and updates
+        conn.execute(
+            "INSERT INTO cached_files (bucket, account_id, key, parent_path, name, size, last_modified, synced_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT (bucket, account_id, key) DO UPDATE SET
+               size = ?6, last_modified = ?7, synced_at = ?8,
+               storage_class = NULL, etag = NULL, content_type = NULL, checksum = NULL",
+            turso::params![bucket, account_id, key, parent_path, name, new_size, last_modified, now],
+        ).await?;
+        // An existing key keeps its terms
+        if is_new_file {
+            insert_key_terms(&conn, "cached_file_terms", bucket, account_id, &[key]).await?;
+        }
+
+        Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+    }
+    .await;
+
+    if let Err(err) = tx_result {
+        let _ = conn.execute("ROLLBACK", ()).await;
+        return Err(err);
+    }
 
+    conn.execute("COMMIT", ()).await?;
     Ok((new_size - old_size, is_new_file))
 }
 
@@ -438,11 +706,8 @@ pub struct SearchResult {
 /// Search cached files by key pattern (case-insensitive)
 /// Supports multiple terms separated by spaces (AND search)
 /// e.g., "test name" matches files containing both "test" AND "name"
-pub async fn search_cached_files(
-    bucket: &str,
-    account_id: &str,
-    query: &str,
-) -> DbResult<SearchResult> {
+pub async fn search_cached_files(scope: &CacheScope, query: &str) -> DbResult<SearchResult> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
 
     // Split query into terms and create LIKE conditions for each
@@ -464,8 +729,7 @@ pub async fn search_cached_files(
 
     let where_clause = like_conditions.join(" AND ");
     let sql = format!(
-        "SELECT bucket, account_id, key, parent_path, name, size, last_modified, synced_at
-         FROM cached_files
+        "SELECT {CACHED_FILE_COLUMNS} FROM cached_files
          WHERE bucket = ?1 AND account_id = ?2 AND {}
          ORDER BY key",
         where_clause
@@ -483,16 +747,7 @@ pub async fn search_cached_files(
 
     let mut files = Vec::new();
     while let Some(row) = rows.next().await? {
-        files.push(CachedFile {
-            bucket: row.get(0)?,
-            account_id: row.get(1)?,
-            key: row.get(2)?,
-            parent_path: row.get(3)?,
-            name: row.get(4)?,
-            size: row.get(5)?,
-            last_modified: row.get(6)?,
-            synced_at: row.get(7)?,
-        });
+        files.push(row_to_cached_file(&row)?);
     }
 
     let total_count = files.len() as i32;
@@ -500,7 +755,8 @@ pub async fn search_cached_files(
 }
 
 /// Calculate folder size by prefix
-pub async fn calculate_folder_size(bucket: &str, account_id: &str, prefix: &str) -> DbResult<i64> {
+pub async fn calculate_folder_size(scope: &CacheScope, prefix: &str) -> DbResult<i64> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
     let pattern = format!("{}%", prefix);
 
@@ -532,7 +788,20 @@ pub struct BucketSummary {
     pub is_complete: bool,
 }
 
-/// Get a bucket-wide summary (total file count + total size).
+/// Whether a full listing of the bucket has been stored (a sync_meta row exists).
+pub async fn has_full_sync(scope: &CacheScope) -> DbResult<bool> {
+    let (bucket, account_id) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    let mut rows = conn
+        .query(
+            "SELECT 1 FROM sync_meta WHERE bucket = ?1 AND account_id = ?2",
+            turso::params![bucket, account_id],
+        )
+        .await?;
+    Ok(rows.next().await?.is_some())
+}
+
+/// Get a bucket-wide summary (total file count + total size).
 ///
 /// Resolution order:
 /// 1. directory_tree root node — exact totals, but only trusted once a
@@ -540,23 +809,15 @@ pub struct BucketSummary {
 ///    deltas can create a partial root node before any full sync).
 /// 2. SQL aggregate over cached_files — covers lazy-browsed partial data.
 ///    Exact-match on indexed columns, no LIKE.
-pub async fn get_bucket_summary(bucket: &str, account_id: &str) -> DbResult<BucketSummary> {
+pub async fn get_bucket_summary(scope: &CacheScope) -> DbResult<BucketSummary> {
+    let (bucket, account_id) = scope.columns();
     // sync_meta only exists after a full sync finished — it both gates the
     // directory-tree shortcut and distinguishes a genuinely empty bucket
     // (complete) from not-yet-synced lazy data in the aggregate fallback.
-    let has_full_sync = {
-        let conn = get_connection()?.lock().await;
-        let mut meta_rows = conn
-            .query(
-                "SELECT 1 FROM sync_meta WHERE bucket = ?1 AND account_id = ?2",
-                turso::params![bucket, account_id],
-            )
-            .await?;
-        meta_rows.next().await?.is_some()
-    };
+    let synced = has_full_sync(scope).await?;
 
-    if has_full_sync {
-        if let Some(root) = get_directory_node(bucket, account_id, "").await? {
+    if synced {
+        if let Some(root) = get_directory_node(scope, "").await? {
             return Ok(BucketSummary {
                 total_files: root.total_file_count as i64,
                 total_size: root.total_size,
@@ -582,24 +843,24 @@ pub async fn get_bucket_summary(bucket: &str, account_id: &str) -> DbResult<Buck
             total_files: row.get(0)?,
             total_size: row.get(1)?,
             last_modified: row.get(2)?,
-            is_complete: has_full_sync,
+            is_complete: synced,
         })
     } else {
         Ok(BucketSummary {
             total_files: 0,
             total_size: 0,
             last_modified: None,
-            is_complete: has_full_sync,
+            is_complete: synced,
         })
     }
 }
 
 /// Get directory node by path
 pub async fn get_directory_node(
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     path: &str,
 ) -> DbResult<Option<CachedDirectoryNode>> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
     let mut rows = conn.query(
         "SELECT bucket, account_id, path, parent_path, file_count, total_file_count, size, total_size, last_modified, last_updated
@@ -627,10 +888,8 @@ pub async fn get_directory_node(
 }
 
 /// Get all directory nodes for a bucket
-pub async fn get_all_directory_nodes(
-    bucket: &str,
-    account_id: &str,
-) -> DbResult<Vec<CachedDirectoryNode>> {
+pub async fn get_all_directory_nodes(scope: &CacheScope) -> DbResult<Vec<CachedDirectoryNode>> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
     let mut rows = conn.query(
         "SELECT bucket, account_id, path, parent_path, file_count, total_file_count, size, total_size, last_modified, last_updated
@@ -659,7 +918,8 @@ pub async fn get_all_directory_nodes(
 }
 
 /// Clear all cached data for a bucket
-pub async fn clear_file_cache(bucket: &str, account_id: &str) -> DbResult<()> {
+pub async fn clear_file_cache(scope: &CacheScope) -> DbResult<()> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
 
     conn.execute(
@@ -680,6 +940,9 @@ pub async fn clear_file_cache(bucket: &str, account_id: &str) -> DbResult<()> {
     )
     .await?;
 
+    super::file_search::clear_search_terms(&conn, bucket, account_id).await?;
+    super::object_hashes::clear_object_hashes(&conn, bucket, account_id).await?;
+
     Ok(())
 }
 
@@ -694,37 +957,26 @@ pub struct FolderContents {
 /// This mimics S3 ListObjectsV2 with delimiter="/" behavior
 ///
 /// FAST: Uses exact match on parent_path (indexed) instead of LIKE patterns
-pub async fn get_folder_contents(
-    bucket: &str,
-    account_id: &str,
-    prefix: &str,
-) -> DbResult<FolderContents> {
+pub async fn get_folder_contents(scope: &CacheScope, prefix: &str) -> DbResult<FolderContents> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
 
     // Query 1: Get files directly in this folder using EXACT MATCH on parent_path
     // This is O(1) index lookup instead of O(n) LIKE scan
     let mut rows = conn
         .query(
-            "SELECT bucket, account_id, key, parent_path, name, size, last_modified, synced_at
-         FROM cached_files
-         WHERE bucket = ?1 AND account_id = ?2 AND parent_path = ?3
-         ORDER BY name",
+            &format!(
+                "SELECT {CACHED_FILE_COLUMNS} FROM cached_files
+                 WHERE bucket = ?1 AND account_id = ?2 AND parent_path = ?3
+                 ORDER BY name"
+            ),
             turso::params![bucket, account_id, prefix],
         )
         .await?;
 
     let mut files = Vec::new();
     while let Some(row) = rows.next().await? {
-        files.push(CachedFile {
-            bucket: row.get(0)?,
-            account_id: row.get(1)?,
-            key: row.get(2)?,
-            parent_path: row.get(3)?,
-            name: row.get(4)?,
-            size: row.get(5)?,
-            last_modified: row.get(6)?,
-            synced_at: row.get(7)?,
-        });
+        files.push(row_to_cached_file(&row)?);
     }
 
     // Query 2: Get immediate child folders from directory_tree using EXACT MATCH on parent_path
@@ -747,38 +999,191 @@ pub async fn get_folder_contents(
     Ok(FolderContents { files, folders })
 }
 
+// ============ Fetched Metadata ============
+//
+// Content type and checksum come from uploads and HEAD requests, never from
+// a listing. Rows written from a listing are upserted with
+// `KEEP_FETCHED_META`, so unchanged objects keep them in place.
+
+/// Conflict clause for writing a listed row over a cached one. Content type
+/// and checksum stay while the row still holds the same object: same size,
+/// and the same ETag, or the same last_modified where either side has no
+/// ETag. Every expression reads the cached row as it was before the update.
+const KEEP_FETCHED_META: &str = "ON CONFLICT (bucket, account_id, key) DO UPDATE SET
+       parent_path = excluded.parent_path,
+       name = excluded.name,
+       size = excluded.size,
+       last_modified = excluded.last_modified,
+       synced_at = excluded.synced_at,
+       storage_class = excluded.storage_class,
+       etag = excluded.etag,
+       content_type = CASE
+         WHEN size = excluded.size
+          AND CASE WHEN etag IS NOT NULL AND excluded.etag IS NOT NULL THEN etag = excluded.etag
+                   ELSE last_modified = excluded.last_modified END
+         THEN content_type END,
+       checksum = CASE
+         WHEN size = excluded.size
+          AND CASE WHEN etag IS NOT NULL AND excluded.etag IS NOT NULL THEN etag = excluded.etag
+                   ELSE last_modified = excluded.last_modified END
+         THEN checksum END";
+
+/// Cached keys of a bucket, or of the files directly in `parent_path`.
+async fn cached_keys(
+    conn: &turso::Connection,
+    bucket: &str,
+    account_id: &str,
+    parent_path: Option<&str>,
+) -> DbResult<HashSet<String>> {
+    let mut rows = match parent_path {
+        Some(parent_path) => {
+            conn.query(
+                "SELECT key FROM cached_files
+                 WHERE bucket = ?1 AND account_id = ?2 AND parent_path = ?3",
+                turso::params![bucket, account_id, parent_path],
+            )
+            .await?
+        }
+        None => {
+            conn.query(
+                "SELECT key FROM cached_files WHERE bucket = ?1 AND account_id = ?2",
+                turso::params![bucket, account_id],
+            )
+            .await?
+        }
+    };
+    let mut keys = HashSet::new();
+    while let Some(row) = rows.next().await? {
+        keys.insert(row.get(0)?);
+    }
+    Ok(keys)
+}
+
+/// Write a fresh listing over `cached`, the cached keys it covers: keys no
+/// longer listed are deleted with their search terms, listed files are
+/// upserted with `KEEP_FETCHED_META`. Terms depend on the key alone, so only
+/// new keys get them. `synced_at` overrides the files' own.
+async fn write_listing(
+    conn: &turso::Connection,
+    bucket: &str,
+    account_id: &str,
+    cached: &HashSet<String>,
+    files: &[CachedFile],
+    synced_at: Option<i64>,
+) -> DbResult<()> {
+    // Keep well below SQLite parameter limits: 1000 * 10 = 10000 params.
+    const BATCH_SIZE: usize = 1000;
+    const YIELD_EVERY_BATCHES: usize = 8;
+
+    let listed: HashSet<&str> = files.iter().map(|f| f.key.as_str()).collect();
+    let gone: Vec<&str> = cached
+        .iter()
+        .map(String::as_str)
+        .filter(|key| !listed.contains(key))
+        .collect();
+    execute_for_keys(
+        conn,
+        "DELETE FROM cached_files WHERE bucket = ?1 AND account_id = ?2 AND key IN ({})",
+        bucket,
+        account_id,
+        &gone,
+    )
+    .await?;
+    execute_for_keys(
+        conn,
+        "DELETE FROM cached_file_terms WHERE bucket = ?1 AND account_id = ?2 AND key IN ({})",
+        bucket,
+        account_id,
+        &gone,
+    )
+    .await?;
+
+    for (batch_idx, chunk) in files.chunks(BATCH_SIZE).enumerate() {
+        let placeholders: Vec<String> = chunk
+            .iter()
+            .enumerate()
+            .map(|(i, _)| {
+                let base = i * 10;
+                format!(
+                    "(?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{})",
+                    base + 1,
+                    base + 2,
+                    base + 3,
+                    base + 4,
+                    base + 5,
+                    base + 6,
+                    base + 7,
+                    base + 8,
+                    base + 9,
+                    base + 10
+                )
+            })
+            .collect();
+
+        let sql = format!(
+            "INSERT INTO cached_files (bucket, account_id, key, parent_path, name, size, last_modified, synced_at, storage_class, etag) VALUES {} {}",
+            placeholders.join(", "),
+            KEEP_FETCHED_META
+        );
+
+        let mut params: Vec<turso::Value> = Vec::with_capacity(chunk.len() * 10);
+        for file in chunk {
+            // Reuse precomputed parent_path/name when provided.
+            let (parent_path, name) = if file.name.is_empty() {
+                parse_key(&file.key)
+            } else {
+                (file.parent_path.clone(), file.name.clone())
+            };
+
+            params.push(bucket.to_string().into());
+            params.push(account_id.to_string().into());
+            params.push(file.key.clone().into());
+            params.push(parent_path.into());
+            params.push(name.into());
+            params.push(file.size.into());
+            params.push(file.last_modified.clone().into());
+            params.push(synced_at.unwrap_or(file.synced_at).into());
+            params.push(optional_value(&file.storage_class));
+            params.push(optional_value(&file.etag));
+        }
+
+        conn.execute(&sql, params).await?;
+        let new_keys: Vec<&str> = chunk
+            .iter()
+            .map(|f| f.key.as_str())
+            .filter(|key| !cached.contains(*key))
+            .collect();
+        insert_key_terms(conn, "cached_file_terms", bucket, account_id, &new_keys).await?;
+
+        if (batch_idx + 1) % YIELD_EVERY_BATCHES == 0 {
+            tokio::task::yield_now().await;
+        }
+    }
+    Ok(())
+}
+
 // ============ Streaming Sync Functions ============
 //
 // Uses a staging table so the live cached_files table is untouched during sync.
 // If the sync fails midway, old data is preserved — the staging table is just
 // abandoned and cleaned up on the next begin_sync call.
 // finish_sync atomically swaps staging → live in a single transaction.
+// Search terms are staged with the listing and swapped in along with it.
 
-/// Step 1: prepare staging table for new sync data.
+/// Step 1: clear the scope's rows from the staging tables.
 /// Old data in cached_files stays intact and queryable during the entire sync.
-pub async fn begin_sync(bucket: &str, account_id: &str) -> DbResult<()> {
+pub async fn begin_sync(scope: &CacheScope) -> DbResult<()> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
 
-    // Create staging table if it doesn't exist (same schema, no indexes needed)
+    // Clear any leftover staging data from a previous failed sync
     conn.execute(
-        "CREATE TABLE IF NOT EXISTS cached_files_staging (
-            bucket TEXT NOT NULL,
-            account_id TEXT NOT NULL,
-            key TEXT NOT NULL,
-            parent_path TEXT NOT NULL,
-            name TEXT NOT NULL,
-            size INTEGER NOT NULL,
-            last_modified TEXT NOT NULL,
-            synced_at INTEGER NOT NULL,
-            PRIMARY KEY (bucket, account_id, key)
-        )",
-        (),
+        "DELETE FROM cached_files_staging WHERE bucket = ?1 AND account_id = ?2",
+        turso::params![bucket, account_id],
     )
     .await?;
-
-    // Clear any leftover staging data from a previous failed sync
     conn.execute(
-        "DELETE FROM cached_files_staging WHERE bucket = ?1 AND account_id = ?2",
+        "DELETE FROM cached_file_terms_staging WHERE bucket = ?1 AND account_id = ?2",
         turso::params![bucket, account_id],
     )
     .await?;
@@ -786,13 +1191,10 @@ pub async fn begin_sync(bucket: &str, account_id: &str) -> DbResult<()> {
     Ok(())
 }
 
-/// Step 2: insert a batch of files into the STAGING table.
-/// The live cached_files table is not modified.
-pub async fn store_file_batch(
-    bucket: &str,
-    account_id: &str,
-    files: &[CachedFile],
-) -> DbResult<()> {
+/// Step 2: insert a batch of files and their search terms into the STAGING
+/// tables. The live cached_files table is not modified.
+pub async fn store_file_batch(scope: &CacheScope, files: &[CachedFile]) -> DbResult<()> {
+    let (bucket, account_id) = scope.columns();
     if files.is_empty() {
         return Ok(());
     }
@@ -811,21 +1213,21 @@ pub async fn store_file_batch(
                 .iter()
                 .enumerate()
                 .map(|(i, _)| {
-                    let base = i * 8;
+                    let base = i * 10;
                     format!(
-                        "(?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{})",
-                        base + 1, base + 2, base + 3, base + 4,
-                        base + 5, base + 6, base + 7, base + 8
+                        "(?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{})",
+                        base + 1, base + 2, base + 3, base + 4, base + 5,
+                        base + 6, base + 7, base + 8, base + 9, base + 10
                     )
                 })
                 .collect();
 
             let sql = format!(
-                "INSERT INTO cached_files_staging (bucket, account_id, key, parent_path, name, size, last_modified, synced_at) VALUES {}",
+                "INSERT INTO cached_files_staging (bucket, account_id, key, parent_path, name, size, last_modified, synced_at, storage_class, etag) VALUES {}",
                 placeholders.join(", ")
             );
 
-            let mut params: Vec<turso::Value> = Vec::with_capacity(chunk.len() * 8);
+            let mut params: Vec<turso::Value> = Vec::with_capacity(chunk.len() * 10);
             for file in chunk {
                 let (parent_path, name) = if file.name.is_empty() {
                     parse_key(&file.key)
@@ -841,9 +1243,24 @@ pub async fn store_file_batch(
                 params.push(file.size.into());
                 params.push(file.last_modified.clone().into());
                 params.push(file.synced_at.into());
+                params.push(
+                    file.storage_class
+                        .clone()
+                        .map(|s| s.into())
+                        .unwrap_or(turso::Value::Null),
+                );
+                params.push(
+                    file.etag
+                        .clone()
+                        .map(|s| s.into())
+                        .unwrap_or(turso::Value::Null),
+                );
             }
 
             conn.execute(&sql, params).await?;
+            let keys: Vec<&str> = chunk.iter().map(|f| f.key.as_str()).collect();
+            insert_key_terms(&conn, "cached_file_terms_staging", bucket, account_id, &keys)
+                .await?;
         }
 
         Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
@@ -860,24 +1277,47 @@ pub async fn store_file_batch(
 }
 
 /// Step 3: atomically swap staging data into the live table.
-/// In one transaction: delete old live data → copy staging → clean staging → update meta.
-/// If this fails, old data is still intact in cached_files.
-pub async fn finish_sync(bucket: &str, account_id: &str, file_count: usize) -> DbResult<()> {
+/// In one transaction: delete keys no longer listed → upsert staging → clean
+/// staging → update meta. If this fails, old data is still intact in cached_files.
+pub async fn finish_sync(scope: &CacheScope, file_count: usize) -> DbResult<()> {
+    let (bucket, account_id) = scope.columns();
     let now = chrono::Utc::now().timestamp();
     let conn = get_connection()?.lock().await;
     conn.execute("BEGIN TRANSACTION", ()).await?;
 
     let tx_result = async {
-        // Remove old live data for this bucket
+        // Remove live rows the listing no longer has
         conn.execute(
-            "DELETE FROM cached_files WHERE bucket = ?1 AND account_id = ?2",
+            "DELETE FROM cached_files
+             WHERE bucket = ?1 AND account_id = ?2
+               AND key NOT IN (SELECT key FROM cached_files_staging
+                               WHERE bucket = ?1 AND account_id = ?2)",
             turso::params![bucket, account_id],
         )
         .await?;
 
         // Copy staging → live
         conn.execute(
-            "INSERT INTO cached_files SELECT * FROM cached_files_staging WHERE bucket = ?1 AND account_id = ?2",
+            &format!(
+                "INSERT INTO cached_files (bucket, account_id, key, parent_path, name, size, last_modified, synced_at, storage_class, etag)
+                 SELECT bucket, account_id, key, parent_path, name, size, last_modified, synced_at, storage_class, etag
+                 FROM cached_files_staging WHERE bucket = ?1 AND account_id = ?2
+                 {KEEP_FETCHED_META}"
+            ),
+            turso::params![bucket, account_id],
+        )
+        .await?;
+
+        // Same for the search terms
+        conn.execute(
+            "DELETE FROM cached_file_terms WHERE bucket = ?1 AND account_id = ?2",
+            turso::params![bucket, account_id],
+        )
+        .await?;
+        conn.execute(
+            "INSERT INTO cached_file_terms (bucket, account_id, key, term, in_name)
+             SELECT bucket, account_id, key, term, in_name
+             FROM cached_file_terms_staging WHERE bucket = ?1 AND account_id = ?2",
             turso::params![bucket, account_id],
         )
         .await?;
@@ -888,6 +1328,11 @@ pub async fn finish_sync(bucket: &str, account_id: &str, file_count: usize) -> D
             turso::params![bucket, account_id],
         )
         .await?;
+        conn.execute(
+            "DELETE FROM cached_file_terms_staging WHERE bucket = ?1 AND account_id = ?2",
+            turso::params![bucket, account_id],
+        )
+        .await?;
 
         // Update sync metadata
         conn.execute(
@@ -897,6 +1342,7 @@ pub async fn finish_sync(bucket: &str, account_id: &str, file_count: usize) -> D
             turso::params![bucket, account_id, now, file_count as i32],
         )
         .await?;
+        record_storage_snapshot(&conn, bucket, account_id, now).await?;
 
         Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
     }
@@ -911,77 +1357,248 @@ pub async fn finish_sync(bucket: &str, account_id: &str, file_count: usize) -> D
     Ok(())
 }
 
-/// Upsert files for a specific prefix (lazy sync).
-/// Does NOT delete files outside this prefix -- only touches files within it.
-/// Removes stale files in this prefix that are no longer present in the fresh listing.
-pub async fn upsert_prefix_files(
+// ============ Delta Sync ============
+//
+// Alternative to step 3 for buckets that were synced before: the fresh listing
+// in staging is diffed against the live table by key, size, ETag and
+// last_modified, and only the differences are written.
+
+/// A file added, changed or removed by a delta sync.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeltaFile {
+    pub key: String,
+    /// Size in the fresh listing; 0 for removed files.
+    pub size: i64,
+    /// Size in the cache before the sync; 0 for added files.
+    pub previous_size: i64,
+    pub last_modified: String,
+}
+
+/// Differences a delta sync applied to the cache.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncDelta {
+    pub added: Vec<DeltaFile>,
+    pub updated: Vec<DeltaFile>,
+    pub removed: Vec<DeltaFile>,
+}
+
+async fn query_delta_files(
+    conn: &turso::Connection,
+    sql: &str,
     bucket: &str,
     account_id: &str,
-    prefix: &str,
-    files: &[CachedFile],
+) -> DbResult<Vec<DeltaFile>> {
+    let mut rows = conn.query(sql, turso::params![bucket, account_id]).await?;
+    let mut files = Vec::new();
+    while let Some(row) = rows.next().await? {
+        files.push(DeltaFile {
+            key: row.get(0)?,
+            size: row.get(1)?,
+            previous_size: row.get(2)?,
+            last_modified: row.get(3)?,
+        });
+    }
+    Ok(files)
+}
+
+/// Run `sql` once per chunk of `keys`; `{}` in `sql` becomes the chunk's
+/// placeholder list, after bucket (?1) and account_id (?2).
+async fn execute_for_keys(
+    conn: &turso::Connection,
+    sql: &str,
+    bucket: &str,
+    account_id: &str,
+    keys: &[&str],
 ) -> DbResult<()> {
+    const CHUNK_SIZE: usize = 500;
+    for chunk in keys.chunks(CHUNK_SIZE) {
+        let placeholders: Vec<String> = (0..chunk.len()).map(|i| format!("?{}", i + 3)).collect();
+        let mut params: Vec<turso::Value> = Vec::with_capacity(chunk.len() + 2);
+        params.push(bucket.to_string().into());
+        params.push(account_id.to_string().into());
+        for key in chunk {
+            params.push(key.to_string().into());
+        }
+        conn.execute(&sql.replace("{}", &placeholders.join(", ")), params)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Step 3 (delta): apply only the differences between staging and the live
+/// table, then clean staging and update sync metadata. Unchanged rows are not
+/// touched. The directory tree is left to the caller to patch.
+pub async fn finish_delta_sync(scope: &CacheScope, file_count: usize) -> DbResult<SyncDelta> {
+    let (bucket, account_id) = scope.columns();
     let now = chrono::Utc::now().timestamp();
     let conn = get_connection()?.lock().await;
+
+    let delta = SyncDelta {
+        added: query_delta_files(
+            &conn,
+            "SELECT s.key, s.size, 0, s.last_modified
+             FROM cached_files_staging s
+             LEFT JOIN cached_files c
+               ON c.bucket = s.bucket AND c.account_id = s.account_id AND c.key = s.key
+             WHERE s.bucket = ?1 AND s.account_id = ?2 AND c.key IS NULL",
+            bucket,
+            account_id,
+        )
+        .await?,
+        // ETags only count when both sides have one: rows cached before
+        // ETags were recorded would otherwise all look changed.
+        updated: query_delta_files(
+            &conn,
+            "SELECT s.key, s.size, c.size, s.last_modified
+             FROM cached_files_staging s
+             JOIN cached_files c
+               ON c.bucket = s.bucket AND c.account_id = s.account_id AND c.key = s.key
+             WHERE s.bucket = ?1 AND s.account_id = ?2
+               AND (s.size <> c.size OR s.last_modified <> c.last_modified
+                    OR (s.etag IS NOT NULL AND c.etag IS NOT NULL AND s.etag <> c.etag))",
+            bucket,
+            account_id,
+        )
+        .await?,
+        removed: query_delta_files(
+            &conn,
+            "SELECT c.key, 0, c.size, c.last_modified
+             FROM cached_files c
+             LEFT JOIN cached_files_staging s
+               ON s.bucket = c.bucket AND s.account_id = c.account_id AND s.key = c.key
+             WHERE c.bucket = ?1 AND c.account_id = ?2 AND s.key IS NULL",
+            bucket,
+            account_id,
+        )
+        .await?,
+    };
+
+    // Unchanged rows still missing an ETag pick it up from the listing
+    let mut backfill: Vec<String> = Vec::new();
+    {
+        let mut rows = conn
+            .query(
+                "SELECT s.key
+                 FROM cached_files_staging s
+                 JOIN cached_files c
+                   ON c.bucket = s.bucket AND c.account_id = s.account_id AND c.key = s.key
+                 WHERE s.bucket = ?1 AND s.account_id = ?2
+                   AND c.etag IS NULL AND s.etag IS NOT NULL",
+                turso::params![bucket, account_id],
+            )
+            .await?;
+        while let Some(row) = rows.next().await? {
+            backfill.push(row.get(0)?);
+        }
+    }
+
+    let removed_keys: Vec<&str> = delta.removed.iter().map(|f| f.key.as_str()).collect();
+    let added_keys: Vec<&str> = delta.added.iter().map(|f| f.key.as_str()).collect();
+    let copied_keys: Vec<&str> = delta
+        .added
+        .iter()
+        .chain(&delta.updated)
+        .map(|f| f.key.as_str())
+        .chain(backfill.iter().map(String::as_str))
+        .collect();
+
     conn.execute("BEGIN TRANSACTION", ()).await?;
 
     let tx_result = async {
-        // Delete existing files in this prefix (exact parent_path match)
+        execute_for_keys(
+            &conn,
+            "DELETE FROM cached_files WHERE bucket = ?1 AND account_id = ?2 AND key IN ({})",
+            bucket,
+            account_id,
+            &removed_keys,
+        )
+        .await?;
+
+        // Backfilled rows are unchanged objects and keep their fetched metadata
+        execute_for_keys(
+            &conn,
+            &format!(
+                "INSERT INTO cached_files (bucket, account_id, key, parent_path, name, size, last_modified, synced_at, storage_class, etag)
+                 SELECT bucket, account_id, key, parent_path, name, size, last_modified, synced_at, storage_class, etag
+                 FROM cached_files_staging WHERE bucket = ?1 AND account_id = ?2 AND key IN ({{}})
+                 {KEEP_FETCHED_META}"
+            ),
+            bucket,
+            account_id,
+            &copied_keys,
+        )
+        .await?;
+
+        // Terms only depend on the key, so updated rows keep theirs
+        execute_for_keys(
+            &conn,
+            "DELETE FROM cached_file_terms WHERE bucket = ?1 AND account_id = ?2 AND key IN ({})",
+            bucket,
+            account_id,
+            &removed_keys,
+        )
+        .await?;
+        execute_for_keys(
+            &conn,
+            "INSERT OR REPLACE INTO cached_file_terms (bucket, account_id, key, term, in_name)
+             SELECT bucket, account_id, key, term, in_name
+             FROM cached_file_terms_staging WHERE bucket = ?1 AND account_id = ?2 AND key IN ({})",
+            bucket,
+            account_id,
+            &added_keys,
+        )
+        .await?;
+
         conn.execute(
-            "DELETE FROM cached_files WHERE bucket = ?1 AND account_id = ?2 AND parent_path = ?3",
-            turso::params![bucket, account_id, prefix],
+            "DELETE FROM cached_files_staging WHERE bucket = ?1 AND account_id = ?2",
+            turso::params![bucket, account_id],
+        )
+        .await?;
+        conn.execute(
+            "DELETE FROM cached_file_terms_staging WHERE bucket = ?1 AND account_id = ?2",
+            turso::params![bucket, account_id],
         )
         .await?;
 
-        // Batch insert fresh files
-        const BATCH_SIZE: usize = 1000;
-        for chunk in files.chunks(BATCH_SIZE) {
-            if chunk.is_empty() {
-                continue;
-            }
+        conn.execute(
+            "INSERT INTO sync_meta (bucket, account_id, last_sync, file_count)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (bucket, account_id) DO UPDATE SET last_sync = ?3, file_count = ?4",
+            turso::params![bucket, account_id, now, file_count as i32],
+        )
+        .await?;
+        record_storage_snapshot(&conn, bucket, account_id, now).await?;
 
-            let placeholders: Vec<String> = chunk
-                .iter()
-                .enumerate()
-                .map(|(i, _)| {
-                    let base = i * 8;
-                    format!(
-                        "(?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{})",
-                        base + 1,
-                        base + 2,
-                        base + 3,
-                        base + 4,
-                        base + 5,
-                        base + 6,
-                        base + 7,
-                        base + 8
-                    )
-                })
-                .collect();
+        Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+    }
+    .await;
 
-            let sql = format!(
-                "INSERT OR REPLACE INTO cached_files (bucket, account_id, key, parent_path, name, size, last_modified, synced_at) VALUES {}",
-                placeholders.join(", ")
-            );
+    if let Err(err) = tx_result {
+        let _ = conn.execute("ROLLBACK", ()).await;
+        return Err(err);
+    }
 
-            let mut params: Vec<turso::Value> = Vec::with_capacity(chunk.len() * 8);
-            for file in chunk {
-                let (parent_path, name) = if file.name.is_empty() {
-                    parse_key(&file.key)
-                } else {
-                    (file.parent_path.clone(), file.name.clone())
-                };
-                params.push(bucket.to_string().into());
-                params.push(account_id.to_string().into());
-                params.push(file.key.clone().into());
-                params.push(parent_path.into());
-                params.push(name.into());
-                params.push(file.size.into());
-                params.push(file.last_modified.clone().into());
-                params.push(now.into());
-            }
+    conn.execute("COMMIT", ()).await?;
+    Ok(delta)
+}
 
-            conn.execute(&sql, params).await?;
-        }
+/// Upsert files for a specific prefix (lazy sync).
+/// Does NOT delete files outside this prefix -- only touches files within it.
+/// Removes stale files in this prefix that are no longer present in the fresh listing.
+pub async fn upsert_prefix_files(
+    scope: &CacheScope,
+    prefix: &str,
+    files: &[CachedFile],
+) -> DbResult<()> {
+    let (bucket, account_id) = scope.columns();
+    let now = chrono::Utc::now().timestamp();
+    let conn = get_connection()?.lock().await;
+    conn.execute("BEGIN TRANSACTION", ()).await?;
+
+    let tx_result = async {
+        // Files in this prefix (exact parent_path match)
+        let cached = cached_keys(&conn, bucket, account_id, Some(prefix)).await?;
+        write_listing(&conn, bucket, account_id, &cached, files, Some(now)).await?;
 
         Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
     }
@@ -995,3 +1612,169 @@ pub async fn upsert_prefix_files(
     conn.execute("COMMIT", ()).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listed(key: &str, size: i64, etag: &str) -> CachedFile {
+        let (parent_path, name) = parse_key(key);
+        CachedFile {
+            bucket: "delta-test".to_string(),
+            account_id: "acct".to_string(),
+            key: key.to_string(),
+            parent_path,
+            name,
+            size,
+            last_modified: "2024-01-01T00:00:00Z".to_string(),
+            synced_at: 0,
+            storage_class: None,
+            etag: Some(etag.to_string()),
+            content_type: None,
+            checksum: None,
+        }
+    }
+
+    async fn full_sync(scope: &CacheScope, files: &[CachedFile]) {
+        begin_sync(scope).await.unwrap();
+        store_file_batch(scope, files).await.unwrap();
+        finish_sync(scope, files.len()).await.unwrap();
+    }
+
+    #[test]
+    fn fetched_metadata_survives_syncs_of_unchanged_objects() {
+        crate::db::run_db_test(async {
+            let scope = CacheScope::r2("acct", "meta-test");
+
+            full_sync(
+                &scope,
+                &[
+                    listed("same.txt", 1, "\"a\""),
+                    listed("edited.txt", 2, "\"b\""),
+                ],
+            )
+            .await;
+            let meta = CachedObjectMeta {
+                content_type: Some("text/plain".to_string()),
+                checksum: Some("CRC32:AAAAAA==".to_string()),
+                ..Default::default()
+            };
+            for key in ["same.txt", "edited.txt"] {
+                assert!(set_cached_object_meta(&scope, key, &meta).await.unwrap());
+            }
+            assert!(!set_cached_object_meta(&scope, "missing.txt", &meta)
+                .await
+                .unwrap());
+
+            full_sync(
+                &scope,
+                &[
+                    listed("same.txt", 1, "\"a\""),
+                    listed("edited.txt", 3, "\"c\""),
+                ],
+            )
+            .await;
+            let same = get_cached_file(&scope, "same.txt").await.unwrap().unwrap();
+            assert_eq!(same.content_type.as_deref(), Some("text/plain"));
+            assert_eq!(same.checksum.as_deref(), Some("CRC32:AAAAAA=="));
+            let edited = get_cached_file(&scope, "edited.txt")
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(edited.content_type, None);
+            assert_eq!(edited.checksum, None);
+
+            let by_etag = find_cached_files_by_etag(&scope, "a").await.unwrap();
+            assert_eq!(by_etag.len(), 1);
+            assert_eq!(by_etag[0].key, "same.txt");
+
+            let usage = get_storage_class_usage(&scope).await.unwrap();
+            assert_eq!(usage.len(), 1);
+            assert_eq!((usage[0].file_count, usage[0].total_size), (2, 4));
+        });
+    }
+
+    #[test]
+    fn delta_sync_applies_only_differences() {
+        crate::db::run_db_test(async {
+            let scope = CacheScope::r2("acct", "delta-test");
+
+            let first = vec![
+                listed("a.txt", 1, "\"a\""),
+                listed("docs/b.txt", 2, "\"b\""),
+                listed("docs/c.txt", 3, "\"c\""),
+            ];
+            begin_sync(&scope).await.unwrap();
+            store_file_batch(&scope, &first).await.unwrap();
+            finish_sync(&scope, first.len()).await.unwrap();
+            assert!(has_full_sync(&scope).await.unwrap());
+
+            let second = vec![
+                listed("a.txt", 1, "\"a\""),
+                listed("docs/b.txt", 2, "\"b2\""),
+                listed("new/d.txt", 4, "\"d\""),
+            ];
+            begin_sync(&scope).await.unwrap();
+            store_file_batch(&scope, &second).await.unwrap();
+            let delta = finish_delta_sync(&scope, second.len()).await.unwrap();
+
+            let keys = |files: &[DeltaFile]| -> Vec<String> {
+                files.iter().map(|f| f.key.clone()).collect()
+            };
+            assert_eq!(keys(&delta.added), vec!["new/d.txt"]);
+            assert_eq!(keys(&delta.updated), vec!["docs/b.txt"]);
+            assert_eq!(keys(&delta.removed), vec!["docs/c.txt"]);
+            assert_eq!(delta.removed[0].previous_size, 3);
+
+            let cached = get_all_cached_files(&scope).await.unwrap();
+            let cached: Vec<(String, Option<String>)> =
+                cached.into_iter().map(|f| (f.key, f.etag)).collect();
+            assert_eq!(
+                cached,
+                vec![
+                    ("a.txt".to_string(), Some("\"a\"".to_string())),
+                    ("docs/b.txt".to_string(), Some("\"b2\"".to_string())),
+                    ("new/d.txt".to_string(), Some("\"d\"".to_string())),
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn scopes_with_the_same_bucket_and_account_are_separate() {
+        crate::db::run_db_test(async {
+            let r2 = CacheScope::r2("acct", "scope-test");
+            let minio = CacheScope::new(
+                "minio",
+                Some("http"),
+                Some("nas:9000"),
+                "acct",
+                "scope-test",
+            );
+
+            for (scope, key) in [(&r2, "from-r2.txt"), (&minio, "from-minio.txt")] {
+                begin_sync(scope).await.unwrap();
+                store_file_batch(scope, &[listed(key, 1, "\"e\"")])
+                    .await
+                    .unwrap();
+                finish_sync(scope, 1).await.unwrap();
+            }
+
+            let keys = |files: Vec<CachedFile>| -> Vec<String> {
+                files.into_iter().map(|f| f.key).collect()
+            };
+            assert_eq!(
+                keys(get_all_cached_files(&r2).await.unwrap()),
+                vec!["from-r2.txt"]
+            );
+            assert_eq!(
+                keys(get_all_cached_files(&minio).await.unwrap()),
+                vec!["from-minio.txt"]
+            );
+
+            clear_file_cache(&r2).await.unwrap();
+            assert!(get_all_cached_files(&r2).await.unwrap().is_empty());
+            assert_eq!(get_all_cached_files(&minio).await.unwrap().len(), 1);
+        });
+    }
+}
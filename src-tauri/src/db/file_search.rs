@@ -0,0 +1,697 @@
+//! Search over the cached file index.
+//!
+//! Keys are split into lowercase terms stored in `cached_file_terms`, so text
+//! search is an indexed range lookup per term instead of a `LIKE` scan. Terms
+//! only depend on the key and are written in the same transaction as the
+//! `cached_files` rows they belong to; syncs stage them next to the listing
+//! and swap them in with it. Structured filters (prefix, extension, size,
+//! date, storage class) are plain SQL; glob and regex patterns are matched on
+//! the key in Rust.
+
+use super::file_cache::{row_to_cached_file, CACHED_FILE_COLUMNS};
+use super::{get_connection, CacheScope, CachedFile, DbResult};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL, Engine};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+pub fn get_table_sql() -> &'static str {
+    "
+    -- Terms of cached keys; in_name is 1 when the term occurs in the file name
+    CREATE TABLE IF NOT EXISTS cached_file_terms (
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        key TEXT NOT NULL,
+        term TEXT NOT NULL,
+        in_name INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY (bucket, account_id, key, term)
+    );
+
+    -- Terms of a sync in flight, swapped in with cached_files_staging
+    CREATE TABLE IF NOT EXISTS cached_file_terms_staging (
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        key TEXT NOT NULL,
+        term TEXT NOT NULL,
+        in_name INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY (bucket, account_id, key, term)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_cached_file_terms_term ON cached_file_terms(bucket, account_id, term);
+    "
+}
+
+// ============ Query Types ============
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchSort {
+    #[default]
+    Key,
+    Name,
+    Size,
+    LastModified,
+}
+
+impl SearchSort {
+    fn column(self) -> &'static str {
+        match self {
+            SearchSort::Key => "key",
+            SearchSort::Name => "name",
+            SearchSort::Size => "size",
+            SearchSort::LastModified => "last_modified",
+        }
+    }
+}
+
+/// Pattern matched against the whole key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "pattern", rename_all = "lowercase")]
+pub enum KeyPattern {
+    /// `*` and `?` stay within one path segment, `**` crosses segments,
+    /// `[...]` is a character class.
+    Glob(String),
+    /// Unanchored regular expression.
+    Regex(String),
+}
+
+/// A search over one bucket's cached files. Every given filter must match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SearchQuery {
+    /// Whitespace-separated words; each must prefix a term of the key.
+    pub text: Option<String>,
+    /// Match `text` against terms of the file name only.
+    pub name_only: bool,
+    pub pattern: Option<KeyPattern>,
+    pub case_sensitive: bool,
+    pub prefix: Option<String>,
+    /// Extensions without the dot, e.g. `jpg` or `tar.gz`.
+    pub extensions: Vec<String>,
+    pub min_size: Option<i64>,
+    pub max_size: Option<i64>,
+    /// RFC 3339 timestamps, inclusive.
+    pub modified_after: Option<String>,
+    pub modified_before: Option<String>,
+    /// `STANDARD` also matches files whose listing reported no class.
+    pub storage_classes: Vec<String>,
+    pub sort: SearchSort,
+    pub descending: bool,
+    /// Page size; 0 means `DEFAULT_PAGE_SIZE`.
+    pub limit: usize,
+    /// `next_cursor` of the previous page.
+    pub cursor: Option<String>,
+}
+
+pub const DEFAULT_PAGE_SIZE: usize = 200;
+pub const MAX_PAGE_SIZE: usize = 5000;
+
+#[derive(Debug, Clone)]
+pub struct SearchPage {
+    pub files: Vec<CachedFile>,
+    /// Matches across all pages.
+    pub total_count: i64,
+    /// Present when more results follow.
+    pub next_cursor: Option<String>,
+}
+
+// ============ Terms ============
+
+/// Lowercase alphanumeric runs of `text`.
+fn terms(text: &str) -> Vec<String> {
+    let mut terms: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect();
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+/// Terms of a key with whether each occurs in the file name.
+fn key_terms(key: &str) -> Vec<(String, bool)> {
+    let name = key.rsplit('/').next().unwrap_or(key);
+    let name_terms = terms(name);
+    terms(key)
+        .into_iter()
+        .map(|term| {
+            let in_name = name_terms.binary_search(&term).is_ok();
+            (term, in_name)
+        })
+        .collect()
+}
+
+/// Upper bound for a range scan over terms starting with `prefix`.
+fn prefix_upper_bound(prefix: &str) -> String {
+    format!("{}\u{10FFFF}", prefix)
+}
+
+/// Write the terms of `keys` to `table`: `cached_file_terms`, or its staging
+/// copy during a sync. Meant to run in the transaction writing the rows.
+pub(super) async fn insert_key_terms(
+    conn: &turso::Connection,
+    table: &str,
+    bucket: &str,
+    account_id: &str,
+    keys: &[&str],
+) -> DbResult<()> {
+    // Term rows per statement, three parameters each
+    const BATCH_ROWS: usize = 1000;
+
+    let sql = |placeholders: &[String]| {
+        format!(
+            "INSERT OR REPLACE INTO {table} (bucket, account_id, key, term, in_name) VALUES {}",
+            placeholders.join(", ")
+        )
+    };
+    let mut placeholders: Vec<String> = Vec::new();
+    let mut params: Vec<turso::Value> =
+        vec![bucket.to_string().into(), account_id.to_string().into()];
+    for key in keys {
+        for (term, in_name) in key_terms(key) {
+            let base = params.len();
+            placeholders.push(format!(
+                "(?1, ?2, ?{}, ?{}, ?{})",
+                base + 1,
+                base + 2,
+                base + 3
+            ));
+            params.push(key.to_string().into());
+            params.push(term.into());
+            params.push((in_name as i64).into());
+
+            if placeholders.len() == BATCH_ROWS {
+                let batch = std::mem::replace(
+                    &mut params,
+                    vec![bucket.to_string().into(), account_id.to_string().into()],
+                );
+                conn.execute(&sql(&placeholders), batch).await?;
+                placeholders.clear();
+            }
+        }
+    }
+    if !placeholders.is_empty() {
+        conn.execute(&sql(&placeholders), params).await?;
+    }
+    Ok(())
+}
+
+/// Drop the search terms of a bucket, e.g. when its cache is cleared.
+pub(crate) async fn clear_search_terms(
+    conn: &turso::Connection,
+    bucket: &str,
+    account_id: &str,
+) -> DbResult<()> {
+    conn.execute(
+        "DELETE FROM cached_file_terms WHERE bucket = ?1 AND account_id = ?2",
+        turso::params![bucket, account_id],
+    )
+    .await?;
+    Ok(())
+}
+
+// ============ Patterns ============
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut out = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                out.push_str(".*");
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '[' => {
+                let mut class = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == ']' && !class.is_empty() {
+                        closed = true;
+                        break;
+                    }
+                    class.push(c);
+                }
+                if closed {
+                    let class = match class.strip_prefix('!') {
+                        Some(rest) => format!("^{}", rest),
+                        None => class,
+                    };
+                    out.push('[');
+                    out.push_str(&class.replace('\\', "\\\\"));
+                    out.push(']');
+                } else {
+                    out.push_str(&regex::escape(&format!("[{}", class)));
+                }
+            }
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    out.push('$');
+    out
+}
+
+fn compile_pattern(pattern: &KeyPattern, case_sensitive: bool) -> Result<Regex, String> {
+    let source = match pattern {
+        KeyPattern::Glob(glob) => glob_to_regex(glob),
+        KeyPattern::Regex(regex) => regex.clone(),
+    };
+    RegexBuilder::new(&source)
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid pattern: {}", e))
+}
+
+// ============ Cursor ============
+
+/// Position after the last row of a page: its sort value and key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Cursor {
+    sort: SearchSort,
+    descending: bool,
+    value: CursorValue,
+    key: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum CursorValue {
+    Int(i64),
+    Text(String),
+}
+
+impl Cursor {
+    fn after(file: &CachedFile, sort: SearchSort, descending: bool) -> Self {
+        let value = match sort {
+            SearchSort::Key => CursorValue::Text(file.key.clone()),
+            SearchSort::Name => CursorValue::Text(file.name.clone()),
+            SearchSort::Size => CursorValue::Int(file.size),
+            SearchSort::LastModified => CursorValue::Text(file.last_modified.clone()),
+        };
+        Cursor {
+            sort,
+            descending,
+            value,
+            key: file.key.clone(),
+        }
+    }
+
+    fn encode(&self) -> String {
+        BASE64_URL.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    fn decode(cursor: &str) -> Result<Self, String> {
+        BASE64_URL
+            .decode(cursor)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| "Invalid search cursor".to_string())
+    }
+}
+
+impl From<CursorValue> for turso::Value {
+    fn from(value: CursorValue) -> Self {
+        match value {
+            CursorValue::Int(v) => v.into(),
+            CursorValue::Text(v) => v.into(),
+        }
+    }
+}
+
+// ============ Search ============
+
+/// Push a parameter and return its placeholder.
+fn bind(params: &mut Vec<turso::Value>, value: turso::Value) -> String {
+    params.push(value);
+    format!("?{}", params.len())
+}
+
+/// WHERE clause and parameters for everything but the cursor and pattern.
+/// ?1 and ?2 are bucket and account_id.
+fn build_filters(
+    query: &SearchQuery,
+    bucket: &str,
+    account_id: &str,
+) -> (Vec<String>, Vec<turso::Value>) {
+    let mut conditions = vec!["bucket = ?1".to_string(), "account_id = ?2".to_string()];
+    let mut params: Vec<turso::Value> =
+        vec![bucket.to_string().into(), account_id.to_string().into()];
+
+    let words = query.text.as_deref().map(terms).unwrap_or_default();
+    for word in words {
+        let low = bind(&mut params, word.clone().into());
+        let high = bind(&mut params, prefix_upper_bound(&word).into());
+        let name_filter = if query.name_only {
+            " AND in_name = 1"
+        } else {
+            ""
+        };
+        conditions.push(format!(
+            "key IN (SELECT key FROM cached_file_terms
+                     WHERE bucket = ?1 AND account_id = ?2 AND term >= {} AND term < {}{})",
+            low, high, name_filter
+        ));
+    }
+
+    if let Some(prefix) = query.prefix.as_deref().filter(|p| !p.is_empty()) {
+        let p = bind(&mut params, prefix.to_string().into());
+        conditions.push(format!("key >= {p} AND SUBSTR(key, 1, LENGTH({p})) = {p}"));
+    }
+
+    let extensions: Vec<String> = query
+        .extensions
+        .iter()
+        .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+        .filter(|e| !e.is_empty())
+        .collect();
+    if !extensions.is_empty() {
+        let checks: Vec<String> = extensions
+            .into_iter()
+            .map(|ext| {
+                let suffix = bind(&mut params, format!(".{}", ext).into());
+                format!("SUBSTR(LOWER(name), -LENGTH({s})) = {s}", s = suffix)
+            })
+            .collect();
+        conditions.push(format!("({})", checks.join(" OR ")));
+    }
+
+    if let Some(min) = query.min_size {
+        let p = bind(&mut params, min.into());
+        conditions.push(format!("size >= {}", p));
+    }
+    if let Some(max) = query.max_size {
+        let p = bind(&mut params, max.into());
+        conditions.push(format!("size <= {}", p));
+    }
+    if let Some(after) = query.modified_after.as_deref() {
+        let p = bind(&mut params, after.to_string().into());
+        conditions.push(format!("last_modified >= {}", p));
+    }
+    if let Some(before) = query.modified_before.as_deref() {
+        let p = bind(&mut params, before.to_string().into());
+        conditions.push(format!("last_modified <= {}", p));
+    }
+
+    if !query.storage_classes.is_empty() {
+        let mut checks: Vec<String> = Vec::new();
+        for class in &query.storage_classes {
+            let class = class.trim().to_uppercase();
+            if class == "STANDARD" {
+                checks.push("storage_class IS NULL".to_string());
+            }
+            let p = bind(&mut params, class.into());
+            checks.push(format!("storage_class = {}", p));
+        }
+        conditions.push(format!("({})", checks.join(" OR ")));
+    }
+
+    (conditions, params)
+}
+
+/// Search one bucket's cached files. Results are ordered by `query.sort`, then
+/// key, and paged with an opaque cursor.
+pub async fn search_files(scope: &CacheScope, query: &SearchQuery) -> DbResult<SearchPage> {
+    let (bucket, account_id) = scope.columns();
+    let limit = match query.limit {
+        0 => DEFAULT_PAGE_SIZE,
+        n => n.min(MAX_PAGE_SIZE),
+    };
+    let pattern = query
+        .pattern
+        .as_ref()
+        .map(|p| compile_pattern(p, query.case_sensitive))
+        .transpose()?;
+    let cursor = query.cursor.as_deref().map(Cursor::decode).transpose()?;
+    if let Some(cursor) = &cursor {
+        if cursor.sort != query.sort || cursor.descending != query.descending {
+            return Err("Search cursor belongs to a different sort order".into());
+        }
+    }
+
+    let conn = get_connection()?.lock().await;
+
+    let (conditions, params) = build_filters(query, bucket, account_id);
+    let where_clause = conditions.join(" AND ");
+
+    // Total across pages; with a pattern every SQL match has to be checked
+    let total_count = match &pattern {
+        None => {
+            let mut rows = conn
+                .query(
+                    &format!("SELECT COUNT(*) FROM cached_files WHERE {}", where_clause),
+                    params.clone(),
+                )
+                .await?;
+            match rows.next().await? {
+                Some(row) => row.get(0)?,
+                None => 0,
+            }
+        }
+        Some(regex) => {
+            let mut rows = conn
+                .query(
+                    &format!("SELECT key FROM cached_files WHERE {}", where_clause),
+                    params.clone(),
+                )
+                .await?;
+            let mut count = 0i64;
+            while let Some(row) = rows.next().await? {
+                let key: String = row.get(0)?;
+                if regex.is_match(&key) {
+                    count += 1;
+                }
+            }
+            count
+        }
+    };
+
+    let column = query.sort.column();
+    let (cmp, order) = if query.descending {
+        ("<", "DESC")
+    } else {
+        (">", "ASC")
+    };
+    let mut page_conditions = conditions;
+    let mut page_params = params;
+    if let Some(cursor) = cursor {
+        page_params.push(cursor.value.into());
+        let value = page_params.len();
+        page_params.push(cursor.key.into());
+        let key = page_params.len();
+        if query.sort == SearchSort::Key {
+            page_conditions.push(format!("key {} ?{}", cmp, key));
+        } else {
+            page_conditions.push(format!(
+                "({column} {cmp} ?{value} OR ({column} = ?{value} AND key {cmp} ?{key}))"
+            ));
+        }
+    }
+    let order_by = if query.sort == SearchSort::Key {
+        format!("key {}", order)
+    } else {
+        format!("{} {}, key {}", column, order, order)
+    };
+    // One extra row tells whether another page follows
+    let sql_limit = if pattern.is_none() {
+        format!(" LIMIT {}", limit + 1)
+    } else {
+        String::new()
+    };
+    let sql = format!(
+        "SELECT {CACHED_FILE_COLUMNS} FROM cached_files WHERE {} ORDER BY {}{}",
+        page_conditions.join(" AND "),
+        order_by,
+        sql_limit
+    );
+
+    let mut rows = conn.query(&sql, page_params).await?;
+    let mut files = Vec::with_capacity(limit + 1);
+    while let Some(row) = rows.next().await? {
+        let file = row_to_cached_file(&row)?;
+        if pattern
+            .as_ref()
+            .is_some_and(|regex| !regex.is_match(&file.key))
+        {
+            continue;
+        }
+        files.push(file);
+        if files.len() > limit {
+            break;
+        }
+    }
+
+    let next_cursor = if files.len() > limit {
+        files.truncate(limit);
+        files
+            .last()
+            .map(|last| Cursor::after(last, query.sort, query.descending).encode())
+    } else {
+        None
+    };
+
+    Ok(SearchPage {
+        files,
+        total_count,
+        next_cursor,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::file_cache::{begin_sync, finish_sync, parse_key, store_file_batch};
+
+    #[test]
+    fn key_terms_mark_name_terms() {
+        assert_eq!(
+            key_terms("Photos/2024/IMG_1234.jpg"),
+            vec![
+                ("1234".to_string(), true),
+                ("2024".to_string(), false),
+                ("img".to_string(), true),
+                ("jpg".to_string(), true),
+                ("photos".to_string(), false),
+            ]
+        );
+        assert!(key_terms("--/").is_empty());
+    }
+
+    #[test]
+    fn globs_stay_within_segments() {
+        let single = compile_pattern(&KeyPattern::Glob("docs/*.PDF".into()), false).unwrap();
+        assert!(single.is_match("docs/report.pdf"));
+        assert!(!single.is_match("docs/2024/report.pdf"));
+
+        let deep = compile_pattern(&KeyPattern::Glob("docs/**.pdf".into()), true).unwrap();
+        assert!(deep.is_match("docs/2024/report.pdf"));
+        assert!(!deep.is_match("docs/2024/report.PDF"));
+
+        let class = compile_pattern(&KeyPattern::Glob("v[!0-4].txt".into()), false).unwrap();
+        assert!(class.is_match("v7.txt"));
+        assert!(!class.is_match("v3.txt"));
+
+        assert!(compile_pattern(&KeyPattern::Regex("(".into()), false).is_err());
+    }
+
+    fn listed(key: &str, size: i64, last_modified: &str, class: Option<&str>) -> CachedFile {
+        let (parent_path, name) = parse_key(key);
+        CachedFile {
+            bucket: "search-test".to_string(),
+            account_id: "acct".to_string(),
+            key: key.to_string(),
+            parent_path,
+            name,
+            size,
+            last_modified: last_modified.to_string(),
+            synced_at: 0,
+            storage_class: class.map(str::to_string),
+            etag: None,
+            content_type: None,
+            checksum: None,
+        }
+    }
+
+    fn keys(page: &SearchPage) -> Vec<&str> {
+        page.files.iter().map(|f| f.key.as_str()).collect()
+    }
+
+    #[test]
+    fn searches_filter_sort_and_page() {
+        crate::db::run_db_test(async {
+            let scope = CacheScope::r2("acct", "search-test");
+            let files = vec![
+                listed("photos/2024/beach.jpg", 300, "2024-07-01T00:00:00Z", None),
+                listed("photos/2023/beach.png", 200, "2023-07-01T00:00:00Z", None),
+                listed(
+                    "reports/beach-survey.pdf",
+                    100,
+                    "2024-01-01T00:00:00Z",
+                    Some("GLACIER"),
+                ),
+                listed("reports/q1.pdf", 50, "2024-03-01T00:00:00Z", None),
+            ];
+            begin_sync(&scope).await.unwrap();
+            store_file_batch(&scope, &files).await.unwrap();
+            finish_sync(&scope, files.len()).await.unwrap();
+
+            let text = SearchQuery {
+                text: Some("bea".into()),
+                sort: SearchSort::Size,
+                descending: true,
+                limit: 2,
+                ..Default::default()
+            };
+            let first = search_files(&scope, &text).await.unwrap();
+            assert_eq!(first.total_count, 3);
+            assert_eq!(
+                keys(&first),
+                vec!["photos/2024/beach.jpg", "photos/2023/beach.png"]
+            );
+            let second = search_files(
+                &scope,
+                &SearchQuery {
+                    cursor: first.next_cursor.clone(),
+                    ..text.clone()
+                },
+            )
+            .await
+            .unwrap();
+            assert_eq!(keys(&second), vec!["reports/beach-survey.pdf"]);
+            assert!(second.next_cursor.is_none());
+
+            let structured = search_files(
+                &scope,
+                &SearchQuery {
+                    prefix: Some("photos/".into()),
+                    extensions: vec!["JPG".into(), ".png".into()],
+                    modified_after: Some("2024-01-01T00:00:00Z".into()),
+                    storage_classes: vec!["standard".into()],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            assert_eq!(keys(&structured), vec!["photos/2024/beach.jpg"]);
+
+            let names = search_files(
+                &scope,
+                &SearchQuery {
+                    text: Some("reports".into()),
+                    name_only: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            assert_eq!(names.total_count, 0);
+
+            let pattern = search_files(
+                &scope,
+                &SearchQuery {
+                    pattern: Some(KeyPattern::Regex(r"q\d\.pdf$".into())),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            assert_eq!(keys(&pattern), vec!["reports/q1.pdf"]);
+
+            // Terms follow the cache
+            crate::db::file_cache::delete_cached_file(&scope, "reports/beach-survey.pdf")
+                .await
+                .unwrap();
+            let after_delete = search_files(&scope, &text).await.unwrap();
+            assert_eq!(after_delete.total_count, 2);
+            crate::db::file_cache::move_cached_file(&scope, "reports/q1.pdf", "archive/q2.pdf")
+                .await
+                .unwrap();
+            let archive = SearchQuery {
+                text: Some("archive".into()),
+                ..Default::default()
+            };
+            let moved = search_files(&scope, &archive).await.unwrap();
+            assert_eq!(keys(&moved), vec!["archive/q2.pdf"]);
+        });
+    }
+}
@@ -0,0 +1,1387 @@
+//! Versioned schema migrations.
+//!
+//! `schema_version` records every migration applied to a database. Pending
+//! migrations run in order at startup, each in its own transaction, so tables
+//! and the rows in them survive restarts and upgrades. New schema changes are
+//! added as a new migration at the end of `MIGRATIONS`; applied migrations are
+//! never edited.
+
+use super::{cache_scope, file_search, DbResult};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use turso::Connection;
+
+const SCHEMA_VERSION_SQL: &str = "
+    CREATE TABLE IF NOT EXISTS schema_version (
+        version INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        applied_at INTEGER NOT NULL
+    );
+";
+
+/// Schema of the first tracked release. Frozen: columns added since then
+/// belong to later migrations.
+const INITIAL_SCHEMA_SQL: &str = "
+    CREATE TABLE IF NOT EXISTS upload_sessions (
+        id TEXT PRIMARY KEY,
+        file_path TEXT NOT NULL,
+        file_size INTEGER NOT NULL,
+        file_mtime INTEGER NOT NULL,
+        object_key TEXT NOT NULL,
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        upload_id TEXT,
+        content_type TEXT NOT NULL,
+        total_parts INTEGER NOT NULL,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL,
+        status TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS completed_parts (
+        session_id TEXT NOT NULL,
+        part_number INTEGER NOT NULL,
+        etag TEXT NOT NULL,
+        PRIMARY KEY (session_id, part_number),
+        FOREIGN KEY (session_id) REFERENCES upload_sessions(id)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_sessions_status ON upload_sessions(status);
+    CREATE INDEX IF NOT EXISTS idx_sessions_file ON upload_sessions(file_path, file_size, file_mtime);
+
+    CREATE TABLE IF NOT EXISTS accounts (
+        id TEXT PRIMARY KEY,
+        name TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS tokens (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        account_id TEXT NOT NULL REFERENCES accounts(id),
+        name TEXT,
+        api_token TEXT NOT NULL,
+        access_key_id TEXT NOT NULL,
+        secret_access_key TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_tokens_account ON tokens(account_id);
+
+    CREATE TABLE IF NOT EXISTS buckets (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        token_id INTEGER NOT NULL REFERENCES tokens(id),
+        name TEXT NOT NULL,
+        public_domain TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL,
+        UNIQUE(token_id, name)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_buckets_token ON buckets(token_id);
+    CREATE INDEX IF NOT EXISTS idx_buckets_unique ON buckets(token_id, name);
+
+    CREATE TABLE IF NOT EXISTS aws_accounts (
+        id TEXT PRIMARY KEY,
+        name TEXT,
+        access_key_id TEXT NOT NULL,
+        secret_access_key TEXT NOT NULL,
+        region TEXT NOT NULL,
+        endpoint_scheme TEXT NOT NULL,
+        endpoint_host TEXT,
+        force_path_style INTEGER NOT NULL DEFAULT 0,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_aws_accounts_created ON aws_accounts(created_at);
+
+    CREATE TABLE IF NOT EXISTS aws_buckets (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        account_id TEXT NOT NULL REFERENCES aws_accounts(id),
+        name TEXT NOT NULL,
+        public_domain_scheme TEXT,
+        public_domain_host TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL,
+        UNIQUE(account_id, name)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_aws_buckets_account ON aws_buckets(account_id);
+    CREATE INDEX IF NOT EXISTS idx_aws_buckets_unique ON aws_buckets(account_id, name);
+
+    CREATE TABLE IF NOT EXISTS minio_accounts (
+        id TEXT PRIMARY KEY,
+        name TEXT,
+        access_key_id TEXT NOT NULL,
+        secret_access_key TEXT NOT NULL,
+        endpoint_scheme TEXT NOT NULL,
+        endpoint_host TEXT NOT NULL,
+        force_path_style INTEGER NOT NULL DEFAULT 1,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_minio_accounts_created ON minio_accounts(created_at);
+
+    CREATE TABLE IF NOT EXISTS minio_buckets (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        account_id TEXT NOT NULL REFERENCES minio_accounts(id),
+        name TEXT NOT NULL,
+        public_domain_scheme TEXT,
+        public_domain_host TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL,
+        UNIQUE(account_id, name)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_minio_buckets_account ON minio_buckets(account_id);
+    CREATE INDEX IF NOT EXISTS idx_minio_buckets_unique ON minio_buckets(account_id, name);
+
+    CREATE TABLE IF NOT EXISTS rustfs_accounts (
+        id TEXT PRIMARY KEY,
+        name TEXT,
+        access_key_id TEXT NOT NULL,
+        secret_access_key TEXT NOT NULL,
+        endpoint_scheme TEXT NOT NULL,
+        endpoint_host TEXT NOT NULL,
+        force_path_style INTEGER NOT NULL DEFAULT 1,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_rustfs_accounts_created ON rustfs_accounts(created_at);
+
+    CREATE TABLE IF NOT EXISTS rustfs_buckets (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        account_id TEXT NOT NULL REFERENCES rustfs_accounts(id),
+        name TEXT NOT NULL,
+        public_domain_scheme TEXT,
+        public_domain_host TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL,
+        UNIQUE(account_id, name)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_rustfs_buckets_account ON rustfs_buckets(account_id);
+    CREATE INDEX IF NOT EXISTS idx_rustfs_buckets_unique ON rustfs_buckets(account_id, name);
+
+    CREATE TABLE IF NOT EXISTS app_state (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS cached_files (
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        key TEXT NOT NULL,
+        parent_path TEXT NOT NULL,
+        name TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        last_modified TEXT NOT NULL,
+        synced_at INTEGER NOT NULL,
+        PRIMARY KEY (bucket, account_id, key)
+    );
+
+    CREATE TABLE IF NOT EXISTS directory_tree (
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        path TEXT NOT NULL,
+        parent_path TEXT NOT NULL,
+        file_count INTEGER NOT NULL,
+        total_file_count INTEGER NOT NULL,
+        size INTEGER NOT NULL,
+        total_size INTEGER NOT NULL,
+        last_modified TEXT,
+        last_updated INTEGER NOT NULL,
+        PRIMARY KEY (bucket, account_id, path)
+    );
+
+    CREATE TABLE IF NOT EXISTS sync_meta (
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        last_sync INTEGER NOT NULL,
+        file_count INTEGER NOT NULL,
+        PRIMARY KEY (bucket, account_id)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_cached_files_parent ON cached_files(bucket, account_id, parent_path);
+    CREATE INDEX IF NOT EXISTS idx_directory_tree_parent ON directory_tree(bucket, account_id, parent_path);
+
+    CREATE TABLE IF NOT EXISTS download_sessions (
+        id TEXT PRIMARY KEY,
+        object_key TEXT NOT NULL,
+        file_name TEXT NOT NULL,
+        file_size INTEGER NOT NULL,
+        downloaded_bytes INTEGER NOT NULL DEFAULT 0,
+        local_path TEXT NOT NULL,
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        status TEXT NOT NULL DEFAULT 'pending',
+        error TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_download_sessions_status ON download_sessions(status);
+    CREATE INDEX IF NOT EXISTS idx_download_sessions_bucket ON download_sessions(bucket, account_id);
+
+    CREATE TABLE IF NOT EXISTS move_sessions (
+        id TEXT PRIMARY KEY,
+        source_key TEXT NOT NULL,
+        dest_key TEXT NOT NULL,
+        source_bucket TEXT NOT NULL,
+        source_account_id TEXT NOT NULL,
+        source_provider TEXT NOT NULL,
+        dest_bucket TEXT NOT NULL,
+        dest_account_id TEXT NOT NULL,
+        dest_provider TEXT NOT NULL,
+        delete_original INTEGER NOT NULL DEFAULT 1,
+        file_size INTEGER,
+        progress INTEGER NOT NULL DEFAULT 0,
+        status TEXT NOT NULL DEFAULT 'pending',
+        error TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_move_sessions_status ON move_sessions(status);
+    CREATE INDEX IF NOT EXISTS idx_move_sessions_source ON move_sessions(source_bucket, source_account_id);
+
+    CREATE TABLE IF NOT EXISTS move_upload_sessions (
+        task_id TEXT PRIMARY KEY,
+        upload_id TEXT NOT NULL,
+        part_size INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS move_upload_parts (
+        task_id TEXT NOT NULL,
+        part_number INTEGER NOT NULL,
+        etag TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        PRIMARY KEY (task_id, part_number)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_move_upload_parts_task ON move_upload_parts(task_id);
+
+    CREATE TABLE IF NOT EXISTS prefix_sync_times (
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        prefix TEXT NOT NULL,
+        last_synced_at INTEGER NOT NULL,
+        file_count INTEGER NOT NULL DEFAULT 0,
+        folder_count INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY (bucket, account_id, prefix)
+    );
+    CREATE INDEX IF NOT EXISTS idx_prefix_sync ON prefix_sync_times(bucket, account_id, prefix);
+
+    CREATE TABLE IF NOT EXISTS shared_links (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        provider TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        bucket TEXT NOT NULL,
+        key TEXT NOT NULL,
+        method TEXT NOT NULL DEFAULT 'GET',
+        url TEXT NOT NULL,
+        signing_key_id TEXT NOT NULL,
+        note TEXT,
+        created_at INTEGER NOT NULL,
+        expires_at INTEGER NOT NULL,
+        revoked_at INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS idx_shared_links_expiry ON shared_links(expires_at);
+    CREATE INDEX IF NOT EXISTS idx_shared_links_signer ON shared_links(provider, signing_key_id);
+
+    CREATE TABLE IF NOT EXISTS bucket_encryption (
+        provider TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        bucket TEXT NOT NULL,
+        mode TEXT NOT NULL DEFAULT 'none',
+        kms_key_id TEXT,
+        bucket_key_enabled INTEGER NOT NULL DEFAULT 0,
+        customer_key_ref TEXT,
+        updated_at INTEGER NOT NULL,
+        PRIMARY KEY (provider, account_id, bucket)
+    );
+
+    CREATE TABLE IF NOT EXISTS client_encryption (
+        provider TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        bucket TEXT NOT NULL,
+        key_source TEXT NOT NULL,
+        passphrase_ref TEXT,
+        key_file_path TEXT,
+        updated_at INTEGER NOT NULL,
+        PRIMARY KEY (provider, account_id, bucket)
+    );
+
+    CREATE TABLE IF NOT EXISTS object_restores (
+        account_id TEXT NOT NULL,
+        bucket TEXT NOT NULL,
+        object_key TEXT NOT NULL,
+        tier TEXT NOT NULL,
+        days INTEGER,
+        status TEXT NOT NULL,
+        expiry_date TEXT,
+        requested_at INTEGER NOT NULL,
+        checked_at INTEGER NOT NULL,
+        PRIMARY KEY (account_id, bucket, object_key)
+    );
+";
+
+const CACHED_FILE_SEARCH_SQL: &str = "
+    CREATE TABLE IF NOT EXISTS cached_file_terms (
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        key TEXT NOT NULL,
+        term TEXT NOT NULL,
+        in_name INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY (bucket, account_id, key, term)
+    );
+
+    CREATE TABLE IF NOT EXISTS search_index_state (
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        fingerprint TEXT NOT NULL,
+        indexed_at INTEGER NOT NULL,
+        PRIMARY KEY (bucket, account_id)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_cached_file_terms_term ON cached_file_terms(bucket, account_id, term);
+";
+
+const OBJECT_HASHES_SQL: &str = "
+    CREATE TABLE IF NOT EXISTS object_hashes (
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        key TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        etag TEXT NOT NULL,
+        sha256 TEXT NOT NULL,
+        hashed_at INTEGER NOT NULL,
+        PRIMARY KEY (bucket, account_id, key)
+    );
+";
+
+const STORAGE_HISTORY_SQL: &str = "
+    CREATE TABLE IF NOT EXISTS storage_history (
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        day INTEGER NOT NULL,
+        storage_class TEXT NOT NULL,
+        file_count INTEGER NOT NULL,
+        total_size INTEGER NOT NULL,
+        recorded_at INTEGER NOT NULL,
+        PRIMARY KEY (bucket, account_id, day, storage_class)
+    );
+";
+
+const SCOPED_TRANSFER_INDEXES_SQL: &str = "
+    DROP INDEX IF EXISTS idx_download_sessions_bucket;
+    CREATE INDEX IF NOT EXISTS idx_download_sessions_bucket ON download_sessions(bucket, scope_key);
+    DROP INDEX IF EXISTS idx_move_sessions_source;
+    CREATE INDEX IF NOT EXISTS idx_move_sessions_source ON move_sessions(source_bucket, source_scope_key);
+";
+
+const SCOPED_OBJECT_RESTORES_SQL: &str = "
+    CREATE TABLE IF NOT EXISTS object_restores (
+        account_id TEXT NOT NULL,
+        scope_key TEXT NOT NULL,
+        bucket TEXT NOT NULL,
+        object_key TEXT NOT NULL,
+        tier TEXT NOT NULL,
+        days INTEGER,
+        status TEXT NOT NULL,
+        expiry_date TEXT,
+        requested_at INTEGER NOT NULL,
+        checked_at INTEGER NOT NULL,
+        PRIMARY KEY (scope_key, bucket, object_key)
+    );
+";
+
+const SYNC_STAGING_SQL: &str = "
+    DROP TABLE IF EXISTS cached_files_staging;
+    CREATE TABLE cached_files_staging (
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        key TEXT NOT NULL,
+        parent_path TEXT NOT NULL,
+        name TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        last_modified TEXT NOT NULL,
+        synced_at INTEGER NOT NULL,
+        storage_class TEXT,
+        etag TEXT,
+        PRIMARY KEY (bucket, account_id, key)
+    );
+
+    DROP TABLE IF EXISTS cached_file_terms_staging;
+    CREATE TABLE cached_file_terms_staging (
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        key TEXT NOT NULL,
+        term TEXT NOT NULL,
+        in_name INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY (bucket, account_id, key, term)
+    );
+";
+
+type StepFuture<'a> = Pin<Box<dyn Future<Output = DbResult<()>> + 'a>>;
+
+/// One change to the schema. Steps are literal so applied migrations never
+/// change; the table modules' `get_table_sql` describe the resulting schema.
+enum Step {
+    /// Statements run as a batch.
+    Sql(&'static str),
+    /// Add a column. Databases created before migrations were tracked may
+    /// already have it from ad-hoc upgrades, and are left as they are.
+    AddColumn {
+        table: &'static str,
+        column: &'static str,
+        definition: &'static str,
+    },
+    /// Data changes that need more than SQL.
+    Run(fn(&Connection) -> StepFuture<'_>),
+}
+
+struct Migration {
+    version: i64,
+    name: &'static str,
+    steps: &'static [Step],
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        steps: &[Step::Sql(INITIAL_SCHEMA_SQL)],
+    },
+    Migration {
+        version: 2,
+        name: "bucket_public_access",
+        steps: &[
+            Step::AddColumn {
+                table: "buckets",
+                column: "public_domain_scheme",
+                definition: "TEXT",
+            },
+            Step::Sql(
+                "UPDATE buckets SET public_domain_scheme = 'https' WHERE public_domain_scheme IS NULL",
+            ),
+            Step::AddColumn {
+                table: "buckets",
+                column: "is_public",
+                definition: "INTEGER NOT NULL DEFAULT 0",
+            },
+            Step::AddColumn {
+                table: "buckets",
+                column: "public_path_prefix",
+                definition: "TEXT",
+            },
+            // Buckets that already carried a public domain were served
+            // publicly before the flag existed.
+            Step::Sql(
+                "UPDATE buckets SET is_public = 1 WHERE public_domain IS NOT NULL AND public_domain <> ''",
+            ),
+            Step::AddColumn {
+                table: "aws_buckets",
+                column: "is_public",
+                definition: "INTEGER NOT NULL DEFAULT 0",
+            },
+            Step::AddColumn {
+                table: "aws_buckets",
+                column: "public_path_prefix",
+                definition: "TEXT",
+            },
+            Step::AddColumn {
+                table: "minio_buckets",
+                column: "is_public",
+                definition: "INTEGER NOT NULL DEFAULT 0",
+            },
+            Step::AddColumn {
+                table: "minio_buckets",
+                column: "public_path_prefix",
+                definition: "TEXT",
+            },
+            Step::AddColumn {
+                table: "rustfs_buckets",
+                column: "is_public",
+                definition: "INTEGER NOT NULL DEFAULT 0",
+            },
+            Step::AddColumn {
+                table: "rustfs_buckets",
+                column: "public_path_prefix",
+                definition: "TEXT",
+            },
+            Step::Sql(
+                "
+                UPDATE aws_buckets SET is_public = 1 WHERE public_domain_host IS NOT NULL AND public_domain_host <> '';
+                UPDATE minio_buckets SET is_public = 1 WHERE public_domain_host IS NOT NULL AND public_domain_host <> '';
+                UPDATE rustfs_buckets SET is_public = 1 WHERE public_domain_host IS NOT NULL AND public_domain_host <> '';
+                ",
+            ),
+        ],
+    },
+    Migration {
+        version: 3,
+        name: "aws_credential_source",
+        steps: &[
+            Step::AddColumn {
+                table: "aws_accounts",
+                column: "credential_source",
+                definition: "TEXT NOT NULL DEFAULT 'static'",
+            },
+            Step::AddColumn {
+                table: "aws_accounts",
+                column: "profile_name",
+                definition: "TEXT",
+            },
+        ],
+    },
+    Migration {
+        version: 4,
+        name: "credential_capabilities",
+        steps: &[
+            Step::AddColumn {
+                table: "tokens",
+                column: "capabilities",
+                definition: "TEXT",
+            },
+            Step::AddColumn {
+                table: "aws_accounts",
+                column: "capabilities",
+                definition: "TEXT",
+            },
+            Step::AddColumn {
+                table: "minio_accounts",
+                column: "capabilities",
+                definition: "TEXT",
+            },
+            Step::AddColumn {
+                table: "rustfs_accounts",
+                column: "capabilities",
+                definition: "TEXT",
+            },
+        ],
+    },
+    Migration {
+        version: 5,
+        name: "cached_storage_class",
+        steps: &[Step::AddColumn {
+            table: "cached_files",
+            column: "storage_class",
+            definition: "TEXT",
+        }],
+    },
+    Migration {
+        version: 6,
+        name: "cached_etag",
+        steps: &[Step::AddColumn {
+            table: "cached_files",
+            column: "etag",
+            definition: "TEXT",
+        }],
+    },
+    Migration {
+        version: 7,
+        name: "cache_scope",
+        steps: &[
+            // Rebuilt by the next sync
+            Step::Sql("DROP TABLE IF EXISTS cached_files_staging"),
+            Step::Run(scope_cached_rows_step),
+        ],
+    },
+    Migration {
+        version: 8,
+        name: "cached_object_metadata",
+        steps: &[
+            Step::AddColumn {
+                table: "cached_files",
+                column: "content_type",
+                definition: "TEXT",
+            },
+            Step::AddColumn {
+                table: "cached_files",
+                column: "checksum",
+                definition: "TEXT",
+            },
+            Step::Sql(
+                "CREATE INDEX IF NOT EXISTS idx_cached_files_etag ON cached_files(bucket, account_id, etag)",
+            ),
+        ],
+    },
+    Migration {
+        version: 9,
+        name: "cached_file_search",
+        // Terms of keys cached before this are written by `search_terms_at_write_time`
+        steps: &[Step::Sql(CACHED_FILE_SEARCH_SQL)],
+    },
+    Migration {
+        version: 10,
+        name: "object_hashes",
+        steps: &[Step::Sql(OBJECT_HASHES_SQL)],
+    },
+    Migration {
+        version: 11,
+        name: "storage_history",
+        steps: &[Step::Sql(STORAGE_HISTORY_SQL)],
+    },
+    Migration {
+        version: 12,
+        name: "scoped_transfers",
+        steps: &[
+            Step::AddColumn {
+                table: "download_sessions",
+                column: "scope_key",
+                definition: "TEXT NOT NULL DEFAULT ''",
+            },
+            Step::AddColumn {
+                table: "move_sessions",
+                column: "source_scope_key",
+                definition: "TEXT NOT NULL DEFAULT ''",
+            },
+            Step::Run(scope_transfer_rows_step),
+            Step::Sql(SCOPED_TRANSFER_INDEXES_SQL),
+        ],
+    },
+    Migration {
+        version: 13,
+        name: "search_terms_at_write_time",
+        steps: &[
+            Step::Run(index_cached_keys_step),
+            Step::Sql("DROP TABLE IF EXISTS search_index_state"),
+        ],
+    },
+    Migration {
+        version: 14,
+        name: "sync_staging_tables",
+        // Staging only holds syncs in flight, which restart from scratch
+        steps: &[Step::Sql(SYNC_STAGING_SQL)],
+    },
+];
+
+/// Tables whose `account_id` column holds a cache scope key.
+const SCOPED_CACHE_TABLES: &[&str] = &[
+    "cached_files",
+    "directory_tree",
+    "sync_meta",
+    "prefix_sync_times",
+];
+
+fn scope_cached_rows_step(conn: &Connection) -> StepFuture<'_> {
+    Box::pin(scope_cached_rows(conn))
+}
+
+fn scope_transfer_rows_step(conn: &Connection) -> StepFuture<'_> {
+    Box::pin(scope_transfer_rows(conn))
+}
+
+fn index_cached_keys_step(conn: &Connection) -> StepFuture<'_> {
+    Box::pin(index_cached_keys(conn))
+}
+
+/// Scope keys of the stored accounts by account id, with their provider.
+type AccountScopes = HashMap<String, Vec<(&'static str, String)>>;
+
+async fn account_scopes(conn: &Connection) -> DbResult<AccountScopes> {
+    let mut scopes: AccountScopes = HashMap::new();
+    let sources = [
+        ("r2", "SELECT id, NULL, NULL FROM accounts"),
+        (
+            "aws",
+            "SELECT id, endpoint_scheme, endpoint_host FROM aws_accounts",
+        ),
+        (
+            "minio",
+            "SELECT id, endpoint_scheme, endpoint_host FROM minio_accounts",
+        ),
+        (
+            "rustfs",
+            "SELECT id, endpoint_scheme, endpoint_host FROM rustfs_accounts",
+        ),
+    ];
+    for (provider, sql) in sources {
+        let mut rows = conn.query(sql, ()).await?;
+        while let Some(row) = rows.next().await? {
+            let id: String = row.get(0)?;
+            let scheme: Option<String> = row.get(1)?;
+            let host: Option<String> = row.get(2)?;
+            let key = cache_scope::scope_key(provider, scheme.as_deref(), host.as_deref(), &id);
+            scopes.entry(id).or_default().push((provider, key));
+        }
+    }
+    Ok(scopes)
+}
+
+/// Scope key of rows that only name an account. Ids no provider knows
+/// predate multi-provider support and go to the R2 scope; `None` for ids
+/// used by more than one provider.
+fn account_scope(scopes: &AccountScopes, account_id: &str) -> Option<String> {
+    match scopes.get(account_id).map(Vec::as_slice) {
+        None => Some(cache_scope::scope_key("r2", None, None, account_id)),
+        Some([(_, key)]) => Some(key.clone()),
+        Some(_) => None,
+    }
+}
+
+/// Scope key of rows that name their provider too. Accounts that were
+/// removed since keep the provider's own endpoint.
+fn provider_account_scope(scopes: &AccountScopes, provider: &str, account_id: &str) -> String {
+    scopes
+        .get(account_id)
+        .and_then(|keys| keys.iter().find(|(p, _)| *p == provider))
+        .map(|(_, key)| key.clone())
+        .unwrap_or_else(|| cache_scope::scope_key(provider, None, None, account_id))
+}
+
+async fn distinct_values(conn: &Connection, sql: &str) -> DbResult<Vec<String>> {
+    let mut values = Vec::new();
+    let mut rows = conn.query(sql, ()).await?;
+    while let Some(row) = rows.next().await? {
+        values.push(row.get(0)?);
+    }
+    Ok(values)
+}
+
+/// Rewrite cache rows keyed by a bare account id to the account's scope key.
+/// Rows of ids used by more than one provider can't be attributed and are
+/// dropped; the next sync lists them again.
+async fn scope_cached_rows(conn: &Connection) -> DbResult<()> {
+    let scopes = account_scopes(conn).await?;
+
+    for table in SCOPED_CACHE_TABLES {
+        let account_ids =
+            distinct_values(conn, &format!("SELECT DISTINCT account_id FROM {table}")).await?;
+
+        for account_id in account_ids {
+            match account_scope(&scopes, &account_id) {
+                Some(key) => {
+                    conn.execute(
+                        &format!("UPDATE {table} SET account_id = ?1 WHERE account_id = ?2"),
+                        turso::params![key.as_str(), account_id.as_str()],
+                    )
+                    .await?;
+                }
+                None => {
+                    conn.execute(
+                        &format!("DELETE FROM {table} WHERE account_id = ?1"),
+                        turso::params![account_id.as_str()],
+                    )
+                    .await?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+struct LegacyRestore {
+    account_id: String,
+    bucket: String,
+    object_key: String,
+    tier: String,
+    days: Option<i64>,
+    status: String,
+    expiry_date: Option<String>,
+    requested_at: i64,
+    checked_at: i64,
+}
+
+/// Fill in the scope keys of download, move and restore rows, which keep
+/// their bare account id for display. Downloads of ids used by more than one
+/// provider are dropped like cache rows. Restores are AWS only; their table
+/// is rebuilt since the scope key joins its primary key.
+async fn scope_transfer_rows(conn: &Connection) -> DbResult<()> {
+    let scopes = account_scopes(conn).await?;
+
+    let account_ids =
+        distinct_values(conn, "SELECT DISTINCT account_id FROM download_sessions").await?;
+    for account_id in account_ids {
+        match account_scope(&scopes, &account_id) {
+            Some(key) => {
+                conn.execute(
+                    "UPDATE download_sessions SET scope_key = ?1 WHERE account_id = ?2",
+                    turso::params![key.as_str(), account_id.as_str()],
+                )
+                .await?;
+            }
+            None => {
+                conn.execute(
+                    "DELETE FROM download_sessions WHERE account_id = ?1",
+                    turso::params![account_id.as_str()],
+                )
+                .await?;
+            }
+        }
+    }
+
+    let mut sources: Vec<(String, String)> = Vec::new();
+    let mut rows = conn
+        .query(
+            "SELECT DISTINCT source_provider, source_account_id FROM move_sessions",
+            (),
+        )
+        .await?;
+    while let Some(row) = rows.next().await? {
+        sources.push((row.get(0)?, row.get(1)?));
+    }
+    drop(rows);
+    for (provider, account_id) in sources {
+        let key = provider_account_scope(&scopes, &provider, &account_id);
+        conn.execute(
+            "UPDATE move_sessions SET source_scope_key = ?1
+             WHERE source_provider = ?2 AND source_account_id = ?3",
+            turso::params![key.as_str(), provider.as_str(), account_id.as_str()],
+        )
+        .await?;
+    }
+
+    let mut restores = Vec::new();
+    let mut rows = conn
+        .query(
+            "SELECT account_id, bucket, object_key, tier, days, status, expiry_date,
+                requested_at, checked_at
+             FROM object_restores",
+            (),
+        )
+        .await?;
+    while let Some(row) = rows.next().await? {
+        restores.push(LegacyRestore {
+            account_id: row.get(0)?,
+            bucket: row.get(1)?,
+            object_key: row.get(2)?,
+            tier: row.get(3)?,
+            days: row.get(4)?,
+            status: row.get(5)?,
+            expiry_date: row.get(6)?,
+            requested_at: row.get(7)?,
+            checked_at: row.get(8)?,
+        });
+    }
+    drop(rows);
+
+    conn.execute_batch("DROP TABLE object_restores").await?;
+    conn.execute_batch(SCOPED_OBJECT_RESTORES_SQL).await?;
+    for restore in restores {
+        let key = provider_account_scope(&scopes, "aws", &restore.account_id);
+        conn.execute(
+            "INSERT INTO object_restores
+             (account_id, scope_key, bucket, object_key, tier, days, status, expiry_date,
+              requested_at, checked_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            turso::params![
+                restore.account_id,
+                key,
+                restore.bucket,
+                restore.object_key,
+                restore.tier,
+                restore.days,
+                restore.status,
+                restore.expiry_date,
+                restore.requested_at,
+                restore.checked_at
+            ],
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Bring the search terms in line with the cache once, now that they are
+/// written with the rows: buckets never searched while terms were built
+/// lazily have none, and other buckets may hold terms of deleted keys.
+async fn index_cached_keys(conn: &Connection) -> DbResult<()> {
+    // Placeholder the lazy build gave keys without any term
+    conn.execute("DELETE FROM cached_file_terms WHERE term = ''", ())
+        .await?;
+
+    let mut scopes: Vec<(String, String)> = Vec::new();
+    {
+        let mut rows = conn
+            .query(
+                "SELECT DISTINCT bucket, account_id FROM cached_file_terms
+                 UNION
+                 SELECT DISTINCT bucket, account_id FROM cached_files",
+                (),
+            )
+            .await?;
+        while let Some(row) = rows.next().await? {
+            scopes.push((row.get(0)?, row.get(1)?));
+        }
+    }
+
+    for (bucket, account_id) in &scopes {
+        conn.execute(
+            "DELETE FROM cached_file_terms
+             WHERE bucket = ?1 AND account_id = ?2
+               AND key NOT IN (SELECT key FROM cached_files WHERE bucket = ?1 AND account_id = ?2)",
+            turso::params![bucket.as_str(), account_id.as_str()],
+        )
+        .await?;
+
+        let mut missing: Vec<String> = Vec::new();
+        let mut rows = conn
+            .query(
+                "SELECT c.key FROM cached_files c
+                 WHERE c.bucket = ?1 AND c.account_id = ?2
+                   AND NOT EXISTS (
+                     SELECT 1 FROM cached_file_terms t
+                     WHERE t.bucket = c.bucket AND t.account_id = c.account_id AND t.key = c.key
+                   )",
+                turso::params![bucket.as_str(), account_id.as_str()],
+            )
+            .await?;
+        while let Some(row) = rows.next().await? {
+            missing.push(row.get(0)?);
+        }
+        drop(rows);
+
+        let keys: Vec<&str> = missing.iter().map(String::as_str).collect();
+        file_search::insert_key_terms(conn, "cached_file_terms", bucket, account_id, &keys).await?;
+    }
+    Ok(())
+}
+
+fn latest_version() -> i64 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+async fn current_version(conn: &Connection) -> DbResult<i64> {
+    let mut rows = conn
+        .query("SELECT COALESCE(MAX(version), 0) FROM schema_version", ())
+        .await?;
+    match rows.next().await? {
+        Some(row) => Ok(row.get(0)?),
+        None => Ok(0),
+    }
+}
+
+async fn has_column(conn: &Connection, table: &str, column: &str) -> DbResult<bool> {
+    let mut rows = conn
+        .query(&format!("PRAGMA table_info({table})"), ())
+        .await?;
+    while let Some(row) = rows.next().await? {
+        let name: String = row.get(1)?;
+        if name.eq_ignore_ascii_case(column) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+async fn apply(conn: &Connection, migration: &Migration) -> DbResult<()> {
+    for step in migration.steps {
+        match step {
+            Step::Sql(sql) => conn.execute_batch(sql).await?,
+            Step::AddColumn {
+                table,
+                column,
+                definition,
+            } => {
+                if !has_column(conn, table, column).await? {
+                    conn.execute(
+                        &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+                        (),
+                    )
+                    .await?;
+                }
+            }
+            Step::Run(run) => run(conn).await?,
+        }
+    }
+    conn.execute(
+        "INSERT INTO schema_version (version, name, applied_at) VALUES (?1, ?2, ?3)",
+        turso::params![
+            migration.version,
+            migration.name,
+            chrono::Utc::now().timestamp()
+        ],
+    )
+    .await?;
+    Ok(())
+}
+
+/// Bring the schema up to date and return its version.
+pub(crate) async fn run_migrations(conn: &Connection) -> DbResult<i64> {
+    conn.execute_batch(SCHEMA_VERSION_SQL).await?;
+    let current = current_version(conn).await?;
+    if current > latest_version() {
+        log::warn!(
+            "Database schema version {} is newer than this app ({}); continuing without migrating",
+            current,
+            latest_version()
+        );
+        return Ok(current);
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        conn.execute("BEGIN", ()).await?;
+        if let Err(e) = apply(conn, migration).await {
+            let _ = conn.execute("ROLLBACK", ()).await;
+            return Err(format!(
+                "Schema migration {} ({}) failed: {}",
+                migration.version, migration.name, e
+            )
+            .into());
+        }
+        conn.execute("COMMIT", ()).await?;
+        log::info!(
+            "Applied schema migration {} ({})",
+            migration.version,
+            migration.name
+        );
+    }
+    Ok(latest_version())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        accounts, app_state, aws_accounts, aws_buckets, bucket_encryption, buckets,
+        client_encryption, downloads, file_cache, file_search, minio_accounts, minio_buckets,
+        move_sessions, object_hashes, object_restores, prefix_sync, rustfs_accounts,
+        rustfs_buckets, sessions, shared_links, storage_analytics, tokens,
+    };
+    use turso::Builder;
+
+    /// Current table definitions, which the migrations must arrive at.
+    const TABLE_SQL: &[fn() -> &'static str] = &[
+        sessions::get_table_sql,
+        accounts::get_table_sql,
+        tokens::get_table_sql,
+        buckets::get_table_sql,
+        aws_accounts::get_table_sql,
+        aws_buckets::get_table_sql,
+        minio_accounts::get_table_sql,
+        minio_buckets::get_table_sql,
+        rustfs_accounts::get_table_sql,
+        rustfs_buckets::get_table_sql,
+        app_state::get_table_sql,
+        file_cache::get_table_sql,
+        downloads::get_table_sql,
+        move_sessions::get_table_sql,
+        prefix_sync::get_table_sql,
+        shared_links::get_table_sql,
+        bucket_encryption::get_table_sql,
+        client_encryption::get_table_sql,
+        object_restores::get_table_sql,
+        file_search::get_table_sql,
+        object_hashes::get_table_sql,
+        storage_analytics::get_table_sql,
+    ];
+
+    fn with_memory_db<F, Fut>(test: F)
+    where
+        F: FnOnce(Connection) -> Fut,
+        Fut: std::future::Future<Output = ()>,
+    {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let db = Builder::new_local(":memory:").build().await.unwrap();
+            test(db.connect().unwrap()).await;
+        });
+    }
+
+    async fn count(conn: &Connection, sql: &str) -> i64 {
+        let mut rows = conn.query(sql, ()).await.unwrap();
+        rows.next().await.unwrap().unwrap().get(0).unwrap()
+    }
+
+    /// Tables with their columns, ignoring column order (added columns go last).
+    async fn schema(conn: &Connection) -> Vec<(String, Vec<String>)> {
+        let mut tables: Vec<String> = Vec::new();
+        let mut rows = conn
+            .query(
+                "SELECT name FROM sqlite_master
+                 WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name <> 'schema_version'
+                 ORDER BY name",
+                (),
+            )
+            .await
+            .unwrap();
+        while let Some(row) = rows.next().await.unwrap() {
+            tables.push(row.get(0).unwrap());
+        }
+        drop(rows);
+
+        let mut schema = Vec::new();
+        for table in tables {
+            let mut columns = Vec::new();
+            let mut rows = conn
+                .query(&format!("PRAGMA table_info({table})"), ())
+                .await
+                .unwrap();
+            while let Some(row) = rows.next().await.unwrap() {
+                let name: String = row.get(1).unwrap();
+                let kind: String = row.get(2).unwrap();
+                let not_null: i64 = row.get(3).unwrap();
+                let default: Option<String> = row.get(4).unwrap();
+                let pk: i64 = row.get(5).unwrap();
+                columns.push(format!("{name} {kind} {not_null} {default:?} {pk}"));
+            }
+            columns.sort();
+            schema.push((table, columns));
+        }
+        schema
+    }
+
+    #[test]
+    fn migrations_are_ordered() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, index as i64 + 1, "{}", migration.name);
+        }
+    }
+
+    #[test]
+    fn fresh_database_reaches_latest_version_once() {
+        with_memory_db(|conn| async move {
+            assert_eq!(run_migrations(&conn).await.unwrap(), latest_version());
+            assert_eq!(run_migrations(&conn).await.unwrap(), latest_version());
+            assert_eq!(
+                count(&conn, "SELECT COUNT(*) FROM schema_version").await,
+                MIGRATIONS.len() as i64
+            );
+            assert!(has_column(&conn, "cached_files", "storage_class")
+                .await
+                .unwrap());
+            assert!(has_column(&conn, "cached_files", "checksum").await.unwrap());
+        })
+    }
+
+    #[test]
+    fn migrations_arrive_at_the_table_definitions() {
+        with_memory_db(|conn| async move {
+            run_migrations(&conn).await.unwrap();
+
+            let db = Builder::new_local(":memory:").build().await.unwrap();
+            let reference = db.connect().unwrap();
+            for sql in TABLE_SQL {
+                reference.execute_batch(sql()).await.unwrap();
+            }
+
+            assert_eq!(schema(&conn).await, schema(&reference).await);
+        })
+    }
+
+    #[test]
+    fn cached_files_survive_restarts() {
+        with_memory_db(|conn| async move {
+            run_migrations(&conn).await.unwrap();
+            conn.execute(
+                "INSERT INTO cached_files (bucket, account_id, key, parent_path, name, size, last_modified, synced_at)
+                 VALUES ('b', 'a', 'docs/readme.md', 'docs/', 'readme.md', 10, '2024-01-01T00:00:00Z', 0)",
+                (),
+            )
+            .await
+            .unwrap();
+            run_migrations(&conn).await.unwrap();
+            assert_eq!(count(&conn, "SELECT COUNT(*) FROM cached_files").await, 1);
+        })
+    }
+
+    #[test]
+    fn cached_rows_move_into_their_provider_scope() {
+        with_memory_db(|conn| async move {
+            run_migrations(&conn).await.unwrap();
+            // Rows as written before cache scopes, then replay the migration
+            conn.execute_batch(
+                "
+                INSERT INTO accounts (id, name, created_at, updated_at) VALUES ('cf', 'r2', 0, 0);
+                INSERT INTO minio_accounts
+                    (id, access_key_id, secret_access_key, endpoint_scheme, endpoint_host, created_at, updated_at)
+                    VALUES ('nas', 'k', 's', 'http', 'nas:9000', 0, 0);
+                INSERT INTO accounts (id, name, created_at, updated_at) VALUES ('twin', 'r2', 0, 0);
+                INSERT INTO rustfs_accounts
+                    (id, access_key_id, secret_access_key, endpoint_scheme, endpoint_host, created_at, updated_at)
+                    VALUES ('twin', 'k', 's', 'http', 'nas:9100', 0, 0);
+                INSERT INTO sync_meta VALUES ('b', 'cf', 0, 1);
+                INSERT INTO sync_meta VALUES ('b', 'nas', 0, 1);
+                INSERT INTO sync_meta VALUES ('b', 'twin', 0, 1);
+                INSERT INTO sync_meta VALUES ('b', 'legacy', 0, 1);
+                DELETE FROM schema_version WHERE version >= 7;
+                ",
+            )
+            .await
+            .unwrap();
+
+            run_migrations(&conn).await.unwrap();
+            assert_eq!(
+                count(
+                    &conn,
+                    "SELECT COUNT(*) FROM sync_meta
+                     WHERE account_id IN ('r2||cf', 'minio|http://nas:9000|nas', 'r2||legacy')"
+                )
+                .await,
+                3
+            );
+            assert_eq!(count(&conn, "SELECT COUNT(*) FROM sync_meta").await, 3);
+        })
+    }
+
+    #[test]
+    fn transfer_rows_get_their_source_scope() {
+        with_memory_db(|conn| async move {
+            run_migrations(&conn).await.unwrap();
+            // Rows as written before transfers were scoped, then replay the migration
+            conn.execute_batch(
+                "
+                INSERT INTO minio_accounts
+                    (id, access_key_id, secret_access_key, endpoint_scheme, endpoint_host, created_at, updated_at)
+                    VALUES ('nas', 'k', 's', 'http', 'nas:9000', 0, 0);
+                INSERT INTO aws_accounts
+                    (id, access_key_id, secret_access_key, region, endpoint_scheme, created_at, updated_at)
+                    VALUES ('nas', 'k', 's', 'us-east-1', 'https', 0, 0);
+                INSERT INTO download_sessions
+                    (id, object_key, file_name, file_size, local_path, bucket, account_id, created_at, updated_at)
+                    VALUES ('d1', 'a.txt', 'a.txt', 1, '/tmp', 'b', 'cf', 0, 0);
+                INSERT INTO download_sessions
+                    (id, object_key, file_name, file_size, local_path, bucket, account_id, created_at, updated_at)
+                    VALUES ('d2', 'a.txt', 'a.txt', 1, '/tmp', 'b', 'nas', 0, 0);
+                INSERT INTO move_sessions
+                    (id, source_key, dest_key, source_bucket, source_account_id, source_provider,
+                     dest_bucket, dest_account_id, dest_provider, created_at, updated_at)
+                    VALUES ('m1', 'a', 'b', 'b', 'nas', 'minio', 'c', 'cf', 'r2', 0, 0);
+                INSERT INTO object_restores
+                    (account_id, scope_key, bucket, object_key, tier, status, requested_at, checked_at)
+                    VALUES ('nas', '', 'b', 'a.txt', 'bulk', 'in-progress', 0, 0);
+                DELETE FROM schema_version WHERE version >= 12;
+                ",
+            )
+            .await
+            .unwrap();
+
+            run_migrations(&conn).await.unwrap();
+            assert_eq!(
+                count(
+                    &conn,
+                    "SELECT COUNT(*) FROM download_sessions WHERE scope_key = 'r2||cf'"
+                )
+                .await,
+                1
+            );
+            // 'nas' is both a MinIO and an AWS account
+            assert_eq!(
+                count(&conn, "SELECT COUNT(*) FROM download_sessions").await,
+                1
+            );
+            assert_eq!(
+                count(
+                    &conn,
+                    "SELECT COUNT(*) FROM move_sessions
+                     WHERE source_scope_key = 'minio|http://nas:9000|nas'"
+                )
+                .await,
+                1
+            );
+            assert_eq!(
+                count(
+                    &conn,
+                    "SELECT COUNT(*) FROM object_restores WHERE scope_key = 'aws||nas'"
+                )
+                .await,
+                1
+            );
+        })
+    }
+
+    #[test]
+    fn search_terms_cover_the_cached_keys() {
+        with_memory_db(|conn| async move {
+            run_migrations(&conn).await.unwrap();
+            // Terms as left by the lazy build, then replay the migration
+            conn.execute_batch(
+                "
+                CREATE TABLE search_index_state (
+                    bucket TEXT NOT NULL,
+                    account_id TEXT NOT NULL,
+                    fingerprint TEXT NOT NULL,
+                    indexed_at INTEGER NOT NULL,
+                    PRIMARY KEY (bucket, account_id)
+                );
+                INSERT INTO cached_files (bucket, account_id, key, parent_path, name, size, last_modified, synced_at)
+                    VALUES ('b', 'r2||cf', 'docs/report.pdf', 'docs/', 'report.pdf', 1, '', 0);
+                INSERT INTO cached_files (bucket, account_id, key, parent_path, name, size, last_modified, synced_at)
+                    VALUES ('b', 'r2||cf', '--', '', '--', 1, '', 0);
+                INSERT INTO cached_file_terms VALUES ('b', 'r2||cf', '--', '', 0);
+                INSERT INTO cached_file_terms VALUES ('b', 'r2||cf', 'gone.txt', 'gone', 1);
+                DELETE FROM schema_version WHERE version >= 13;
+                ",
+            )
+            .await
+            .unwrap();
+
+            run_migrations(&conn).await.unwrap();
+            assert_eq!(
+                count(
+                    &conn,
+                    "SELECT COUNT(*) FROM cached_file_terms
+                     WHERE key = 'docs/report.pdf' AND term IN ('docs', 'report', 'pdf')"
+                )
+                .await,
+                3
+            );
+            assert_eq!(
+                count(&conn, "SELECT COUNT(*) FROM cached_file_terms").await,
+                3
+            );
+            assert_eq!(
+                count(
+                    &conn,
+                    "SELECT COUNT(*) FROM sqlite_master WHERE name = 'search_index_state'"
+                )
+                .await,
+                0
+            );
+        })
+    }
+
+    #[test]
+    fn untracked_databases_are_upgraded_in_place() {
+        with_memory_db(|conn| async move {
+            // Tables as created by a release without migrations
+            conn.execute_batch(
+                "
+                CREATE TABLE buckets (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    token_id INTEGER NOT NULL,
+                    name TEXT NOT NULL,
+                    public_domain TEXT,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL,
+                    UNIQUE(token_id, name)
+                );
+                INSERT INTO buckets (token_id, name, public_domain, created_at, updated_at)
+                    VALUES (1, 'site', 'cdn.example.com', 0, 0);
+                CREATE TABLE cached_files (
+                    bucket TEXT NOT NULL,
+                    account_id TEXT NOT NULL,
+                    key TEXT NOT NULL,
+                    parent_path TEXT NOT NULL,
+                    name TEXT NOT NULL,
+                    size INTEGER NOT NULL,
+                    last_modified TEXT NOT NULL,
+                    synced_at INTEGER NOT NULL,
+                    PRIMARY KEY (bucket, account_id, key)
+                );
+                INSERT INTO cached_files VALUES ('b', 'a', 'x', '', 'x', 1, '', 0);
+                ",
+            )
+            .await
+            .unwrap();
+
+            run_migrations(&conn).await.unwrap();
+            assert_eq!(count(&conn, "SELECT COUNT(*) FROM cached_files").await, 1);
+            assert_eq!(
+                count(
+                    &conn,
+                    "SELECT COUNT(*) FROM buckets WHERE is_public = 1 AND public_domain_scheme = 'https'"
+                )
+                .await,
+                1
+            );
+        })
+    }
+}
@@ -1,4 +1,6 @@
+use super::capabilities::parse_capabilities;
 use super::{get_connection, DbResult};
+use crate::providers::credential_probe::CapabilityReport;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +12,8 @@ pub struct MinioAccount {
     pub endpoint_scheme: String,
     pub endpoint_host: String,
     pub force_path_style: bool,
+    #[serde(default)]
+    pub capabilities: Option<CapabilityReport>,
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -24,6 +28,7 @@ pub fn get_table_sql() -> &'static str {
         endpoint_scheme TEXT NOT NULL,
         endpoint_host TEXT NOT NULL,
         force_path_style INTEGER NOT NULL DEFAULT 1,
+        capabilities TEXT,
         created_at INTEGER NOT NULL,
         updated_at INTEGER NOT NULL
     );
@@ -78,6 +83,7 @@ pub async fn create_minio_account(
         endpoint_scheme: endpoint_scheme.to_string(),
         endpoint_host: endpoint_host.to_string(),
         force_path_style,
+        capabilities: None,
         created_at: now,
         updated_at: now,
     })
@@ -87,7 +93,7 @@ pub async fn list_minio_accounts() -> DbResult<Vec<MinioAccount>> {
     let conn = get_connection()?.lock().await;
     let mut rows = conn
         .query(
-            "SELECT id, name, access_key_id, secret_access_key, endpoint_scheme, endpoint_host, force_path_style, created_at, updated_at
+            "SELECT id, name, access_key_id, secret_access_key, endpoint_scheme, endpoint_host, force_path_style, created_at, updated_at, capabilities
              FROM minio_accounts ORDER BY created_at",
             (),
         )
@@ -104,6 +110,7 @@ pub async fn list_minio_accounts() -> DbResult<Vec<MinioAccount>> {
             endpoint_scheme: row.get(4)?,
             endpoint_host: row.get(5)?,
             force_path_style: force_value != 0,
+            capabilities: parse_capabilities(row.get(9)?),
             created_at: row.get(7)?,
             updated_at: row.get(8)?,
         });
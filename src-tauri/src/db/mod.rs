@@ -15,32 +15,53 @@ pub mod accounts;
 pub mod app_state;
 pub mod aws_accounts;
 pub mod aws_buckets;
+pub mod bucket_encryption;
 pub mod buckets;
+pub mod cache_scope;
+pub mod capabilities;
+pub mod client_encryption;
+pub mod credential_refs;
 pub mod dir_tree;
 pub mod downloads;
 pub mod file_cache;
+pub mod file_search;
+pub mod migrations;
 pub mod minio_accounts;
 pub mod minio_buckets;
 pub mod move_sessions;
+pub mod object_hashes;
+pub mod object_restores;
 pub mod prefix_sync;
 pub mod rustfs_accounts;
 pub mod rustfs_buckets;
 pub mod sessions;
+pub mod shared_links;
+pub mod storage_analytics;
 pub mod tokens;
 
 // Re-export types
 pub use accounts::Account;
 pub use aws_accounts::AwsAccount;
 pub use aws_buckets::AwsBucket;
+pub use bucket_encryption::BucketEncryptionRow;
 pub use buckets::Bucket;
+pub use cache_scope::{default_endpoint_scheme, CacheScope};
+pub use client_encryption::ClientEncryptionRow;
+pub use credential_refs::PlaintextCredential;
 pub use downloads::DownloadSession;
-pub use file_cache::{CachedDirectoryNode, CachedFile};
+pub use file_cache::{
+    CachedDirectoryNode, CachedFile, CachedObjectMeta, StorageClassUsage, SyncDelta,
+};
+pub use file_search::{KeyPattern, SearchPage, SearchQuery, SearchSort};
 pub use minio_accounts::MinioAccount;
 pub use minio_buckets::MinioBucket;
 pub use move_sessions::MoveSession;
+pub use object_restores::ObjectRestore;
 pub use rustfs_accounts::RustfsAccount;
 pub use rustfs_buckets::RustfsBucket;
 pub use sessions::UploadSession;
+pub use shared_links::{NewSharedLink, SharedLink, SharedLinkStatus};
+pub use storage_analytics::{AgeBucket, PrefixUsage, StorageSnapshot, TypeGrouping, TypeUsage};
 pub use tokens::{CurrentConfig, Token};
 
 // ============ Connection and Initialization ============
@@ -70,140 +91,8 @@ pub async fn init_db(db_path: &Path) -> DbResult<()> {
     let _ = conn.execute("PRAGMA temp_store = MEMORY;", ()).await;
     let _ = conn.execute("PRAGMA mmap_size = 268435456;", ()).await;
 
-    conn.execute_batch(&format!(
-        "{}{}{}",
-        sessions::get_table_sql(),
-        "
-        -- Multi-account tables
-        CREATE TABLE IF NOT EXISTS accounts (
-            id TEXT PRIMARY KEY,
-            name TEXT,
-            created_at INTEGER NOT NULL,
-            updated_at INTEGER NOT NULL
-        );
-        ",
-        tokens::get_table_sql()
-    ))
-    .await?;
-
-    // Create buckets and app_state tables
-    conn.execute_batch(
-        "
-        CREATE TABLE IF NOT EXISTS buckets (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            token_id INTEGER NOT NULL REFERENCES tokens(id),
-            name TEXT NOT NULL,
-            public_domain TEXT,
-            public_domain_scheme TEXT,
-            is_public INTEGER NOT NULL DEFAULT 0,
-            public_path_prefix TEXT,
-            created_at INTEGER NOT NULL,
-            updated_at INTEGER NOT NULL,
-            UNIQUE(token_id, name)
-        );
-
-        CREATE INDEX IF NOT EXISTS idx_buckets_token ON buckets(token_id);
-        CREATE INDEX IF NOT EXISTS idx_buckets_unique ON buckets(token_id, name);
-        ",
-    )
-    .await?;
-
-    // Backfill public_domain_scheme for existing R2 buckets (idempotent)
-    let _ = conn
-        .execute(
-            "ALTER TABLE buckets ADD COLUMN public_domain_scheme TEXT",
-            (),
-        )
-        .await;
-    let _ = conn
-        .execute(
-            "UPDATE buckets SET public_domain_scheme = 'https' WHERE public_domain_scheme IS NULL",
-            (),
-        )
-        .await;
-
-    // Add explicit public-access flag + R2 public path prefix for existing DBs
-    // (idempotent: the ADD COLUMN fails harmlessly once the column exists).
-    let _ = conn
-        .execute(
-            "ALTER TABLE buckets ADD COLUMN is_public INTEGER NOT NULL DEFAULT 0",
-            (),
-        )
-        .await;
-    let _ = conn
-        .execute("ALTER TABLE buckets ADD COLUMN public_path_prefix TEXT", ())
-        .await;
-    // Buckets that already carried a public domain were served publicly before
-    // this flag existed — preserve that behavior on upgrade.
-    let _ = conn
-        .execute(
-            "UPDATE buckets SET is_public = 1 WHERE public_domain IS NOT NULL AND public_domain <> ''",
-            (),
-        )
-        .await;
-
-    // Provider-specific tables
-    conn.execute_batch(&format!(
-        "{}{}",
-        aws_accounts::get_table_sql(),
-        aws_buckets::get_table_sql()
-    ))
-    .await?;
-
-    conn.execute_batch(&format!(
-        "{}{}",
-        minio_accounts::get_table_sql(),
-        minio_buckets::get_table_sql()
-    ))
-    .await?;
-
-    conn.execute_batch(&format!(
-        "{}{}",
-        rustfs_accounts::get_table_sql(),
-        rustfs_buckets::get_table_sql()
-    ))
-    .await?;
-
-    // Add the explicit public-access flag to S3-family bucket tables for
-    // existing DBs (idempotent). Backfill: any bucket that already had a custom
-    // public domain host was served publicly before the flag existed.
-    for table in ["aws_buckets", "minio_buckets", "rustfs_buckets"] {
-        let _ = conn
-            .execute(
-                &format!("ALTER TABLE {table} ADD COLUMN is_public INTEGER NOT NULL DEFAULT 0"),
-                (),
-            )
-            .await;
-        let _ = conn
-            .execute(
-                &format!("ALTER TABLE {table} ADD COLUMN public_path_prefix TEXT"),
-                (),
-            )
-            .await;
-        let _ = conn
-            .execute(
-                &format!(
-                    "UPDATE {table} SET is_public = 1 WHERE public_domain_host IS NOT NULL AND public_domain_host <> ''"
-                ),
-                (),
-            )
-            .await;
-    }
-
-    // Create app_state table
-    conn.execute_batch(app_state::get_table_sql()).await?;
-
-    // Create file cache tables
-    conn.execute_batch(file_cache::get_table_sql()).await?;
-
-    // Create download sessions table
-    conn.execute_batch(downloads::get_table_sql()).await?;
-
-    // Create move sessions table
-    conn.execute_batch(move_sessions::get_table_sql()).await?;
-
-    // Create prefix sync times table (for lazy sync)
-    conn.execute_batch(prefix_sync::get_table_sql()).await?;
+    // Create and upgrade tables
+    migrations::run_migrations(&conn).await?;
 
     DB_CONNECTION
         .set(Mutex::new(conn))
@@ -212,6 +101,29 @@ pub async fn init_db(db_path: &Path) -> DbResult<()> {
     Ok(())
 }
 
+/// Run `test` on a runtime shared by the test binary, after initializing the
+/// scratch database every test shares.
+#[cfg(test)]
+pub(crate) fn run_db_test<F: std::future::Future>(test: F) -> F::Output {
+    static RUNTIME: std::sync::LazyLock<tokio::runtime::Runtime> = std::sync::LazyLock::new(|| {
+        tokio::runtime::Runtime::new().expect("Failed to create test runtime")
+    });
+    static TEST_DB: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+
+    RUNTIME.block_on(async {
+        TEST_DB
+            .get_or_init(|| async {
+                let path = std::env::temp_dir().join(format!("r2-test-{}.db", std::process::id()));
+                let _ = std::fs::remove_file(&path);
+                init_db(&path)
+                    .await
+                    .expect("Failed to initialize test database");
+            })
+            .await;
+        test.await
+    })
+}
+
 // Re-export session functions
 pub use sessions::{
     cleanup_old_sessions, create_session, delete_session, find_resumable_session,
@@ -232,7 +144,10 @@ pub use app_state::set_app_state;
 // Re-export account functions
 pub use accounts::{create_account, delete_account, has_accounts, list_accounts, update_account};
 // Re-export bucket functions
-pub use buckets::{delete_bucket, list_buckets_by_token, save_buckets_for_token, update_bucket};
+pub use buckets::{
+    delete_bucket, list_buckets_by_token, save_buckets_for_token, set_bucket_public_state,
+    update_bucket,
+};
 // Re-export AWS provider functions
 pub use aws_accounts::{
     create_aws_account, delete_aws_account, list_aws_accounts, update_aws_account,
@@ -251,23 +166,28 @@ pub use rustfs_buckets::{list_rustfs_buckets_by_account, save_rustfs_buckets_for
 // Re-export file cache functions
 pub use file_cache::{
     begin_sync, calculate_folder_size, clear_file_cache, delete_cached_file,
-    delete_cached_files_batch, finish_sync, get_all_cached_files, get_all_directory_nodes,
-    get_bucket_summary, get_cached_file_size, get_directory_node, get_folder_contents,
-    move_cached_file, parse_key, search_cached_files, store_all_files, store_file_batch,
-    update_cached_file, upsert_prefix_files,
+    delete_cached_files_batch, delete_cached_prefix, find_cached_files_by_etag, finish_delta_sync,
+    finish_sync, get_all_cached_files, get_all_directory_nodes, get_bucket_summary,
+    get_cached_file, get_cached_file_size, get_cached_files_by_storage_class,
+    get_cached_storage_class, get_directory_node, get_folder_contents, get_storage_class_usage,
+    has_full_sync, move_cached_file, parse_key, search_cached_files, set_cached_object_meta,
+    set_cached_storage_class, store_all_files, store_file_batch, update_cached_file,
+    upsert_prefix_files,
 };
+// Re-export cached index search
+pub use file_search::search_files;
 // Re-export directory tree builder
 pub use dir_tree::{
-    build_directory_tree_from_db, ensure_directory_node, update_directory_tree_for_delete,
-    update_directory_tree_for_delete_batch, update_directory_tree_for_file,
-    update_directory_tree_for_move,
+    build_directory_tree_from_db, ensure_directory_node, remove_directory_subtree,
+    update_directory_tree_for_delete, update_directory_tree_for_delete_batch,
+    update_directory_tree_for_file, update_directory_tree_for_move,
 };
 // Re-export download session functions
 pub use downloads::{
     count_active_downloads, create_download_session, delete_all_downloads, delete_download_session,
     delete_finished_downloads, get_download_sessions_for_bucket, get_pending_downloads,
-    pause_all_downloads, resume_all_downloads, update_download_file_size, update_download_progress,
-    update_download_status,
+    pause_all_downloads, release_restored_downloads, resume_all_downloads,
+    update_download_file_size, update_download_progress, update_download_status,
 };
 // Re-export move session functions
 pub use move_sessions::{
@@ -278,3 +198,33 @@ pub use move_sessions::{
     resume_all_moves, save_move_upload_part, save_move_upload_session, update_move_progress,
     update_move_status, update_move_status_and_progress,
 };
+// Re-export bucket encryption functions
+pub use bucket_encryption::{
+    delete_bucket_encryption, get_bucket_encryption, save_bucket_encryption,
+};
+// Re-export client-side encryption functions
+pub use client_encryption::{
+    delete_client_encryption, get_client_encryption, save_client_encryption,
+};
+// Re-export content hash functions
+pub use object_hashes::{get_object_hash, get_same_size_files, save_object_hash};
+// Re-export storage analytics functions
+pub use storage_analytics::{
+    get_age_histogram, get_largest_files, get_largest_prefixes, get_storage_history,
+    get_type_breakdown,
+};
+// Re-export archive restore functions
+pub use object_restores::{
+    delete_object_restore, list_object_restores, save_object_restore, update_object_restore_status,
+};
+// Re-export shared link functions
+pub use shared_links::{
+    create_shared_link, delete_shared_link, get_shared_link, list_links_signed_by,
+    list_shared_links, mark_shared_link_revoked,
+};
+// Re-export credential capability functions
+pub use capabilities::{save_account_capabilities, save_token_capabilities};
+// Re-export credential column functions
+pub use credential_refs::{
+    list_account_credentials, list_plaintext_credentials, replace_plaintext_credential,
+};
@@ -1,4 +1,4 @@
-use super::{get_connection, DbResult};
+use super::{get_connection, CacheScope, DbResult};
 use serde::{Deserialize, Serialize};
 
 /// Move session status (string-based storage)
@@ -97,11 +97,12 @@ pub fn get_table_sql() -> &'static str {
         status TEXT NOT NULL DEFAULT 'pending',
         error TEXT,
         created_at INTEGER NOT NULL,
-        updated_at INTEGER NOT NULL
+        updated_at INTEGER NOT NULL,
+        source_scope_key TEXT NOT NULL DEFAULT ''
     );
 
     CREATE INDEX IF NOT EXISTS idx_move_sessions_status ON move_sessions(status);
-    CREATE INDEX IF NOT EXISTS idx_move_sessions_source ON move_sessions(source_bucket, source_account_id);
+    CREATE INDEX IF NOT EXISTS idx_move_sessions_source ON move_sessions(source_bucket, source_scope_key);
 
     CREATE TABLE IF NOT EXISTS move_upload_sessions (
         task_id TEXT PRIMARY KEY,
@@ -123,14 +124,14 @@ pub fn get_table_sql() -> &'static str {
 
 /// Create a new move session
 #[allow(dead_code)]
-pub async fn create_move_session(session: &MoveSession) -> DbResult<()> {
+pub async fn create_move_session(source: &CacheScope, session: &MoveSession) -> DbResult<()> {
     let conn = get_connection()?.lock().await;
     conn.execute(
         "INSERT INTO move_sessions
          (id, source_key, dest_key, source_bucket, source_account_id, source_provider,
           dest_bucket, dest_account_id, dest_provider, delete_original, file_size, progress,
-          status, error, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+          status, error, created_at, updated_at, source_scope_key)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
         turso::params![
             session.id.clone(),
             session.source_key.clone(),
@@ -148,6 +149,7 @@ pub async fn create_move_session(session: &MoveSession) -> DbResult<()> {
             session.error.clone(),
             session.created_at,
             session.updated_at,
+            source.columns().1,
         ],
     )
     .await?;
@@ -155,7 +157,10 @@ pub async fn create_move_session(session: &MoveSession) -> DbResult<()> {
 }
 
 /// Batch create move sessions (faster for multiple sessions)
-pub async fn create_move_sessions_batch(sessions: &[MoveSession]) -> DbResult<()> {
+pub async fn create_move_sessions_batch(
+    source: &CacheScope,
+    sessions: &[MoveSession],
+) -> DbResult<()> {
     if sessions.is_empty() {
         return Ok(());
     }
@@ -171,8 +176,8 @@ pub async fn create_move_sessions_batch(sessions: &[MoveSession]) -> DbResult<()
                 "INSERT INTO move_sessions
                  (id, source_key, dest_key, source_bucket, source_account_id, source_provider,
                   dest_bucket, dest_account_id, dest_provider, delete_original, file_size, progress,
-                  status, error, created_at, updated_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+                  status, error, created_at, updated_at, source_scope_key)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
                 turso::params![
                     session.id.clone(),
                     session.source_key.clone(),
@@ -190,6 +195,7 @@ pub async fn create_move_sessions_batch(sessions: &[MoveSession]) -> DbResult<()
                     session.error.clone(),
                     session.created_at,
                     session.updated_at,
+                    source.columns().1,
                 ],
             )
             .await
@@ -385,10 +391,8 @@ pub async fn get_move_session(session_id: &str) -> DbResult<Option<MoveSession>>
 }
 
 /// Get all move sessions for a specific source bucket
-pub async fn get_move_sessions_for_source(
-    source_bucket: &str,
-    source_account_id: &str,
-) -> DbResult<Vec<MoveSession>> {
+pub async fn get_move_sessions_for_source(source: &CacheScope) -> DbResult<Vec<MoveSession>> {
+    let (source_bucket, source_scope_key) = source.columns();
     let conn = get_connection()?.lock().await;
     let mut rows = conn
         .query(
@@ -396,9 +400,9 @@ pub async fn get_move_sessions_for_source(
                 dest_bucket, dest_account_id, dest_provider, delete_original, file_size, progress,
                 status, error, created_at, updated_at
          FROM move_sessions
-         WHERE source_bucket = ?1 AND source_account_id = ?2
+         WHERE source_bucket = ?1 AND source_scope_key = ?2
          ORDER BY updated_at DESC",
-            turso::params![source_bucket, source_account_id],
+            turso::params![source_bucket, source_scope_key],
         )
         .await?;
 
@@ -428,11 +432,8 @@ pub async fn get_move_sessions_for_source(
 
 /// Get pending move sessions for a source bucket (ordered by created_at)
 #[allow(dead_code)]
-pub async fn get_pending_moves(
-    source_bucket: &str,
-    source_account_id: &str,
-    limit: i64,
-) -> DbResult<Vec<MoveSession>> {
+pub async fn get_pending_moves(source: &CacheScope, limit: i64) -> DbResult<Vec<MoveSession>> {
+    let (source_bucket, source_scope_key) = source.columns();
     let conn = get_connection()?.lock().await;
     let mut rows = conn
         .query(
@@ -440,10 +441,10 @@ pub async fn get_pending_moves(
                 dest_bucket, dest_account_id, dest_provider, delete_original, file_size, progress,
                 status, error, created_at, updated_at
          FROM move_sessions
-         WHERE source_bucket = ?1 AND source_account_id = ?2 AND status = 'pending'
+         WHERE source_bucket = ?1 AND source_scope_key = ?2 AND status = 'pending'
          ORDER BY created_at ASC
          LIMIT ?3",
-            turso::params![source_bucket, source_account_id, limit],
+            turso::params![source_bucket, source_scope_key, limit],
         )
         .await?;
 
@@ -473,10 +474,10 @@ pub async fn get_pending_moves(
 
 /// Get pending move sessions for a source (ordered by created_at)
 pub async fn get_pending_moves_for_source(
-    source_bucket: &str,
-    source_account_id: &str,
+    source: &CacheScope,
     limit: i64,
 ) -> DbResult<Vec<MoveSession>> {
+    let (source_bucket, source_scope_key) = source.columns();
     let conn = get_connection()?.lock().await;
     let mut rows = conn
         .query(
@@ -484,11 +485,11 @@ pub async fn get_pending_moves_for_source(
                 dest_bucket, dest_account_id, dest_provider, delete_original, file_size, progress,
                 status, error, created_at, updated_at
          FROM move_sessions
-         WHERE source_bucket = ?1 AND source_account_id = ?2
+         WHERE source_bucket = ?1 AND source_scope_key = ?2
          AND status = 'pending'
          ORDER BY created_at ASC
          LIMIT ?3",
-            turso::params![source_bucket, source_account_id, limit],
+            turso::params![source_bucket, source_scope_key, limit],
         )
         .await?;
 
@@ -518,15 +519,16 @@ pub async fn get_pending_moves_for_source(
 
 /// Count active move sessions for a source bucket (for queue slot calculation)
 /// Excludes tasks at 100% progress (deleting/finalizing) to allow new tasks to start immediately
-pub async fn count_active_moves(source_bucket: &str, source_account_id: &str) -> DbResult<i64> {
+pub async fn count_active_moves(source: &CacheScope) -> DbResult<i64> {
+    let (source_bucket, source_scope_key) = source.columns();
     let conn = get_connection()?.lock().await;
     let mut rows = conn
         .query(
             "SELECT COUNT(*) FROM move_sessions
-         WHERE source_bucket = ?1 AND source_account_id = ?2
+         WHERE source_bucket = ?1 AND source_scope_key = ?2
          AND status IN ('downloading', 'uploading')
          AND progress < 100",
-            turso::params![source_bucket, source_account_id],
+            turso::params![source_bucket, source_scope_key],
         )
         .await?;
 
@@ -538,17 +540,15 @@ pub async fn count_active_moves(source_bucket: &str, source_account_id: &str) ->
 }
 
 /// Count any in-progress moves (for clear_all validation - includes deleting)
-pub async fn count_in_progress_moves(
-    source_bucket: &str,
-    source_account_id: &str,
-) -> DbResult<i64> {
+pub async fn count_in_progress_moves(source: &CacheScope) -> DbResult<i64> {
+    let (source_bucket, source_scope_key) = source.columns();
     let conn = get_connection()?.lock().await;
     let mut rows = conn
         .query(
             "SELECT COUNT(*) FROM move_sessions
-         WHERE source_bucket = ?1 AND source_account_id = ?2
+         WHERE source_bucket = ?1 AND source_scope_key = ?2
          AND status IN ('downloading', 'uploading', 'finishing', 'deleting')",
-            turso::params![source_bucket, source_account_id],
+            turso::params![source_bucket, source_scope_key],
         )
         .await?;
 
@@ -599,14 +599,15 @@ pub async fn get_all_active_move_sessions() -> DbResult<Vec<MoveSession>> {
 }
 
 /// Pause all active moves
-pub async fn pause_all_moves(source_bucket: &str, source_account_id: &str) -> DbResult<i64> {
+pub async fn pause_all_moves(source: &CacheScope) -> DbResult<i64> {
+    let (source_bucket, source_scope_key) = source.columns();
     let conn = get_connection()?.lock().await;
     let now = chrono::Utc::now().timestamp();
     conn.execute(
         "UPDATE move_sessions SET status = 'paused', updated_at = ?1
-         WHERE source_bucket = ?2 AND source_account_id = ?3
+         WHERE source_bucket = ?2 AND source_scope_key = ?3
          AND status IN ('downloading', 'uploading', 'finishing', 'deleting', 'pending')",
-        turso::params![now, source_bucket, source_account_id],
+        turso::params![now, source_bucket, source_scope_key],
     )
     .await?;
 
@@ -638,13 +639,14 @@ pub async fn pause_stale_moves_on_startup() -> DbResult<i64> {
 }
 
 /// Resume all paused moves
-pub async fn resume_all_moves(source_bucket: &str, source_account_id: &str) -> DbResult<i64> {
+pub async fn resume_all_moves(source: &CacheScope) -> DbResult<i64> {
+    let (source_bucket, source_scope_key) = source.columns();
     let conn = get_connection()?.lock().await;
     let now = chrono::Utc::now().timestamp();
     conn.execute(
         "UPDATE move_sessions SET status = 'pending', updated_at = ?1
-         WHERE source_bucket = ?2 AND source_account_id = ?3 AND status = 'paused'",
-        turso::params![now, source_bucket, source_account_id],
+         WHERE source_bucket = ?2 AND source_scope_key = ?3 AND status = 'paused'",
+        turso::params![now, source_bucket, source_scope_key],
     )
     .await?;
 
@@ -677,17 +679,18 @@ pub async fn delete_move_session(session_id: &str) -> DbResult<()> {
     Ok(())
 }
 
-/// Delete all finished moves (success, error, cancelled)
-pub async fn delete_finished_moves(source_bucket: &str, source_account_id: &str) -> DbResult<i64> {
+/// Delete all finished moves (success, error, cancelled, skipped)
+pub async fn delete_finished_moves(source: &CacheScope) -> DbResult<i64> {
+    let (source_bucket, source_scope_key) = source.columns();
     let conn = get_connection()?.lock().await;
 
     // First, get the IDs of finished sessions (libsql doesn't support subqueries in IN clauses)
     let mut rows = conn
         .query(
             "SELECT id FROM move_sessions
-             WHERE source_bucket = ?1 AND source_account_id = ?2
-             AND status IN ('success', 'error', 'cancelled')",
-            turso::params![source_bucket, source_account_id],
+             WHERE source_bucket = ?1 AND source_scope_key = ?2
+             AND status IN ('success', 'error', 'cancelled', 'skipped')",
+            turso::params![source_bucket, source_scope_key],
         )
         .await?;
 
@@ -719,14 +722,15 @@ pub async fn delete_finished_moves(source_bucket: &str, source_account_id: &str)
 }
 
 /// Delete all move sessions for a source bucket (only when no active moves)
-pub async fn delete_all_moves(source_bucket: &str, source_account_id: &str) -> DbResult<i64> {
+pub async fn delete_all_moves(source: &CacheScope) -> DbResult<i64> {
+    let (source_bucket, source_scope_key) = source.columns();
     let conn = get_connection()?.lock().await;
 
     // First, get the IDs of all sessions (libsql doesn't support subqueries in IN clauses)
     let mut rows = conn
         .query(
-            "SELECT id FROM move_sessions WHERE source_bucket = ?1 AND source_account_id = ?2",
-            turso::params![source_bucket, source_account_id],
+            "SELECT id FROM move_sessions WHERE source_bucket = ?1 AND source_scope_key = ?2",
+            turso::params![source_bucket, source_scope_key],
         )
         .await?;
 
@@ -767,7 +771,7 @@ pub async fn cleanup_old_move_sessions() -> DbResult<usize> {
     let mut rows = conn
         .query(
             "SELECT id FROM move_sessions
-             WHERE status IN ('success', 'error', 'cancelled')
+             WHERE status IN ('success', 'error', 'cancelled', 'skipped')
              AND updated_at < ?1",
             turso::params![cutoff],
         )
@@ -0,0 +1,218 @@
+//! Content hashes of cached objects, computed by downloading them.
+//!
+//! A hash belongs to one version of an object: it is only returned while the
+//! cached row still has the size and ETag it was computed for.
+
+use super::file_cache::{row_to_cached_file, CACHED_FILE_COLUMNS};
+use super::{get_connection, CacheScope, CachedFile, DbResult};
+
+pub fn get_table_sql() -> &'static str {
+    "
+    CREATE TABLE IF NOT EXISTS object_hashes (
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,   -- cache scope key, see cache_scope.rs
+        key TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        etag TEXT NOT NULL,         -- empty when the listing had none
+        sha256 TEXT NOT NULL,       -- lowercase hex
+        hashed_at INTEGER NOT NULL,
+        PRIMARY KEY (bucket, account_id, key)
+    );
+    "
+}
+
+/// Cached files of `scopes` (size at least `min_size`) whose size occurs more
+/// than once across all of them, largest first. Each file comes with the
+/// index of its scope.
+pub async fn get_same_size_files(
+    scopes: &[CacheScope],
+    min_size: i64,
+) -> DbResult<Vec<(usize, CachedFile)>> {
+    if scopes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut params: Vec<turso::Value> = vec![min_size.into()];
+    let mut scope_conditions: Vec<String> = Vec::with_capacity(scopes.len());
+    for scope in scopes {
+        let (bucket, account_id) = scope.columns();
+        params.push(bucket.to_string().into());
+        params.push(account_id.to_string().into());
+        scope_conditions.push(format!(
+            "(bucket = ?{} AND account_id = ?{})",
+            params.len() - 1,
+            params.len()
+        ));
+    }
+    let in_scopes = scope_conditions.join(" OR ");
+
+    let conn = get_connection()?.lock().await;
+    let mut rows = conn
+        .query(
+            &format!(
+                "SELECT {CACHED_FILE_COLUMNS} FROM cached_files
+                 WHERE ({in_scopes}) AND size >= ?1
+                   AND size IN (
+                     SELECT size FROM cached_files
+                     WHERE ({in_scopes}) AND size >= ?1
+                     GROUP BY size HAVING COUNT(*) > 1
+                   )
+                 ORDER BY size DESC, bucket, key"
+            ),
+            params,
+        )
+        .await?;
+
+    let mut files = Vec::new();
+    while let Some(row) = rows.next().await? {
+        let file = row_to_cached_file(&row)?;
+        // account_id holds the scope key here
+        let columns = (file.bucket.as_str(), file.account_id.as_str());
+        if let Some(index) = scopes.iter().position(|scope| scope.columns() == columns) {
+            files.push((index, file));
+        }
+    }
+    Ok(files)
+}
+
+/// The recorded hash of `key`, if it was computed for this size and ETag.
+pub async fn get_object_hash(
+    scope: &CacheScope,
+    key: &str,
+    size: i64,
+    etag: Option<&str>,
+) -> DbResult<Option<String>> {
+    let (bucket, account_id) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    let mut rows = conn
+        .query(
+            "SELECT sha256 FROM object_hashes
+             WHERE bucket = ?1 AND account_id = ?2 AND key = ?3 AND size = ?4 AND etag = ?5",
+            turso::params![bucket, account_id, key, size, etag.unwrap_or_default()],
+        )
+        .await?;
+
+    match rows.next().await? {
+        Some(row) => Ok(Some(row.get(0)?)),
+        None => Ok(None),
+    }
+}
+
+pub async fn save_object_hash(
+    scope: &CacheScope,
+    key: &str,
+    size: i64,
+    etag: Option<&str>,
+    sha256: &str,
+) -> DbResult<()> {
+    let (bucket, account_id) = scope.columns();
+    let now = chrono::Utc::now().timestamp();
+    let conn = get_connection()?.lock().await;
+    conn.execute(
+        "INSERT INTO object_hashes (bucket, account_id, key, size, etag, sha256, hashed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT (bucket, account_id, key) DO UPDATE SET
+           size = ?4, etag = ?5, sha256 = ?6, hashed_at = ?7",
+        turso::params![
+            bucket,
+            account_id,
+            key,
+            size,
+            etag.unwrap_or_default(),
+            sha256,
+            now
+        ],
+    )
+    .await?;
+    Ok(())
+}
+
+/// Drop the hashes of a bucket, e.g. when its cache is cleared.
+pub(crate) async fn clear_object_hashes(
+    conn: &turso::Connection,
+    bucket: &str,
+    account_id: &str,
+) -> DbResult<()> {
+    conn.execute(
+        "DELETE FROM object_hashes WHERE bucket = ?1 AND account_id = ?2",
+        turso::params![bucket, account_id],
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::file_cache::{begin_sync, finish_sync, parse_key, store_file_batch};
+
+    fn listed(scope: &CacheScope, key: &str, size: i64) -> CachedFile {
+        let (parent_path, name) = parse_key(key);
+        CachedFile {
+            bucket: scope.bucket().to_string(),
+            account_id: scope.account_id().to_string(),
+            key: key.to_string(),
+            parent_path,
+            name,
+            size,
+            last_modified: "2024-01-01T00:00:00Z".to_string(),
+            synced_at: 0,
+            storage_class: None,
+            etag: Some("\"e\"".to_string()),
+            content_type: None,
+            checksum: None,
+        }
+    }
+
+    #[test]
+    fn same_size_files_span_scopes_and_hashes_follow_versions() {
+        crate::db::run_db_test(async {
+            let first = CacheScope::r2("acct", "dupes-a");
+            let second = CacheScope::new("minio", None, Some("nas:9000"), "acct", "dupes-b");
+            for (scope, files) in [
+                (
+                    &first,
+                    vec![("a.bin", 10), ("small.txt", 1), ("odd.bin", 7)],
+                ),
+                (&second, vec![("b.bin", 10), ("tiny.txt", 1)]),
+            ] {
+                let files: Vec<CachedFile> = files
+                    .into_iter()
+                    .map(|(key, size)| listed(scope, key, size))
+                    .collect();
+                begin_sync(scope).await.unwrap();
+                store_file_batch(scope, &files).await.unwrap();
+                finish_sync(scope, files.len()).await.unwrap();
+            }
+
+            let scopes = [first.clone(), second.clone()];
+            let found: Vec<(usize, String)> = get_same_size_files(&scopes, 2)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|(index, file)| (index, file.key))
+                .collect();
+            assert_eq!(
+                found,
+                vec![(0, "a.bin".to_string()), (1, "b.bin".to_string())]
+            );
+
+            save_object_hash(&first, "a.bin", 10, Some("\"e\""), "abc")
+                .await
+                .unwrap();
+            assert_eq!(
+                get_object_hash(&first, "a.bin", 10, Some("\"e\""))
+                    .await
+                    .unwrap()
+                    .as_deref(),
+                Some("abc")
+            );
+            assert_eq!(
+                get_object_hash(&first, "a.bin", 10, Some("\"changed\""))
+                    .await
+                    .unwrap(),
+                None
+            );
+        });
+    }
+}
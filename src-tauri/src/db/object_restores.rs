@@ -0,0 +1,170 @@
+use super::{get_connection, CacheScope, DbResult};
+use serde::{Deserialize, Serialize};
+
+/// A restore requested for an archived (Glacier / Deep Archive) object, and
+/// the latest status read back from HEAD `x-amz-restore`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectRestore {
+    pub account_id: String,
+    pub bucket: String,
+    pub object_key: String,
+    /// `expedited`, `standard` or `bulk`
+    pub tier: String,
+    /// Days the restored copy is kept; `None` for Intelligent-Tiering.
+    pub days: Option<i64>,
+    /// `in-progress`, `restored`, `not-archived` or `archived` (expired)
+    pub status: String,
+    pub expiry_date: Option<String>,
+    pub requested_at: i64,
+    pub checked_at: i64,
+}
+
+pub fn get_table_sql() -> &'static str {
+    "
+    CREATE TABLE IF NOT EXISTS object_restores (
+        account_id TEXT NOT NULL,
+        scope_key TEXT NOT NULL,
+        bucket TEXT NOT NULL,
+        object_key TEXT NOT NULL,
+        tier TEXT NOT NULL,
+        days INTEGER,
+        status TEXT NOT NULL,
+        expiry_date TEXT,
+        requested_at INTEGER NOT NULL,
+        checked_at INTEGER NOT NULL,
+        PRIMARY KEY (scope_key, bucket, object_key)
+    );
+    "
+}
+
+const SELECT_COLUMNS: &str = "SELECT account_id, bucket, object_key, tier, days, status, expiry_date, requested_at, checked_at
+     FROM object_restores";
+
+fn row_to_restore(row: &turso::Row) -> DbResult<ObjectRestore> {
+    Ok(ObjectRestore {
+        account_id: row.get(0)?,
+        bucket: row.get(1)?,
+        object_key: row.get(2)?,
+        tier: row.get(3)?,
+        days: row.get(4)?,
+        status: row.get(5)?,
+        expiry_date: row.get(6)?,
+        requested_at: row.get(7)?,
+        checked_at: row.get(8)?,
+    })
+}
+
+/// Record a restore request, replacing any earlier one for the object
+pub async fn save_object_restore(
+    scope: &CacheScope,
+    object_key: &str,
+    tier: &str,
+    days: Option<i64>,
+    status: &str,
+) -> DbResult<ObjectRestore> {
+    let (bucket, scope_key) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO object_restores
+         (account_id, scope_key, bucket, object_key, tier, days, status, expiry_date, requested_at, checked_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, NULL, ?8, ?8)
+         ON CONFLICT (scope_key, bucket, object_key) DO UPDATE SET
+            tier = excluded.tier,
+            days = excluded.days,
+            status = excluded.status,
+            expiry_date = NULL,
+            requested_at = excluded.requested_at,
+            checked_at = excluded.checked_at",
+        turso::params![
+            scope.account_id(),
+            scope_key,
+            bucket,
+            object_key,
+            tier,
+            days,
+            status,
+            now
+        ],
+    )
+    .await?;
+
+    Ok(ObjectRestore {
+        account_id: scope.account_id().to_string(),
+        bucket: bucket.to_string(),
+        object_key: object_key.to_string(),
+        tier: tier.to_string(),
+        days,
+        status: status.to_string(),
+        expiry_date: None,
+        requested_at: now,
+        checked_at: now,
+    })
+}
+
+/// Store the latest polled status of a tracked restore.
+/// Returns the updated row, or None if the object is not tracked.
+pub async fn update_object_restore_status(
+    scope: &CacheScope,
+    object_key: &str,
+    status: &str,
+    expiry_date: Option<&str>,
+) -> DbResult<Option<ObjectRestore>> {
+    let (bucket, scope_key) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "UPDATE object_restores SET status = ?1, expiry_date = ?2, checked_at = ?3
+         WHERE scope_key = ?4 AND bucket = ?5 AND object_key = ?6",
+        turso::params![
+            status,
+            expiry_date.map(str::to_string),
+            now,
+            scope_key,
+            bucket,
+            object_key
+        ],
+    )
+    .await?;
+
+    let sql = format!(
+        "{} WHERE scope_key = ?1 AND bucket = ?2 AND object_key = ?3",
+        SELECT_COLUMNS
+    );
+    let mut rows = conn
+        .query(&sql, turso::params![scope_key, bucket, object_key])
+        .await?;
+    match rows.next().await? {
+        Some(row) => Ok(Some(row_to_restore(&row)?)),
+        None => Ok(None),
+    }
+}
+
+/// List tracked restores for a bucket, newest request first
+pub async fn list_object_restores(scope: &CacheScope) -> DbResult<Vec<ObjectRestore>> {
+    let (bucket, scope_key) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    let sql = format!(
+        "{} WHERE scope_key = ?1 AND bucket = ?2 ORDER BY requested_at DESC",
+        SELECT_COLUMNS
+    );
+    let mut rows = conn.query(&sql, turso::params![scope_key, bucket]).await?;
+
+    let mut restores = Vec::new();
+    while let Some(row) = rows.next().await? {
+        restores.push(row_to_restore(&row)?);
+    }
+    Ok(restores)
+}
+
+/// Stop tracking a restore
+pub async fn delete_object_restore(scope: &CacheScope, object_key: &str) -> DbResult<()> {
+    let (bucket, scope_key) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    conn.execute(
+        "DELETE FROM object_restores WHERE scope_key = ?1 AND bucket = ?2 AND object_key = ?3",
+        turso::params![scope_key, bucket, object_key],
+    )
+    .await?;
+    Ok(())
+}
@@ -1,7 +1,8 @@
-use super::{get_connection, DbResult};
+use super::{get_connection, CacheScope, DbResult};
 
 pub fn get_table_sql() -> &'static str {
     "
+    -- account_id holds the cache scope key, see cache_scope.rs
     CREATE TABLE IF NOT EXISTS prefix_sync_times (
         bucket TEXT NOT NULL,
         account_id TEXT NOT NULL,
@@ -16,11 +17,8 @@ pub fn get_table_sql() -> &'static str {
 }
 
 /// Get the last sync time for a prefix. Returns None if never synced.
-pub async fn get_prefix_sync_time(
-    bucket: &str,
-    account_id: &str,
-    prefix: &str,
-) -> DbResult<Option<i64>> {
+pub async fn get_prefix_sync_time(scope: &CacheScope, prefix: &str) -> DbResult<Option<i64>> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
     let mut rows = conn
         .query(
@@ -39,12 +37,12 @@ pub async fn get_prefix_sync_time(
 
 /// Update the sync time for a prefix after a successful lazy sync.
 pub async fn set_prefix_sync_time(
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
     prefix: &str,
     file_count: i32,
     folder_count: i32,
 ) -> DbResult<()> {
+    let (bucket, account_id) = scope.columns();
     let now = chrono::Utc::now().timestamp();
     let conn = get_connection()?.lock().await;
     conn.execute(
@@ -60,7 +58,8 @@ pub async fn set_prefix_sync_time(
 
 /// Clear all prefix sync times for a bucket (used when switching accounts or full re-sync).
 #[allow(dead_code)]
-pub async fn clear_prefix_sync_times(bucket: &str, account_id: &str) -> DbResult<()> {
+pub async fn clear_prefix_sync_times(scope: &CacheScope) -> DbResult<()> {
+    let (bucket, account_id) = scope.columns();
     let conn = get_connection()?.lock().await;
     conn.execute(
         "DELETE FROM prefix_sync_times WHERE bucket = ?1 AND account_id = ?2",
@@ -0,0 +1,221 @@
+use super::{get_connection, DbResult};
+use serde::{Deserialize, Serialize};
+
+/// A presigned link that was handed out, with the credential that signed it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedLink {
+    pub id: i64,
+    pub provider: String,
+    pub account_id: String,
+    pub bucket: String,
+    pub key: String,
+    pub method: String,
+    pub url: String,
+    /// Access key ID of the credential that signed the URL.
+    pub signing_key_id: String,
+    pub note: Option<String>,
+    pub created_at: i64,
+    pub expires_at: i64,
+    /// Set when the user marked the link revoked (the URL itself stays valid
+    /// until the signing key is rotated or it expires).
+    pub revoked_at: Option<i64>,
+}
+
+impl SharedLink {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at <= now
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewSharedLink {
+    pub provider: String,
+    pub account_id: String,
+    pub bucket: String,
+    pub key: String,
+    pub method: String,
+    pub url: String,
+    pub signing_key_id: String,
+    pub note: Option<String>,
+    pub expires_at: i64,
+}
+
+/// Which links to list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SharedLinkStatus {
+    #[default]
+    All,
+    Active,
+    Expired,
+}
+
+pub fn get_table_sql() -> &'static str {
+    "
+    CREATE TABLE IF NOT EXISTS shared_links (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        provider TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        bucket TEXT NOT NULL,
+        key TEXT NOT NULL,
+        method TEXT NOT NULL DEFAULT 'GET',
+        url TEXT NOT NULL,
+        signing_key_id TEXT NOT NULL,
+        note TEXT,
+        created_at INTEGER NOT NULL,
+        expires_at INTEGER NOT NULL,
+        revoked_at INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS idx_shared_links_expiry ON shared_links(expires_at);
+    CREATE INDEX IF NOT EXISTS idx_shared_links_signer ON shared_links(provider, signing_key_id);
+    "
+}
+
+const SELECT_COLUMNS: &str = "SELECT id, provider, account_id, bucket, key, method, url,
+    signing_key_id, note, created_at, expires_at, revoked_at FROM shared_links";
+
+fn row_to_link(row: &turso::Row) -> DbResult<SharedLink> {
+    Ok(SharedLink {
+        id: row.get(0)?,
+        provider: row.get(1)?,
+        account_id: row.get(2)?,
+        bucket: row.get(3)?,
+        key: row.get(4)?,
+        method: row.get(5)?,
+        url: row.get(6)?,
+        signing_key_id: row.get(7)?,
+        note: row.get(8)?,
+        created_at: row.get(9)?,
+        expires_at: row.get(10)?,
+        revoked_at: row.get(11)?,
+    })
+}
+
+/// Record a link that was generated for sharing
+pub async fn create_shared_link(link: &NewSharedLink) -> DbResult<SharedLink> {
+    let conn = get_connection()?.lock().await;
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO shared_links
+         (provider, account_id, bucket, key, method, url, signing_key_id, note, created_at, expires_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        turso::params![
+            link.provider.clone(),
+            link.account_id.clone(),
+            link.bucket.clone(),
+            link.key.clone(),
+            link.method.clone(),
+            link.url.clone(),
+            link.signing_key_id.clone(),
+            link.note.clone(),
+            now,
+            link.expires_at,
+        ],
+    )
+    .await?;
+    let id = conn.last_insert_rowid();
+    Ok(SharedLink {
+        id,
+        provider: link.provider.clone(),
+        account_id: link.account_id.clone(),
+        bucket: link.bucket.clone(),
+        key: link.key.clone(),
+        method: link.method.clone(),
+        url: link.url.clone(),
+        signing_key_id: link.signing_key_id.clone(),
+        note: link.note.clone(),
+        created_at: now,
+        expires_at: link.expires_at,
+        revoked_at: None,
+    })
+}
+
+/// List recorded links, newest first. Revoked links count as neither active
+/// nor expired until they actually expire.
+pub async fn list_shared_links(status: SharedLinkStatus) -> DbResult<Vec<SharedLink>> {
+    let now = chrono::Utc::now().timestamp();
+    let conn = get_connection()?.lock().await;
+    let sql = match status {
+        SharedLinkStatus::All => format!("{} ORDER BY created_at DESC", SELECT_COLUMNS),
+        SharedLinkStatus::Active => format!(
+            "{} WHERE expires_at > ?1 AND revoked_at IS NULL ORDER BY created_at DESC",
+            SELECT_COLUMNS
+        ),
+        SharedLinkStatus::Expired => format!(
+            "{} WHERE expires_at <= ?1 ORDER BY expires_at DESC",
+            SELECT_COLUMNS
+        ),
+    };
+    let mut rows = if status == SharedLinkStatus::All {
+        conn.query(&sql, turso::params![]).await?
+    } else {
+        conn.query(&sql, turso::params![now]).await?
+    };
+
+    let mut links = Vec::new();
+    while let Some(row) = rows.next().await? {
+        links.push(row_to_link(&row)?);
+    }
+    Ok(links)
+}
+
+/// Get a link by ID
+pub async fn get_shared_link(id: i64) -> DbResult<Option<SharedLink>> {
+    let conn = get_connection()?.lock().await;
+    let mut rows = conn
+        .query(
+            &format!("{} WHERE id = ?1", SELECT_COLUMNS),
+            turso::params![id],
+        )
+        .await?;
+    match rows.next().await? {
+        Some(row) => Ok(Some(row_to_link(&row)?)),
+        None => Ok(None),
+    }
+}
+
+/// Unexpired links signed by the same credential, excluding `exclude_id`
+pub async fn list_links_signed_by(
+    provider: &str,
+    signing_key_id: &str,
+    exclude_id: i64,
+) -> DbResult<Vec<SharedLink>> {
+    let now = chrono::Utc::now().timestamp();
+    let conn = get_connection()?.lock().await;
+    let mut rows = conn
+        .query(
+            &format!(
+                "{} WHERE provider = ?1 AND signing_key_id = ?2 AND id <> ?3 AND expires_at > ?4
+                 ORDER BY expires_at DESC",
+                SELECT_COLUMNS
+            ),
+            turso::params![provider, signing_key_id, exclude_id, now],
+        )
+        .await?;
+
+    let mut links = Vec::new();
+    while let Some(row) = rows.next().await? {
+        links.push(row_to_link(&row)?);
+    }
+    Ok(links)
+}
+
+/// Mark a link as revoked
+pub async fn mark_shared_link_revoked(id: i64) -> DbResult<()> {
+    let conn = get_connection()?.lock().await;
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "UPDATE shared_links SET revoked_at = ?1 WHERE id = ?2 AND revoked_at IS NULL",
+        turso::params![now, id],
+    )
+    .await?;
+    Ok(())
+}
+
+/// Forget a link
+pub async fn delete_shared_link(id: i64) -> DbResult<()> {
+    let conn = get_connection()?.lock().await;
+    conn.execute("DELETE FROM shared_links WHERE id = ?1", turso::params![id])
+        .await?;
+    Ok(())
+}
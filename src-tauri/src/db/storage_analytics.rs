@@ -0,0 +1,445 @@
+//! Storage analytics over the cached index: largest prefixes and files, type
+//! and age breakdowns, and a size history recorded at the end of every sync.
+
+use super::file_cache::{row_to_cached_file, CACHED_FILE_COLUMNS};
+use super::{get_connection, CacheScope, CachedFile, DbResult, StorageClassUsage};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+pub fn get_table_sql() -> &'static str {
+    "
+    -- One row per storage class and UTC day; later syncs of the day replace it
+    CREATE TABLE IF NOT EXISTS storage_history (
+        bucket TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        day INTEGER NOT NULL,              -- days since the Unix epoch
+        storage_class TEXT NOT NULL,       -- empty when the listing had none
+        file_count INTEGER NOT NULL,
+        total_size INTEGER NOT NULL,
+        recorded_at INTEGER NOT NULL,
+        PRIMARY KEY (bucket, account_id, day, storage_class)
+    );
+    "
+}
+
+// ============ History ============
+
+/// Record the bucket's current totals per storage class. Runs inside the
+/// sync's transaction, after the live table holds the new listing.
+pub(crate) async fn record_storage_snapshot(
+    conn: &turso::Connection,
+    bucket: &str,
+    account_id: &str,
+    now: i64,
+) -> DbResult<()> {
+    let day = now.div_euclid(86_400);
+    conn.execute(
+        "DELETE FROM storage_history WHERE bucket = ?1 AND account_id = ?2 AND day = ?3",
+        turso::params![bucket, account_id, day],
+    )
+    .await?;
+    conn.execute(
+        "INSERT INTO storage_history (bucket, account_id, day, storage_class, file_count, total_size, recorded_at)
+         SELECT bucket, account_id, ?3, COALESCE(storage_class, ''), COUNT(*), COALESCE(SUM(size), 0), ?4
+         FROM cached_files
+         WHERE bucket = ?1 AND account_id = ?2
+         GROUP BY bucket, account_id, COALESCE(storage_class, '')",
+        turso::params![bucket, account_id, day, now],
+    )
+    .await?;
+    // An empty bucket still gets a point on the chart
+    conn.execute(
+        "INSERT INTO storage_history (bucket, account_id, day, storage_class, file_count, total_size, recorded_at)
+         SELECT ?1, ?2, ?3, '', 0, 0, ?4
+         WHERE NOT EXISTS (SELECT 1 FROM cached_files WHERE bucket = ?1 AND account_id = ?2)",
+        turso::params![bucket, account_id, day, now],
+    )
+    .await?;
+    Ok(())
+}
+
+/// Bucket totals as of one sync.
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageSnapshot {
+    pub recorded_at: i64,
+    pub file_count: i64,
+    pub total_size: i64,
+    pub classes: Vec<StorageClassUsage>,
+}
+
+/// Recorded snapshots since `since` (Unix seconds), oldest first.
+pub async fn get_storage_history(
+    scope: &CacheScope,
+    since: Option<i64>,
+) -> DbResult<Vec<StorageSnapshot>> {
+    let (bucket, account_id) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    let mut rows = conn
+        .query(
+            "SELECT day, recorded_at, storage_class, file_count, total_size
+             FROM storage_history
+             WHERE bucket = ?1 AND account_id = ?2 AND recorded_at >= ?3
+             ORDER BY day, total_size DESC",
+            turso::params![bucket, account_id, since.unwrap_or(0)],
+        )
+        .await?;
+
+    let mut by_day: BTreeMap<i64, StorageSnapshot> = BTreeMap::new();
+    while let Some(row) = rows.next().await? {
+        let day: i64 = row.get(0)?;
+        let recorded_at: i64 = row.get(1)?;
+        let storage_class: String = row.get(2)?;
+        let usage = StorageClassUsage {
+            storage_class: (!storage_class.is_empty()).then_some(storage_class),
+            file_count: row.get(3)?,
+            total_size: row.get(4)?,
+        };
+        let snapshot = by_day.entry(day).or_insert_with(|| StorageSnapshot {
+            recorded_at,
+            file_count: 0,
+            total_size: 0,
+            classes: Vec::new(),
+        });
+        snapshot.file_count += usage.file_count;
+        snapshot.total_size += usage.total_size;
+        // The empty-bucket marker carries no class
+        if usage.file_count > 0 {
+            snapshot.classes.push(usage);
+        }
+    }
+    Ok(by_day.into_values().collect())
+}
+
+// ============ Largest Prefixes and Files ============
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrefixUsage {
+    pub path: String,
+    /// Files under the prefix, recursively.
+    pub file_count: i64,
+    pub total_size: i64,
+}
+
+/// Largest folders by total size. With `parent`, only its immediate
+/// subfolders (one treemap level); otherwise folders at any depth.
+/// Needs the directory tree of a finished sync.
+pub async fn get_largest_prefixes(
+    scope: &CacheScope,
+    parent: Option<&str>,
+    limit: usize,
+) -> DbResult<Vec<PrefixUsage>> {
+    let (bucket, account_id) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    let mut rows = match parent {
+        Some(parent) => {
+            conn.query(
+                "SELECT path, total_file_count, total_size FROM directory_tree
+                 WHERE bucket = ?1 AND account_id = ?2 AND parent_path = ?3 AND path <> ''
+                 ORDER BY total_size DESC, path
+                 LIMIT ?4",
+                turso::params![bucket, account_id, parent, limit as i64],
+            )
+            .await?
+        }
+        None => {
+            conn.query(
+                "SELECT path, total_file_count, total_size FROM directory_tree
+                 WHERE bucket = ?1 AND account_id = ?2 AND path <> ''
+                 ORDER BY total_size DESC, path
+                 LIMIT ?3",
+                turso::params![bucket, account_id, limit as i64],
+            )
+            .await?
+        }
+    };
+
+    let mut prefixes = Vec::new();
+    while let Some(row) = rows.next().await? {
+        prefixes.push(PrefixUsage {
+            path: row.get(0)?,
+            file_count: row.get(1)?,
+            total_size: row.get(2)?,
+        });
+    }
+    Ok(prefixes)
+}
+
+/// Largest cached files, biggest first.
+pub async fn get_largest_files(scope: &CacheScope, limit: usize) -> DbResult<Vec<CachedFile>> {
+    let (bucket, account_id) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    let mut rows = conn
+        .query(
+            &format!(
+                "SELECT {CACHED_FILE_COLUMNS} FROM cached_files
+                 WHERE bucket = ?1 AND account_id = ?2
+                 ORDER BY size DESC, key
+                 LIMIT ?3"
+            ),
+            turso::params![bucket, account_id, limit as i64],
+        )
+        .await?;
+
+    let mut files = Vec::new();
+    while let Some(row) = rows.next().await? {
+        files.push(row_to_cached_file(&row)?);
+    }
+    Ok(files)
+}
+
+// ============ Type Breakdown ============
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeGrouping {
+    #[default]
+    Extension,
+    ContentType,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeUsage {
+    /// Extension or content type; `None` when there is none or it is unknown.
+    pub kind: Option<String>,
+    pub file_count: i64,
+    pub total_size: i64,
+}
+
+/// Lowercase extension of a file name. Dot files and overly long suffixes
+/// don't count as extensions.
+fn file_extension(name: &str) -> Option<String> {
+    let (stem, ext) = name.rsplit_once('.')?;
+    if stem.is_empty() || ext.is_empty() || ext.len() > 10 {
+        return None;
+    }
+    Some(ext.to_lowercase())
+}
+
+/// File count and bytes per extension or content type, largest first.
+/// Content types are only known for files uploaded from here or fetched with
+/// HEAD, the rest count as unknown.
+pub async fn get_type_breakdown(
+    scope: &CacheScope,
+    grouping: TypeGrouping,
+) -> DbResult<Vec<TypeUsage>> {
+    let (bucket, account_id) = scope.columns();
+    let conn = get_connection()?.lock().await;
+    let mut usage: Vec<TypeUsage> = Vec::new();
+
+    match grouping {
+        TypeGrouping::ContentType => {
+            let mut rows = conn
+                .query(
+                    "SELECT content_type, COUNT(*), COALESCE(SUM(size), 0)
+                     FROM cached_files
+                     WHERE bucket = ?1 AND account_id = ?2
+                     GROUP BY content_type",
+                    turso::params![bucket, account_id],
+                )
+                .await?;
+            while let Some(row) = rows.next().await? {
+                usage.push(TypeUsage {
+                    kind: row.get(0)?,
+                    file_count: row.get(1)?,
+                    total_size: row.get(2)?,
+                });
+            }
+        }
+        TypeGrouping::Extension => {
+            let mut rows = conn
+                .query(
+                    "SELECT name, size FROM cached_files WHERE bucket = ?1 AND account_id = ?2",
+                    turso::params![bucket, account_id],
+                )
+                .await?;
+            let mut by_extension: HashMap<Option<String>, (i64, i64)> = HashMap::new();
+            while let Some(row) = rows.next().await? {
+                let name: String = row.get(0)?;
+                let size: i64 = row.get(1)?;
+                let entry = by_extension.entry(file_extension(&name)).or_default();
+                entry.0 += 1;
+                entry.1 += size;
+            }
+            usage = by_extension
+                .into_iter()
+                .map(|(kind, (file_count, total_size))| TypeUsage {
+                    kind,
+                    file_count,
+                    total_size,
+                })
+                .collect();
+        }
+    }
+
+    usage.sort_by(|a, b| {
+        b.total_size
+            .cmp(&a.total_size)
+            .then_with(|| a.kind.cmp(&b.kind))
+    });
+    Ok(usage)
+}
+
+// ============ Age Histogram ============
+
+/// Upper bounds of the age buckets in days; the last bucket is open-ended.
+const AGE_BUCKET_DAYS: &[(&str, Option<i64>)] = &[
+    ("7d", Some(7)),
+    ("30d", Some(30)),
+    ("90d", Some(90)),
+    ("180d", Some(180)),
+    ("1y", Some(365)),
+    ("2y", Some(730)),
+    ("older", None),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AgeBucket {
+    pub label: String,
+    /// Files in this bucket are younger than this many days; `None` for the
+    /// oldest bucket and for files whose date couldn't be read.
+    pub max_age_days: Option<i64>,
+    pub file_count: i64,
+    pub total_size: i64,
+}
+
+/// Index into `AGE_BUCKET_DAYS` for a file modified at `last_modified`.
+fn age_bucket(last_modified: &str, now: DateTime<Utc>) -> Option<usize> {
+    let modified = DateTime::parse_from_rfc3339(last_modified).ok()?;
+    let age_days = (now - modified.with_timezone(&Utc)).num_days().max(0);
+    AGE_BUCKET_DAYS.iter().position(|(_, max)| match max {
+        Some(max) => age_days < *max,
+        None => true,
+    })
+}
+
+/// File count and bytes by age of `last_modified`, youngest first, followed
+/// by an `unknown` bucket for unparseable dates.
+pub async fn get_age_histogram(scope: &CacheScope, now: DateTime<Utc>) -> DbResult<Vec<AgeBucket>> {
+    let (bucket, account_id) = scope.columns();
+    let mut buckets: Vec<AgeBucket> = AGE_BUCKET_DAYS
+        .iter()
+        .map(|(label, max_age_days)| AgeBucket {
+            label: label.to_string(),
+            max_age_days: *max_age_days,
+            file_count: 0,
+            total_size: 0,
+        })
+        .chain(std::iter::once(AgeBucket {
+            label: "unknown".to_string(),
+            max_age_days: None,
+            file_count: 0,
+            total_size: 0,
+        }))
+        .collect();
+    let unknown = buckets.len() - 1;
+
+    let conn = get_connection()?.lock().await;
+    let mut rows = conn
+        .query(
+            "SELECT last_modified, size FROM cached_files WHERE bucket = ?1 AND account_id = ?2",
+            turso::params![bucket, account_id],
+        )
+        .await?;
+    while let Some(row) = rows.next().await? {
+        let last_modified: String = row.get(0)?;
+        let size: i64 = row.get(1)?;
+        let index = age_bucket(&last_modified, now).unwrap_or(unknown);
+        buckets[index].file_count += 1;
+        buckets[index].total_size += size;
+    }
+    Ok(buckets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::file_cache::{begin_sync, finish_sync, parse_key, store_file_batch};
+
+    #[test]
+    fn extensions_ignore_dot_files() {
+        assert_eq!(file_extension("photo.JPG").as_deref(), Some("jpg"));
+        assert_eq!(file_extension("archive.tar.gz").as_deref(), Some("gz"));
+        assert_eq!(file_extension(".env"), None);
+        assert_eq!(file_extension("README"), None);
+        assert_eq!(file_extension("trailing."), None);
+    }
+
+    #[test]
+    fn ages_fall_into_open_ended_buckets() {
+        let now = DateTime::parse_from_rfc3339("2024-12-31T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(age_bucket("2024-12-30T00:00:00Z", now), Some(0));
+        assert_eq!(age_bucket("2024-11-30T00:00:00+00:00", now), Some(2));
+        assert_eq!(age_bucket("2010-01-01T00:00:00Z", now), Some(6));
+        assert_eq!(age_bucket("yesterday", now), None);
+    }
+
+    #[test]
+    fn syncs_record_one_snapshot_per_day() {
+        crate::db::run_db_test(async {
+            let scope = CacheScope::r2("acct", "history-test");
+            let file = |key: &str, size: i64, class: Option<&str>| {
+                let (parent_path, name) = parse_key(key);
+                CachedFile {
+                    bucket: "history-test".to_string(),
+                    account_id: "acct".to_string(),
+                    key: key.to_string(),
+                    parent_path,
+                    name,
+                    size,
+                    last_modified: "2024-01-01T00:00:00Z".to_string(),
+                    synced_at: 0,
+                    storage_class: class.map(str::to_string),
+                    etag: None,
+                    content_type: None,
+                    checksum: None,
+                }
+            };
+
+            // Syncs snapshot at the current time, which may cross midnight;
+            // each is also recorded at a fixed hour of 2024-01-01.
+            let noon = 1_704_110_400;
+            for (hour, files) in [
+                vec![file("a.txt", 1, None)],
+                vec![file("a.txt", 1, None), file("b.iso", 10, Some("GLACIER"))],
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                begin_sync(&scope).await.unwrap();
+                store_file_batch(&scope, &files).await.unwrap();
+                finish_sync(&scope, files.len()).await.unwrap();
+
+                let (bucket, account_id) = scope.columns();
+                let conn = get_connection().unwrap().lock().await;
+                record_storage_snapshot(&conn, bucket, account_id, noon + hour as i64 * 3600)
+                    .await
+                    .unwrap();
+            }
+
+            let history = get_storage_history(&scope, None).await.unwrap();
+            let fixed_day: Vec<_> = history
+                .iter()
+                .filter(|snapshot| snapshot.recorded_at.div_euclid(86_400) == noon / 86_400)
+                .collect();
+            assert_eq!(fixed_day.len(), 1);
+            let latest = history.last().unwrap();
+            for snapshot in [fixed_day[0], latest] {
+                assert_eq!((snapshot.file_count, snapshot.total_size), (2, 11));
+                assert_eq!(
+                    snapshot.classes[0].storage_class.as_deref(),
+                    Some("GLACIER")
+                );
+            }
+
+            let types = get_type_breakdown(&scope, TypeGrouping::Extension)
+                .await
+                .unwrap();
+            assert_eq!(types[0].kind.as_deref(), Some("iso"));
+            let largest = get_largest_files(&scope, 1).await.unwrap();
+            assert_eq!(largest[0].key, "b.iso");
+        });
+    }
+}
@@ -1,4 +1,7 @@
+use super::capabilities::parse_capabilities;
 use super::{get_connection, DbResult};
+use crate::providers::aws::CredentialSource;
+use crate::providers::credential_probe::CapabilityReport;
 use serde::{Deserialize, Serialize};
 
 // ============ Token Struct ============
@@ -8,9 +11,14 @@ pub struct Token {
     pub id: i64,
     pub account_id: String,
     pub name: Option<String>,
+    /// Secret store handle, like `secret_access_key`.
     pub api_token: String,
     pub access_key_id: String,
+    /// Secret store handle; plain text only until `secrets` migrates the row.
     pub secret_access_key: String,
+    /// Result of the last `validate_credentials` run, if any.
+    #[serde(default)]
+    pub capabilities: Option<CapabilityReport>,
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -24,6 +32,17 @@ pub enum StorageProvider {
     Rustfs,
 }
 
+impl StorageProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StorageProvider::R2 => "r2",
+            StorageProvider::Aws => "aws",
+            StorageProvider::Minio => "minio",
+            StorageProvider::Rustfs => "rustfs",
+        }
+    }
+}
+
 /// Full configuration needed for storage operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CurrentConfig {
@@ -32,6 +51,7 @@ pub struct CurrentConfig {
     pub account_name: Option<String>,
     pub token_id: Option<i64>,
     pub token_name: Option<String>,
+    /// Credentials are secret store handles, resolved by the backend.
     pub api_token: Option<String>,
     pub access_key_id: String,
     pub secret_access_key: String,
@@ -44,6 +64,10 @@ pub struct CurrentConfig {
     pub endpoint_scheme: Option<String>,
     pub endpoint_host: Option<String>,
     pub force_path_style: Option<bool>,
+    /// AWS only; `None` for other providers.
+    pub credentials: Option<CredentialSource>,
+    /// Capability report saved with the token or account.
+    pub capabilities: Option<CapabilityReport>,
 }
 
 /// Get SQL for creating token tables
@@ -56,6 +80,7 @@ pub fn get_table_sql() -> &'static str {
         api_token TEXT NOT NULL,
         access_key_id TEXT NOT NULL,
         secret_access_key TEXT NOT NULL,
+        capabilities TEXT,
         created_at INTEGER NOT NULL,
         updated_at INTEGER NOT NULL
     );
@@ -89,6 +114,7 @@ pub async fn create_token(
         api_token: api_token.to_string(),
         access_key_id: access_key_id.to_string(),
         secret_access_key: secret_access_key.to_string(),
+        capabilities: None,
         created_at: now,
         updated_at: now,
     })
@@ -98,7 +124,7 @@ pub async fn create_token(
 pub async fn get_token(id: i64) -> DbResult<Option<Token>> {
     let conn = get_connection()?.lock().await;
     let mut rows = conn.query(
-        "SELECT id, account_id, name, api_token, access_key_id, secret_access_key, created_at, updated_at
+        "SELECT id, account_id, name, api_token, access_key_id, secret_access_key, created_at, updated_at, capabilities
          FROM tokens WHERE id = ?1",
         turso::params![id]
     ).await?;
@@ -111,6 +137,7 @@ pub async fn get_token(id: i64) -> DbResult<Option<Token>> {
             api_token: row.get(3)?,
             access_key_id: row.get(4)?,
             secret_access_key: row.get(5)?,
+            capabilities: parse_capabilities(row.get(8)?),
             created_at: row.get(6)?,
             updated_at: row.get(7)?,
         }))
@@ -123,7 +150,7 @@ pub async fn get_token(id: i64) -> DbResult<Option<Token>> {
 pub async fn list_tokens_by_account(account_id: &str) -> DbResult<Vec<Token>> {
     let conn = get_connection()?.lock().await;
     let mut rows = conn.query(
-        "SELECT id, account_id, name, api_token, access_key_id, secret_access_key, created_at, updated_at
+        "SELECT id, account_id, name, api_token, access_key_id, secret_access_key, created_at, updated_at, capabilities
          FROM tokens WHERE account_id = ?1 ORDER BY created_at",
         turso::params![account_id]
     ).await?;
@@ -137,6 +164,7 @@ pub async fn list_tokens_by_account(account_id: &str) -> DbResult<Vec<Token>> {
             api_token: row.get(3)?,
             access_key_id: row.get(4)?,
             secret_access_key: row.get(5)?,
+            capabilities: parse_capabilities(row.get(8)?),
             created_at: row.get(6)?,
             updated_at: row.get(7)?,
         });
@@ -236,7 +264,7 @@ pub async fn get_current_config() -> DbResult<Option<CurrentConfig>> {
             let mut rows = conn
                 .query(
                     "SELECT t.id, t.account_id, t.name, t.api_token, t.access_key_id, t.secret_access_key,
-                            a.name as account_name
+                            a.name as account_name, t.capabilities
                      FROM tokens t
                      JOIN accounts a ON t.account_id = a.id
                      WHERE t.id = ?1",
@@ -287,6 +315,8 @@ pub async fn get_current_config() -> DbResult<Option<CurrentConfig>> {
                     endpoint_scheme: None,
                     endpoint_host: None,
                     force_path_style: None,
+                    credentials: None,
+                    capabilities: parse_capabilities(row.get(7)?),
                 }))
             } else {
                 Ok(None)
@@ -319,7 +349,7 @@ pub async fn get_current_config() -> DbResult<Option<CurrentConfig>> {
 
             let mut rows = conn
                 .query(
-                    "SELECT id, name, access_key_id, secret_access_key, region, endpoint_scheme, endpoint_host, force_path_style
+                    "SELECT id, name, access_key_id, secret_access_key, region, endpoint_scheme, endpoint_host, force_path_style, credential_source, profile_name, capabilities
                      FROM aws_accounts WHERE id = ?1",
                     turso::params![account_id.as_str()],
                 )
@@ -368,6 +398,11 @@ pub async fn get_current_config() -> DbResult<Option<CurrentConfig>> {
                     endpoint_scheme: Some(row.get(5)?),
                     endpoint_host: row.get(6)?,
                     force_path_style: Some(force_value != 0),
+                    credentials: Some(CredentialSource::from_columns(
+                        &row.get::<String>(8)?,
+                        row.get(9)?,
+                    )),
+                    capabilities: parse_capabilities(row.get(10)?),
                 }))
             } else {
                 Ok(None)
@@ -400,7 +435,7 @@ pub async fn get_current_config() -> DbResult<Option<CurrentConfig>> {
 
             let mut rows = conn
                 .query(
-                    "SELECT id, name, access_key_id, secret_access_key, endpoint_scheme, endpoint_host, force_path_style
+                    "SELECT id, name, access_key_id, secret_access_key, endpoint_scheme, endpoint_host, force_path_style, capabilities
                      FROM minio_accounts WHERE id = ?1",
                     turso::params![account_id.as_str()],
                 )
@@ -449,6 +484,8 @@ pub async fn get_current_config() -> DbResult<Option<CurrentConfig>> {
                     endpoint_scheme: Some(row.get(4)?),
                     endpoint_host: Some(row.get(5)?),
                     force_path_style: Some(force_value != 0),
+                    credentials: None,
+                    capabilities: parse_capabilities(row.get(7)?),
                 }))
             } else {
                 Ok(None)
@@ -481,7 +518,7 @@ pub async fn get_current_config() -> DbResult<Option<CurrentConfig>> {
 
             let mut rows = conn
                 .query(
-                    "SELECT id, name, access_key_id, secret_access_key, endpoint_scheme, endpoint_host, force_path_style
+                    "SELECT id, name, access_key_id, secret_access_key, endpoint_scheme, endpoint_host, force_path_style, capabilities
                      FROM rustfs_accounts WHERE id = ?1",
                     turso::params![account_id.as_str()],
                 )
@@ -530,6 +567,8 @@ pub async fn get_current_config() -> DbResult<Option<CurrentConfig>> {
                     endpoint_scheme: Some(row.get(4)?),
                     endpoint_host: Some(row.get(5)?),
                     force_path_style: Some(force_value != 0),
+                    credentials: None,
+                    capabilities: parse_capabilities(row.get(7)?),
                 }))
             } else {
                 Ok(None)
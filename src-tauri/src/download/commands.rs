@@ -1,6 +1,8 @@
 //! Download Tauri commands
 
-use crate::db::{self, DownloadSession};
+use crate::commands::bucket_encryption::resolve_bucket_encryption;
+use crate::commands::client_encryption::resolve_client_key;
+use crate::db::{self, CacheScope, DownloadSession};
 use crate::providers::{aws, minio, rustfs};
 use chrono::Utc;
 use serde::Deserialize;
@@ -24,9 +26,25 @@ pub struct DownloadConfigInput {
     pub endpoint_scheme: Option<String>,
     pub endpoint_host: Option<String>,
     pub force_path_style: Option<bool>,
+    #[serde(default)]
+    pub credentials: aws::CredentialSource,
 }
 
-fn build_download_config(input: &DownloadConfigInput) -> Result<DownloadConfig, String> {
+impl DownloadConfigInput {
+    fn cache_scope(&self) -> CacheScope {
+        CacheScope::new(
+            &self.provider,
+            self.endpoint_scheme.as_deref(),
+            self.endpoint_host.as_deref(),
+            &self.account_id,
+            &self.bucket,
+        )
+    }
+}
+
+async fn build_download_config(input: &DownloadConfigInput) -> Result<DownloadConfig, String> {
+    let encryption =
+        resolve_bucket_encryption(&input.provider, &input.account_id, &input.bucket).await?;
     match input.provider.as_str() {
         "aws" => {
             let region = input
@@ -42,13 +60,16 @@ fn build_download_config(input: &DownloadConfigInput) -> Result<DownloadConfig,
                 endpoint_scheme: input.endpoint_scheme.clone(),
                 endpoint_host: input.endpoint_host.clone(),
                 force_path_style: input.force_path_style.unwrap_or(false),
+                credentials: input.credentials.clone(),
+                encryption,
+                storage_class: None,
             }))
         }
         "minio" => {
             let endpoint_scheme = input
                 .endpoint_scheme
                 .clone()
-                .unwrap_or_else(|| "https".to_string());
+                .unwrap_or_else(|| db::default_endpoint_scheme(&input.provider).to_string());
             let endpoint_host = input
                 .endpoint_host
                 .clone()
@@ -60,13 +81,14 @@ fn build_download_config(input: &DownloadConfigInput) -> Result<DownloadConfig,
                 endpoint_scheme,
                 endpoint_host,
                 force_path_style: input.force_path_style.unwrap_or(true),
+                encryption,
             }))
         }
         "rustfs" => {
             let endpoint_scheme = input
                 .endpoint_scheme
                 .clone()
-                .unwrap_or_else(|| "https".to_string());
+                .unwrap_or_else(|| db::default_endpoint_scheme(&input.provider).to_string());
             let endpoint_host = input
                 .endpoint_host
                 .clone()
@@ -78,6 +100,7 @@ fn build_download_config(input: &DownloadConfigInput) -> Result<DownloadConfig,
                 endpoint_scheme,
                 endpoint_host,
                 force_path_style: true,
+                encryption,
             }))
         }
         "r2" => Ok(DownloadConfig::R2(crate::r2::R2Config {
@@ -85,6 +108,7 @@ fn build_download_config(input: &DownloadConfigInput) -> Result<DownloadConfig,
             bucket: input.bucket.clone(),
             access_key_id: input.access_key_id.clone(),
             secret_access_key: input.secret_access_key.clone(),
+            encryption,
         })),
         _ => Err(format!("Unsupported provider: {}", input.provider)),
     }
@@ -101,10 +125,21 @@ pub async fn create_download_task(
     local_path: String,
     bucket: String,
     account_id: String,
+    provider: String,
+    endpoint_scheme: Option<String>,
+    endpoint_host: Option<String>,
 ) -> Result<(), String> {
+    let scope = CacheScope::new(
+        &provider,
+        endpoint_scheme.as_deref(),
+        endpoint_host.as_deref(),
+        &account_id,
+        &bucket,
+    );
+
     // If file_size is 0, try to get it from the file cache
     let actual_file_size = if file_size == 0 {
-        db::get_cached_file_size(&bucket, &account_id, &object_key)
+        db::get_cached_file_size(&scope, &object_key)
             .await
             .unwrap_or(0)
     } else {
@@ -127,7 +162,7 @@ pub async fn create_download_task(
         updated_at: now,
     };
 
-    db::create_download_session(&session)
+    db::create_download_session(&scope, &session)
         .await
         .map_err(|e| format!("Failed to create download session: {}", e))
 }
@@ -138,18 +173,21 @@ pub async fn start_download_queue(
     app: AppHandle,
     config: DownloadConfigInput,
 ) -> Result<i64, String> {
-    let download_config = build_download_config(&config)?;
+    let download_config = build_download_config(&config).await?;
+    let client_key =
+        resolve_client_key(&config.provider, &config.account_id, &config.bucket).await?;
 
     // Get sessions to start (this updates their status in DB and emits events)
-    let sessions = get_pending_sessions_to_start(&app, &config.bucket, &config.account_id).await?;
+    let sessions = get_pending_sessions_to_start(&app, &config.cache_scope()).await?;
     let started_count = sessions.len() as i64;
 
     // Spawn download tasks for each session
     for session in sessions {
         let app_clone = app.clone();
         let config_clone = download_config.clone();
+        let client_key = client_key.clone();
         tokio::spawn(async move {
-            spawn_download_task(app_clone, session, config_clone).await;
+            spawn_download_task(app_clone, session, config_clone, client_key).await;
         });
     }
 
@@ -163,7 +201,8 @@ pub async fn start_all_downloads(
     config: DownloadConfigInput,
 ) -> Result<i64, String> {
     // First, set all paused tasks to pending in DB
-    let resumed_count = db::resume_all_downloads(&config.bucket, &config.account_id)
+    let scope = config.cache_scope();
+    let resumed_count = db::resume_all_downloads(&scope)
         .await
         .map_err(|e| format!("Failed to resume downloads: {}", e))?;
 
@@ -178,14 +217,17 @@ pub async fn start_all_downloads(
     );
 
     // Then get sessions to start and spawn tasks
-    let download_config = build_download_config(&config)?;
-    let sessions = get_pending_sessions_to_start(&app, &config.bucket, &config.account_id).await?;
+    let download_config = build_download_config(&config).await?;
+    let client_key =
+        resolve_client_key(&config.provider, &config.account_id, &config.bucket).await?;
+    let sessions = get_pending_sessions_to_start(&app, &scope).await?;
 
     for session in sessions {
         let app_clone = app.clone();
         let config_clone = download_config.clone();
+        let client_key = client_key.clone();
         tokio::spawn(async move {
-            spawn_download_task(app_clone, session, config_clone).await;
+            spawn_download_task(app_clone, session, config_clone, client_key).await;
         });
     }
 
@@ -198,7 +240,18 @@ pub async fn pause_all_downloads(
     app: AppHandle,
     bucket: String,
     account_id: String,
+    provider: String,
+    endpoint_scheme: Option<String>,
+    endpoint_host: Option<String>,
 ) -> Result<i64, String> {
+    let scope = CacheScope::new(
+        &provider,
+        endpoint_scheme.as_deref(),
+        endpoint_host.as_deref(),
+        &account_id,
+        &bucket,
+    );
+
     // Set pause flag for all active downloads
     {
         let registry = DOWNLOAD_PAUSE_REGISTRY.lock().await;
@@ -208,7 +261,7 @@ pub async fn pause_all_downloads(
     }
 
     // Also update DB directly for any that might not be in registry
-    let paused_count = db::pause_all_downloads(&bucket, &account_id)
+    let paused_count = db::pause_all_downloads(&scope)
         .await
         .map_err(|e| format!("Failed to pause downloads: {}", e))?;
 
@@ -321,8 +374,18 @@ pub async fn delete_download_task(app: AppHandle, task_id: String) -> Result<(),
 pub async fn get_download_tasks(
     bucket: String,
     account_id: String,
+    provider: String,
+    endpoint_scheme: Option<String>,
+    endpoint_host: Option<String>,
 ) -> Result<Vec<DownloadSession>, String> {
-    db::get_download_sessions_for_bucket(&bucket, &account_id)
+    let scope = CacheScope::new(
+        &provider,
+        endpoint_scheme.as_deref(),
+        endpoint_host.as_deref(),
+        &account_id,
+        &bucket,
+    );
+    db::get_download_sessions_for_bucket(&scope)
         .await
         .map_err(|e| format!("Failed to get download tasks: {}", e))
 }
@@ -333,8 +396,18 @@ pub async fn clear_finished_downloads(
     app: AppHandle,
     bucket: String,
     account_id: String,
+    provider: String,
+    endpoint_scheme: Option<String>,
+    endpoint_host: Option<String>,
 ) -> Result<i64, String> {
-    let deleted_count = db::delete_finished_downloads(&bucket, &account_id)
+    let scope = CacheScope::new(
+        &provider,
+        endpoint_scheme.as_deref(),
+        endpoint_host.as_deref(),
+        &account_id,
+        &bucket,
+    );
+    let deleted_count = db::delete_finished_downloads(&scope)
         .await
         .map_err(|e| format!("Failed to clear finished downloads: {}", e))?;
 
@@ -357,9 +430,20 @@ pub async fn clear_all_downloads(
     app: AppHandle,
     bucket: String,
     account_id: String,
+    provider: String,
+    endpoint_scheme: Option<String>,
+    endpoint_host: Option<String>,
 ) -> Result<i64, String> {
+    let scope = CacheScope::new(
+        &provider,
+        endpoint_scheme.as_deref(),
+        endpoint_host.as_deref(),
+        &account_id,
+        &bucket,
+    );
+
     // Check if there are active downloads
-    let active_count = db::count_active_downloads(&bucket, &account_id)
+    let active_count = db::count_active_downloads(&scope)
         .await
         .map_err(|e| format!("Failed to count active downloads: {}", e))?;
 
@@ -367,7 +451,7 @@ pub async fn clear_all_downloads(
         return Err("Cannot clear all downloads while downloads are active".to_string());
     }
 
-    let deleted_count = db::delete_all_downloads(&bucket, &account_id)
+    let deleted_count = db::delete_all_downloads(&scope)
         .await
         .map_err(|e| format!("Failed to clear all downloads: {}", e))?;
 
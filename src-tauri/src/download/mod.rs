@@ -6,7 +6,9 @@
 //! - Database persistence for resume after app restart
 //! - Pause/Resume/Cancel support
 //! - Backend-managed download queue with concurrency control
+//! - Waiting on archive restores before downloading Glacier objects
 
 pub mod commands;
+pub(crate) mod restore;
 mod types;
 mod worker;
@@ -0,0 +1,188 @@
+//! Archive restore tracking for the download queue
+//!
+//! Downloads of archived AWS objects (Glacier / Deep Archive) wait in
+//! `waiting-restore` while a background task polls HEAD `x-amz-restore` with
+//! backoff. Once the restore finishes they go back to `pending`.
+
+use crate::db::{self, CacheScope, DownloadSession};
+use crate::providers::aws;
+use crate::providers::storage_class::{may_be_archived, ArchiveState, RestoreStatus};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+use super::types::DownloadStatusChanged;
+
+/// Expedited restores finish within minutes, bulk ones can take two days
+const FIRST_POLL_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(30 * 60);
+/// Consecutive failed HEAD requests before a watcher gives up
+const MAX_POLL_FAILURES: u32 = 10;
+
+lazy_static::lazy_static! {
+    /// Bucket scope and key of objects that already have a watcher
+    static ref RESTORE_WATCHERS: Mutex<HashSet<(CacheScope, String)>> = Mutex::new(HashSet::new());
+}
+
+/// `restore-status-changed` event payload
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreStatusChanged {
+    pub account_id: String,
+    pub bucket: String,
+    pub key: String,
+    pub state: ArchiveState,
+}
+
+/// Store a freshly read state on the tracked restore, notify the frontend and
+/// requeue downloads that were waiting for it.
+pub(crate) async fn record_archive_state(
+    app: &AppHandle,
+    scope: &CacheScope,
+    key: &str,
+    state: &ArchiveState,
+) {
+    if let Err(e) = db::update_object_restore_status(
+        scope,
+        key,
+        state.status.as_str(),
+        state.expiry_date.as_deref(),
+    )
+    .await
+    {
+        log::warn!("Failed to save restore status of {}: {}", key, e);
+    }
+
+    let _ = app.emit(
+        "restore-status-changed",
+        RestoreStatusChanged {
+            account_id: scope.account_id().to_string(),
+            bucket: scope.bucket().to_string(),
+            key: key.to_string(),
+            state: state.clone(),
+        },
+    );
+
+    // Readable now, or the restore lapsed: either way waiting no longer helps.
+    // A lapsed one fails on its next start with a restore hint.
+    if state.status == RestoreStatus::InProgress {
+        return;
+    }
+    match db::release_restored_downloads(scope, key).await {
+        Ok(ids) => {
+            for task_id in ids {
+                let _ = app.emit(
+                    "download-status-changed",
+                    DownloadStatusChanged {
+                        task_id,
+                        status: "pending".to_string(),
+                        error: None,
+                    },
+                );
+            }
+        }
+        Err(e) => log::warn!("Failed to requeue downloads of {}: {}", key, e),
+    }
+}
+
+/// Poll an object's restore in the background until it is no longer in
+/// progress. At most one watcher runs per object.
+pub(crate) fn watch_restore(
+    app: AppHandle,
+    config: aws::AwsConfig,
+    scope: CacheScope,
+    key: String,
+) {
+    tokio::spawn(async move {
+        let watch_key = (scope.clone(), key.clone());
+        if !RESTORE_WATCHERS.lock().await.insert(watch_key.clone()) {
+            return;
+        }
+
+        let mut interval = FIRST_POLL_INTERVAL;
+        let mut failures = 0;
+        loop {
+            tokio::time::sleep(interval).await;
+            match aws::head_archive_state(&config, &key).await {
+                Ok(state) => {
+                    failures = 0;
+                    record_archive_state(&app, &scope, &key, &state).await;
+                    if state.status != RestoreStatus::InProgress {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    failures += 1;
+                    log::warn!("Failed to check restore status of {}: {}", key, e);
+                    if failures >= MAX_POLL_FAILURES {
+                        break;
+                    }
+                }
+            }
+            interval = (interval * 2).min(MAX_POLL_INTERVAL);
+        }
+
+        RESTORE_WATCHERS.lock().await.remove(&watch_key);
+    });
+}
+
+/// Whether an AWS download can start now. An archived object with a restore
+/// in progress parks the session in `waiting-restore`; one that was never
+/// restored fails with a hint instead of the raw GET error.
+pub(crate) async fn ensure_readable(
+    app: &AppHandle,
+    config: &aws::AwsConfig,
+    session: &DownloadSession,
+) -> Result<bool, String> {
+    // Skip the HEAD when the cache knows the class can't be archived
+    let scope = CacheScope::new(
+        "aws",
+        config.endpoint_scheme.as_deref(),
+        config.endpoint_host.as_deref(),
+        &session.account_id,
+        &session.bucket,
+    );
+    let cached_class = db::get_cached_storage_class(&scope, &session.object_key)
+        .await
+        .ok()
+        .flatten();
+    if cached_class
+        .as_deref()
+        .is_some_and(|class| !may_be_archived(class))
+    {
+        return Ok(true);
+    }
+
+    let state = aws::head_archive_state(config, &session.object_key)
+        .await
+        .map_err(|e| format!("Failed to check object storage class: {}", e))?;
+    if state.readable() {
+        return Ok(true);
+    }
+    match state.status {
+        RestoreStatus::InProgress => {
+            let _ = db::update_download_status(&session.id, "waiting-restore", None).await;
+            let _ = app.emit(
+                "download-status-changed",
+                DownloadStatusChanged {
+                    task_id: session.id.clone(),
+                    status: "waiting-restore".to_string(),
+                    error: None,
+                },
+            );
+            watch_restore(
+                app.clone(),
+                config.clone(),
+                scope,
+                session.object_key.clone(),
+            );
+            Ok(false)
+        }
+        _ => Err(format!(
+            "'{}' is archived in {}; restore it before downloading",
+            session.object_key,
+            state.storage_class.as_deref().unwrap_or("an archive tier")
+        )),
+    }
+}
@@ -4,13 +4,15 @@
 //! - Files < 10MB: single-stream download (original path)
 //! - Files >= 10MB: multi-chunk parallel download via range-dl crate
 
-use crate::db::{self, DownloadSession};
+use crate::client_encryption::{open_file, ClientKey, Envelope};
+use crate::db::{self, CacheScope, DownloadSession};
+use crate::providers::encryption::read_headers;
 use crate::providers::{aws, minio, rustfs};
 use crate::r2::R2Config;
 use crate::transfer_progress::SpeedWindow;
 use reqwest::Client;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
@@ -18,6 +20,7 @@ use tokio::fs::{File, OpenOptions};
 use tokio::io::{AsyncSeekExt, AsyncWriteExt, SeekFrom};
 use tokio::sync::Mutex;
 
+use super::restore::ensure_readable;
 use super::types::{
     ChunkProgressInfo, DownloadChunkProgressEvent, DownloadProgress, DownloadStatusChanged,
     MAX_CONCURRENT_DOWNLOADS,
@@ -31,6 +34,97 @@ pub(crate) enum DownloadConfig {
     Rustfs(rustfs::RustfsConfig),
 }
 
+impl DownloadConfig {
+    /// SSE-C headers the object's GET requests must carry, if any.
+    fn read_headers(&self) -> Vec<(String, String)> {
+        let encryption = match self {
+            DownloadConfig::R2(cfg) => cfg.encryption.as_ref(),
+            DownloadConfig::Aws(cfg) => cfg.encryption.as_ref(),
+            DownloadConfig::Minio(cfg) | DownloadConfig::Rustfs(cfg) => cfg.encryption.as_ref(),
+        };
+        read_headers(encryption)
+    }
+}
+
+/// A client-side encrypted object. It is downloaded to `ciphertext` and
+/// decrypted into `destination` once the transfer completes.
+pub(crate) struct ClientDecryption {
+    ciphertext: PathBuf,
+    destination: PathBuf,
+    envelope: Envelope,
+    key: ClientKey,
+}
+
+/// Side file the ciphertext of an encrypted object is downloaded to.
+fn ciphertext_path(destination: &Path) -> PathBuf {
+    let mut name = destination.file_name().unwrap_or_default().to_os_string();
+    name.push(".cse");
+    destination.with_file_name(name)
+}
+
+/// Read the client-side encryption envelope from the object's metadata with a
+/// one-byte ranged GET; `None` for objects uploaded without it.
+async fn probe_envelope(config: &DownloadConfig, key: &str) -> Result<Option<Envelope>, String> {
+    let presigned_url = generate_presigned_url_for_config(config, key, 3600).await?;
+    let client = Client::builder()
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let mut request = client.get(&presigned_url).header("Range", "bytes=0-0");
+    for (name, value) in config.read_headers() {
+        request = request.header(name, value);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to read object metadata: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to read object metadata: {}",
+            response.status()
+        ));
+    }
+    Envelope::from_headers(response.headers())
+}
+
+/// Mark a download completed, decrypting it into place first when the object
+/// is client-side encrypted.
+async fn complete_download(
+    app: &AppHandle,
+    task_id: &str,
+    downloaded_bytes: u64,
+    decryption: Option<&ClientDecryption>,
+) -> Result<(), String> {
+    if let Some(decryption) = decryption {
+        // Already decrypted if completion was reported twice
+        if decryption.ciphertext.exists() {
+            open_file(
+                &decryption.ciphertext,
+                &decryption.destination,
+                &decryption.envelope,
+                &decryption.key,
+            )
+            .await?;
+            let _ = tokio::fs::remove_file(&decryption.ciphertext).await;
+        }
+    }
+
+    let _ = db::update_download_progress(task_id, downloaded_bytes as i64).await;
+    let _ = db::update_download_status(task_id, "completed", None).await;
+    let _ = app.emit(
+        "download-status-changed",
+        DownloadStatusChanged {
+            task_id: task_id.to_string(),
+            status: "completed".to_string(),
+            error: None,
+        },
+    );
+
+    // Emit download-complete event for queue management
+    let _ = app.emit("download-complete", task_id.to_string());
+
+    Ok(())
+}
+
 /// Write buffer size for downloads (2 MB) - reduces I/O operations
 const WRITE_BUFFER_SIZE: usize = 2 * 1024 * 1024;
 const DOWNLOAD_CANCELLED_ERROR: &str = "Download cancelled";
@@ -54,6 +148,7 @@ pub(crate) async fn download_file_internal(
     app: &AppHandle,
     cancelled: &Arc<AtomicBool>,
     paused: &Arc<AtomicBool>,
+    decryption: Option<&ClientDecryption>,
 ) -> Result<(), String> {
     // Emit initial progress to show download task has started
     let _ = app.emit(
@@ -82,6 +177,9 @@ pub(crate) async fn download_file_internal(
 
     // Start the download request with range header if resuming
     let mut request = client.get(&presigned_url);
+    for (name, value) in config.read_headers() {
+        request = request.header(name, value);
+    }
     if existing_bytes > 0 {
         request = request.header("Range", format!("bytes={}-", existing_bytes));
     }
@@ -299,24 +397,7 @@ pub(crate) async fn download_file_internal(
         },
     );
 
-    // Update DB with completed status
-    let _ = db::update_download_progress(task_id, final_downloaded as i64).await;
-    let _ = db::update_download_status(task_id, "completed", None).await;
-
-    // Emit status change event for completion
-    let _ = app.emit(
-        "download-status-changed",
-        DownloadStatusChanged {
-            task_id: task_id.to_string(),
-            status: "completed".to_string(),
-            error: None,
-        },
-    );
-
-    // Emit download-complete event for queue management
-    let _ = app.emit("download-complete", task_id.to_string());
-
-    Ok(())
+    complete_download(app, task_id, final_downloaded, decryption).await
 }
 
 /// Minimum file size (10 MB) to use chunked parallel download.
@@ -355,6 +436,7 @@ pub(crate) async fn download_file_chunked(
     task_id: &str,
     file_size: u64,
     app: &AppHandle,
+    decryption: Option<&ClientDecryption>,
 ) -> Result<(), String> {
     use range_dl::{ChunkEvent, DownloadTarget, RangeDownloadConfig, RangeDownloader};
 
@@ -367,7 +449,10 @@ pub(crate) async fn download_file_chunked(
         Box::pin(async move { generate_presigned_url_for_config(&cfg, &key, 3600).await })
     });
 
-    let dl_config = RangeDownloadConfig::default();
+    let dl_config = RangeDownloadConfig {
+        headers: config.read_headers(),
+        ..Default::default()
+    };
     let target = DownloadTarget {
         file_size,
         destination: destination.clone(),
@@ -527,21 +612,8 @@ pub(crate) async fn download_file_chunked(
                     },
                 );
 
-                // Update DB
-                let _ = db::update_download_progress(&task_id_owned, total_bytes as i64).await;
-                let _ = db::update_download_status(&task_id_owned, "completed", None).await;
-
-                // Emit status change
-                let _ = app.emit(
-                    "download-status-changed",
-                    DownloadStatusChanged {
-                        task_id: task_id_owned.clone(),
-                        status: "completed".to_string(),
-                        error: None,
-                    },
-                );
-                let _ = app.emit("download-complete", task_id_owned.clone());
-                break;
+                cleanup_registries(&task_id_owned).await;
+                return complete_download(app, &task_id_owned, total_bytes, decryption).await;
             }
             ChunkEvent::Paused { chunks_state: _ } => {
                 let _ = db::update_download_status(&task_id_owned, "paused", None).await;
@@ -573,20 +645,9 @@ pub(crate) async fn download_file_chunked(
                         .unwrap_or(0);
                     if actual_size > 0 && (file_size == 0 || actual_size == file_size) {
                         // File exists at expected size — treat as completed
-                        let _ =
-                            db::update_download_progress(&task_id_owned, actual_size as i64).await;
-                        let _ = db::update_download_status(&task_id_owned, "completed", None).await;
-                        let _ = app.emit(
-                            "download-status-changed",
-                            DownloadStatusChanged {
-                                task_id: task_id_owned.clone(),
-                                status: "completed".to_string(),
-                                error: None,
-                            },
-                        );
-                        let _ = app.emit("download-complete", task_id_owned.clone());
                         cleanup_registries(&task_id_owned).await;
-                        return Ok(());
+                        return complete_download(app, &task_id_owned, actual_size, decryption)
+                            .await;
                     }
                 }
                 // Actually cancelled
@@ -618,18 +679,7 @@ pub(crate) async fn download_file_chunked(
                 "Download {}: file exists at destination, treating as success",
                 task_id
             );
-            let _ = db::update_download_progress(task_id, actual_size as i64).await;
-            let _ = db::update_download_status(task_id, "completed", None).await;
-            let _ = app.emit(
-                "download-status-changed",
-                DownloadStatusChanged {
-                    task_id: task_id.to_string(),
-                    status: "completed".to_string(),
-                    error: None,
-                },
-            );
-            let _ = app.emit("download-complete", task_id.to_string());
-            return Ok(());
+            return complete_download(app, task_id, actual_size, decryption).await;
         }
     }
     Err("Download ended unexpectedly without completion event".to_string())
@@ -661,11 +711,63 @@ pub(crate) async fn spawn_download_task(
     app: AppHandle,
     session: DownloadSession,
     config: DownloadConfig,
+    client_key: Option<ClientKey>,
 ) {
     let task_id = session.id.clone();
     let file_size = session.file_size as u64;
     let destination = PathBuf::from(&session.local_path).join(&session.file_name);
 
+    // Archived objects wait for their restore instead of failing on GET
+    if let DownloadConfig::Aws(cfg) = &config {
+        match ensure_readable(&app, cfg, &session).await {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                let _ = db::update_download_status(&task_id, "failed", Some(&e)).await;
+                let _ = app.emit(
+                    "download-status-changed",
+                    DownloadStatusChanged {
+                        task_id: task_id.clone(),
+                        status: "failed".to_string(),
+                        error: Some(e),
+                    },
+                );
+                return;
+            }
+        }
+    }
+
+    // Client-side encrypted objects are fetched to a side file and decrypted
+    // when the transfer completes
+    let decryption = match client_key {
+        Some(key) => match probe_envelope(&config, &session.object_key).await {
+            Ok(envelope) => envelope.map(|envelope| ClientDecryption {
+                ciphertext: ciphertext_path(&destination),
+                destination: destination.clone(),
+                envelope,
+                key,
+            }),
+            Err(e) => {
+                let _ = db::update_download_status(&task_id, "failed", Some(&e)).await;
+                let _ = app.emit(
+                    "download-status-changed",
+                    DownloadStatusChanged {
+                        task_id: task_id.clone(),
+                        status: "failed".to_string(),
+                        error: Some(e),
+                    },
+                );
+                return;
+            }
+        },
+        None => None,
+    };
+    let download_path = decryption
+        .as_ref()
+        .map_or(destination.clone(), |decryption| {
+            decryption.ciphertext.clone()
+        });
+
     // Dispatch based on file size
     let result = if file_size >= CHUNKED_DOWNLOAD_THRESHOLD {
         // Multi-chunk parallel download via range-dl
@@ -677,10 +779,11 @@ pub(crate) async fn spawn_download_task(
         download_file_chunked(
             &config,
             &session.object_key,
-            &destination,
+            &download_path,
             &task_id,
             file_size,
             &app,
+            decryption.as_ref(),
         )
         .await
     } else {
@@ -721,12 +824,13 @@ pub(crate) async fn spawn_download_task(
             &client,
             &config,
             &session.object_key,
-            &destination,
+            &download_path,
             &task_id,
             file_size,
             &app,
             &cancelled,
             &paused,
+            decryption.as_ref(),
         )
         .await;
 
@@ -755,11 +859,10 @@ pub(crate) async fn spawn_download_task(
 /// Internal function to process download queue - returns sessions to start
 pub(crate) async fn get_pending_sessions_to_start(
     app: &AppHandle,
-    bucket: &str,
-    account_id: &str,
+    scope: &CacheScope,
 ) -> Result<Vec<DownloadSession>, String> {
     // Count currently active downloads
-    let active_count = db::count_active_downloads(bucket, account_id)
+    let active_count = db::count_active_downloads(scope)
         .await
         .map_err(|e| format!("Failed to count active downloads: {}", e))?;
 
@@ -769,7 +872,7 @@ pub(crate) async fn get_pending_sessions_to_start(
     }
 
     // Get pending tasks up to available slots
-    let pending = db::get_pending_downloads(bucket, account_id, slots_available)
+    let pending = db::get_pending_downloads(scope, slots_available)
         .await
         .map_err(|e| format!("Failed to get pending downloads: {}", e))?;
 
@@ -9,12 +9,16 @@ use tauri::{
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
 
 mod account;
+mod account_bundle;
+mod client_encryption;
 mod commands;
+mod crypto;
 mod db;
 mod download;
 mod move_transfer;
 mod providers;
 mod r2;
+mod secrets;
 mod transfer_progress;
 mod upload;
 
@@ -128,6 +132,7 @@ pub fn run() {
             std::fs::create_dir_all(&app_data_dir).expect("Failed to create app data dir");
 
             let db_path = app_data_dir.join("uploads-turso.db");
+            secrets::init(&app_data_dir);
 
             let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
             let app_handle = app.handle();
@@ -156,6 +161,12 @@ pub fn run() {
                     eprintln!("Failed to cleanup old sessions: {}", e);
                 }
 
+                // Move plaintext credentials into the keychain (the file
+                // store waits for its passphrase)
+                if let Err(e) = secrets::migrate_plaintext_credentials().await {
+                    eprintln!("Failed to migrate credentials to the secret store: {}", e);
+                }
+
                 // Default queue behavior on restart: pause all in-progress download/move tasks.
                 if let Err(e) = db::downloads::pause_stale_downloads_on_startup().await {
                     eprintln!("Failed to pause stale download tasks on startup: {}", e);
@@ -297,6 +308,9 @@ pub fn run() {
             account::create_aws_account,
             account::update_aws_account,
             account::delete_aws_account,
+            account::list_aws_profiles,
+            account::import_aws_profiles,
+            account::start_aws_mfa_session,
             account::list_minio_accounts,
             account::create_minio_account,
             account::update_minio_account,
@@ -334,6 +348,9 @@ pub fn run() {
             account::get_all_aws_accounts_with_buckets,
             account::get_all_minio_accounts_with_buckets,
             account::get_all_rustfs_accounts_with_buckets,
+            // Account export / import commands
+            account_bundle::export_accounts,
+            account_bundle::import_accounts,
             // R2 commands
             commands::list_r2_buckets,
             commands::list_r2_objects,
@@ -346,6 +363,11 @@ pub fn run() {
             commands::generate_signed_url,
             commands::upload_r2_content,
             commands::sync_bucket,
+            commands::get_r2_public_access,
+            commands::set_r2_managed_domain,
+            commands::add_r2_custom_domain,
+            commands::set_r2_custom_domain_enabled,
+            commands::remove_r2_custom_domain,
             // AWS commands
             commands::list_aws_buckets,
             commands::list_aws_objects,
@@ -385,14 +407,67 @@ pub fn run() {
             commands::upload_rustfs_content,
             commands::upload_rustfs_file,
             commands::sync_rustfs_bucket,
+            // Bucket administration commands
+            commands::create_storage_bucket,
+            commands::delete_storage_bucket,
+            commands::get_bucket_cors,
+            commands::put_bucket_cors,
+            // Bucket encryption commands
+            commands::get_bucket_encryption_settings,
+            commands::set_bucket_encryption_settings,
+            commands::export_bucket_customer_key,
+            // Client-side encryption commands
+            commands::get_client_encryption_settings,
+            commands::set_client_encryption_settings,
+            commands::create_client_key_file,
+            // Archive restore commands
+            commands::restore_aws_object,
+            commands::get_aws_restore_status,
+            commands::list_aws_object_restores,
+            commands::forget_aws_object_restore,
+            // Folder (prefix) operations
+            commands::delete_prefix,
+            commands::copy_prefix,
+            commands::rename_prefix,
+            commands::cancel_prefix_operation,
+            // Presigned request commands
+            commands::generate_presigned_request,
+            commands::get_presign_max_expiry,
+            commands::list_shared_links,
+            commands::revoke_shared_link,
+            commands::delete_shared_link,
+            // Tagging & lifecycle commands
+            commands::get_object_tags,
+            commands::put_object_tags,
+            commands::delete_object_tags,
+            commands::batch_tag_objects,
+            commands::get_bucket_lifecycle,
+            commands::put_bucket_lifecycle,
+            commands::delete_bucket_lifecycle,
+            // Object Lock commands
+            commands::get_bucket_object_lock,
+            commands::get_object_lock,
+            commands::put_object_retention,
+            commands::put_object_legal_hold,
+            // Credential validation commands
+            commands::validate_credentials,
+            // Secret store commands
+            commands::get_secret_store_status,
+            commands::unlock_secret_store,
+            commands::lock_secret_store,
             // Lazy sync commands
             commands::lazy_sync::list_prefix,
             commands::lazy_sync::start_background_sync,
             commands::lazy_sync::cancel_background_sync,
+            commands::sync_manager::list_sync_jobs,
+            commands::prefix_watch::start_prefix_watch,
+            commands::prefix_watch::set_watched_prefixes,
+            commands::prefix_watch::stop_prefix_watch,
             // Cache commands
             commands::store_all_files,
             commands::get_all_cached_files,
             commands::search_cached_files,
+            commands::search_files,
             commands::get_bucket_summary,
             commands::calculate_folder_size,
             commands::build_directory_tree,
@@ -400,6 +475,19 @@ pub fn run() {
             commands::get_all_directory_nodes,
             commands::clear_file_cache,
             commands::get_folder_contents,
+            commands::get_cached_file,
+            commands::find_cached_files_by_etag,
+            commands::get_cached_files_by_storage_class,
+            commands::get_storage_class_usage,
+            commands::get_largest_prefixes,
+            commands::get_largest_files,
+            commands::get_type_breakdown,
+            commands::get_age_histogram,
+            commands::get_storage_history,
+            commands::estimate_storage_cost,
+            commands::fetch_object_metadata,
+            commands::find_duplicates,
+            commands::plan_duplicate_cleanup,
             commands::fetch_url_bytes,
             // Download commands
             download::commands::create_download_task,
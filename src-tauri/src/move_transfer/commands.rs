@@ -1,6 +1,7 @@
 //! Move transfer Tauri commands
 
-use crate::db::{self, MoveSession};
+use crate::commands::bucket_encryption::resolve_bucket_encryption;
+use crate::db::{self, CacheScope, MoveSession};
 use chrono::Utc;
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
@@ -25,6 +26,20 @@ pub struct MoveConfigInput {
     pub endpoint_scheme: Option<String>,
     pub endpoint_host: Option<String>,
     pub force_path_style: Option<bool>,
+    #[serde(default)]
+    pub credentials: crate::providers::aws::CredentialSource,
+}
+
+impl MoveConfigInput {
+    fn cache_scope(&self) -> CacheScope {
+        CacheScope::new(
+            &self.provider,
+            self.endpoint_scheme.as_deref(),
+            self.endpoint_host.as_deref(),
+            &self.account_id,
+            &self.bucket,
+        )
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -38,7 +53,9 @@ pub struct StartMoveResult {
     pub created: i64,
 }
 
-fn build_move_config(input: &MoveConfigInput) -> Result<MoveConfig, String> {
+async fn build_move_config(input: &MoveConfigInput) -> Result<MoveConfig, String> {
+    let encryption =
+        resolve_bucket_encryption(&input.provider, &input.account_id, &input.bucket).await?;
     match input.provider.as_str() {
         "aws" => {
             let region = input
@@ -54,13 +71,16 @@ fn build_move_config(input: &MoveConfigInput) -> Result<MoveConfig, String> {
                 endpoint_scheme: input.endpoint_scheme.clone(),
                 endpoint_host: input.endpoint_host.clone(),
                 force_path_style: input.force_path_style.unwrap_or(false),
+                credentials: input.credentials.clone(),
+                encryption,
+                storage_class: None,
             }))
         }
         "minio" => {
             let endpoint_scheme = input
                 .endpoint_scheme
                 .clone()
-                .unwrap_or_else(|| "https".to_string());
+                .unwrap_or_else(|| db::default_endpoint_scheme(&input.provider).to_string());
             let endpoint_host = input
                 .endpoint_host
                 .clone()
@@ -72,13 +92,14 @@ fn build_move_config(input: &MoveConfigInput) -> Result<MoveConfig, String> {
                 endpoint_scheme,
                 endpoint_host,
                 force_path_style: input.force_path_style.unwrap_or(true),
+                encryption,
             }))
         }
         "rustfs" => {
             let endpoint_scheme = input
                 .endpoint_scheme
                 .clone()
-                .unwrap_or_else(|| "https".to_string());
+                .unwrap_or_else(|| db::default_endpoint_scheme(&input.provider).to_string());
             let endpoint_host = input
                 .endpoint_host
                 .clone()
@@ -90,6 +111,7 @@ fn build_move_config(input: &MoveConfigInput) -> Result<MoveConfig, String> {
                 endpoint_scheme,
                 endpoint_host,
                 force_path_style: true,
+                encryption,
             }))
         }
         "r2" => Ok(MoveConfig::R2(crate::r2::R2Config {
@@ -97,6 +119,7 @@ fn build_move_config(input: &MoveConfigInput) -> Result<MoveConfig, String> {
             bucket: input.bucket.clone(),
             access_key_id: input.access_key_id.clone(),
             secret_access_key: input.secret_access_key.clone(),
+            encryption,
         })),
         _ => Err(format!("Unsupported provider: {}", input.provider)),
     }
@@ -121,6 +144,7 @@ pub async fn start_batch_move(
 
     let source_bucket = source_config.bucket.clone();
     let source_account_id = source_config.account_id.clone();
+    let source_scope = source_config.cache_scope();
     let now = Utc::now().timestamp();
     info!(
         "start_batch_move: {} ops from {}/{} to {}/{} delete_original={}",
@@ -137,10 +161,9 @@ pub async fn start_batch_move(
 
     for (index, op) in operations.iter().enumerate() {
         let task_id = build_task_id(index);
-        let file_size =
-            db::get_cached_file_size(&source_bucket, &source_account_id, &op.source_key)
-                .await
-                .unwrap_or(0);
+        let file_size = db::get_cached_file_size(&source_scope, &op.source_key)
+            .await
+            .unwrap_or(0);
 
         sessions_to_create.push(MoveSession {
             id: task_id,
@@ -163,7 +186,7 @@ pub async fn start_batch_move(
     }
 
     // Batch insert all sessions in a single transaction
-    db::create_move_sessions_batch(&sessions_to_create)
+    db::create_move_sessions_batch(&source_scope, &sessions_to_create)
         .await
         .map_err(|e| format!("Failed to create move sessions: {}", e))?;
     info!(
@@ -173,8 +196,8 @@ pub async fn start_batch_move(
         source_account_id
     );
 
-    let source_cfg = build_move_config(&source_config)?;
-    let dest_cfg = build_move_config(&dest_config)?;
+    let source_cfg = build_move_config(&source_config).await?;
+    let dest_cfg = build_move_config(&dest_config).await?;
     register_move_config(
         &source_config.provider,
         &source_config.account_id,
@@ -187,7 +210,7 @@ pub async fn start_batch_move(
         &dest_config.bucket,
         dest_cfg,
     );
-    let started = request_queue_run(&app, &source_bucket, &source_account_id).await;
+    let started = request_queue_run(&app, &source_scope).await;
     info!(
         "start_batch_move: queued {} sessions for {}/{} started={}",
         operations.len(),
@@ -210,12 +233,13 @@ pub async fn start_move_queue(
 ) -> Result<i64, String> {
     let source_bucket = source_config.bucket.clone();
     let source_account_id = source_config.account_id.clone();
+    let source_scope = source_config.cache_scope();
     info!(
         "start_move_queue: source {}/{} dest {}/{}",
         source_config.provider, source_bucket, dest_config.provider, dest_config.bucket
     );
-    let source_cfg = build_move_config(&source_config)?;
-    let dest_cfg = build_move_config(&dest_config)?;
+    let source_cfg = build_move_config(&source_config).await?;
+    let dest_cfg = build_move_config(&dest_config).await?;
     register_move_config(
         &source_config.provider,
         &source_config.account_id,
@@ -228,7 +252,7 @@ pub async fn start_move_queue(
         &dest_config.bucket,
         dest_cfg,
     );
-    let started_count = request_queue_run(&app, &source_bucket, &source_account_id).await;
+    let started_count = request_queue_run(&app, &source_scope).await;
     info!(
         "start_move_queue: starting {} sessions for {}/{}",
         started_count, source_bucket, source_account_id
@@ -243,9 +267,20 @@ pub async fn pause_all_moves(
     app: AppHandle,
     source_bucket: String,
     source_account_id: String,
+    source_provider: String,
+    source_endpoint_scheme: Option<String>,
+    source_endpoint_host: Option<String>,
 ) -> Result<i64, String> {
+    let source = CacheScope::new(
+        &source_provider,
+        source_endpoint_scheme.as_deref(),
+        source_endpoint_host.as_deref(),
+        &source_account_id,
+        &source_bucket,
+    );
+
     // Get task IDs for this bucket/account before setting pause flags
-    let active_sessions = db::get_move_sessions_for_source(&source_bucket, &source_account_id)
+    let active_sessions = db::get_move_sessions_for_source(&source)
         .await
         .map_err(|e| format!("Failed to get sessions: {}", e))?;
 
@@ -266,7 +301,7 @@ pub async fn pause_all_moves(
         }
     }
 
-    let paused_count = db::pause_all_moves(&source_bucket, &source_account_id)
+    let paused_count = db::pause_all_moves(&source)
         .await
         .map_err(|e| format!("Failed to pause moves: {}", e))?;
     info!(
@@ -292,9 +327,20 @@ pub async fn resume_all_moves(
     app: AppHandle,
     source_bucket: String,
     source_account_id: String,
+    source_provider: String,
+    source_endpoint_scheme: Option<String>,
+    source_endpoint_host: Option<String>,
 ) -> Result<i64, String> {
+    let source = CacheScope::new(
+        &source_provider,
+        source_endpoint_scheme.as_deref(),
+        source_endpoint_host.as_deref(),
+        &source_account_id,
+        &source_bucket,
+    );
+
     // Get paused task IDs before resuming
-    let paused_sessions = db::get_move_sessions_for_source(&source_bucket, &source_account_id)
+    let paused_sessions = db::get_move_sessions_for_source(&source)
         .await
         .map_err(|e| format!("Failed to get sessions: {}", e))?;
 
@@ -314,10 +360,10 @@ pub async fn resume_all_moves(
         }
     }
 
-    let resumed_count = db::resume_all_moves(&source_bucket, &source_account_id)
+    let resumed_count = db::resume_all_moves(&source)
         .await
         .map_err(|e| format!("Failed to resume moves: {}", e))?;
-    let started_count = request_queue_run(&app, &source_bucket, &source_account_id).await;
+    let started_count = request_queue_run(&app, &source).await;
     info!(
         "resume_all_moves: resumed {} for {}/{} started={}",
         resumed_count, source_bucket, source_account_id, started_count
@@ -441,8 +487,18 @@ pub async fn delete_move_task(app: AppHandle, task_id: String) -> Result<(), Str
 pub async fn get_move_tasks(
     source_bucket: String,
     source_account_id: String,
+    source_provider: String,
+    source_endpoint_scheme: Option<String>,
+    source_endpoint_host: Option<String>,
 ) -> Result<Vec<MoveSession>, String> {
-    db::get_move_sessions_for_source(&source_bucket, &source_account_id)
+    let source = CacheScope::new(
+        &source_provider,
+        source_endpoint_scheme.as_deref(),
+        source_endpoint_host.as_deref(),
+        &source_account_id,
+        &source_bucket,
+    );
+    db::get_move_sessions_for_source(&source)
         .await
         .map_err(|e| format!("Failed to get move tasks: {}", e))
 }
@@ -461,8 +517,18 @@ pub async fn clear_finished_moves(
     app: AppHandle,
     source_bucket: String,
     source_account_id: String,
+    source_provider: String,
+    source_endpoint_scheme: Option<String>,
+    source_endpoint_host: Option<String>,
 ) -> Result<i64, String> {
-    let deleted_count = db::delete_finished_moves(&source_bucket, &source_account_id)
+    let source = CacheScope::new(
+        &source_provider,
+        source_endpoint_scheme.as_deref(),
+        source_endpoint_host.as_deref(),
+        &source_account_id,
+        &source_bucket,
+    );
+    let deleted_count = db::delete_finished_moves(&source)
         .await
         .map_err(|e| format!("Failed to clear finished moves: {}", e))?;
     info!(
@@ -488,9 +554,20 @@ pub async fn clear_all_moves(
     app: AppHandle,
     source_bucket: String,
     source_account_id: String,
+    source_provider: String,
+    source_endpoint_scheme: Option<String>,
+    source_endpoint_host: Option<String>,
 ) -> Result<i64, String> {
+    let source = CacheScope::new(
+        &source_provider,
+        source_endpoint_scheme.as_deref(),
+        source_endpoint_host.as_deref(),
+        &source_account_id,
+        &source_bucket,
+    );
+
     // Use count_in_progress_moves to include deleting tasks
-    let active_count = db::count_in_progress_moves(&source_bucket, &source_account_id)
+    let active_count = db::count_in_progress_moves(&source)
         .await
         .map_err(|e| format!("Failed to count active moves: {}", e))?;
 
@@ -502,7 +579,7 @@ pub async fn clear_all_moves(
         return Err("Cannot clear all moves while moves are active".to_string());
     }
 
-    let deleted_count = db::delete_all_moves(&source_bucket, &source_account_id)
+    let deleted_count = db::delete_all_moves(&source)
         .await
         .map_err(|e| format!("Failed to clear all moves: {}", e))?;
     info!(
@@ -1,3 +1,5 @@
+use crate::db::CacheScope;
+use crate::providers::encryption::ObjectEncryption;
 use crate::providers::{aws, minio};
 use crate::r2::R2Config;
 
@@ -8,3 +10,41 @@ pub(crate) enum MoveConfig {
     Minio(minio::MinioConfig),
     Rustfs(minio::MinioConfig),
 }
+
+impl MoveConfig {
+    pub(crate) fn encryption(&self) -> Option<&ObjectEncryption> {
+        match self {
+            MoveConfig::R2(cfg) => cfg.encryption.as_ref(),
+            MoveConfig::Aws(cfg) => cfg.encryption.as_ref(),
+            MoveConfig::Minio(cfg) | MoveConfig::Rustfs(cfg) => cfg.encryption.as_ref(),
+        }
+    }
+
+    /// Cache scope of this bucket for `account_id`.
+    pub(crate) fn cache_scope(&self, account_id: &str) -> CacheScope {
+        match self {
+            MoveConfig::R2(cfg) => CacheScope::r2(account_id, &cfg.bucket),
+            MoveConfig::Aws(cfg) => CacheScope::new(
+                "aws",
+                cfg.endpoint_scheme.as_deref(),
+                cfg.endpoint_host.as_deref(),
+                account_id,
+                &cfg.bucket,
+            ),
+            MoveConfig::Minio(cfg) => CacheScope::new(
+                "minio",
+                Some(&cfg.endpoint_scheme),
+                Some(&cfg.endpoint_host),
+                account_id,
+                &cfg.bucket,
+            ),
+            MoveConfig::Rustfs(cfg) => CacheScope::new(
+                "rustfs",
+                Some(&cfg.endpoint_scheme),
+                Some(&cfg.endpoint_host),
+                account_id,
+                &cfg.bucket,
+            ),
+        }
+    }
+}
@@ -2,12 +2,16 @@
 
 use crate::commands::delete_cache::queue_cache_after_delete;
 use crate::commands::upload_cache::update_cache_after_upload;
-use crate::db::MoveSession;
+use crate::db::{CacheScope, CachedObjectMeta, MoveSession};
+use crate::providers::capability::Feature;
+use crate::providers::config::ProviderConfig;
+use crate::providers::object_lock::{self, SkippedObject};
 use crate::providers::{aws, minio};
 use crate::r2;
 use chrono::Utc;
-use log::{error, info};
+use log::{error, info, warn};
 use tauri::AppHandle;
+use tokio::sync::OnceCell;
 
 use super::config::MoveConfig;
 use super::state::{update_move_status, update_move_status_with_progress};
@@ -29,32 +33,110 @@ async fn delete_source_object(config: &MoveConfig, key: &str) -> Result<(), Stri
     }
 }
 
+/// Object Lock on a move source bucket, resolved once per batch.
+pub(crate) enum SourceLock {
+    /// No Object Lock, or its state could not be read; keys are not checked.
+    Unchecked,
+    /// Each key is checked with a HEAD through the batch's client.
+    Enabled {
+        client: aws_sdk_s3::Client,
+        bucket: String,
+    },
+}
+
+async fn resolve_source_lock(config: &MoveConfig) -> SourceLock {
+    let config = match config {
+        MoveConfig::R2(_) => return SourceLock::Unchecked,
+        MoveConfig::Aws(cfg) => ProviderConfig::Aws(cfg.clone()),
+        MoveConfig::Minio(cfg) => ProviderConfig::Minio(cfg.clone()),
+        MoveConfig::Rustfs(cfg) => ProviderConfig::Rustfs(cfg.clone()),
+    };
+    if !config.supports(Feature::ObjectLock) {
+        return SourceLock::Unchecked;
+    }
+    let client = match config.create_client().await {
+        Ok(client) => client,
+        Err(e) => {
+            warn!(
+                "move_lock_check_failed: bucket={} error={}",
+                config.bucket(),
+                e
+            );
+            return SourceLock::Unchecked;
+        }
+    };
+    match object_lock::bucket_lock_enabled_with(&client, &config).await {
+        Ok(true) => SourceLock::Enabled {
+            client,
+            bucket: config.bucket().to_string(),
+        },
+        Ok(false) => SourceLock::Unchecked,
+        Err(e) => {
+            warn!(
+                "move_lock_check_failed: bucket={} error={}",
+                config.bucket(),
+                e
+            );
+            SourceLock::Unchecked
+        }
+    }
+}
+
+/// Object Lock on the source, which would make deleting it after the copy
+/// fail. The bucket's lock state is resolved on the batch's first check; a
+/// failed check lets the move go ahead.
+pub(crate) async fn locked_source(
+    source_lock: &OnceCell<SourceLock>,
+    config: &MoveConfig,
+    key: &str,
+) -> Option<SkippedObject> {
+    let SourceLock::Enabled { client, bucket } = source_lock
+        .get_or_init(|| resolve_source_lock(config))
+        .await
+    else {
+        return None;
+    };
+    match object_lock::check_locked_with(client, bucket, key).await {
+        Ok(skipped) => skipped,
+        Err(e) => {
+            warn!("move_lock_check_failed: key={} error={}", key, e);
+            None
+        }
+    }
+}
+
 /// Run post-upload cache operations in background (non-blocking)
 pub(crate) async fn run_cache_operations(
     app: AppHandle,
     task_id: String,
-    dest_bucket: String,
-    dest_account_id: String,
+    dest_scope: CacheScope,
     dest_key: String,
     uploaded_size: u64,
 ) {
     info!(
         "finishing_cache_start: {} {}/{} key={} size={}",
-        task_id, dest_account_id, dest_bucket, dest_key, uploaded_size
+        task_id,
+        dest_scope.account_id(),
+        dest_scope.bucket(),
+        dest_key,
+        uploaded_size
     );
     let last_modified = Utc::now().to_rfc3339();
     let _ = update_cache_after_upload(
         &app,
-        &dest_bucket,
-        &dest_account_id,
+        &dest_scope,
         &dest_key,
         uploaded_size as i64,
         &last_modified,
+        &CachedObjectMeta::default(),
     )
     .await;
     info!(
         "finishing_cache_done: {} {}/{} key={}",
-        task_id, dest_account_id, dest_bucket, dest_key
+        task_id,
+        dest_scope.account_id(),
+        dest_scope.bucket(),
+        dest_key
     );
 }
 
@@ -84,8 +166,7 @@ pub(crate) async fn run_delete_original(
     // Queue source cache updates to avoid repeated directory calculations
     queue_cache_after_delete(
         app.clone(),
-        session.source_bucket.clone(),
-        session.source_account_id.clone(),
+        source_config.cache_scope(&session.source_account_id),
         session.source_key.clone(),
     )
     .await;
@@ -1,10 +1,11 @@
 use crate::db;
+use crate::providers::encryption;
 use crate::providers::{aws, minio};
 use crate::r2;
 use crate::transfer_progress::{SpeedWindow, ThrottleGate};
 use futures_util::{future::join_all, StreamExt};
 use log::{debug, info};
-use reqwest::{Body, Client};
+use reqwest::{Body, Client, RequestBuilder};
 use std::collections::HashMap;
 use std::io;
 use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
@@ -15,7 +16,7 @@ use tokio::sync::Semaphore;
 
 /// Minimum interval between move-progress IPC emissions for streaming paths.
 /// Network streams yield ~16-64KB chunks; emitting per chunk floods the bridge.
-const PROGRESS_EMIT_INTERVAL: Duration = Duration::from_millis(150);
+pub(super) const PROGRESS_EMIT_INTERVAL: Duration = Duration::from_millis(150);
 
 use super::config::MoveConfig;
 use super::state::update_move_status;
@@ -25,8 +26,32 @@ use crate::db::MoveSession;
 const MULTIPART_THRESHOLD: u64 = 100 * 1024 * 1024;
 const PART_SIZE: u64 = 20 * 1024 * 1024;
 
-async fn generate_download_url(config: &MoveConfig, key: &str) -> Result<String, String> {
-    match config {
+/// Presigned URL plus the headers signed into it (bucket encryption).
+#[derive(Debug, Clone)]
+struct SignedUrl {
+    url: String,
+    headers: Vec<(String, String)>,
+}
+
+impl SignedUrl {
+    fn get(&self, client: &Client) -> RequestBuilder {
+        self.with_headers(client.get(&self.url))
+    }
+
+    fn put(&self, client: &Client) -> RequestBuilder {
+        self.with_headers(client.put(&self.url))
+    }
+
+    fn with_headers(&self, mut request: RequestBuilder) -> RequestBuilder {
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        request
+    }
+}
+
+async fn generate_download_url(config: &MoveConfig, key: &str) -> Result<SignedUrl, String> {
+    let url = match config {
         MoveConfig::R2(cfg) => r2::generate_presigned_url(cfg, key, 3600)
             .await
             .map_err(|e| format!("Failed to generate R2 URL: {}", e)),
@@ -39,11 +64,15 @@ async fn generate_download_url(config: &MoveConfig, key: &str) -> Result<String,
         MoveConfig::Rustfs(cfg) => minio::generate_presigned_url(cfg, key, 3600)
             .await
             .map_err(|e| format!("Failed to generate RustFS URL: {}", e)),
-    }
+    }?;
+    Ok(SignedUrl {
+        url,
+        headers: encryption::read_headers(config.encryption()),
+    })
 }
 
-async fn generate_upload_url(config: &MoveConfig, key: &str) -> Result<String, String> {
-    match config {
+async fn generate_upload_url(config: &MoveConfig, key: &str) -> Result<SignedUrl, String> {
+    let url = match config {
         MoveConfig::R2(cfg) => r2::generate_presigned_put_url(cfg, key, 3600)
             .await
             .map_err(|e| format!("Failed to generate R2 upload URL: {}", e)),
@@ -56,20 +85,27 @@ async fn generate_upload_url(config: &MoveConfig, key: &str) -> Result<String, S
         MoveConfig::Rustfs(cfg) => minio::generate_presigned_put_url(cfg, key, 3600)
             .await
             .map_err(|e| format!("Failed to generate RustFS upload URL: {}", e)),
-    }
+    }?;
+    Ok(SignedUrl {
+        url,
+        headers: config
+            .encryption()
+            .map(|e| e.write_headers())
+            .unwrap_or_default(),
+    })
 }
 
 async fn resolve_source_size(
     client: &Client,
-    download_url: &str,
+    download: &SignedUrl,
     known_size: u64,
 ) -> Result<u64, String> {
     if known_size > 0 {
         return Ok(known_size);
     }
 
-    let response = client
-        .get(download_url)
+    let response = download
+        .get(client)
         .header("Range", "bytes=0-0")
         .send()
         .await
@@ -97,12 +133,12 @@ async fn resolve_source_size(
 
 async fn fetch_range_bytes(
     client: &Client,
-    download_url: &str,
+    download: &SignedUrl,
     start: u64,
     end: u64,
 ) -> Result<Vec<u8>, String> {
-    let response = client
-        .get(download_url)
+    let response = download
+        .get(client)
         .header("Range", format!("bytes={}-{}", start, end))
         .send()
         .await
@@ -123,16 +159,16 @@ async fn fetch_range_bytes(
 
 async fn initiate_multipart_upload(config: &MoveConfig, key: &str) -> Result<String, String> {
     match config {
-        MoveConfig::R2(cfg) => r2::upload::initiate_multipart_upload(cfg, key, None)
+        MoveConfig::R2(cfg) => r2::upload::initiate_multipart_upload(cfg, key, None, None)
             .await
             .map_err(|e| format!("Failed to initiate R2 multipart upload: {}", e)),
-        MoveConfig::Aws(cfg) => aws::initiate_multipart_upload(cfg, key, None)
+        MoveConfig::Aws(cfg) => aws::initiate_multipart_upload(cfg, key, None, None)
             .await
             .map_err(|e| format!("Failed to initiate AWS multipart upload: {}", e)),
-        MoveConfig::Minio(cfg) => minio::initiate_multipart_upload(cfg, key, None)
+        MoveConfig::Minio(cfg) => minio::initiate_multipart_upload(cfg, key, None, None)
             .await
             .map_err(|e| format!("Failed to initiate MinIO multipart upload: {}", e)),
-        MoveConfig::Rustfs(cfg) => minio::initiate_multipart_upload(cfg, key, None)
+        MoveConfig::Rustfs(cfg) => minio::initiate_multipart_upload(cfg, key, None, None)
             .await
             .map_err(|e| format!("Failed to initiate RustFS multipart upload: {}", e)),
     }
@@ -195,8 +231,8 @@ async fn abort_multipart_upload(config: &MoveConfig, key: &str, upload_id: &str)
 #[allow(clippy::too_many_arguments)]
 async fn stream_single_put(
     client: &Client,
-    download_url: &str,
-    upload_url: &str,
+    download: &SignedUrl,
+    upload: &SignedUrl,
     total_bytes: u64,
     session: &MoveSession,
     app: &AppHandle,
@@ -207,8 +243,8 @@ async fn stream_single_put(
         "single_put_start: {} total_bytes={}",
         session.id, total_bytes
     );
-    let response = client
-        .get(download_url)
+    let response = download
+        .get(client)
         .send()
         .await
         .map_err(|e| format!("Download request failed: {}", e))?;
@@ -326,7 +362,7 @@ async fn stream_single_put(
     });
 
     let body = Body::wrap_stream(stream);
-    let mut request = client.put(upload_url).body(body);
+    let mut request = upload.put(client).body(body);
     if total_bytes > 0 {
         request = request.header("content-length", total_bytes);
     }
@@ -389,7 +425,7 @@ struct PartUploadResult {
 async fn stream_multipart(
     client: &Client,
     session: &MoveSession,
-    download_url: &str,
+    download: &SignedUrl,
     dest_config: &MoveConfig,
     total_bytes: u64,
     app: &AppHandle,
@@ -471,7 +507,7 @@ async fn stream_multipart(
 
         let permit = semaphore.clone().acquire_owned().await.unwrap();
         let client = client.clone();
-        let download_url = download_url.to_string();
+        let download = download.clone();
         let dest_config = dest_config.clone();
         let dest_key = session.dest_key.clone();
         let upload_id = upload_id.clone();
@@ -507,7 +543,7 @@ async fn stream_multipart(
 
             // Download part
             let download_timer = Instant::now();
-            let bytes = match fetch_range_bytes(&client, &download_url, start, end).await {
+            let bytes = match fetch_range_bytes(&client, &download, start, end).await {
                 Ok(b) => b,
                 Err(e) => {
                     error_flag.store(true, Ordering::SeqCst);
@@ -688,22 +724,21 @@ pub(crate) async fn stream_transfer_without_temp(
         "move_prepare_start: {} source_key={} dest_key={}",
         session.id, session.source_key, session.dest_key
     );
-    let download_url = generate_download_url(source_config, &session.source_key).await?;
-    let upload_url = generate_upload_url(dest_config, &session.dest_key).await?;
+    let download = generate_download_url(source_config, &session.source_key).await?;
+    let upload = generate_upload_url(dest_config, &session.dest_key).await?;
 
     let known_size = if session.file_size > 0 {
         session.file_size as u64
     } else {
         db::get_cached_file_size(
-            &session.source_bucket,
-            &session.source_account_id,
+            &source_config.cache_scope(&session.source_account_id),
             &session.source_key,
         )
         .await
         .unwrap_or(0) as u64
     };
 
-    let total_bytes = resolve_source_size(client, &download_url, known_size).await?;
+    let total_bytes = resolve_source_size(client, &download, known_size).await?;
     update_move_status(app, &session.id, "uploading", None).await;
     info!(
         "move_prepare_done: {} total_bytes={} multipart={}",
@@ -734,7 +769,7 @@ pub(crate) async fn stream_transfer_without_temp(
         stream_multipart(
             client,
             session,
-            &download_url,
+            &download,
             dest_config,
             total_bytes,
             app,
@@ -745,8 +780,8 @@ pub(crate) async fn stream_transfer_without_temp(
     } else {
         stream_single_put(
             client,
-            &download_url,
-            &upload_url,
+            &download,
+            &upload,
             total_bytes,
             session,
             app,
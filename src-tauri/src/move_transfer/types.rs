@@ -29,6 +29,9 @@ pub enum MoveStatus {
     Error,
     #[serde(rename = "cancelled")]
     Cancelled,
+    /// Not moved because the source is under Object Lock
+    #[serde(rename = "skipped")]
+    Skipped,
 }
 
 impl std::fmt::Display for MoveStatus {
@@ -43,6 +46,7 @@ impl std::fmt::Display for MoveStatus {
             MoveStatus::Success => write!(f, "success"),
             MoveStatus::Error => write!(f, "error"),
             MoveStatus::Cancelled => write!(f, "cancelled"),
+            MoveStatus::Skipped => write!(f, "skipped"),
         }
     }
 }
@@ -59,6 +63,7 @@ impl From<String> for MoveStatus {
             "success" => MoveStatus::Success,
             "error" => MoveStatus::Error,
             "cancelled" => MoveStatus::Cancelled,
+            "skipped" => MoveStatus::Skipped,
             _ => MoveStatus::Pending,
         }
     }
@@ -112,6 +117,7 @@ mod tests {
         assert_eq!(MoveStatus::Success.to_string(), "success");
         assert_eq!(MoveStatus::Error.to_string(), "error");
         assert_eq!(MoveStatus::Cancelled.to_string(), "cancelled");
+        assert_eq!(MoveStatus::Skipped.to_string(), "skipped");
     }
 
     #[test]
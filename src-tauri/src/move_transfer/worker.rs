@@ -1,6 +1,6 @@
 //! Move transfer worker - download to temp, upload to destination, optional delete
 
-use crate::db::{self, MoveSession};
+use crate::db::{self, CacheScope, MoveSession};
 use crate::providers::{aws, minio};
 use crate::r2::{self};
 use log::{debug, error, info, warn};
@@ -9,13 +9,15 @@ use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Emitter};
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{mpsc, oneshot, OnceCell};
 
 use super::config::MoveConfig;
-use super::finishing::{run_cache_operations, run_delete_original};
+use super::finishing::{locked_source, run_cache_operations, run_delete_original, SourceLock};
 use super::state::{update_move_status, update_move_status_with_progress};
-use super::stream::stream_transfer_without_temp;
+use super::stream::{stream_transfer_without_temp, PROGRESS_EMIT_INTERVAL};
 use super::types::{MoveProgress, MoveStatusChanged, MAX_CONCURRENT_MOVES};
+use crate::providers::multipart_copy::CopyProgressFn;
+use crate::transfer_progress::ThrottleGate;
 
 // Global cancel/pause registry for move tasks (using std::sync::Mutex for Send compatibility)
 lazy_static::lazy_static! {
@@ -24,7 +26,10 @@ lazy_static::lazy_static! {
     pub(crate) static ref MOVE_PAUSE_REGISTRY: Mutex<HashMap<String, Arc<AtomicBool>>> =
         Mutex::new(HashMap::new());
     static ref MOVE_CONFIG_REGISTRY: Mutex<HashMap<String, MoveConfig>> = Mutex::new(HashMap::new());
-    static ref MOVE_QUEUE_SENDERS: Mutex<HashMap<String, mpsc::Sender<QueueSignal>>> =
+    // Object Lock state of source buckets, keyed like the configs
+    static ref MOVE_SOURCE_LOCKS: Mutex<HashMap<String, Arc<OnceCell<SourceLock>>>> =
+        Mutex::new(HashMap::new());
+    static ref MOVE_QUEUE_SENDERS: Mutex<HashMap<CacheScope, mpsc::Sender<QueueSignal>>> =
         Mutex::new(HashMap::new());
 }
 
@@ -44,38 +49,37 @@ pub(crate) fn register_move_config(
     config: MoveConfig,
 ) {
     let key = config_key(provider, account_id, bucket);
+    // A new batch resolves the bucket's lock state afresh
+    MOVE_SOURCE_LOCKS.lock().unwrap().remove(&key);
     let mut registry = MOVE_CONFIG_REGISTRY.lock().unwrap();
     registry.insert(key, config);
 }
 
+fn get_source_lock(provider: &str, account_id: &str, bucket: &str) -> Arc<OnceCell<SourceLock>> {
+    let key = config_key(provider, account_id, bucket);
+    let mut locks = MOVE_SOURCE_LOCKS.lock().unwrap();
+    locks.entry(key).or_default().clone()
+}
+
 fn get_move_config(provider: &str, account_id: &str, bucket: &str) -> Option<MoveConfig> {
     let key = config_key(provider, account_id, bucket);
     let registry = MOVE_CONFIG_REGISTRY.lock().unwrap();
     registry.get(&key).cloned()
 }
 
-fn queue_key(source_bucket: &str, source_account_id: &str) -> String {
-    format!("{}:{}", source_account_id, source_bucket)
-}
-
-fn get_or_create_queue_sender(
-    app: &AppHandle,
-    source_bucket: &str,
-    source_account_id: &str,
-) -> mpsc::Sender<QueueSignal> {
-    let key = queue_key(source_bucket, source_account_id);
+/// Queue of the source bucket `source`, one per bucket scope.
+fn get_or_create_queue_sender(app: &AppHandle, source: &CacheScope) -> mpsc::Sender<QueueSignal> {
     let mut senders = MOVE_QUEUE_SENDERS.lock().unwrap();
-    if let Some(sender) = senders.get(&key) {
+    if let Some(sender) = senders.get(source) {
         return sender.clone();
     }
     let (sender, receiver) = mpsc::channel(8);
     let app_clone = app.clone();
-    let source_bucket = source_bucket.to_string();
-    let source_account_id = source_account_id.to_string();
+    let source_clone = source.clone();
     tokio::spawn(async move {
-        run_queue_worker(app_clone, source_bucket, source_account_id, receiver).await;
+        run_queue_worker(app_clone, source_clone, receiver).await;
     });
-    senders.insert(key, sender.clone());
+    senders.insert(source.clone(), sender.clone());
     sender
 }
 
@@ -85,10 +89,39 @@ struct MoveUploadResult {
     delete_original: bool,
 }
 
+/// Byte progress for server-side copies; only multipart copies (> 5 GB) report it.
+fn server_copy_progress(app: &AppHandle, task_id: &str) -> CopyProgressFn {
+    let app = app.clone();
+    let task_id = task_id.to_string();
+    let gate = ThrottleGate::new(PROGRESS_EMIT_INTERVAL);
+    Arc::new(move |copied, total| {
+        if copied < total && !gate.try_pass() {
+            return;
+        }
+        let percent = if total > 0 {
+            ((copied as f64 / total as f64) * 100.0) as u32
+        } else {
+            100
+        };
+        let _ = app.emit(
+            "move-progress",
+            MoveProgress {
+                task_id: task_id.clone(),
+                phase: "uploading".to_string(),
+                percent,
+                transferred_bytes: copied,
+                total_bytes: total,
+                speed: 0.0,
+            },
+        );
+    })
+}
+
 async fn try_server_side_copy(
     session: &MoveSession,
     source_config: &MoveConfig,
     dest_config: &MoveConfig,
+    app: &AppHandle,
 ) -> Option<MoveUploadResult> {
     info!(
         "move_copy_try: {} {}/{} -> {}/{}",
@@ -98,12 +131,15 @@ async fn try_server_side_copy(
         session.dest_account_id,
         session.dest_bucket
     );
+    let on_progress = Some(server_copy_progress(app, &session.id));
     let copy_result = match (source_config, dest_config) {
         (MoveConfig::R2(_), MoveConfig::R2(dest_cfg)) => r2::copy_object_between_buckets(
             dest_cfg,
             &session.source_bucket,
             &session.source_key,
+            source_config.encryption(),
             &session.dest_key,
+            on_progress,
         )
         .await
         .map_err(|e| format!("R2 copy failed: {}", e)),
@@ -111,7 +147,9 @@ async fn try_server_side_copy(
             dest_cfg,
             &session.source_bucket,
             &session.source_key,
+            source_config.encryption(),
             &session.dest_key,
+            on_progress,
         )
         .await
         .map_err(|e| format!("AWS copy failed: {}", e)),
@@ -121,7 +159,9 @@ async fn try_server_side_copy(
                 dest_cfg,
                 &session.source_bucket,
                 &session.source_key,
+                source_config.encryption(),
                 &session.dest_key,
+                on_progress,
             )
             .await
             .map_err(|e| format!("MinIO copy failed: {}", e))
@@ -129,20 +169,20 @@ async fn try_server_side_copy(
         _ => return None,
     };
 
-    let file_size = if session.file_size > 0 {
-        session.file_size as u64
-    } else {
-        db::get_cached_file_size(
-            &session.source_bucket,
-            &session.source_account_id,
-            &session.source_key,
-        )
-        .await
-        .unwrap_or(0) as u64
-    };
-
     match copy_result {
-        Ok(()) => {
+        Ok(copied_size) => {
+            let file_size = if copied_size > 0 {
+                copied_size
+            } else if session.file_size > 0 {
+                session.file_size as u64
+            } else {
+                db::get_cached_file_size(
+                    &source_config.cache_scope(&session.source_account_id),
+                    &session.source_key,
+                )
+                .await
+                .unwrap_or(0) as u64
+            };
             info!(
                 "move_copy_finish: {} size={} delete_original={}",
                 session.id, file_size, session.delete_original
@@ -172,7 +212,9 @@ async fn move_file_internal(
     paused: &Arc<AtomicBool>,
 ) -> Result<Option<MoveUploadResult>, String> {
     // Try server-side copy first (finishing handled in background)
-    if let Some(upload_result) = try_server_side_copy(session, source_config, dest_config).await {
+    if let Some(upload_result) =
+        try_server_side_copy(session, source_config, dest_config, app).await
+    {
         return Ok(Some(upload_result));
     }
 
@@ -207,8 +249,7 @@ pub(crate) async fn spawn_move_task(
     dest_config: MoveConfig,
 ) {
     let task_id = session.id.clone();
-    let source_bucket = session.source_bucket.clone();
-    let source_account_id = session.source_account_id.clone();
+    let source_scope = source_config.cache_scope(&session.source_account_id);
     info!(
         "spawn_move_task: {} {} -> {} delete_original={} size={}",
         task_id, session.source_key, session.dest_key, session.delete_original, session.file_size
@@ -235,11 +276,28 @@ pub(crate) async fn spawn_move_task(
         Err(e) => {
             update_move_status(&app, &task_id, "error", Some(e.to_string())).await;
             cleanup_registries(&task_id);
-            schedule_queue_continuation(app, source_bucket, source_account_id);
+            schedule_queue_continuation(app, source_scope);
             return;
         }
     };
 
+    // A locked source can be copied but never deleted; skip before transferring
+    if session.delete_original {
+        let source_lock = get_source_lock(
+            &session.source_provider,
+            &session.source_account_id,
+            &session.source_bucket,
+        );
+        if let Some(skipped) =
+            locked_source(&source_lock, &source_config, &session.source_key).await
+        {
+            update_move_status(&app, &task_id, "skipped", Some(skipped.message)).await;
+            cleanup_registries(&task_id);
+            schedule_queue_continuation(app, source_scope);
+            return;
+        }
+    }
+
     let result = move_file_internal(
         &client,
         &session,
@@ -279,18 +337,13 @@ pub(crate) async fn spawn_move_task(
                 let app_for_cache = app.clone();
                 let app_for_delete = app.clone();
                 let task_id_cleanup = task_id.clone();
-                let dest_bucket_clone = session.dest_bucket.clone();
-                let dest_account_id_clone = session.dest_account_id.clone();
+                let dest_scope = dest_config.cache_scope(&session.dest_account_id);
                 let dest_key_clone = session.dest_key.clone();
                 let uploaded_size = upload_result.uploaded_size;
                 let session_for_delete = session.clone();
                 let source_config_for_delete = source_config.clone();
 
-                schedule_queue_continuation(
-                    app.clone(),
-                    source_bucket.clone(),
-                    source_account_id.clone(),
-                );
+                schedule_queue_continuation(app.clone(), source_scope.clone());
                 scheduled_continuation = true;
 
                 // Spawn cache update + delete original in background, cleanup after both
@@ -298,8 +351,7 @@ pub(crate) async fn spawn_move_task(
                     let cache_future = run_cache_operations(
                         app_for_cache,
                         task_id_cleanup.clone(),
-                        dest_bucket_clone,
-                        dest_account_id_clone,
+                        dest_scope,
                         dest_key_clone,
                         uploaded_size,
                     );
@@ -316,16 +368,11 @@ pub(crate) async fn spawn_move_task(
                 let app_for_cache = app.clone();
                 let app_for_status = app.clone();
                 let task_id_cleanup = session.id.clone();
-                let dest_bucket_clone = session.dest_bucket.clone();
-                let dest_account_id_clone = session.dest_account_id.clone();
+                let dest_scope = dest_config.cache_scope(&session.dest_account_id);
                 let dest_key_clone = session.dest_key.clone();
                 let uploaded_size = upload_result.uploaded_size;
 
-                schedule_queue_continuation(
-                    app.clone(),
-                    source_bucket.clone(),
-                    source_account_id.clone(),
-                );
+                schedule_queue_continuation(app.clone(), source_scope.clone());
                 scheduled_continuation = true;
 
                 // Spawn cache update in background (non-blocking), cleanup after
@@ -333,8 +380,7 @@ pub(crate) async fn spawn_move_task(
                     run_cache_operations(
                         app_for_cache,
                         task_id_cleanup.clone(),
-                        dest_bucket_clone,
-                        dest_account_id_clone,
+                        dest_scope,
                         dest_key_clone,
                         uploaded_size,
                     )
@@ -369,7 +415,7 @@ pub(crate) async fn spawn_move_task(
     // After upload completes, IMMEDIATELY schedule next pending tasks
     // Post-upload operations (cache update, delete) run in parallel
     if !scheduled_continuation {
-        schedule_queue_continuation(app, source_bucket, source_account_id);
+        schedule_queue_continuation(app, source_scope);
     }
 }
 
@@ -387,8 +433,7 @@ fn cleanup_registries(task_id: &str) {
 
 async fn run_queue_worker(
     app: AppHandle,
-    source_bucket: String,
-    source_account_id: String,
+    source: CacheScope,
     mut receiver: mpsc::Receiver<QueueSignal>,
 ) {
     while let Some(signal) = receiver.recv().await {
@@ -401,7 +446,7 @@ async fn run_queue_worker(
                 responders.push(respond);
             }
         }
-        let started = continue_move_queue(&app, &source_bucket, &source_account_id).await;
+        let started = continue_move_queue(&app, &source).await;
         for respond in responders {
             let _ = respond.send(started);
         }
@@ -409,23 +454,20 @@ async fn run_queue_worker(
 }
 
 /// Schedule queue continuation for a source
-fn schedule_queue_continuation(app: AppHandle, source_bucket: String, source_account_id: String) {
-    let sender = get_or_create_queue_sender(&app, &source_bucket, &source_account_id);
+fn schedule_queue_continuation(app: AppHandle, source: CacheScope) {
+    let sender = get_or_create_queue_sender(&app, &source);
     if sender.try_send(QueueSignal::Continue).is_err() {
         debug!(
             "schedule_queue_continuation: skip pending {}/{}",
-            source_account_id, source_bucket
+            source.account_id(),
+            source.bucket()
         );
     }
 }
 
 /// Request a single queue run and return number started
-pub(crate) async fn request_queue_run(
-    app: &AppHandle,
-    source_bucket: &str,
-    source_account_id: &str,
-) -> i64 {
-    let sender = get_or_create_queue_sender(app, source_bucket, source_account_id);
+pub(crate) async fn request_queue_run(app: &AppHandle, source: &CacheScope) -> i64 {
+    let sender = get_or_create_queue_sender(app, source);
     let (respond, receiver) = oneshot::channel();
     if sender.send(QueueSignal::RunOnce { respond }).await.is_err() {
         return 0;
@@ -434,12 +476,13 @@ pub(crate) async fn request_queue_run(
 }
 
 /// Continue processing the move queue by starting next pending tasks
-async fn continue_move_queue(app: &AppHandle, source_bucket: &str, source_account_id: &str) -> i64 {
+async fn continue_move_queue(app: &AppHandle, source: &CacheScope) -> i64 {
+    let (source_bucket, source_account_id) = (source.bucket(), source.account_id());
     debug!(
         "continue_move_queue: {}/{}",
         source_account_id, source_bucket
     );
-    match get_pending_sessions_to_start(source_bucket, source_account_id).await {
+    match get_pending_sessions_to_start(source).await {
         Ok((next_sessions, slots_available)) => {
             if next_sessions.is_empty() || slots_available <= 0 {
                 debug!(
@@ -540,10 +583,10 @@ async fn continue_move_queue(app: &AppHandle, source_bucket: &str, source_accoun
 
 /// Internal queue helper - returns pending candidates plus available worker slots.
 pub(crate) async fn get_pending_sessions_to_start(
-    source_bucket: &str,
-    source_account_id: &str,
+    source: &CacheScope,
 ) -> Result<(Vec<MoveSession>, i64), String> {
-    let active_count = db::count_active_moves(source_bucket, source_account_id)
+    let (source_bucket, source_account_id) = (source.bucket(), source.account_id());
+    let active_count = db::count_active_moves(source)
         .await
         .map_err(|e| format!("Failed to count active moves: {}", e))?;
 
@@ -563,7 +606,7 @@ pub(crate) async fn get_pending_sessions_to_start(
     // Scan ahead so tasks waiting for different destination account configs do not block
     // other ready tasks at the front of the queue.
     let scan_limit = std::cmp::max(slots_available * 20, slots_available);
-    let pending = db::get_pending_moves_for_source(source_bucket, source_account_id, scan_limit)
+    let pending = db::get_pending_moves_for_source(source, scan_limit)
         .await
         .map_err(|e| format!("Failed to get pending moves: {}", e))?;
 
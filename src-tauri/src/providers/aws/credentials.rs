@@ -0,0 +1,500 @@
+//! Credential sources for AWS accounts: stored keys, named profiles from the
+//! shared config / credentials files, or the process environment.
+//!
+//! Profiles are resolved by `aws-config`, which handles `credential_process`,
+//! SSO and assume-role (with `external_id`) chains. `aws-config` cannot prompt
+//! for an MFA code, so profiles with `mfa_serial` get their session from
+//! `start_mfa_session` instead. Clients are created per operation, so resolved
+//! credentials are cached here and refreshed shortly before they expire.
+
+use super::types::AwsResult;
+use crate::secrets;
+use aws_config::environment::EnvironmentVariableCredentialsProvider;
+use aws_config::profile::ProfileFileCredentialsProvider;
+use aws_credential_types::provider::error::CredentialsError;
+use aws_credential_types::provider::{future, ProvideCredentials, SharedCredentialsProvider};
+use aws_credential_types::Credentials;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Refresh cached credentials this long before they expire.
+const REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+/// Region used for STS when the profile does not name one.
+const DEFAULT_REGION: &str = "us-east-1";
+
+lazy_static::lazy_static! {
+    static ref PROVIDERS: Mutex<HashMap<CredentialSource, SharedCredentialsProvider>> =
+        Mutex::new(HashMap::new());
+    /// MFA-backed sessions by profile name.
+    static ref MFA_SESSIONS: Mutex<HashMap<String, Credentials>> = Mutex::new(HashMap::new());
+}
+
+/// Where an AWS account's credentials come from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum CredentialSource {
+    /// The access key and secret stored with the account.
+    #[default]
+    Static,
+    /// A named profile from `~/.aws/config` and `~/.aws/credentials`.
+    Profile { name: String },
+    /// `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` / `AWS_SESSION_TOKEN`.
+    Environment,
+}
+
+impl CredentialSource {
+    /// Database representation: (kind, profile name).
+    pub fn to_columns(&self) -> (&'static str, Option<&str>) {
+        match self {
+            CredentialSource::Static => ("static", None),
+            CredentialSource::Profile { name } => ("profile", Some(name)),
+            CredentialSource::Environment => ("environment", None),
+        }
+    }
+
+    pub fn from_columns(kind: &str, profile_name: Option<String>) -> Self {
+        match (kind, profile_name) {
+            ("profile", Some(name)) => CredentialSource::Profile { name },
+            ("environment", _) => CredentialSource::Environment,
+            _ => CredentialSource::Static,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProfileKind {
+    StaticKeys,
+    AssumeRole,
+    CredentialProcess,
+    Sso,
+    WebIdentity,
+    /// Settings only (e.g. just a region); credentials come from elsewhere.
+    Other,
+}
+
+/// A profile from the shared files, as shown when importing or linking.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AwsProfile {
+    pub name: String,
+    pub kind: ProfileKind,
+    pub region: Option<String>,
+    pub role_arn: Option<String>,
+    pub source_profile: Option<String>,
+    pub external_id: Option<String>,
+    pub mfa_serial: Option<String>,
+    /// Expiry (unix seconds) of the current `start_mfa_session` session.
+    pub mfa_session_expires_at: Option<i64>,
+}
+
+type ProfileSections = BTreeMap<String, HashMap<String, String>>;
+
+/// Parse an INI-style shared file. In the config file every profile but
+/// `default` is written `[profile name]`; other section types (`sso-session`,
+/// `services`) are not profiles. Indented sub-properties are skipped.
+fn parse_shared_file(text: &str, is_config: bool, sections: &mut ProfileSections) {
+    let mut current: Option<String> = None;
+    for line in text.lines() {
+        if line.starts_with([' ', '\t']) {
+            continue;
+        }
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let header = header.trim();
+            current = if !is_config || header == "default" {
+                Some(header.to_string())
+            } else {
+                header
+                    .strip_prefix("profile ")
+                    .map(|name| name.trim().to_string())
+            };
+            if let Some(name) = &current {
+                sections.entry(name.clone()).or_default();
+            }
+            continue;
+        }
+        let (Some(name), Some((key, value))) = (&current, line.split_once('=')) else {
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        if let Some(section) = sections.get_mut(name) {
+            section.insert(key.trim().to_ascii_lowercase(), value.to_string());
+        }
+    }
+}
+
+fn describe_profile(name: &str, settings: &HashMap<String, String>) -> AwsProfile {
+    let has = |key: &str| settings.contains_key(key);
+    let kind = if has("role_arn") {
+        ProfileKind::AssumeRole
+    } else if has("sso_start_url") || has("sso_session") {
+        ProfileKind::Sso
+    } else if has("credential_process") {
+        ProfileKind::CredentialProcess
+    } else if has("web_identity_token_file") {
+        ProfileKind::WebIdentity
+    } else if has("aws_access_key_id") {
+        ProfileKind::StaticKeys
+    } else {
+        ProfileKind::Other
+    };
+    let get = |key: &str| settings.get(key).cloned();
+    AwsProfile {
+        name: name.to_string(),
+        kind,
+        region: get("region"),
+        role_arn: get("role_arn"),
+        source_profile: get("source_profile"),
+        external_id: get("external_id"),
+        mfa_serial: get("mfa_serial"),
+        mfa_session_expires_at: None,
+    }
+}
+
+fn shared_file_path(env_var: &str, file_name: &str) -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(env_var) {
+        return Some(PathBuf::from(path));
+    }
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(PathBuf::from(home).join(".aws").join(file_name))
+}
+
+fn load_profile_sections() -> ProfileSections {
+    let mut sections = ProfileSections::new();
+    for (env_var, file_name, is_config) in [
+        ("AWS_CONFIG_FILE", "config", true),
+        ("AWS_SHARED_CREDENTIALS_FILE", "credentials", false),
+    ] {
+        let Some(path) = shared_file_path(env_var, file_name) else {
+            continue;
+        };
+        if let Ok(text) = std::fs::read_to_string(&path) {
+            parse_shared_file(&text, is_config, &mut sections);
+        }
+    }
+    sections
+}
+
+fn to_unix_secs(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn mfa_session(profile: &str) -> Option<Credentials> {
+    let sessions = MFA_SESSIONS.lock().unwrap();
+    sessions
+        .get(profile)
+        .filter(|creds| !expires_soon(creds, SystemTime::now()))
+        .cloned()
+}
+
+/// Profiles from the shared files, sorted by name. Secrets are not returned.
+pub fn list_profiles() -> Vec<AwsProfile> {
+    load_profile_sections()
+        .iter()
+        .map(|(name, settings)| {
+            let mut profile = describe_profile(name, settings);
+            if profile.mfa_serial.is_some() {
+                profile.mfa_session_expires_at = mfa_session(name)
+                    .and_then(|creds| creds.expiry())
+                    .map(to_unix_secs);
+            }
+            profile
+        })
+        .collect()
+}
+
+pub fn find_profile(name: &str) -> Option<AwsProfile> {
+    load_profile_sections()
+        .get(name)
+        .map(|settings| describe_profile(name, settings))
+}
+
+fn expires_soon(credentials: &Credentials, now: SystemTime) -> bool {
+    credentials
+        .expiry()
+        .is_some_and(|expiry| expiry <= now + REFRESH_MARGIN)
+}
+
+/// Caches what `inner` returns until shortly before it expires.
+#[derive(Debug)]
+struct RefreshingProvider {
+    inner: SharedCredentialsProvider,
+    cached: tokio::sync::Mutex<Option<Credentials>>,
+}
+
+impl ProvideCredentials for RefreshingProvider {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        future::ProvideCredentials::new(async move {
+            let mut cached = self.cached.lock().await;
+            if let Some(credentials) = cached.as_ref() {
+                if !expires_soon(credentials, SystemTime::now()) {
+                    return Ok(credentials.clone());
+                }
+            }
+            let fresh = self.inner.provide_credentials().await?;
+            *cached = Some(fresh.clone());
+            Ok(fresh)
+        })
+    }
+}
+
+/// Hands out the session stored by `start_mfa_session` for a profile.
+#[derive(Debug)]
+struct MfaSessionProvider {
+    profile: String,
+}
+
+impl ProvideCredentials for MfaSessionProvider {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        future::ProvideCredentials::ready(mfa_session(&self.profile).ok_or_else(|| {
+            CredentialsError::not_loaded(format!(
+                "Profile '{}' requires an MFA code; start a new MFA session",
+                self.profile
+            ))
+        }))
+    }
+}
+
+/// Credentials provider for a non-static source, shared across clients so
+/// resolved sessions are reused. `None` means use the stored keys.
+pub fn credentials_provider(
+    source: &CredentialSource,
+) -> AwsResult<Option<SharedCredentialsProvider>> {
+    let mut providers = PROVIDERS.lock().unwrap();
+    if let Some(provider) = providers.get(source) {
+        return Ok(Some(provider.clone()));
+    }
+
+    let provider = match source {
+        CredentialSource::Static => return Ok(None),
+        CredentialSource::Profile { name } => {
+            let profile =
+                find_profile(name).ok_or_else(|| format!("AWS profile '{}' not found", name))?;
+            if profile.mfa_serial.is_some() {
+                // Sessions are cached in MFA_SESSIONS already
+                SharedCredentialsProvider::new(MfaSessionProvider {
+                    profile: name.clone(),
+                })
+            } else {
+                SharedCredentialsProvider::new(RefreshingProvider {
+                    inner: SharedCredentialsProvider::new(
+                        ProfileFileCredentialsProvider::builder()
+                            .profile_name(name)
+                            .build(),
+                    ),
+                    cached: tokio::sync::Mutex::new(None),
+                })
+            }
+        }
+        CredentialSource::Environment => SharedCredentialsProvider::new(RefreshingProvider {
+            inner: SharedCredentialsProvider::new(EnvironmentVariableCredentialsProvider::new()),
+            cached: tokio::sync::Mutex::new(None),
+        }),
+    };
+    providers.insert(source.clone(), provider.clone());
+    Ok(Some(provider))
+}
+
+/// Drop cached providers so edited profile files are picked up.
+pub fn reset_providers() {
+    PROVIDERS.lock().unwrap().clear();
+}
+
+/// Resolve credentials for code that signs requests itself (presigned POST).
+pub async fn resolve_credentials(
+    source: &CredentialSource,
+    access_key_id: &str,
+    secret_access_key: &str,
+) -> AwsResult<Credentials> {
+    match credentials_provider(source)? {
+        Some(provider) => Ok(provider.provide_credentials().await?),
+        None => Ok(Credentials::new(
+            access_key_id,
+            secrets::resolve(secret_access_key).await?,
+            None,
+            None,
+            "stored-keys",
+        )),
+    }
+}
+
+fn sts_credentials(
+    credentials: Option<&aws_sdk_sts::types::Credentials>,
+) -> AwsResult<Credentials> {
+    let credentials = credentials.ok_or("STS returned no credentials")?;
+    let expiry = UNIX_EPOCH + Duration::from_secs(credentials.expiration().secs().max(0) as u64);
+    Ok(Credentials::new(
+        credentials.access_key_id(),
+        credentials.secret_access_key(),
+        Some(credentials.session_token().to_string()),
+        Some(expiry),
+        "mfa-session",
+    ))
+}
+
+/// Start an MFA session for a profile with `mfa_serial`: AssumeRole (with the
+/// profile's `external_id`) when it has `role_arn`, otherwise GetSessionToken
+/// with the profile's own keys. Returns the session expiry in unix seconds.
+pub async fn start_mfa_session(
+    profile_name: &str,
+    token_code: &str,
+    duration_secs: Option<i32>,
+) -> AwsResult<i64> {
+    let profile = find_profile(profile_name)
+        .ok_or_else(|| format!("AWS profile '{}' not found", profile_name))?;
+    let serial = profile
+        .mfa_serial
+        .clone()
+        .ok_or_else(|| format!("AWS profile '{}' has no mfa_serial", profile_name))?;
+    let token_code = token_code.trim();
+    if token_code.len() != 6 || !token_code.chars().all(|c| c.is_ascii_digit()) {
+        return Err("MFA code must be 6 digits".into());
+    }
+
+    // Role profiles take their base credentials from source_profile
+    let base_profile = match (&profile.role_arn, &profile.source_profile) {
+        (Some(_), Some(source)) => source.clone(),
+        (Some(_), None) => {
+            return Err(format!(
+                "AWS profile '{}' needs source_profile to assume a role with MFA",
+                profile_name
+            )
+            .into())
+        }
+        (None, _) => profile_name.to_string(),
+    };
+    let region = profile
+        .region
+        .clone()
+        .unwrap_or_else(|| DEFAULT_REGION.to_string());
+    let sts_config = aws_sdk_sts::Config::builder()
+        .behavior_version(aws_sdk_sts::config::BehaviorVersion::latest())
+        .region(aws_sdk_sts::config::Region::new(region))
+        .credentials_provider(
+            ProfileFileCredentialsProvider::builder()
+                .profile_name(&base_profile)
+                .build(),
+        )
+        .build();
+    let sts = aws_sdk_sts::Client::from_conf(sts_config);
+
+    let credentials = match &profile.role_arn {
+        Some(role_arn) => {
+            let response = sts
+                .assume_role()
+                .role_arn(role_arn)
+                .role_session_name(format!("r2-{}", chrono::Utc::now().timestamp()))
+                .set_external_id(profile.external_id.clone())
+                .serial_number(&serial)
+                .token_code(token_code)
+                .set_duration_seconds(duration_secs)
+                .send()
+                .await
+                .map_err(|e| format!("AssumeRole failed: {}", e))?;
+            sts_credentials(response.credentials())?
+        }
+        None => {
+            let response = sts
+                .get_session_token()
+                .serial_number(&serial)
+                .token_code(token_code)
+                .set_duration_seconds(duration_secs)
+                .send()
+                .await
+                .map_err(|e| format!("GetSessionToken failed: {}", e))?;
+            sts_credentials(response.credentials())?
+        }
+    };
+
+    let expires_at = credentials.expiry().map(to_unix_secs).unwrap_or_default();
+    MFA_SESSIONS
+        .lock()
+        .unwrap()
+        .insert(profile_name.to_string(), credentials);
+    Ok(expires_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_files_are_merged_by_profile() {
+        let mut sections = ProfileSections::new();
+        parse_shared_file(
+            "[default]\nregion = eu-west-1\n\n[profile admin]\nrole_arn = arn:aws:iam::1:role/Admin\nsource_profile = default\nmfa_serial = arn:aws:iam::1:mfa/me\ns3 =\n  max_concurrent_requests = 4\n\n[sso-session corp]\nsso_region = us-east-1\n",
+            true,
+            &mut sections,
+        );
+        parse_shared_file(
+            "# keys\n[default]\naws_access_key_id = AKIA\naws_secret_access_key = secret\n[tool]\ncredential_process = /usr/bin/creds\n",
+            false,
+            &mut sections,
+        );
+
+        let names: Vec<&str> = sections.keys().map(String::as_str).collect();
+        assert_eq!(names, vec!["admin", "default", "tool"]);
+
+        let default = describe_profile("default", &sections["default"]);
+        assert_eq!(default.kind, ProfileKind::StaticKeys);
+        assert_eq!(default.region.as_deref(), Some("eu-west-1"));
+
+        let admin = describe_profile("admin", &sections["admin"]);
+        assert_eq!(admin.kind, ProfileKind::AssumeRole);
+        assert_eq!(admin.source_profile.as_deref(), Some("default"));
+        assert!(admin.mfa_serial.is_some());
+        assert!(!sections["admin"].contains_key("max_concurrent_requests"));
+
+        let tool = describe_profile("tool", &sections["tool"]);
+        assert_eq!(tool.kind, ProfileKind::CredentialProcess);
+    }
+
+    #[test]
+    fn credential_source_round_trips_through_columns() {
+        for source in [
+            CredentialSource::Static,
+            CredentialSource::Environment,
+            CredentialSource::Profile {
+                name: "dev".to_string(),
+            },
+        ] {
+            let (kind, name) = source.to_columns();
+            assert_eq!(
+                CredentialSource::from_columns(kind, name.map(str::to_string)),
+                source
+            );
+        }
+        assert_eq!(
+            CredentialSource::from_columns("profile", None),
+            CredentialSource::Static
+        );
+    }
+
+    #[test]
+    fn credentials_near_expiry_are_refreshed() {
+        let now = SystemTime::now();
+        let soon = Credentials::new("a", "b", None, Some(now + Duration::from_secs(60)), "t");
+        let later = Credentials::new("a", "b", None, Some(now + Duration::from_secs(3600)), "t");
+        let forever = Credentials::new("a", "b", None, None, "t");
+        assert!(expires_soon(&soon, now));
+        assert!(!expires_soon(&later, now));
+        assert!(!expires_soon(&forever, now));
+    }
+}
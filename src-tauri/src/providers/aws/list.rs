@@ -68,6 +68,7 @@ pub async fn list_objects(
                     .map(|dt| dt.to_string())
                     .unwrap_or_default(),
                 etag: obj.e_tag().unwrap_or_default().to_string(),
+                storage_class: obj.storage_class().map(|class| class.as_str().to_string()),
             })
         })
         .collect();
@@ -124,6 +125,7 @@ pub async fn list_all_objects_recursive(
                             .map(|dt| dt.to_string())
                             .unwrap_or_default(),
                         etag: obj.e_tag().unwrap_or_default().to_string(),
+                        storage_class: obj.storage_class().map(|class| class.as_str().to_string()),
                     });
                 }
             }
@@ -190,6 +192,7 @@ pub async fn list_folder_objects(
                         .map(|dt| dt.to_string())
                         .unwrap_or_default(),
                     etag: obj.e_tag().unwrap_or_default().to_string(),
+                    storage_class: obj.storage_class().map(|class| class.as_str().to_string()),
                 })
             })
             .collect();
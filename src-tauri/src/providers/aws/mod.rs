@@ -1,11 +1,16 @@
+pub mod credentials;
 mod list;
 mod objects;
 mod presigned;
 mod types;
 mod upload;
 
+pub use credentials::CredentialSource;
 pub use list::{list_all_objects_recursive, list_buckets, list_folder_objects, list_objects};
-pub use objects::{copy_object_between_buckets, delete_object, delete_objects, rename_object};
+pub use objects::{
+    copy_object_between_buckets, delete_object, delete_objects, head_archive_state, rename_object,
+    restore_object,
+};
 pub use presigned::{generate_presigned_put_url, generate_presigned_url};
 pub use types::{create_aws_client, AwsBucket, AwsConfig, AwsObject, ListObjectsResult};
 pub use upload::{
@@ -1,5 +1,10 @@
 use super::types::{create_aws_client, AwsConfig, AwsResult};
-use aws_sdk_s3::types::{Delete, ObjectIdentifier};
+use crate::providers::batch_delete::{self, DeleteObjectsOutcome, DeleteProgressFn};
+use crate::providers::capability::describe_sdk_error;
+use crate::providers::encryption::{ObjectEncryption, SseCustomerKey};
+use crate::providers::multipart_copy::{self, CopyEncryption, CopyProgressFn};
+use crate::providers::storage_class::{restore_request, ArchiveState, RestoreTier};
+use aws_sdk_s3::error::ProvideErrorMetadata;
 
 pub async fn delete_object(config: &AwsConfig, key: &str) -> AwsResult<()> {
     let client = create_aws_client(config).await?;
@@ -12,67 +17,112 @@ pub async fn delete_object(config: &AwsConfig, key: &str) -> AwsResult<()> {
     Ok(())
 }
 
-pub async fn delete_objects(config: &AwsConfig, keys: Vec<String>) -> AwsResult<()> {
+pub async fn delete_objects(
+    config: &AwsConfig,
+    keys: Vec<String>,
+    on_progress: Option<DeleteProgressFn>,
+) -> AwsResult<DeleteObjectsOutcome> {
     if keys.is_empty() {
-        return Ok(());
+        return Ok(DeleteObjectsOutcome::default());
     }
 
     let client = create_aws_client(config).await?;
-
-    let objects: Vec<ObjectIdentifier> = keys
-        .iter()
-        .map(|key| ObjectIdentifier::builder().key(key).build().unwrap())
-        .collect();
-
-    let delete = Delete::builder().set_objects(Some(objects)).build()?;
-
-    client
-        .delete_objects()
-        .bucket(&config.bucket)
-        .delete(delete)
-        .send()
-        .await?;
-
-    Ok(())
+    batch_delete::delete_objects(&client, &config.bucket, keys, on_progress).await
 }
 
-pub async fn copy_object(config: &AwsConfig, source_key: &str, dest_key: &str) -> AwsResult<()> {
+pub async fn copy_object(
+    config: &AwsConfig,
+    source_key: &str,
+    dest_key: &str,
+    on_progress: Option<CopyProgressFn>,
+) -> AwsResult<u64> {
     let client = create_aws_client(config).await?;
-    let copy_source = format!("{}/{}", config.bucket, source_key);
-
-    client
-        .copy_object()
-        .bucket(&config.bucket)
-        .copy_source(copy_source)
-        .key(dest_key)
-        .send()
-        .await?;
-
-    Ok(())
+    multipart_copy::copy_object(
+        &client,
+        &config.bucket,
+        source_key,
+        &config.bucket,
+        dest_key,
+        CopyEncryption::same(config.encryption.as_ref()),
+        config.storage_class.as_ref(),
+        on_progress,
+    )
+    .await
 }
 
 pub async fn copy_object_between_buckets(
     config: &AwsConfig,
     source_bucket: &str,
     source_key: &str,
+    source_encryption: Option<&ObjectEncryption>,
     dest_key: &str,
-) -> AwsResult<()> {
+    on_progress: Option<CopyProgressFn>,
+) -> AwsResult<u64> {
     let client = create_aws_client(config).await?;
-    let copy_source = format!("{}/{}", source_bucket, source_key);
+    multipart_copy::copy_object(
+        &client,
+        source_bucket,
+        source_key,
+        &config.bucket,
+        dest_key,
+        CopyEncryption {
+            source: source_encryption,
+            dest: config.encryption.as_ref(),
+        },
+        config.storage_class.as_ref(),
+        on_progress,
+    )
+    .await
+}
 
-    client
-        .copy_object()
+pub async fn rename_object(
+    config: &AwsConfig,
+    old_key: &str,
+    new_key: &str,
+    on_progress: Option<CopyProgressFn>,
+) -> AwsResult<()> {
+    copy_object(config, old_key, new_key, on_progress).await?;
+    delete_object(config, old_key).await?;
+    Ok(())
+}
+
+/// Storage class and restore progress of an object, from HEAD.
+pub async fn head_archive_state(config: &AwsConfig, key: &str) -> AwsResult<ArchiveState> {
+    let client = create_aws_client(config).await?;
+    let head = client
+        .head_object()
         .bucket(&config.bucket)
-        .copy_source(copy_source)
-        .key(dest_key)
+        .key(key)
+        .sse_customer_key_from(config.encryption.as_ref())
         .send()
-        .await?;
-
-    Ok(())
+        .await
+        .map_err(describe_sdk_error)?;
+    Ok(ArchiveState::from_head(
+        head.storage_class().map(|class| class.as_str()),
+        head.archive_status().map(|status| status.as_str()),
+        head.restore(),
+    ))
 }
 
-pub async fn rename_object(config: &AwsConfig, old_key: &str, new_key: &str) -> AwsResult<()> {
-    copy_object(config, old_key, new_key).await?;
-    delete_object(config, old_key).await?;
-    Ok(())
+/// Request a restore of an archived object. A restore that is already
+/// running is not an error.
+pub async fn restore_object(
+    config: &AwsConfig,
+    key: &str,
+    tier: RestoreTier,
+    days: Option<i32>,
+) -> AwsResult<()> {
+    let client = create_aws_client(config).await?;
+    let result = client
+        .restore_object()
+        .bucket(&config.bucket)
+        .key(key)
+        .restore_request(restore_request(tier, days)?)
+        .send()
+        .await;
+    match result {
+        Ok(_) => Ok(()),
+        Err(err) if err.code() == Some("RestoreAlreadyInProgress") => Ok(()),
+        Err(err) => Err(describe_sdk_error(err)),
+    }
 }
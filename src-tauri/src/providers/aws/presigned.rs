@@ -1,4 +1,5 @@
 use super::types::{create_aws_client, AwsConfig, AwsResult};
+use crate::providers::encryption::{SseCustomerKey, SseWrite};
 use aws_sdk_s3::presigning::PresigningConfig;
 use std::time::Duration;
 
@@ -17,6 +18,7 @@ pub async fn generate_presigned_url(
         .get_object()
         .bucket(&config.bucket)
         .key(key)
+        .sse_customer_key_from(config.encryption.as_ref())
         .presigned(presigning_config)
         .await?;
 
@@ -38,6 +40,7 @@ pub async fn generate_presigned_put_url(
         .put_object()
         .bucket(&config.bucket)
         .key(key)
+        .sse_from(config.encryption.as_ref())
         .presigned(presigning_config)
         .await?;
 
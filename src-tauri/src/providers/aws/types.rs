@@ -1,4 +1,9 @@
+use super::credentials::{credentials_provider, CredentialSource};
+use crate::db::default_endpoint_scheme;
+use crate::providers::encryption::ObjectEncryption;
 use crate::providers::s3_client::{create_s3_client, S3ClientConfig, S3Result};
+use crate::secrets;
+use aws_sdk_s3::types::StorageClass;
 use aws_sdk_s3::Client;
 use serde::{Deserialize, Serialize};
 
@@ -13,6 +18,17 @@ pub struct AwsConfig {
     pub endpoint_scheme: Option<String>,
     pub endpoint_host: Option<String>,
     pub force_path_style: bool,
+    /// Where credentials come from; the key fields are unused unless `Static`.
+    #[serde(default)]
+    pub credentials: CredentialSource,
+    /// Bucket encryption resolved from the saved settings; never sent by the
+    /// frontend.
+    #[serde(skip)]
+    pub encryption: Option<ObjectEncryption>,
+    /// Storage class for objects this config writes (uploads and copies);
+    /// `None` uses the bucket default, or keeps the source's class on copy.
+    #[serde(skip)]
+    pub storage_class: Option<StorageClass>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +37,7 @@ pub struct AwsObject {
     pub size: i64,
     pub last_modified: String,
     pub etag: String,
+    pub storage_class: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,18 +66,23 @@ fn build_endpoint_url(config: &AwsConfig) -> Option<String> {
     if host.is_empty() {
         return None;
     }
-    let scheme = config.endpoint_scheme.as_deref().unwrap_or("https");
+    let scheme = config
+        .endpoint_scheme
+        .as_deref()
+        .unwrap_or(default_endpoint_scheme("aws"));
     Some(format!("{}://{}", scheme, host))
 }
 
 pub async fn create_aws_client(config: &AwsConfig) -> AwsResult<Client> {
     let endpoint_url = build_endpoint_url(config);
+    let secret_access_key = secrets::resolve(&config.secret_access_key).await?;
     let client = create_s3_client(&S3ClientConfig {
         access_key_id: &config.access_key_id,
-        secret_access_key: &config.secret_access_key,
+        secret_access_key: &secret_access_key,
         region: &config.region,
         endpoint_url: endpoint_url.as_deref(),
         force_path_style: config.force_path_style,
+        credentials_provider: credentials_provider(&config.credentials)?,
     })?;
 
     Ok(client)
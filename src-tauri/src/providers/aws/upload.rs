@@ -1,6 +1,9 @@
 use super::types::{create_aws_client, AwsConfig, AwsResult};
+use crate::client_encryption::{seal_file, ClientKey};
+use crate::providers::encryption::{SseCustomerKey, SseWrite};
 use aws_sdk_s3::primitives::ByteStream;
 use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
@@ -21,6 +24,7 @@ pub async fn upload_content(
         .put_object()
         .bucket(&config.bucket)
         .key(key)
+        .sse_from(config.encryption.as_ref())
         .body(body);
 
     if let Some(ct) = content_type {
@@ -39,6 +43,7 @@ pub async fn upload_file_simple(
     key: &str,
     file_path: &Path,
     content_type: Option<&str>,
+    metadata: Option<&HashMap<String, String>>,
 ) -> AwsResult<String> {
     let client = create_aws_client(config).await?;
     let body = ByteStream::from_path(file_path).await?;
@@ -47,6 +52,9 @@ pub async fn upload_file_simple(
         .put_object()
         .bucket(&config.bucket)
         .key(key)
+        .sse_from(config.encryption.as_ref())
+        .set_storage_class(config.storage_class.clone())
+        .set_metadata(metadata.cloned())
         .body(body);
 
     if let Some(ct) = content_type {
@@ -64,13 +72,17 @@ pub async fn initiate_multipart_upload(
     config: &AwsConfig,
     key: &str,
     content_type: Option<&str>,
+    metadata: Option<&HashMap<String, String>>,
 ) -> AwsResult<String> {
     let client = create_aws_client(config).await?;
 
     let mut request = client
         .create_multipart_upload()
         .bucket(&config.bucket)
-        .key(key);
+        .key(key)
+        .sse_from(config.encryption.as_ref())
+        .set_storage_class(config.storage_class.clone())
+        .set_metadata(metadata.cloned());
 
     if let Some(ct) = content_type {
         request = request.content_type(ct);
@@ -102,6 +114,7 @@ pub async fn upload_part(
         .key(key)
         .upload_id(upload_id)
         .part_number(part_number)
+        .sse_customer_key_from(config.encryption.as_ref())
         .body(body)
         .send()
         .await?;
@@ -138,6 +151,7 @@ pub async fn complete_multipart_upload(
         .bucket(&config.bucket)
         .key(key)
         .upload_id(upload_id)
+        .sse_customer_key_from(config.encryption.as_ref())
         .multipart_upload(completed_upload)
         .send()
         .await?;
@@ -194,23 +208,24 @@ pub async fn list_parts(
 }
 
 #[allow(dead_code)]
+#[allow(clippy::too_many_arguments)]
 pub async fn upload_file_multipart(
     config: &AwsConfig,
     key: &str,
     file_path: &Path,
     content_type: Option<&str>,
+    metadata: Option<&HashMap<String, String>>,
     part_size: u64,
     concurrency: usize,
     progress_callback: Option<Box<dyn Fn(u64, u64) + Send + Sync>>,
 ) -> AwsResult<String> {
-    let metadata = tokio::fs::metadata(file_path).await?;
-    let file_size = metadata.len();
+    let file_size = tokio::fs::metadata(file_path).await?.len();
 
     if file_size == 0 {
         return Err("Cannot upload empty file".into());
     }
 
-    let upload_id = initiate_multipart_upload(config, key, content_type).await?;
+    let upload_id = initiate_multipart_upload(config, key, content_type, metadata).await?;
     let total_parts = file_size.div_ceil(part_size) as usize;
 
     let semaphore = Arc::new(Semaphore::new(concurrency));
@@ -293,25 +308,37 @@ pub async fn upload_file(
     key: &str,
     file_path: &Path,
     content_type: Option<&str>,
+    client_key: Option<&ClientKey>,
     progress_callback: Option<Box<dyn Fn(u64, u64) + Send + Sync>>,
 ) -> AwsResult<String> {
     const MULTIPART_THRESHOLD: u64 = 100 * 1024 * 1024;
     const PART_SIZE: u64 = 20 * 1024 * 1024;
     const CONCURRENCY: usize = 6;
 
+    // Client-side encryption uploads an encrypted temp copy instead
+    let sealed = match client_key {
+        Some(client_key) => Some(seal_file(file_path, client_key).await?),
+        None => None,
+    };
+    let metadata = sealed
+        .as_ref()
+        .map(|sealed| sealed.envelope().to_metadata());
+    let file_path = sealed.as_ref().map_or(file_path, |sealed| sealed.path());
+
     let file_size = tokio::fs::metadata(file_path).await?.len();
 
     if file_size < MULTIPART_THRESHOLD {
         if let Some(ref cb) = progress_callback {
             cb(file_size, file_size);
         }
-        upload_file_simple(config, key, file_path, content_type).await
+        upload_file_simple(config, key, file_path, content_type, metadata.as_ref()).await
     } else {
         upload_file_multipart(
             config,
             key,
             file_path,
             content_type,
+            metadata.as_ref(),
             PART_SIZE,
             CONCURRENCY,
             progress_callback,
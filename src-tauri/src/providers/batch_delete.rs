@@ -0,0 +1,271 @@
+//! Batched DeleteObjects with per-key error reporting.
+//!
+//! DeleteObjects accepts at most 1000 keys and reports failures per key in
+//! the response body rather than as a request error, so large selections are
+//! split into chunks sent with bounded parallelism and every chunk's `Errors`
+//! list is folded into a single outcome.
+
+use super::capability::describe_sdk_error;
+use super::s3_client::S3Result;
+use aws_sdk_s3::types::{Delete, ObjectIdentifier};
+use aws_sdk_s3::Client;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Keys per DeleteObjects request (S3 hard limit).
+const DELETE_BATCH_SIZE: usize = 1000;
+const MAX_CONCURRENT_DELETE_BATCHES: usize = 4;
+
+/// Progress callback: (deleted, failed) so far.
+pub type DeleteProgressFn = Arc<dyn Fn(usize, usize) + Send + Sync>;
+
+/// An object to delete: its current version, or one specific version (or
+/// delete marker) of a versioned bucket.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeleteTarget {
+    pub key: String,
+    pub version_id: Option<String>,
+}
+
+impl DeleteTarget {
+    pub fn current(key: String) -> Self {
+        DeleteTarget {
+            key,
+            version_id: None,
+        }
+    }
+}
+
+/// A key the server refused to delete.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeleteFailure {
+    pub key: String,
+    #[serde(rename = "versionId", skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    pub code: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeleteObjectsOutcome {
+    pub deleted: Vec<String>,
+    pub failed: Vec<DeleteFailure>,
+}
+
+impl DeleteObjectsOutcome {
+    fn merge(&mut self, other: DeleteObjectsOutcome) {
+        self.deleted.extend(other.deleted);
+        self.failed.extend(other.failed);
+    }
+
+    /// Every key of a chunk failed with the same request-level error.
+    fn chunk_failed(chunk: &[DeleteTarget], message: &str) -> Self {
+        DeleteObjectsOutcome {
+            deleted: Vec::new(),
+            failed: chunk
+                .iter()
+                .map(|target| DeleteFailure {
+                    key: target.key.clone(),
+                    version_id: target.version_id.clone(),
+                    code: None,
+                    message: message.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Split a chunk into deleted and failed keys given the response's `Errors`.
+/// Quiet mode only lists failures, so every other target counts as deleted.
+fn fold_chunk(chunk: &[DeleteTarget], failures: Vec<DeleteFailure>) -> DeleteObjectsOutcome {
+    let mut by_target: HashMap<DeleteTarget, DeleteFailure> = failures
+        .into_iter()
+        .map(|failure| {
+            let target = DeleteTarget {
+                key: failure.key.clone(),
+                version_id: failure.version_id.clone(),
+            };
+            (target, failure)
+        })
+        .collect();
+
+    let mut outcome = DeleteObjectsOutcome::default();
+    for target in chunk {
+        match by_target.remove(target) {
+            Some(failure) => outcome.failed.push(failure),
+            None => outcome.deleted.push(target.key.clone()),
+        }
+    }
+    outcome
+}
+
+async fn delete_chunk(
+    client: &Client,
+    bucket: &str,
+    chunk: &[DeleteTarget],
+) -> S3Result<DeleteObjectsOutcome> {
+    let objects = chunk
+        .iter()
+        .map(|target| {
+            ObjectIdentifier::builder()
+                .key(&target.key)
+                .set_version_id(target.version_id.clone())
+                .build()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let delete = Delete::builder()
+        .set_objects(Some(objects))
+        .quiet(true)
+        .build()?;
+
+    let response = client
+        .delete_objects()
+        .bucket(bucket)
+        .delete(delete)
+        .send()
+        .await
+        .map_err(describe_sdk_error)?;
+
+    let failures = response
+        .errors()
+        .iter()
+        .filter_map(|error| {
+            Some(DeleteFailure {
+                key: error.key()?.to_string(),
+                version_id: error.version_id().map(str::to_string),
+                code: error.code().map(str::to_string),
+                message: error.message().unwrap_or("Delete failed").to_string(),
+            })
+        })
+        .collect();
+
+    Ok(fold_chunk(chunk, failures))
+}
+
+/// Delete `keys` in chunks of 1000 with bounded parallelism. Request-level
+/// failures mark the whole chunk as failed instead of aborting the batch.
+pub async fn delete_objects(
+    client: &Client,
+    bucket: &str,
+    keys: Vec<String>,
+    on_progress: Option<DeleteProgressFn>,
+) -> S3Result<DeleteObjectsOutcome> {
+    let targets = keys.into_iter().map(DeleteTarget::current).collect();
+    delete_targets(client, bucket, targets, on_progress).await
+}
+
+/// Like `delete_objects`, for targets that may name specific versions.
+pub async fn delete_targets(
+    client: &Client,
+    bucket: &str,
+    targets: Vec<DeleteTarget>,
+    on_progress: Option<DeleteProgressFn>,
+) -> S3Result<DeleteObjectsOutcome> {
+    if targets.is_empty() {
+        return Ok(DeleteObjectsOutcome::default());
+    }
+
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_DELETE_BATCHES));
+    let deleted = Arc::new(AtomicUsize::new(0));
+    let failed = Arc::new(AtomicUsize::new(0));
+    let mut handles = Vec::new();
+
+    for chunk in targets.chunks(DELETE_BATCH_SIZE) {
+        let permit = semaphore.clone().acquire_owned().await?;
+        let chunk = chunk.to_vec();
+        let client = client.clone();
+        let bucket = bucket.to_string();
+        let deleted = deleted.clone();
+        let failed = failed.clone();
+        let on_progress = on_progress.clone();
+
+        handles.push(tokio::spawn(async move {
+            let _permit = permit;
+            let outcome = match delete_chunk(&client, &bucket, &chunk).await {
+                Ok(outcome) => outcome,
+                Err(e) => DeleteObjectsOutcome::chunk_failed(&chunk, &e.to_string()),
+            };
+
+            let deleted_so_far =
+                deleted.fetch_add(outcome.deleted.len(), Ordering::SeqCst) + outcome.deleted.len();
+            let failed_so_far =
+                failed.fetch_add(outcome.failed.len(), Ordering::SeqCst) + outcome.failed.len();
+            if let Some(cb) = &on_progress {
+                cb(deleted_so_far, failed_so_far);
+            }
+            outcome
+        }));
+    }
+
+    let mut outcome = DeleteObjectsOutcome::default();
+    for handle in handles {
+        outcome.merge(handle.await?);
+    }
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn targets(names: &[&str]) -> Vec<DeleteTarget> {
+        keys(names).into_iter().map(DeleteTarget::current).collect()
+    }
+
+    #[test]
+    fn fold_chunk_separates_failed_keys() {
+        let chunk = targets(&["a", "b", "c"]);
+        let outcome = fold_chunk(
+            &chunk,
+            vec![DeleteFailure {
+                key: "b".to_string(),
+                version_id: None,
+                code: Some("AccessDenied".to_string()),
+                message: "Access Denied".to_string(),
+            }],
+        );
+
+        assert_eq!(outcome.deleted, keys(&["a", "c"]));
+        assert_eq!(outcome.failed.len(), 1);
+        assert_eq!(outcome.failed[0].key, "b");
+        assert_eq!(outcome.failed[0].code.as_deref(), Some("AccessDenied"));
+    }
+
+    #[test]
+    fn chunk_failure_marks_every_key() {
+        let chunk = targets(&["a", "b"]);
+        let outcome = DeleteObjectsOutcome::chunk_failed(&chunk, "timeout");
+        assert!(outcome.deleted.is_empty());
+        assert_eq!(outcome.failed.len(), 2);
+        assert!(outcome.failed.iter().all(|f| f.message == "timeout"));
+    }
+
+    #[test]
+    fn fold_chunk_matches_failures_by_version() {
+        let version = |key: &str, id: &str| DeleteTarget {
+            key: key.to_string(),
+            version_id: Some(id.to_string()),
+        };
+        let chunk = vec![version("a", "v1"), version("a", "v2")];
+        let outcome = fold_chunk(
+            &chunk,
+            vec![DeleteFailure {
+                key: "a".to_string(),
+                version_id: Some("v2".to_string()),
+                code: Some("AccessDenied".to_string()),
+                message: "Access Denied".to_string(),
+            }],
+        );
+
+        assert_eq!(outcome.deleted, keys(&["a"]));
+        assert_eq!(outcome.failed.len(), 1);
+        assert_eq!(outcome.failed[0].version_id.as_deref(), Some("v2"));
+    }
+}
@@ -0,0 +1,202 @@
+//! Bucket create/delete across providers.
+
+use super::batch_delete::{self, DeleteObjectsOutcome, DeleteProgressFn, DeleteTarget};
+use super::capability::{describe_sdk_error, is_unsupported_code};
+use super::config::ProviderConfig;
+use super::s3_client::{list_all_keys, S3Result};
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::types::{
+    BucketLocationConstraint, BucketVersioningStatus, CreateBucketConfiguration,
+};
+use aws_sdk_s3::Client;
+use std::sync::Arc;
+
+/// Create the bucket named in `config`.
+///
+/// `location` is the AWS region / MinIO region, or an R2 location hint
+/// (`wnam`, `enam`, `weur`, `eeur`, `apac`, `oc`). For AWS it defaults to the
+/// client region; `us-east-1` must be sent without a constraint.
+pub async fn create_bucket(config: &ProviderConfig, location: Option<&str>) -> S3Result<()> {
+    let client = config.create_client().await?;
+
+    let location = location
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .or_else(|| match config {
+            ProviderConfig::Aws(aws) => Some(aws.region.clone()),
+            _ => None,
+        })
+        .filter(|l| l != "us-east-1" && l != "auto");
+
+    let mut request = client.create_bucket().bucket(config.bucket());
+    if let Some(location) = location {
+        request = request.create_bucket_configuration(
+            CreateBucketConfiguration::builder()
+                .location_constraint(BucketLocationConstraint::from(location.as_str()))
+                .build(),
+        );
+    }
+
+    request.send().await.map_err(describe_sdk_error)?;
+    Ok(())
+}
+
+/// Whether the bucket has (or once had) versioning turned on, in which case
+/// deleting keys only adds delete markers. Checked before anything is deleted;
+/// providers without the API have no versions to remove.
+async fn has_object_versions(config: &ProviderConfig, client: &Client) -> S3Result<bool> {
+    if matches!(config, ProviderConfig::R2(_)) {
+        return Ok(false);
+    }
+
+    match client
+        .get_bucket_versioning()
+        .bucket(config.bucket())
+        .send()
+        .await
+    {
+        Ok(response) => Ok(matches!(
+            response.status(),
+            Some(BucketVersioningStatus::Enabled | BucketVersioningStatus::Suspended)
+        )),
+        Err(err) if is_unsupported_code(err.code()) => Ok(false),
+        Err(err) => Err(format!(
+            "Could not check versioning of bucket '{}': {}",
+            config.bucket(),
+            describe_sdk_error(err)
+        )
+        .into()),
+    }
+}
+
+/// Every version and delete marker in the bucket, folder placeholders included.
+async fn list_all_versions(client: &Client, bucket: &str) -> S3Result<Vec<DeleteTarget>> {
+    let mut targets = Vec::new();
+    let mut key_marker: Option<String> = None;
+    let mut version_id_marker: Option<String> = None;
+
+    loop {
+        let response = client
+            .list_object_versions()
+            .bucket(bucket)
+            .set_key_marker(key_marker.clone())
+            .set_version_id_marker(version_id_marker.clone())
+            .send()
+            .await
+            .map_err(describe_sdk_error)?;
+
+        let versions = response
+            .versions()
+            .iter()
+            .map(|v| (v.key(), v.version_id()));
+        let markers = response
+            .delete_markers()
+            .iter()
+            .map(|m| (m.key(), m.version_id()));
+        for (key, version_id) in versions.chain(markers) {
+            if let Some(key) = key {
+                targets.push(DeleteTarget {
+                    key: key.to_string(),
+                    version_id: version_id.map(str::to_string),
+                });
+            }
+        }
+
+        if response.is_truncated() != Some(true) {
+            break;
+        }
+        key_marker = response.next_key_marker().map(str::to_string);
+        version_id_marker = response.next_version_id_marker().map(str::to_string);
+        if key_marker.is_none() && version_id_marker.is_none() {
+            break;
+        }
+    }
+
+    Ok(targets)
+}
+
+/// Delete every object in the bucket, reporting `(deleted, failed, total)`
+/// as each DeleteObjects chunk completes. In versioned buckets every version
+/// and delete marker is removed, so the bucket can be deleted afterwards.
+pub async fn empty_bucket<F>(
+    config: &ProviderConfig,
+    on_progress: F,
+) -> S3Result<DeleteObjectsOutcome>
+where
+    F: Fn(usize, usize, usize) + Send + Sync + 'static,
+{
+    let client = config.create_client().await?;
+    let bucket = config.bucket();
+
+    let outcome = if has_object_versions(config, &client).await? {
+        let targets = list_all_versions(&client, bucket).await?;
+        let total = targets.len();
+        let progress: DeleteProgressFn =
+            Arc::new(move |deleted, failed| on_progress(deleted, failed, total));
+        batch_delete::delete_targets(&client, bucket, targets, Some(progress)).await?
+    } else {
+        let keys = list_all_keys(&client, bucket, "").await?;
+        let total = keys.len();
+        let progress: DeleteProgressFn =
+            Arc::new(move |deleted, failed| on_progress(deleted, failed, total));
+        config.delete_objects(keys, Some(progress)).await?
+    };
+
+    abort_all_multipart_uploads(&client, bucket).await?;
+    Ok(outcome)
+}
+
+/// Abort in-progress multipart uploads; their parts would otherwise keep a
+/// bucket from being deleted on some providers.
+async fn abort_all_multipart_uploads(client: &Client, bucket: &str) -> S3Result<()> {
+    let mut key_marker: Option<String> = None;
+    let mut upload_id_marker: Option<String> = None;
+
+    loop {
+        let response = client
+            .list_multipart_uploads()
+            .bucket(bucket)
+            .set_key_marker(key_marker.clone())
+            .set_upload_id_marker(upload_id_marker.clone())
+            .send()
+            .await
+            .map_err(describe_sdk_error)?;
+
+        for upload in response.uploads() {
+            if let (Some(key), Some(upload_id)) = (upload.key(), upload.upload_id()) {
+                client
+                    .abort_multipart_upload()
+                    .bucket(bucket)
+                    .key(key)
+                    .upload_id(upload_id)
+                    .send()
+                    .await
+                    .map_err(describe_sdk_error)?;
+            }
+        }
+
+        if response.is_truncated() != Some(true) {
+            break;
+        }
+        key_marker = response.next_key_marker().map(str::to_string);
+        upload_id_marker = response.next_upload_id_marker().map(str::to_string);
+        if key_marker.is_none() && upload_id_marker.is_none() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Delete the (already empty) bucket.
+pub async fn delete_bucket(config: &ProviderConfig) -> S3Result<()> {
+    let client = config.create_client().await?;
+    client
+        .delete_bucket()
+        .bucket(config.bucket())
+        .send()
+        .await
+        .map_err(describe_sdk_error)?;
+    Ok(())
+}
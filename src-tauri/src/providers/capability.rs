@@ -0,0 +1,117 @@
+//! Optional S3 features and clean reporting when a backend lacks them.
+//!
+//! S3-compatible servers answer unimplemented APIs with a handful of error
+//! codes (`NotImplemented`, `XNotImplemented`, ...). Those are turned into an
+//! `UnsupportedFeature` error so the UI can disable the action instead of
+//! showing a raw SDK error.
+
+use super::config::ProviderConfig;
+use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    ObjectTagging,
+    BucketLifecycle,
+    BucketCors,
+    PresignedPost,
+    ObjectLock,
+}
+
+impl Feature {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Feature::ObjectTagging => "Object tagging",
+            Feature::BucketLifecycle => "Bucket lifecycle rules",
+            Feature::BucketCors => "Bucket CORS configuration",
+            Feature::PresignedPost => "Presigned POST uploads",
+            Feature::ObjectLock => "Object Lock",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UnsupportedFeature {
+    pub feature: Feature,
+    pub provider: &'static str,
+    pub bucket: String,
+}
+
+impl fmt::Display for UnsupportedFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} not supported by {} bucket '{}'",
+            self.feature.label(),
+            self.provider,
+            self.bucket
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedFeature {}
+
+impl UnsupportedFeature {
+    pub fn new(config: &ProviderConfig, feature: Feature) -> Self {
+        Self {
+            feature,
+            provider: config.provider_name(),
+            bucket: config.bucket().to_string(),
+        }
+    }
+}
+
+/// Error codes S3-compatible servers use for APIs they do not implement.
+const UNSUPPORTED_CODES: &[&str] = &[
+    "NotImplemented",
+    "XNotImplemented",
+    "NotSupported",
+    "UnsupportedOperation",
+    "MethodNotAllowed",
+];
+
+pub fn is_unsupported_code(code: Option<&str>) -> bool {
+    code.is_some_and(|code| UNSUPPORTED_CODES.contains(&code))
+}
+
+/// Fail fast for features the provider is known not to implement.
+pub fn ensure_supported(
+    config: &ProviderConfig,
+    feature: Feature,
+) -> Result<(), UnsupportedFeature> {
+    if config.supports(feature) {
+        Ok(())
+    } else {
+        Err(UnsupportedFeature::new(config, feature))
+    }
+}
+
+/// Map an SDK error from a feature call into either `UnsupportedFeature` or
+/// a readable `Code: message` error.
+pub fn feature_error<E, R>(
+    config: &ProviderConfig,
+    feature: Feature,
+    err: SdkError<E, R>,
+) -> Box<dyn std::error::Error + Send + Sync>
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+    R: fmt::Debug + Send + Sync + 'static,
+{
+    if is_unsupported_code(err.code()) {
+        return Box::new(UnsupportedFeature::new(config, feature));
+    }
+    describe_sdk_error(err)
+}
+
+/// Prefer the service's `Code: message` over the SDK's generic "service error".
+pub fn describe_sdk_error<E, R>(err: SdkError<E, R>) -> Box<dyn std::error::Error + Send + Sync>
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+    R: fmt::Debug + Send + Sync + 'static,
+{
+    match (err.code(), err.message()) {
+        (Some(code), Some(message)) => format!("{}: {}", code, message).into(),
+        (Some(code), None) => code.to_string().into(),
+        _ => Box::new(err),
+    }
+}
@@ -0,0 +1,99 @@
+//! Provider-agnostic bucket configuration.
+//!
+//! Bucket-level features (tagging, lifecycle, ...) behave the same across
+//! every S3-compatible backend once a client exists, so they take a
+//! `ProviderConfig` instead of being duplicated per provider module.
+
+use super::batch_delete::{DeleteObjectsOutcome, DeleteProgressFn};
+use super::capability::Feature;
+use super::encryption::ObjectEncryption;
+use super::s3_client::S3Result;
+use super::{aws, minio};
+use crate::r2::{self, R2Config};
+use aws_sdk_s3::Client;
+
+#[derive(Debug, Clone)]
+pub enum ProviderConfig {
+    R2(R2Config),
+    Aws(aws::AwsConfig),
+    Minio(minio::MinioConfig),
+    Rustfs(minio::MinioConfig),
+}
+
+impl ProviderConfig {
+    pub fn provider_name(&self) -> &'static str {
+        match self {
+            ProviderConfig::R2(_) => "r2",
+            ProviderConfig::Aws(_) => "aws",
+            ProviderConfig::Minio(_) => "minio",
+            ProviderConfig::Rustfs(_) => "rustfs",
+        }
+    }
+
+    pub fn bucket(&self) -> &str {
+        match self {
+            ProviderConfig::R2(config) => &config.bucket,
+            ProviderConfig::Aws(config) => &config.bucket,
+            ProviderConfig::Minio(config) | ProviderConfig::Rustfs(config) => &config.bucket,
+        }
+    }
+
+    /// Encryption resolved for the bucket, if any.
+    pub fn encryption(&self) -> Option<&ObjectEncryption> {
+        match self {
+            ProviderConfig::R2(config) => config.encryption.as_ref(),
+            ProviderConfig::Aws(config) => config.encryption.as_ref(),
+            ProviderConfig::Minio(config) | ProviderConfig::Rustfs(config) => {
+                config.encryption.as_ref()
+            }
+        }
+    }
+
+    pub fn set_encryption(&mut self, encryption: Option<ObjectEncryption>) {
+        match self {
+            ProviderConfig::R2(config) => config.encryption = encryption,
+            ProviderConfig::Aws(config) => config.encryption = encryption,
+            ProviderConfig::Minio(config) | ProviderConfig::Rustfs(config) => {
+                config.encryption = encryption
+            }
+        }
+    }
+
+    pub async fn create_client(&self) -> S3Result<Client> {
+        match self {
+            ProviderConfig::R2(config) => r2::create_r2_client(config).await,
+            ProviderConfig::Aws(config) => aws::create_aws_client(config).await,
+            ProviderConfig::Minio(config) | ProviderConfig::Rustfs(config) => {
+                minio::create_minio_client(config).await
+            }
+        }
+    }
+
+    /// Delete keys through the provider's chunked DeleteObjects.
+    pub async fn delete_objects(
+        &self,
+        keys: Vec<String>,
+        on_progress: Option<DeleteProgressFn>,
+    ) -> S3Result<DeleteObjectsOutcome> {
+        match self {
+            ProviderConfig::R2(config) => r2::delete_objects(config, keys, on_progress).await,
+            ProviderConfig::Aws(config) => aws::delete_objects(config, keys, on_progress).await,
+            ProviderConfig::Minio(config) | ProviderConfig::Rustfs(config) => {
+                minio::delete_objects(config, keys, on_progress).await
+            }
+        }
+    }
+
+    /// Features the provider is known not to implement at all, checked
+    /// before any request is sent. Anything else is discovered from the
+    /// error code the server returns.
+    pub fn supports(&self, feature: Feature) -> bool {
+        !matches!(
+            (self, feature),
+            (
+                ProviderConfig::R2(_),
+                Feature::ObjectTagging | Feature::PresignedPost | Feature::ObjectLock
+            )
+        )
+    }
+}
@@ -0,0 +1,163 @@
+//! Bucket CORS configuration.
+
+use super::capability::{ensure_supported, feature_error, Feature};
+use super::config::ProviderConfig;
+use super::s3_client::S3Result;
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::types::{CorsConfiguration, CorsRule};
+use serde::{Deserialize, Serialize};
+
+const ALLOWED_METHODS: &[&str] = &["GET", "PUT", "POST", "DELETE", "HEAD"];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BucketCorsRule {
+    pub id: Option<String>,
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    #[serde(default)]
+    pub allowed_headers: Vec<String>,
+    #[serde(default)]
+    pub expose_headers: Vec<String>,
+    pub max_age_seconds: Option<i32>,
+}
+
+impl BucketCorsRule {
+    fn validate(&self) -> Result<(), String> {
+        if self.allowed_origins.is_empty() {
+            return Err("CORS rule needs at least one allowed origin".to_string());
+        }
+        if self.allowed_methods.is_empty() {
+            return Err("CORS rule needs at least one allowed method".to_string());
+        }
+        if let Some(method) = self
+            .allowed_methods
+            .iter()
+            .find(|m| !ALLOWED_METHODS.contains(&m.to_uppercase().as_str()))
+        {
+            return Err(format!("Unsupported CORS method '{}'", method));
+        }
+        if matches!(self.max_age_seconds, Some(age) if age < 0) {
+            return Err("CORS max age cannot be negative".to_string());
+        }
+        Ok(())
+    }
+
+    fn to_sdk(&self) -> S3Result<CorsRule> {
+        Ok(CorsRule::builder()
+            .set_id(self.id.clone().filter(|id| !id.is_empty()))
+            .set_allowed_origins(Some(self.allowed_origins.clone()))
+            .set_allowed_methods(Some(
+                self.allowed_methods
+                    .iter()
+                    .map(|m| m.to_uppercase())
+                    .collect(),
+            ))
+            .set_allowed_headers(Some(self.allowed_headers.clone()))
+            .set_expose_headers(Some(self.expose_headers.clone()))
+            .set_max_age_seconds(self.max_age_seconds)
+            .build()?)
+    }
+
+    fn from_sdk(rule: &CorsRule) -> Self {
+        BucketCorsRule {
+            id: rule.id().map(str::to_string),
+            allowed_origins: rule.allowed_origins().to_vec(),
+            allowed_methods: rule.allowed_methods().to_vec(),
+            allowed_headers: rule.allowed_headers().to_vec(),
+            expose_headers: rule.expose_headers().to_vec(),
+            max_age_seconds: rule.max_age_seconds(),
+        }
+    }
+}
+
+/// Get CORS rules. A bucket without a configuration returns no rules.
+pub async fn get_bucket_cors(config: &ProviderConfig) -> S3Result<Vec<BucketCorsRule>> {
+    ensure_supported(config, Feature::BucketCors)?;
+    let client = config.create_client().await?;
+
+    match client
+        .get_bucket_cors()
+        .bucket(config.bucket())
+        .send()
+        .await
+    {
+        Ok(response) => Ok(response
+            .cors_rules()
+            .iter()
+            .map(BucketCorsRule::from_sdk)
+            .collect()),
+        Err(err) if err.code() == Some("NoSuchCORSConfiguration") => Ok(Vec::new()),
+        Err(err) => Err(feature_error(config, Feature::BucketCors, err)),
+    }
+}
+
+/// Replace the bucket's CORS rules. An empty rule set deletes the configuration.
+pub async fn put_bucket_cors(config: &ProviderConfig, rules: &[BucketCorsRule]) -> S3Result<()> {
+    ensure_supported(config, Feature::BucketCors)?;
+    for rule in rules {
+        rule.validate()?;
+    }
+
+    let client = config.create_client().await?;
+
+    if rules.is_empty() {
+        client
+            .delete_bucket_cors()
+            .bucket(config.bucket())
+            .send()
+            .await
+            .map_err(|e| feature_error(config, Feature::BucketCors, e))?;
+        return Ok(());
+    }
+
+    let sdk_rules = rules
+        .iter()
+        .map(BucketCorsRule::to_sdk)
+        .collect::<S3Result<Vec<_>>>()?;
+    let configuration = CorsConfiguration::builder()
+        .set_cors_rules(Some(sdk_rules))
+        .build()?;
+
+    client
+        .put_bucket_cors()
+        .bucket(config.bucket())
+        .cors_configuration(configuration)
+        .send()
+        .await
+        .map_err(|e| feature_error(config, Feature::BucketCors, e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cors_rule_round_trips_and_normalizes_methods() {
+        let rule = BucketCorsRule {
+            id: Some("web".to_string()),
+            allowed_origins: vec!["https://example.com".to_string()],
+            allowed_methods: vec!["get".to_string(), "PUT".to_string()],
+            allowed_headers: vec!["*".to_string()],
+            expose_headers: vec!["ETag".to_string()],
+            max_age_seconds: Some(3600),
+        };
+        assert!(rule.validate().is_ok());
+
+        let back = BucketCorsRule::from_sdk(&rule.to_sdk().expect("valid rule"));
+        assert_eq!(back.allowed_methods, vec!["GET", "PUT"]);
+        assert_eq!(back.allowed_origins, rule.allowed_origins);
+        assert_eq!(back.max_age_seconds, Some(3600));
+    }
+
+    #[test]
+    fn cors_rule_rejects_unknown_methods() {
+        let rule = BucketCorsRule {
+            allowed_origins: vec!["*".to_string()],
+            allowed_methods: vec!["PATCH".to_string()],
+            ..Default::default()
+        };
+        assert!(rule.validate().is_err());
+    }
+}
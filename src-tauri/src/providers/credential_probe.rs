@@ -0,0 +1,265 @@
+//! Credential validation by probing what a key pair is allowed to do.
+//!
+//! ListBuckets and ListObjectsV2 are tried first, then a tiny object is
+//! written under a scratch key, read back and deleted by the version the
+//! write returned, so versioned buckets keep no copy. Each step is recorded
+//! independently, so a read-only or bucket-scoped key still gets a useful
+//! report instead of a single error.
+
+use super::capability::describe_sdk_error;
+use super::config::ProviderConfig;
+use super::encryption::{SseCustomerKey, SseWrite};
+use super::s3_client::S3Result;
+use crate::crypto::random_bytes;
+use aws_sdk_s3::primitives::ByteStream;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Prefix of the scratch objects written by the write probe.
+const PROBE_KEY_PREFIX: &str = ".credential-probe-";
+const PROBE_BODY: &[u8] = b"credential probe";
+
+/// Actions the UI offers, and the permissions each one needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CredentialAction {
+    Browse,
+    Download,
+    Share,
+    Upload,
+    Delete,
+    /// Move and rename: copy, then delete the source.
+    Move,
+}
+
+impl CredentialAction {
+    const ALL: [CredentialAction; 6] = [
+        CredentialAction::Browse,
+        CredentialAction::Download,
+        CredentialAction::Share,
+        CredentialAction::Upload,
+        CredentialAction::Delete,
+        CredentialAction::Move,
+    ];
+}
+
+/// What the credentials were allowed to do when last checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityReport {
+    pub can_list_buckets: bool,
+    pub visible_buckets: Vec<String>,
+    /// Bucket the object probes ran against: the configured one, or the
+    /// first visible bucket. `None` when there was nothing to probe.
+    pub bucket: Option<String>,
+    pub can_list: bool,
+    pub can_read: bool,
+    pub can_write: bool,
+    pub can_delete: bool,
+    /// Why a check failed, by check (`list-buckets`, `list`, `read`,
+    /// `write`, `delete`).
+    pub errors: BTreeMap<String, String>,
+    /// Scratch object left behind because deleting it, or the version the
+    /// write created, was refused.
+    pub leftover_key: Option<String>,
+    /// Actions to disable for these credentials.
+    pub disabled_actions: Vec<CredentialAction>,
+    pub checked_at: i64,
+}
+
+impl CapabilityReport {
+    pub fn allows(&self, action: CredentialAction) -> bool {
+        match action {
+            CredentialAction::Browse => self.can_list,
+            CredentialAction::Download | CredentialAction::Share => self.can_read,
+            CredentialAction::Upload => self.can_write,
+            CredentialAction::Delete => self.can_delete,
+            CredentialAction::Move => self.can_read && self.can_write && self.can_delete,
+        }
+    }
+
+    fn fail(&mut self, check: &str, message: impl ToString) {
+        self.errors.insert(check.to_string(), message.to_string());
+    }
+
+    fn finish(mut self) -> Self {
+        self.disabled_actions = CredentialAction::ALL
+            .into_iter()
+            .filter(|action| !self.allows(*action))
+            .collect();
+        self
+    }
+}
+
+fn probe_key() -> Result<String, String> {
+    Ok(format!(
+        "{}{}",
+        PROBE_KEY_PREFIX,
+        hex::encode(random_bytes::<8>()?)
+    ))
+}
+
+/// Probe the credentials in `config`. Only a failure to build a client is an
+/// error; refused requests are recorded in the report.
+pub async fn probe_credentials(config: &ProviderConfig) -> S3Result<CapabilityReport> {
+    let client = config.create_client().await?;
+    let mut report = CapabilityReport {
+        checked_at: chrono::Utc::now().timestamp(),
+        ..Default::default()
+    };
+
+    match client.list_buckets().send().await {
+        Ok(output) => {
+            report.can_list_buckets = true;
+            report.visible_buckets = output
+                .buckets()
+                .iter()
+                .filter_map(|bucket| bucket.name().map(str::to_string))
+                .collect();
+        }
+        Err(e) => report.fail("list-buckets", describe_sdk_error(e)),
+    }
+
+    let bucket = match config.bucket() {
+        "" => match report.visible_buckets.first() {
+            Some(bucket) => bucket.clone(),
+            None => return Ok(report.finish()),
+        },
+        bucket => bucket.to_string(),
+    };
+    report.bucket = Some(bucket.clone());
+
+    let mut sample_key = None;
+    match client
+        .list_objects_v2()
+        .bucket(&bucket)
+        .max_keys(1)
+        .send()
+        .await
+    {
+        Ok(output) => {
+            report.can_list = true;
+            sample_key = output
+                .contents()
+                .first()
+                .and_then(|object| object.key())
+                .map(str::to_string);
+        }
+        Err(e) => report.fail("list", describe_sdk_error(e)),
+    }
+
+    let key = probe_key()?;
+    let mut version_id = None;
+    match client
+        .put_object()
+        .bucket(&bucket)
+        .key(&key)
+        .sse_from(config.encryption())
+        .body(ByteStream::from_static(PROBE_BODY))
+        .send()
+        .await
+    {
+        Ok(output) => {
+            report.can_write = true;
+            version_id = output.version_id().map(str::to_string);
+        }
+        Err(e) => report.fail("write", describe_sdk_error(e)),
+    }
+
+    // Read the probe back, or an existing object when the write was refused
+    let read_key = if report.can_write {
+        Some(key.clone())
+    } else {
+        sample_key
+    };
+    match read_key {
+        Some(read_key) => match client
+            .get_object()
+            .bucket(&bucket)
+            .key(&read_key)
+            .range("bytes=0-0")
+            .sse_customer_key_from(config.encryption())
+            .send()
+            .await
+        {
+            Ok(_) => report.can_read = true,
+            Err(e) => report.fail("read", describe_sdk_error(e)),
+        },
+        None => report.fail(
+            "read",
+            "Not checked: the bucket is empty and the write probe was refused",
+        ),
+    }
+
+    if report.can_write {
+        match client
+            .delete_object()
+            .bucket(&bucket)
+            .key(&key)
+            .set_version_id(version_id.clone())
+            .send()
+            .await
+        {
+            Ok(_) => report.can_delete = true,
+            // Deleting a version needs more than deleting an object; the
+            // app's own deletes are plain ones, so check those instead
+            Err(_) if version_id.is_some() => {
+                report.leftover_key = Some(key.clone());
+                match client
+                    .delete_object()
+                    .bucket(&bucket)
+                    .key(&key)
+                    .send()
+                    .await
+                {
+                    Ok(_) => report.can_delete = true,
+                    Err(e) => report.fail("delete", describe_sdk_error(e)),
+                }
+            }
+            Err(e) => {
+                report.fail("delete", describe_sdk_error(e));
+                report.leftover_key = Some(key);
+            }
+        }
+    } else {
+        report.fail("delete", "Not checked: the write probe was refused");
+    }
+
+    Ok(report.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_only_keys_disable_changes() {
+        let report = CapabilityReport {
+            can_list: true,
+            can_read: true,
+            ..Default::default()
+        }
+        .finish();
+        assert_eq!(
+            report.disabled_actions,
+            vec![
+                CredentialAction::Upload,
+                CredentialAction::Delete,
+                CredentialAction::Move
+            ]
+        );
+    }
+
+    #[test]
+    fn moves_need_read_write_and_delete() {
+        let report = CapabilityReport {
+            can_list: true,
+            can_read: true,
+            can_write: true,
+            ..Default::default()
+        }
+        .finish();
+        assert!(report.allows(CredentialAction::Upload));
+        assert!(!report.allows(CredentialAction::Move));
+        assert!(!report.allows(CredentialAction::Delete));
+    }
+}
@@ -0,0 +1,344 @@
+//! Server-side encryption (SSE-S3, SSE-KMS, SSE-C) for object requests.
+//!
+//! SSE-S3 and SSE-KMS only have to be requested when an object is written.
+//! SSE-C is different: the same customer key must accompany every request that
+//! touches the object afterwards (reads, part uploads, copies), so the resolved
+//! `ObjectEncryption` travels inside the provider config.
+
+use aws_sdk_s3::operation::complete_multipart_upload::builders::CompleteMultipartUploadFluentBuilder;
+use aws_sdk_s3::operation::copy_object::builders::CopyObjectFluentBuilder;
+use aws_sdk_s3::operation::create_multipart_upload::builders::CreateMultipartUploadFluentBuilder;
+use aws_sdk_s3::operation::get_object::builders::GetObjectFluentBuilder;
+use aws_sdk_s3::operation::head_object::builders::HeadObjectFluentBuilder;
+use aws_sdk_s3::operation::put_object::builders::PutObjectFluentBuilder;
+use aws_sdk_s3::operation::upload_part::builders::UploadPartFluentBuilder;
+use aws_sdk_s3::operation::upload_part_copy::builders::UploadPartCopyFluentBuilder;
+use aws_sdk_s3::types::ServerSideEncryption;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The only algorithm S3 accepts for customer-provided keys.
+pub const SSE_C_ALGORITHM: &str = "AES256";
+
+pub const HEADER_SSE: &str = "x-amz-server-side-encryption";
+pub const HEADER_SSE_KMS_KEY_ID: &str = "x-amz-server-side-encryption-aws-kms-key-id";
+pub const HEADER_SSE_BUCKET_KEY: &str = "x-amz-server-side-encryption-bucket-key-enabled";
+pub const HEADER_SSE_C_ALGORITHM: &str = "x-amz-server-side-encryption-customer-algorithm";
+pub const HEADER_SSE_C_KEY: &str = "x-amz-server-side-encryption-customer-key";
+pub const HEADER_SSE_C_KEY_MD5: &str = "x-amz-server-side-encryption-customer-key-MD5";
+
+/// Encryption mode a bucket's writes default to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EncryptionMode {
+    #[default]
+    None,
+    SseS3,
+    SseKms,
+    SseC,
+}
+
+impl EncryptionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EncryptionMode::None => "none",
+            EncryptionMode::SseS3 => "sse-s3",
+            EncryptionMode::SseKms => "sse-kms",
+            EncryptionMode::SseC => "sse-c",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "none" => Ok(EncryptionMode::None),
+            "sse-s3" => Ok(EncryptionMode::SseS3),
+            "sse-kms" => Ok(EncryptionMode::SseKms),
+            "sse-c" => Ok(EncryptionMode::SseC),
+            other => Err(format!("Unknown encryption mode: {}", other)),
+        }
+    }
+}
+
+/// Modes a provider accepts. R2 always encrypts at rest and only exposes
+/// SSE-C; MinIO and RustFS need a KMS configured server-side for SSE-S3/KMS,
+/// which surfaces as a request error rather than being checked here.
+pub fn supported_modes(provider: &str) -> &'static [EncryptionMode] {
+    match provider {
+        "r2" => &[EncryptionMode::None, EncryptionMode::SseC],
+        _ => &[
+            EncryptionMode::None,
+            EncryptionMode::SseS3,
+            EncryptionMode::SseKms,
+            EncryptionMode::SseC,
+        ],
+    }
+}
+
+/// A 256-bit SSE-C key, kept base64-encoded together with its MD5 digest.
+/// `Debug` only shows the digest.
+#[derive(Clone, PartialEq, Eq)]
+pub struct CustomerKey {
+    key: String,
+    key_md5: String,
+}
+
+impl CustomerKey {
+    /// Parse a base64-encoded 32-byte key.
+    pub fn from_base64(encoded: &str) -> Result<Self, String> {
+        let raw = BASE64
+            .decode(encoded.trim())
+            .map_err(|_| "SSE-C key must be base64 encoded".to_string())?;
+        if raw.len() != 32 {
+            return Err(format!(
+                "SSE-C key must be 256 bits (32 bytes), got {} bytes",
+                raw.len()
+            ));
+        }
+        Ok(Self::from_bytes(&raw))
+    }
+
+    /// A fresh random key.
+    pub fn generate() -> Result<Self, String> {
+        let mut raw = [0u8; 32];
+        getrandom::getrandom(&mut raw).map_err(|e| format!("Failed to generate key: {}", e))?;
+        Ok(Self::from_bytes(&raw))
+    }
+
+    fn from_bytes(raw: &[u8]) -> Self {
+        CustomerKey {
+            key: BASE64.encode(raw),
+            key_md5: BASE64.encode(Md5::digest(raw)),
+        }
+    }
+
+    pub fn key_base64(&self) -> &str {
+        &self.key
+    }
+
+    pub fn key_md5(&self) -> &str {
+        &self.key_md5
+    }
+
+    /// SSE-C headers for a raw HTTP request.
+    pub fn headers(&self) -> Vec<(String, String)> {
+        vec![
+            (
+                HEADER_SSE_C_ALGORITHM.to_string(),
+                SSE_C_ALGORITHM.to_string(),
+            ),
+            (HEADER_SSE_C_KEY.to_string(), self.key.clone()),
+            (HEADER_SSE_C_KEY_MD5.to_string(), self.key_md5.clone()),
+        ]
+    }
+}
+
+impl fmt::Debug for CustomerKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomerKey")
+            .field("key_md5", &self.key_md5)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Resolved encryption for requests against one bucket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjectEncryption {
+    S3,
+    Kms {
+        key_id: Option<String>,
+        bucket_key_enabled: bool,
+    },
+    Customer(CustomerKey),
+}
+
+impl ObjectEncryption {
+    pub fn customer_key(&self) -> Option<&CustomerKey> {
+        match self {
+            ObjectEncryption::Customer(key) => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Headers a raw HTTP client sends when creating an object (PUT or
+    /// CreateMultipartUpload).
+    pub fn write_headers(&self) -> Vec<(String, String)> {
+        match self {
+            ObjectEncryption::S3 => vec![(HEADER_SSE.to_string(), "AES256".to_string())],
+            ObjectEncryption::Kms {
+                key_id,
+                bucket_key_enabled,
+            } => {
+                let mut headers = vec![(HEADER_SSE.to_string(), "aws:kms".to_string())];
+                if let Some(key_id) = key_id {
+                    headers.push((HEADER_SSE_KMS_KEY_ID.to_string(), key_id.clone()));
+                }
+                if *bucket_key_enabled {
+                    headers.push((HEADER_SSE_BUCKET_KEY.to_string(), "true".to_string()));
+                }
+                headers
+            }
+            ObjectEncryption::Customer(key) => key.headers(),
+        }
+    }
+}
+
+/// Headers a raw HTTP client sends when reading an object or uploading one of
+/// its parts. Only SSE-C needs any.
+pub fn read_headers(encryption: Option<&ObjectEncryption>) -> Vec<(String, String)> {
+    encryption
+        .and_then(ObjectEncryption::customer_key)
+        .map(CustomerKey::headers)
+        .unwrap_or_default()
+}
+
+/// Request builders that carry SSE-C key headers.
+pub trait SseCustomerKey: Sized {
+    fn sse_customer_key_from(self, encryption: Option<&ObjectEncryption>) -> Self;
+}
+
+/// Request builders that create objects and accept a default encryption.
+pub trait SseWrite: SseCustomerKey {
+    fn sse_from(self, encryption: Option<&ObjectEncryption>) -> Self;
+}
+
+/// Copy requests reading an SSE-C encrypted source.
+pub trait SseCopySource: Sized {
+    fn copy_source_sse_from(self, encryption: Option<&ObjectEncryption>) -> Self;
+}
+
+macro_rules! impl_sse_customer_key {
+    ($($builder:ty),* $(,)?) => {$(
+        impl SseCustomerKey for $builder {
+            fn sse_customer_key_from(self, encryption: Option<&ObjectEncryption>) -> Self {
+                match encryption.and_then(ObjectEncryption::customer_key) {
+                    Some(key) => self
+                        .sse_customer_algorithm(SSE_C_ALGORITHM)
+                        .sse_customer_key(key.key_base64())
+                        .sse_customer_key_md5(key.key_md5()),
+                    None => self,
+                }
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_sse_write {
+    ($($builder:ty),* $(,)?) => {$(
+        impl SseWrite for $builder {
+            fn sse_from(self, encryption: Option<&ObjectEncryption>) -> Self {
+                match encryption {
+                    Some(ObjectEncryption::S3) => {
+                        self.server_side_encryption(ServerSideEncryption::Aes256)
+                    }
+                    Some(ObjectEncryption::Kms {
+                        key_id,
+                        bucket_key_enabled,
+                    }) => {
+                        let builder = self
+                            .server_side_encryption(ServerSideEncryption::AwsKms)
+                            .set_ssekms_key_id(key_id.clone());
+                        if *bucket_key_enabled {
+                            builder.bucket_key_enabled(true)
+                        } else {
+                            builder
+                        }
+                    }
+                    Some(ObjectEncryption::Customer(_)) => self.sse_customer_key_from(encryption),
+                    None => self,
+                }
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_sse_copy_source {
+    ($($builder:ty),* $(,)?) => {$(
+        impl SseCopySource for $builder {
+            fn copy_source_sse_from(self, encryption: Option<&ObjectEncryption>) -> Self {
+                match encryption.and_then(ObjectEncryption::customer_key) {
+                    Some(key) => self
+                        .copy_source_sse_customer_algorithm(SSE_C_ALGORITHM)
+                        .copy_source_sse_customer_key(key.key_base64())
+                        .copy_source_sse_customer_key_md5(key.key_md5()),
+                    None => self,
+                }
+            }
+        }
+    )*};
+}
+
+impl_sse_customer_key!(
+    PutObjectFluentBuilder,
+    CreateMultipartUploadFluentBuilder,
+    UploadPartFluentBuilder,
+    CompleteMultipartUploadFluentBuilder,
+    CopyObjectFluentBuilder,
+    UploadPartCopyFluentBuilder,
+    GetObjectFluentBuilder,
+    HeadObjectFluentBuilder,
+);
+impl_sse_write!(
+    PutObjectFluentBuilder,
+    CreateMultipartUploadFluentBuilder,
+    CopyObjectFluentBuilder,
+);
+impl_sse_copy_source!(CopyObjectFluentBuilder, UploadPartCopyFluentBuilder);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn customer_key_requires_32_bytes() {
+        assert!(CustomerKey::from_base64(&BASE64.encode([7u8; 32])).is_ok());
+        assert!(CustomerKey::from_base64(&BASE64.encode([7u8; 16])).is_err());
+        assert!(CustomerKey::from_base64("not base64!").is_err());
+    }
+
+    #[test]
+    fn customer_key_md5_matches_raw_key() {
+        let key = CustomerKey::from_base64(&BASE64.encode([0u8; 32])).unwrap();
+        // MD5 of 32 zero bytes
+        assert_eq!(key.key_md5(), "cLyPS3KoaSFGi/joRB3OUQ==");
+    }
+
+    #[test]
+    fn debug_output_hides_the_key() {
+        let key = CustomerKey::generate().unwrap();
+        let debug = format!("{:?}", ObjectEncryption::Customer(key.clone()));
+        assert!(!debug.contains(key.key_base64()));
+        assert!(debug.contains(key.key_md5()));
+    }
+
+    #[test]
+    fn only_sse_c_adds_read_headers() {
+        assert!(read_headers(Some(&ObjectEncryption::S3)).is_empty());
+        let key = CustomerKey::generate().unwrap();
+        let headers = read_headers(Some(&ObjectEncryption::Customer(key)));
+        assert_eq!(headers.len(), 3);
+        assert_eq!(
+            headers[0],
+            (HEADER_SSE_C_ALGORITHM.to_string(), "AES256".to_string())
+        );
+    }
+
+    #[test]
+    fn kms_write_headers_include_key_and_bucket_key() {
+        let headers = ObjectEncryption::Kms {
+            key_id: Some("alias/media".to_string()),
+            bucket_key_enabled: true,
+        }
+        .write_headers();
+        assert!(headers.contains(&(HEADER_SSE.to_string(), "aws:kms".to_string())));
+        assert!(headers.contains(&(HEADER_SSE_KMS_KEY_ID.to_string(), "alias/media".to_string())));
+        assert!(headers.contains(&(HEADER_SSE_BUCKET_KEY.to_string(), "true".to_string())));
+    }
+
+    #[test]
+    fn r2_only_offers_sse_c() {
+        assert!(!supported_modes("r2").contains(&EncryptionMode::SseKms));
+        assert!(supported_modes("aws").contains(&EncryptionMode::SseKms));
+    }
+}
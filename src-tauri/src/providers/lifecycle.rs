@@ -0,0 +1,447 @@
+//! Bucket lifecycle configuration with a structured editor model.
+//!
+//! `BucketLifecycleRule` is what the rule editor edits; it converts to and
+//! from the SDK's `LifecycleRule` so the UI never deals with filter unions or
+//! S3 date formats.
+
+use super::capability::{ensure_supported, feature_error, Feature};
+use super::config::ProviderConfig;
+use super::s3_client::S3Result;
+use super::tagging::{from_sdk_tags, ObjectTag};
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::primitives::DateTime;
+use aws_sdk_s3::types::{
+    AbortIncompleteMultipartUpload, BucketLifecycleConfiguration, ExpirationStatus,
+    LifecycleExpiration, LifecycleRule, LifecycleRuleAndOperator, LifecycleRuleFilter, Tag,
+    Transition, TransitionStorageClass,
+};
+use serde::{Deserialize, Serialize};
+
+/// S3 caps a lifecycle configuration at 1000 rules.
+const MAX_RULES: usize = 1000;
+const MAX_RULE_ID_LEN: usize = 255;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BucketLifecycleRule {
+    pub id: Option<String>,
+    pub enabled: bool,
+    /// Key prefix the rule applies to ("" = whole bucket).
+    #[serde(default)]
+    pub prefix: String,
+    /// Objects must carry all of these tags for the rule to apply.
+    #[serde(default)]
+    pub tags: Vec<ObjectTag>,
+    pub expiration: Option<LifecycleExpirationRule>,
+    pub abort_incomplete_multipart_days: Option<i32>,
+    #[serde(default)]
+    pub transitions: Vec<LifecycleTransitionRule>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LifecycleExpirationRule {
+    pub days: Option<i32>,
+    /// RFC 3339 timestamp or `YYYY-MM-DD` (midnight UTC).
+    pub date: Option<String>,
+    pub expired_object_delete_marker: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LifecycleTransitionRule {
+    pub days: Option<i32>,
+    pub date: Option<String>,
+    pub storage_class: String,
+}
+
+impl BucketLifecycleRule {
+    fn label(&self) -> String {
+        match &self.id {
+            Some(id) if !id.is_empty() => format!("Rule '{}'", id),
+            _ => format!("Rule for prefix '{}'", self.prefix),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let label = self.label();
+
+        if let Some(id) = &self.id {
+            if id.chars().count() > MAX_RULE_ID_LEN {
+                return Err(format!(
+                    "{}: id exceeds {} characters",
+                    label, MAX_RULE_ID_LEN
+                ));
+            }
+        }
+
+        if self.expiration.is_none()
+            && self.abort_incomplete_multipart_days.is_none()
+            && self.transitions.is_empty()
+        {
+            return Err(format!("{}: at least one action is required", label));
+        }
+
+        if let Some(expiration) = &self.expiration {
+            let set = [
+                expiration.days.is_some(),
+                expiration.date.is_some(),
+                expiration.expired_object_delete_marker.is_some(),
+            ]
+            .iter()
+            .filter(|set| **set)
+            .count();
+            if set != 1 {
+                return Err(format!(
+                    "{}: expiration needs exactly one of days, date or expired_object_delete_marker",
+                    label
+                ));
+            }
+            if expiration.expired_object_delete_marker.is_some() && !self.tags.is_empty() {
+                return Err(format!(
+                    "{}: expired_object_delete_marker cannot be combined with a tag filter",
+                    label
+                ));
+            }
+            validate_days(&label, "expiration", expiration.days)?;
+            if let Some(date) = &expiration.date {
+                parse_rule_date(date).map_err(|e| format!("{}: {}", label, e))?;
+            }
+        }
+
+        validate_days(
+            &label,
+            "abort incomplete multipart",
+            self.abort_incomplete_multipart_days,
+        )?;
+
+        for transition in &self.transitions {
+            if transition.storage_class.trim().is_empty() {
+                return Err(format!("{}: transition storage class is required", label));
+            }
+            if transition.days.is_some() == transition.date.is_some() {
+                return Err(format!(
+                    "{}: transition needs exactly one of days or date",
+                    label
+                ));
+            }
+            if let Some(days) = transition.days {
+                if days < 0 {
+                    return Err(format!("{}: transition days cannot be negative", label));
+                }
+            }
+            if let Some(date) = &transition.date {
+                parse_rule_date(date).map_err(|e| format!("{}: {}", label, e))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn to_sdk(&self) -> S3Result<LifecycleRule> {
+        let status = if self.enabled {
+            ExpirationStatus::Enabled
+        } else {
+            ExpirationStatus::Disabled
+        };
+
+        let mut builder = LifecycleRule::builder()
+            .set_id(self.id.clone().filter(|id| !id.is_empty()))
+            .status(status)
+            .filter(self.sdk_filter()?);
+
+        if let Some(expiration) = &self.expiration {
+            let mut exp = LifecycleExpiration::builder()
+                .set_days(expiration.days)
+                .set_expired_object_delete_marker(expiration.expired_object_delete_marker);
+            if let Some(date) = &expiration.date {
+                exp = exp.date(parse_rule_date(date)?);
+            }
+            builder = builder.expiration(exp.build());
+        }
+
+        if let Some(days) = self.abort_incomplete_multipart_days {
+            builder = builder.abort_incomplete_multipart_upload(
+                AbortIncompleteMultipartUpload::builder()
+                    .days_after_initiation(days)
+                    .build(),
+            );
+        }
+
+        for transition in &self.transitions {
+            let mut t = Transition::builder()
+                .set_days(transition.days)
+                .storage_class(TransitionStorageClass::from(
+                    transition.storage_class.trim(),
+                ));
+            if let Some(date) = &transition.date {
+                t = t.date(parse_rule_date(date)?);
+            }
+            builder = builder.transitions(t.build());
+        }
+
+        Ok(builder.build()?)
+    }
+
+    fn sdk_filter(&self) -> S3Result<LifecycleRuleFilter> {
+        let tags: Vec<Tag> = self
+            .tags
+            .iter()
+            .map(|tag| Tag::builder().key(&tag.key).value(&tag.value).build())
+            .collect::<Result<_, _>>()?;
+
+        let filter = match (self.prefix.is_empty(), tags.len()) {
+            (_, 0) => LifecycleRuleFilter::builder().prefix(&self.prefix).build(),
+            (true, 1) => LifecycleRuleFilter::builder()
+                .tag(tags.into_iter().next().expect("one tag"))
+                .build(),
+            _ => LifecycleRuleFilter::builder()
+                .and(
+                    LifecycleRuleAndOperator::builder()
+                        .prefix(&self.prefix)
+                        .set_tags(Some(tags))
+                        .build(),
+                )
+                .build(),
+        };
+        Ok(filter)
+    }
+
+    fn from_sdk(rule: &LifecycleRule) -> Self {
+        let filter = rule.filter();
+        let and = filter.and_then(|f| f.and());
+
+        let prefix = filter
+            .and_then(|f| f.prefix())
+            .or_else(|| and.and_then(|a| a.prefix()))
+            .unwrap_or_default()
+            .to_string();
+
+        let tags = match (filter.and_then(|f| f.tag()), and) {
+            (Some(tag), _) => from_sdk_tags(std::slice::from_ref(tag)),
+            (None, Some(and)) => from_sdk_tags(and.tags()),
+            (None, None) => Vec::new(),
+        };
+
+        let expiration = rule.expiration().map(|exp| LifecycleExpirationRule {
+            days: exp.days(),
+            date: exp.date().map(|d| d.to_string()),
+            expired_object_delete_marker: exp.expired_object_delete_marker(),
+        });
+
+        let transitions = rule
+            .transitions()
+            .iter()
+            .map(|t| LifecycleTransitionRule {
+                days: t.days(),
+                date: t.date().map(|d| d.to_string()),
+                storage_class: t
+                    .storage_class()
+                    .map(|c| c.as_str().to_string())
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        BucketLifecycleRule {
+            id: rule.id().map(str::to_string),
+            enabled: *rule.status() == ExpirationStatus::Enabled,
+            prefix,
+            tags,
+            expiration,
+            abort_incomplete_multipart_days: rule
+                .abort_incomplete_multipart_upload()
+                .and_then(|a| a.days_after_initiation()),
+            transitions,
+        }
+    }
+}
+
+fn validate_days(label: &str, action: &str, days: Option<i32>) -> Result<(), String> {
+    match days {
+        Some(days) if days <= 0 => Err(format!(
+            "{}: {} days must be a positive number",
+            label, action
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Parse an RFC 3339 timestamp or a bare `YYYY-MM-DD` date.
+/// S3 only accepts lifecycle dates at midnight UTC.
+fn parse_rule_date(value: &str) -> Result<DateTime, String> {
+    let value = value.trim();
+    let parsed = if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        date.and_hms_opt(0, 0, 0)
+            .map(|dt| dt.and_utc())
+            .ok_or_else(|| format!("Invalid date '{}'", value))?
+    } else {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", value))?
+            .with_timezone(&chrono::Utc)
+    };
+
+    if parsed.timestamp() % 86_400 != 0 {
+        return Err(format!("Date '{}' must be midnight UTC", value));
+    }
+    Ok(DateTime::from_secs(parsed.timestamp()))
+}
+
+/// Validate a full rule set before sending it.
+pub fn validate_rules(rules: &[BucketLifecycleRule]) -> Result<(), String> {
+    if rules.len() > MAX_RULES {
+        return Err(format!("At most {} lifecycle rules are allowed", MAX_RULES));
+    }
+    for (i, rule) in rules.iter().enumerate() {
+        rule.validate()?;
+        if let Some(id) = rule.id.as_deref().filter(|id| !id.is_empty()) {
+            if rules[..i]
+                .iter()
+                .any(|other| other.id.as_deref() == Some(id))
+            {
+                return Err(format!("Duplicate rule id '{}'", id));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Get lifecycle rules. A bucket without a configuration returns no rules.
+pub async fn get_bucket_lifecycle(config: &ProviderConfig) -> S3Result<Vec<BucketLifecycleRule>> {
+    ensure_supported(config, Feature::BucketLifecycle)?;
+    let client = config.create_client().await?;
+
+    match client
+        .get_bucket_lifecycle_configuration()
+        .bucket(config.bucket())
+        .send()
+        .await
+    {
+        Ok(response) => Ok(response
+            .rules()
+            .iter()
+            .map(BucketLifecycleRule::from_sdk)
+            .collect()),
+        Err(err) if err.code() == Some("NoSuchLifecycleConfiguration") => Ok(Vec::new()),
+        Err(err) => Err(feature_error(config, Feature::BucketLifecycle, err)),
+    }
+}
+
+/// Replace the bucket's lifecycle configuration. An empty rule set deletes it.
+pub async fn put_bucket_lifecycle(
+    config: &ProviderConfig,
+    rules: &[BucketLifecycleRule],
+) -> S3Result<()> {
+    ensure_supported(config, Feature::BucketLifecycle)?;
+    validate_rules(rules)?;
+
+    if rules.is_empty() {
+        return delete_bucket_lifecycle(config).await;
+    }
+
+    let sdk_rules = rules
+        .iter()
+        .map(BucketLifecycleRule::to_sdk)
+        .collect::<S3Result<Vec<_>>>()?;
+    let configuration = BucketLifecycleConfiguration::builder()
+        .set_rules(Some(sdk_rules))
+        .build()?;
+
+    let client = config.create_client().await?;
+    client
+        .put_bucket_lifecycle_configuration()
+        .bucket(config.bucket())
+        .lifecycle_configuration(configuration)
+        .send()
+        .await
+        .map_err(|e| feature_error(config, Feature::BucketLifecycle, e))?;
+
+    Ok(())
+}
+
+pub async fn delete_bucket_lifecycle(config: &ProviderConfig) -> S3Result<()> {
+    ensure_supported(config, Feature::BucketLifecycle)?;
+    let client = config.create_client().await?;
+    client
+        .delete_bucket_lifecycle()
+        .bucket(config.bucket())
+        .send()
+        .await
+        .map_err(|e| feature_error(config, Feature::BucketLifecycle, e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expiring_rule(days: i32) -> BucketLifecycleRule {
+        BucketLifecycleRule {
+            id: Some("logs".to_string()),
+            enabled: true,
+            prefix: "logs/".to_string(),
+            expiration: Some(LifecycleExpirationRule {
+                days: Some(days),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rule_round_trips_through_sdk_model() {
+        let rule = BucketLifecycleRule {
+            tags: vec![ObjectTag {
+                key: "tier".to_string(),
+                value: "cold".to_string(),
+            }],
+            abort_incomplete_multipart_days: Some(7),
+            transitions: vec![LifecycleTransitionRule {
+                days: Some(30),
+                date: None,
+                storage_class: "STANDARD_IA".to_string(),
+            }],
+            ..expiring_rule(365)
+        };
+
+        let sdk = rule.to_sdk().expect("valid rule");
+        assert_eq!(BucketLifecycleRule::from_sdk(&sdk), rule);
+    }
+
+    #[test]
+    fn single_tag_without_prefix_round_trips() {
+        let rule = BucketLifecycleRule {
+            id: None,
+            enabled: false,
+            prefix: String::new(),
+            tags: vec![ObjectTag {
+                key: "temp".to_string(),
+                value: "true".to_string(),
+            }],
+            expiration: Some(LifecycleExpirationRule {
+                date: None,
+                days: Some(1),
+                expired_object_delete_marker: None,
+            }),
+            ..Default::default()
+        };
+
+        let sdk = rule.to_sdk().expect("valid rule");
+        assert_eq!(BucketLifecycleRule::from_sdk(&sdk), rule);
+    }
+
+    #[test]
+    fn validation_requires_an_action_and_unique_ids() {
+        let mut no_action = expiring_rule(1);
+        no_action.expiration = None;
+        assert!(validate_rules(&[no_action]).is_err());
+
+        assert!(validate_rules(&[expiring_rule(1), expiring_rule(2)]).is_err());
+        assert!(validate_rules(&[expiring_rule(0)]).is_err());
+        assert!(validate_rules(&[expiring_rule(30)]).is_ok());
+    }
+
+    #[test]
+    fn rule_dates_must_be_midnight_utc() {
+        assert!(parse_rule_date("2030-01-01").is_ok());
+        assert!(parse_rule_date("2030-01-01T00:00:00Z").is_ok());
+        assert!(parse_rule_date("2030-01-01T12:00:00Z").is_err());
+        assert!(parse_rule_date("next week").is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use super::types::{create_minio_client, MinioConfig, MinioResult};
-use aws_sdk_s3::types::{Delete, ObjectIdentifier};
+use crate::providers::batch_delete::{self, DeleteObjectsOutcome, DeleteProgressFn};
+use crate::providers::encryption::ObjectEncryption;
+use crate::providers::multipart_copy::{self, CopyEncryption, CopyProgressFn};
 
 pub async fn delete_object(config: &MinioConfig, key: &str) -> MinioResult<()> {
     let client = create_minio_client(config).await?;
@@ -12,71 +14,71 @@ pub async fn delete_object(config: &MinioConfig, key: &str) -> MinioResult<()> {
     Ok(())
 }
 
-pub async fn delete_objects(config: &MinioConfig, keys: Vec<String>) -> MinioResult<()> {
+pub async fn delete_objects(
+    config: &MinioConfig,
+    keys: Vec<String>,
+    on_progress: Option<DeleteProgressFn>,
+) -> MinioResult<DeleteObjectsOutcome> {
     if keys.is_empty() {
-        return Ok(());
+        return Ok(DeleteObjectsOutcome::default());
     }
 
     let client = create_minio_client(config).await?;
-
-    let objects: Vec<ObjectIdentifier> = keys
-        .iter()
-        .map(|key| ObjectIdentifier::builder().key(key).build().unwrap())
-        .collect();
-
-    let delete = Delete::builder().set_objects(Some(objects)).build()?;
-
-    client
-        .delete_objects()
-        .bucket(&config.bucket)
-        .delete(delete)
-        .send()
-        .await?;
-
-    Ok(())
+    batch_delete::delete_objects(&client, &config.bucket, keys, on_progress).await
 }
 
 pub async fn copy_object(
     config: &MinioConfig,
     source_key: &str,
     dest_key: &str,
-) -> MinioResult<()> {
+    on_progress: Option<CopyProgressFn>,
+) -> MinioResult<u64> {
     let client = create_minio_client(config).await?;
-    let copy_source = format!("{}/{}", config.bucket, source_key);
-
-    client
-        .copy_object()
-        .bucket(&config.bucket)
-        .copy_source(copy_source)
-        .key(dest_key)
-        .send()
-        .await?;
-
-    Ok(())
+    multipart_copy::copy_object(
+        &client,
+        &config.bucket,
+        source_key,
+        &config.bucket,
+        dest_key,
+        CopyEncryption::same(config.encryption.as_ref()),
+        None,
+        on_progress,
+    )
+    .await
 }
 
 pub async fn copy_object_between_buckets(
     config: &MinioConfig,
     source_bucket: &str,
     source_key: &str,
+    source_encryption: Option<&ObjectEncryption>,
     dest_key: &str,
-) -> MinioResult<()> {
+    on_progress: Option<CopyProgressFn>,
+) -> MinioResult<u64> {
     let client = create_minio_client(config).await?;
-    let copy_source = format!("{}/{}", source_bucket, source_key);
-
-    client
-        .copy_object()
-        .bucket(&config.bucket)
-        .copy_source(copy_source)
-        .key(dest_key)
-        .send()
-        .await?;
-
-    Ok(())
+    multipart_copy::copy_object(
+        &client,
+        source_bucket,
+        source_key,
+        &config.bucket,
+        dest_key,
+        CopyEncryption {
+            source: source_encryption,
+            dest: config.encryption.as_ref(),
+        },
+        None,
+        on_progress,
+    )
+    .await
 }
 
-pub async fn rename_object(config: &MinioConfig, old_key: &str, new_key: &str) -> MinioResult<()> {
-    copy_object(config, old_key, new_key).await?;
+pub async fn rename_object(
+    config: &MinioConfig,
+    old_key: &str,
+    new_key: &str,
+    on_progress: Option<CopyProgressFn>,
+) -> MinioResult<()> {
+    copy_object(config, old_key, new_key, on_progress).await?;
     delete_object(config, old_key).await?;
     Ok(())
 }
@@ -1,4 +1,6 @@
+use crate::providers::encryption::ObjectEncryption;
 use crate::providers::s3_client::{create_s3_client, S3ClientConfig, S3Result};
+use crate::secrets;
 use aws_sdk_s3::Client;
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +14,10 @@ pub struct MinioConfig {
     pub endpoint_scheme: String,
     pub endpoint_host: String,
     pub force_path_style: bool,
+    /// Bucket encryption resolved from the saved settings; never sent by the
+    /// frontend.
+    #[serde(skip)]
+    pub encryption: Option<ObjectEncryption>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,12 +55,14 @@ fn build_endpoint_url(config: &MinioConfig) -> String {
 
 pub async fn create_minio_client(config: &MinioConfig) -> MinioResult<Client> {
     let endpoint_url = build_endpoint_url(config);
+    let secret_access_key = secrets::resolve(&config.secret_access_key).await?;
     let client = create_s3_client(&S3ClientConfig {
         access_key_id: &config.access_key_id,
-        secret_access_key: &config.secret_access_key,
+        secret_access_key: &secret_access_key,
         region: "us-east-1",
         endpoint_url: Some(endpoint_url.as_str()),
         force_path_style: config.force_path_style,
+        credentials_provider: None,
     })?;
 
     Ok(client)
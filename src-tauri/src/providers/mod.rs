@@ -1,4 +1,19 @@
 pub mod aws;
+pub mod batch_delete;
+pub mod buckets;
+pub mod capability;
+pub mod config;
+pub mod cors;
+pub mod credential_probe;
+pub mod encryption;
+pub mod lifecycle;
 pub mod minio;
+pub mod multipart_copy;
+pub mod object_lock;
+pub mod object_metadata;
+pub mod presign;
+pub mod pricing;
 pub mod rustfs;
 pub mod s3_client;
+pub mod storage_class;
+pub mod tagging;
@@ -0,0 +1,292 @@
+//! Server-side object copy that handles sources over the 5 GB CopyObject limit.
+//!
+//! Small objects use a single CopyObject call. Larger ones are copied with
+//! CreateMultipartUpload + parallel UploadPartCopy ranges, so renames and
+//! moves of big files never fall back to download/re-upload.
+
+use super::capability::describe_sdk_error;
+use super::encryption::{ObjectEncryption, SseCopySource, SseCustomerKey, SseWrite};
+use super::s3_client::S3Result;
+use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart, StorageClass};
+use aws_sdk_s3::Client;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Largest source CopyObject accepts.
+pub const COPY_OBJECT_MAX_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+/// Copy parts are server-side, so large parts keep request count low.
+const MIN_COPY_PART_SIZE: u64 = 256 * 1024 * 1024;
+const MAX_PARTS: u64 = 10_000;
+const MAX_CONCURRENT_COPY_PARTS: usize = 8;
+
+/// Progress callback: (copied_bytes, total_bytes).
+pub type CopyProgressFn = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+/// Encryption on both sides of a copy: the source's SSE-C key is needed to
+/// read it, the destination's encryption is applied to the new object.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CopyEncryption<'a> {
+    pub source: Option<&'a ObjectEncryption>,
+    pub dest: Option<&'a ObjectEncryption>,
+}
+
+impl<'a> CopyEncryption<'a> {
+    /// Copy within one bucket (or between buckets sharing settings).
+    pub fn same(encryption: Option<&'a ObjectEncryption>) -> Self {
+        CopyEncryption {
+            source: encryption,
+            dest: encryption,
+        }
+    }
+}
+
+/// `CopySource` header value: `bucket/key` with the key URL-encoded.
+pub(crate) fn copy_source(bucket: &str, key: &str) -> String {
+    format!(
+        "{}/{}",
+        bucket,
+        urlencoding::encode(key).replace("%2F", "/")
+    )
+}
+
+/// Part size for a multipart copy of `size` bytes, staying within 10,000 parts.
+fn copy_part_size(size: u64) -> u64 {
+    MIN_COPY_PART_SIZE.max(size.div_ceil(MAX_PARTS))
+}
+
+/// Split `size` bytes into (part_number, first_byte, last_byte) ranges.
+fn plan_copy_parts(size: u64, part_size: u64) -> Vec<(i32, u64, u64)> {
+    (0..size.div_ceil(part_size))
+        .map(|i| {
+            let start = i * part_size;
+            let end = (start + part_size).min(size) - 1;
+            (i as i32 + 1, start, end)
+        })
+        .collect()
+}
+
+/// Copy `source_bucket/source_key` to `dest_bucket/dest_key` on the same endpoint.
+/// Without a `storage_class` the copy keeps a non-standard source class rather
+/// than falling back to STANDARD. Returns the number of bytes copied.
+#[allow(clippy::too_many_arguments)]
+pub async fn copy_object(
+    client: &Client,
+    source_bucket: &str,
+    source_key: &str,
+    dest_bucket: &str,
+    dest_key: &str,
+    encryption: CopyEncryption<'_>,
+    storage_class: Option<&StorageClass>,
+    on_progress: Option<CopyProgressFn>,
+) -> S3Result<u64> {
+    let head = client
+        .head_object()
+        .bucket(source_bucket)
+        .key(source_key)
+        .sse_customer_key_from(encryption.source)
+        .send()
+        .await
+        .map_err(describe_sdk_error)?;
+    let size = head.content_length().unwrap_or(0).max(0) as u64;
+    let storage_class = storage_class.cloned().or_else(|| {
+        head.storage_class()
+            .filter(|class| **class != StorageClass::Standard)
+            .cloned()
+    });
+
+    if size <= COPY_OBJECT_MAX_SIZE {
+        client
+            .copy_object()
+            .bucket(dest_bucket)
+            .copy_source(copy_source(source_bucket, source_key))
+            .copy_source_sse_from(encryption.source)
+            .key(dest_key)
+            .sse_from(encryption.dest)
+            .set_storage_class(storage_class)
+            .send()
+            .await
+            .map_err(describe_sdk_error)?;
+        return Ok(size);
+    }
+
+    let upload = client
+        .create_multipart_upload()
+        .bucket(dest_bucket)
+        .key(dest_key)
+        .sse_from(encryption.dest)
+        .set_storage_class(storage_class)
+        .set_content_type(head.content_type().map(str::to_string))
+        .set_content_disposition(head.content_disposition().map(str::to_string))
+        .set_content_encoding(head.content_encoding().map(str::to_string))
+        .set_cache_control(head.cache_control().map(str::to_string))
+        .set_metadata(head.metadata().cloned())
+        .send()
+        .await
+        .map_err(describe_sdk_error)?;
+    let upload_id = upload
+        .upload_id()
+        .ok_or("No upload ID returned")?
+        .to_string();
+
+    let result = copy_parts(
+        client,
+        copy_source(source_bucket, source_key),
+        dest_bucket,
+        dest_key,
+        &upload_id,
+        size,
+        encryption,
+        on_progress,
+    )
+    .await;
+
+    match result {
+        Ok(parts) => {
+            let completed = CompletedMultipartUpload::builder()
+                .set_parts(Some(parts))
+                .build();
+            client
+                .complete_multipart_upload()
+                .bucket(dest_bucket)
+                .key(dest_key)
+                .upload_id(&upload_id)
+                .sse_customer_key_from(encryption.dest)
+                .multipart_upload(completed)
+                .send()
+                .await
+                .map_err(describe_sdk_error)?;
+            Ok(size)
+        }
+        Err(err) => {
+            let _ = client
+                .abort_multipart_upload()
+                .bucket(dest_bucket)
+                .key(dest_key)
+                .upload_id(&upload_id)
+                .send()
+                .await;
+            Err(err)
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn copy_parts(
+    client: &Client,
+    source: String,
+    dest_bucket: &str,
+    dest_key: &str,
+    upload_id: &str,
+    size: u64,
+    encryption: CopyEncryption<'_>,
+    on_progress: Option<CopyProgressFn>,
+) -> S3Result<Vec<CompletedPart>> {
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_COPY_PARTS));
+    let copied = Arc::new(AtomicU64::new(0));
+    let mut handles = Vec::new();
+
+    for (part_number, start, end) in plan_copy_parts(size, copy_part_size(size)) {
+        let permit = semaphore.clone().acquire_owned().await?;
+        let client = client.clone();
+        let source = source.clone();
+        let dest_bucket = dest_bucket.to_string();
+        let dest_key = dest_key.to_string();
+        let upload_id = upload_id.to_string();
+        let copied = copied.clone();
+        let on_progress = on_progress.clone();
+        let source_encryption = encryption.source.cloned();
+        let dest_encryption = encryption.dest.cloned();
+
+        handles.push(tokio::spawn(async move {
+            let _permit = permit;
+            let response = client
+                .upload_part_copy()
+                .bucket(dest_bucket)
+                .key(dest_key)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .copy_source(source)
+                .copy_source_range(format!("bytes={}-{}", start, end))
+                .copy_source_sse_from(source_encryption.as_ref())
+                .sse_customer_key_from(dest_encryption.as_ref())
+                .send()
+                .await
+                .map_err(describe_sdk_error)?;
+
+            let etag = response
+                .copy_part_result()
+                .and_then(|r| r.e_tag())
+                .ok_or_else(|| format!("No ETag returned for copy part {}", part_number))?
+                .to_string();
+
+            let done = copied.fetch_add(end - start + 1, Ordering::SeqCst) + end - start + 1;
+            if let Some(cb) = &on_progress {
+                cb(done, size);
+            }
+
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(
+                CompletedPart::builder()
+                    .part_number(part_number)
+                    .e_tag(etag)
+                    .build(),
+            )
+        }));
+    }
+
+    let mut parts = Vec::with_capacity(handles.len());
+    let mut first_error = None;
+    for handle in handles {
+        match handle.await {
+            Ok(Ok(part)) => parts.push(part),
+            Ok(Err(e)) => {
+                first_error.get_or_insert(e);
+            }
+            Err(e) => {
+                first_error.get_or_insert(format!("Copy part task failed: {}", e).into());
+            }
+        }
+    }
+
+    if let Some(err) = first_error {
+        return Err(err);
+    }
+
+    parts.sort_by_key(|p| p.part_number());
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parts_cover_the_whole_object_without_gaps() {
+        let size = 6 * 1024 * 1024 * 1024 + 123;
+        let part_size = copy_part_size(size);
+        let parts = plan_copy_parts(size, part_size);
+
+        assert_eq!(parts.first().map(|p| (p.0, p.1)), Some((1, 0)));
+        assert_eq!(parts.last().map(|p| p.2), Some(size - 1));
+        for pair in parts.windows(2) {
+            assert_eq!(pair[0].2 + 1, pair[1].1);
+            assert_eq!(pair[0].0 + 1, pair[1].0);
+        }
+    }
+
+    #[test]
+    fn part_size_grows_to_stay_under_part_limit() {
+        let five_tb = 5 * 1024 * 1024 * 1024 * 1024;
+        let part_size = copy_part_size(five_tb);
+        assert!(five_tb.div_ceil(part_size) <= MAX_PARTS);
+        assert_eq!(copy_part_size(COPY_OBJECT_MAX_SIZE + 1), MIN_COPY_PART_SIZE);
+    }
+
+    #[test]
+    fn copy_source_encodes_key_but_keeps_slashes() {
+        assert_eq!(
+            copy_source("media", "videos/my clip#1.mp4"),
+            "media/videos/my%20clip%231.mp4"
+        );
+    }
+}
@@ -0,0 +1,500 @@
+//! Object Lock: retention and legal hold (GetObjectRetention / PutObjectRetention /
+//! GetObjectLegalHold / PutObjectLegalHold)
+//!
+//! A locked object cannot be deleted, and the server only answers with a bare
+//! `AccessDenied`. Destructive operations therefore check lock state first and
+//! report locked keys as `SkippedObject`s with the reason.
+
+use super::capability::{ensure_supported, feature_error, Feature};
+use super::config::ProviderConfig;
+use super::s3_client::S3Result;
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::primitives::DateTime;
+use aws_sdk_s3::types::{
+    ObjectLockEnabled, ObjectLockLegalHold, ObjectLockLegalHoldStatus, ObjectLockRetention,
+    ObjectLockRetentionMode,
+};
+use aws_sdk_s3::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Concurrent HEAD requests when checking a selection for locks.
+const MAX_CONCURRENT_LOCK_CHECKS: usize = 16;
+
+/// Returned for objects (or buckets) that never had retention or a legal hold set.
+const NO_LOCK_CODES: &[&str] = &[
+    "NoSuchObjectLockConfiguration",
+    "ObjectLockConfigurationNotFoundError",
+];
+
+fn is_no_lock_code(code: Option<&str>) -> bool {
+    code.is_some_and(|code| NO_LOCK_CODES.contains(&code))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RetentionMode {
+    /// Users with `s3:BypassGovernanceRetention` can shorten or remove it.
+    Governance,
+    /// Nobody can shorten or remove it before it expires.
+    Compliance,
+}
+
+impl RetentionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetentionMode::Governance => "GOVERNANCE",
+            RetentionMode::Compliance => "COMPLIANCE",
+        }
+    }
+
+    fn from_sdk(mode: &str) -> Option<Self> {
+        match mode {
+            "GOVERNANCE" => Some(RetentionMode::Governance),
+            "COMPLIANCE" => Some(RetentionMode::Compliance),
+            _ => None,
+        }
+    }
+
+    fn to_sdk(self) -> ObjectLockRetentionMode {
+        match self {
+            RetentionMode::Governance => ObjectLockRetentionMode::Governance,
+            RetentionMode::Compliance => ObjectLockRetentionMode::Compliance,
+        }
+    }
+}
+
+/// Retention to apply to an object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectRetention {
+    pub mode: RetentionMode,
+    /// Unix seconds
+    pub retain_until: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ObjectLockState {
+    pub retention: Option<ObjectRetention>,
+    pub legal_hold: bool,
+}
+
+/// Why a key was left out of a delete or move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkipReason {
+    LegalHold,
+    Retention,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkippedObject {
+    pub key: String,
+    pub reason: SkipReason,
+    pub message: String,
+}
+
+impl ObjectLockState {
+    /// Retention that still protects the object at `now` (unix seconds).
+    pub fn active_retention(&self, now: i64) -> Option<ObjectRetention> {
+        self.retention
+            .filter(|retention| retention.retain_until > now)
+    }
+
+    /// Why the object cannot be deleted at `now`, if it is locked. Legal hold
+    /// wins since it has no expiry.
+    pub fn skip_reason(&self, now: i64) -> Option<SkipReason> {
+        if self.legal_hold {
+            Some(SkipReason::LegalHold)
+        } else if self.active_retention(now).is_some() {
+            Some(SkipReason::Retention)
+        } else {
+            None
+        }
+    }
+
+    pub fn skipped(&self, key: &str, now: i64) -> Option<SkippedObject> {
+        let reason = self.skip_reason(now)?;
+        let message = match (reason, self.active_retention(now)) {
+            (SkipReason::Retention, Some(retention)) => format!(
+                "Locked in {} mode until {}",
+                retention.mode.as_str(),
+                format_timestamp(retention.retain_until)
+            ),
+            _ => "Under legal hold".to_string(),
+        };
+        Some(SkippedObject {
+            key: key.to_string(),
+            reason,
+            message,
+        })
+    }
+}
+
+fn format_timestamp(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_else(|| secs.to_string())
+}
+
+/// Check a retention change against the object's current lock. Compliance
+/// retention can only be extended; shortening or removing governance
+/// retention needs `bypass_governance`.
+pub fn validate_retention(
+    current: &ObjectLockState,
+    next: Option<&ObjectRetention>,
+    bypass_governance: bool,
+    now: i64,
+) -> Result<(), String> {
+    if let Some(next) = next {
+        if next.retain_until <= now {
+            return Err("Retain-until date must be in the future".to_string());
+        }
+    }
+
+    let Some(active) = current.active_retention(now) else {
+        return Ok(());
+    };
+    let weakened = match next {
+        None => true,
+        Some(next) => {
+            next.retain_until < active.retain_until
+                || (active.mode == RetentionMode::Compliance
+                    && next.mode == RetentionMode::Governance)
+        }
+    };
+    if !weakened {
+        return Ok(());
+    }
+    match active.mode {
+        RetentionMode::Compliance => Err(format!(
+            "Compliance retention cannot be shortened or removed before {}",
+            format_timestamp(active.retain_until)
+        )),
+        RetentionMode::Governance if !bypass_governance => Err(
+            "Shortening or removing governance retention requires bypassing governance mode"
+                .to_string(),
+        ),
+        RetentionMode::Governance => Ok(()),
+    }
+}
+
+/// Whether Object Lock is enabled on the bucket. Objects can only be locked
+/// in buckets created with it, so this lets callers skip per-key checks.
+pub async fn bucket_lock_enabled_with(client: &Client, config: &ProviderConfig) -> S3Result<bool> {
+    ensure_supported(config, Feature::ObjectLock)?;
+    match client
+        .get_object_lock_configuration()
+        .bucket(config.bucket())
+        .send()
+        .await
+    {
+        Ok(response) => Ok(response
+            .object_lock_configuration()
+            .and_then(|c| c.object_lock_enabled())
+            == Some(&ObjectLockEnabled::Enabled)),
+        Err(err) if is_no_lock_code(err.code()) => Ok(false),
+        Err(err) => Err(feature_error(config, Feature::ObjectLock, err)),
+    }
+}
+
+/// Read retention and legal hold through their dedicated APIs.
+pub async fn get_object_lock_with(
+    client: &Client,
+    config: &ProviderConfig,
+    key: &str,
+) -> S3Result<ObjectLockState> {
+    ensure_supported(config, Feature::ObjectLock)?;
+
+    let retention = match client
+        .get_object_retention()
+        .bucket(config.bucket())
+        .key(key)
+        .send()
+        .await
+    {
+        Ok(response) => response.retention().and_then(|retention| {
+            Some(ObjectRetention {
+                mode: RetentionMode::from_sdk(retention.mode()?.as_str())?,
+                retain_until: retention.retain_until_date()?.secs(),
+            })
+        }),
+        Err(err) if is_no_lock_code(err.code()) => None,
+        Err(err) => return Err(feature_error(config, Feature::ObjectLock, err)),
+    };
+
+    let legal_hold = match client
+        .get_object_legal_hold()
+        .bucket(config.bucket())
+        .key(key)
+        .send()
+        .await
+    {
+        Ok(response) => {
+            response.legal_hold().and_then(|hold| hold.status())
+                == Some(&ObjectLockLegalHoldStatus::On)
+        }
+        Err(err) if is_no_lock_code(err.code()) => false,
+        Err(err) => return Err(feature_error(config, Feature::ObjectLock, err)),
+    };
+
+    Ok(ObjectLockState {
+        retention,
+        legal_hold,
+    })
+}
+
+/// Set or (with `None`) remove retention. Removing or shortening governance
+/// retention is sent with `x-amz-bypass-governance-retention`.
+pub async fn put_object_retention_with(
+    client: &Client,
+    config: &ProviderConfig,
+    key: &str,
+    retention: Option<&ObjectRetention>,
+    bypass_governance: bool,
+) -> S3Result<()> {
+    ensure_supported(config, Feature::ObjectLock)?;
+    let body = match retention {
+        Some(retention) => ObjectLockRetention::builder()
+            .mode(retention.mode.to_sdk())
+            .retain_until_date(DateTime::from_secs(retention.retain_until))
+            .build(),
+        None => ObjectLockRetention::builder().build(),
+    };
+
+    client
+        .put_object_retention()
+        .bucket(config.bucket())
+        .key(key)
+        .retention(body)
+        .bypass_governance_retention(bypass_governance)
+        .send()
+        .await
+        .map_err(|e| feature_error(config, Feature::ObjectLock, e))?;
+    Ok(())
+}
+
+pub async fn put_object_legal_hold_with(
+    client: &Client,
+    config: &ProviderConfig,
+    key: &str,
+    enabled: bool,
+) -> S3Result<()> {
+    ensure_supported(config, Feature::ObjectLock)?;
+    let status = if enabled {
+        ObjectLockLegalHoldStatus::On
+    } else {
+        ObjectLockLegalHoldStatus::Off
+    };
+
+    client
+        .put_object_legal_hold()
+        .bucket(config.bucket())
+        .key(key)
+        .legal_hold(ObjectLockLegalHold::builder().status(status).build())
+        .send()
+        .await
+        .map_err(|e| feature_error(config, Feature::ObjectLock, e))?;
+    Ok(())
+}
+
+/// Lock state from a single HEAD, which is cheaper than the two dedicated
+/// calls when checking many keys. HEAD leaves the lock headers out when the
+/// caller lacks `s3:GetObjectRetention`, so this can only err towards unlocked.
+async fn head_lock_state(client: &Client, bucket: &str, key: &str) -> S3Result<ObjectLockState> {
+    let head = client.head_object().bucket(bucket).key(key).send().await?;
+    let retention = match (
+        head.object_lock_mode(),
+        head.object_lock_retain_until_date(),
+    ) {
+        (Some(mode), Some(until)) => {
+            RetentionMode::from_sdk(mode.as_str()).map(|mode| ObjectRetention {
+                mode,
+                retain_until: until.secs(),
+            })
+        }
+        _ => None,
+    };
+    Ok(ObjectLockState {
+        retention,
+        legal_hold: head.object_lock_legal_hold_status() == Some(&ObjectLockLegalHoldStatus::On),
+    })
+}
+
+/// Split keys into those that can be deleted and those Object Lock would
+/// refuse. Buckets without Object Lock, and providers without it, are not
+/// checked per key. Keys whose check fails are kept so the delete itself
+/// reports the error.
+pub async fn partition_locked(
+    config: &ProviderConfig,
+    keys: Vec<String>,
+) -> S3Result<(Vec<String>, Vec<SkippedObject>)> {
+    if keys.is_empty() || !config.supports(Feature::ObjectLock) {
+        return Ok((keys, Vec::new()));
+    }
+    let client = config.create_client().await?;
+    match bucket_lock_enabled_with(&client, config).await {
+        Ok(true) => {}
+        Ok(false) => return Ok((keys, Vec::new())),
+        Err(err) => {
+            log::warn!("Skipping lock check on {}: {}", config.bucket(), err);
+            return Ok((keys, Vec::new()));
+        }
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_LOCK_CHECKS));
+    let mut handles = Vec::with_capacity(keys.len());
+    for key in keys {
+        let permit = semaphore.clone().acquire_owned().await?;
+        let client = client.clone();
+        let bucket = config.bucket().to_string();
+        handles.push(tokio::spawn(async move {
+            let _permit = permit;
+            let skipped = match head_lock_state(&client, &bucket, &key).await {
+                Ok(state) => state.skipped(&key, now),
+                Err(_) => None,
+            };
+            (key, skipped)
+        }));
+    }
+
+    let mut unlocked = Vec::new();
+    let mut locked = Vec::new();
+    for handle in handles {
+        match handle.await? {
+            (_, Some(skipped)) => locked.push(skipped),
+            (key, None) => unlocked.push(key),
+        }
+    }
+    Ok((unlocked, locked))
+}
+
+/// Lock check for a single key of a bucket known to have Object Lock
+/// enabled, e.g. before moving it.
+pub async fn check_locked_with(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+) -> S3Result<Option<SkippedObject>> {
+    let state = head_lock_state(client, bucket, key).await?;
+    Ok(state.skipped(key, chrono::Utc::now().timestamp()))
+}
+
+pub async fn get_object_lock(config: &ProviderConfig, key: &str) -> S3Result<ObjectLockState> {
+    let client = config.create_client().await?;
+    get_object_lock_with(&client, config, key).await
+}
+
+pub async fn bucket_lock_enabled(config: &ProviderConfig) -> S3Result<bool> {
+    let client = config.create_client().await?;
+    bucket_lock_enabled_with(&client, config).await
+}
+
+/// Validate against the current lock, then apply the retention change.
+pub async fn put_object_retention(
+    config: &ProviderConfig,
+    key: &str,
+    retention: Option<&ObjectRetention>,
+    bypass_governance: bool,
+) -> S3Result<()> {
+    let client = config.create_client().await?;
+    let current = get_object_lock_with(&client, config, key).await?;
+    validate_retention(
+        &current,
+        retention,
+        bypass_governance,
+        chrono::Utc::now().timestamp(),
+    )?;
+    put_object_retention_with(&client, config, key, retention, bypass_governance).await
+}
+
+pub async fn put_object_legal_hold(
+    config: &ProviderConfig,
+    key: &str,
+    enabled: bool,
+) -> S3Result<()> {
+    let client = config.create_client().await?;
+    put_object_legal_hold_with(&client, config, key, enabled).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn retained(mode: RetentionMode, retain_until: i64) -> ObjectLockState {
+        ObjectLockState {
+            retention: Some(ObjectRetention { mode, retain_until }),
+            legal_hold: false,
+        }
+    }
+
+    #[test]
+    fn skip_reason_prefers_legal_hold_and_ignores_expired_retention() {
+        let expired = retained(RetentionMode::Compliance, NOW - 1);
+        assert_eq!(expired.skip_reason(NOW), None);
+
+        let active = retained(RetentionMode::Governance, NOW + 60);
+        assert_eq!(active.skip_reason(NOW), Some(SkipReason::Retention));
+        let skipped = active.skipped("a.txt", NOW).unwrap();
+        assert!(skipped
+            .message
+            .starts_with("Locked in GOVERNANCE mode until"));
+
+        let held = ObjectLockState {
+            legal_hold: true,
+            ..active
+        };
+        assert_eq!(held.skip_reason(NOW), Some(SkipReason::LegalHold));
+        assert_eq!(
+            held.skipped("a.txt", NOW).unwrap().message,
+            "Under legal hold"
+        );
+    }
+
+    #[test]
+    fn compliance_retention_can_only_be_extended() {
+        let current = retained(RetentionMode::Compliance, NOW + 100);
+        let extend = ObjectRetention {
+            mode: RetentionMode::Compliance,
+            retain_until: NOW + 200,
+        };
+        let shorten = ObjectRetention {
+            retain_until: NOW + 50,
+            ..extend
+        };
+        let downgrade = ObjectRetention {
+            mode: RetentionMode::Governance,
+            ..extend
+        };
+
+        assert!(validate_retention(&current, Some(&extend), false, NOW).is_ok());
+        assert!(validate_retention(&current, Some(&shorten), true, NOW).is_err());
+        assert!(validate_retention(&current, Some(&downgrade), true, NOW).is_err());
+        assert!(validate_retention(&current, None, true, NOW).is_err());
+    }
+
+    #[test]
+    fn weakening_governance_retention_needs_bypass() {
+        let current = retained(RetentionMode::Governance, NOW + 100);
+        assert!(validate_retention(&current, None, false, NOW).is_err());
+        assert!(validate_retention(&current, None, true, NOW).is_ok());
+
+        let upgrade = ObjectRetention {
+            mode: RetentionMode::Compliance,
+            retain_until: NOW + 100,
+        };
+        assert!(validate_retention(&current, Some(&upgrade), false, NOW).is_ok());
+    }
+
+    #[test]
+    fn retain_until_must_be_in_the_future() {
+        let past = ObjectRetention {
+            mode: RetentionMode::Governance,
+            retain_until: NOW,
+        };
+        assert!(validate_retention(&ObjectLockState::default(), Some(&past), false, NOW).is_err());
+    }
+}
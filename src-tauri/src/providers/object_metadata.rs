@@ -0,0 +1,92 @@
+//! Object metadata a listing doesn't return (content type, additional
+//! checksum), read with HEAD.
+
+use super::capability::describe_sdk_error;
+use super::config::ProviderConfig;
+use super::encryption::SseCustomerKey;
+use super::s3_client::S3Result;
+use aws_sdk_s3::operation::head_object::HeadObjectOutput;
+use aws_sdk_s3::types::ChecksumMode;
+use aws_sdk_s3::Client;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjectMetadata {
+    pub size: i64,
+    pub etag: Option<String>,
+    /// `None` for the provider's default class, which HEAD leaves out.
+    pub storage_class: Option<String>,
+    pub content_type: Option<String>,
+    /// Additional checksum as `ALGORITHM:base64`; `None` for objects
+    /// uploaded without one.
+    pub checksum: Option<String>,
+}
+
+impl ObjectMetadata {
+    fn from_head(head: &HeadObjectOutput) -> Self {
+        ObjectMetadata {
+            size: head.content_length().unwrap_or(0),
+            etag: head.e_tag().map(str::to_string),
+            storage_class: head.storage_class().map(|class| class.as_str().to_string()),
+            content_type: head.content_type().map(str::to_string),
+            checksum: checksum_of(head),
+        }
+    }
+}
+
+/// The strongest checksum the object carries. Multipart uploads report a
+/// checksum of part checksums, suffixed with `-<parts>`.
+fn checksum_of(head: &HeadObjectOutput) -> Option<String> {
+    [
+        ("SHA256", head.checksum_sha256()),
+        ("SHA1", head.checksum_sha1()),
+        ("CRC64NVME", head.checksum_crc64_nvme()),
+        ("CRC32C", head.checksum_crc32_c()),
+        ("CRC32", head.checksum_crc32()),
+    ]
+    .into_iter()
+    .find_map(|(algorithm, value)| value.map(|value| format!("{}:{}", algorithm, value)))
+}
+
+/// HEAD an object with checksum mode enabled, using an existing client.
+pub async fn head_object_metadata_with(
+    client: &Client,
+    config: &ProviderConfig,
+    key: &str,
+) -> S3Result<ObjectMetadata> {
+    let head = client
+        .head_object()
+        .bucket(config.bucket())
+        .key(key)
+        .checksum_mode(ChecksumMode::Enabled)
+        .sse_customer_key_from(config.encryption())
+        .send()
+        .await
+        .map_err(describe_sdk_error)?;
+    Ok(ObjectMetadata::from_head(&head))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strongest_checksum_wins() {
+        let head = HeadObjectOutput::builder()
+            .content_length(5)
+            .content_type("text/plain")
+            .checksum_crc32("AAAAAA==")
+            .checksum_sha256("n4bQgYhMfWWaL+qgxVrQFaO/TxsrC4Is0V1sFbDwCgg=")
+            .build();
+        let meta = ObjectMetadata::from_head(&head);
+        assert_eq!(meta.size, 5);
+        assert_eq!(meta.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(
+            meta.checksum.as_deref(),
+            Some("SHA256:n4bQgYhMfWWaL+qgxVrQFaO/TxsrC4Is0V1sFbDwCgg=")
+        );
+        assert_eq!(
+            ObjectMetadata::from_head(&HeadObjectOutput::builder().build()).checksum,
+            None
+        );
+    }
+}
@@ -0,0 +1,456 @@
+//! Presigned request builder: GET / PUT URLs with response-header overrides
+//! and presigned POST policies for browser-style form uploads.
+//!
+//! GET and PUT are signed by the SDK. POST policies are not part of the SDK,
+//! so the policy document is built and signed here (SigV4, `s3` service).
+
+use super::aws;
+use super::capability::{ensure_supported, Feature};
+use super::config::ProviderConfig;
+use super::encryption::{SseCustomerKey, SseWrite};
+use super::s3_client::S3Result;
+use crate::db::default_endpoint_scheme;
+use crate::secrets;
+use aws_sdk_s3::presigning::PresigningConfig;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// SigV4 query signatures are valid for at most 7 days.
+const SIGV4_MAX_EXPIRY_SECS: u64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum PresignMethod {
+    Get,
+    Put,
+    Post,
+}
+
+impl PresignMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PresignMethod::Get => "GET",
+            PresignMethod::Put => "PUT",
+            PresignMethod::Post => "POST",
+        }
+    }
+}
+
+/// Conditions for a presigned POST upload.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PostPolicyOptions {
+    /// Accept any key under this prefix instead of exactly the request key.
+    /// The returned `key` field ends in `${filename}`.
+    pub key_prefix: Option<String>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    /// Exact Content-Type the upload must use.
+    pub content_type: Option<String>,
+    /// Content-Type prefix the upload must use, e.g. `image/`.
+    pub content_type_prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresignOptions {
+    pub method: PresignMethod,
+    #[serde(default)]
+    pub key: String,
+    pub expires_in_secs: u64,
+    /// GET: force a download with this file name (`response-content-disposition`).
+    pub download_filename: Option<String>,
+    /// GET: raw `response-content-disposition`, takes precedence over `download_filename`.
+    pub response_content_disposition: Option<String>,
+    /// GET: `response-content-type` override.
+    pub response_content_type: Option<String>,
+    /// PUT: Content-Type the uploader has to send (part of the signature).
+    pub content_type: Option<String>,
+    /// POST: policy conditions.
+    pub post: Option<PostPolicyOptions>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PresignedRequest {
+    pub method: PresignMethod,
+    pub url: String,
+    /// Headers the caller must send with the request (PUT, and GET on SSE-C
+    /// buckets).
+    pub headers: BTreeMap<String, String>,
+    /// Form fields to submit before the file (POST).
+    pub fields: BTreeMap<String, String>,
+    /// RFC 3339 expiry time.
+    pub expires_at: String,
+}
+
+/// Longest expiry each provider accepts for a presigned request.
+pub fn max_expiry_secs(config: &ProviderConfig) -> u64 {
+    match config {
+        // R2, S3 and MinIO all enforce the SigV4 limit.
+        ProviderConfig::R2(_)
+        | ProviderConfig::Aws(_)
+        | ProviderConfig::Minio(_)
+        | ProviderConfig::Rustfs(_) => SIGV4_MAX_EXPIRY_SECS,
+    }
+}
+
+fn validate_expiry(config: &ProviderConfig, expires_in_secs: u64) -> Result<(), String> {
+    let max = max_expiry_secs(config);
+    if expires_in_secs == 0 {
+        return Err("Expiry must be at least 1 second".to_string());
+    }
+    if expires_in_secs > max {
+        return Err(format!(
+            "Expiry of {}s exceeds the {} maximum of {}s ({} days)",
+            expires_in_secs,
+            config.provider_name(),
+            max,
+            max / 86_400
+        ));
+    }
+    Ok(())
+}
+
+/// `attachment` disposition with an ASCII fallback and an RFC 5987 UTF-8 name.
+pub fn attachment_disposition(filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii() && !c.is_ascii_control() && c != '"' && c != '\\' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        fallback,
+        urlencoding::encode(filename)
+    )
+}
+
+/// Build a presigned GET, PUT or POST request for `config.bucket`.
+pub async fn presign(
+    config: &ProviderConfig,
+    options: &PresignOptions,
+) -> S3Result<PresignedRequest> {
+    validate_expiry(config, options.expires_in_secs)?;
+    if options.method != PresignMethod::Post && options.key.is_empty() {
+        return Err("An object key is required".into());
+    }
+
+    let expires_at = Utc::now() + chrono::Duration::seconds(options.expires_in_secs as i64);
+
+    if options.method == PresignMethod::Post {
+        ensure_supported(config, Feature::PresignedPost)?;
+        return presign_post(config, options, expires_at).await;
+    }
+
+    let client = config.create_client().await?;
+    let presigning_config = PresigningConfig::builder()
+        .expires_in(Duration::from_secs(options.expires_in_secs))
+        .build()?;
+
+    let presigned = if options.method == PresignMethod::Get {
+        let disposition = options.response_content_disposition.clone().or_else(|| {
+            options
+                .download_filename
+                .as_deref()
+                .map(attachment_disposition)
+        });
+        client
+            .get_object()
+            .bucket(config.bucket())
+            .key(&options.key)
+            .set_response_content_disposition(disposition)
+            .set_response_content_type(options.response_content_type.clone())
+            .sse_customer_key_from(config.encryption())
+            .presigned(presigning_config)
+            .await?
+    } else {
+        client
+            .put_object()
+            .bucket(config.bucket())
+            .key(&options.key)
+            .set_content_type(options.content_type.clone())
+            .sse_from(config.encryption())
+            .presigned(presigning_config)
+            .await?
+    };
+
+    Ok(PresignedRequest {
+        method: options.method,
+        url: presigned.uri().to_string(),
+        headers: presigned
+            .headers()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        fields: BTreeMap::new(),
+        expires_at: expires_at.to_rfc3339(),
+    })
+}
+
+/// Signing credentials, region and form target for POST uploads.
+struct PostTarget {
+    access_key_id: String,
+    secret_access_key: String,
+    /// Set for temporary credentials (profiles, assumed roles)
+    session_token: Option<String>,
+    region: String,
+    url: String,
+}
+
+async fn post_target(config: &ProviderConfig) -> S3Result<PostTarget> {
+    match config {
+        ProviderConfig::Aws(aws) => {
+            let custom_host = aws
+                .endpoint_host
+                .as_deref()
+                .map(str::trim)
+                .filter(|h| !h.is_empty());
+            let url = match custom_host {
+                Some(host) => {
+                    let scheme = aws
+                        .endpoint_scheme
+                        .as_deref()
+                        .unwrap_or(default_endpoint_scheme("aws"));
+                    bucket_url(scheme, host, &aws.bucket, aws.force_path_style)
+                }
+                None => bucket_url(
+                    "https",
+                    &format!("s3.{}.amazonaws.com", aws.region),
+                    &aws.bucket,
+                    aws.force_path_style,
+                ),
+            };
+            let credentials = aws::credentials::resolve_credentials(
+                &aws.credentials,
+                &aws.access_key_id,
+                &aws.secret_access_key,
+            )
+            .await?;
+            Ok(PostTarget {
+                access_key_id: credentials.access_key_id().to_string(),
+                secret_access_key: credentials.secret_access_key().to_string(),
+                session_token: credentials.session_token().map(str::to_string),
+                region: aws.region.clone(),
+                url,
+            })
+        }
+        ProviderConfig::Minio(minio) | ProviderConfig::Rustfs(minio) => Ok(PostTarget {
+            access_key_id: minio.access_key_id.clone(),
+            secret_access_key: secrets::resolve(&minio.secret_access_key).await?,
+            session_token: None,
+            region: "us-east-1".to_string(),
+            url: bucket_url(
+                &minio.endpoint_scheme,
+                &minio.endpoint_host,
+                &minio.bucket,
+                minio.force_path_style,
+            ),
+        }),
+        ProviderConfig::R2(_) => {
+            Err(super::capability::UnsupportedFeature::new(config, Feature::PresignedPost).into())
+        }
+    }
+}
+
+fn bucket_url(scheme: &str, host: &str, bucket: &str, path_style: bool) -> String {
+    if path_style {
+        format!("{}://{}/{}/", scheme, host, bucket)
+    } else {
+        format!("{}://{}.{}/", scheme, bucket, host)
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC can take key of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn signing_key(secret_key: &str, date_stamp: &str, region: &str) -> Vec<u8> {
+    let k_date = hmac_sha256(
+        format!("AWS4{}", secret_key).as_bytes(),
+        date_stamp.as_bytes(),
+    );
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, b"s3");
+    hmac_sha256(&k_service, b"aws4_request")
+}
+
+/// Policy conditions plus the form fields that must accompany them.
+fn post_conditions(
+    bucket: &str,
+    key: &str,
+    post: &PostPolicyOptions,
+) -> Result<(Vec<Value>, BTreeMap<String, String>), String> {
+    let mut conditions = vec![json!({ "bucket": bucket })];
+    let mut fields = BTreeMap::new();
+
+    match post.key_prefix.as_deref() {
+        Some(prefix) => {
+            conditions.push(json!(["starts-with", "$key", prefix]));
+            fields.insert("key".to_string(), format!("{}${{filename}}", prefix));
+        }
+        None if key.is_empty() => {
+            return Err("POST policies need a key or a key prefix".to_string());
+        }
+        None => {
+            conditions.push(json!({ "key": key }));
+            fields.insert("key".to_string(), key.to_string());
+        }
+    }
+
+    if post.min_size.is_some() || post.max_size.is_some() {
+        let min = post.min_size.unwrap_or(0);
+        let max = post.max_size.unwrap_or(5 * 1024 * 1024 * 1024);
+        if min > max {
+            return Err("Minimum upload size is larger than the maximum".to_string());
+        }
+        conditions.push(json!(["content-length-range", min, max]));
+    }
+
+    match (&post.content_type, &post.content_type_prefix) {
+        (Some(_), Some(_)) => {
+            return Err("Use either an exact content type or a content type prefix".to_string());
+        }
+        (Some(content_type), None) => {
+            conditions.push(json!({ "Content-Type": content_type }));
+            fields.insert("Content-Type".to_string(), content_type.clone());
+        }
+        (None, Some(prefix)) => {
+            conditions.push(json!(["starts-with", "$Content-Type", prefix]));
+        }
+        (None, None) => {}
+    }
+
+    Ok((conditions, fields))
+}
+
+async fn presign_post(
+    config: &ProviderConfig,
+    options: &PresignOptions,
+    expires_at: DateTime<Utc>,
+) -> S3Result<PresignedRequest> {
+    let target = post_target(config).await?;
+    let post = options.post.clone().unwrap_or_default();
+    let (mut conditions, mut fields) = post_conditions(config.bucket(), &options.key, &post)?;
+    if let Some(encryption) = config.encryption() {
+        for (name, value) in encryption.write_headers() {
+            conditions.push(json!({ name.as_str(): value.as_str() }));
+            fields.insert(name, value);
+        }
+    }
+
+    let now = Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date_stamp = now.format("%Y%m%d").to_string();
+    let credential = format!(
+        "{}/{}/{}/s3/aws4_request",
+        target.access_key_id, date_stamp, target.region
+    );
+
+    conditions.push(json!({ "x-amz-algorithm": "AWS4-HMAC-SHA256" }));
+    conditions.push(json!({ "x-amz-credential": credential }));
+    conditions.push(json!({ "x-amz-date": amz_date }));
+    if let Some(token) = &target.session_token {
+        conditions.push(json!({ "x-amz-security-token": token }));
+        fields.insert("x-amz-security-token".to_string(), token.clone());
+    }
+
+    let policy = json!({
+        "expiration": expires_at.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+        "conditions": conditions,
+    });
+    let encoded_policy = BASE64.encode(serde_json::to_string(&policy)?);
+    let signature = hex::encode(hmac_sha256(
+        &signing_key(&target.secret_access_key, &date_stamp, &target.region),
+        encoded_policy.as_bytes(),
+    ));
+
+    fields.insert("policy".to_string(), encoded_policy);
+    fields.insert(
+        "x-amz-algorithm".to_string(),
+        "AWS4-HMAC-SHA256".to_string(),
+    );
+    fields.insert("x-amz-credential".to_string(), credential);
+    fields.insert("x-amz-date".to_string(), amz_date);
+    fields.insert("x-amz-signature".to_string(), signature);
+
+    Ok(PresignedRequest {
+        method: PresignMethod::Post,
+        url: target.url,
+        headers: BTreeMap::new(),
+        fields,
+        expires_at: expires_at.to_rfc3339(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r2::R2Config;
+
+    fn r2_config() -> ProviderConfig {
+        ProviderConfig::R2(R2Config {
+            account_id: "acct".to_string(),
+            bucket: "media".to_string(),
+            access_key_id: "key".to_string(),
+            secret_access_key: "secret".to_string(),
+            encryption: None,
+        })
+    }
+
+    #[test]
+    fn expiry_is_capped_at_seven_days() {
+        let config = r2_config();
+        assert!(validate_expiry(&config, 7 * 24 * 60 * 60).is_ok());
+        assert!(validate_expiry(&config, 7 * 24 * 60 * 60 + 1).is_err());
+        assert!(validate_expiry(&config, 0).is_err());
+    }
+
+    #[test]
+    fn attachment_disposition_keeps_utf8_name() {
+        assert_eq!(
+            attachment_disposition("résumé \"final\".pdf"),
+            "attachment; filename=\"r_sum_ _final_.pdf\"; filename*=UTF-8''r%C3%A9sum%C3%A9%20%22final%22.pdf"
+        );
+    }
+
+    #[test]
+    fn post_conditions_cover_prefix_size_and_type() {
+        let post = PostPolicyOptions {
+            key_prefix: Some("uploads/".to_string()),
+            max_size: Some(10 * 1024 * 1024),
+            content_type_prefix: Some("image/".to_string()),
+            ..Default::default()
+        };
+        let (conditions, fields) = post_conditions("media", "", &post).expect("valid policy");
+
+        assert!(conditions.contains(&json!(["starts-with", "$key", "uploads/"])));
+        assert!(conditions.contains(&json!(["content-length-range", 0, 10 * 1024 * 1024])));
+        assert!(conditions.contains(&json!(["starts-with", "$Content-Type", "image/"])));
+        assert_eq!(
+            fields.get("key").map(String::as_str),
+            Some("uploads/${filename}")
+        );
+    }
+
+    #[test]
+    fn post_conditions_reject_inverted_size_range() {
+        let post = PostPolicyOptions {
+            min_size: Some(10),
+            max_size: Some(5),
+            ..Default::default()
+        };
+        assert!(post_conditions("media", "a.txt", &post).is_err());
+    }
+}
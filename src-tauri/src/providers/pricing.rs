@@ -0,0 +1,83 @@
+//! List prices for storing data, used to estimate a bucket's monthly cost.
+//!
+//! Prices are USD per GB-month (GB = 2^30 bytes) for the cheapest region and
+//! tier, without request, retrieval or egress fees. Self-hosted providers
+//! cost nothing here; the caller can override any class.
+
+use std::collections::HashMap;
+
+use crate::db::tokens::StorageProvider;
+
+const GIB: f64 = (1u64 << 30) as f64;
+
+/// Class assumed for objects whose listing reported none.
+pub const DEFAULT_STORAGE_CLASS: &str = "STANDARD";
+
+fn list_price(provider: &StorageProvider, storage_class: &str) -> Option<f64> {
+    match provider {
+        StorageProvider::R2 => match storage_class {
+            "STANDARD" => Some(0.015),
+            "STANDARD_IA" => Some(0.01),
+            _ => None,
+        },
+        StorageProvider::Aws => match storage_class {
+            "STANDARD" | "INTELLIGENT_TIERING" => Some(0.023),
+            "STANDARD_IA" => Some(0.0125),
+            "ONEZONE_IA" => Some(0.01),
+            "GLACIER_IR" => Some(0.004),
+            "GLACIER" => Some(0.0036),
+            "DEEP_ARCHIVE" => Some(0.00099),
+            "REDUCED_REDUNDANCY" => Some(0.024),
+            _ => None,
+        },
+        StorageProvider::Minio | StorageProvider::Rustfs => Some(0.0),
+    }
+}
+
+/// Price per GB-month of `storage_class` (`None` meaning the default class),
+/// preferring `overrides`. `None` when the class has no known price.
+pub fn price_per_gb_month(
+    provider: &StorageProvider,
+    storage_class: Option<&str>,
+    overrides: &HashMap<String, f64>,
+) -> Option<f64> {
+    let storage_class = storage_class.unwrap_or(DEFAULT_STORAGE_CLASS);
+    overrides
+        .get(storage_class)
+        .copied()
+        .or_else(|| list_price(provider, storage_class))
+}
+
+/// Monthly cost of keeping `bytes` at `price` per GB-month.
+pub fn monthly_cost(bytes: i64, price: f64) -> f64 {
+    bytes.max(0) as f64 / GIB * price
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prices_fall_back_to_the_default_class_and_honour_overrides() {
+        let none = HashMap::new();
+        assert_eq!(
+            price_per_gb_month(&StorageProvider::R2, None, &none),
+            Some(0.015)
+        );
+        assert_eq!(
+            price_per_gb_month(&StorageProvider::R2, Some("GLACIER"), &none),
+            None
+        );
+        assert_eq!(
+            price_per_gb_month(&StorageProvider::Minio, Some("STANDARD"), &none),
+            Some(0.0)
+        );
+
+        let overrides = HashMap::from([("STANDARD".to_string(), 0.02)]);
+        assert_eq!(
+            price_per_gb_month(&StorageProvider::Aws, None, &overrides),
+            Some(0.02)
+        );
+        assert_eq!(monthly_cost(1 << 31, 0.02), 0.04);
+    }
+}
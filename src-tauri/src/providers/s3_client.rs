@@ -1,4 +1,5 @@
 use aws_config::Region;
+use aws_credential_types::provider::SharedCredentialsProvider;
 use aws_credential_types::Credentials;
 use aws_sdk_s3::config::Builder as S3ConfigBuilder;
 use aws_sdk_s3::Client;
@@ -11,20 +12,22 @@ pub struct S3ClientConfig<'a> {
     pub region: &'a str,
     pub endpoint_url: Option<&'a str>,
     pub force_path_style: bool,
+    /// Resolves credentials instead of the static key pair when set.
+    pub credentials_provider: Option<SharedCredentialsProvider>,
 }
 
 pub fn create_s3_client(config: &S3ClientConfig<'_>) -> S3Result<Client> {
-    let credentials = Credentials::new(
-        config.access_key_id,
-        config.secret_access_key,
-        None,
-        None,
-        "s3-provider",
-    );
-
-    let mut builder = S3ConfigBuilder::new()
-        .credentials_provider(credentials)
-        .region(Region::new(config.region.to_string()));
+    let mut builder = S3ConfigBuilder::new().region(Region::new(config.region.to_string()));
+    builder = match &config.credentials_provider {
+        Some(provider) => builder.credentials_provider(provider.clone()),
+        None => builder.credentials_provider(Credentials::new(
+            config.access_key_id,
+            config.secret_access_key,
+            None,
+            None,
+            "s3-provider",
+        )),
+    };
 
     if let Some(endpoint_url) = config.endpoint_url {
         builder = builder.endpoint_url(endpoint_url);
@@ -37,3 +40,87 @@ pub fn create_s3_client(config: &S3ClientConfig<'_>) -> S3Result<Client> {
     let s3_config = builder.build();
     Ok(Client::from_conf(s3_config))
 }
+
+/// An object from a recursive listing.
+#[derive(Debug, Clone)]
+pub struct ListedObject {
+    pub key: String,
+    pub size: i64,
+    pub last_modified: String,
+}
+
+/// One page (up to 1000 objects) of a recursive listing.
+#[derive(Debug, Clone, Default)]
+pub struct ObjectPage {
+    pub objects: Vec<ListedObject>,
+    /// Continuation token for the next page; `None` on the last page.
+    pub next_token: Option<String>,
+}
+
+/// List one page of every object under `prefix` (no delimiter), folder
+/// placeholder keys included.
+pub async fn list_objects_page(
+    client: &Client,
+    bucket: &str,
+    prefix: &str,
+    continuation_token: Option<&str>,
+) -> S3Result<ObjectPage> {
+    let mut request = client.list_objects_v2().bucket(bucket).max_keys(1000);
+    if !prefix.is_empty() {
+        request = request.prefix(prefix);
+    }
+    if let Some(token) = continuation_token {
+        request = request.continuation_token(token);
+    }
+
+    let response = request.send().await?;
+    let objects = response
+        .contents()
+        .iter()
+        .filter_map(|obj| {
+            Some(ListedObject {
+                key: obj.key()?.to_string(),
+                size: obj.size().unwrap_or(0),
+                last_modified: obj
+                    .last_modified()
+                    .map(|d| d.to_string())
+                    .unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    let next_token = if response.is_truncated() == Some(true) {
+        response.next_continuation_token().map(str::to_string)
+    } else {
+        None
+    };
+
+    Ok(ObjectPage {
+        objects,
+        next_token,
+    })
+}
+
+/// List every object key under `prefix` (no delimiter), following pagination.
+/// Folder placeholder keys ending in `/` are skipped.
+pub async fn list_all_keys(client: &Client, bucket: &str, prefix: &str) -> S3Result<Vec<String>> {
+    let mut keys = Vec::new();
+    let mut continuation_token: Option<String> = None;
+
+    loop {
+        let page = list_objects_page(client, bucket, prefix, continuation_token.as_deref()).await?;
+        keys.extend(
+            page.objects
+                .into_iter()
+                .map(|obj| obj.key)
+                .filter(|key| !key.ends_with('/')),
+        );
+
+        continuation_token = page.next_token;
+        if continuation_token.is_none() {
+            break;
+        }
+    }
+
+    Ok(keys)
+}
@@ -0,0 +1,266 @@
+//! Storage classes and the archive restore workflow.
+//!
+//! Objects in GLACIER / DEEP_ARCHIVE, or in an Intelligent-Tiering archive
+//! tier, cannot be read until a RestoreObject request completes. HEAD reports
+//! progress in the `x-amz-restore` header, which `ArchiveState` interprets.
+
+use aws_sdk_s3::types::{GlacierJobParameters, RestoreRequest, StorageClass, Tier};
+use serde::{Deserialize, Serialize};
+
+/// Storage classes uploads and copies can choose.
+pub const SELECTABLE_STORAGE_CLASSES: &[&str] = &[
+    "STANDARD",
+    "STANDARD_IA",
+    "ONEZONE_IA",
+    "INTELLIGENT_TIERING",
+    "GLACIER_IR",
+    "GLACIER",
+    "DEEP_ARCHIVE",
+];
+
+/// Intelligent-Tiering archive tier reported by HEAD's `x-amz-archive-status`.
+const DEEP_ARCHIVE_ACCESS: &str = "DEEP_ARCHIVE_ACCESS";
+
+pub fn parse_storage_class(value: &str) -> Result<StorageClass, String> {
+    let value = value.trim().to_ascii_uppercase();
+    if SELECTABLE_STORAGE_CLASSES.contains(&value.as_str()) {
+        Ok(StorageClass::from(value.as_str()))
+    } else {
+        Err(format!("Unsupported storage class: {}", value))
+    }
+}
+
+/// Whether objects of this class need a restore before they can be read.
+pub fn is_archive_class(storage_class: &str) -> bool {
+    matches!(storage_class, "GLACIER" | "DEEP_ARCHIVE")
+}
+
+/// Whether an object of this class might be unreadable. Intelligent-Tiering
+/// objects only are once moved to an archive tier, which needs a HEAD to tell.
+pub fn may_be_archived(storage_class: &str) -> bool {
+    is_archive_class(storage_class) || storage_class == "INTELLIGENT_TIERING"
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RestoreTier {
+    Expedited,
+    Standard,
+    Bulk,
+}
+
+impl RestoreTier {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RestoreTier::Expedited => "expedited",
+            RestoreTier::Standard => "standard",
+            RestoreTier::Bulk => "bulk",
+        }
+    }
+
+    fn to_sdk(self) -> Tier {
+        match self {
+            RestoreTier::Expedited => Tier::Expedited,
+            RestoreTier::Standard => Tier::Standard,
+            RestoreTier::Bulk => Tier::Bulk,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestoreStatus {
+    /// Readable without a restore.
+    NotArchived,
+    /// Archived and no restore has been requested.
+    Archived,
+    InProgress,
+    /// A temporary copy is readable until `expiry_date`.
+    Restored,
+}
+
+impl RestoreStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RestoreStatus::NotArchived => "not-archived",
+            RestoreStatus::Archived => "archived",
+            RestoreStatus::InProgress => "in-progress",
+            RestoreStatus::Restored => "restored",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArchiveState {
+    pub storage_class: Option<String>,
+    /// Intelligent-Tiering archive tier, if the object is in one.
+    pub archive_status: Option<String>,
+    pub status: RestoreStatus,
+    pub expiry_date: Option<String>,
+}
+
+impl ArchiveState {
+    /// Interpret a HEAD response's storage class, `x-amz-archive-status` and
+    /// `x-amz-restore` values.
+    pub fn from_head(
+        storage_class: Option<&str>,
+        archive_status: Option<&str>,
+        restore: Option<&str>,
+    ) -> Self {
+        let archived = storage_class.is_some_and(is_archive_class) || archive_status.is_some();
+        let (status, expiry_date) = match restore.map(parse_restore_header) {
+            _ if !archived => (RestoreStatus::NotArchived, None),
+            Some((true, _)) => (RestoreStatus::InProgress, None),
+            Some((false, expiry)) => (RestoreStatus::Restored, expiry),
+            None => (RestoreStatus::Archived, None),
+        };
+        ArchiveState {
+            storage_class: storage_class.map(str::to_string),
+            archive_status: archive_status.map(str::to_string),
+            status,
+            expiry_date,
+        }
+    }
+
+    pub fn readable(&self) -> bool {
+        matches!(
+            self.status,
+            RestoreStatus::NotArchived | RestoreStatus::Restored
+        )
+    }
+
+    /// Check a restore request against the object's archive. Intelligent-
+    /// Tiering restores move the object back to a frequent tier, so they take
+    /// no `days`; expedited retrieval is not offered for deep archive.
+    pub fn validate_restore(&self, tier: RestoreTier, days: Option<i32>) -> Result<(), String> {
+        let deep = self.storage_class.as_deref() == Some("DEEP_ARCHIVE")
+            || self.archive_status.as_deref() == Some(DEEP_ARCHIVE_ACCESS);
+        if tier == RestoreTier::Expedited && deep {
+            return Err("Expedited retrieval is not available for deep archive".to_string());
+        }
+        match (self.archive_status.is_some(), days) {
+            (true, Some(_)) => {
+                Err("Intelligent-Tiering restores do not take a number of days".to_string())
+            }
+            (false, None) => {
+                Err("Number of days to keep the restored copy is required".to_string())
+            }
+            (false, Some(days)) if days < 1 => {
+                Err("Restored copies must be kept for at least one day".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Build the RestoreObject request body.
+pub fn restore_request(tier: RestoreTier, days: Option<i32>) -> Result<RestoreRequest, String> {
+    let job = GlacierJobParameters::builder()
+        .tier(tier.to_sdk())
+        .build()
+        .map_err(|e| format!("Invalid restore request: {}", e))?;
+    Ok(RestoreRequest::builder()
+        .set_days(days)
+        .glacier_job_parameters(job)
+        .build())
+}
+
+/// Parse `ongoing-request="false", expiry-date="Fri, 21 Dec 2012 00:00:00 GMT"`
+/// into (ongoing, expiry_date). Values are quoted and may contain commas.
+fn parse_restore_header(value: &str) -> (bool, Option<String>) {
+    let mut ongoing = false;
+    let mut expiry = None;
+    let mut rest = value;
+    while let Some(eq) = rest.find('=') {
+        let name = rest[..eq].trim().trim_start_matches(',').trim();
+        let after = rest[eq + 1..].trim_start();
+        let (field, remainder) = match after.strip_prefix('"') {
+            Some(quoted) => match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => (quoted, ""),
+            },
+            None => match after.find(',') {
+                Some(end) => (&after[..end], &after[end..]),
+                None => (after, ""),
+            },
+        };
+        match name {
+            "ongoing-request" => ongoing = field.eq_ignore_ascii_case("true"),
+            "expiry-date" => expiry = Some(field.to_string()),
+            _ => {}
+        }
+        rest = remainder;
+    }
+    (ongoing, expiry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_header_reports_progress_and_expiry() {
+        assert_eq!(
+            parse_restore_header(r#"ongoing-request="true""#),
+            (true, None)
+        );
+        assert_eq!(
+            parse_restore_header(
+                r#"ongoing-request="false", expiry-date="Fri, 21 Dec 2012 00:00:00 GMT""#
+            ),
+            (false, Some("Fri, 21 Dec 2012 00:00:00 GMT".to_string()))
+        );
+    }
+
+    #[test]
+    fn archive_state_follows_class_and_restore_header() {
+        let standard = ArchiveState::from_head(Some("STANDARD"), None, None);
+        assert_eq!(standard.status, RestoreStatus::NotArchived);
+        assert!(standard.readable());
+
+        let glacier = ArchiveState::from_head(Some("GLACIER"), None, None);
+        assert_eq!(glacier.status, RestoreStatus::Archived);
+        assert!(!glacier.readable());
+
+        let restoring = ArchiveState::from_head(
+            Some("DEEP_ARCHIVE"),
+            None,
+            Some(r#"ongoing-request="true""#),
+        );
+        assert_eq!(restoring.status, RestoreStatus::InProgress);
+
+        let tiered = ArchiveState::from_head(
+            Some("INTELLIGENT_TIERING"),
+            Some("ARCHIVE_ACCESS"),
+            Some(r#"ongoing-request="false", expiry-date="Fri, 21 Dec 2012 00:00:00 GMT""#),
+        );
+        assert_eq!(tiered.status, RestoreStatus::Restored);
+        assert!(tiered.readable());
+    }
+
+    #[test]
+    fn restore_options_must_fit_the_archive() {
+        let deep = ArchiveState::from_head(Some("DEEP_ARCHIVE"), None, None);
+        assert!(deep
+            .validate_restore(RestoreTier::Expedited, Some(1))
+            .is_err());
+        assert!(deep.validate_restore(RestoreTier::Bulk, None).is_err());
+        assert!(deep.validate_restore(RestoreTier::Bulk, Some(7)).is_ok());
+
+        let tiered =
+            ArchiveState::from_head(Some("INTELLIGENT_TIERING"), Some("ARCHIVE_ACCESS"), None);
+        assert!(tiered
+            .validate_restore(RestoreTier::Standard, Some(7))
+            .is_err());
+        assert!(tiered.validate_restore(RestoreTier::Standard, None).is_ok());
+    }
+
+    #[test]
+    fn storage_class_names_are_validated() {
+        assert_eq!(
+            parse_storage_class("glacier").unwrap(),
+            StorageClass::Glacier
+        );
+        assert!(parse_storage_class("COLD").is_err());
+    }
+}
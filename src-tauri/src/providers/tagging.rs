@@ -0,0 +1,227 @@
+//! Object tagging (GetObjectTagging / PutObjectTagging / DeleteObjectTagging)
+
+use super::capability::{ensure_supported, feature_error, Feature};
+use super::config::ProviderConfig;
+use super::s3_client::S3Result;
+use aws_sdk_s3::types::{Tag, Tagging};
+use aws_sdk_s3::Client;
+use serde::{Deserialize, Serialize};
+
+/// S3 allows at most 10 tags per object.
+pub const MAX_TAGS_PER_OBJECT: usize = 10;
+const MAX_TAG_KEY_LEN: usize = 128;
+const MAX_TAG_VALUE_LEN: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectTag {
+    pub key: String,
+    pub value: String,
+}
+
+/// How a batch tag operation combines new tags with existing ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMode {
+    /// Overwrite the whole tag set.
+    #[default]
+    Replace,
+    /// Add or update the given keys, keep the rest.
+    Merge,
+    /// Drop the given keys, keep the rest.
+    Remove,
+}
+
+/// Validate a tag set against S3 limits before sending it.
+pub fn validate_tags(tags: &[ObjectTag]) -> Result<(), String> {
+    if tags.len() > MAX_TAGS_PER_OBJECT {
+        return Err(format!(
+            "At most {} tags are allowed per object (got {})",
+            MAX_TAGS_PER_OBJECT,
+            tags.len()
+        ));
+    }
+    for (i, tag) in tags.iter().enumerate() {
+        if tag.key.is_empty() {
+            return Err("Tag key cannot be empty".to_string());
+        }
+        if tag.key.chars().count() > MAX_TAG_KEY_LEN {
+            return Err(format!(
+                "Tag key '{}' exceeds {} characters",
+                tag.key, MAX_TAG_KEY_LEN
+            ));
+        }
+        if tag.value.chars().count() > MAX_TAG_VALUE_LEN {
+            return Err(format!(
+                "Value of tag '{}' exceeds {} characters",
+                tag.key, MAX_TAG_VALUE_LEN
+            ));
+        }
+        if tags[..i].iter().any(|other| other.key == tag.key) {
+            return Err(format!("Duplicate tag key '{}'", tag.key));
+        }
+    }
+    Ok(())
+}
+
+/// Combine `existing` tags with `changes` according to `mode`.
+pub fn apply_tag_mode(
+    existing: &[ObjectTag],
+    changes: &[ObjectTag],
+    mode: TagMode,
+) -> Vec<ObjectTag> {
+    match mode {
+        TagMode::Replace => changes.to_vec(),
+        TagMode::Merge => {
+            let mut merged: Vec<ObjectTag> = existing
+                .iter()
+                .filter(|tag| !changes.iter().any(|c| c.key == tag.key))
+                .cloned()
+                .collect();
+            merged.extend(changes.iter().cloned());
+            merged
+        }
+        TagMode::Remove => existing
+            .iter()
+            .filter(|tag| !changes.iter().any(|c| c.key == tag.key))
+            .cloned()
+            .collect(),
+    }
+}
+
+fn to_sdk_tags(tags: &[ObjectTag]) -> S3Result<Vec<Tag>> {
+    tags.iter()
+        .map(|tag| {
+            Tag::builder()
+                .key(&tag.key)
+                .value(&tag.value)
+                .build()
+                .map_err(Into::into)
+        })
+        .collect()
+}
+
+pub(crate) fn from_sdk_tags(tags: &[Tag]) -> Vec<ObjectTag> {
+    tags.iter()
+        .map(|tag| ObjectTag {
+            key: tag.key().to_string(),
+            value: tag.value().to_string(),
+        })
+        .collect()
+}
+
+/// Get tags with an existing client (batch callers reuse one client).
+pub async fn get_object_tags_with(
+    client: &Client,
+    config: &ProviderConfig,
+    key: &str,
+) -> S3Result<Vec<ObjectTag>> {
+    ensure_supported(config, Feature::ObjectTagging)?;
+    let response = client
+        .get_object_tagging()
+        .bucket(config.bucket())
+        .key(key)
+        .send()
+        .await
+        .map_err(|e| feature_error(config, Feature::ObjectTagging, e))?;
+
+    Ok(from_sdk_tags(response.tag_set()))
+}
+
+/// Put tags with an existing client. An empty set removes all tags.
+pub async fn put_object_tags_with(
+    client: &Client,
+    config: &ProviderConfig,
+    key: &str,
+    tags: &[ObjectTag],
+) -> S3Result<()> {
+    ensure_supported(config, Feature::ObjectTagging)?;
+    validate_tags(tags)?;
+
+    if tags.is_empty() {
+        return delete_object_tags_with(client, config, key).await;
+    }
+
+    let tagging = Tagging::builder()
+        .set_tag_set(Some(to_sdk_tags(tags)?))
+        .build()?;
+
+    client
+        .put_object_tagging()
+        .bucket(config.bucket())
+        .key(key)
+        .tagging(tagging)
+        .send()
+        .await
+        .map_err(|e| feature_error(config, Feature::ObjectTagging, e))?;
+
+    Ok(())
+}
+
+pub async fn delete_object_tags_with(
+    client: &Client,
+    config: &ProviderConfig,
+    key: &str,
+) -> S3Result<()> {
+    ensure_supported(config, Feature::ObjectTagging)?;
+    client
+        .delete_object_tagging()
+        .bucket(config.bucket())
+        .key(key)
+        .send()
+        .await
+        .map_err(|e| feature_error(config, Feature::ObjectTagging, e))?;
+    Ok(())
+}
+
+pub async fn get_object_tags(config: &ProviderConfig, key: &str) -> S3Result<Vec<ObjectTag>> {
+    let client = config.create_client().await?;
+    get_object_tags_with(&client, config, key).await
+}
+
+pub async fn put_object_tags(
+    config: &ProviderConfig,
+    key: &str,
+    tags: &[ObjectTag],
+) -> S3Result<()> {
+    let client = config.create_client().await?;
+    put_object_tags_with(&client, config, key, tags).await
+}
+
+pub async fn delete_object_tags(config: &ProviderConfig, key: &str) -> S3Result<()> {
+    let client = config.create_client().await?;
+    delete_object_tags_with(&client, config, key).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(key: &str, value: &str) -> ObjectTag {
+        ObjectTag {
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn merge_overrides_matching_keys_and_keeps_others() {
+        let existing = vec![tag("team", "media"), tag("retention", "30d")];
+        let merged = apply_tag_mode(&existing, &[tag("retention", "90d")], TagMode::Merge);
+        assert_eq!(merged, vec![tag("team", "media"), tag("retention", "90d")]);
+    }
+
+    #[test]
+    fn remove_drops_keys_regardless_of_value() {
+        let existing = vec![tag("team", "media"), tag("retention", "30d")];
+        let remaining = apply_tag_mode(&existing, &[tag("retention", "")], TagMode::Remove);
+        assert_eq!(remaining, vec![tag("team", "media")]);
+    }
+
+    #[test]
+    fn validate_rejects_duplicates_and_oversized_sets() {
+        assert!(validate_tags(&[tag("a", "1"), tag("a", "2")]).is_err());
+        let many: Vec<ObjectTag> = (0..11).map(|i| tag(&format!("k{}", i), "v")).collect();
+        assert!(validate_tags(&many).is_err());
+        assert!(validate_tags(&[tag("a", "1"), tag("b", "2")]).is_ok());
+    }
+}
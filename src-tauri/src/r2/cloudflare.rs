@@ -0,0 +1,245 @@
+//! Cloudflare REST API for R2 bucket public access
+//!
+//! r2.dev access and custom domains are not part of the S3 API; they are
+//! managed through `api.cloudflare.com` with the token's Cloudflare API token.
+
+use super::types::R2Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+const API_BASE: &str = "https://api.cloudflare.com/client/v4";
+
+/// r2.dev managed subdomain state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagedDomain {
+    pub domain: String,
+    pub enabled: bool,
+}
+
+/// Custom domain attached to a bucket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomDomain {
+    pub domain: String,
+    pub enabled: bool,
+    /// Ownership verification status (`active`, `pending`, ...)
+    pub ownership_status: Option<String>,
+    /// Certificate status (`active`, `pending`, ...)
+    pub ssl_status: Option<String>,
+    pub zone_id: Option<String>,
+    pub zone_name: Option<String>,
+}
+
+impl CustomDomain {
+    /// Enabled and fully provisioned, i.e. actually serving the bucket.
+    pub fn is_active(&self) -> bool {
+        self.enabled
+            && self.ownership_status.as_deref() == Some("active")
+            && self.ssl_status.as_deref() == Some("active")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiEnvelope<T> {
+    success: bool,
+    #[serde(default)]
+    errors: Vec<ApiError>,
+    result: Option<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCustomDomainStatus {
+    ownership: Option<String>,
+    ssl: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCustomDomain {
+    domain: String,
+    enabled: bool,
+    status: Option<RawCustomDomainStatus>,
+    zone_id: Option<String>,
+    zone_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawCustomDomainList {
+    #[serde(default)]
+    domains: Vec<RawCustomDomain>,
+}
+
+impl From<RawCustomDomain> for CustomDomain {
+    fn from(raw: RawCustomDomain) -> Self {
+        let status = raw.status;
+        CustomDomain {
+            domain: raw.domain,
+            enabled: raw.enabled,
+            ownership_status: status.as_ref().and_then(|s| s.ownership.clone()),
+            ssl_status: status.and_then(|s| s.ssl),
+            zone_id: raw.zone_id,
+            zone_name: raw.zone_name,
+        }
+    }
+}
+
+fn bucket_url(account_id: &str, bucket: &str, path: &str) -> String {
+    format!(
+        "{}/accounts/{}/r2/buckets/{}/{}",
+        API_BASE,
+        account_id,
+        urlencoding::encode(bucket),
+        path
+    )
+}
+
+/// Send a request and unwrap the Cloudflare `{ success, errors, result }` envelope.
+async fn send<T: DeserializeOwned>(
+    request: reqwest::RequestBuilder,
+    api_token: &str,
+) -> R2Result<T> {
+    if api_token.trim().is_empty() {
+        return Err("Cloudflare API token is not configured for this account".into());
+    }
+
+    let response = request.bearer_auth(api_token.trim()).send().await?;
+    let status = response.status();
+    let body = response.text().await?;
+
+    let envelope: ApiEnvelope<T> = serde_json::from_str(&body)
+        .map_err(|_| format!("Cloudflare API returned HTTP {}: {}", status.as_u16(), body))?;
+
+    if !envelope.success {
+        let message = envelope
+            .errors
+            .iter()
+            .map(|e| format!("{} ({})", e.message, e.code))
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(format!("Cloudflare API error: {}", message).into());
+    }
+
+    envelope
+        .result
+        .ok_or_else(|| "Cloudflare API returned no result".into())
+}
+
+fn json_body(value: serde_json::Value) -> R2Result<String> {
+    Ok(serde_json::to_string(&value)?)
+}
+
+/// Get the r2.dev managed domain state
+pub async fn get_managed_domain(
+    account_id: &str,
+    api_token: &str,
+    bucket: &str,
+) -> R2Result<ManagedDomain> {
+    let client = reqwest::Client::new();
+    send(
+        client.get(bucket_url(account_id, bucket, "domains/managed")),
+        api_token,
+    )
+    .await
+}
+
+/// Enable or disable public access through the r2.dev managed domain
+pub async fn set_managed_domain(
+    account_id: &str,
+    api_token: &str,
+    bucket: &str,
+    enabled: bool,
+) -> R2Result<ManagedDomain> {
+    let client = reqwest::Client::new();
+    send(
+        client
+            .put(bucket_url(account_id, bucket, "domains/managed"))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(json_body(serde_json::json!({ "enabled": enabled }))?),
+        api_token,
+    )
+    .await
+}
+
+/// List custom domains attached to the bucket
+pub async fn list_custom_domains(
+    account_id: &str,
+    api_token: &str,
+    bucket: &str,
+) -> R2Result<Vec<CustomDomain>> {
+    let client = reqwest::Client::new();
+    let list: RawCustomDomainList = send(
+        client.get(bucket_url(account_id, bucket, "domains/custom")),
+        api_token,
+    )
+    .await?;
+    Ok(list.domains.into_iter().map(CustomDomain::from).collect())
+}
+
+/// Attach a custom domain from a zone on the same Cloudflare account
+pub async fn add_custom_domain(
+    account_id: &str,
+    api_token: &str,
+    bucket: &str,
+    domain: &str,
+    zone_id: &str,
+    enabled: bool,
+) -> R2Result<()> {
+    let client = reqwest::Client::new();
+    let _: serde_json::Value = send(
+        client
+            .post(bucket_url(account_id, bucket, "domains/custom"))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(json_body(serde_json::json!({
+                "domain": domain,
+                "zoneId": zone_id,
+                "enabled": enabled,
+            }))?),
+        api_token,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Enable or disable an attached custom domain
+pub async fn set_custom_domain_enabled(
+    account_id: &str,
+    api_token: &str,
+    bucket: &str,
+    domain: &str,
+    enabled: bool,
+) -> R2Result<()> {
+    let client = reqwest::Client::new();
+    let path = format!("domains/custom/{}", urlencoding::encode(domain));
+    let _: serde_json::Value = send(
+        client
+            .put(bucket_url(account_id, bucket, &path))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(json_body(serde_json::json!({ "enabled": enabled }))?),
+        api_token,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Detach a custom domain from the bucket
+pub async fn remove_custom_domain(
+    account_id: &str,
+    api_token: &str,
+    bucket: &str,
+    domain: &str,
+) -> R2Result<()> {
+    let client = reqwest::Client::new();
+    let path = format!("domains/custom/{}", urlencoding::encode(domain));
+    let _: serde_json::Value = send(
+        client.delete(bucket_url(account_id, bucket, &path)),
+        api_token,
+    )
+    .await?;
+    Ok(())
+}
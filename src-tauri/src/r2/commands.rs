@@ -2,6 +2,7 @@
 
 use super::types::R2Config;
 use super::upload::upload_file;
+use crate::commands::client_encryption::resolve_upload_encryption;
 use serde::Serialize;
 use std::path::PathBuf;
 use tauri::{AppHandle, Emitter};
@@ -36,12 +37,26 @@ pub async fn upload_file_sdk(
     access_key_id: String,
     secret_access_key: String,
 ) -> Result<UploadResult, String> {
-    let config = R2Config {
+    let mut config = R2Config {
         account_id,
         bucket,
         access_key_id,
         secret_access_key,
+        encryption: None,
     };
+    let (encryption, client_key) =
+        match resolve_upload_encryption("r2", &config.account_id, &config.bucket).await {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                return Ok(UploadResult {
+                    task_id,
+                    success: false,
+                    error: Some(e),
+                    upload_id: None,
+                })
+            }
+        };
+    config.encryption = encryption;
 
     let path = PathBuf::from(&file_path);
     if !path.exists() {
@@ -86,6 +101,7 @@ pub async fn upload_file_sdk(
         &key,
         &path,
         content_type.as_deref(),
+        client_key.as_ref(),
         Some(progress_callback),
     )
     .await;
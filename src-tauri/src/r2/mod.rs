@@ -6,8 +6,10 @@
 //! - `objects`: Object operations (delete, copy, rename)
 //! - `upload`: Upload operations (simple, multipart)
 //! - `presigned`: Presigned URL generation
+//! - `cloudflare`: Cloudflare API (r2.dev access, custom domains)
 //! - `commands`: Tauri commands
 
+mod cloudflare;
 pub mod commands;
 mod list;
 mod objects;
@@ -25,6 +27,12 @@ pub use list::{list_all_objects_recursive, list_buckets, list_folder_objects, li
 pub(crate) use objects::copy_object_between_buckets;
 pub use objects::{delete_object, delete_objects, rename_object};
 
+// Re-export Cloudflare API operations
+pub use cloudflare::{
+    add_custom_domain, get_managed_domain, list_custom_domains, remove_custom_domain,
+    set_custom_domain_enabled, set_managed_domain, CustomDomain, ManagedDomain,
+};
+
 // Re-export presigned URL
 pub use presigned::{generate_presigned_put_url, generate_presigned_url};
 
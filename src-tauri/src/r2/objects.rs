@@ -1,7 +1,9 @@
 //! R2 object operations (delete, copy, rename)
 
 use super::types::{create_r2_client, R2Config, R2Result};
-use aws_sdk_s3::types::{Delete, ObjectIdentifier};
+use crate::providers::batch_delete::{self, DeleteObjectsOutcome, DeleteProgressFn};
+use crate::providers::encryption::ObjectEncryption;
+use crate::providers::multipart_copy::{self, CopyEncryption, CopyProgressFn};
 
 /// Delete a single object
 pub async fn delete_object(config: &R2Config, key: &str) -> R2Result<()> {
@@ -15,71 +17,77 @@ pub async fn delete_object(config: &R2Config, key: &str) -> R2Result<()> {
     Ok(())
 }
 
-/// Delete multiple objects in batch
-#[allow(dead_code)]
-pub async fn delete_objects(config: &R2Config, keys: Vec<String>) -> R2Result<()> {
+/// Delete objects in chunks of 1000, reporting which keys failed
+pub async fn delete_objects(
+    config: &R2Config,
+    keys: Vec<String>,
+    on_progress: Option<DeleteProgressFn>,
+) -> R2Result<DeleteObjectsOutcome> {
     if keys.is_empty() {
-        return Ok(());
+        return Ok(DeleteObjectsOutcome::default());
     }
 
     let client = create_r2_client(config).await?;
-
-    let objects: Vec<ObjectIdentifier> = keys
-        .iter()
-        .map(|key| ObjectIdentifier::builder().key(key).build().unwrap())
-        .collect();
-
-    let delete = Delete::builder().set_objects(Some(objects)).build()?;
-
-    client
-        .delete_objects()
-        .bucket(&config.bucket)
-        .delete(delete)
-        .send()
-        .await?;
-
-    Ok(())
+    batch_delete::delete_objects(&client, &config.bucket, keys, on_progress).await
 }
 
-/// Copy an object (used for rename)
-pub async fn copy_object(config: &R2Config, source_key: &str, dest_key: &str) -> R2Result<()> {
+/// Copy an object within the bucket (used for rename).
+/// Sources over 5 GB are copied with parallel UploadPartCopy.
+pub async fn copy_object(
+    config: &R2Config,
+    source_key: &str,
+    dest_key: &str,
+    on_progress: Option<CopyProgressFn>,
+) -> R2Result<u64> {
     let client = create_r2_client(config).await?;
-    let copy_source = format!("{}/{}", config.bucket, source_key);
-
-    client
-        .copy_object()
-        .bucket(&config.bucket)
-        .copy_source(copy_source)
-        .key(dest_key)
-        .send()
-        .await?;
-
-    Ok(())
+    multipart_copy::copy_object(
+        &client,
+        &config.bucket,
+        source_key,
+        &config.bucket,
+        dest_key,
+        CopyEncryption::same(config.encryption.as_ref()),
+        None,
+        on_progress,
+    )
+    .await
 }
 
+/// Copy an object from another bucket on the same endpoint.
+/// Returns the number of bytes copied.
 pub async fn copy_object_between_buckets(
     config: &R2Config,
     source_bucket: &str,
     source_key: &str,
+    source_encryption: Option<&ObjectEncryption>,
     dest_key: &str,
-) -> R2Result<()> {
+    on_progress: Option<CopyProgressFn>,
+) -> R2Result<u64> {
     let client = create_r2_client(config).await?;
-    let copy_source = format!("{}/{}", source_bucket, source_key);
-
-    client
-        .copy_object()
-        .bucket(&config.bucket)
-        .copy_source(copy_source)
-        .key(dest_key)
-        .send()
-        .await?;
-
-    Ok(())
+    multipart_copy::copy_object(
+        &client,
+        source_bucket,
+        source_key,
+        &config.bucket,
+        dest_key,
+        CopyEncryption {
+            source: source_encryption,
+            dest: config.encryption.as_ref(),
+        },
+        None,
+        on_progress,
+    )
+    .await
 }
 
 /// Rename an object (copy then delete)
-pub async fn rename_object(config: &R2Config, old_key: &str, new_key: &str) -> R2Result<()> {
-    copy_object(config, old_key, new_key).await?;
+pub async fn rename_object(
+    config: &R2Config,
+    old_key: &str,
+    new_key: &str,
+    on_progress: Option<CopyProgressFn>,
+) -> R2Result<()> {
+    copy_object(config, old_key, new_key, on_progress).await?;
     delete_object(config, old_key).await?;
     Ok(())
 }
@@ -1,6 +1,7 @@
 //! R2 presigned URL generation
 
 use super::types::{create_r2_client, R2Config, R2Result};
+use crate::providers::encryption::{SseCustomerKey, SseWrite};
 use aws_sdk_s3::presigning::PresigningConfig;
 use std::time::Duration;
 
@@ -20,6 +21,7 @@ pub async fn generate_presigned_url(
         .get_object()
         .bucket(&config.bucket)
         .key(key)
+        .sse_customer_key_from(config.encryption.as_ref())
         .presigned(presigning_config)
         .await?;
 
@@ -42,6 +44,7 @@ pub async fn generate_presigned_put_url(
         .put_object()
         .bucket(&config.bucket)
         .key(key)
+        .sse_from(config.encryption.as_ref())
         .presigned(presigning_config)
         .await?;
 
@@ -1,6 +1,8 @@
 //! R2 types and client creation
 
+use crate::providers::encryption::ObjectEncryption;
 use crate::providers::s3_client::{create_s3_client, S3ClientConfig};
+use crate::secrets;
 use aws_sdk_s3::Client;
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +14,10 @@ pub struct R2Config {
     pub bucket: String,
     pub access_key_id: String,
     pub secret_access_key: String,
+    /// Bucket encryption resolved from the saved settings; never sent by the
+    /// frontend.
+    #[serde(skip)]
+    pub encryption: Option<ObjectEncryption>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,12 +52,14 @@ pub struct RecursiveListResult {
 /// Create an S3 client configured for Cloudflare R2
 pub async fn create_r2_client(config: &R2Config) -> R2Result<Client> {
     let endpoint_url = format!("https://{}.r2.cloudflarestorage.com", config.account_id);
+    let secret_access_key = secrets::resolve(&config.secret_access_key).await?;
     let client = create_s3_client(&S3ClientConfig {
         access_key_id: &config.access_key_id,
-        secret_access_key: &config.secret_access_key,
+        secret_access_key: &secret_access_key,
         region: "auto",
         endpoint_url: Some(endpoint_url.as_str()),
         force_path_style: true,
+        credentials_provider: None,
     })?;
 
     Ok(client)
@@ -1,8 +1,11 @@
 //! R2 upload operations (simple, multipart)
 
 use super::types::{create_r2_client, R2Config, R2Result};
+use crate::client_encryption::{seal_file, ClientKey};
+use crate::providers::encryption::{SseCustomerKey, SseWrite};
 use aws_sdk_s3::primitives::ByteStream;
 use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
@@ -24,6 +27,7 @@ pub async fn upload_content(
         .put_object()
         .bucket(&config.bucket)
         .key(key)
+        .sse_from(config.encryption.as_ref())
         .body(body);
 
     if let Some(ct) = content_type {
@@ -43,6 +47,7 @@ pub async fn upload_file_simple(
     key: &str,
     file_path: &Path,
     content_type: Option<&str>,
+    metadata: Option<&HashMap<String, String>>,
 ) -> R2Result<String> {
     let client = create_r2_client(config).await?;
     let body = ByteStream::from_path(file_path).await?;
@@ -51,6 +56,8 @@ pub async fn upload_file_simple(
         .put_object()
         .bucket(&config.bucket)
         .key(key)
+        .sse_from(config.encryption.as_ref())
+        .set_metadata(metadata.cloned())
         .body(body);
 
     if let Some(ct) = content_type {
@@ -69,13 +76,16 @@ pub async fn initiate_multipart_upload(
     config: &R2Config,
     key: &str,
     content_type: Option<&str>,
+    metadata: Option<&HashMap<String, String>>,
 ) -> R2Result<String> {
     let client = create_r2_client(config).await?;
 
     let mut request = client
         .create_multipart_upload()
         .bucket(&config.bucket)
-        .key(key);
+        .key(key)
+        .sse_from(config.encryption.as_ref())
+        .set_metadata(metadata.cloned());
 
     if let Some(ct) = content_type {
         request = request.content_type(ct);
@@ -108,6 +118,7 @@ pub async fn upload_part(
         .key(key)
         .upload_id(upload_id)
         .part_number(part_number)
+        .sse_customer_key_from(config.encryption.as_ref())
         .body(body)
         .send()
         .await?;
@@ -145,6 +156,7 @@ pub async fn complete_multipart_upload(
         .bucket(&config.bucket)
         .key(key)
         .upload_id(upload_id)
+        .sse_customer_key_from(config.encryption.as_ref())
         .multipart_upload(completed_upload)
         .send()
         .await?;
@@ -200,25 +212,26 @@ pub async fn list_parts(
 
 /// Upload a large file using multipart upload (AWS SDK orchestration)
 #[allow(dead_code)]
+#[allow(clippy::too_many_arguments)]
 pub async fn upload_file_multipart(
     config: &R2Config,
     key: &str,
     file_path: &Path,
     content_type: Option<&str>,
+    metadata: Option<&HashMap<String, String>>,
     part_size: u64,
     concurrency: usize,
     progress_callback: Option<Box<dyn Fn(u64, u64) + Send + Sync>>,
 ) -> R2Result<String> {
     // Get file size
-    let metadata = tokio::fs::metadata(file_path).await?;
-    let file_size = metadata.len();
+    let file_size = tokio::fs::metadata(file_path).await?.len();
 
     if file_size == 0 {
         return Err("Cannot upload empty file".into());
     }
 
     // Initiate multipart upload
-    let upload_id = initiate_multipart_upload(config, key, content_type).await?;
+    let upload_id = initiate_multipart_upload(config, key, content_type, metadata).await?;
 
     // Calculate parts
     let total_parts = file_size.div_ceil(part_size) as usize;
@@ -317,12 +330,23 @@ pub async fn upload_file(
     key: &str,
     file_path: &Path,
     content_type: Option<&str>,
+    client_key: Option<&ClientKey>,
     progress_callback: Option<Box<dyn Fn(u64, u64) + Send + Sync>>,
 ) -> R2Result<String> {
     const MULTIPART_THRESHOLD: u64 = 100 * 1024 * 1024; // 100MB
     const PART_SIZE: u64 = 20 * 1024 * 1024; // 20MB per part
     const CONCURRENCY: usize = 6; // 6 parallel uploads
 
+    // Client-side encryption uploads an encrypted temp copy instead
+    let sealed = match client_key {
+        Some(client_key) => Some(seal_file(file_path, client_key).await?),
+        None => None,
+    };
+    let metadata = sealed
+        .as_ref()
+        .map(|sealed| sealed.envelope().to_metadata());
+    let file_path = sealed.as_ref().map_or(file_path, |sealed| sealed.path());
+
     let file_size = tokio::fs::metadata(file_path).await?.len();
 
     if file_size < MULTIPART_THRESHOLD {
@@ -330,7 +354,7 @@ pub async fn upload_file(
         if let Some(ref cb) = progress_callback {
             cb(file_size, file_size);
         }
-        upload_file_simple(config, key, file_path, content_type).await
+        upload_file_simple(config, key, file_path, content_type, metadata.as_ref()).await
     } else {
         // Large file: multipart upload
         upload_file_multipart(
@@ -338,6 +362,7 @@ pub async fn upload_file(
             key,
             file_path,
             content_type,
+            metadata.as_ref(),
             PART_SIZE,
             CONCURRENCY,
             progress_callback,
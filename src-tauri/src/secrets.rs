@@ -0,0 +1,475 @@
+//! Secrets kept out of SQLite.
+//!
+//! The OS credential store (Keychain, Credential Manager, Secret Service) is
+//! used where it answers. Linux desktops without a Secret Service fall back to
+//! an encrypted file in the app data directory, unlocked for the session with
+//! a master passphrase.
+//!
+//! Rows in the database only hold an opaque reference (`secret_ref`); the
+//! secret itself is read back when a request needs it. Account credentials
+//! reach the webview as handles (`secret://<ref>`) that only `resolve` turns
+//! back into the key.
+
+use crate::crypto::{self, derive_key, random_bytes, KEY_SIZE, NONCE_SIZE, SALT_SIZE};
+use crate::db;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use keyring::Entry;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, OnceLock};
+use tokio::sync::Mutex;
+
+/// Service name every entry is filed under in the OS keychain.
+const KEYCHAIN_SERVICE: &str = "com.lifefarmer.r2";
+/// Entry read at startup to tell whether the keychain is reachable.
+const PROBE_REF: &str = "probe";
+
+/// Prefix of credential handles stored in rows and sent to the webview.
+pub const HANDLE_PREFIX: &str = "secret://";
+const CREDENTIAL_KIND: &str = "credential";
+
+const STORE_FILE_NAME: &str = "secrets.enc";
+const STORE_VERSION: u32 = 1;
+const STORE_KDF: &str = "argon2id";
+const STORE_AAD: &[u8] = b"r2-secret-store-v1";
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+pub type SecretResult<T> = Result<T, String>;
+
+enum Backend {
+    Keychain,
+    /// Encrypted file at this path.
+    File(PathBuf),
+}
+
+static BACKEND: OnceLock<Backend> = OnceLock::new();
+
+/// Decrypted file store; `None` while locked.
+static FILE_STORE: LazyLock<Mutex<Option<UnlockedStore>>> = LazyLock::new(|| Mutex::new(None));
+/// Secrets already read from the keychain, by reference.
+static KEYCHAIN_CACHE: LazyLock<Mutex<HashMap<String, String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Pick the backend: the keychain when it answers, otherwise an encrypted
+/// file in `app_data_dir`. Call once at startup, before any secret is read.
+pub fn init(app_data_dir: &Path) {
+    let backend = if keychain_available() {
+        Backend::Keychain
+    } else {
+        log::warn!("OS keychain unavailable, secrets are kept in an encrypted file");
+        Backend::File(app_data_dir.join(STORE_FILE_NAME))
+    };
+    let _ = BACKEND.set(backend);
+}
+
+fn backend() -> &'static Backend {
+    BACKEND.get_or_init(|| Backend::Keychain)
+}
+
+fn keychain_available() -> bool {
+    let Ok(entry) = entry(PROBE_REF) else {
+        return false;
+    };
+    matches!(entry.get_password(), Ok(_) | Err(keyring::Error::NoEntry))
+}
+
+fn locked_error() -> String {
+    "Secret store is locked; unlock it with the master passphrase".to_string()
+}
+
+/// Stable keychain account name for a secret, e.g. `sse-c/aws/<account>/<bucket>`.
+pub fn secret_ref(kind: &str, provider: &str, account_id: &str, name: &str) -> String {
+    format!("{}/{}/{}/{}", kind, provider, account_id, name)
+}
+
+fn entry(secret_ref: &str) -> SecretResult<Entry> {
+    Entry::new(KEYCHAIN_SERVICE, secret_ref)
+        .map_err(|e| format!("Failed to open keychain entry: {}", e))
+}
+
+// ============ Encrypted File Store ============
+
+/// On-disk layout of the file store. The entries map is sealed as one JSON
+/// document with a key derived from the master passphrase.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoreFile {
+    version: u32,
+    kdf: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+struct UnlockedStore {
+    key: [u8; KEY_SIZE],
+    salt: [u8; SALT_SIZE],
+    entries: BTreeMap<String, String>,
+}
+
+impl UnlockedStore {
+    fn create(passphrase: &str) -> SecretResult<Self> {
+        if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+            return Err(format!(
+                "Master passphrase must be at least {} characters",
+                MIN_PASSPHRASE_LEN
+            ));
+        }
+        let salt = random_bytes::<SALT_SIZE>()?;
+        Ok(UnlockedStore {
+            key: derive_key(passphrase, &salt)?,
+            salt,
+            entries: BTreeMap::new(),
+        })
+    }
+
+    fn open(file: &StoreFile, passphrase: &str) -> SecretResult<Self> {
+        if file.version != STORE_VERSION || file.kdf != STORE_KDF {
+            return Err(format!(
+                "Unsupported secret store format (version {}, {})",
+                file.version, file.kdf
+            ));
+        }
+        let decode = |value: &str| {
+            BASE64
+                .decode(value)
+                .map_err(|e| format!("Secret store is corrupted: {}", e))
+        };
+        let salt: [u8; SALT_SIZE] = decode(&file.salt)?
+            .try_into()
+            .map_err(|_| "Secret store is corrupted: bad salt".to_string())?;
+        let mut sealed = decode(&file.nonce)?;
+        if sealed.len() != NONCE_SIZE {
+            return Err("Secret store is corrupted: bad nonce".to_string());
+        }
+        sealed.extend_from_slice(&decode(&file.ciphertext)?);
+
+        let key = derive_key(passphrase, &salt)?;
+        let plaintext = crypto::open(&key, &sealed, STORE_AAD)
+            .ok_or_else(|| "Wrong master passphrase".to_string())?;
+        let entries = serde_json::from_slice(&plaintext)
+            .map_err(|e| format!("Secret store is corrupted: {}", e))?;
+        Ok(UnlockedStore { key, salt, entries })
+    }
+
+    fn seal(&self) -> SecretResult<StoreFile> {
+        let plaintext = serde_json::to_vec(&self.entries)
+            .map_err(|e| format!("Failed to serialize secrets: {}", e))?;
+        let sealed = crypto::seal(&self.key, &plaintext, STORE_AAD)
+            .map_err(|_| "Failed to encrypt secrets".to_string())?;
+        let (nonce, ciphertext) = sealed.split_at(NONCE_SIZE);
+        Ok(StoreFile {
+            version: STORE_VERSION,
+            kdf: STORE_KDF.to_string(),
+            salt: BASE64.encode(self.salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        })
+    }
+}
+
+async fn read_store_file(path: &Path) -> SecretResult<Option<StoreFile>> {
+    match tokio::fs::read(path).await {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| format!("Secret store is corrupted: {}", e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read secret store: {}", e)),
+    }
+}
+
+/// Replace the store file atomically so a crash never leaves it half written.
+async fn write_store_file(path: &Path, store: &UnlockedStore) -> SecretResult<()> {
+    let json = serde_json::to_vec(&store.seal()?)
+        .map_err(|e| format!("Failed to serialize secret store: {}", e))?;
+    let tmp_path = path.with_extension("enc.tmp");
+    tokio::fs::write(&tmp_path, json)
+        .await
+        .map_err(|e| format!("Failed to write secret store: {}", e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = tokio::fs::set_permissions(&tmp_path, std::fs::Permissions::from_mode(0o600)).await;
+    }
+    tokio::fs::rename(&tmp_path, path)
+        .await
+        .map_err(|e| format!("Failed to write secret store: {}", e))
+}
+
+// ============ Secrets by Reference ============
+
+/// Store (or replace) a secret.
+pub async fn store_secret(secret_ref: &str, value: &str) -> SecretResult<()> {
+    match backend() {
+        Backend::Keychain => {
+            let key = secret_ref.to_string();
+            let secret = value.to_string();
+            tokio::task::spawn_blocking(move || {
+                entry(&key)?
+                    .set_password(&secret)
+                    .map_err(|e| format!("Failed to store secret in keychain: {}", e))
+            })
+            .await
+            .map_err(|e| format!("Keychain task failed: {}", e))??;
+            KEYCHAIN_CACHE
+                .lock()
+                .await
+                .insert(secret_ref.to_string(), value.to_string());
+            Ok(())
+        }
+        Backend::File(path) => {
+            let mut guard = FILE_STORE.lock().await;
+            let store = guard.as_mut().ok_or_else(locked_error)?;
+            let previous = store
+                .entries
+                .insert(secret_ref.to_string(), value.to_string());
+            if let Err(e) = write_store_file(path, store).await {
+                match previous {
+                    Some(previous) => store.entries.insert(secret_ref.to_string(), previous),
+                    None => store.entries.remove(secret_ref),
+                };
+                return Err(e);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Read a secret; `None` when the store has no entry for it.
+pub async fn load_secret(secret_ref: &str) -> SecretResult<Option<String>> {
+    match backend() {
+        Backend::Keychain => {
+            if let Some(value) = KEYCHAIN_CACHE.lock().await.get(secret_ref) {
+                return Ok(Some(value.clone()));
+            }
+            let key = secret_ref.to_string();
+            let value = tokio::task::spawn_blocking(move || match entry(&key)?.get_password() {
+                Ok(value) => Ok(Some(value)),
+                Err(keyring::Error::NoEntry) => Ok(None),
+                Err(e) => Err(format!("Failed to read secret from keychain: {}", e)),
+            })
+            .await
+            .map_err(|e| format!("Keychain task failed: {}", e))??;
+            if let Some(value) = &value {
+                KEYCHAIN_CACHE
+                    .lock()
+                    .await
+                    .insert(secret_ref.to_string(), value.clone());
+            }
+            Ok(value)
+        }
+        Backend::File(_) => {
+            let guard = FILE_STORE.lock().await;
+            let store = guard.as_ref().ok_or_else(locked_error)?;
+            Ok(store.entries.get(secret_ref).cloned())
+        }
+    }
+}
+
+/// Remove a secret. Missing entries are not an error.
+pub async fn delete_secret(secret_ref: &str) -> SecretResult<()> {
+    match backend() {
+        Backend::Keychain => {
+            KEYCHAIN_CACHE.lock().await.remove(secret_ref);
+            let key = secret_ref.to_string();
+            tokio::task::spawn_blocking(move || match entry(&key)?.delete_credential() {
+                Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+                Err(e) => Err(format!("Failed to delete secret from keychain: {}", e)),
+            })
+            .await
+            .map_err(|e| format!("Keychain task failed: {}", e))?
+        }
+        Backend::File(path) => {
+            let mut guard = FILE_STORE.lock().await;
+            let store = guard.as_mut().ok_or_else(locked_error)?;
+            if store.entries.remove(secret_ref).is_some() {
+                write_store_file(path, store).await?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// ============ Credential Handles ============
+
+/// Keychain account name of a saved credential. Ids are random: accounts of
+/// some providers only get their id once the row is inserted.
+fn credential_ref(provider: &str, id: &str) -> String {
+    format!("{}/{}/{}", CREDENTIAL_KIND, provider, id)
+}
+
+/// Secret reference of a credential handle; `None` for any other value.
+fn handle_ref(value: &str) -> Option<&str> {
+    value.strip_prefix(HANDLE_PREFIX)
+}
+
+/// The secret behind a credential handle. Any other value is returned as is:
+/// keys typed into a form and not saved yet, or rows not migrated so far.
+pub async fn resolve(value: &str) -> SecretResult<String> {
+    match handle_ref(value) {
+        Some(secret_ref) => load_secret(secret_ref).await?.ok_or_else(|| {
+            "Saved credential is missing from the secret store; enter it again".to_string()
+        }),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Save a credential submitted for a row and return the handle to store in
+/// it. `current` is the row's existing value, which is kept when submitted
+/// unchanged. A handle belonging to another row is copied, so deleting one
+/// row never removes a secret the other still uses.
+pub async fn store_credential(
+    provider: &str,
+    value: &str,
+    current: Option<&str>,
+) -> SecretResult<String> {
+    if value.is_empty() || current == Some(value) {
+        return Ok(value.to_string());
+    }
+    let secret = resolve(value).await?;
+    let secret_ref = credential_ref(provider, &hex::encode(random_bytes::<16>()?));
+    store_secret(&secret_ref, &secret).await?;
+    Ok(format!("{}{}", HANDLE_PREFIX, secret_ref))
+}
+
+/// Remove the secret behind a handle that a row no longer uses.
+pub async fn delete_credential(value: &str) -> SecretResult<()> {
+    match handle_ref(value) {
+        Some(secret_ref) => delete_secret(secret_ref).await,
+        None => Ok(()),
+    }
+}
+
+/// Release the previous value of a credential column after a row was saved.
+pub async fn release_replaced_credential(previous: &str, saved: &str) {
+    if previous != saved {
+        if let Err(e) = delete_credential(previous).await {
+            log::warn!("Failed to remove replaced credential: {}", e);
+        }
+    }
+}
+
+// ============ Store Status and Migration ============
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecretStoreStatus {
+    /// `keychain` or `file`
+    pub backend: &'static str,
+    /// File store only: whether a master passphrase has been set.
+    pub initialized: bool,
+    /// Always true for the keychain.
+    pub unlocked: bool,
+    /// Credential columns that still hold the secret itself.
+    pub plaintext_credentials: usize,
+}
+
+async fn is_unlocked() -> bool {
+    match backend() {
+        Backend::Keychain => true,
+        Backend::File(_) => FILE_STORE.lock().await.is_some(),
+    }
+}
+
+pub async fn status() -> SecretResult<SecretStoreStatus> {
+    let plaintext_credentials = db::list_plaintext_credentials()
+        .await
+        .map_err(|e| format!("Failed to read credentials: {}", e))?
+        .len();
+    let (backend, initialized) = match backend() {
+        Backend::Keychain => ("keychain", true),
+        Backend::File(path) => ("file", tokio::fs::try_exists(path).await.unwrap_or(false)),
+    };
+    Ok(SecretStoreStatus {
+        backend,
+        initialized,
+        unlocked: is_unlocked().await,
+        plaintext_credentials,
+    })
+}
+
+/// Unlock the file store, creating it with this passphrase on first use.
+/// Nothing to do when secrets live in the keychain.
+pub async fn unlock(passphrase: &str) -> SecretResult<()> {
+    let Backend::File(path) = backend() else {
+        return Ok(());
+    };
+    let existing = read_store_file(path).await?;
+    let passphrase = passphrase.to_string();
+    // Key derivation is deliberately slow; keep it off the async workers
+    let created = existing.is_none();
+    let store = tokio::task::spawn_blocking(move || match existing {
+        Some(file) => UnlockedStore::open(&file, &passphrase),
+        None => UnlockedStore::create(&passphrase),
+    })
+    .await
+    .map_err(|e| format!("Secret store task failed: {}", e))??;
+    if created {
+        write_store_file(path, &store).await?;
+    }
+    *FILE_STORE.lock().await = Some(store);
+    Ok(())
+}
+
+/// Forget the decrypted file store until the next unlock.
+pub async fn lock() {
+    *FILE_STORE.lock().await = None;
+}
+
+/// Move credentials still stored in plain text into the secret store and
+/// leave handles in their rows. Returns how many were moved; nothing moves
+/// while the file store is locked.
+pub async fn migrate_plaintext_credentials() -> SecretResult<usize> {
+    if !is_unlocked().await {
+        return Ok(0);
+    }
+    let credentials = db::list_plaintext_credentials()
+        .await
+        .map_err(|e| format!("Failed to read credentials: {}", e))?;
+    let mut moved = 0;
+    for credential in credentials {
+        let handle = store_credential(credential.provider, &credential.value, None).await?;
+        db::replace_plaintext_credential(&credential, &handle)
+            .await
+            .map_err(|e| format!("Failed to save credential handle: {}", e))?;
+        moved += 1;
+    }
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_store_round_trips_with_the_passphrase() {
+        let mut store = UnlockedStore::create("correct horse").unwrap();
+        store
+            .entries
+            .insert(credential_ref("r2", "1"), "s3cr3t".to_string());
+        let file = store.seal().unwrap();
+        assert!(!file.ciphertext.contains("s3cr3t"));
+
+        let reopened = UnlockedStore::open(&file, "correct horse").unwrap();
+        assert_eq!(
+            reopened
+                .entries
+                .get(&credential_ref("r2", "1"))
+                .map(String::as_str),
+            Some("s3cr3t")
+        );
+        assert!(UnlockedStore::open(&file, "wrong horse").is_err());
+    }
+
+    #[test]
+    fn short_master_passphrases_are_rejected() {
+        assert!(UnlockedStore::create("short").is_err());
+    }
+
+    #[test]
+    fn handles_are_told_apart_from_secrets() {
+        let handle = format!("{}{}", HANDLE_PREFIX, credential_ref("aws", "id"));
+        assert_eq!(handle_ref(&handle), Some("credential/aws/id"));
+        assert_eq!(handle_ref("wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY"), None);
+        assert_eq!(handle_ref(""), None);
+    }
+}
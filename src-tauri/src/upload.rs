@@ -1,5 +1,9 @@
+use crate::client_encryption::{ciphertext_len, seal_file};
+use crate::commands::client_encryption::resolve_upload_encryption;
 use crate::commands::upload_cache::update_cache_after_upload;
-use crate::db::{self, UploadSession};
+use crate::db::{self, CacheScope, CachedObjectMeta, UploadSession};
+use crate::providers::encryption::{read_headers, ObjectEncryption};
+use crate::secrets;
 use chrono::Utc;
 use hmac::{Hmac, Mac};
 use reqwest::Client;
@@ -29,6 +33,9 @@ pub struct R2Config {
     pub bucket: String,
     pub access_key_id: String,
     pub secret_access_key: String,
+    /// Bucket encryption; only SSE-C applies to R2.
+    #[serde(skip)]
+    pub encryption: Option<ObjectEncryption>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -82,7 +89,8 @@ fn encode_uri_path(path: &str) -> String {
         .join("/")
 }
 
-/// Generate AWS Signature V4 presigned URL
+/// Generate AWS Signature V4 presigned URL. `headers` are signed along with
+/// `host` and must be sent unchanged with the request.
 fn generate_presigned_url(
     config: &R2Config,
     method: &str,
@@ -90,6 +98,7 @@ fn generate_presigned_url(
     expires_in: u64,
     query_params: Option<&[(&str, &str)]>,
     _content_type: Option<&str>,
+    headers: &[(String, String)],
 ) -> String {
     let now = Utc::now();
     let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
@@ -102,6 +111,24 @@ fn generate_presigned_url(
 
     let credential_scope = format!("{}/{}/{}/aws4_request", date_stamp, region, service);
 
+    // Canonical headers
+    let mut header_pairs: Vec<(String, String)> = vec![("host".to_string(), host.clone())];
+    header_pairs.extend(
+        headers
+            .iter()
+            .map(|(name, value)| (name.to_lowercase(), value.trim().to_string())),
+    );
+    header_pairs.sort_by(|a, b| a.0.cmp(&b.0));
+    let canonical_headers: String = header_pairs
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect();
+    let signed_headers = header_pairs
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+
     // Build query string
     let mut query_parts: Vec<(String, String)> = vec![
         (
@@ -115,7 +142,10 @@ fn generate_presigned_url(
         ),
         ("X-Amz-Date".to_string(), amz_date.clone()),
         ("X-Amz-Expires".to_string(), expires_in.to_string()),
-        ("X-Amz-SignedHeaders".to_string(), "host".to_string()),
+        (
+            "X-Amz-SignedHeaders".to_string(),
+            urlencoding::encode(&signed_headers).to_string(),
+        ),
     ];
 
     if let Some(params) = query_params {
@@ -131,10 +161,6 @@ fn generate_presigned_url(
         .collect::<Vec<_>>()
         .join("&");
 
-    // Canonical headers
-    let canonical_headers = format!("host:{}\n", host);
-    let signed_headers = "host";
-
     // For presigned URL, payload is UNSIGNED-PAYLOAD
     let payload_hash = "UNSIGNED-PAYLOAD";
 
@@ -164,7 +190,19 @@ fn generate_presigned_url(
     )
 }
 
-/// Upload a single file using PUT (for files < 100MB)
+/// Attach headers that were signed into a presigned URL.
+fn with_headers(
+    mut request: reqwest::RequestBuilder,
+    headers: &[(String, String)],
+) -> reqwest::RequestBuilder {
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    request
+}
+
+/// Upload a single file using PUT (for files < 100MB). `write_headers` carry
+/// the SSE and client-side encryption headers for the new object.
 #[allow(clippy::too_many_arguments)]
 async fn upload_single_part(
     client: &Client,
@@ -172,11 +210,20 @@ async fn upload_single_part(
     key: &str,
     file_path: &PathBuf,
     content_type: &str,
+    write_headers: &[(String, String)],
     task_id: &str,
     app: &AppHandle,
     cancelled: &Arc<AtomicBool>,
 ) -> Result<(), String> {
-    let presigned_url = generate_presigned_url(config, "PUT", key, 3600, None, Some(content_type));
+    let presigned_url = generate_presigned_url(
+        config,
+        "PUT",
+        key,
+        3600,
+        None,
+        Some(content_type),
+        write_headers,
+    );
 
     let file_size = tokio::fs::metadata(file_path)
         .await
@@ -199,8 +246,7 @@ async fn upload_single_part(
 
     let start_time = std::time::Instant::now();
 
-    let response = client
-        .put(&presigned_url)
+    let response = with_headers(client.put(&presigned_url), write_headers)
         .header("Content-Type", content_type)
         .body(buffer)
         .send()
@@ -234,7 +280,54 @@ async fn upload_single_part(
     Ok(())
 }
 
-/// Multipart upload for large files with resume support
+/// Abort a multipart upload, dropping the parts uploaded so far.
+async fn abort_multipart(
+    client: &Client,
+    config: &R2Config,
+    key: &str,
+    upload_id: &str,
+) -> Result<(), String> {
+    let abort_url = generate_presigned_url(
+        config,
+        "DELETE",
+        key,
+        3600,
+        Some(&[("uploadId", upload_id)]),
+        None,
+        &[],
+    );
+    let response = client
+        .delete(&abort_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to abort multipart upload: {}", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!(
+            "Failed to abort multipart upload: {} - {}",
+            status, text
+        ));
+    }
+    Ok(())
+}
+
+/// Drop an upload that can't be resumed: abort it on R2 and delete its session.
+async fn discard_multipart(
+    client: &Client,
+    config: &R2Config,
+    key: &str,
+    upload_id: &str,
+    session_id: &str,
+) {
+    if let Err(e) = abort_multipart(client, config, key, upload_id).await {
+        log::warn!("{}", e);
+    }
+    let _ = db::delete_session(session_id).await;
+}
+
+/// Multipart upload for large files with resume support. Uploads that are
+/// not `resumable` are aborted on cancel or error instead of being kept.
 #[allow(clippy::too_many_arguments)]
 async fn upload_multipart(
     client: &Client,
@@ -242,9 +335,11 @@ async fn upload_multipart(
     key: &str,
     file_path: &PathBuf,
     content_type: &str,
+    write_headers: &[(String, String)],
     task_id: &str,
     app: &AppHandle,
     cancelled: &Arc<AtomicBool>,
+    resumable: bool,
 ) -> Result<(), String> {
     let metadata = tokio::fs::metadata(file_path)
         .await
@@ -264,16 +359,20 @@ async fn upload_multipart(
     let file_path_str = file_path.to_string_lossy().to_string();
 
     // Check for existing resumable session
-    let (upload_id, existing_parts, session_id) = match db::find_resumable_session(
-        &file_path_str,
-        file_size as i64,
-        file_mtime,
-        key,
-        &config.bucket,
-        &config.account_id,
-    )
-    .await
-    {
+    let existing_session = if resumable {
+        db::find_resumable_session(
+            &file_path_str,
+            file_size as i64,
+            file_mtime,
+            key,
+            &config.bucket,
+            &config.account_id,
+        )
+        .await
+    } else {
+        Ok(None)
+    };
+    let (upload_id, existing_parts, session_id) = match existing_session {
         Ok(Some(session)) => {
             // Found existing session - verify upload_id is still valid on R2
             let upload_id = session.upload_id.clone().unwrap();
@@ -306,10 +405,10 @@ async fn upload_multipart(
                 3600,
                 Some(&[("uploads", "")]),
                 Some(content_type),
+                write_headers,
             );
 
-            let create_response = client
-                .post(&create_url)
+            let create_response = with_headers(client.post(&create_url), write_headers)
                 .send()
                 .await
                 .map_err(|e| format!("Failed to initiate multipart upload: {}", e))?;
@@ -456,6 +555,7 @@ async fn upload_multipart(
             }
 
             // Generate presigned URL for this part
+            let sse_headers = read_headers(config.encryption.as_ref());
             let part_url = generate_presigned_url(
                 &config,
                 "PUT",
@@ -466,10 +566,10 @@ async fn upload_multipart(
                     ("uploadId", &upload_id),
                 ]),
                 None,
+                &sse_headers,
             );
 
-            let response = client
-                .put(&part_url)
+            let response = with_headers(client.put(&part_url), &sse_headers)
                 .body(buffer)
                 .send()
                 .await
@@ -545,16 +645,24 @@ async fn upload_multipart(
     }
 
     if cancelled.load(Ordering::SeqCst) {
-        // Mark session as cancelled but don't delete (can be resumed later)
-        let _ = db::update_session_status(&session_id, "cancelled").await;
+        if resumable {
+            // Mark session as cancelled but don't delete (can be resumed later)
+            let _ = db::update_session_status(&session_id, "cancelled").await;
 
-        // Note: We don't abort the multipart upload on R2 so it can be resumed
+            // Note: We don't abort the multipart upload on R2 so it can be resumed
+        } else {
+            discard_multipart(client, config, key, &upload_id, &session_id).await;
+        }
         return Err("Upload cancelled".to_string());
     }
 
     if let Some(err) = upload_error {
-        // Mark session as failed but keep it for retry
-        let _ = db::update_session_status(&session_id, "uploading").await;
+        if resumable {
+            // Mark session as failed but keep it for retry
+            let _ = db::update_session_status(&session_id, "uploading").await;
+        } else {
+            discard_multipart(client, config, key, &upload_id, &session_id).await;
+        }
         return Err(err);
     }
 
@@ -577,6 +685,7 @@ async fn upload_multipart(
         parts_xml
     );
 
+    let sse_headers = read_headers(config.encryption.as_ref());
     let complete_url = generate_presigned_url(
         config,
         "POST",
@@ -584,23 +693,31 @@ async fn upload_multipart(
         3600,
         Some(&[("uploadId", &upload_id)]),
         None,
+        &sse_headers,
     );
 
-    let complete_response = client
-        .post(&complete_url)
+    let complete_result = match with_headers(client.post(&complete_url), &sse_headers)
         .header("Content-Type", "application/xml")
         .body(complete_xml)
         .send()
         .await
-        .map_err(|e| format!("Failed to complete multipart upload: {}", e))?;
-
-    if !complete_response.status().is_success() {
-        let status = complete_response.status();
-        let text = complete_response.text().await.unwrap_or_default();
-        return Err(format!(
-            "Failed to complete multipart upload: {} - {}",
-            status, text
-        ));
+    {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(response) => {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            Err(format!(
+                "Failed to complete multipart upload: {} - {}",
+                status, text
+            ))
+        }
+        Err(e) => Err(format!("Failed to complete multipart upload: {}", e)),
+    };
+    if let Err(err) = complete_result {
+        if !resumable {
+            discard_multipart(client, config, key, &upload_id, &session_id).await;
+        }
+        return Err(err);
     }
 
     // Mark session as completed and clean up
@@ -643,12 +760,36 @@ pub async fn upload_file(
     access_key_id: String,
     secret_access_key: String,
 ) -> Result<UploadResult, String> {
-    let config = R2Config {
+    // Raw part uploads sign requests themselves, so they need the key itself
+    let secret_access_key = match secrets::resolve(&secret_access_key).await {
+        Ok(secret) => secret,
+        Err(e) => {
+            return Ok(UploadResult {
+                task_id,
+                success: false,
+                error: Some(e),
+            })
+        }
+    };
+    let mut config = R2Config {
         account_id,
         bucket,
         access_key_id,
         secret_access_key,
+        encryption: None,
     };
+    let (encryption, client_key) =
+        match resolve_upload_encryption("r2", &config.account_id, &config.bucket).await {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                return Ok(UploadResult {
+                    task_id,
+                    success: false,
+                    error: Some(e),
+                })
+            }
+        };
+    config.encryption = encryption;
 
     let path = PathBuf::from(&file_path);
     if !path.exists() {
@@ -664,6 +805,38 @@ pub async fn upload_file(
         .map_err(|e| format!("Failed to get file metadata: {}", e))?
         .len();
 
+    // Client-side encryption uploads an encrypted temp copy instead
+    let sealed = match client_key {
+        Some(ref client_key) => match seal_file(&path, client_key).await {
+            Ok(sealed) => Some(sealed),
+            Err(e) => {
+                return Ok(UploadResult {
+                    task_id,
+                    success: false,
+                    error: Some(e),
+                })
+            }
+        },
+        None => None,
+    };
+    let path = sealed
+        .as_ref()
+        .map_or(path, |sealed| sealed.path().to_path_buf());
+    // Client-side encrypted objects are stored larger than the source file
+    let stored_size = match sealed {
+        Some(_) => ciphertext_len(file_size),
+        None => file_size,
+    };
+
+    let mut write_headers = config
+        .encryption
+        .as_ref()
+        .map(ObjectEncryption::write_headers)
+        .unwrap_or_default();
+    if let Some(ref sealed) = sealed {
+        write_headers.extend(sealed.envelope().to_headers());
+    }
+
     // Register cancel flag
     let cancelled = Arc::new(AtomicBool::new(false));
     {
@@ -675,13 +848,14 @@ pub async fn upload_file(
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
 
-    let result = if file_size < MULTIPART_THRESHOLD {
+    let result = if stored_size < MULTIPART_THRESHOLD {
         upload_single_part(
             &client,
             &config,
             &key,
             &path,
             &content_type,
+            &write_headers,
             &task_id,
             &app,
             &cancelled,
@@ -694,9 +868,13 @@ pub async fn upload_file(
             &key,
             &path,
             &content_type,
+            &write_headers,
             &task_id,
             &app,
             &cancelled,
+            // A sealed copy is encrypted afresh on every attempt, so its
+            // parts can never be reused
+            sealed.is_none(),
         )
         .await
     };
@@ -712,11 +890,14 @@ pub async fn upload_file(
             let last_modified = chrono::Utc::now().to_rfc3339();
             if let Err(err) = update_cache_after_upload(
                 &app,
-                &config.bucket,
-                &config.account_id,
+                &CacheScope::r2(&config.account_id, &config.bucket),
                 &key,
-                file_size as i64,
+                stored_size as i64,
                 &last_modified,
+                &CachedObjectMeta {
+                    content_type: Some(content_type),
+                    ..Default::default()
+                },
             )
             .await
             {
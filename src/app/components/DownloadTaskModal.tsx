@@ -16,7 +16,7 @@ import {
 } from '@/app/stores/downloadStore';
 import DownloadTaskItem from '@/app/components/DownloadTaskItem';
 import { formatBytes, formatEta } from '@/app/utils/formatBytes';
-import type { StorageConfig } from '@/app/lib/r2cache';
+import { bucketScopeArgs, type StorageConfig } from '@/app/lib/r2cache';
 
 interface DownloadTaskModalProps {
   storageConfig?: StorageConfig | null;
@@ -65,15 +65,15 @@ export default function DownloadTaskModal({ storageConfig }: DownloadTaskModalPr
   const reloadTasksFromDatabase = useCallback(async () => {
     if (!storageConfig?.bucket || !storageConfig?.accountId) return;
     try {
-      const sessions = await invoke<DownloadSession[]>('get_download_tasks', {
-        bucket: storageConfig.bucket,
-        accountId: storageConfig.accountId,
-      });
+      const sessions = await invoke<DownloadSession[]>(
+        'get_download_tasks',
+        bucketScopeArgs(storageConfig)
+      );
       loadFromDatabase(sessions);
     } catch (e) {
       console.error('Failed to reload download tasks:', e);
     }
-  }, [storageConfig?.bucket, storageConfig?.accountId, loadFromDatabase]);
+  }, [storageConfig, loadFromDatabase]);
 
   // Refresh tasks in background when modal opens (no loading spinner - show existing data immediately)
   useEffect(() => {
@@ -91,10 +91,7 @@ export default function DownloadTaskModal({ storageConfig }: DownloadTaskModalPr
     if (!storageConfig?.bucket || !storageConfig?.accountId) return;
 
     try {
-      const count = await invoke<number>('pause_all_downloads', {
-        bucket: storageConfig.bucket,
-        accountId: storageConfig.accountId,
-      });
+      const count = await invoke<number>('pause_all_downloads', bucketScopeArgs(storageConfig));
       // Reload immediately to update UI - don't rely solely on async event
       await reloadTasksFromDatabase();
       message.success(`Paused ${count} downloads`);
@@ -187,10 +184,7 @@ export default function DownloadTaskModal({ storageConfig }: DownloadTaskModalPr
     if (!storageConfig?.bucket || !storageConfig?.accountId) return;
 
     try {
-      await invoke('clear_finished_downloads', {
-        bucket: storageConfig.bucket,
-        accountId: storageConfig.accountId,
-      });
+      await invoke('clear_finished_downloads', bucketScopeArgs(storageConfig));
       // Reload immediately to update UI
       await reloadTasksFromDatabase();
     } catch (e) {
@@ -205,10 +199,7 @@ export default function DownloadTaskModal({ storageConfig }: DownloadTaskModalPr
     if (!storageConfig?.bucket || !storageConfig?.accountId) return;
 
     try {
-      await invoke('clear_all_downloads', {
-        bucket: storageConfig.bucket,
-        accountId: storageConfig.accountId,
-      });
+      await invoke('clear_all_downloads', bucketScopeArgs(storageConfig));
       // Reload immediately to update UI
       await reloadTasksFromDatabase();
     } catch (e) {
@@ -19,7 +19,7 @@ import {
 } from '@/app/stores/moveStore';
 import { useAccountStore, type ProviderAccount } from '@/app/stores/accountStore';
 import MoveTaskItem from '@/app/components/MoveTaskItem';
-import type { StorageConfig } from '@/app/lib/r2cache';
+import { moveSourceArgs, type StorageConfig } from '@/app/lib/r2cache';
 
 interface MoveTaskModalProps {
   storageConfig?: StorageConfig | null;
@@ -109,6 +109,36 @@ function buildMoveConfigFromAccounts(
   return null;
 }
 
+/** Arguments naming a task's source queue for the move commands. */
+interface SourceTarget {
+  sourceBucket: string;
+  sourceAccountId: string;
+  sourceProvider: string;
+  sourceEndpointScheme: string | null;
+  sourceEndpointHost: string | null;
+}
+
+function sourceTargetKey(target: SourceTarget): string {
+  return `${target.sourceProvider}:${target.sourceAccountId}:${target.sourceBucket}`;
+}
+
+/** The source queue of `task`, on the endpoint its account points at. */
+function sourceTargetFromAccounts(task: MoveTask, accounts: ProviderAccount[]): SourceTarget {
+  const accountEntry = accounts.find(
+    (account) =>
+      account.provider === task.sourceProvider && account.account.id === task.sourceAccountId
+  );
+  const endpointAccount =
+    accountEntry && accountEntry.provider !== 'r2' ? accountEntry.account : null;
+  return {
+    sourceBucket: task.sourceBucket,
+    sourceAccountId: task.sourceAccountId,
+    sourceProvider: task.sourceProvider,
+    sourceEndpointScheme: endpointAccount?.endpoint_scheme ?? null,
+    sourceEndpointHost: endpointAccount?.endpoint_host ?? null,
+  };
+}
+
 function buildDestinationConfigFromAccounts(
   task: MoveTask,
   accounts: ProviderAccount[]
@@ -207,22 +237,19 @@ export default function MoveTaskModal({ storageConfig }: MoveTaskModalProps) {
       // Load global active tasks and merge source history for all known source queues.
       const activeSessions = await invoke<MoveSession[]>('get_all_active_move_tasks');
       const currentTasksSnapshot = useMoveStore.getState().tasks;
-      const targets = new Map<string, { sourceBucket: string; sourceAccountId: string }>();
+      const accountList = useAccountStore.getState().accounts;
+      const targets = new Map<string, SourceTarget>();
 
       if (storageConfig?.bucket && storageConfig?.accountId) {
-        targets.set(`${storageConfig.accountId}:${storageConfig.bucket}`, {
-          sourceBucket: storageConfig.bucket,
-          sourceAccountId: storageConfig.accountId,
-        });
+        const target = moveSourceArgs(storageConfig);
+        targets.set(sourceTargetKey(target), target);
       }
 
       for (const task of useMoveStore.getState().tasks) {
-        const key = `${task.sourceAccountId}:${task.sourceBucket}`;
+        const target = sourceTargetFromAccounts(task, accountList);
+        const key = sourceTargetKey(target);
         if (!targets.has(key)) {
-          targets.set(key, {
-            sourceBucket: task.sourceBucket,
-            sourceAccountId: task.sourceAccountId,
-          });
+          targets.set(key, target);
         }
       }
 
@@ -246,7 +273,7 @@ export default function MoveTaskModal({ storageConfig }: MoveTaskModalProps) {
 
         const failedTarget = targetList[index];
         if (failedTarget) {
-          failedSourceKeys.add(`${failedTarget.sourceAccountId}:${failedTarget.sourceBucket}`);
+          failedSourceKeys.add(sourceTargetKey(failedTarget));
         }
       }
 
@@ -254,7 +281,7 @@ export default function MoveTaskModal({ storageConfig }: MoveTaskModalProps) {
       // so transient per-source fetch failures do not make tasks disappear.
       if (failedSourceKeys.size > 0) {
         for (const task of currentTasksSnapshot) {
-          const sourceKey = `${task.sourceAccountId}:${task.sourceBucket}`;
+          const sourceKey = `${task.sourceProvider}:${task.sourceAccountId}:${task.sourceBucket}`;
           if (!failedSourceKeys.has(sourceKey) || byId.has(task.id)) continue;
 
           byId.set(task.id, {
@@ -283,7 +310,7 @@ export default function MoveTaskModal({ storageConfig }: MoveTaskModalProps) {
     } catch (e) {
       console.error('Failed to reload move tasks:', e);
     }
-  }, [storageConfig?.bucket, storageConfig?.accountId, loadFromDatabase]);
+  }, [storageConfig, loadFromDatabase]);
 
   // Load accounts and refresh tasks in background when modal opens (no loading spinner)
   useEffect(() => {
@@ -297,22 +324,23 @@ export default function MoveTaskModal({ storageConfig }: MoveTaskModalProps) {
     setModalOpen(false);
   };
 
-  const collectSourceTargets = useCallback((taskList: MoveTask[]) => {
-    const targets = new Map<string, { sourceBucket: string; sourceAccountId: string }>();
-    for (const task of taskList) {
-      const key = `${task.sourceAccountId}:${task.sourceBucket}`;
-      if (!targets.has(key)) {
-        targets.set(key, {
-          sourceBucket: task.sourceBucket,
-          sourceAccountId: task.sourceAccountId,
-        });
+  const collectSourceTargets = useCallback(
+    (taskList: MoveTask[]) => {
+      const targets = new Map<string, SourceTarget>();
+      for (const task of taskList) {
+        const target = sourceTargetFromAccounts(task, accounts);
+        const key = sourceTargetKey(target);
+        if (!targets.has(key)) {
+          targets.set(key, target);
+        }
       }
-    }
-    return Array.from(targets.values());
-  }, []);
+      return Array.from(targets.values());
+    },
+    [accounts]
+  );
 
   const handlePauseAll = async () => {
-    const pauseTargets = new Map<string, { sourceBucket: string; sourceAccountId: string }>();
+    const pauseTargets = new Map<string, SourceTarget>();
     for (const task of inProgressTasks) {
       const shouldPause =
         task.status === 'pending' ||
@@ -321,12 +349,10 @@ export default function MoveTaskModal({ storageConfig }: MoveTaskModalProps) {
 
       if (!shouldPause) continue;
 
-      const key = `${task.sourceAccountId}:${task.sourceBucket}`;
+      const target = sourceTargetFromAccounts(task, accounts);
+      const key = sourceTargetKey(target);
       if (!pauseTargets.has(key)) {
-        pauseTargets.set(key, {
-          sourceBucket: task.sourceBucket,
-          sourceAccountId: task.sourceAccountId,
-        });
+        pauseTargets.set(key, target);
       }
     }
 
@@ -430,15 +456,14 @@ export default function MoveTaskModal({ storageConfig }: MoveTaskModalProps) {
       }
 
       // Resume paused tasks for all source queues in view.
-      const resumeTargets = new Map<string, { sourceBucket: string; sourceAccountId: string }>();
+      const currentAccounts = useAccountStore.getState().accounts;
+      const resumeTargets = new Map<string, SourceTarget>();
       for (const task of pendingTargets) {
         if (task.status !== 'paused') continue;
-        const key = `${task.sourceAccountId}:${task.sourceBucket}`;
+        const target = sourceTargetFromAccounts(task, currentAccounts);
+        const key = sourceTargetKey(target);
         if (!resumeTargets.has(key)) {
-          resumeTargets.set(key, {
-            sourceBucket: task.sourceBucket,
-            sourceAccountId: task.sourceAccountId,
-          });
+          resumeTargets.set(key, target);
         }
       }
 
@@ -19,17 +19,17 @@ import {
   setupGlobalDownloadListeners,
   loadDownloadTasks,
 } from '@/app/stores/downloadStore';
+import type { StorageConfig } from '@/app/lib/r2cache';
 import { formatBytes, formatEta } from '@/app/utils/formatBytes';
 import Sparkline from '@/app/components/Sparkline';
 
 const { Text } = Typography;
 
 interface DownloadProgressProps {
-  bucket?: string;
-  accountId?: string;
+  config?: StorageConfig | null;
 }
 
-export default function DownloadProgress({ bucket, accountId }: DownloadProgressProps) {
+export default function DownloadProgress({ config }: DownloadProgressProps) {
   const tasks = useDownloadStore((state) => state.tasks);
   const downloadingCount = useDownloadStore(selectDownloadingCount);
   const pendingCount = useDownloadStore(selectPendingCount);
@@ -46,9 +46,9 @@ export default function DownloadProgress({ bucket, accountId }: DownloadProgress
 
   // Load tasks for current bucket
   useEffect(() => {
-    if (!bucket || !accountId) return;
-    loadDownloadTasks(bucket, accountId);
-  }, [bucket, accountId]);
+    if (!config?.bucket || !config?.accountId) return;
+    loadDownloadTasks(config);
+  }, [config]);
 
   // Aggregate speed across all downloading tasks
   const totalSpeed = useMemo(
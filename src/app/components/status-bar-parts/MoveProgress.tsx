@@ -24,6 +24,7 @@ import {
   setupGlobalMoveListeners,
   loadAllActiveMoves,
 } from '@/app/stores/moveStore';
+import { moveSourceArgs, type StorageConfig } from '@/app/lib/r2cache';
 import { formatBytes } from '@/app/utils/formatBytes';
 
 const { Text } = Typography;
@@ -33,11 +34,10 @@ const SPEED_SMOOTHING_ALPHA = 0.2;
 const MAX_SPEED_SPIKE_MULTIPLIER = 3;
 
 interface MoveProgressProps {
-  sourceBucket?: string;
-  sourceAccountId?: string;
+  config?: StorageConfig | null;
 }
 
-export default function MoveProgress({ sourceBucket, sourceAccountId }: MoveProgressProps) {
+export default function MoveProgress({ config }: MoveProgressProps) {
   const tasks = useMoveStore((state) => state.tasks);
   const activeCount = useMoveStore(selectActiveCount);
   const downloadingCount = useMoveStore(selectDownloadingCount);
@@ -62,14 +62,11 @@ export default function MoveProgress({ sourceBucket, sourceAccountId }: MoveProg
 
   // Load current account's tasks when switching accounts (to get finished tasks)
   useEffect(() => {
-    if (!sourceBucket || !sourceAccountId) return;
+    if (!config?.bucket || !config?.accountId) return;
 
     const loadAccountTasks = async () => {
       try {
-        const sessions = await invoke<MoveSession[]>('get_move_tasks', {
-          sourceBucket,
-          sourceAccountId,
-        });
+        const sessions = await invoke<MoveSession[]>('get_move_tasks', moveSourceArgs(config));
         if (sessions.length > 0) {
           loadFromDatabase(sessions);
         }
@@ -79,7 +76,7 @@ export default function MoveProgress({ sourceBucket, sourceAccountId }: MoveProg
     };
 
     loadAccountTasks();
-  }, [sourceBucket, sourceAccountId, loadFromDatabase]);
+  }, [config, loadFromDatabase]);
 
   const activeTransferTasks = useMemo(
     () =>
@@ -170,6 +170,32 @@ export function isBucketPublic(config: StorageConfig | null | undefined): boolea
   return true;
 }
 
+/**
+ * Arguments naming a bucket for the download commands. Its endpoint is part
+ * of the name, since two endpoints can hold buckets under the same account id.
+ */
+export function bucketScopeArgs(config: StorageConfig) {
+  return {
+    bucket: config.bucket,
+    accountId: config.accountId,
+    provider: config.provider,
+    endpointScheme: config.provider !== 'r2' ? (config.endpointScheme ?? null) : null,
+    endpointHost: config.provider !== 'r2' ? (config.endpointHost ?? null) : null,
+  };
+}
+
+/** `bucketScopeArgs` for the move commands, which name the source bucket. */
+export function moveSourceArgs(config: StorageConfig) {
+  const scope = bucketScopeArgs(config);
+  return {
+    sourceBucket: scope.bucket,
+    sourceAccountId: scope.accountId,
+    sourceProvider: scope.provider,
+    sourceEndpointScheme: scope.endpointScheme,
+    sourceEndpointHost: scope.endpointHost,
+  };
+}
+
 // ============ Folder Contents (from cache) ============
 
 export interface FolderContents {
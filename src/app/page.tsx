@@ -32,6 +32,7 @@ import { useAccountStore, ProviderAccount, Token } from '@/app/stores/accountSto
 import { useR2Files, FileItem } from '@/app/hooks/useR2Files';
 import { useFilesSync } from '@/app/hooks/useFilesSync';
 import {
+  bucketScopeArgs,
   deleteObject,
   searchFiles,
   listAllObjectsUnderPrefix,
@@ -186,10 +187,7 @@ export default function Home() {
             created_at: number;
             updated_at: number;
           }>
-        >('get_download_tasks', {
-          bucket: config.bucket,
-          accountId: config.accountId,
-        });
+        >('get_download_tasks', bucketScopeArgs(config));
         loadDownloadsFromDatabase(sessions);
       } catch (e) {
         console.error('Failed to load download tasks:', e);
@@ -197,7 +195,7 @@ export default function Home() {
     };
 
     loadDownloads();
-  }, [config?.bucket, config?.accountId, loadDownloadsFromDatabase]);
+  }, [config, loadDownloadsFromDatabase]);
 
   const { items, isLoading, isFetching, error, refresh } = useR2Files(config, currentPath);
   const { isSyncing, isSynced, lastSyncTime, refresh: refreshSync } = useFilesSync(config);
@@ -672,8 +670,7 @@ export default function Home() {
               fileName,
               fileSize,
               localPath: folder,
-              bucket: config.bucket,
-              accountId: config.accountId,
+              ...bucketScopeArgs(config),
             });
           } catch (e) {
             console.error('Failed to create download task:', e);
@@ -792,8 +789,7 @@ export default function Home() {
           fileName: item.name,
           fileSize,
           localPath: folder,
-          bucket: config.bucket,
-          accountId: config.accountId,
+          ...bucketScopeArgs(config),
         });
 
         // Add task to download store (as pending)
@@ -863,8 +859,7 @@ export default function Home() {
             fileName,
             fileSize,
             localPath: folder,
-            bucket: config.bucket,
-            accountId: config.accountId,
+            ...bucketScopeArgs(config),
           });
         } catch (e) {
           console.error('Failed to create download task:', e);
@@ -2,6 +2,7 @@ import { create } from 'zustand';
 import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import { invoke } from '@tauri-apps/api/core';
 import { createProgressBatcher, smoothSpeed } from '@/app/lib/progressThrottle';
+import { bucketScopeArgs, type StorageConfig } from '@/app/lib/r2cache';
 
 // Maximum concurrent downloads
 export const MAX_CONCURRENT_DOWNLOADS = 5;
@@ -495,12 +496,9 @@ export async function setupGlobalDownloadListeners(): Promise<void> {
 /**
  * Load download tasks for a specific bucket.
  */
-export async function loadDownloadTasks(bucket: string, accountId: string): Promise<void> {
+export async function loadDownloadTasks(config: StorageConfig): Promise<void> {
   try {
-    const sessions = await invoke<DownloadSession[]>('get_download_tasks', {
-      bucket,
-      accountId,
-    });
+    const sessions = await invoke<DownloadSession[]>('get_download_tasks', bucketScopeArgs(config));
     useDownloadStore.getState().loadFromDatabase(sessions);
   } catch (e) {
     console.error('Failed to load download tasks:', e);